@@ -95,7 +95,9 @@ fn convert_validation_error_data(error: &ValidationError) -> Option<serde_json::
             .hint
             .as_ref()
             .and_then(|hint| match hint {
-                UnrecognizedActionIdHelp::SuggestAlternative(alternative) => alternative.into(),
+                UnrecognizedActionIdHelp::SuggestAlternative(alternative)
+                | UnrecognizedActionIdHelp::SuggestCaseSensitiveAlternative(alternative)
+                | UnrecognizedActionIdHelp::SuggestActionGroup(alternative) => alternative.into(),
                 UnrecognizedActionIdHelp::AvoidActionTypeInActionId(_) => None,
             })
             .zip(error.source_loc.as_ref()),
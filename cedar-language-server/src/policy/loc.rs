@@ -79,13 +79,10 @@ impl<'a> SchemaActionLoc<'a> {
                                         self.0.span.offset() + absolute_context_pos;
                                     let length = context_end - absolute_context_pos;
 
-                                    return Some(Loc {
-                                        span: SourceSpan::new(
-                                            SourceOffset::from(absolute_start),
-                                            length,
-                                        ),
-                                        src: self.0.src.clone(),
-                                    });
+                                    return Some(self.0.span(SourceSpan::new(
+                                        SourceOffset::from(absolute_start),
+                                        length,
+                                    )));
                                 }
                             }
                             _ => {}
@@ -105,10 +102,10 @@ impl<'a> SchemaActionLoc<'a> {
 
                     let absolute_start = self.0.span.offset() + absolute_context_pos;
                     let length = start_offset + last_non_whitespace - absolute_context_pos;
-                    return Some(Loc {
-                        span: SourceSpan::new(SourceOffset::from(absolute_start), length),
-                        src: self.0.src.clone(),
-                    });
+                    return Some(
+                        self.0
+                            .span(SourceSpan::new(SourceOffset::from(absolute_start), length)),
+                    );
                 }
             }
         }
@@ -125,10 +122,10 @@ mod tests {
     use super::*;
 
     fn make_loc(text: &str) -> Loc {
-        Loc {
-            span: SourceSpan::new(SourceOffset::from(0), text.len()),
-            src: Arc::from(text),
-        }
+        Loc::new(
+            SourceSpan::new(SourceOffset::from(0), text.len()),
+            Arc::from(text),
+        )
     }
 
     #[track_caller]
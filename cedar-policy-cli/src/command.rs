@@ -44,6 +44,10 @@ mod translate_schema;
 pub use translate_schema::*;
 mod visualize;
 pub use visualize::*;
+mod example_entities;
+pub use example_entities::*;
+mod example_request;
+pub use example_request::*;
 mod new;
 pub use new::*;
 mod language_version;
@@ -109,6 +113,10 @@ pub enum Commands {
     /// Visualize a set of JSON entities to the graphviz format.
     /// Warning: Entity visualization is best-effort and not well tested.
     Visualize(VisualizeArgs),
+    /// Generate example entities conforming to a schema, in JSON format
+    ExampleEntities(ExampleEntitiesArgs),
+    /// Generate an example request for an action conforming to a schema, in JSON format
+    ExampleRequest(ExampleRequestArgs),
     /// Create a Cedar project
     New(NewArgs),
     /// Partially evaluate an authorization request
@@ -0,0 +1,50 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use clap::Args;
+use miette::{IntoDiagnostic, Result};
+
+use crate::{CedarExitCode, SchemaArgs};
+
+#[derive(Args, Debug)]
+pub struct ExampleEntitiesArgs {
+    /// Schema args (incorporated by reference)
+    #[command(flatten)]
+    pub schema: SchemaArgs,
+    /// Seed for generating the entities. The same schema and seed always
+    /// produce the same entities.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+}
+
+pub fn example_entities(args: &ExampleEntitiesArgs) -> CedarExitCode {
+    match example_entities_inner(args) {
+        Ok(json) => {
+            println!("{json}");
+            CedarExitCode::Success
+        }
+        Err(err) => {
+            eprintln!("{err:?}");
+            CedarExitCode::Failure
+        }
+    }
+}
+
+fn example_entities_inner(args: &ExampleEntitiesArgs) -> Result<String> {
+    let schema = args.schema.get_schema()?;
+    let entities = schema.example_entities(args.seed)?;
+    serde_json::to_string_pretty(&entities.to_json_value()?).into_diagnostic()
+}
@@ -0,0 +1,70 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::str::FromStr;
+
+use cedar_policy::EntityUid;
+use clap::Args;
+use miette::{IntoDiagnostic, Result};
+use serde_json::json;
+
+use crate::{CedarExitCode, SchemaArgs};
+
+#[derive(Args, Debug)]
+pub struct ExampleRequestArgs {
+    /// Schema args (incorporated by reference)
+    #[command(flatten)]
+    pub schema: SchemaArgs,
+    /// Action to generate the request for, e.g. `Action::"view"`
+    #[arg(short = 'a', long)]
+    pub action: String,
+    /// Seed for generating the request. The same schema, action and seed
+    /// always produce the same request, and its principal and resource are
+    /// among the entities `example-entities` generates for that seed.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+}
+
+pub fn example_request(args: &ExampleRequestArgs) -> CedarExitCode {
+    match example_request_inner(args) {
+        Ok(json) => {
+            println!("{json}");
+            CedarExitCode::Success
+        }
+        Err(err) => {
+            eprintln!("{err:?}");
+            CedarExitCode::Failure
+        }
+    }
+}
+
+/// Produces the request in the format accepted by `--request-json`
+fn example_request_inner(args: &ExampleRequestArgs) -> Result<String> {
+    let schema = args.schema.get_schema()?;
+    let action = EntityUid::from_str(&args.action)?;
+    let request = schema.example_request(&action, args.seed)?;
+    let context = match request.context() {
+        Some(context) => context.to_json_value()?,
+        None => json!({}),
+    };
+    let json = json!({
+        "principal": request.principal().map(ToString::to_string),
+        "action": request.action().map(ToString::to_string),
+        "resource": request.resource().map(ToString::to_string),
+        "context": context,
+    });
+    serde_json::to_string_pretty(&json).into_diagnostic()
+}
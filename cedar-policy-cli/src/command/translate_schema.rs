@@ -18,7 +18,7 @@ use cedar_policy::SchemaFragment;
 use clap::Args;
 use miette::{IntoDiagnostic, Result};
 
-use crate::{read_from_file_or_stdin, CedarExitCode};
+use crate::{json_schema_error, read_from_file_or_stdin, CedarExitCode};
 
 /// The direction of translation
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
@@ -70,7 +70,8 @@ fn translate_schema_inner(args: &TranslateSchemaArgs) -> Result<String> {
 }
 
 fn translate_schema_to_cedar(json_src: impl AsRef<str>) -> Result<String> {
-    let fragment = SchemaFragment::from_json_str(json_src.as_ref())?;
+    let fragment = SchemaFragment::from_json_str(json_src.as_ref())
+        .map_err(|err| json_schema_error(err, "failed to parse JSON schema".to_string()))?;
     let output = fragment.to_cedarschema()?;
     Ok(output)
 }
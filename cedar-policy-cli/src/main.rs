@@ -20,9 +20,9 @@ use clap::Parser;
 use miette::ErrorHook;
 
 use cedar_policy_cli::{
-    authorize, check_parse, evaluate, format_policies, language_version, link, new,
-    partial_authorize, run_tests, symcc, tpe, translate_policy, translate_schema, validate,
-    visualize, CedarExitCode, Cli, Commands, ErrorFormat,
+    authorize, check_parse, evaluate, example_entities, example_request, format_policies,
+    language_version, link, new, partial_authorize, run_tests, symcc, tpe, translate_policy,
+    translate_schema, validate, visualize, CedarExitCode, Cli, Commands, ErrorFormat,
 };
 
 fn main() -> CedarExitCode {
@@ -52,6 +52,8 @@ fn main() -> CedarExitCode {
         Commands::Link(args) => link(&args),
         Commands::TranslatePolicy(args) => translate_policy(&args),
         Commands::Visualize(args) => visualize(&args),
+        Commands::ExampleEntities(args) => example_entities(&args),
+        Commands::ExampleRequest(args) => example_request(&args),
         Commands::TranslateSchema(args) => translate_schema(&args),
         Commands::New(args) => new(&args),
         Commands::PartiallyAuthorize(args) => partial_authorize(&args),
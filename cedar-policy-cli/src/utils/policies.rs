@@ -248,6 +248,7 @@ mod tests {
             × failed to parse JSON policy
             ├─▶ error deserializing a policy/template from JSON
             ╰─▶ unknown variant `bogus`, expected one of `All`, `all`, `==`, `in`, `is`
+            help: the error is at `/principal`
             "#);
         });
     }
@@ -14,7 +14,7 @@
  * limitations under the License.
  */
 
-use cedar_policy::Schema;
+use cedar_policy::{Schema, SchemaError};
 use clap::{Args, ValueEnum};
 use miette::{Result, WrapErr};
 use std::path::{Path, PathBuf};
@@ -74,8 +74,12 @@ fn read_schema_from_file(path: impl AsRef<Path>, format: SchemaFormat) -> Result
     let path = path.as_ref();
     let schema_src = read_from_file(path, "schema")?;
     match format {
-        SchemaFormat::Json => Schema::from_json_str(&schema_src)
-            .wrap_err_with(|| format!("failed to parse schema from file {}", path.display())),
+        SchemaFormat::Json => Schema::from_json_str(&schema_src).map_err(|err| {
+            json_schema_error(
+                err,
+                format!("failed to parse schema from file {}", path.display()),
+            )
+        }),
         SchemaFormat::Cedar => {
             let (schema, warnings) = Schema::from_cedarschema_str(&schema_src)
                 .wrap_err_with(|| format!("failed to parse schema from file {}", path.display()))?;
@@ -88,6 +92,16 @@ fn read_schema_from_file(path: impl AsRef<Path>, format: SchemaFormat) -> Result
     }
 }
 
+/// Report an error in a JSON-format schema, saying where in the schema it
+/// occurred if known
+pub(crate) fn json_schema_error(err: SchemaError, context: String) -> miette::Report {
+    let context = match err.json_location() {
+        Some(location) => format!("{context} at {location}"),
+        None => context,
+    };
+    miette::Report::new(err).wrap_err(context)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,13 +133,25 @@ mod tests {
         let err = read_schema_from_file(f.path(), SchemaFormat::Json).unwrap_err();
         insta::with_settings!({filters => vec![TEMPFILE_FILTER]}, {
             insta::assert_snapshot!(render_err(&err), @r"
-            × failed to parse schema from file <TEMPFILE>
+            × failed to parse schema from file <TEMPFILE> at line 1, column 2
             ╰─▶ expected ident at line 1 column 2
             help: this API was expecting a schema in the JSON format; did you mean to use a different function, which expects the Cedar schema format?
             ");
         });
     }
 
+    #[test]
+    fn json_schema_from_file_error_location() {
+        let mut f = tempfile::NamedTempFile::new().unwrap();
+        f.write_all(br#"{ "": { "entityTypes": {}, "actions": { "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": [] } } } } }"#).unwrap();
+        let err = read_schema_from_file(f.path(), SchemaFormat::Json).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with(" at `//actions/view/appliesTo/principalTypes/0` (line 1, column 85)"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn schema_from_missing_file() {
         let err = read_schema_from_file("/tmp/nonexistent_cedar_schema.json", SchemaFormat::Cedar)
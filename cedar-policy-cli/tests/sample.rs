@@ -1948,6 +1948,7 @@ fn test_json_policy_from_stdin_parse_error() {
     × failed to parse JSON policy
     ├─▶ error deserializing a policy/template from JSON
     ╰─▶ unknown variant `bogus`, expected one of `All`, `all`, `==`, `in`, `is`
+    help: the error is at `/principal`
     ");
 }
 
@@ -2084,3 +2085,55 @@ fn link_file_cant_read() {
     });
     assert!(!output.status.success());
 }
+
+#[test]
+fn example_request_is_accepted_by_authorize() {
+    let dir = tempfile::tempdir().expect("failed to create tempdir");
+    let schema = "sample-data/sandbox_c/schema.cedarschema";
+    let request = cargo::cargo_bin_cmd!("cedar")
+        .arg("example-request")
+        .arg("--schema")
+        .arg(schema)
+        .arg("--action")
+        .arg(r#"Action::"view""#)
+        .output()
+        .expect("failed to run cedar");
+    assert!(request.status.success());
+    let request_file = dir.path().join("request.json");
+    std::fs::write(&request_file, &request.stdout).unwrap();
+    let entities = cargo::cargo_bin_cmd!("cedar")
+        .arg("example-entities")
+        .arg("--schema")
+        .arg(schema)
+        .output()
+        .expect("failed to run cedar");
+    assert!(entities.status.success());
+    let entities_file = dir.path().join("entities.json");
+    std::fs::write(&entities_file, &entities.stdout).unwrap();
+
+    cargo::cargo_bin_cmd!("cedar")
+        .arg("authorize")
+        .arg("--request-json")
+        .arg(&request_file)
+        .arg("--entities")
+        .arg(&entities_file)
+        .arg("--schema")
+        .arg(schema)
+        .arg("--policies")
+        .arg("sample-data/sandbox_c/policies.cedar")
+        .assert()
+        .stdout(predicate::str::contains("DENY"));
+}
+
+#[test]
+fn example_request_undeclared_action() {
+    cargo::cargo_bin_cmd!("cedar")
+        .arg("example-request")
+        .arg("--schema")
+        .arg("sample-data/sandbox_c/schema.cedarschema")
+        .arg("--action")
+        .arg(r#"Action::"nope""#)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not declared in the schema"));
+}
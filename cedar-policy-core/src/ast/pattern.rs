@@ -28,16 +28,72 @@ pub enum PatternElem {
 
 /// Represent a pattern literal (the RHS of the like operator)
 /// Also provides an implementation of the Display trait as well as a wildcard matching method.
-#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
+///
+/// The pattern is compiled for matching when it is constructed, so that
+/// evaluating a `like` expression does not re-interpret the pattern.
+#[derive(Debug, Clone)]
 pub struct Pattern {
     /// A vector of pattern elements
     elems: Arc<Vec<PatternElem>>,
+    /// The pattern compiled for matching
+    matcher: Arc<Matcher>,
+}
+
+/// A pattern compiled for matching: the literal text between its wildcards
+#[derive(Debug)]
+struct Matcher {
+    /// The literal text before the first wildcard, between each pair of
+    /// consecutive wildcards, and after the last wildcard. There is always
+    /// one more segment than there are wildcards, so this is never empty.
+    segments: Vec<String>,
+}
+
+impl Matcher {
+    fn new(elems: &[PatternElem]) -> Self {
+        let mut segments = vec![String::new()];
+        for elem in elems {
+            match elem {
+                PatternElem::Char(c) => {
+                    if let Some(segment) = segments.last_mut() {
+                        segment.push(*c);
+                    }
+                }
+                PatternElem::Wildcard => segments.push(String::new()),
+            }
+        }
+        Self { segments }
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match self.segments.as_slice() {
+            [] => text.is_empty(),
+            [literal] => text == literal,
+            [first, middle @ .., last] => {
+                let Some(rest) = text.strip_prefix(first.as_str()) else {
+                    return false;
+                };
+                let Some(mut rest) = rest.strip_suffix(last.as_str()) else {
+                    return false;
+                };
+                // Matching each segment at its leftmost occurrence leaves the
+                // most text for the segments after it
+                for segment in middle {
+                    match rest.split_once(segment.as_str()) {
+                        Some((_, after)) => rest = after,
+                        None => return false,
+                    }
+                }
+                true
+            }
+        }
+    }
 }
 
 impl Pattern {
     /// Explicitly create a pattern literal out of a shared vector of pattern elements
     fn new(elems: Arc<Vec<PatternElem>>) -> Self {
-        Self { elems }
+        let matcher = Arc::new(Matcher::new(&elems));
+        Self { elems, matcher }
     }
 
     /// Getter to the wrapped vector
@@ -59,6 +115,39 @@ impl Pattern {
     pub fn is_empty(&self) -> bool {
         self.elems.is_empty()
     }
+
+    /// Find if the argument text matches the pattern
+    pub fn wildcard_match(&self, text: &str) -> bool {
+        self.matcher.matches(text)
+    }
+}
+
+// The compiled matcher is determined by the pattern elements, so only they
+// are compared and hashed
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.elems == other.elems
+    }
+}
+
+impl Eq for Pattern {}
+
+impl std::hash::Hash for Pattern {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.elems.hash(state);
+    }
+}
+
+impl PartialOrd for Pattern {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pattern {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.elems.cmp(&other.elems)
+    }
 }
 
 impl From<Arc<Vec<PatternElem>>> for Pattern {
@@ -92,56 +181,65 @@ impl std::fmt::Display for Pattern {
     }
 }
 
-impl PatternElem {
-    fn match_char(self, text_char: char) -> bool {
-        match self {
-            PatternElem::Char(c) => text_char == c,
-            PatternElem::Wildcard => true,
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    impl std::ops::Add for Pattern {
+        type Output = Pattern;
+        fn add(self, rhs: Self) -> Self::Output {
+            let elems = [self.get_elems(), rhs.get_elems()].concat();
+            Pattern::from(elems)
         }
     }
-    fn is_wildcard(self) -> bool {
-        matches!(self, PatternElem::Wildcard)
+
+    // Map a string into a pattern literal with `PatternElem::Char`
+    fn string_map(text: &str) -> Pattern {
+        text.chars().map(PatternElem::Char).collect()
     }
-}
 
-impl Pattern {
-    /// Find if the argument text matches the pattern
-    pub fn wildcard_match(&self, text: &str) -> bool {
-        let pattern = self.get_elems();
+    // Create a star pattern literal
+    fn star() -> Pattern {
+        Pattern::from(vec![PatternElem::Wildcard])
+    }
+
+    // Create an empty pattern literal
+    fn empty() -> Pattern {
+        Pattern::from(vec![])
+    }
+
+    // The matcher used before patterns were compiled, which re-interprets the
+    // pattern on every match. Kept as a reference for differential testing.
+    #[expect(
+        clippy::indexing_slicing,
+        reason = "indices are checked to be less than the lengths"
+    )]
+    fn reference_match(pattern: &[PatternElem], text: &str) -> bool {
         if pattern.is_empty() {
             return text.is_empty();
         }
-
-        // Copying the strings into vectors requires extra space, but has two benefits:
-        // 1. It makes accessing elements more efficient. The alternative (i.e.,
-        //    chars().nth()) needs to re-scan the string for each invocation. Note
-        //    that a simple iterator will not work here since we move both forward
-        //    and backward through the string.
-        // 2. It provides an unambiguous length. In general for a string s,
-        //    s.len() is not the same as s.chars().count(). The length of these
-        //    created vectors will match .chars().count()
         let text: Vec<char> = text.chars().collect();
+        let is_wildcard = |elem: PatternElem| matches!(elem, PatternElem::Wildcard);
+        let match_char = |elem: PatternElem, c: char| match elem {
+            PatternElem::Char(p) => p == c,
+            PatternElem::Wildcard => true,
+        };
 
-        let mut i: usize = 0; // index into text
-        let mut j: usize = 0; // index into pattern
-        let mut star_idx: usize = 0; // index in pattern (j) of the most recent *
-        let mut tmp_idx: usize = 0; // index in text (i) of the most recent *
-        let mut contains_star: bool = false; // does the pattern contain *?
-
+        let mut i: usize = 0;
+        let mut j: usize = 0;
+        let mut star_idx: usize = 0;
+        let mut tmp_idx: usize = 0;
+        let mut contains_star: bool = false;
         let text_len = text.len();
         let pattern_len = pattern.len();
 
         while i < text_len && (!contains_star || star_idx != pattern_len - 1) {
-            #[expect(
-                clippy::indexing_slicing,
-                reason = "`j` is checked to be less than length"
-            )]
-            if j < pattern_len && pattern[j].is_wildcard() {
+            if j < pattern_len && is_wildcard(pattern[j]) {
                 contains_star = true;
                 star_idx = j;
                 tmp_idx = i;
                 j += 1;
-            } else if j < pattern_len && pattern[j].match_char(text[i]) {
+            } else if j < pattern_len && match_char(pattern[j], text[i]) {
                 i += 1;
                 j += 1;
             } else if contains_star {
@@ -152,45 +250,11 @@ impl Pattern {
                 return false;
             }
         }
-
-        #[expect(
-            clippy::indexing_slicing,
-            reason = "`j` is checked to be less than length"
-        )]
-        while j < pattern_len && pattern[j].is_wildcard() {
+        while j < pattern_len && is_wildcard(pattern[j]) {
             j += 1;
         }
-
         j == pattern_len
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    impl std::ops::Add for Pattern {
-        type Output = Pattern;
-        fn add(self, rhs: Self) -> Self::Output {
-            let elems = [self.get_elems(), rhs.get_elems()].concat();
-            Pattern::from(elems)
-        }
-    }
-
-    // Map a string into a pattern literal with `PatternElem::Char`
-    fn string_map(text: &str) -> Pattern {
-        text.chars().map(PatternElem::Char).collect()
-    }
-
-    // Create a star pattern literal
-    fn star() -> Pattern {
-        Pattern::from(vec![PatternElem::Wildcard])
-    }
-
-    // Create an empty pattern literal
-    fn empty() -> Pattern {
-        Pattern::from(vec![])
-    }
 
     #[test]
     fn test_wildcard_match_basic() {
@@ -242,4 +306,42 @@ mod test {
         // Patterns that do not match "ḛ̶͑͝x̶͔͛a̵̰̯͛m̴͉̋́p̷̠͂l̵͇̍̔ȩ̶̣͝"
         assert!(!(string_map("y") + star()).wildcard_match("ḛ̶͑͝x̶͔͛a̵̰̯͛m̴͉̋́p̷̠͂l̵͇̍̔ȩ̶̣͝"));
     }
+
+    #[test]
+    #[expect(clippy::indexing_slicing, reason = "unit test code")]
+    fn compiled_matches_reference() {
+        // A small alphabet, including a literal `*` and multi-byte characters,
+        // so that random patterns and texts often (partially) match
+        const CHARS: [char; 5] = ['a', 'b', '*', 'é', '🦀'];
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: u64| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            usize::try_from(state % bound).unwrap()
+        };
+        for _ in 0..50_000 {
+            let pattern: Pattern = (0..next(8))
+                .map(|_| match next(6) {
+                    0 | 1 => PatternElem::Wildcard,
+                    _ => PatternElem::Char(CHARS[next(5)]),
+                })
+                .collect();
+            let text: String = (0..next(10)).map(|_| CHARS[next(5)]).collect();
+            assert_eq!(
+                pattern.wildcard_match(&text),
+                reference_match(pattern.get_elems(), &text),
+                "pattern `{pattern}` on text `{text}`"
+            );
+        }
+    }
+
+    #[test]
+    fn pattern_eq_ignores_matcher() {
+        let a = string_map("a") + star();
+        let b: Pattern = vec![PatternElem::Char('a'), PatternElem::Wildcard].into();
+        assert_eq!(a, b);
+        assert!(a < string_map("b"));
+    }
 }
@@ -324,7 +324,7 @@ impl Diagnostic for NotValue {
 
     fn source_code(&self) -> Option<&dyn miette::SourceCode> {
         match self {
-            Self::NotValue { loc } => loc.as_ref().map(|loc| &loc.src as &dyn miette::SourceCode),
+            Self::NotValue { loc } => loc.as_ref().map(|loc| loc as &dyn miette::SourceCode),
         }
     }
 }
@@ -20,6 +20,7 @@ use super::{
 };
 use crate::ast::{Context, ContextCreationError};
 use crate::extensions::Extensions;
+use crate::parser::ParserOptions;
 use miette::Diagnostic;
 use std::collections::BTreeMap;
 use thiserror::Error;
@@ -84,6 +85,14 @@ impl<S: ContextSchema> ContextJsonParser<'_, '_, S> {
         &self,
         json: serde_json::Value,
     ) -> Result<Context, ContextJsonDeserializationError> {
+        let max = ParserOptions::default().max_nesting_depth();
+        if nesting_depth(&json) > max {
+            return Err(JsonDeserializationError::nesting_too_deep(
+                JsonDeserializationErrorContext::Context,
+                max,
+            )
+            .into());
+        }
         let vparser = ValueParser::new(self.extensions);
         let expected_ty = self.schema.map(|s| s.context_type());
         let rexpr = vparser.val_into_restricted_expr(json, expected_ty.as_ref(), &|| {
@@ -103,6 +112,23 @@ impl<S: ContextSchema> ContextJsonParser<'_, '_, S> {
     }
 }
 
+/// The nesting depth of `json`, where a value which is not an array or object
+/// has depth 1. Computed without recursion, since `json` may be nested deeper
+/// than we could recurse.
+fn nesting_depth(json: &serde_json::Value) -> usize {
+    let mut stack = vec![(json, 1)];
+    let mut max_depth = 0;
+    while let Some((val, depth)) = stack.pop() {
+        max_depth = max_depth.max(depth);
+        match val {
+            serde_json::Value::Array(vals) => stack.extend(vals.iter().map(|v| (v, depth + 1))),
+            serde_json::Value::Object(map) => stack.extend(map.values().map(|v| (v, depth + 1))),
+            _ => {}
+        }
+    }
+    max_depth
+}
+
 /// Errors possible when deserializing request context from JSON
 #[derive(Debug, Diagnostic, Error)]
 pub enum ContextJsonDeserializationError {
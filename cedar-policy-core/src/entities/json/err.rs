@@ -99,6 +99,10 @@ pub enum JsonDeserializationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     DuplicateKey(DuplicateKey),
+    /// A value is nested deeper than the maximum nesting depth
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NestingTooDeep(NestingTooDeep),
     /// Error when evaluating an entity attribute
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -216,6 +220,13 @@ impl JsonDeserializationError {
         })
     }
 
+    pub(crate) fn nesting_too_deep(ctx: JsonDeserializationErrorContext, max: usize) -> Self {
+        Self::NestingTooDeep(NestingTooDeep {
+            ctx: Box::new(ctx),
+            max,
+        })
+    }
+
     pub(crate) fn unexpected_record_attr(
         ctx: JsonDeserializationErrorContext,
         record_attr: impl Into<SmolStr>,
@@ -290,6 +301,17 @@ pub struct DuplicateKey {
     key: SmolStr,
 }
 
+#[derive(Debug, Error, Diagnostic)]
+#[error("{}, value is nested more than {} levels deep", .ctx, .max)]
+#[diagnostic(help("the maximum nesting depth is that of the default `ParserOptions`"))]
+/// Error type for values nested deeper than the maximum nesting depth
+pub struct NestingTooDeep {
+    /// Context of this error
+    ctx: Box<JsonDeserializationErrorContext>,
+    /// The maximum nesting depth
+    max: usize,
+}
+
 #[derive(Debug, Error, Diagnostic)]
 #[error("{}, missing extension constructor for {}", .ctx, .return_type)]
 #[diagnostic(help("expected a value of type {} because of the schema", .return_type))]
@@ -23,7 +23,7 @@ macro_rules! impl_diagnostic_from_source_loc_opt_field {
         fn source_code(&self) -> Option<&dyn miette::SourceCode> {
             self.$($id).+
                 .as_ref()
-                .map(|loc| &loc.src as &dyn miette::SourceCode)
+                .map(|loc| loc as &dyn miette::SourceCode)
         }
 
         fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
@@ -45,12 +45,8 @@ macro_rules! impl_diagnostic_from_two_source_loc_opt_fields {
         fn source_code(&self) -> Option<&dyn miette::SourceCode> {
             self.$i
                 .as_ref()
-                .map(|loc| &loc.src as &dyn miette::SourceCode)
-                .or_else(|| {
-                    self.$j
-                        .as_ref()
-                        .map(|loc| &loc.src as &dyn miette::SourceCode)
-                })
+                .map(|loc| loc as &dyn miette::SourceCode)
+                .or_else(|| self.$j.as_ref().map(|loc| loc as &dyn miette::SourceCode))
         }
 
         fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
@@ -80,10 +76,7 @@ macro_rules! impl_diagnostic_from_two_source_loc_opt_fields {
 macro_rules! impl_diagnostic_from_method_on_field {
     ( $i:ident, $m:ident ) => {
         fn source_code(&self) -> Option<&dyn miette::SourceCode> {
-            self.$i
-                .$m()
-                .as_ref()
-                .map(|loc| &loc.src as &dyn miette::SourceCode)
+            self.$i.$m().map(|loc| loc as &dyn miette::SourceCode)
         }
 
         fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
@@ -107,8 +100,7 @@ macro_rules! impl_diagnostic_from_method_on_nonempty_field {
             self.$i
                 .first()
                 .$m()
-                .as_ref()
-                .map(|loc| &loc.src as &dyn miette::SourceCode)
+                .map(|loc| loc as &dyn miette::SourceCode)
         }
 
         fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
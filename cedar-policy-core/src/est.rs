@@ -20,6 +20,8 @@ mod err;
 pub use err::*;
 mod expr;
 pub use expr::*;
+mod json_location;
+pub use json_location::{locate_deserialization_error, locate_out_of_range_integer};
 mod policy_set;
 pub use policy_set::*;
 mod scope_constraints;
@@ -29,13 +31,15 @@ pub use annotation::*;
 
 use crate::ast::EntityUID;
 use crate::ast::{self, Annotation};
-use crate::entities::json::{err::JsonDeserializationError, EntityUidJson};
+use crate::entities::json::err::{JsonDeserializationError, JsonDeserializationErrorContext};
+use crate::entities::json::EntityUidJson;
 use crate::expr_builder::ExprBuilder;
 use crate::parser::cst;
 use crate::parser::err::{parse_errors, ParseErrors, ToASTError, ToASTErrorKind};
 use crate::parser::util::{flatten_tuple_2, flatten_tuple_4};
 #[cfg(feature = "tolerant-ast")]
 use crate::parser::Loc;
+use crate::parser::ParserOptions;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::collections::{BTreeMap, HashMap};
@@ -132,6 +136,12 @@ impl Policy {
             || self.resource.has_slot()
             || self.conditions.iter().any(|c| c.has_slot())
     }
+
+    /// Get a mutable reference to the annotations of this policy or template,
+    /// e.g., to add or remove annotations
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        &mut self.annotations
+    }
 }
 
 impl Clause {
@@ -332,6 +342,17 @@ impl Clause {
     }
     /// `id` is the ID of the policy the clause belongs to, used only for reporting errors
     fn try_into_ast(self, id: &ast::PolicyID) -> Result<ast::Expr, FromJsonError> {
+        // Converting the expression recurses once per level of nesting, so
+        // check its height, which is computed without recursion, first
+        let (Clause::When(expr) | Clause::Unless(expr)) = &self;
+        let max = ParserOptions::default().max_nesting_depth();
+        if expr.height() >= max {
+            return Err(JsonDeserializationError::nesting_too_deep(
+                JsonDeserializationErrorContext::Policy { id: id.clone() },
+                max,
+            )
+            .into());
+        }
         match self {
             Clause::When(expr) => Self::filter_slots(expr.try_into_ast(id)?, true),
             Clause::Unless(expr) => {
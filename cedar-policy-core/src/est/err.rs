@@ -84,6 +84,18 @@ pub enum FromJsonError {
     #[cfg(feature = "tolerant-ast")]
     #[error("AST error node")]
     ASTErrorNode,
+    /// EST contained an integer literal that is out of range. This is the JSON
+    /// counterpart of [`crate::parser::err::ToASTErrorKind::IntegerLiteralOutOfRange`].
+    #[error("integer literal `{literal}` is out of range")]
+    #[diagnostic(help(
+        "integer literals must be between `{}` and `{}`",
+        ast::InputInteger::MIN,
+        ast::InputInteger::MAX
+    ))]
+    IntegerLiteralOutOfRange {
+        /// The integer literal, as it appears in the JSON value
+        literal: String,
+    },
 }
 
 /// Errors arising while converting a policy set from its JSON representation (aka EST) into an AST
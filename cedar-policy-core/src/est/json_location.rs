@@ -0,0 +1,303 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Locating deserialization errors in policies in the EST (JSON) format.
+//!
+//! `serde_json` does not report where in a JSON value deserialization failed,
+//! so we find the innermost part of the policy which fails to deserialize on
+//! its own, and report its JSON pointer (RFC 6901).
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::{ActionConstraint, Annotations, Clause, Expr, PrincipalConstraint, ResourceConstraint};
+use crate::ast;
+
+/// Keys of the fields of an operator which hold subexpressions
+const SUBEXPRESSION_FIELDS: [&str; 7] = ["left", "right", "arg", "if", "then", "else", "in"];
+
+/// Get the JSON pointer to the innermost part of `json` which fails to
+/// deserialize as part of an [`super::Policy`], if any.
+///
+/// Returns `None` if every part deserializes on its own, e.g., when the
+/// policy is missing a field or has an unknown field.
+pub fn locate_deserialization_error(json: &Value) -> Option<String> {
+    locate(json).map(pointer)
+}
+
+/// If the innermost part of `json` which fails to deserialize as part of an
+/// [`super::Policy`] contains an integer which is out of the range of Cedar
+/// integers, get the JSON pointer (RFC 6901) to that integer and the integer
+/// as it appears in the JSON value.
+///
+/// Integers below `i64::MIN` or above `u64::MAX` are represented as floats by
+/// `serde_json`, so they are given in floating point notation, e.g.,
+/// `-9.223372036854776e+18`.
+pub fn locate_out_of_range_integer(json: &Value) -> Option<(String, String)> {
+    let mut path = locate(json)?;
+    let value = json.pointer(&pointer(&path))?;
+    let literal = find_out_of_range_integer(value, &mut path)?;
+    Some((pointer(path), literal))
+}
+
+/// Get the path to the innermost part of `json` which fails to deserialize as
+/// part of an [`super::Policy`], as for [`locate_deserialization_error`]
+fn locate(json: &Value) -> Option<Vec<String>> {
+    let policy = json.as_object()?;
+    let scope_fails: [(&str, Check); 5] = [
+        ("effect", fails::<ast::Effect>),
+        ("principal", fails::<PrincipalConstraint>),
+        ("action", fails::<ActionConstraint>),
+        ("resource", fails::<ResourceConstraint>),
+        ("annotations", fails::<Annotations>),
+    ];
+    for (key, fails) in scope_fails {
+        if policy.get(key).is_some_and(fails) {
+            return Some(vec![key.to_string()]);
+        }
+    }
+    let conditions = policy.get("conditions")?;
+    let Some(clauses) = conditions.as_array() else {
+        return Some(vec!["conditions".to_string()]);
+    };
+    let (index, clause) = clauses
+        .iter()
+        .enumerate()
+        .find(|(_, clause)| fails::<Clause>(clause))?;
+    let mut path = vec!["conditions".to_string(), index.to_string()];
+    if let Some(body) = clause.get("body").filter(|body| fails::<Expr>(body)) {
+        path.push("body".into());
+        locate_in_expr(body, &mut path);
+    }
+    Some(path)
+}
+
+/// Extend `path` to the operator of the innermost subexpression of `expr`
+/// which fails to deserialize, where `expr` itself fails to deserialize
+fn locate_in_expr(expr: &Value, path: &mut Vec<String>) {
+    let Some((op, arg)) = expr
+        .as_object()
+        .filter(|obj| obj.len() == 1)
+        .and_then(|obj| obj.iter().next())
+    else {
+        return;
+    };
+    path.push(op.clone());
+    // The argument of `Value` is a literal, not an expression
+    if op == "Value" {
+        return;
+    }
+    let subexpressions: Vec<(String, &Value)> = match arg {
+        // Set literals and extension function calls
+        Value::Array(args) => args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| (i.to_string(), arg))
+            .collect(),
+        Value::Object(fields) if op == "Record" => {
+            fields.iter().map(|(k, v)| (k.clone(), v)).collect()
+        }
+        Value::Object(fields) => SUBEXPRESSION_FIELDS
+            .iter()
+            .filter_map(|key| Some((key.to_string(), fields.get(*key)?)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    if let Some((key, subexpression)) = subexpressions
+        .into_iter()
+        .find(|(_, subexpression)| fails::<Expr>(subexpression))
+    {
+        path.push(key);
+        locate_in_expr(subexpression, path);
+    }
+}
+
+/// Find an integer in `value` which is out of the range of Cedar integers,
+/// extending `path` to it
+fn find_out_of_range_integer(value: &Value, path: &mut Vec<String>) -> Option<String> {
+    match value {
+        Value::Number(n) if is_out_of_range_integer(n) => Some(n.to_string()),
+        Value::Array(values) => values.iter().enumerate().find_map(|(i, value)| {
+            path.push(i.to_string());
+            let found = find_out_of_range_integer(value, path);
+            if found.is_none() {
+                path.pop();
+            }
+            found
+        }),
+        Value::Object(fields) => fields.iter().find_map(|(key, value)| {
+            path.push(key.clone());
+            let found = find_out_of_range_integer(value, path);
+            if found.is_none() {
+                path.pop();
+            }
+            found
+        }),
+        _ => None,
+    }
+}
+
+/// Is `n` an integer which does not fit in an `i64`? Such integers are parsed
+/// as `u64`s if they fit, and as floats otherwise.
+fn is_out_of_range_integer(n: &serde_json::Number) -> bool {
+    // 2^63, the smallest magnitude a float parsed from an integer outside the
+    // `i64` range can have after rounding
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    n.as_i64().is_none() && (n.is_u64() || n.as_f64().is_some_and(|f| f.abs() >= LIMIT))
+}
+
+/// Check whether a JSON value fails to deserialize as some type
+type Check = fn(&Value) -> bool;
+
+/// Does `json` fail to deserialize as a `T`?
+fn fails<T: DeserializeOwned>(json: &Value) -> bool {
+    T::deserialize(json).is_err()
+}
+
+/// Build a JSON pointer (RFC 6901) from the keys and indices leading to a value
+fn pointer<S: AsRef<str>>(path: impl IntoIterator<Item = S>) -> String {
+    let mut pointer = String::new();
+    for key in path {
+        pointer.push('/');
+        pointer.push_str(&key.as_ref().replace('~', "~0").replace('/', "~1"));
+    }
+    pointer
+}
+
+#[cfg(test)]
+#[expect(
+    clippy::indexing_slicing,
+    clippy::needless_pass_by_value,
+    reason = "unit test code"
+)]
+mod test {
+    use serde_json::json;
+
+    use super::{locate_deserialization_error, locate_out_of_range_integer};
+
+    fn policy(body: serde_json::Value) -> serde_json::Value {
+        json!({
+            "effect": "permit",
+            "principal": { "op": "All" },
+            "action": { "op": "All" },
+            "resource": { "op": "All" },
+            "conditions": [
+                { "kind": "when", "body": { "Value": true } },
+                { "kind": "unless", "body": body }
+            ]
+        })
+    }
+
+    #[test]
+    fn valid_policy() {
+        assert_eq!(
+            locate_deserialization_error(&policy(json!({ "Var": "principal" }))),
+            None
+        );
+    }
+
+    #[test]
+    fn scope() {
+        let mut json = policy(json!({ "Var": "principal" }));
+        json["principal"] =
+            json!({ "op": "contains", "entity": { "type": "User", "id": "alice" } });
+        assert_eq!(
+            locate_deserialization_error(&json).as_deref(),
+            Some("/principal")
+        );
+        json["effect"] = json!("allow");
+        assert_eq!(
+            locate_deserialization_error(&json).as_deref(),
+            Some("/effect")
+        );
+    }
+
+    #[test]
+    fn nested_expressions() {
+        for (body, expected) in [
+            (
+                json!({ "==": { "left": { "Var": "principal" }, "right": { "Value": 1.5 } } }),
+                "/conditions/1/body/==/right/Value",
+            ),
+            (
+                json!({ "if-then-else": {
+                    "if": { "Value": true },
+                    "then": { "Set": [{ "Value": 1 }, { "Var": "nobody" }] },
+                    "else": { "Set": [] }
+                } }),
+                "/conditions/1/body/if-then-else/then/Set/1/Var",
+            ),
+            (
+                json!({ "Record": { "a/b": { "!": { "args": { "Value": true } } } } }),
+                "/conditions/1/body/Record/a~1b/!",
+            ),
+            (
+                json!({ "ip": [{ "Value": "10.0.0.1" }, { "bogus": {} }] }),
+                "/conditions/1/body/ip/1/bogus",
+            ),
+            (
+                json!({ "like": { "left": { "Var": "resource" }, "pattern": [{ "Wildcard": 1 }] } }),
+                "/conditions/1/body/like",
+            ),
+        ] {
+            assert_eq!(
+                locate_deserialization_error(&policy(body)).as_deref(),
+                Some(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn bad_clause() {
+        let mut json = policy(json!({ "Var": "principal" }));
+        json["conditions"][0]["kind"] = json!("if");
+        assert_eq!(
+            locate_deserialization_error(&json).as_deref(),
+            Some("/conditions/0")
+        );
+        json["conditions"] = json!({});
+        assert_eq!(
+            locate_deserialization_error(&json).as_deref(),
+            Some("/conditions")
+        );
+    }
+
+    #[test]
+    fn out_of_range_integers() {
+        for (body, expected) in [
+            (
+                json!({ "==": { "left": { "Var": "principal" }, "right": { "Value": 9_223_372_036_854_775_808_u64 } } }),
+                Some(("/conditions/1/body/==/right/Value", "9223372036854775808")),
+            ),
+            (
+                serde_json::from_str(r#"{ "Value": [1, { "n": -9223372036854775809 }] }"#).unwrap(),
+                Some(("/conditions/1/body/Value/1/n", "-9.223372036854776e+18")),
+            ),
+            (
+                json!({ "==": { "left": { "Var": "principal" }, "right": { "Value": 1.5 } } }),
+                None,
+            ),
+            (json!({ "Value": -9_223_372_036_854_775_808_i64 }), None),
+        ] {
+            assert_eq!(
+                locate_out_of_range_integer(&policy(body))
+                    .as_ref()
+                    .map(|(pointer, literal)| (pointer.as_str(), literal.as_str())),
+                expected
+            );
+        }
+    }
+}
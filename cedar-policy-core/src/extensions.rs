@@ -336,6 +336,15 @@ impl ExtStyles<'static> {
         EXTENSION_STYLES.methods.contains(id)
     }
 
+    /// If this string is a known method name. Unlike [`ExtStyles::is_method`],
+    /// this does not require `s` to be a valid identifier.
+    pub(crate) fn is_method_str(s: &str) -> bool {
+        EXTENSION_STYLES
+            .methods
+            .iter()
+            .any(|m| <UnreservedId as AsRef<str>>::as_ref(m) == s)
+    }
+
     /// If this [`Name`] is a known function name
     pub(crate) fn is_function(id: &Name) -> bool {
         EXTENSION_STYLES.functions.contains(id)
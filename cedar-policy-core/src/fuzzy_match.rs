@@ -49,7 +49,7 @@ pub fn fuzzy_search_limited(
     }
 }
 
-fn levenshtein_distance(word1: &str, word2: &str) -> usize {
+pub(crate) fn levenshtein_distance(word1: &str, word2: &str) -> usize {
     let w1 = word1.chars().collect::<Vec<_>>();
     let w2 = word2.chars().collect::<Vec<_>>();
     let word1_length = w1.len() + 1;
@@ -16,6 +16,8 @@
 
 //! This module contains the parser for the Cedar language.
 
+/// Comments around and inside parsed policies
+pub mod comments;
 /// Concrete Syntax Tree def used as parser first pass
 pub mod cst;
 /// Step two: convert CST to package AST
@@ -31,6 +33,14 @@ pub use loc::Loc;
 /// Metadata wrapper for CST Nodes
 mod node;
 pub use node::Node;
+/// Limits on the size of the input, enforced while parsing
+mod limits;
+pub use limits::{ParseLimit, ParseLimits};
+/// Check of the nesting depth of CST expressions
+mod nesting;
+/// Options controlling the parser
+mod options;
+pub use options::ParserOptions;
 /// Step one: Convert text to CST
 pub mod text_to_cst;
 /// Utility functions to unescape string literals
@@ -38,7 +48,9 @@ pub mod unescape;
 /// Utility functions
 pub mod util;
 
-use smol_str::SmolStr;
+use itertools::Either;
+use miette::Diagnostic;
+use smol_str::{format_smolstr, SmolStr};
 use std::collections::HashMap;
 
 use crate::ast;
@@ -52,6 +64,15 @@ pub fn parse_policyset(text: &str) -> Result<ast::PolicySet, err::ParseErrors> {
     cst.to_policyset()
 }
 
+/// Like `parse_policyset()`, but a policy with an `@id("...")` annotation gets
+/// the value of the annotation as its `PolicyID`. Policies without one get
+/// the same numbered ids as with `parse_policyset()`. The `@id` annotation is
+/// kept in the annotations of the policy.
+pub fn parse_policyset_with_id_annotations(text: &str) -> Result<ast::PolicySet, err::ParseErrors> {
+    let cst = text_to_cst::parse_policies(text)?;
+    cst.to_policyset_with_id_annotations()
+}
+
 /// Like `parse_policyset()`, but also returns the (lossless) original text of
 /// each individual policy.
 /// INVARIANT: The `PolicyId` of every `Policy` and `Template` returned by the
@@ -60,7 +81,49 @@ pub fn parse_policyset(text: &str) -> Result<ast::PolicySet, err::ParseErrors> {
 pub fn parse_policyset_and_also_return_policy_text(
     text: &str,
 ) -> Result<(HashMap<ast::PolicyID, Option<&str>>, ast::PolicySet), err::ParseErrors> {
-    let cst = text_to_cst::parse_policies(text)?;
+    parse_policyset_and_also_return_policy_text_with_options(text, &ParserOptions::default())
+}
+
+/// Like `parse_policyset_and_also_return_policy_text()`, but with the limits in
+/// `options` rather than the default ones.
+pub fn parse_policyset_and_also_return_policy_text_with_options<'a>(
+    text: &'a str,
+    options: &ParserOptions,
+) -> Result<(HashMap<ast::PolicyID, Option<&'a str>>, ast::PolicySet), err::ParseErrors> {
+    policyset_and_policy_text(
+        &text_to_cst::parse_policies_with_options(text, options)?,
+        text,
+    )
+}
+
+/// Like `parse_policyset_and_also_return_policy_text()`, but failing as soon as
+/// the input exceeds one of the `limits`.
+pub fn parse_policyset_and_also_return_policy_text_with_limits<'a>(
+    text: &'a str,
+    limits: &ParseLimits,
+) -> Result<(HashMap<ast::PolicyID, Option<&'a str>>, ast::PolicySet), err::ParseErrors> {
+    policyset_and_policy_text(
+        &text_to_cst::parse_policies_with_limits(text, limits)?,
+        text,
+    )
+}
+
+/// Like `parse_policyset_and_also_return_policy_text()`, but naming the source
+/// text `name`, e.g., with the name of the file it was read from. Every source
+/// location in the policy set, and in any errors, carries the name.
+pub fn parse_named_policyset_and_also_return_policy_text<'a>(
+    name: &str,
+    text: &'a str,
+) -> Result<(HashMap<ast::PolicyID, Option<&'a str>>, ast::PolicySet), err::ParseErrors> {
+    policyset_and_policy_text(&text_to_cst::parse_named_policies(name, text)?, text)
+}
+
+/// Convert the CST `cst` of the policy set `text` to an AST, also returning the
+/// text of each policy. See `parse_policyset_and_also_return_policy_text()`.
+fn policyset_and_policy_text<'a>(
+    cst: &Node<Option<cst::Policies>>,
+    text: &'a str,
+) -> Result<(HashMap<ast::PolicyID, Option<&'a str>>, ast::PolicySet), err::ParseErrors> {
     let pset = cst.to_policyset()?;
     #[expect(
         clippy::expect_used,
@@ -112,6 +175,135 @@ pub fn parse_policyset_to_ests_and_pset(
     Ok((ests, pset))
 }
 
+/// Like `parse_policyset()`, but policies which fail to parse do not prevent
+/// parsing the rest of the policy set.
+///
+/// Returns the policy set of the policies which parsed successfully, along
+/// with the parse errors for each policy which did not. Policies are given
+/// the same numbered ids as with `parse_policyset()`, and errors are reported
+/// under the id the failing policy would have had.
+pub fn parse_policyset_recovering(
+    text: &str,
+) -> (ast::PolicySet, Vec<(ast::PolicyID, err::ParseErrors)>) {
+    let (cst, recovered_errs, trailing_errs) = parse_cst_recovering(text);
+
+    let mut pset = ast::PolicySet::new();
+    let mut failed = Vec::new();
+    let mut recovered_errs = recovered_errs.into_iter().peekable();
+    let mut num_policies = 0;
+    if let Ok(policies) = cst.with_generated_policyids() {
+        for (id, policy) in policies {
+            num_policies += 1;
+            // Errors the parser recovered from belong to the policy they occur in
+            let policy_end = policy.loc.as_ref().map_or(usize::MAX, Loc::end);
+            let policy_errs = std::iter::from_fn(|| {
+                recovered_errs.next_if(|e| error_offset(e).is_none_or(|o| o <= policy_end))
+            });
+            if let Some(errs) = err::ParseErrors::from_iter(policy_errs) {
+                failed.push((id, errs));
+                continue;
+            }
+            if let Err(err) = nesting::check_nesting_depth(policy, ParserOptions::default()) {
+                failed.push((id, err.into()));
+                continue;
+            }
+            let added = match policy.to_policy_or_template(id.clone()) {
+                Ok(Either::Left(static_policy)) => pset.add_static(static_policy),
+                Ok(Either::Right(template)) => pset.add_template(template),
+                Err(errs) => {
+                    failed.push((id, errs));
+                    continue;
+                }
+            };
+            if let Err(ast::PolicySetError::Occupied { id }) = added {
+                let err = policy.to_ast_err(err::ToASTErrorKind::DuplicatePolicyId(id.clone()));
+                failed.push((id, err.into()));
+            }
+        }
+    }
+    if let Some(errs) = err::ParseErrors::from_iter(recovered_errs.chain(trailing_errs)) {
+        failed.push((
+            ast::PolicyID::from_smolstr(format_smolstr!("policy{num_policies}")),
+            errs,
+        ));
+    }
+    (pset, failed)
+}
+
+/// Check that `text` parses as a policy set, recovering from syntax errors
+/// like `parse_policyset_recovering()`, but without converting the policies to
+/// ASTs or building a policy set.
+///
+/// Returns the errors from parsing the text to a CST, ordered by their
+/// location. These are exactly the `ParseError::ToCST` errors reported by
+/// `parse_policyset_recovering()` for the same text. Errors found when
+/// converting the CST to an AST, e.g., for an invalid scope constraint or an
+/// unknown extension function, are not reported.
+pub fn check_parse(text: &str) -> Vec<err::ParseError> {
+    let (_, mut errs, trailing_errs) = parse_cst_recovering(text);
+    errs.extend(trailing_errs);
+    errs.sort_by_key(|e| error_offset(e).unwrap_or(usize::MAX));
+    errs
+}
+
+/// Parse `text` to the CST of a policy set, recovering from syntax errors.
+/// Returns the CST, the errors the parser recovered from, and the errors in
+/// any text at the end of `text` which had to be dropped to get a CST.
+fn parse_cst_recovering(
+    text: &str,
+) -> (
+    Node<Option<cst::Policies>>,
+    Vec<err::ParseError>,
+    Vec<err::ParseError>,
+) {
+    // When the parser fails to recover from an error, we get no CST at all.
+    // The error must then be in a policy the parser could not find the end of,
+    // so we retry with the prefix of the text ending at the previous `;`,
+    // keeping the errors located after that prefix.
+    let mut end = text.len();
+    let mut trailing_errs = Vec::new();
+    loop {
+        #[expect(
+            clippy::string_slice,
+            reason = "`end` is either `text.len()`, zero, or just after a `;`"
+        )]
+        let prefix = &text[..end];
+        match text_to_cst::parse_policies_recovering(prefix) {
+            Ok((cst, recovered_errs)) => break (cst, recovered_errs, trailing_errs),
+            Err(errs) => {
+                let searched = prefix.strip_suffix(';').unwrap_or(prefix);
+                let prefix_end = searched.rfind(';').map_or(0, |i| i + 1);
+                // Errors at the very end of a truncated prefix are only due to
+                // the truncation, and errors before `prefix_end` will be found
+                // again when parsing the shorter prefix.
+                trailing_errs.extend(errs.into_iter().filter(|e| {
+                    error_offset(e)
+                        .is_none_or(|o| o >= prefix_end && (end == text.len() || o < end))
+                }));
+                end = prefix_end;
+            }
+        }
+    }
+}
+
+/// Offset in the source of the primary location of a parse error
+fn error_offset(err: &err::ParseError) -> Option<usize> {
+    err.labels()
+        .and_then(|mut labels| labels.next())
+        .map(|label| label.offset())
+}
+
+/// Parse a single policy or template with `parse`, giving it the id `id`, or
+/// "policy0" if `id` is `None`. Any errors record the id, so that they can be
+/// reported under the same id as the policy would have had.
+fn parse_with_policy_id<T>(
+    id: Option<ast::PolicyID>,
+    parse: impl FnOnce(ast::PolicyID) -> Result<T, err::ParseErrors>,
+) -> Result<T, err::ParseErrors> {
+    let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
+    parse(id.clone()).map_err(|errs| errs.with_policy_id(id))
+}
+
 /// Main function for parsing a policy _or_ template. In either case, the
 /// returned value will be a [`ast::Template`].
 /// If `id` is Some, then the resulting template will have that `id`.
@@ -120,9 +312,10 @@ pub fn parse_policy_or_template(
     id: Option<ast::PolicyID>,
     text: &str,
 ) -> Result<ast::Template, err::ParseErrors> {
-    let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
-    let cst = text_to_cst::parse_policy(text)?;
-    cst.to_template(id)
+    parse_with_policy_id(id, |id| {
+        let cst = text_to_cst::parse_policy(text)?;
+        cst.to_template(id)
+    })
 }
 
 /// Like `parse_policy_or_template()`, but also returns the (lossless) EST -- that
@@ -132,11 +325,12 @@ pub fn parse_policy_or_template_to_est_and_ast(
     id: Option<ast::PolicyID>,
     text: &str,
 ) -> Result<(est::Policy, ast::Template), err::ParseErrors> {
-    let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
-    let cst = text_to_cst::parse_policy(text)?;
-    let ast = cst.to_template(id)?;
-    let est = cst.try_into_inner()?.try_into()?;
-    Ok((est, ast))
+    parse_with_policy_id(id, |id| {
+        let cst = text_to_cst::parse_policy(text)?;
+        let ast = cst.to_template(id)?;
+        let est = cst.try_into_inner()?.try_into()?;
+        Ok((est, ast))
+    })
 }
 
 /// Main function for parsing a template.
@@ -147,10 +341,35 @@ pub fn parse_template(
     id: Option<ast::PolicyID>,
     text: &str,
 ) -> Result<ast::Template, err::ParseErrors> {
-    let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
-    let cst = text_to_cst::parse_policy(text)?;
-    let template = cst.to_template(id)?;
-    validate_template_has_slots(template, cst)
+    parse_template_with_options(id, text, &ParserOptions::default())
+}
+
+/// Like `parse_template()`, but with the limits in `options` rather than the
+/// default ones.
+pub fn parse_template_with_options(
+    id: Option<ast::PolicyID>,
+    text: &str,
+    options: &ParserOptions,
+) -> Result<ast::Template, err::ParseErrors> {
+    parse_with_policy_id(id, |id| {
+        let cst = text_to_cst::parse_policy_with_options(text, options)?;
+        let template = cst.to_template(id)?;
+        validate_template_has_slots(template, cst)
+    })
+}
+
+/// Like `parse_template()`, but failing as soon as the input exceeds one of
+/// the `limits`.
+pub fn parse_template_with_limits(
+    id: Option<ast::PolicyID>,
+    text: &str,
+    limits: &ParseLimits,
+) -> Result<ast::Template, err::ParseErrors> {
+    parse_with_policy_id(id, |id| {
+        let cst = text_to_cst::parse_policy_with_limits(text, limits)?;
+        let template = cst.to_template(id)?;
+        validate_template_has_slots(template, cst)
+    })
 }
 
 /// Main function for parsing a (static) policy.
@@ -161,9 +380,33 @@ pub fn parse_policy(
     id: Option<ast::PolicyID>,
     text: &str,
 ) -> Result<ast::StaticPolicy, err::ParseErrors> {
-    let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
-    let cst = text_to_cst::parse_policy(text)?;
-    cst.to_policy(id)
+    parse_policy_with_options(id, text, &ParserOptions::default())
+}
+
+/// Like `parse_policy()`, but with the limits in `options` rather than the
+/// default ones.
+pub fn parse_policy_with_options(
+    id: Option<ast::PolicyID>,
+    text: &str,
+    options: &ParserOptions,
+) -> Result<ast::StaticPolicy, err::ParseErrors> {
+    parse_with_policy_id(id, |id| {
+        let cst = text_to_cst::parse_policy_with_options(text, options)?;
+        cst.to_policy(id)
+    })
+}
+
+/// Like `parse_policy()`, but failing as soon as the input exceeds one of the
+/// `limits`.
+pub fn parse_policy_with_limits(
+    id: Option<ast::PolicyID>,
+    text: &str,
+    limits: &ParseLimits,
+) -> Result<ast::StaticPolicy, err::ParseErrors> {
+    parse_with_policy_id(id, |id| {
+        let cst = text_to_cst::parse_policy_with_limits(text, limits)?;
+        cst.to_policy(id)
+    })
 }
 
 /// Like `parse_policy()`, but also returns the (lossless) EST -- that is, the
@@ -173,11 +416,12 @@ pub fn parse_policy_to_est_and_ast(
     id: Option<ast::PolicyID>,
     text: &str,
 ) -> Result<(est::Policy, ast::StaticPolicy), err::ParseErrors> {
-    let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
-    let cst = text_to_cst::parse_policy(text)?;
-    let ast = cst.to_policy(id)?;
-    let est = cst.try_into_inner()?.try_into()?;
-    Ok((est, ast))
+    parse_with_policy_id(id, |id| {
+        let cst = text_to_cst::parse_policy(text)?;
+        let ast = cst.to_policy(id)?;
+        let est = cst.try_into_inner()?.try_into()?;
+        Ok((est, ast))
+    })
 }
 
 /// Parse a policy or template (either one works) to its EST representation
@@ -186,7 +430,9 @@ pub fn parse_policy_or_template_to_est(text: &str) -> Result<est::Policy, err::P
     // checks are applied by the CST-to-AST conversion and not CST-to-EST, and
     // we do not want to return any EST if the policy text would not parse
     // normally.
-    parse_policy_or_template_to_est_and_ast(None, text).map(|(est, _ast)| est)
+    let cst = text_to_cst::parse_policy(text)?;
+    cst.to_template(ast::PolicyID::from_string("policy0"))?;
+    cst.try_into_inner()?.try_into()
 }
 
 /// parse an Expr
@@ -366,9 +612,355 @@ mod tests {
     use crate::test_utils::*;
     use cool_asserts::assert_matches;
     use insta::assert_debug_snapshot;
+    use itertools::Itertools;
     use std::collections::HashSet;
     use std::sync::Arc;
 
+    #[track_caller]
+    fn expect_nesting_too_deep(errs: &err::ParseErrors, max: usize) {
+        assert_eq!(errs.len(), 1, "{errs:?}");
+        assert_matches!(errs.iter().next(), Some(ParseError::ToAST(e)) => {
+            assert_eq!(e.kind(), &ToASTErrorKind::NestingTooDeep(max));
+        });
+    }
+
+    /// Deeply nested input is rejected with an error rather than overflowing
+    /// the stack, however the expressions are nested
+    #[test]
+    fn deeply_nested_input_does_not_overflow() {
+        const DEPTH: usize = 10_000;
+        let wrappers = [
+            ("(", ")"),
+            ("-(", ")"),
+            ("!(", ")"),
+            ("[", "]"),
+            ("{a: ", "}"),
+            ("if true then ", " else 1"),
+            ("ip(", ")"),
+            ("context.contains(", ")"),
+            ("context[", "]"),
+        ];
+        // Each kind of nesting on its own, then random mixes of them, using a
+        // fixed xorshift generator so that any failure can be reproduced
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |n: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            usize::try_from(state % n as u64).unwrap()
+        };
+        let kinds = (0..wrappers.len())
+            .map(|i| vec![i; DEPTH])
+            .chain((0..10).map(|_| (0..DEPTH).map(|_| next(wrappers.len())).collect()));
+        for kind in kinds {
+            let expr = format!(
+                "{}1{}",
+                kind.iter().map(|&i| wrappers[i].0).join(""),
+                kind.iter().rev().map(|&i| wrappers[i].1).join("")
+            );
+            let max = ParserOptions::default().max_nesting_depth();
+            expect_nesting_too_deep(&parse_expr(&expr).unwrap_err(), max);
+
+            let policy = format!("permit(principal, action, resource) when {{ {expr} }};");
+            expect_nesting_too_deep(&parse_policyset(&policy).unwrap_err(), max);
+            let (pset, failed) = parse_policyset_recovering(&policy);
+            assert_eq!(pset.policies().count(), 0);
+            assert_matches!(failed.iter().exactly_one(), Ok((_, errs)) => {
+                expect_nesting_too_deep(errs, max);
+            });
+
+            let options = ParserOptions::default().with_max_nesting_depth(1000);
+            expect_nesting_too_deep(
+                &parse_policy_with_options(None, &policy, &options).unwrap_err(),
+                1000,
+            );
+        }
+    }
+
+    #[test]
+    fn long_chains_do_not_overflow() {
+        const TERMS: usize = 50_000;
+        let chains = ["||", "&&", "==", "<", "+", "-", "*"]
+            .map(|op| vec!["context.a"; TERMS].join(op))
+            .into_iter()
+            .chain([
+                format!("context{}", ".a".repeat(TERMS)),
+                format!("context{}", "[\"a\"]".repeat(TERMS)),
+                format!("context{}", ".contains(1)".repeat(TERMS)),
+            ]);
+        let max = ParserOptions::default().max_nesting_depth();
+        for expr in chains {
+            expect_nesting_too_deep(&parse_expr(&expr).unwrap_err(), max);
+            let policy = format!("permit(principal, action, resource) when {{ {expr} }};");
+            expect_nesting_too_deep(&parse_policyset(&policy).unwrap_err(), max);
+        }
+    }
+
+    #[test]
+    fn nesting_depth_limit() {
+        let nested = |depth: usize| {
+            format!(
+                "permit(principal, action, resource) when {{ {}1{} }};",
+                "[".repeat(depth - 1),
+                "]".repeat(depth - 1)
+            )
+        };
+        let max = ParserOptions::default().max_nesting_depth();
+        parse_policyset(&nested(max)).expect("should parse at the limit");
+        expect_nesting_too_deep(&parse_policyset(&nested(max + 1)).unwrap_err(), max);
+
+        let options = ParserOptions::default().with_max_nesting_depth(3);
+        parse_policy_with_options(None, &nested(3), &options).expect("should parse at the limit");
+        expect_nesting_too_deep(
+            &parse_policy_with_options(None, &nested(4), &options).unwrap_err(),
+            3,
+        );
+        parse_policy_with_options(None, &nested(max + 1), &ParserOptions::unlimited())
+            .expect("should parse without a limit");
+
+        let template = "permit(principal == ?principal, action, resource) when { [[1]] };";
+        parse_template_with_options(None, template, &options).expect("should parse at the limit");
+        expect_nesting_too_deep(
+            &parse_template_with_options(None, template, &options.with_max_nesting_depth(2))
+                .unwrap_err(),
+            2,
+        );
+    }
+
+    #[track_caller]
+    fn expect_limit_exceeded(
+        errs: &err::ParseErrors,
+        limit: ParseLimit,
+        max: usize,
+        snippet: Option<&str>,
+    ) {
+        assert_eq!(errs.len(), 1, "{errs:?}");
+        assert_matches!(errs.iter().next(), Some(ParseError::ToAST(e)) => {
+            assert_eq!(e.kind(), &ToASTErrorKind::LimitExceeded { limit, max });
+            assert_eq!(e.source_loc().map(Loc::snippet), snippet.map(Some));
+        });
+    }
+
+    #[test]
+    fn input_bytes_limit() {
+        let policy = "permit(principal, action, resource);";
+        let limits = ParseLimits::default().with_max_input_bytes(policy.len());
+        parse_policy_with_limits(None, policy, &limits).expect("should parse at the limit");
+        let limits = limits.with_max_input_bytes(policy.len() - 1);
+        expect_limit_exceeded(
+            &parse_policy_with_limits(None, policy, &limits).unwrap_err(),
+            ParseLimit::InputBytes,
+            policy.len() - 1,
+            None,
+        );
+
+        // Oversized input is rejected before it is parsed, so it need not
+        // even be valid
+        let huge = "(".repeat(1 << 20);
+        let limits = ParseLimits::default().with_max_input_bytes(1024);
+        let errs = text_to_cst::parse_policies_with_limits(&huge, &limits).unwrap_err();
+        expect_limit_exceeded(&errs, ParseLimit::InputBytes, 1024, None);
+        let errs = text_to_cst::parse_expr_with_limits(&huge, &limits).unwrap_err();
+        expect_limit_exceeded(&errs, ParseLimit::InputBytes, 1024, None);
+        assert_eq!(
+            errs.to_string(),
+            "the input size in bytes exceeds the limit of 1024"
+        );
+    }
+
+    #[test]
+    fn policies_limit() {
+        let policies = "permit(principal, action, resource);\n\
+                        forbid(principal, action, resource);\n\
+                        permit(principal == User::\"alice\", action, resource);";
+        let limits = ParseLimits::default().with_max_policies(3);
+        let (_, pset) = parse_policyset_and_also_return_policy_text_with_limits(policies, &limits)
+            .expect("should parse at the limit");
+        assert_eq!(pset.policies().count(), 3);
+        let limits = limits.with_max_policies(2);
+        expect_limit_exceeded(
+            &parse_policyset_and_also_return_policy_text_with_limits(policies, &limits)
+                .unwrap_err(),
+            ParseLimit::Policies,
+            2,
+            Some("permit(principal == User::\"alice\", action, resource);"),
+        );
+        let limits = limits.with_max_policies(0);
+        parse_policyset_and_also_return_policy_text_with_limits("", &limits)
+            .expect("an empty policy set should parse with no policies allowed");
+
+        // The parser stops at the first policy over the limit, so the syntax
+        // error after it is never reached
+        let policies = format!(
+            "{}this is not a policy",
+            "permit(principal, action, resource);".repeat(3)
+        );
+        expect_limit_exceeded(
+            &parse_policyset_and_also_return_policy_text_with_limits(
+                &policies,
+                &limits.with_max_policies(2),
+            )
+            .unwrap_err(),
+            ParseLimit::Policies,
+            2,
+            Some("permit(principal, action, resource);"),
+        );
+    }
+
+    #[test]
+    fn condition_nodes_limit() {
+        let policy = "permit(principal, action, resource) when { context.a + 1 > 2 } unless { [1, {c: 2}].contains(3) };";
+        // `context.a + 1 > 2` has 6 nodes, and the second condition 7: the
+        // set, its elements `1` and `{c: 2}`, the key `c` and value `2` of the
+        // record, the call of `contains`, and `3`
+        let limits = ParseLimits::default().with_max_condition_nodes(7);
+        parse_policy_with_limits(None, policy, &limits).expect("should parse at the limit");
+        let limits = limits.with_max_condition_nodes(5);
+        expect_limit_exceeded(
+            &parse_policy_with_limits(None, policy, &limits).unwrap_err(),
+            ParseLimit::ConditionNodes,
+            5,
+            Some("context.a + 1 > 2"),
+        );
+
+        let limits = ParseLimits::default().with_max_condition_nodes(3);
+        text_to_cst::parse_expr_with_limits("1 + 2", &limits).expect("should parse at the limit");
+        expect_limit_exceeded(
+            &text_to_cst::parse_expr_with_limits("1 + 2 + 3", &limits).unwrap_err(),
+            ParseLimit::ConditionNodes,
+            3,
+            Some("1 + 2 + 3"),
+        );
+        expect_limit_exceeded(
+            &text_to_cst::parse_expr_with_limits("if true then 1 else 2", &limits).unwrap_err(),
+            ParseLimit::ConditionNodes,
+            3,
+            Some("if true then 1 else 2"),
+        );
+    }
+
+    #[test]
+    fn condition_nodes_limit_fails_fast() {
+        // The operands of a long chain of operators are counted as they are
+        // parsed, so the parser stops at the first operand over the limit,
+        // without collecting the rest of the chain or reaching the syntax
+        // error at its end
+        let expr = format!("x{} +", " + x".repeat(100_000));
+        let limits = ParseLimits::default().with_max_condition_nodes(10);
+        let errs = text_to_cst::parse_expr_with_limits(&expr, &limits).unwrap_err();
+        expect_limit_exceeded(&errs, ParseLimit::ConditionNodes, 10, Some("x"));
+        assert_matches!(errs.iter().next(), Some(ParseError::ToAST(e)) => {
+            assert_eq!(
+                e.source_loc().map(Loc::start),
+                Some("x + x + x + x + x + x + x + x + x + x + ".len())
+            );
+        });
+
+        // Nodes are counted separately for each condition
+        let policy = format!(
+            "permit(principal, action, resource) when {{ {0} }} unless {{ {0} }};",
+            "1 + 2 + 3"
+        );
+        let limits = ParseLimits::default().with_max_condition_nodes(5);
+        parse_policy_with_limits(None, &policy, &limits).expect("should parse at the limit");
+    }
+
+    #[test]
+    fn annotation_bytes_limit() {
+        let policy = "@id(\"short\") @doc(\"a longer one\") permit(principal, action, resource);";
+        let limits = ParseLimits::default().with_max_annotation_bytes("a longer one".len());
+        parse_policy_with_limits(None, policy, &limits).expect("should parse at the limit");
+        let limits = limits.with_max_annotation_bytes("a longer one".len() - 1);
+        expect_limit_exceeded(
+            &parse_policy_with_limits(None, policy, &limits).unwrap_err(),
+            ParseLimit::AnnotationBytes,
+            "a longer one".len() - 1,
+            Some("\"a longer one\""),
+        );
+    }
+
+    #[test]
+    fn errors_record_policy_id() {
+        // A syntax error, and an error converting the CST to an AST
+        for text in [
+            "permit(principal, action, resource) when { 1 + };",
+            "permit(principal == 1, action, resource);",
+        ] {
+            for id in ["a", "b"] {
+                let id = ast::PolicyID::from_string(id);
+                let policy_errs = parse_policy(Some(id.clone()), text).unwrap_err();
+                assert_eq!(policy_errs.policy_id(), Some(&id));
+                let template_errs = parse_policy_or_template(Some(id.clone()), text).unwrap_err();
+                assert_eq!(template_errs.policy_id(), Some(&id));
+            }
+            let errs = parse_policy(None, text).unwrap_err();
+            assert_eq!(
+                errs.policy_id(),
+                Some(&ast::PolicyID::from_string("policy0"))
+            );
+            assert_eq!(parse_policyset(text).unwrap_err().policy_id(), None);
+            // The policy id does not affect equality
+            assert_eq!(
+                parse_policy(Some(ast::PolicyID::from_string("a")), text).unwrap_err(),
+                parse_policyset(text).unwrap_err()
+            );
+        }
+        // Errors found after the policy is converted to an AST
+        let id = ast::PolicyID::from_string("a");
+        let errs = parse_template(Some(id.clone()), "permit(principal, action, resource);");
+        assert_eq!(errs.unwrap_err().policy_id(), Some(&id));
+        let errs = parse_policy(
+            Some(id.clone()),
+            "permit(principal == ?principal, action, resource);",
+        );
+        assert_eq!(errs.unwrap_err().policy_id(), Some(&id));
+    }
+
+    #[test]
+    fn check_parse_reports_cst_errors_of_recovering_parse() {
+        let corpus = [
+            "permit(principal, action, resource);",
+            "",
+            "permit(principal, action, resource) when { 1 + };",
+            "permit(principal, action, resource) when { 1 + }; forbid(principal, action);",
+            "permit(principal, action, resource) when { principal == }; permit(principal, action, resource) unless { ! };",
+            "permit(principal, action, resource) when { true }",
+            "permit(principal, action, resource) when { \"unterminated };",
+            // Errors converting the CST to an AST are not syntax errors
+            "permit(principal == 1, action, resource) when { foo(1) };",
+            "permit(principal, action, resource) when { true }; @id(\"a\") permit(principal, action, resource) when { ( };",
+        ];
+        for text in corpus {
+            let offsets_and_messages = |errs: Vec<ParseError>| {
+                errs.into_iter()
+                    .map(|e| (error_offset(&e), e.to_string()))
+                    .sorted()
+                    .collect_vec()
+            };
+            let (_, failed) = parse_policyset_recovering(text);
+            let cst_errs = failed
+                .into_iter()
+                .flat_map(|(_, errs)| errs.into_iter())
+                .filter(|e| matches!(e, ParseError::ToCST(_)))
+                .collect_vec();
+            let checked = check_parse(text);
+            assert!(checked
+                .windows(2)
+                .all(|pair| error_offset(&pair[0]) <= error_offset(&pair[1])));
+            assert_eq!(
+                offsets_and_messages(checked),
+                offsets_and_messages(cst_errs),
+                "for {text:?}"
+            );
+        }
+        assert!(check_parse("permit(principal, action, resource);").is_empty());
+        assert!(check_parse("permit(principal == 1, action, resource);").is_empty());
+        assert_eq!(
+            check_parse("permit(principal, action, resource) when { 1 + };").len(),
+            1
+        );
+    }
+
     #[test]
     fn test_template_parsing() {
         for template in all_templates() {
@@ -789,7 +1381,7 @@ mod tests {
             permit(principal, action, resource) when { principal.name.like == "3" };
             "#;
         let p = parse_policyset_to_ests_and_pset(src);
-        assert_matches!(p, Err(e) => expect_err(src, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error("this identifier is reserved and cannot be used: like").exactly_one_underline("like").build()));
+        assert_matches!(p, Err(e) => expect_err(src, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error_starts_with("`like` is a reserved word and cannot be used as an attribute name").help("quote it to use it as an attribute name").exactly_one_underline("like").build()));
     }
 
     #[test]
@@ -1241,29 +1833,58 @@ mod tests {
         test_valid("abc\tde\\fg");
         test_valid("aaa\u{1F408}bcd👍👍👍");
         // test string with invalid escapes
-        let test_invalid = |s: &str, bad_escapes: Vec<&str>| {
+        let test_invalid = |s: &str, bad_escapes: Vec<(&str, &str)>| {
             let src: &str = &format!("\"{s}\"");
             assert_matches!(parse_literal(src), Err(LiteralParseError::Parse(e)) => {
                 expect_n_errors(src, &e, bad_escapes.len());
-                bad_escapes.iter().for_each(|esc|
+                bad_escapes.iter().for_each(|(esc, help)|
                     expect_some_error_matches(
                         src,
                         &e,
                         &ExpectedErrorMessageBuilder::error(&format!("the input `{esc}` is not a valid escape"))
-                            .exactly_one_underline(src)
+                            .help(help)
+                            .exactly_one_underline(esc)
                             .build()
                     )
                 );
             })
         };
+        let invalid_escape_help = r#"the valid escapes are `\n`, `\r`, `\t`, `\\`, `\0`, `\'`, `\"`, `\x` with two hexadecimal digits, `\u{...}` with one to six hexadecimal digits, and `\*` in the pattern of a `like` expression"#;
         // invalid escape `\a`
-        test_invalid("\\a", vec!["\\a"]);
+        test_invalid("\\a", vec![("\\a", invalid_escape_help)]);
         // invalid escape `\b`
-        test_invalid("\\b", vec!["\\b"]);
+        test_invalid("\\b", vec![("\\b", invalid_escape_help)]);
         // invalid escape `\p`
-        test_invalid("\\\\aa\\p", vec!["\\p"]);
+        test_invalid("\\\\aa\\p", vec![("\\p", invalid_escape_help)]);
         // invalid escape `\a` and empty unicode escape
-        test_invalid(r"\aaa\u{}", vec!["\\a", "\\u{}"]);
+        test_invalid(
+            r"\aaa\u{}",
+            vec![
+                ("\\a", invalid_escape_help),
+                (
+                    "\\u{}",
+                    r"unicode escapes must have at least one hexadecimal digit, e.g., `\u{41}`",
+                ),
+            ],
+        );
+        // surrogate, out-of-range, and overlong unicode escapes
+        test_invalid(
+            r"a\u{D834}b\u{110000}c\u{0000041}",
+            vec![
+                (
+                    r"\u{D834}",
+                    r"unicode escapes for surrogate code points, `\u{D800}` through `\u{DFFF}`, are not allowed",
+                ),
+                (
+                    r"\u{110000}",
+                    r"unicode escapes must be at most `\u{10FFFF}`",
+                ),
+                (
+                    r"\u{0000041}",
+                    r"unicode escapes must have at most six hexadecimal digits, e.g., `\u{10FFFF}`",
+                ),
+            ],
+        );
     }
 
     #[test]
@@ -1367,4 +1988,66 @@ mod tests {
         )
         "#);
     }
+
+    #[test]
+    fn policyset_with_id_annotations() {
+        let src = r#"
+            @id("billing-admin-access")
+            permit(principal, action, resource);
+            forbid(principal, action, resource);
+            @other("x") @id("policy0")
+            permit(principal == ?principal, action, resource);
+        "#;
+        let pset = parse_policyset_with_id_annotations(src).unwrap();
+        let annotated = pset
+            .get(&ast::PolicyID::from_string("billing-admin-access"))
+            .expect("policy with annotated id");
+        assert_matches!(
+            annotated.annotation(&"id".parse().unwrap()),
+            Some(annotation) => assert_eq!(annotation.as_ref(), "billing-admin-access")
+        );
+        assert!(pset.get(&ast::PolicyID::from_string("policy1")).is_some());
+        assert!(pset
+            .get_template(&ast::PolicyID::from_string("policy0"))
+            .is_some());
+        assert_eq!(pset.policies().count(), 2);
+    }
+
+    #[test]
+    fn policyset_with_duplicate_id_annotations() {
+        let src = r#"
+            @id("a") permit(principal, action, resource);
+            @id("b") permit(principal, action, resource);
+            @id("a") forbid(principal, action, resource);
+        "#;
+        let errs = parse_policyset_with_id_annotations(src).unwrap_err();
+        expect_exactly_one_error(
+            src,
+            &errs,
+            &ExpectedErrorMessageBuilder::error(
+                "policy id `a` given by this `@id` annotation is already used by another policy",
+            )
+            .help("each `@id` annotation in a policy set must have a different value")
+            .with_underlines_or_labels([
+                (r#"@id("a")"#, None),
+                (r#"@id("a")"#, Some("first used here")),
+            ])
+            .build(),
+        );
+
+        // An `@id` annotation can also clash with a generated id
+        let src = r#"
+            permit(principal, action, resource);
+            @id("policy0") forbid(principal, action, resource);
+        "#;
+        let errs = parse_policyset_with_id_annotations(src).unwrap_err();
+        expect_n_errors(src, &errs, 1);
+        assert_matches!(
+            errs.iter().next(),
+            Some(ParseError::ToAST(e)) => assert_eq!(
+                e.kind(),
+                &ToASTErrorKind::DuplicatePolicyId(ast::PolicyID::from_string("policy0"))
+            )
+        );
+    }
 }
@@ -0,0 +1,272 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Recovering the comments around and inside a parsed policy.
+//!
+//! The parser drops comments, but every parsed policy keeps its source
+//! location, which indexes into the full original source. This lets us find
+//! the comments attached to a policy afterwards by scanning the source around
+//! it.
+//!
+//! Since the comments are not attached to nodes of the policy, they are only
+//! placed coarsely: all comments inside a policy are [`CommentPosition::Inner`]
+//! without saying which part of the policy they belong to, and comments at the
+//! start of the source, such as a license header, are
+//! [`CommentPosition::Leading`] comments of the first policy.
+
+use miette::SourceSpan;
+use smol_str::SmolStr;
+
+use super::Loc;
+
+/// Where a comment is relative to the policy it is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentPosition {
+    /// On the lines directly before the policy (including its annotations),
+    /// with nothing but whitespace and other comments in between. This
+    /// includes comments at the start of the source for the first policy,
+    /// even if they are separated from it by blank lines.
+    Leading,
+    /// Inside the policy, e.g., inside the scope or between `when` and its
+    /// block. Which part of the policy the comment is in is not recorded.
+    Inner,
+    /// After the policy on the line of its closing `;`, or anywhere after the
+    /// last policy in the source
+    Trailing,
+}
+
+/// A `//` comment in the source of a policy
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Comment {
+    position: CommentPosition,
+    text: SmolStr,
+    span: SourceSpan,
+}
+
+impl Comment {
+    /// Where the comment is relative to its policy
+    pub fn position(&self) -> CommentPosition {
+        self.position
+    }
+
+    /// The text of the comment, including the leading `//` but without the
+    /// line break or trailing whitespace
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The source span of the comment
+    pub fn span(&self) -> SourceSpan {
+        self.span
+    }
+}
+
+/// Get the comments attached to the policy with source location `loc`, in
+/// source order.
+#[expect(
+    clippy::string_slice,
+    reason = "`start` and `end` were checked above, and the other indices are found by searching for ASCII characters"
+)]
+pub fn policy_comments(loc: &Loc) -> Vec<Comment> {
+    let src: &str = &loc.src;
+    let (start, end) = (loc.start(), loc.end());
+    if src.get(start..end).is_none() {
+        return Vec::new();
+    }
+
+    let mut comments = Vec::new();
+    find_comments(
+        src,
+        leading_start(src, start),
+        start,
+        CommentPosition::Leading,
+        &mut comments,
+    );
+    find_comments(src, start, end, CommentPosition::Inner, &mut comments);
+    let rest = &src[end..];
+    let trailing_end = if is_trivia(rest) {
+        src.len()
+    } else {
+        end + rest.find(['\n', '\r']).unwrap_or(rest.len())
+    };
+    // Only a comment directly after the `;` belongs to this policy; one after
+    // another policy on the same line belongs to that policy
+    if src[end..trailing_end].trim_start().starts_with("//") {
+        find_comments(
+            src,
+            end,
+            trailing_end,
+            CommentPosition::Trailing,
+            &mut comments,
+        );
+    }
+    comments
+}
+
+/// Get the start of the lines before `start` which contain only whitespace and
+/// comments
+#[expect(
+    clippy::string_slice,
+    reason = "`start` is a char boundary, and the other indices are next to ASCII line breaks"
+)]
+fn leading_start(src: &str, start: usize) -> usize {
+    let mut leading_start = start;
+    let mut line_end = start;
+    loop {
+        let line_start = src[..line_end].rfind('\n').map_or(0, |i| i + 1);
+        if !is_trivia(&src[line_start..line_end]) {
+            return leading_start;
+        }
+        leading_start = line_start;
+        if line_start == 0 {
+            return leading_start;
+        }
+        line_end = line_start - 1;
+    }
+}
+
+/// Does `text` contain only whitespace and comments?
+fn is_trivia(text: &str) -> bool {
+    text.lines().all(|line| {
+        let line = line.trim();
+        line.is_empty() || line.starts_with("//")
+    })
+}
+
+/// Push every comment in `src[start..end]` to `comments`, skipping over
+/// string literals
+#[expect(
+    clippy::string_slice,
+    reason = "`start` and `end` are char boundaries, and the other indices are from `char_indices()` or next to ASCII line breaks"
+)]
+fn find_comments(
+    src: &str,
+    start: usize,
+    end: usize,
+    position: CommentPosition,
+    comments: &mut Vec<Comment>,
+) {
+    let text = &src[start..end];
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => (),
+                    }
+                }
+            }
+            '/' if chars.peek().is_some_and(|(_, c)| *c == '/') => {
+                let len = text[i..].find(['\n', '\r']).unwrap_or(text.len() - i);
+                let comment = text[i..i + len].trim_end();
+                comments.push(Comment {
+                    position,
+                    text: comment.into(),
+                    span: SourceSpan::from((start + i, comment.len())),
+                });
+                while chars.next_if(|(j, _)| *j < i + len).is_some() {}
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{policy_comments, CommentPosition};
+    use crate::parser::parse_policyset;
+
+    fn comments(src: &str) -> Vec<Vec<(CommentPosition, String)>> {
+        let policies = parse_policyset(src).unwrap();
+        let mut policies = policies.static_policies().collect::<Vec<_>>();
+        policies.sort_by_key(|p| p.loc().unwrap().start());
+        policies
+            .into_iter()
+            .map(|p| {
+                policy_comments(p.loc().unwrap())
+                    .into_iter()
+                    .map(|c| (c.position(), c.text().to_string()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leading_inner_and_trailing() {
+        use CommentPosition::*;
+        let src = r#"
+// header
+
+// why this exists
+@id("first")
+permit(
+    principal, // any principal
+    action,
+    resource
+) when // for now
+{ context.path like "//*" }; // trailing
+// before the second
+forbid(principal, action, resource); permit(principal, action, resource); // last
+// end of file
+"#;
+        assert_eq!(
+            comments(src),
+            vec![
+                vec![
+                    (Leading, "// header".to_string()),
+                    (Leading, "// why this exists".to_string()),
+                    (Inner, "// any principal".to_string()),
+                    (Inner, "// for now".to_string()),
+                    (Trailing, "// trailing".to_string()),
+                ],
+                vec![(Leading, "// before the second".to_string())],
+                vec![
+                    (Trailing, "// last".to_string()),
+                    (Trailing, "// end of file".to_string()),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    #[expect(
+        clippy::indexing_slicing,
+        clippy::string_slice,
+        reason = "unit test code"
+    )]
+    fn spans() {
+        let src = "permit(principal, action, resource); // done\r\n";
+        let policies = parse_policyset(src).unwrap();
+        let policy = policies.static_policies().next().unwrap();
+        let comments = policy_comments(policy.loc().unwrap());
+        assert_eq!(comments.len(), 1);
+        let span = comments[0].span();
+        assert_eq!(&src[span.offset()..span.offset() + span.len()], "// done");
+    }
+
+    #[test]
+    fn no_comments() {
+        assert_eq!(
+            comments(r#"permit(principal, action, resource) when { "a//b" == "\"//" };"#),
+            vec![vec![]]
+        );
+    }
+}
@@ -379,8 +379,9 @@ pub enum Literal {
     True,
     /// false
     False,
-    /// some integer
-    Num(u64),
+    /// some non-negative integer, as written in the source, which may be too
+    /// large to be a Cedar integer
+    Num(SmolStr),
     /// some String
     Str(Node<Str>),
 }
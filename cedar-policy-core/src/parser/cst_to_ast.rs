@@ -35,7 +35,7 @@
 
 use super::err::{parse_errors, ParseError, ParseErrors, ToASTError, ToASTErrorKind};
 use super::node::Node;
-use super::unescape::{to_pattern, to_unescaped_string};
+use super::unescape::{to_pattern, to_unescaped_string, UnescapeError};
 use super::util::{flatten_tuple_2, flatten_tuple_3, flatten_tuple_4};
 use super::{cst, Loc};
 #[cfg(feature = "tolerant-ast")]
@@ -50,8 +50,8 @@ use itertools::{Either, Itertools};
 use nonempty::nonempty;
 use nonempty::NonEmpty;
 use smol_str::{format_smolstr, SmolStr, ToSmolStr};
-use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::mem;
 use std::sync::Arc;
 
@@ -63,6 +63,13 @@ use to_ref_or_refs::OneOrMultipleRefs;
 
 const INVALID_SNIPPET: &str = "<invalid>";
 
+/// Converting an expression takes a lot of stack for each level of nesting, so
+/// when less than this much stack is left, we continue on a newly allocated
+/// segment of `STACK_SEGMENT_SIZE`. The nesting depth of the CST is limited by
+/// the `ParserOptions`, so this is only needed for policies within the limit.
+const STACK_RED_ZONE: usize = 1024 * 256;
+const STACK_SEGMENT_SIZE: usize = 1024 * 1024 * 2;
+
 /// Type alias for convenience
 type Result<T> = std::result::Result<T, ParseErrors>;
 
@@ -82,14 +89,70 @@ impl Node<Option<cst::Policies>> {
         }))
     }
 
+    /// Like `with_generated_policyids()`, but a policy with an `@id("...")`
+    /// annotation gets the value of the annotation as its `PolicyID`. Policies
+    /// without one keep their generated id. Errors if two policies have `@id`
+    /// annotations with the same value.
+    pub fn with_annotated_policyids(
+        &self,
+    ) -> Result<impl Iterator<Item = (ast::PolicyID, &Node<Option<cst::Policy>>)>> {
+        let mut annotation_locs: HashMap<ast::PolicyID, Option<Loc>> = HashMap::new();
+        let mut all_errs: Vec<ParseErrors> = vec![];
+        let mut policies = vec![];
+        for (generated_id, policy) in self.with_generated_policyids()? {
+            let policy_id = match policy.id_annotation() {
+                Some((id, loc)) => {
+                    match annotation_locs.entry(id.clone()) {
+                        Entry::Occupied(first) => all_errs.push(
+                            ToASTError::new(
+                                ToASTErrorKind::DuplicateIdAnnotation(
+                                    parse_errors::DuplicateIdAnnotation {
+                                        id: id.clone(),
+                                        first_loc: first.get().clone(),
+                                    },
+                                ),
+                                loc,
+                            )
+                            .into(),
+                        ),
+                        Entry::Vacant(entry) => {
+                            entry.insert(loc);
+                        }
+                    }
+                    id
+                }
+                None => generated_id,
+            };
+            policies.push((policy_id, policy));
+        }
+        match ParseErrors::flatten(all_errs) {
+            Some(errs) => Err(errs),
+            None => Ok(policies.into_iter()),
+        }
+    }
+
     /// convert `cst::Policies` to `ast::PolicySet`
     pub fn to_policyset(&self) -> Result<ast::PolicySet> {
-        let mut pset = ast::PolicySet::new();
-        let mut all_errs: Vec<ParseErrors> = vec![];
         // Caution: `parser::parse_policyset_and_also_return_policy_text()`
         // depends on this function returning a policy set with `PolicyID`s as
         // generated by `with_generated_policyids()` to maintain an invariant.
-        for (policy_id, policy) in self.with_generated_policyids()? {
+        self.to_policyset_with_ids(self.with_generated_policyids()?)
+    }
+
+    /// convert `cst::Policies` to `ast::PolicySet`, using the `@id`
+    /// annotations of policies as their `PolicyID`s as in
+    /// `with_annotated_policyids()`
+    pub fn to_policyset_with_id_annotations(&self) -> Result<ast::PolicySet> {
+        self.to_policyset_with_ids(self.with_annotated_policyids()?)
+    }
+
+    fn to_policyset_with_ids<'a>(
+        &self,
+        policies: impl IntoIterator<Item = (ast::PolicyID, &'a Node<Option<cst::Policy>>)>,
+    ) -> Result<ast::PolicySet> {
+        let mut pset = ast::PolicySet::new();
+        let mut all_errs: Vec<ParseErrors> = vec![];
+        for (policy_id, policy) in policies {
             // policy may have convert error
             match policy.to_policy_or_template(policy_id) {
                 Ok(Either::Right(template)) => {
@@ -173,6 +236,24 @@ impl Node<Option<cst::Policies>> {
 }
 
 impl Node<Option<cst::Policy>> {
+    /// Get the value of the `@id` annotation of this policy, if it has one,
+    /// along with the location of the annotation. Errors in the annotations
+    /// are ignored here, since they are reported when converting the policy.
+    fn id_annotation(&self) -> Option<(ast::PolicyID, Option<Loc>)> {
+        let policy = match self.as_inner()? {
+            cst::Policy::Policy(policy_impl) => policy_impl,
+            #[cfg(feature = "tolerant-ast")]
+            cst::Policy::PolicyError => return None,
+        };
+        policy.annotations.iter().find_map(|annotation| {
+            let (key, value) = annotation
+                .to_kv_pair(|value, loc| value.map(|value| (value, loc.cloned())))
+                .ok()?;
+            let (value, loc) = value.filter(|_| key.as_ref() == "id")?;
+            Some((ast::PolicyID::from_smolstr(value), loc))
+        })
+    }
+
     /// Convert `cst::Policy` to `ast::Template`. Works for static policies as
     /// well, which will become templates with 0 slots
     pub fn to_template(&self, id: ast::PolicyID) -> Result<ast::Template> {
@@ -413,10 +494,49 @@ impl Node<Option<cst::Policy>> {
 }
 
 impl cst::PolicyImpl {
+    /// Error if the scope contains the variables `principal`, `action`, and
+    /// `resource`, but not in that order, so that we can say so rather than
+    /// report each variable which is out of place
+    fn check_scope_order(&self) -> Result<()> {
+        let vars = self
+            .variables
+            .iter()
+            .map(|var| match var.as_inner()?.variable.as_inner()? {
+                cst::Ident::Principal => Some(ast::Var::Principal),
+                cst::Ident::Action => Some(ast::Var::Action),
+                cst::Ident::Resource => Some(ast::Var::Resource),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        match vars.as_deref() {
+            Some(&[first, second, third])
+                if first != second
+                    && second != third
+                    && first != third
+                    && [first, second, third]
+                        != [ast::Var::Principal, ast::Var::Action, ast::Var::Resource] =>
+            {
+                let loc = match (self.variables.first(), self.variables.last()) {
+                    (Some(first), Some(last)) => first.loc.as_ref().map(|loc| {
+                        loc.span(loc.start()..last.loc.as_ref().map_or_else(|| loc.end(), Loc::end))
+                    }),
+                    _ => None,
+                };
+                Err(ToASTError::new(
+                    ToASTErrorKind::ScopeVariablesOutOfOrder(first, second, third),
+                    loc,
+                )
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Get the scope constraints from the `cst::Policy`
     pub fn extract_scope(
         &self,
     ) -> Result<(PrincipalConstraint, ActionConstraint, ResourceConstraint)> {
+        self.check_scope_order()?;
         // Tracks where the last variable in the scope ended. We'll point to
         // this position to indicate where to fill in vars if we're missing one.
         let mut end_of_last_var = self.effect.loc.as_ref().map(|loc| loc.end());
@@ -498,6 +618,7 @@ impl cst::PolicyImpl {
     pub fn extract_scope_tolerant_ast(
         &self,
     ) -> Result<(PrincipalConstraint, ActionConstraint, ResourceConstraint)> {
+        self.check_scope_order()?;
         // Tracks where the last variable in the scope ended. We'll point to
         // this position to indicate where to fill in vars if we're missing one.
         let mut end_of_last_var = self.effect.loc.as_ref().map(|loc| loc.end());
@@ -627,11 +748,8 @@ impl Node<Option<cst::Annotation>> {
             .as_ref()
             .map(|a| {
                 a.as_valid_string().and_then(|s| {
-                    to_unescaped_string(s).map_err(|unescape_errs| {
-                        ParseErrors::new_from_nonempty(
-                            unescape_errs.map(|e| self.to_ast_err(e).into()),
-                        )
-                    })
+                    to_unescaped_string(s)
+                        .map_err(|unescape_errs| unescape_errors(unescape_errs, a.loc.as_ref()))
                 })
             })
             .transpose();
@@ -1086,6 +1204,16 @@ impl Node<Option<cst::Str>> {
     }
 }
 
+/// Convert the errors from unescaping the string literal at `lit_loc` to
+/// `ParseErrors`, each located at its invalid escape rather than the whole
+/// literal where possible
+fn unescape_errors(errs: NonEmpty<UnescapeError>, lit_loc: Option<&Loc>) -> ParseErrors {
+    ParseErrors::new_from_nonempty(errs.map(|e| {
+        let loc = lit_loc.map(|lit_loc| e.escape_loc(lit_loc).unwrap_or_else(|| lit_loc.clone()));
+        ToASTError::new(ToASTErrorKind::Unescape(e), loc).into()
+    }))
+}
+
 #[cfg(feature = "tolerant-ast")]
 fn build_ast_error_node_if_possible<Build: ExprBuilderInfallibleBuild>(
     error: ParseErrors,
@@ -1166,14 +1294,10 @@ where
                 .into(),
                 loc.as_ref(),
             ),
-            Self::StrLit { lit, loc } => {
-                match to_unescaped_string(lit) {
-                    Ok(s) => Ok(Build::new().with_maybe_source_loc(loc.as_ref()).val(s)),
-                    Err(escape_errs) => Err(ParseErrors::new_from_nonempty(escape_errs.map(|e| {
-                        ToASTError::new(ToASTErrorKind::Unescape(e), loc.clone()).into()
-                    }))),
-                }
-            }
+            Self::StrLit { lit, loc } => match to_unescaped_string(lit) {
+                Ok(s) => Ok(Build::new().with_maybe_source_loc(loc.as_ref()).val(s)),
+                Err(escape_errs) => Err(unescape_errors(escape_errs, loc.as_ref())),
+            },
             Self::BoolLit { val, loc } => {
                 Ok(Build::new().with_maybe_source_loc(loc.as_ref()).val(val))
             }
@@ -1185,12 +1309,8 @@ where
         match self {
             Self::Var { var, .. } => Ok(construct_string_from_var(var)),
             Self::Name { name, loc } => name.into_valid_attr(loc),
-            Self::StrLit { lit, loc } => to_unescaped_string(lit).map_err(|escape_errs| {
-                ParseErrors::new_from_nonempty(
-                    escape_errs
-                        .map(|e| ToASTError::new(ToASTErrorKind::Unescape(e), loc.clone()).into()),
-                )
-            }),
+            Self::StrLit { lit, loc } => to_unescaped_string(lit)
+                .map_err(|escape_errs| unescape_errors(escape_errs, loc.as_ref())),
             Self::Expr { expr, loc } => Err(ToASTError::new(
                 ToASTErrorKind::InvalidAttribute(expr.to_string().into()),
                 loc,
@@ -1210,11 +1330,9 @@ where
 
     pub(crate) fn into_pattern(self) -> Result<Vec<PatternElem>> {
         match &self {
-            Self::StrLit { lit, .. } => to_pattern(lit).map_err(|escape_errs| {
-                ParseErrors::new_from_nonempty(
-                    escape_errs.map(|e| self.to_ast_err(ToASTErrorKind::Unescape(e)).into()),
-                )
-            }),
+            Self::StrLit { lit, loc } => {
+                to_pattern(lit).map_err(|escape_errs| unescape_errors(escape_errs, loc.as_ref()))
+            }
             Self::Var { var, .. } => Err(self
                 .to_ast_err(ToASTErrorKind::InvalidPattern(var.to_string()))
                 .into()),
@@ -1232,11 +1350,8 @@ where
     /// to string literal
     fn into_string_literal(self) -> Result<SmolStr> {
         match &self {
-            Self::StrLit { lit, .. } => to_unescaped_string(lit).map_err(|escape_errs| {
-                ParseErrors::new_from_nonempty(
-                    escape_errs.map(|e| self.to_ast_err(ToASTErrorKind::Unescape(e)).into()),
-                )
-            }),
+            Self::StrLit { lit, loc } => to_unescaped_string(lit)
+                .map_err(|escape_errs| unescape_errors(escape_errs, loc.as_ref())),
             Self::Var { var, .. } => Err(self
                 .to_ast_err(ToASTErrorKind::InvalidString(var.to_string()))
                 .into()),
@@ -1274,6 +1389,14 @@ impl Node<Option<cst::Expr>> {
     }
     pub(crate) fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+    ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.to_expr_or_special_on_stack::<Build>()
+        })
+    }
+
+    fn to_expr_or_special_on_stack<Build: ExprBuilderInfallibleBuild>(
+        &self,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let expr_opt = self.try_as_inner()?;
 
@@ -1398,7 +1521,10 @@ impl Node<Option<cst::Relation>> {
             }
             cst::Relation::Has { target, field } => {
                 let maybe_target = target.to_expr::<Build>();
-                let maybe_fields = Ok(match field.to_has_rhs::<Build>()? {
+                let fields = field
+                    .to_has_rhs::<Build>()
+                    .map_err(ParseErrors::in_attribute_name)?;
+                let maybe_fields = Ok(match fields {
                     Either::Left(s) => nonempty![s],
                     Either::Right(ids) => ids.map(|id| id.into_smolstr()),
                 });
@@ -1529,13 +1655,11 @@ impl Node<Option<cst::Add>> {
                 cst::Primary::Literal(_) | cst::Primary::Name(_) => {
                     let item = item.to_expr_or_special::<Build>()?;
                     match (item, access.as_slice()) {
-                        (ExprOrSpecial::StrLit { lit, loc }, []) => Ok(Either::Left(
-                            to_unescaped_string(lit).map_err(|escape_errs| {
-                                ParseErrors::new_from_nonempty(escape_errs.map(|e| {
-                                    ToASTError::new(ToASTErrorKind::Unescape(e), loc.clone()).into()
-                                }))
-                            })?,
-                        )),
+                        (ExprOrSpecial::StrLit { lit, loc }, []) => {
+                            Ok(Either::Left(to_unescaped_string(lit).map_err(
+                                |escape_errs| unescape_errors(escape_errs, loc.as_ref()),
+                            )?))
+                        }
                         (ExprOrSpecial::Var { var, .. }, rest) => {
                             #[expect(
                                 clippy::unwrap_used,
@@ -1671,22 +1795,24 @@ impl Node<Option<cst::Unary>> {
                 // Given a successful match, the number of negation operations
                 // decreases by one.
                 let (last, rc) = if let Some(cst::Literal::Num(n)) = unary.item.to_lit() {
-                    match n.cmp(&(i64::MAX as u64 + 1)) {
-                        Ordering::Equal => (
-                            Ok(Build::new()
-                                .with_maybe_source_loc(unary.item.loc.as_ref())
-                                .val(i64::MIN)),
-                            c - 1,
-                        ),
-                        Ordering::Less => (
+                    // `i64::MIN` is the only integer whose magnitude does not
+                    // fit in an `i64`, so negate in `i128` before narrowing
+                    match n
+                        .parse::<u64>()
+                        .ok()
+                        .and_then(|n| i64::try_from(-i128::from(n)).ok())
+                    {
+                        Some(i) => (
                             Ok(Build::new()
                                 .with_maybe_source_loc(unary.item.loc.as_ref())
-                                .val(-(*n as i64))),
+                                .val(i)),
                             c - 1,
                         ),
-                        Ordering::Greater => (
+                        None => (
                             Err(self
-                                .to_ast_err(ToASTErrorKind::IntegerLiteralTooLarge(*n))
+                                .to_ast_err(ToASTErrorKind::IntegerLiteralOutOfRange(
+                                    format!("-{n}").into(),
+                                ))
                                 .into()),
                             0,
                         ),
@@ -1807,8 +1933,20 @@ impl Node<Option<cst::Member>> {
         let mem = self.try_as_inner()?;
 
         let maybe_prim = mem.item.to_expr_or_special::<Build>();
+        let next_accesses = mem.access.iter().skip(1).map(Some).chain([None]);
         let maybe_accessors =
-            ParseErrors::transpose(mem.access.iter().map(|a| a.to_access::<Build>()));
+            ParseErrors::transpose(mem.access.iter().zip(next_accesses).map(|(access, next)| {
+                let access = access.to_access::<Build>();
+                // A field followed by a call is a method name, not an attribute
+                if matches!(
+                    next.and_then(|next| next.as_inner()),
+                    Some(cst::MemAccess::Call(_))
+                ) {
+                    access
+                } else {
+                    access.map_err(ParseErrors::in_attribute_name)
+                }
+            }));
 
         // Return errors in case parsing failed for any element
         let (prim, mut accessors) = flatten_tuple_2(maybe_prim, maybe_accessors)?;
@@ -2163,12 +2301,8 @@ impl Node<Option<cst::Ref>> {
             cst::Ref::Uid { path, eid } => {
                 let maybe_path = path.to_name().map(ast::EntityType::from);
                 let maybe_eid = eid.as_valid_string().and_then(|s| {
-                    to_unescaped_string(s).map_err(|escape_errs| {
-                        ParseErrors::new_from_nonempty(
-                            escape_errs
-                                .map(|e| self.to_ast_err(ToASTErrorKind::Unescape(e)).into()),
-                        )
-                    })
+                    to_unescaped_string(s)
+                        .map_err(|escape_errs| unescape_errors(escape_errs, eid.loc.as_ref()))
                 });
 
                 let (p, e) = flatten_tuple_2(maybe_path, maybe_eid)?;
@@ -2206,13 +2340,13 @@ impl Node<Option<cst::Literal>> {
                 val: false,
                 loc: self.loc.clone(),
             }),
-            cst::Literal::Num(n) => match Integer::try_from(*n) {
+            cst::Literal::Num(n) => match n.parse::<Integer>() {
                 Ok(i) => Ok(ExprOrSpecial::Expr {
                     expr: Build::new().with_maybe_source_loc(self.loc.as_ref()).val(i),
                     loc: self.loc.clone(),
                 }),
                 Err(_) => Err(self
-                    .to_ast_err(ToASTErrorKind::IntegerLiteralTooLarge(*n))
+                    .to_ast_err(ToASTErrorKind::IntegerLiteralOutOfRange(n.clone()))
                     .into()),
             },
             cst::Literal::Str(s) => {
@@ -2230,7 +2364,12 @@ impl Node<Option<cst::RecInit>> {
     fn to_init<Build: ExprBuilderInfallibleBuild>(&self) -> Result<(SmolStr, Build::Expr)> {
         let lit = self.try_as_inner()?;
 
-        let maybe_attr = lit.0.to_expr_or_special::<Build>()?.into_valid_attr();
+        let maybe_attr = lit
+            .0
+            .to_expr_or_special::<Build>()
+            .map_err(ParseErrors::in_attribute_name)?
+            .into_valid_attr()
+            .map_err(ParseErrors::in_attribute_name);
         let maybe_value = lit.1.to_expr::<Build>();
 
         flatten_tuple_2(maybe_attr, maybe_value)
@@ -2332,6 +2471,8 @@ mod tests {
     use ast::{InternalName, ReservedNameError};
     use cool_asserts::assert_matches;
 
+    const INVALID_ESCAPE_HELP: &str = r#"the valid escapes are `\n`, `\r`, `\t`, `\\`, `\0`, `\'`, `\"`, `\x` with two hexadecimal digits, `\u{...}` with one to six hexadecimal digits, and `\*` in the pattern of a `like` expression"#;
+
     #[track_caller]
     fn assert_parse_expr_succeeds(text: &str) -> Expr {
         text_to_cst::parse_expr(text)
@@ -2548,9 +2689,10 @@ mod tests {
         expect_some_error_matches(
             src,
             &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: false",
+            &ExpectedErrorMessageBuilder::error_starts_with(
+                "`false` is a reserved word and cannot be used as an attribute name",
             )
+            .help("quote it to use it as an attribute name")
             .exactly_one_underline("false")
             .build(),
         );
@@ -2566,18 +2708,20 @@ mod tests {
         expect_some_error_matches(
             src,
             &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: if",
+            &ExpectedErrorMessageBuilder::error_starts_with(
+                "`if` is a reserved word and cannot be used as an attribute name",
             )
+            .help("quote it to use it as an attribute name")
             .exactly_one_underline("if: true")
             .build(),
         );
         expect_some_error_matches(
             src,
             &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: if",
+            &ExpectedErrorMessageBuilder::error_starts_with(
+                "`if` is a reserved word and cannot be used as an attribute name",
             )
+            .help("quote it to use it as an attribute name")
             .exactly_one_underline("if")
             .build(),
         );
@@ -2593,50 +2737,86 @@ mod tests {
         expect_some_error_matches(
             src,
             &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: has",
+            &ExpectedErrorMessageBuilder::error_starts_with(
+                "`has` is a reserved word and cannot be used as an attribute name",
             )
+            .help("quote it to use it as an attribute name")
             .exactly_one_underline("has")
             .build(),
         );
         expect_some_error_matches(
             src,
             &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: like",
+            &ExpectedErrorMessageBuilder::error_starts_with(
+                "`like` is a reserved word and cannot be used as an attribute name",
             )
+            .help("quote it to use it as an attribute name")
             .exactly_one_underline("like")
             .build(),
         );
         expect_some_error_matches(
             src,
             &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: in",
+            &ExpectedErrorMessageBuilder::error_starts_with(
+                "`in` is a reserved word and cannot be used as an attribute name",
             )
+            .help("quote it to use it as an attribute name")
             .exactly_one_underline("in")
             .build(),
         );
         expect_some_error_matches(
             src,
             &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: then",
+            &ExpectedErrorMessageBuilder::error_starts_with(
+                "`then` is a reserved word and cannot be used as an attribute name",
             )
+            .help("quote it to use it as an attribute name")
             .exactly_one_underline("then")
             .build(),
         );
         expect_some_error_matches(
             src,
             &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: else",
+            &ExpectedErrorMessageBuilder::error_starts_with(
+                "`else` is a reserved word and cannot be used as an attribute name",
             )
+            .help("quote it to use it as an attribute name")
             .exactly_one_underline("else")
             .build(),
         );
     }
 
+    #[test]
+    fn reserved_attribute_names() {
+        for (src, word, underline) in [
+            ("principal.has", "has", "has"),
+            ("principal has like", "like", "like"),
+            ("principal has a.in", "in", "in"),
+            ("{ if: 1 }", "if", "if: 1"),
+            ("{ true: 1 }", "true", "true"),
+        ] {
+            let errs = assert_parse_expr_fails(src);
+            expect_err(
+                src,
+                &miette::Report::new(errs),
+                &ExpectedErrorMessageBuilder::error(&format!(
+                    "`{word}` is a reserved word and cannot be used as an attribute name"
+                ))
+                .help(&format!("quote it to use it as an attribute name, e.g., `principal[\"{word}\"]`, `principal has \"{word}\"`, or `{{ \"{word}\": 1 }}`"))
+                .exactly_one_underline(underline)
+                .build(),
+            );
+        }
+        // Quoted reserved words are valid attribute names
+        for src in [
+            r#"principal["has"]"#,
+            r#"principal has "like""#,
+            r#"{ "if": 1, "true": 2 }"#,
+        ] {
+            assert_parse_expr_succeeds(src);
+        }
+    }
+
     #[test]
     fn show_policy1() {
         let src = r#"
@@ -3247,7 +3427,8 @@ mod tests {
             src,
             &errs,
             &ExpectedErrorMessageBuilder::error("the input `\\*` is not a valid escape")
-                .exactly_one_underline(r#""string\*with\*escaped\*stars""#)
+                .help(INVALID_ESCAPE_HELP)
+                .exactly_one_underline(r"\*")
                 .build(),
         );
     }
@@ -3548,46 +3729,114 @@ mod tests {
 
     #[test]
     fn unescape_err_positions() {
-        let assert_invalid_escape = |p_src, underline| {
+        let assert_invalid_escape = |p_src| {
             assert_matches!(parse_policy_or_template(None, p_src), Err(e) => {
-                expect_err(p_src, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error("the input `\\q` is not a valid escape").exactly_one_underline(underline).build());
+                expect_err(p_src, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error("the input `\\q` is not a valid escape").help(INVALID_ESCAPE_HELP).exactly_one_underline(r"\q").build());
             });
         };
-        assert_invalid_escape(
-            r#"@foo("\q")permit(principal, action, resource);"#,
-            r#"@foo("\q")"#,
-        );
-        assert_invalid_escape(
-            r#"permit(principal, action, resource) when { "\q" };"#,
-            r#""\q""#,
+        assert_invalid_escape(r#"@foo("\q")permit(principal, action, resource);"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { "\q" };"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { "ab\qcd" };"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { "\q".contains(0) };"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { "\q".bar };"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { "\q"["a"] };"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { "" like "\q" };"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { {}["\q"] };"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { {"\q": 0} };"#);
+        assert_invalid_escape(r#"permit(principal, action, resource) when { User::"\q" };"#);
+        assert_invalid_escape(r#"permit(principal == User::"a\q", action, resource);"#);
+        assert_invalid_escape(r#"permit(principal, action in [Action::"\q"], resource);"#);
+
+        // Each invalid escape in a literal is reported at its own position
+        let src = r#"permit(principal, action, resource) when { "\a\u{}" };"#;
+        let errs = assert_parse_policy_fails(src);
+        expect_n_errors(src, &errs, 2);
+        expect_some_error_matches(
+            src,
+            &errs,
+            &ExpectedErrorMessageBuilder::error("the input `\\a` is not a valid escape")
+                .help(INVALID_ESCAPE_HELP)
+                .exactly_one_underline(r"\a")
+                .build(),
         );
-        assert_invalid_escape(
-            r#"permit(principal, action, resource) when { "\q".contains(0) };"#,
-            r#""\q""#,
+        expect_some_error_matches(
+            src,
+            &errs,
+            &ExpectedErrorMessageBuilder::error("the input `\\u{}` is not a valid escape")
+                .help(r"unicode escapes must have at least one hexadecimal digit, e.g., `\u{41}`")
+                .exactly_one_underline(r"\u{}")
+                .build(),
         );
-        assert_invalid_escape(
-            r#"permit(principal, action, resource) when { "\q".bar };"#,
-            r#""\q""#,
+    }
+
+    #[test]
+    fn unicode_escapes() {
+        let expr = assert_parse_expr_succeeds(r#"User::"\u{1F408}""#);
+        assert_matches!(expr.expr_kind(), ast::ExprKind::Lit(ast::Literal::EntityUID(euid)) => {
+            assert_eq!(euid.eid().escaped(), "🐈");
+        });
+        let expr = assert_parse_expr_succeeds(r#""\u{0}\u{41}\u{10FFFF}" like "\u{2A}\*""#);
+        assert_matches!(expr.expr_kind(), ast::ExprKind::Like { expr, pattern } => {
+            assert_matches!(expr.expr_kind(), ast::ExprKind::Lit(ast::Literal::String(s)) => {
+                assert_eq!(s, "\0A\u{10FFFF}");
+            });
+            // like a bare `*`, `\u{2A}` is a wildcard; only `\*` matches a literal `*`
+            assert_eq!(pattern.to_string(), r"*\*");
+        });
+
+        // Printing a policy re-escapes control characters rather than
+        // printing them raw, and the result parses to the same policy
+        let policy = assert_parse_policy_succeeds(
+            "permit(principal == User::\"\u{1b}\", action, resource) when { \"a\u{7}b\u{0}\" like \"\u{7f}*\" };",
+        );
+        let printed = policy.to_string();
+        assert!(!printed.contains(|c: char| c.is_control() && c != '\n'));
+        assert_eq!(assert_parse_policy_succeeds(&printed).to_string(), printed);
+
+        let assert_invalid_unicode_escape = |esc: &str, help: &str| {
+            for p_src in [
+                format!(r#"permit(principal, action, resource) when {{ "ab{esc}cd" }};"#),
+                format!(r#"permit(principal, action, resource) when {{ "" like "ab{esc}cd" }};"#),
+                format!(r#"permit(principal, action, resource) when {{ User::"ab{esc}cd" }};"#),
+                format!(r#"permit(principal == User::"ab{esc}cd", action, resource);"#),
+            ] {
+                let errs = assert_parse_policy_fails(&p_src);
+                expect_n_errors(&p_src, &errs, 1);
+                expect_some_error_matches(
+                    &p_src,
+                    &errs,
+                    &ExpectedErrorMessageBuilder::error(&format!(
+                        "the input `{esc}` is not a valid escape"
+                    ))
+                    .help(help)
+                    .exactly_one_underline(esc)
+                    .build(),
+                );
+            }
+        };
+        assert_invalid_unicode_escape(
+            r"\u{D800}",
+            r"unicode escapes for surrogate code points, `\u{D800}` through `\u{DFFF}`, are not allowed",
         );
-        assert_invalid_escape(
-            r#"permit(principal, action, resource) when { "\q"["a"] };"#,
-            r#""\q""#,
+        assert_invalid_unicode_escape(
+            r"\u{DFFF}",
+            r"unicode escapes for surrogate code points, `\u{D800}` through `\u{DFFF}`, are not allowed",
         );
-        assert_invalid_escape(
-            r#"permit(principal, action, resource) when { "" like "\q" };"#,
-            r#""\q""#,
+        assert_invalid_unicode_escape(
+            r"\u{110000}",
+            r"unicode escapes must be at most `\u{10FFFF}`",
         );
-        assert_invalid_escape(
-            r#"permit(principal, action, resource) when { {}["\q"] };"#,
-            r#""\q""#,
+        assert_invalid_unicode_escape(
+            r"\u{0000041}",
+            r"unicode escapes must have at most six hexadecimal digits, e.g., `\u{10FFFF}`",
         );
-        assert_invalid_escape(
-            r#"permit(principal, action, resource) when { {"\q": 0} };"#,
-            r#""\q""#,
+        assert_invalid_unicode_escape(
+            r"\u{}",
+            r"unicode escapes must have at least one hexadecimal digit, e.g., `\u{41}`",
         );
-        assert_invalid_escape(
-            r#"permit(principal, action, resource) when { User::"\q" };"#,
-            r#"User::"\q""#,
+        assert_invalid_unicode_escape(
+            r"\u{4g",
+            r"unicode escapes may only contain hexadecimal digits, e.g., `\u{41}`",
         );
     }
 
@@ -3823,6 +4072,9 @@ mod tests {
         }
     }
 
+    const INTEGER_RANGE_HELP: &str =
+        "integer literals must be between `-9223372036854775808` and `9223372036854775807`";
+
     #[test]
     fn test_neg() {
         for (es, expr) in [
@@ -3835,6 +4087,7 @@ mod tests {
             ("--(--1)", Expr::neg(Expr::neg(Expr::neg(Expr::val(-1))))),
             ("2--1", Expr::sub(Expr::val(2), Expr::val(-1))),
             ("-9223372036854775808", Expr::val(-(9223372036854775808))),
+            ("9223372036854775807", Expr::val(9223372036854775807)),
             // Evaluating this expression leads to overflows but the parser
             // won't reject it.
             (
@@ -3857,9 +4110,9 @@ mod tests {
             (
                 "-9223372036854775809",
                 ExpectedErrorMessageBuilder::error(
-                    "integer literal `9223372036854775809` is too large",
+                    "integer literal `-9223372036854775809` is out of range",
                 )
-                .help("maximum allowed integer literal is `9223372036854775807`")
+                .help(INTEGER_RANGE_HELP)
                 .exactly_one_underline("-9223372036854775809")
                 .build(),
             ),
@@ -3870,12 +4123,31 @@ mod tests {
             (
                 "-(9223372036854775808)",
                 ExpectedErrorMessageBuilder::error(
-                    "integer literal `9223372036854775808` is too large",
+                    "integer literal `9223372036854775808` is out of range",
+                )
+                .help(INTEGER_RANGE_HELP)
+                .exactly_one_underline("9223372036854775808")
+                .build(),
+            ),
+            (
+                "9223372036854775808",
+                ExpectedErrorMessageBuilder::error(
+                    "integer literal `9223372036854775808` is out of range",
                 )
-                .help("maximum allowed integer literal is `9223372036854775807`")
+                .help(INTEGER_RANGE_HELP)
                 .exactly_one_underline("9223372036854775808")
                 .build(),
             ),
+            // Literals too large for a `u64` are reported the same way
+            (
+                "-18446744073709551616",
+                ExpectedErrorMessageBuilder::error(
+                    "integer literal `-18446744073709551616` is out of range",
+                )
+                .help(INTEGER_RANGE_HELP)
+                .exactly_one_underline("-18446744073709551616")
+                .build(),
+            ),
         ] {
             let errs = assert_parse_expr_fails(es);
             expect_err(es, &miette::Report::new(errs), &em);
@@ -4775,6 +5047,97 @@ mod tests {
         });
     }
 
+    #[test]
+    fn scope_variables_out_of_order() {
+        let p_src = "permit(action, principal, resource);";
+        assert_matches!(parse_policy_or_template(None, p_src), Err(e) => {
+            expect_err(p_src, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error(
+                "the variables in the policy scope are out of order: found `action`, `principal`, `resource`",
+                ).help(
+                "policy scopes must contain a `principal`, `action`, and `resource` element in that order",
+            ).exactly_one_underline("action, principal, resource").build());
+        });
+        let p_src = r#"permit(resource in Folder::"a", action == Action::"view", principal);"#;
+        assert_matches!(parse_policy_or_template(None, p_src), Err(e) => {
+            expect_err(p_src, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error(
+                "the variables in the policy scope are out of order: found `resource`, `action`, `principal`",
+                ).help(
+                "policy scopes must contain a `principal`, `action`, and `resource` element in that order",
+            ).exactly_one_underline(r#"resource in Folder::"a", action == Action::"view", principal"#).build());
+        });
+    }
+
+    #[test]
+    fn keyword_suggestions() {
+        let suggestion = |src: &str| {
+            let errs = parse_policy_or_template(None, src).unwrap_err();
+            errs.iter().map(ParseError::suggestion).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            suggestion("premit(principal, action, resource);"),
+            vec![Some("permit")]
+        );
+        assert_eq!(
+            suggestion("forbd(principal, action, resource);"),
+            vec![Some("forbid")]
+        );
+        assert_eq!(
+            suggestion("permit(principle, action, resource);"),
+            vec![Some("principal")]
+        );
+        assert_eq!(
+            suggestion("permit(principal, actoin, resource);"),
+            vec![Some("action")]
+        );
+        assert_eq!(
+            suggestion("permit(principal, action, resource) wen { true };"),
+            vec![Some("when")]
+        );
+        assert_eq!(
+            suggestion("permit(principal, action, resource) unles { true };"),
+            vec![Some("unless")]
+        );
+        assert_eq!(
+            suggestion(r#"permit(principal inn Group::"a", action, resource);"#),
+            vec![Some("in")]
+        );
+        assert_eq!(
+            suggestion("permit(principal, action, resource) when { context hass foo };"),
+            vec![Some("has")]
+        );
+        // not close enough to any keyword
+        assert_eq!(
+            suggestion("allow(principal, action, resource);"),
+            vec![None]
+        );
+        assert_eq!(
+            suggestion("permit(principal, action, resource) where { true };"),
+            vec![None]
+        );
+        // `if` is a keyword, not a misspelling of `in`
+        assert_eq!(
+            suggestion("permit(principal if, action, resource);"),
+            vec![None]
+        );
+
+        let p_src = "premit(principal, action, resource);";
+        assert_matches!(parse_policy_or_template(None, p_src), Err(e) => {
+            expect_err(p_src, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error(
+                "invalid policy effect: premit",
+                ).help(
+                "did you mean `permit`? effect must be either `permit` or `forbid`",
+            ).exactly_one_underline("premit").build());
+        });
+        let p_src = r#"permit(principal inn Group::"a", action, resource);"#;
+        assert_matches!(parse_policy_or_template(None, p_src), Err(e) => {
+            expect_err(p_src, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error(
+                "unexpected token `inn`",
+                ).help(
+                "did you mean `in`?",
+            ).exactly_one_underline_with_label("inn", "expected `!=`, `)`, `,`, `:`, `<`, `<=`, `==`, `>`, `>=`, `in`, or `is`").build());
+        });
+    }
+
     #[test]
     fn invalid_scope_operator() {
         let p_src = r#"permit(principal > User::"alice", action, resource);"#;
@@ -5105,9 +5468,7 @@ mod tests {
 
         let expr = "principal has if::foo";
         assert_matches!(parse_expr(expr), Err(e) => {
-            expect_err(expr, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error(
-                "this identifier is reserved and cannot be used: if"
-            ).exactly_one_underline("if").build());
+            expect_err(expr, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error_starts_with("`if` is a reserved word and cannot be used as an attribute name").help("quote it to use it as an attribute name").exactly_one_underline("if").build());
         })
     }
 
@@ -5242,9 +5603,7 @@ mod tests {
             parse_policy(None, policy),
             Err(e) => {
                 expect_n_errors(policy, &e, 1);
-                expect_some_error_matches(policy, &e, &ExpectedErrorMessageBuilder::error(
-                    "this identifier is reserved and cannot be used: if",
-                ).exactly_one_underline(r#"if"#).build());
+                expect_some_error_matches(policy, &e, &ExpectedErrorMessageBuilder::error_starts_with("`if` is a reserved word and cannot be used as an attribute name").help("quote it to use it as an attribute name").exactly_one_underline(r#"if"#).build());
             }
         );
         let policy = r#"permit(principal, action, resource) when {
@@ -5254,9 +5613,7 @@ mod tests {
             parse_policy(None, policy),
             Err(e) => {
                 expect_n_errors(policy, &e, 1);
-                expect_some_error_matches(policy, &e, &ExpectedErrorMessageBuilder::error(
-                    "this identifier is reserved and cannot be used: if",
-                ).exactly_one_underline(r#"if"#).build());
+                expect_some_error_matches(policy, &e, &ExpectedErrorMessageBuilder::error_starts_with("`if` is a reserved word and cannot be used as an attribute name").help("quote it to use it as an attribute name").exactly_one_underline(r#"if"#).build());
             }
         );
         let policy = r#"permit(principal, action, resource) when {
@@ -5266,9 +5623,7 @@ mod tests {
             parse_policy(None, policy),
             Err(e) => {
                 expect_n_errors(policy, &e, 1);
-                expect_some_error_matches(policy, &e, &ExpectedErrorMessageBuilder::error(
-                    "this identifier is reserved and cannot be used: true",
-                ).exactly_one_underline(r#"true"#).build());
+                expect_some_error_matches(policy, &e, &ExpectedErrorMessageBuilder::error_starts_with("`true` is a reserved word and cannot be used as an attribute name").help("quote it to use it as an attribute name").exactly_one_underline(r#"true"#).build());
             }
         );
         let policy = r#"permit(principal, action, resource) when {
@@ -19,8 +19,9 @@ use std::fmt::{self, Display, Write};
 use std::iter;
 use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
-use std::sync::{Arc, LazyLock};
+use std::sync::LazyLock;
 
+use educe::Educe;
 use either::Either;
 use lalrpop_util as lalr;
 use miette::{Diagnostic, LabeledSpan, SourceSpan};
@@ -29,10 +30,11 @@ use smol_str::SmolStr;
 use thiserror::Error;
 
 use crate::ast::{self, ReservedNameError};
+use crate::fuzzy_match::levenshtein_distance;
 use crate::parser::fmt::join_with_conjunction;
 use crate::parser::node::Node;
 use crate::parser::unescape::UnescapeError;
-use crate::parser::Loc;
+use crate::parser::{Loc, ParseLimit};
 
 use super::cst;
 
@@ -58,6 +60,30 @@ pub enum ParseError {
     ToAST(#[from] ToASTError),
 }
 
+impl ParseError {
+    /// Get the keyword that an identifier was likely meant to be, when the
+    /// identifier appears where a keyword is expected and is a near miss for
+    /// one
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            Self::ToCST(err) => err.suggestion(),
+            Self::ToAST(err) => err.kind().suggestion(),
+        }
+    }
+
+    /// Get the limit in [`ParseLimits`](crate::parser::ParseLimits) which
+    /// the input exceeded, if that is what this error is for
+    pub fn exceeded_limit(&self) -> Option<ParseLimit> {
+        match self {
+            Self::ToAST(err) => match err.kind() {
+                ToASTErrorKind::LimitExceeded { limit, .. } => Some(*limit),
+                _ => None,
+            },
+            Self::ToCST(_) => None,
+        }
+    }
+}
+
 /// Errors possible from `Literal::from_str()`
 #[derive(Debug, Clone, PartialEq, Diagnostic, Error, Eq)]
 pub enum LiteralParseError {
@@ -79,9 +105,25 @@ pub struct ToASTError {
 }
 
 // Construct `labels` and `source_code` based on the `loc` in this
-// struct; and everything else forwarded directly to `kind`.
+// struct (and, for a duplicate `@id` annotation, the location of the first
+// annotation); and everything else forwarded directly to `kind`.
 impl Diagnostic for ToASTError {
-    impl_diagnostic_from_source_loc_opt_field!(loc);
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.loc.as_ref().map(|loc| loc as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let loc = self.loc.as_ref()?;
+        let first_loc = match &self.kind {
+            ToASTErrorKind::DuplicateIdAnnotation(err) => err.first_loc.as_ref(),
+            _ => None,
+        };
+        Some(Box::new(
+            iter::once(LabeledSpan::underline(loc.span)).chain(first_loc.map(|first| {
+                LabeledSpan::new_with_span(Some("first used here".into()), first.span)
+            })),
+        ))
+    }
 
     fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.kind.code()
@@ -118,6 +160,19 @@ impl ToASTError {
     pub(crate) fn source_loc(&self) -> Option<&Loc> {
         self.loc.as_ref()
     }
+
+    /// Report this error, found while converting an attribute name, as a
+    /// [`ToASTErrorKind::ReservedAttributeName`] if it is for a reserved
+    /// identifier
+    pub(crate) fn in_attribute_name(self) -> Self {
+        match self.kind {
+            ToASTErrorKind::ReservedIdentifier(ident) => Self {
+                kind: ToASTErrorKind::ReservedAttributeName(ident),
+                loc: self.loc,
+            },
+            _ => self,
+        }
+    }
 }
 
 const POLICY_SCOPE_HELP: &str =
@@ -135,6 +190,10 @@ pub enum ToASTErrorKind {
     /// Returned when we attempt to parse a policy with a conflicting id
     #[error("a policy with id `{0}` already exists in the policy set")]
     DuplicatePolicyId(ast::PolicyID),
+    /// Returned when two policies have `@id` annotations with the same value
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    DuplicateIdAnnotation(#[from] parse_errors::DuplicateIdAnnotation),
     /// Returned when a template is encountered but a static policy is expected
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -164,6 +223,13 @@ pub enum ToASTErrorKind {
     /// Returned when a policy uses a reserved keyword as an identifier.
     #[error("this identifier is reserved and cannot be used: {0}")]
     ReservedIdentifier(cst::Ident),
+    /// Returned when a policy uses a reserved keyword as an unquoted attribute
+    /// name, e.g., `principal.has` or `{ if: 1 }`
+    #[error("`{0}` is a reserved word and cannot be used as an attribute name")]
+    #[diagnostic(help(
+        "quote it to use it as an attribute name, e.g., `principal[\"{0}\"]`, `principal has \"{0}\"`, or `{{ \"{0}\": 1 }}`"
+    ))]
+    ReservedAttributeName(cst::Ident),
     /// Returned when a policy contains an invalid identifier.
     /// This error is not currently returned, but is here for future-proofing;
     /// see [`cst::Ident::Invalid`].
@@ -176,17 +242,28 @@ pub enum ToASTErrorKind {
     InvalidSingleEq,
     /// Returned when a policy uses an effect keyword beyond `permit` or `forbid`
     #[error("invalid policy effect: {0}")]
-    #[diagnostic(help("effect must be either `permit` or `forbid`"))]
+    #[diagnostic(help(
+        "{}effect must be either `permit` or `forbid`",
+        did_you_mean(self.suggestion())
+    ))]
     InvalidEffect(cst::Ident),
     /// Returned when a policy uses a condition keyword beyond `when` or `unless`
     #[error("invalid policy condition: {0}")]
-    #[diagnostic(help("condition must be either `when` or `unless`"))]
+    #[diagnostic(help(
+        "{}condition must be either `when` or `unless`",
+        did_you_mean(self.suggestion())
+    ))]
     InvalidCondition(cst::Ident),
     /// Returned when a policy uses a variable in the scope beyond `principal`,
     /// `action`, or `resource`
     #[error("found an invalid variable in the policy scope: {0}")]
-    #[diagnostic(help("{POLICY_SCOPE_HELP}"))]
+    #[diagnostic(help("{}{POLICY_SCOPE_HELP}", did_you_mean(self.suggestion())))]
     InvalidScopeVariable(cst::Ident),
+    /// Returned when a policy scope contains the `principal`, `action`, and
+    /// `resource` variables, but not in that order
+    #[error("the variables in the policy scope are out of order: found `{0}`, `{1}`, `{2}`")]
+    #[diagnostic(help("{POLICY_SCOPE_HELP}"))]
+    ScopeVariablesOutOfOrder(ast::Var, ast::Var, ast::Var),
     /// Returned when a policy scope clause contains the wrong variable.
     /// (`principal` must be in the first clause, etc...)
     #[error("found the variable `{got}` where the variable `{expected}` must be used")]
@@ -290,10 +367,16 @@ pub enum ToASTErrorKind {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ExpressionConstructionError(#[from] ast::ExpressionConstructionError),
-    /// Returned when a policy contains an integer literal that is out of range
-    #[error("integer literal `{0}` is too large")]
-    #[diagnostic(help("maximum allowed integer literal is `{}`", ast::InputInteger::MAX))]
-    IntegerLiteralTooLarge(u64),
+    /// Returned when a policy contains an integer literal that is out of range.
+    /// The literal is given as written, including a leading `-` if it is
+    /// negated.
+    #[error("integer literal `{0}` is out of range")]
+    #[diagnostic(help(
+        "integer literals must be between `{}` and `{}`",
+        ast::InputInteger::MIN,
+        ast::InputInteger::MAX
+    ))]
+    IntegerLiteralOutOfRange(SmolStr),
     /// Returned when a unary operator is chained more than 4 times in a row
     #[error("too many occurrences of `{0}`")]
     #[diagnostic(help("cannot chain more the 4 applications of a unary operator"))]
@@ -405,6 +488,25 @@ pub enum ToASTErrorKind {
     #[error("when `is` and `in` are used together, `is` must come first")]
     #[diagnostic(help("try `_ is _ in _`"))]
     InvertedIsIn,
+    /// Returned when an expression is nested deeper than the maximum nesting
+    /// depth in the `ParserOptions`
+    #[error("expression is nested more than {0} levels deep")]
+    #[diagnostic(help(
+        "try splitting the expression up, or raise the limit with `ParserOptions::with_max_nesting_depth`"
+    ))]
+    NestingTooDeep(usize),
+    /// Returned when the input exceeds one of the `ParseLimits`
+    #[error("the {limit} exceeds the limit of {max}")]
+    #[diagnostic(help(
+        "try splitting the input up, or raise the limit with `ParseLimits::{}`",
+        limit.setter()
+    ))]
+    LimitExceeded {
+        /// The limit which was exceeded
+        limit: ParseLimit,
+        /// The value of the limit
+        max: usize,
+    },
     /// Represents an attempt to convert a CST Error node
     #[cfg(feature = "tolerant-ast")]
     #[error("Trying to convert CST error node")]
@@ -431,7 +533,53 @@ fn strip_surrounding_doublequotes(s: &str) -> Option<&str> {
     s.strip_prefix('"')?.strip_suffix('"')
 }
 
+/// Keywords which an identifier found where a keyword is expected may be
+/// suggested in place of, and the names of their tokens in the grammar
+const SUGGESTED_KEYWORDS: [(&str, &str); 9] = [
+    ("permit", "PERMIT"),
+    ("forbid", "FORBID"),
+    ("when", "WHEN"),
+    ("unless", "UNLESS"),
+    ("principal", "PRINCIPAL"),
+    ("action", "ACTION"),
+    ("resource", "RESOURCE"),
+    ("in", "IN"),
+    ("has", "HAS"),
+];
+
+/// Get the keyword among `keywords` which `got` is most likely a misspelling
+/// of, if any is close enough. Short keywords only match identifiers which
+/// differ from them by a single character.
+fn closest_keyword<'a>(got: &str, keywords: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    keywords
+        .into_iter()
+        .map(|kw| (levenshtein_distance(got, kw), kw))
+        .filter(|(distance, kw)| *distance <= if kw.len() < 5 { 1 } else { 2 })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, kw)| kw)
+}
+
+/// Prefix for a help message suggesting `suggestion`, if there is one
+fn did_you_mean(suggestion: Option<&str>) -> String {
+    suggestion
+        .map(|kw| format!("did you mean `{kw}`? "))
+        .unwrap_or_default()
+}
+
 impl ToASTErrorKind {
+    /// Get the keyword that an identifier was likely meant to be, when the
+    /// identifier appears where a keyword is expected and is a near miss for
+    /// one, e.g., `premit` for `permit`
+    pub fn suggestion(&self) -> Option<&'static str> {
+        let (got, keywords): (_, &[&'static str]) = match self {
+            Self::InvalidEffect(got) => (got, &["permit", "forbid"]),
+            Self::InvalidCondition(got) => (got, &["when", "unless"]),
+            Self::InvalidScopeVariable(got) => (got, &["principal", "action", "resource"]),
+            _ => return None,
+        };
+        closest_keyword(&got.to_string(), keywords.iter().copied())
+    }
+
     /// Constructor for the [`ToASTErrorKind::WrongNode`] error
     pub fn wrong_node(
         expected: &'static str,
@@ -527,6 +675,29 @@ pub mod parse_errors {
         }
     }
 
+    /// Details about a `DuplicateIdAnnotation` error.
+    #[derive(Debug, Clone, Diagnostic, Error, PartialEq, Eq)]
+    #[error("policy id `{id}` given by this `@id` annotation is already used by another policy")]
+    #[diagnostic(help("each `@id` annotation in a policy set must have a different value"))]
+    pub struct DuplicateIdAnnotation {
+        /// The duplicated id
+        pub(crate) id: ast::PolicyID,
+        /// Location of the first `@id` annotation with this id
+        pub(crate) first_loc: Option<Loc>,
+    }
+
+    impl DuplicateIdAnnotation {
+        /// The duplicated id
+        pub fn id(&self) -> &ast::PolicyID {
+            &self.id
+        }
+
+        /// Location of the first `@id` annotation with this id
+        pub fn first_loc(&self) -> Option<&Loc> {
+            self.first_loc.as_ref()
+        }
+    }
+
     /// Details about a `ExpectedTemplate` error.
     #[derive(Debug, Clone, Diagnostic, Error, PartialEq, Eq)]
     #[error("expected a template, got a static policy")]
@@ -612,10 +783,42 @@ pub mod parse_errors {
 #[derive(Clone, Debug, Error, PartialEq, Eq)]
 pub struct ToCSTError {
     err: OwnedRawParseError,
-    src: Arc<str>,
+    /// Location of the whole source text the error was found in, which may
+    /// also name it
+    src: Loc,
 }
 
 impl ToCSTError {
+    /// Get the keyword that the unexpected token was likely meant to be, when
+    /// the token is an identifier and is a near miss for one of the keywords
+    /// the parser expected, e.g., `inn` for `in`
+    pub fn suggestion(&self) -> Option<&'static str> {
+        let OwnedRawParseError::UnrecognizedToken {
+            token: (_, token, _),
+            expected,
+        } = &self.err
+        else {
+            return None;
+        };
+        let is_identifier = token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        let is_keyword = POLICY_TOKEN_CONFIG
+            .special_identifier_tokens
+            .iter()
+            .chain(&POLICY_TOKEN_CONFIG.first_set_identifier_tokens)
+            .any(|name| name.eq_ignore_ascii_case(token));
+        if !is_identifier || is_keyword {
+            return None;
+        }
+        closest_keyword(
+            token,
+            SUGGESTED_KEYWORDS
+                .into_iter()
+                .filter(|(_, name)| expected.iter().any(|e| e == name))
+                .map(|(kw, _)| kw),
+        )
+    }
+
     /// Extract a primary source span locating the error.
     pub fn primary_source_span(&self) -> Option<SourceSpan> {
         match &self.err {
@@ -634,14 +837,14 @@ impl ToCSTError {
         }
     }
 
-    pub(crate) fn from_raw_parse_err(err: RawParseError<'_>, src: Arc<str>) -> Self {
+    pub(crate) fn from_raw_parse_err(err: RawParseError<'_>, src: Loc) -> Self {
         Self {
             err: err.map_token(|token| token.to_string()),
             src,
         }
     }
 
-    pub(crate) fn from_raw_err_recovery(recovery: RawErrorRecovery<'_>, src: Arc<str>) -> Self {
+    pub(crate) fn from_raw_err_recovery(recovery: RawErrorRecovery<'_>, src: Loc) -> Self {
         Self::from_raw_parse_err(recovery.error, src)
     }
 }
@@ -688,7 +891,7 @@ impl Diagnostic for ToCSTError {
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         match self.err {
             OwnedRawParseError::InvalidToken { location } => {
-                let tok_last_char = self.src.get(location..)?.chars().next()?;
+                let tok_last_char = self.src.src.get(location..)?.chars().next()?;
                 match tok_last_char {
                     '"' => Some(Box::new(
                         "try checking that all strings are closed properly",
@@ -699,6 +902,9 @@ impl Diagnostic for ToCSTError {
                     _ => None,
                 }
             }
+            OwnedRawParseError::UnrecognizedToken { .. } => self
+                .suggestion()
+                .map(|kw| Box::new(format!("did you mean `{kw}`?")) as Box<dyn Display>),
             _ => None,
         }
     }
@@ -831,26 +1037,72 @@ pub fn expected_to_string(expected: &[String], config: &ExpectedTokenConfig) ->
 
 /// Represents one or more [`ParseError`]s encountered when parsing a policy or
 /// template.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct ParseErrors(NonEmpty<ParseError>);
+///
+/// Two `ParseErrors` are equal if they contain the same errors, regardless of
+/// the policy id they were found in, since the same text has the same errors
+/// whichever id it is parsed with.
+#[derive(Clone, Debug, Educe)]
+#[educe(PartialEq, Eq)]
+pub struct ParseErrors {
+    /// The errors
+    errors: NonEmpty<ParseError>,
+    /// The id of the policy or template the errors were found in, if they were
+    /// found while parsing a single policy or template
+    #[educe(PartialEq(ignore))]
+    policy_id: Option<ast::PolicyID>,
+}
 
 impl ParseErrors {
     /// Construct a `ParseErrors` with a single element
     pub(crate) fn singleton(err: impl Into<ParseError>) -> Self {
-        Self(NonEmpty::singleton(err.into()))
+        Self::new_from_nonempty(NonEmpty::singleton(err.into()))
     }
 
     /// Construct a new `ParseErrors` with at least one element
     pub(crate) fn new(first: ParseError, rest: impl IntoIterator<Item = ParseError>) -> Self {
-        Self(NonEmpty {
+        Self::new_from_nonempty(NonEmpty {
             head: first,
             tail: rest.into_iter().collect::<Vec<_>>(),
         })
     }
 
+    /// Report any errors for reserved identifiers in these errors, which were
+    /// found while converting an attribute name, as errors for reserved
+    /// attribute names. See [`ToASTError::in_attribute_name`].
+    pub(crate) fn in_attribute_name(self) -> Self {
+        Self {
+            errors: self.errors.map(|err| match err {
+                ParseError::ToAST(err) => ParseError::ToAST(err.in_attribute_name()),
+                err @ ParseError::ToCST(_) => err,
+            }),
+            ..self
+        }
+    }
+
     /// Construct a new `ParseErrors` from another `NonEmpty` type
     pub(crate) fn new_from_nonempty(errs: NonEmpty<ParseError>) -> Self {
-        Self(errs)
+        Self {
+            errors: errs,
+            policy_id: None,
+        }
+    }
+
+    /// Record that these errors were found while parsing the policy or
+    /// template with id `id`
+    pub(crate) fn with_policy_id(self, id: ast::PolicyID) -> Self {
+        Self {
+            policy_id: Some(id),
+            ..self
+        }
+    }
+
+    /// The id of the policy or template these errors were found in, i.e., the
+    /// id the caller supplied to the parser, or the default id it would have
+    /// been given. This is `None` for errors found while parsing a policy set,
+    /// which may be in several policies, or anything other than a policy or
+    /// template.
+    pub fn policy_id(&self) -> Option<&ast::PolicyID> {
+        self.policy_id.as_ref()
     }
 
     pub(crate) fn from_iter(i: impl IntoIterator<Item = ParseError>) -> Option<Self> {
@@ -973,13 +1225,13 @@ impl Diagnostic for ParseErrors {
 
 impl AsRef<NonEmpty<ParseError>> for ParseErrors {
     fn as_ref(&self) -> &NonEmpty<ParseError> {
-        &self.0
+        &self.errors
     }
 }
 
 impl AsMut<NonEmpty<ParseError>> for ParseErrors {
     fn as_mut(&mut self) -> &mut NonEmpty<ParseError> {
-        &mut self.0
+        &mut self.errors
     }
 }
 
@@ -987,13 +1239,13 @@ impl Deref for ParseErrors {
     type Target = NonEmpty<ParseError>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.errors
     }
 }
 
 impl DerefMut for ParseErrors {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.errors
     }
 }
 
@@ -1005,7 +1257,7 @@ impl<T: Into<ParseError>> From<T> for ParseErrors {
 
 impl<T: Into<ParseError>> Extend<T> for ParseErrors {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        self.0.extend(iter.into_iter().map(Into::into))
+        self.errors.extend(iter.into_iter().map(Into::into))
     }
 }
 
@@ -1014,7 +1266,7 @@ impl IntoIterator for ParseErrors {
     type IntoIter = iter::Chain<iter::Once<Self::Item>, std::vec::IntoIter<Self::Item>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.errors.into_iter()
     }
 }
 
@@ -0,0 +1,266 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Limits on the size of policy text, enforced while it is parsed.
+//!
+//! The size of the input text is checked before it is parsed at all. The
+//! other limits are checked by the actions of the grammar as each part of the
+//! input is parsed, so that the parser stops at the first policy, expression
+//! node, or annotation exceeding a limit, without parsing the rest of the
+//! input.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use super::cst;
+use super::err::{ParseErrors, RawUserError, ToASTError, ToASTErrorKind};
+use super::node::Node;
+use super::Loc;
+
+/// Limits on the size of policy text, checked by the `*_with_limits` parse
+/// functions, e.g., [`super::text_to_cst::parse_policies_with_limits`].
+///
+/// The default limits are unlimited, as for the parse functions which do not
+/// take limits. Services parsing untrusted input can set any of them, and
+/// should always limit the input size, which bounds the memory used for
+/// parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    max_input_bytes: usize,
+    max_policies: usize,
+    max_condition_nodes: usize,
+    max_annotation_bytes: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl ParseLimits {
+    /// No limits at all
+    pub fn unlimited() -> Self {
+        Self {
+            max_input_bytes: usize::MAX,
+            max_policies: usize::MAX,
+            max_condition_nodes: usize::MAX,
+            max_annotation_bytes: usize::MAX,
+        }
+    }
+
+    /// Set the maximum size of the input text, in bytes. This is checked
+    /// before the input is parsed at all.
+    pub fn with_max_input_bytes(mut self, max: usize) -> Self {
+        self.max_input_bytes = max;
+        self
+    }
+
+    /// Get the maximum size of the input text, in bytes. See
+    /// [`ParseLimits::with_max_input_bytes`].
+    pub fn max_input_bytes(&self) -> usize {
+        self.max_input_bytes
+    }
+
+    /// Set the maximum number of policies and templates in a policy set
+    pub fn with_max_policies(mut self, max: usize) -> Self {
+        self.max_policies = max;
+        self
+    }
+
+    /// Get the maximum number of policies and templates in a policy set. See
+    /// [`ParseLimits::with_max_policies`].
+    pub fn max_policies(&self) -> usize {
+        self.max_policies
+    }
+
+    /// Set the maximum number of nodes in the expression of a `when` or
+    /// `unless` condition or of a scope constraint, or in an expression parsed
+    /// on its own. Each literal, variable, entity reference, name, set or
+    /// record literal, operator, attribute access, and record key counts as
+    /// one node, so, e.g., `context.a + 1 > 2` has 6 nodes.
+    pub fn with_max_condition_nodes(mut self, max: usize) -> Self {
+        self.max_condition_nodes = max;
+        self
+    }
+
+    /// Get the maximum number of nodes in a condition. See
+    /// [`ParseLimits::with_max_condition_nodes`].
+    pub fn max_condition_nodes(&self) -> usize {
+        self.max_condition_nodes
+    }
+
+    /// Set the maximum size of the value of an annotation, in bytes, as
+    /// written in the input without the surrounding quotes
+    pub fn with_max_annotation_bytes(mut self, max: usize) -> Self {
+        self.max_annotation_bytes = max;
+        self
+    }
+
+    /// Get the maximum size of the value of an annotation, in bytes. See
+    /// [`ParseLimits::with_max_annotation_bytes`].
+    pub fn max_annotation_bytes(&self) -> usize {
+        self.max_annotation_bytes
+    }
+}
+
+/// A limit in [`ParseLimits`], as reported when the input exceeds it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseLimit {
+    /// [`ParseLimits::with_max_input_bytes`]
+    InputBytes,
+    /// [`ParseLimits::with_max_policies`]
+    Policies,
+    /// [`ParseLimits::with_max_condition_nodes`]
+    ConditionNodes,
+    /// [`ParseLimits::with_max_annotation_bytes`]
+    AnnotationBytes,
+}
+
+impl ParseLimit {
+    /// Name of the [`ParseLimits`] method which sets this limit
+    pub(crate) fn setter(self) -> &'static str {
+        match self {
+            Self::InputBytes => "with_max_input_bytes",
+            Self::Policies => "with_max_policies",
+            Self::ConditionNodes => "with_max_condition_nodes",
+            Self::AnnotationBytes => "with_max_annotation_bytes",
+        }
+    }
+}
+
+impl fmt::Display for ParseLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InputBytes => write!(f, "input size in bytes"),
+            Self::Policies => write!(f, "number of policies"),
+            Self::ConditionNodes => write!(f, "number of expression nodes in a condition"),
+            Self::AnnotationBytes => write!(f, "annotation size in bytes"),
+        }
+    }
+}
+
+/// The error for exceeding `limit`, located at `loc`
+fn limit_exceeded(limit: ParseLimit, max: usize, loc: Option<Loc>) -> ToASTError {
+    ToASTError::new(ToASTErrorKind::LimitExceeded { limit, max }, loc)
+}
+
+/// Check that `text` is no larger than `limits.max_input_bytes()`.
+///
+/// The error has no source location, so that reporting it does not copy the
+/// oversized input.
+pub(crate) fn check_input_size(text: &str, limits: &ParseLimits) -> Result<(), ParseErrors> {
+    let max = limits.max_input_bytes();
+    if text.len() > max {
+        Err(limit_exceeded(ParseLimit::InputBytes, max, None).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// The error returned by a grammar action to stop the parser when the input
+/// exceeds a limit. The error to report is kept by the [`LimitCounter`].
+type StopParsing<T> = lalrpop_util::ParseError<usize, T, RawUserError>;
+
+/// Counts of the parts of the input limited by [`ParseLimits`], kept by the
+/// actions of the grammar while parsing.
+///
+/// When a count exceeds its limit, the action records the error and stops the
+/// parser, and [`LimitCounter::into_exceeded`] returns the error to report in
+/// place of the one returned by the parser.
+#[derive(Debug)]
+pub(crate) struct LimitCounter {
+    limits: ParseLimits,
+    /// Number of policies parsed so far
+    policies: Cell<usize>,
+    /// Number of nodes parsed so far in the current condition
+    nodes: Cell<usize>,
+    exceeded: RefCell<Option<ToASTError>>,
+}
+
+impl LimitCounter {
+    /// A counter enforcing `limits`
+    pub(crate) fn new(limits: ParseLimits) -> Self {
+        Self {
+            limits,
+            policies: Cell::new(0),
+            nodes: Cell::new(0),
+            exceeded: RefCell::new(None),
+        }
+    }
+
+    /// Record that `limit` was exceeded at `loc`, returning the error which
+    /// stops the parser
+    fn exceed<T>(&self, limit: ParseLimit, max: usize, loc: &Loc) -> StopParsing<T> {
+        let err = limit_exceeded(limit, max, Some(loc.clone()));
+        let message = err.to_string();
+        *self.exceeded.borrow_mut() = Some(err);
+        StopParsing::User {
+            error: Node::with_source_loc(message, loc.clone()),
+        }
+    }
+
+    /// Count a policy of a policy set, located at `loc`
+    pub(crate) fn count_policy<T>(&self, loc: &Loc) -> Result<(), StopParsing<T>> {
+        let count = self.policies.get().saturating_add(1);
+        self.policies.set(count);
+        let max = self.limits.max_policies();
+        if count > max {
+            Err(self.exceed(ParseLimit::Policies, max, loc))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Count `nodes` nodes of the current condition, in the part of it
+    /// located at `loc`
+    pub(crate) fn count_nodes<T>(&self, nodes: usize, loc: &Loc) -> Result<(), StopParsing<T>> {
+        let count = self.nodes.get().saturating_add(nodes);
+        self.nodes.set(count);
+        let max = self.limits.max_condition_nodes();
+        if count > max {
+            Err(self.exceed(ParseLimit::ConditionNodes, max, loc))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Start counting the nodes of the next condition
+    pub(crate) fn end_condition(&self) {
+        self.nodes.set(0);
+    }
+
+    /// Check the size of the value of an annotation
+    pub(crate) fn check_annotation<T>(
+        &self,
+        value: Option<&Node<Option<cst::Str>>>,
+    ) -> Result<(), StopParsing<T>> {
+        let max = self.limits.max_annotation_bytes();
+        match value {
+            Some(Node {
+                node: Some(cst::Str::String(s) | cst::Str::Invalid(s)),
+                loc: Some(loc),
+            }) if s.len() > max => Err(self.exceed(ParseLimit::AnnotationBytes, max, loc)),
+            _ => Ok(()),
+        }
+    }
+
+    /// The error for the limit which stopped the parser, if any
+    pub(crate) fn into_exceeded(self) -> Option<ToASTError> {
+        self.exceeded.into_inner()
+    }
+}
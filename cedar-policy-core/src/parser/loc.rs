@@ -25,6 +25,11 @@ pub struct Loc {
 
     /// Original source code (which the above source span indexes into)
     pub src: Arc<str>,
+
+    /// Name of the original source code, e.g., the name of the file it was
+    /// read from, if it was given one. Like `src`, this is shared by all the
+    /// `Loc`s into the same source code. See [`Loc::name`].
+    name: Option<Arc<str>>,
 }
 
 impl Loc {
@@ -33,6 +38,17 @@ impl Loc {
         Self {
             span: span.into(),
             src,
+            name: None,
+        }
+    }
+
+    /// Create a new `Loc` into source code with the name `name`, e.g., the
+    /// name of the file it was read from
+    pub fn named(span: impl Into<miette::SourceSpan>, src: Arc<str>, name: Arc<str>) -> Self {
+        Self {
+            span: span.into(),
+            src,
+            name: Some(name),
         }
     }
 
@@ -41,9 +57,15 @@ impl Loc {
         Self {
             span: span.into(),
             src: Arc::clone(&self.src),
+            name: self.name.clone(),
         }
     }
 
+    /// Get the name of the source code, if it was given one
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
     /// Get the index representing the start of the source span
     pub fn start(&self) -> usize {
         self.span.offset()
@@ -74,6 +96,29 @@ impl From<&Loc> for miette::SourceSpan {
     }
 }
 
+/// Read `span` from the source code of `loc`, reporting the span contents under
+/// the name of the source code, if any
+fn read_named_span<'a>(
+    loc: &'a Loc,
+    span: &miette::SourceSpan,
+    context_lines_before: usize,
+    context_lines_after: usize,
+) -> Result<Box<dyn miette::SpanContents<'a> + 'a>, miette::MietteError> {
+    let contents =
+        miette::SourceCode::read_span(&*loc.src, span, context_lines_before, context_lines_after)?;
+    match &loc.name {
+        Some(name) => Ok(Box::new(miette::MietteSpanContents::new_named(
+            name.to_string(),
+            contents.data(),
+            *contents.span(),
+            contents.line(),
+            contents.column(),
+            contents.line_count(),
+        ))),
+        None => Ok(contents),
+    }
+}
+
 impl miette::SourceCode for Loc {
     fn read_span<'a>(
         &'a self,
@@ -81,8 +126,7 @@ impl miette::SourceCode for Loc {
         context_lines_before: usize,
         context_lines_after: usize,
     ) -> Result<Box<dyn miette::SpanContents<'a> + 'a>, miette::MietteError> {
-        self.src
-            .read_span(span, context_lines_before, context_lines_after)
+        read_named_span(self, span, context_lines_before, context_lines_after)
     }
 }
 
@@ -93,8 +137,7 @@ impl miette::SourceCode for &Loc {
         context_lines_before: usize,
         context_lines_after: usize,
     ) -> Result<Box<dyn miette::SpanContents<'a> + 'a>, miette::MietteError> {
-        self.src
-            .read_span(span, context_lines_before, context_lines_after)
+        read_named_span(self, span, context_lines_before, context_lines_after)
     }
 }
 
@@ -122,4 +165,20 @@ mod test {
         assert_eq!(format!("{l:?}"), r#"Loc(`a == b`)"#);
         assert_eq!(format!("{l2:?}"), r#"Loc(`b == "c"`)"#);
     }
+
+    #[test]
+    fn named_loc() {
+        use miette::SourceCode;
+        let src: std::sync::Arc<str> = "permit(principal, action, resource);".into();
+        let l = super::Loc::named(7..16, src.clone(), "policies.cedar".into());
+        assert_eq!(l.name(), Some("policies.cedar"));
+        assert_eq!(l.span(18..24).name(), Some("policies.cedar"));
+        let contents = l.read_span(&l.span, 0, 0).unwrap();
+        assert_eq!(contents.name(), Some("policies.cedar"));
+        assert_eq!(contents.data(), b"principal");
+
+        let l = super::Loc::new(7..16, src);
+        assert_eq!(l.name(), None);
+        assert_eq!(l.read_span(&l.span, 0, 0).unwrap().name(), None);
+    }
 }
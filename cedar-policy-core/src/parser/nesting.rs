@@ -0,0 +1,558 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Check of the nesting depth of the expressions in a CST against
+//! [`ParserOptions::max_nesting_depth`].
+//!
+//! Converting a CST to an AST (or EST), and most operations on the result,
+//! recurse once for every level of nesting, so this check runs before any
+//! conversion, and uses an explicit stack rather than recursion.
+//!
+//! The depth counted is that of the expression once converted: a chain of
+//! binary operators such as `a + b + c` is nested to the left, so each
+//! operator in the chain adds a level, as does each attribute access, index,
+//! method call, and negation.
+
+use super::cst;
+use super::err::{ToASTError, ToASTErrorKind};
+use super::node::Node;
+use super::options::ParserOptions;
+
+/// A CST node whose expressions can be checked by [`check_nesting_depth`]
+pub(crate) trait NestedExprs {
+    /// Push the outermost parts of expressions in this node, i.e., those which
+    /// are not nested in another expression in this node, onto `parts`
+    fn outermost_parts<'a>(&'a self, parts: &mut Vec<Part<'a>>);
+}
+
+/// A part of an expression in a CST, at which the nesting depth is checked
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Part<'a> {
+    /// An expression
+    Expr(&'a Node<Option<cst::Expr>>),
+    /// A chain of `||`
+    Or(&'a Node<Option<cst::Or>>),
+    /// A chain of `&&`
+    And(&'a Node<Option<cst::And>>),
+    /// A chain of comparisons, or a `has`, `like`, or `is`
+    Relation(&'a Node<Option<cst::Relation>>),
+    /// A chain of `+` and `-`
+    Add(&'a Node<Option<cst::Add>>),
+    /// A chain of `*`
+    Mult(&'a Node<Option<cst::Mult>>),
+    /// Negations
+    Unary(&'a Node<Option<cst::Unary>>),
+    /// Attribute accesses, indexes, and calls
+    Member(&'a Node<Option<cst::Member>>),
+    /// A literal, name, parenthesized expression, or set or record literal
+    Primary(&'a Node<Option<cst::Primary>>),
+}
+
+/// Check that no expression in `node` is nested deeper than
+/// `options.max_nesting_depth()`. On failure, the error is located at the
+/// first part of an expression found to be nested too deep.
+pub(crate) fn check_nesting_depth(
+    node: &impl NestedExprs,
+    options: ParserOptions,
+) -> Result<(), ToASTError> {
+    let max = options.max_nesting_depth();
+    let mut outermost = Vec::new();
+    node.outermost_parts(&mut outermost);
+    let mut stack: Vec<(Part<'_>, usize)> = outermost.into_iter().map(|p| (p, 1)).collect();
+    while let Some((part, depth)) = stack.pop() {
+        if depth > max {
+            return Err(part.to_ast_err(ToASTErrorKind::NestingTooDeep(max)));
+        }
+        part.push_subparts(depth, &mut stack);
+    }
+    Ok(())
+}
+
+impl NestedExprs for Node<Option<cst::Policies>> {
+    fn outermost_parts<'a>(&'a self, parts: &mut Vec<Part<'a>>) {
+        if let Some(policies) = &self.node {
+            for policy in &policies.0 {
+                policy.outermost_parts(parts);
+            }
+        }
+    }
+}
+
+impl NestedExprs for Node<Option<cst::Policy>> {
+    fn outermost_parts<'a>(&'a self, parts: &mut Vec<Part<'a>>) {
+        match &self.node {
+            Some(cst::Policy::Policy(policy)) => {
+                for var in policy.variables.iter().filter_map(|var| var.node.as_ref()) {
+                    parts.extend(var.entity_type.as_ref().map(Part::Add));
+                    parts.extend(var.ineq.as_ref().map(|(_, expr)| Part::Expr(expr)));
+                }
+                for cond in policy.conds.iter().filter_map(|cond| cond.node.as_ref()) {
+                    parts.extend(cond.expr.as_ref().map(Part::Expr));
+                }
+            }
+            #[cfg(feature = "tolerant-ast")]
+            Some(cst::Policy::PolicyError) => {}
+            None => {}
+        }
+    }
+}
+
+impl NestedExprs for Node<Option<cst::Expr>> {
+    fn outermost_parts<'a>(&'a self, parts: &mut Vec<Part<'a>>) {
+        parts.push(Part::Expr(self));
+    }
+}
+
+impl NestedExprs for Node<Option<cst::Primary>> {
+    fn outermost_parts<'a>(&'a self, parts: &mut Vec<Part<'a>>) {
+        parts.push(Part::Primary(self));
+    }
+}
+
+impl<'a> Part<'a> {
+    /// Construct an error located at this part
+    fn to_ast_err(self, kind: ToASTErrorKind) -> ToASTError {
+        match self {
+            Part::Expr(node) => node.to_ast_err(kind),
+            Part::Or(node) => node.to_ast_err(kind),
+            Part::And(node) => node.to_ast_err(kind),
+            Part::Relation(node) => node.to_ast_err(kind),
+            Part::Add(node) => node.to_ast_err(kind),
+            Part::Mult(node) => node.to_ast_err(kind),
+            Part::Unary(node) => node.to_ast_err(kind),
+            Part::Member(node) => node.to_ast_err(kind),
+            Part::Primary(node) => node.to_ast_err(kind),
+        }
+    }
+
+    /// Push the parts directly contained in this part, which is at nesting
+    /// depth `depth`, onto `stack`, each with its own depth
+    fn push_subparts(self, depth: usize, stack: &mut Vec<(Part<'a>, usize)>) {
+        match self {
+            Part::Expr(expr) => match &expr.node {
+                Some(cst::Expr::Expr(expr)) => match expr.expr.as_ref() {
+                    cst::ExprData::Or(or) => stack.push((Part::Or(or), depth)),
+                    cst::ExprData::If(test, then, els) => {
+                        stack.extend([test, then, els].map(|e| (Part::Expr(e), depth + 1)));
+                    }
+                },
+                #[cfg(feature = "tolerant-ast")]
+                Some(cst::Expr::ErrorExpr) => {}
+                None => {}
+            },
+            Part::Or(or) => {
+                if let Some(or) = &or.node {
+                    push_chain(&or.initial, or.extended.iter(), Part::And, depth, stack);
+                }
+            }
+            Part::And(and) => {
+                if let Some(and) = &and.node {
+                    push_chain(
+                        &and.initial,
+                        and.extended.iter(),
+                        Part::Relation,
+                        depth,
+                        stack,
+                    );
+                }
+            }
+            Part::Relation(relation) => match &relation.node {
+                Some(cst::Relation::Common { initial, extended }) => {
+                    let extended = extended.iter().map(|(_, add)| add);
+                    push_chain(initial, extended, Part::Add, depth, stack);
+                }
+                Some(
+                    cst::Relation::Has {
+                        target,
+                        field: operand,
+                    }
+                    | cst::Relation::Like {
+                        target,
+                        pattern: operand,
+                    },
+                ) => stack.extend([target, operand].map(|e| (Part::Add(e), depth + 1))),
+                Some(cst::Relation::IsIn {
+                    target,
+                    entity_type,
+                    in_entity,
+                }) => {
+                    // `e is T in x` is converted to `e is T && e in x`
+                    let depth = depth + 1 + usize::from(in_entity.is_some());
+                    stack.extend(
+                        [Some(target), Some(entity_type), in_entity.as_ref()]
+                            .into_iter()
+                            .flatten()
+                            .map(|e| (Part::Add(e), depth)),
+                    );
+                }
+                None => {}
+            },
+            Part::Add(add) => {
+                if let Some(add) = &add.node {
+                    let extended = add.extended.iter().map(|(_, mult)| mult);
+                    push_chain(&add.initial, extended, Part::Mult, depth, stack);
+                }
+            }
+            Part::Mult(mult) => {
+                if let Some(mult) = &mult.node {
+                    let extended = mult.extended.iter().map(|(_, unary)| unary);
+                    push_chain(&mult.initial, extended, Part::Unary, depth, stack);
+                }
+            }
+            Part::Unary(unary) => {
+                if let Some(unary) = &unary.node {
+                    let negations = match unary.op {
+                        None => 0,
+                        Some(cst::NegOp::Bang(n) | cst::NegOp::Dash(n)) => usize::from(n),
+                        Some(cst::NegOp::OverBang | cst::NegOp::OverDash) => 1,
+                    };
+                    stack.push((Part::Member(&unary.item), depth + negations));
+                }
+            }
+            Part::Member(member) => {
+                if let Some(member) = &member.node {
+                    push_accesses(member, depth, stack);
+                }
+            }
+            Part::Primary(primary) => match &primary.node {
+                Some(cst::Primary::Expr(expr)) => stack.push((Part::Expr(expr), depth + 1)),
+                Some(cst::Primary::EList(elements)) => {
+                    stack.extend(elements.iter().map(|e| (Part::Expr(e), depth + 1)));
+                }
+                Some(cst::Primary::RInits(inits)) => {
+                    for cst::RecInit(key, value) in inits.iter().filter_map(|i| i.node.as_ref()) {
+                        stack.extend([key, value].map(|e| (Part::Expr(e), depth + 1)));
+                    }
+                }
+                Some(
+                    cst::Primary::Literal(_)
+                    | cst::Primary::Ref(_)
+                    | cst::Primary::Name(_)
+                    | cst::Primary::Slot(_),
+                )
+                | None => {}
+            },
+        }
+    }
+}
+
+/// Push the operands of a chain of binary operators at depth `depth` onto
+/// `stack`. The chain `a + b + c` is `(a + b) + c`, so `c` is at `depth + 1`
+/// while `a` and `b` are at `depth + 2`.
+fn push_chain<'a, T: 'a>(
+    initial: &'a T,
+    extended: impl ExactSizeIterator<Item = &'a T>,
+    part: impl Fn(&'a T) -> Part<'a>,
+    depth: usize,
+    stack: &mut Vec<(Part<'a>, usize)>,
+) {
+    let operators = extended.len();
+    stack.push((part(initial), depth + operators));
+    stack.extend(
+        extended
+            .enumerate()
+            .map(|(i, operand)| (part(operand), depth + operators - i)),
+    );
+}
+
+/// Push the item and the arguments of the accesses of `member`, at depth
+/// `depth`, onto `stack`. Like a chain of binary operators, `a.b.c` is
+/// `(a.b).c`, and a method call `a.f(x)` counts as a single access.
+fn push_accesses<'a>(member: &'a cst::Member, depth: usize, stack: &mut Vec<(Part<'a>, usize)>) {
+    let mut args_of_accesses: Vec<&'a [Node<Option<cst::Expr>>]> = Vec::new();
+    let mut accesses = member.access.iter().map(|access| access.node.as_ref());
+    let mut next = accesses.next();
+    while let Some(access) = next {
+        next = accesses.next();
+        let args = match access {
+            Some(cst::MemAccess::Field(_)) => match next {
+                Some(Some(cst::MemAccess::Call(args))) => {
+                    next = accesses.next();
+                    args.as_slice()
+                }
+                _ => &[],
+            },
+            Some(cst::MemAccess::Call(args)) => args.as_slice(),
+            Some(cst::MemAccess::Index(index)) => std::slice::from_ref(index),
+            None => &[],
+        };
+        args_of_accesses.push(args);
+    }
+    let count = args_of_accesses.len();
+    stack.push((Part::Primary(&member.item), depth + count));
+    for (i, args) in args_of_accesses.into_iter().enumerate() {
+        stack.extend(args.iter().map(|arg| (Part::Expr(arg), depth + count - i)));
+    }
+}
+
+/// Dropping a CST expression would otherwise recurse once for every level of
+/// nesting, overflowing the stack on input which is rejected by
+/// [`check_nesting_depth`] or parsed with [`ParserOptions::unlimited`]. Instead,
+/// subexpressions are moved onto an explicit stack and dropped from there.
+impl Drop for cst::ExprImpl {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        self.expr.take_subexprs(&mut stack);
+        while let Some(mut expr) = stack.pop() {
+            match &mut expr {
+                cst::Expr::Expr(expr) => expr.expr.take_subexprs(&mut stack),
+                #[cfg(feature = "tolerant-ast")]
+                cst::Expr::ErrorExpr => {}
+            }
+        }
+    }
+}
+
+/// A CST node which may contain expressions, which are dropped by moving them
+/// onto an explicit stack
+trait TakeSubExprs {
+    /// Move the expressions directly contained in this node onto `exprs`,
+    /// leaving empty nodes in their place
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>);
+}
+
+impl TakeSubExprs for Node<Option<cst::Expr>> {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        exprs.extend(self.node.take());
+    }
+}
+
+impl<T: TakeSubExprs> TakeSubExprs for Vec<T> {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        for node in self {
+            node.take_subexprs(exprs);
+        }
+    }
+}
+
+macro_rules! take_subexprs_of_node {
+    ($($t:ty),*) => {
+        $(
+            impl TakeSubExprs for Node<Option<$t>> {
+                fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+                    if let Some(node) = &mut self.node {
+                        node.take_subexprs(exprs);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+take_subexprs_of_node!(
+    cst::Or,
+    cst::And,
+    cst::Relation,
+    cst::Add,
+    cst::Mult,
+    cst::Unary,
+    cst::Member,
+    cst::MemAccess,
+    cst::Primary,
+    cst::RecInit
+);
+
+impl TakeSubExprs for cst::ExprData {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        match self {
+            cst::ExprData::Or(or) => or.take_subexprs(exprs),
+            cst::ExprData::If(test, then, els) => {
+                test.take_subexprs(exprs);
+                then.take_subexprs(exprs);
+                els.take_subexprs(exprs);
+            }
+        }
+    }
+}
+
+impl TakeSubExprs for cst::Or {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        self.initial.take_subexprs(exprs);
+        self.extended.take_subexprs(exprs);
+    }
+}
+
+impl TakeSubExprs for cst::And {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        self.initial.take_subexprs(exprs);
+        self.extended.take_subexprs(exprs);
+    }
+}
+
+impl TakeSubExprs for cst::Relation {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        match self {
+            cst::Relation::Common { initial, extended } => {
+                initial.take_subexprs(exprs);
+                for (_, add) in extended {
+                    add.take_subexprs(exprs);
+                }
+            }
+            cst::Relation::Has { target, field } => {
+                target.take_subexprs(exprs);
+                field.take_subexprs(exprs);
+            }
+            cst::Relation::Like { target, pattern } => {
+                target.take_subexprs(exprs);
+                pattern.take_subexprs(exprs);
+            }
+            cst::Relation::IsIn {
+                target,
+                entity_type,
+                in_entity,
+            } => {
+                target.take_subexprs(exprs);
+                entity_type.take_subexprs(exprs);
+                if let Some(in_entity) = in_entity {
+                    in_entity.take_subexprs(exprs);
+                }
+            }
+        }
+    }
+}
+
+impl TakeSubExprs for cst::Add {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        self.initial.take_subexprs(exprs);
+        for (_, mult) in &mut self.extended {
+            mult.take_subexprs(exprs);
+        }
+    }
+}
+
+impl TakeSubExprs for cst::Mult {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        self.initial.take_subexprs(exprs);
+        for (_, unary) in &mut self.extended {
+            unary.take_subexprs(exprs);
+        }
+    }
+}
+
+impl TakeSubExprs for cst::Unary {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        self.item.take_subexprs(exprs);
+    }
+}
+
+impl TakeSubExprs for cst::Member {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        self.item.take_subexprs(exprs);
+        self.access.take_subexprs(exprs);
+    }
+}
+
+impl TakeSubExprs for cst::MemAccess {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        match self {
+            cst::MemAccess::Call(args) => args.take_subexprs(exprs),
+            cst::MemAccess::Index(index) => index.take_subexprs(exprs),
+            cst::MemAccess::Field(_) => {}
+        }
+    }
+}
+
+impl TakeSubExprs for cst::Primary {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        match self {
+            cst::Primary::Expr(expr) => expr.take_subexprs(exprs),
+            cst::Primary::EList(elements) => elements.take_subexprs(exprs),
+            cst::Primary::RInits(inits) => inits.take_subexprs(exprs),
+            cst::Primary::Literal(_)
+            | cst::Primary::Ref(_)
+            | cst::Primary::Name(_)
+            | cst::Primary::Slot(_) => {}
+        }
+    }
+}
+
+impl TakeSubExprs for cst::RecInit {
+    fn take_subexprs(&mut self, exprs: &mut Vec<cst::Expr>) {
+        self.0.take_subexprs(exprs);
+        self.1.take_subexprs(exprs);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::text_to_cst;
+    use cool_asserts::assert_matches;
+
+    /// The nesting depth of the expression `text`
+    fn depth(text: &str) -> usize {
+        let cst = text_to_cst::parse_expr_with_options(text, &ParserOptions::unlimited())
+            .expect("should parse");
+        (1..)
+            .find(|&max| {
+                check_nesting_depth(&cst, ParserOptions::default().with_max_nesting_depth(max))
+                    .is_ok()
+            })
+            .expect("should be finite")
+    }
+
+    #[test]
+    fn depths() {
+        assert_eq!(depth("1"), 1);
+        assert_eq!(depth("(1)"), 2);
+        assert_eq!(depth("1 + 2 + 3"), 3);
+        assert_eq!(depth("1 + 2 * 3"), 3);
+        assert_eq!(depth("a || b && c || d"), 4);
+        assert_eq!(depth("1 < 2"), 2);
+        assert_eq!(depth("context.a.b.c"), 4);
+        assert_eq!(depth("!!true && --1 == 1"), 5);
+        assert_eq!(depth("-(1 + (2 * 3))"), 6);
+        assert_eq!(depth("[[1], {a: 2}]"), 3);
+        assert_eq!(depth("if a then (b) else c"), 3);
+        assert_eq!(depth("ip(\"1.1.1.1\").isInRange(ip(\"1.1.1.0/24\"))"), 3);
+        assert_eq!(depth("context[\"a\"][\"b\"]"), 3);
+        assert_eq!(depth("a has b"), 2);
+        assert_eq!(depth("a is T"), 2);
+        assert_eq!(depth("a is T in [b]"), 4);
+    }
+
+    #[test]
+    fn long_chains_are_too_deep() {
+        for op in ["||", "&&", "==", "+", "*"] {
+            let text = vec!["1"; 50_000].join(op);
+            let cst = text_to_cst::parse_expr_with_options(&text, &ParserOptions::unlimited())
+                .expect("should parse");
+            assert_matches!(check_nesting_depth(&cst, ParserOptions::default()), Err(e) => {
+                assert_eq!(e.kind(), &ToASTErrorKind::NestingTooDeep(ParserOptions::default().max_nesting_depth()));
+            });
+        }
+        let text = format!("context{}", ".a".repeat(50_000));
+        let cst = text_to_cst::parse_expr_with_options(&text, &ParserOptions::unlimited())
+            .expect("should parse");
+        assert_matches!(
+            check_nesting_depth(&cst, ParserOptions::default()),
+            Err(e) if matches!(e.kind(), ToASTErrorKind::NestingTooDeep(_))
+        );
+    }
+
+    #[test]
+    fn error_is_located_at_outermost_too_deep_expr() {
+        let text = "permit(principal, action, resource) when { [1, [[3]]] };";
+        let cst = text_to_cst::parse_policies_with_options(text, &ParserOptions::unlimited())
+            .expect("should parse");
+        let options = ParserOptions::default().with_max_nesting_depth(2);
+        assert_matches!(check_nesting_depth(&cst, options), Err(e) => {
+            assert_eq!(e.kind(), &ToASTErrorKind::NestingTooDeep(2));
+            assert_eq!(e.source_loc().and_then(|loc| loc.snippet()), Some("[3]"));
+        });
+        assert!(check_nesting_depth(&cst, options.with_max_nesting_depth(4)).is_ok());
+    }
+}
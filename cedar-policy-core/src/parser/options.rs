@@ -0,0 +1,73 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Options controlling the parser, including the limits which protect it from
+//! pathological input.
+
+/// Options for parsing policies and expressions.
+///
+/// The default options are generous enough for any realistic policy.
+/// [`ParserOptions::unlimited`] disables all of the limits. See
+/// [`super::ParseLimits`] for limits on the size of untrusted input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    max_nesting_depth: usize,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+        }
+    }
+}
+
+/// The default for [`ParserOptions::with_max_nesting_depth`]. This allows long
+/// generated `else if` and `||` chains, each link of which adds a level of
+/// nesting, while parsing and validating an expression at this depth takes
+/// well under 512 KiB of stack in a debug build, a quarter of the default
+/// stack of a spawned thread.
+const DEFAULT_MAX_NESTING_DEPTH: usize = 256;
+
+impl ParserOptions {
+    /// No limits at all.
+    ///
+    /// Converting deeply nested input to an AST recurses once per level of
+    /// nesting, so this should only be used with trusted input.
+    pub fn unlimited() -> Self {
+        Self {
+            max_nesting_depth: usize::MAX,
+        }
+    }
+
+    /// Set the maximum nesting depth of an expression. A literal, variable,
+    /// or entity reference has depth 1, and each operator, attribute access,
+    /// index, method or function call, `if`-`then`-`else`, set or record
+    /// literal, and pair of parentheses adds one to the depth of its deepest
+    /// operand. A chain of binary operators is nested to the left, so
+    /// `[[1], {a: 2}]` and `1 + 2 + 3` both have depth 3, and
+    /// `context.a.b.c` has depth 4.
+    pub fn with_max_nesting_depth(mut self, max: usize) -> Self {
+        self.max_nesting_depth = max;
+        self
+    }
+
+    /// Get the maximum nesting depth of an expression. See
+    /// [`ParserOptions::with_max_nesting_depth`].
+    pub fn max_nesting_depth(&self) -> usize {
+        self.max_nesting_depth
+    }
+}
@@ -30,6 +30,8 @@ lalrpop_mod!(
     "/src/parser/grammar.rs"
 );
 
+use super::limits::{check_input_size, LimitCounter};
+use super::nesting::check_nesting_depth;
 use super::*;
 use std::sync::{Arc, LazyLock};
 
@@ -41,31 +43,60 @@ fn parse_collect_errors<'a, P, T>(
     parse: impl FnOnce(
         &P,
         &mut Vec<err::RawErrorRecovery<'a>>,
-        &Arc<str>,
+        &Loc,
         bool,
+        &LimitCounter,
         &'a str,
     ) -> Result<T, err::RawParseError<'a>>,
     keep_src: bool,
     text: &'a str,
+    limits: &ParseLimits,
 ) -> Result<T, err::ParseErrors> {
     // We don't need to copy the source if we won't keep it
-    let shared_src = if keep_src {
-        Arc::from(text)
+    let source = if keep_src {
+        Loc::new(0..0, Arc::from(text))
     } else {
-        Arc::from("")
+        Loc::new(0..0, Arc::from(""))
     };
+    parse_collect_errors_from(parser, parse, &source, keep_src, text, limits)
+}
+
+/// Like [`parse_collect_errors`], but with every source location created from
+/// `source`, which locates `text` and may also name it.
+///
+/// If the parser stopped because the input exceeds one of the `limits`, the
+/// error for that limit is returned in place of any other errors.
+fn parse_collect_errors_from<'a, P, T>(
+    parser: &P,
+    parse: impl FnOnce(
+        &P,
+        &mut Vec<err::RawErrorRecovery<'a>>,
+        &Loc,
+        bool,
+        &LimitCounter,
+        &'a str,
+    ) -> Result<T, err::RawParseError<'a>>,
+    source: &Loc,
+    keep_src: bool,
+    text: &'a str,
+    limits: &ParseLimits,
+) -> Result<T, err::ParseErrors> {
     let mut errs = Vec::new();
-    let result = parse(parser, &mut errs, &shared_src, keep_src, text);
+    let counter = LimitCounter::new(*limits);
+    let result = parse(parser, &mut errs, source, keep_src, &counter, text);
+    if let Some(exceeded) = counter.into_exceeded() {
+        return Err(exceeded.into());
+    }
 
     let errors = errs
         .into_iter()
-        .map(|rc| err::ToCSTError::from_raw_err_recovery(rc, Arc::clone(&shared_src)))
+        .map(|rc| err::ToCSTError::from_raw_err_recovery(rc, source.clone()))
         .map(Into::into);
     let parsed = match result {
         Ok(parsed) => parsed,
         Err(e) => {
             return Err(err::ParseErrors::new(
-                err::ToCSTError::from_raw_parse_err(e, Arc::clone(&shared_src)).into(),
+                err::ToCSTError::from_raw_parse_err(e, source.clone()).into(),
                 errors,
             ));
         }
@@ -85,24 +116,27 @@ fn parse_collect_errors_tolerant<'a, P, T>(
     parse: impl FnOnce(
         &P,
         &mut Vec<err::RawErrorRecovery<'a>>,
-        &Arc<str>,
+        &Loc,
         bool,
+        &LimitCounter,
         &'a str,
     ) -> Result<T, err::RawParseError<'a>>,
     text: &'a str,
 ) -> Result<T, err::ParseErrors> {
+    let source = Loc::new(0..0, Arc::from(text));
     let mut errs = Vec::new();
-    let result = parse(parser, &mut errs, &Arc::from(text), true, text);
+    let counter = LimitCounter::new(ParseLimits::unlimited());
+    let result = parse(parser, &mut errs, &source, true, &counter, text);
 
     let errors = errs
         .into_iter()
-        .map(|rc| err::ToCSTError::from_raw_err_recovery(rc, Arc::from(text)))
+        .map(|rc| err::ToCSTError::from_raw_err_recovery(rc, source.clone()))
         .map(Into::into);
     let parsed = match result {
         Ok(parsed) => parsed,
         Err(e) => {
             return Err(err::ParseErrors::new(
-                err::ToCSTError::from_raw_parse_err(e, Arc::from(text)).into(),
+                err::ToCSTError::from_raw_parse_err(e, source.clone()).into(),
                 errors,
             ));
         }
@@ -124,48 +158,222 @@ static IDENT_PARSER: LazyLock<grammar::IdentParser> = LazyLock::new(grammar::Ide
 
 /// Create CST for multiple policies from text
 pub fn parse_policies(text: &str) -> Result<Node<Option<cst::Policies>>, err::ParseErrors> {
-    parse_collect_errors(
+    parse_policies_with_options(text, &ParserOptions::default())
+}
+
+/// Like [`parse_policies`], but with the limits in `options` rather than the
+/// default ones
+pub fn parse_policies_with_options(
+    text: &str,
+    options: &ParserOptions,
+) -> Result<Node<Option<cst::Policies>>, err::ParseErrors> {
+    parse_policies_with_options_and_limits(text, *options, &ParseLimits::unlimited())
+}
+
+/// Like [`parse_policies`], but failing as soon as the input exceeds one of
+/// the `limits`
+pub fn parse_policies_with_limits(
+    text: &str,
+    limits: &ParseLimits,
+) -> Result<Node<Option<cst::Policies>>, err::ParseErrors> {
+    parse_policies_with_options_and_limits(text, ParserOptions::default(), limits)
+}
+
+fn parse_policies_with_options_and_limits(
+    text: &str,
+    options: ParserOptions,
+    limits: &ParseLimits,
+) -> Result<Node<Option<cst::Policies>>, err::ParseErrors> {
+    check_input_size(text, limits)?;
+    let cst = parse_collect_errors(
         &*POLICIES_PARSER,
         grammar::PoliciesParser::parse,
         true,
         text,
-    )
+        limits,
+    )?;
+    check_nesting_depth(&cst, options)?;
+    Ok(cst)
+}
+
+/// Like [`parse_policies`], but naming the source text `name`, e.g., with the
+/// name of the file it was read from. Every source location in the CST, and in
+/// any errors, carries the name.
+pub fn parse_named_policies(
+    name: &str,
+    text: &str,
+) -> Result<Node<Option<cst::Policies>>, err::ParseErrors> {
+    let source = Loc::named(0..0, Arc::from(text), Arc::from(name));
+    let cst = parse_collect_errors_from(
+        &*POLICIES_PARSER,
+        grammar::PoliciesParser::parse,
+        &source,
+        true,
+        text,
+        &ParseLimits::unlimited(),
+    )?;
+    check_nesting_depth(&cst, ParserOptions::default())?;
+    Ok(cst)
+}
+
+/// Create CST for multiple policies from text, also returning the errors the
+/// parser recovered from. Each policy in which the parser recovered from an
+/// error is still represented in the CST by a node covering its text.
+///
+/// Returns `Err` only if the parser failed to recover from some error. The
+/// nesting depth of the policies is not checked, so callers converting the CST
+/// should check each policy with the default [`ParserOptions`] first.
+pub fn parse_policies_recovering(
+    text: &str,
+) -> Result<(Node<Option<cst::Policies>>, Vec<err::ParseError>), err::ParseErrors> {
+    let source = Loc::new(0..0, Arc::from(text));
+    let mut errs = Vec::new();
+    let counter = LimitCounter::new(ParseLimits::unlimited());
+    let result = POLICIES_PARSER.parse(&mut errs, &source, true, &counter, text);
+    let errors = errs
+        .into_iter()
+        .map(|rc| err::ToCSTError::from_raw_err_recovery(rc, source.clone()).into());
+    match result {
+        Ok(parsed) => Ok((parsed, errors.collect())),
+        Err(e) => Err(err::ParseErrors::new(
+            err::ToCSTError::from_raw_parse_err(e, source.clone()).into(),
+            errors,
+        )),
+    }
 }
 
 /// Create CST for one policy statement from text
 pub fn parse_policy(text: &str) -> Result<Node<Option<cst::Policy>>, err::ParseErrors> {
-    parse_collect_errors(&*POLICY_PARSER, grammar::PolicyParser::parse, true, text)
+    parse_policy_with_options(text, &ParserOptions::default())
+}
+
+/// Like [`parse_policy`], but with the limits in `options` rather than the
+/// default ones
+pub fn parse_policy_with_options(
+    text: &str,
+    options: &ParserOptions,
+) -> Result<Node<Option<cst::Policy>>, err::ParseErrors> {
+    parse_policy_with_options_and_limits(text, *options, &ParseLimits::unlimited())
+}
+
+/// Like [`parse_policy`], but failing as soon as the input exceeds one of the
+/// `limits`
+pub fn parse_policy_with_limits(
+    text: &str,
+    limits: &ParseLimits,
+) -> Result<Node<Option<cst::Policy>>, err::ParseErrors> {
+    parse_policy_with_options_and_limits(text, ParserOptions::default(), limits)
+}
+
+fn parse_policy_with_options_and_limits(
+    text: &str,
+    options: ParserOptions,
+    limits: &ParseLimits,
+) -> Result<Node<Option<cst::Policy>>, err::ParseErrors> {
+    check_input_size(text, limits)?;
+    let cst = parse_collect_errors(
+        &*POLICY_PARSER,
+        grammar::PolicyParser::parse,
+        true,
+        text,
+        limits,
+    )?;
+    check_nesting_depth(&cst, options)?;
+    Ok(cst)
 }
 
 /// Create CST for one Expression from text
 pub fn parse_expr(text: &str) -> Result<Node<Option<cst::Expr>>, err::ParseErrors> {
-    parse_collect_errors(&*EXPR_PARSER, grammar::ExprParser::parse, true, text)
+    parse_expr_with_options(text, &ParserOptions::default())
+}
+
+/// Like [`parse_expr`], but with the limits in `options` rather than the
+/// default ones
+pub fn parse_expr_with_options(
+    text: &str,
+    options: &ParserOptions,
+) -> Result<Node<Option<cst::Expr>>, err::ParseErrors> {
+    parse_expr_with_options_and_limits(text, *options, &ParseLimits::unlimited())
+}
+
+/// Like [`parse_expr`], but failing as soon as the input exceeds one of the
+/// `limits`
+pub fn parse_expr_with_limits(
+    text: &str,
+    limits: &ParseLimits,
+) -> Result<Node<Option<cst::Expr>>, err::ParseErrors> {
+    parse_expr_with_options_and_limits(text, ParserOptions::default(), limits)
+}
+
+fn parse_expr_with_options_and_limits(
+    text: &str,
+    options: ParserOptions,
+    limits: &ParseLimits,
+) -> Result<Node<Option<cst::Expr>>, err::ParseErrors> {
+    check_input_size(text, limits)?;
+    let cst = parse_collect_errors(
+        &*EXPR_PARSER,
+        grammar::ExprParser::parse,
+        true,
+        text,
+        limits,
+    )?;
+    check_nesting_depth(&cst, options)?;
+    Ok(cst)
 }
 
 /// Create CST for one Entity Ref (i.e., UID) from text
 pub fn parse_ref(text: &str) -> Result<Node<Option<cst::Ref>>, err::ParseErrors> {
-    parse_collect_errors(&*REF_PARSER, grammar::RefParser::parse, true, text)
+    parse_collect_errors(
+        &*REF_PARSER,
+        grammar::RefParser::parse,
+        true,
+        text,
+        &ParseLimits::unlimited(),
+    )
 }
 
 /// Create CST for one Primary value from text
 pub fn parse_primary(text: &str) -> Result<Node<Option<cst::Primary>>, err::ParseErrors> {
-    parse_collect_errors(&*PRIMARY_PARSER, grammar::PrimaryParser::parse, true, text)
+    let cst = parse_collect_errors(
+        &*PRIMARY_PARSER,
+        grammar::PrimaryParser::parse,
+        true,
+        text,
+        &ParseLimits::unlimited(),
+    )?;
+    check_nesting_depth(&cst, ParserOptions::default())?;
+    Ok(cst)
 }
 
 /// Parse text as a Name, or fail if it does not parse as a Name
 pub fn parse_name(text: &str) -> Result<Node<Option<cst::Name>>, err::ParseErrors> {
-    parse_collect_errors(&*NAME_PARSER, grammar::NameParser::parse, true, text)
+    parse_collect_errors(
+        &*NAME_PARSER,
+        grammar::NameParser::parse,
+        true,
+        text,
+        &ParseLimits::unlimited(),
+    )
 }
 
 /// Parse text as an identifier, or fail if it does not parse as an identifier
 pub fn parse_ident(text: &str) -> Result<Node<Option<cst::Ident>>, err::ParseErrors> {
-    parse_collect_errors(&*IDENT_PARSER, grammar::IdentParser::parse, true, text)
+    parse_collect_errors(
+        &*IDENT_PARSER,
+        grammar::IdentParser::parse,
+        true,
+        text,
+        &ParseLimits::unlimited(),
+    )
 }
 
 /// Create CST for one policy statement from text - allows CST error nodes on certain parse failures
 #[cfg(feature = "tolerant-ast")]
 pub fn parse_policy_tolerant(text: &str) -> Result<Node<Option<cst::Policy>>, err::ParseErrors> {
-    parse_collect_errors_tolerant(&*POLICY_PARSER, grammar::PolicyParser::parse, text)
+    let cst = parse_collect_errors_tolerant(&*POLICY_PARSER, grammar::PolicyParser::parse, text)?;
+    check_nesting_depth(&cst, ParserOptions::default())?;
+    Ok(cst)
 }
 
 /// Create CST for one policy statement from text - allows CST error nodes on certain parse failures
@@ -173,13 +381,18 @@ pub fn parse_policy_tolerant(text: &str) -> Result<Node<Option<cst::Policy>>, er
 pub fn parse_policies_tolerant(
     text: &str,
 ) -> Result<Node<Option<cst::Policies>>, err::ParseErrors> {
-    parse_collect_errors_tolerant(&*POLICIES_PARSER, grammar::PoliciesParser::parse, text)
+    let cst =
+        parse_collect_errors_tolerant(&*POLICIES_PARSER, grammar::PoliciesParser::parse, text)?;
+    check_nesting_depth(&cst, ParserOptions::default())?;
+    Ok(cst)
 }
 
 /// Create CST for one Expression from text - allows CST error nodes on certain parse failures
 #[cfg(feature = "tolerant-ast")]
 pub fn parse_expr_tolerant(text: &str) -> Result<Node<Option<cst::Expr>>, err::ParseErrors> {
-    parse_collect_errors_tolerant(&*EXPR_PARSER, grammar::ExprParser::parse, text)
+    let cst = parse_collect_errors_tolerant(&*EXPR_PARSER, grammar::ExprParser::parse, text)?;
+    check_nesting_depth(&cst, ParserOptions::default())?;
+    Ok(cst)
 }
 
 #[expect(clippy::panic, reason = "unit test code")]
@@ -281,32 +494,20 @@ mod tests {
 
     #[test]
     fn expr_overflow() {
-        // an error is not a crash!
-        let src = r#"
+        // an error is not a crash! Integer literals are kept as written in the
+        // CST, and ones which are out of range are rejected when converting to
+        // the AST; see `cst_to_ast::tests::test_neg`
+        assert_parse_succeeds(
+            parse_expr,
+            r#"
             principal == -5555555555555555555555
-        "#;
-        let errs = assert_parse_fails(parse_expr, src);
-        expect_exactly_one_error(
-            src,
-            &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "integer parse error: number too large to fit in target type",
-            )
-            .exactly_one_underline("5555555555555555555555")
-            .build(),
+        "#,
         );
-        let src = r#"
+        assert_parse_succeeds(
+            parse_expr,
+            r#"
             principal == 5555555555555555555555
-        "#;
-        let errs = assert_parse_fails(parse_expr, src);
-        expect_exactly_one_error(
-            src,
-            &errs,
-            &ExpectedErrorMessageBuilder::error(
-                "integer parse error: number too large to fit in target type",
-            )
-            .exactly_one_underline("5555555555555555555555")
-            .build(),
+        "#,
         );
     }
 
@@ -1068,7 +1269,13 @@ mod tests {
             permit(principal:p,action:a,resource:r)when{w}unless{u}advice{"doit"};
             "#;
         let policies = POLICIES_PARSER
-            .parse(&mut Vec::new(), &Arc::from(src), false, src)
+            .parse(
+                &mut Vec::new(),
+                &Loc::new(0..0, Arc::from(src)),
+                false,
+                &LimitCounter::new(ParseLimits::unlimited()),
+                src,
+            )
             .expect("parser error")
             .node
             .expect("no data");
@@ -15,6 +15,7 @@
  */
 
 use crate::ast::PatternElem;
+use crate::parser::Loc;
 use itertools::Itertools;
 use miette::Diagnostic;
 use nonempty::NonEmpty;
@@ -79,19 +80,82 @@ pub(crate) fn to_pattern(s: &str) -> Result<Vec<PatternElem>, NonEmpty<UnescapeE
 }
 
 /// Errors generated when processing escapes
-#[derive(Debug, Diagnostic, Error, PartialEq, Eq)]
+#[derive(Debug, Error, PartialEq, Eq)]
 pub struct UnescapeError {
     /// underlying EscapeError
     err: EscapeError,
     /// copy of the input string which had the error
-    #[source_code]
     input: String,
     /// Range of the input string where the error occurred
     /// This range must be within the length of `input`
-    #[label]
     range: Range<usize>,
 }
 
+impl Diagnostic for UnescapeError {
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.input as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(miette::LabeledSpan::underline(
+            self.range.clone(),
+        ))))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        let help = match self.err {
+            EscapeError::LoneSurrogateUnicodeEscape => {
+                r"unicode escapes for surrogate code points, `\u{D800}` through `\u{DFFF}`, are not allowed"
+            }
+            EscapeError::OutOfRangeUnicodeEscape => r"unicode escapes must be at most `\u{10FFFF}`",
+            EscapeError::OverlongUnicodeEscape => {
+                r"unicode escapes must have at most six hexadecimal digits, e.g., `\u{10FFFF}`"
+            }
+            EscapeError::EmptyUnicodeEscape => {
+                r"unicode escapes must have at least one hexadecimal digit, e.g., `\u{41}`"
+            }
+            EscapeError::NoBraceInUnicodeEscape => {
+                r"the hexadecimal digits of a unicode escape must be enclosed in braces, e.g., `\u{41}`"
+            }
+            EscapeError::UnclosedUnicodeEscape => "unicode escapes must end with `}`",
+            EscapeError::InvalidCharInUnicodeEscape
+            | EscapeError::LeadingUnderscoreUnicodeEscape => {
+                r"unicode escapes may only contain hexadecimal digits, e.g., `\u{41}`"
+            }
+            EscapeError::OutOfRangeHexEscape => {
+                r"`\x` escapes must be at most `\x7F`; use a unicode escape such as `\u{FF}` for other characters"
+            }
+            EscapeError::TooShortHexEscape | EscapeError::InvalidCharInHexEscape => {
+                r"`\x` escapes must have exactly two hexadecimal digits, e.g., `\x41`"
+            }
+            EscapeError::InvalidEscape | EscapeError::LoneSlash => {
+                r#"the valid escapes are `\n`, `\r`, `\t`, `\\`, `\0`, `\'`, `\"`, `\x` with two hexadecimal digits, `\u{...}` with one to six hexadecimal digits, and `\*` in the pattern of a `like` expression"#
+            }
+            _ => return None,
+        };
+        Some(Box::new(help))
+    }
+}
+
+impl UnescapeError {
+    /// Get the location of the invalid escape, given the location `lit_loc` of
+    /// the literal which contained it. `lit_loc` must end with the closing `"`
+    /// of the string, and returns `None` if it does not.
+    pub(crate) fn escape_loc(&self, lit_loc: &Loc) -> Option<Loc> {
+        let content_end = lit_loc.end().checked_sub(1)?;
+        if lit_loc.src.get(content_end..lit_loc.end()) != Some("\"") {
+            return None;
+        }
+        let content_start = content_end.checked_sub(self.input.len())?;
+        if content_start <= lit_loc.start()
+            || lit_loc.src.get(content_start..content_end) != Some(self.input.as_str())
+        {
+            return None;
+        }
+        Some(lit_loc.span(content_start + self.range.start..content_start + self.range.end))
+    }
+}
+
 impl Clone for UnescapeError {
     fn clone(&self) -> Self {
         Self {
@@ -142,7 +142,8 @@ impl From<est::FromJsonError> for PstConstructionError {
                 description: e.to_string(),
             }
             .into(),
-            est::FromJsonError::InvalidSlotName => {
+            est::FromJsonError::InvalidSlotName
+            | est::FromJsonError::IntegerLiteralOutOfRange { .. } => {
                 error_body::ParsingFailedError::new(err.to_string()).into()
             }
             est::FromJsonError::TemplateToPolicy(e) => {
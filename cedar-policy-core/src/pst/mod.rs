@@ -219,6 +219,7 @@ pub(crate) mod est_conversions;
 mod expr;
 mod policy;
 mod policy_set;
+mod visitor;
 
 pub use constraints::{ActionConstraint, EntityOrSlot, PrincipalConstraint, ResourceConstraint};
 pub use err::error_body;
@@ -228,6 +229,9 @@ pub use expr::{
 };
 pub use policy::{Clause, Effect, LinkedPolicy, Policy, PolicyID, StaticPolicy, Template};
 pub use policy_set::{PolicySet, TemplateLink};
+pub use visitor::{
+    walk_expr, walk_fold_expr, EntityUidCollector, ExprFolder, ExprVisitor, Subexpressions,
+};
 
 // Re-exported third-party types used in PST public fields and type signatures.
 // These are re-exported so that users don't need to add separate dependencies
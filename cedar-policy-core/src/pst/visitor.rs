@@ -0,0 +1,645 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Visitors for traversing and transforming PST expressions.
+//!
+//! [`ExprVisitor`] traverses an expression, and [`ExprFolder`] builds a new
+//! expression from one. Both have a method for each kind of expression, whose
+//! default implementation recurses into its subexpressions, so implementations
+//! only override the methods for the kinds of expression they care about.
+//! Kinds of expression without a dedicated method, including any added to
+//! [`Expr`] in the future, are passed to [`ExprVisitor::visit_other`] or
+//! [`ExprFolder::fold_other`], so implementations keep compiling when new kinds
+//! of expression are added.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use nonempty::NonEmpty;
+use smol_str::SmolStr;
+
+use super::constraints::{ActionConstraint, EntityOrSlot, PrincipalConstraint, ResourceConstraint};
+use super::expr::{
+    BinaryOp, EntityType, EntityUID, Expr, Literal, PatternElem, SlotId, UnaryOp, Var,
+};
+use super::policy::{Clause, Template};
+
+/// A visitor traversing a PST [`Expr`].
+///
+/// [`ExprVisitor::visit_expr`] calls the method for the kind of expression it
+/// is given. The default implementation of each of those methods visits the
+/// subexpressions in source order, so an implementation overriding one of
+/// them should call [`ExprVisitor::visit_expr`] on the subexpressions it wants
+/// to continue into.
+///
+/// ```
+/// # use cedar_policy_core::pst::*;
+/// # use std::sync::Arc;
+/// /// Count the attribute accesses in an expression
+/// struct CountGetAttr(usize);
+///
+/// impl ExprVisitor for CountGetAttr {
+///     fn visit_get_attr(&mut self, expr: &Expr, _attr: &SmolStr) {
+///         self.0 += 1;
+///         self.visit_expr(expr);
+///     }
+/// }
+///
+/// // principal.manager.name
+/// let expr = Expr::GetAttr {
+///     expr: Arc::new(Expr::GetAttr {
+///         expr: Arc::new(Expr::Var(Var::Principal)),
+///         attr: "manager".into(),
+///     }),
+///     attr: "name".into(),
+/// };
+/// let mut counter = CountGetAttr(0);
+/// counter.visit_expr(&expr);
+/// assert_eq!(counter.0, 2);
+/// ```
+pub trait ExprVisitor {
+    /// Visit an expression, calling the method for its kind
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    /// Visit a literal
+    fn visit_literal(&mut self, _lit: &Literal) {}
+
+    /// Visit a variable
+    fn visit_var(&mut self, _var: &Var) {}
+
+    /// Visit a template slot
+    fn visit_slot(&mut self, _slot: SlotId) {}
+
+    /// Visit an unknown
+    fn visit_unknown(&mut self, _name: &SmolStr) {}
+
+    /// Visit a unary operation or single argument function call
+    fn visit_unary_op(&mut self, _op: UnaryOp, expr: &Expr) {
+        self.visit_expr(expr);
+    }
+
+    /// Visit a binary operation, method call, or two argument function call
+    fn visit_binary_op(&mut self, _op: BinaryOp, left: &Expr, right: &Expr) {
+        self.visit_expr(left);
+        self.visit_expr(right);
+    }
+
+    /// Visit an attribute access
+    fn visit_get_attr(&mut self, expr: &Expr, _attr: &SmolStr) {
+        self.visit_expr(expr);
+    }
+
+    /// Visit a `has` expression
+    fn visit_has_attr(&mut self, expr: &Expr, _attrs: &NonEmpty<SmolStr>) {
+        self.visit_expr(expr);
+    }
+
+    /// Visit a `like` expression
+    fn visit_like(&mut self, expr: &Expr, _pattern: &[PatternElem]) {
+        self.visit_expr(expr);
+    }
+
+    /// Visit an `is` expression, with the expression of its `in` if it has one
+    fn visit_is(&mut self, expr: &Expr, _entity_type: &EntityType, in_expr: Option<&Expr>) {
+        self.visit_expr(expr);
+        if let Some(in_expr) = in_expr {
+            self.visit_expr(in_expr);
+        }
+    }
+
+    /// Visit an `if-then-else` expression
+    fn visit_if_then_else(&mut self, cond: &Expr, then_expr: &Expr, else_expr: &Expr) {
+        self.visit_expr(cond);
+        self.visit_expr(then_expr);
+        self.visit_expr(else_expr);
+    }
+
+    /// Visit a set literal
+    fn visit_set(&mut self, elements: &[Arc<Expr>]) {
+        for element in elements {
+            self.visit_expr(element);
+        }
+    }
+
+    /// Visit a record literal
+    fn visit_record(&mut self, fields: &BTreeMap<String, Arc<Expr>>) {
+        for value in fields.values() {
+            self.visit_expr(value);
+        }
+    }
+
+    /// Visit an expression of a kind without a dedicated method. Kinds of
+    /// expression added in the future are visited with this method until they
+    /// get their own.
+    fn visit_other(&mut self, _expr: &Expr) {}
+}
+
+/// Call the method of `visitor` for the kind of `expr`. This is the default
+/// implementation of [`ExprVisitor::visit_expr`], for use by implementations
+/// overriding it to do something for every expression.
+pub fn walk_expr<V: ExprVisitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Literal(lit) => visitor.visit_literal(lit),
+        Expr::Var(var) => visitor.visit_var(var),
+        Expr::Slot(slot) => visitor.visit_slot(*slot),
+        Expr::Unknown { name } => visitor.visit_unknown(name),
+        Expr::UnaryOp { op, expr } => visitor.visit_unary_op(*op, expr),
+        Expr::BinaryOp { op, left, right } => visitor.visit_binary_op(*op, left, right),
+        Expr::GetAttr { expr, attr } => visitor.visit_get_attr(expr, attr),
+        Expr::HasAttr { expr, attrs } => visitor.visit_has_attr(expr, attrs),
+        Expr::Like { expr, pattern } => visitor.visit_like(expr, pattern),
+        Expr::Is {
+            expr,
+            entity_type,
+            in_expr,
+        } => visitor.visit_is(expr, entity_type, in_expr.as_deref()),
+        Expr::IfThenElse {
+            cond,
+            then_expr,
+            else_expr,
+        } => visitor.visit_if_then_else(cond, then_expr, else_expr),
+        Expr::Set(elements) => visitor.visit_set(elements),
+        Expr::Record(fields) => visitor.visit_record(fields),
+        #[cfg(feature = "tpe")]
+        Expr::ResidualError => visitor.visit_other(expr),
+    }
+}
+
+/// A folder building a new PST [`Expr`] from an existing one, e.g., to rewrite
+/// some kinds of expression.
+///
+/// [`ExprFolder::fold_expr`] calls the method for the kind of expression it is
+/// given. The default implementation of each of those methods rebuilds the
+/// expression from its folded subexpressions, so an implementation overriding
+/// one of them should call [`ExprFolder::fold_expr`] on the subexpressions it
+/// wants to continue into.
+///
+/// ```
+/// # use cedar_policy_core::pst::*;
+/// # use std::sync::Arc;
+/// /// Rename every access of a `user` attribute to `principal`
+/// struct RenameAttr;
+///
+/// impl ExprFolder for RenameAttr {
+///     fn fold_get_attr(&mut self, expr: &Expr, attr: &SmolStr) -> Expr {
+///         let attr = if attr == "user" { "principal".into() } else { attr.clone() };
+///         Expr::GetAttr {
+///             expr: Arc::new(self.fold_expr(expr)),
+///             attr,
+///         }
+///     }
+/// }
+///
+/// let expr = Expr::GetAttr {
+///     expr: Arc::new(Expr::Var(Var::Context)),
+///     attr: "user".into(),
+/// };
+/// assert_eq!(RenameAttr.fold_expr(&expr).to_string(), "context.principal");
+/// ```
+pub trait ExprFolder {
+    /// Fold an expression, calling the method for its kind
+    fn fold_expr(&mut self, expr: &Expr) -> Expr {
+        walk_fold_expr(self, expr)
+    }
+
+    /// Fold a literal
+    fn fold_literal(&mut self, lit: &Literal) -> Expr {
+        Expr::Literal(lit.clone())
+    }
+
+    /// Fold a variable
+    fn fold_var(&mut self, var: &Var) -> Expr {
+        Expr::Var(var.clone())
+    }
+
+    /// Fold a template slot
+    fn fold_slot(&mut self, slot: SlotId) -> Expr {
+        Expr::Slot(slot)
+    }
+
+    /// Fold an unknown
+    fn fold_unknown(&mut self, name: &SmolStr) -> Expr {
+        Expr::Unknown { name: name.clone() }
+    }
+
+    /// Fold a unary operation or single argument function call
+    fn fold_unary_op(&mut self, op: UnaryOp, expr: &Expr) -> Expr {
+        Expr::UnaryOp {
+            op,
+            expr: Arc::new(self.fold_expr(expr)),
+        }
+    }
+
+    /// Fold a binary operation, method call, or two argument function call
+    fn fold_binary_op(&mut self, op: BinaryOp, left: &Expr, right: &Expr) -> Expr {
+        Expr::BinaryOp {
+            op,
+            left: Arc::new(self.fold_expr(left)),
+            right: Arc::new(self.fold_expr(right)),
+        }
+    }
+
+    /// Fold an attribute access
+    fn fold_get_attr(&mut self, expr: &Expr, attr: &SmolStr) -> Expr {
+        Expr::GetAttr {
+            expr: Arc::new(self.fold_expr(expr)),
+            attr: attr.clone(),
+        }
+    }
+
+    /// Fold a `has` expression
+    fn fold_has_attr(&mut self, expr: &Expr, attrs: &NonEmpty<SmolStr>) -> Expr {
+        Expr::HasAttr {
+            expr: Arc::new(self.fold_expr(expr)),
+            attrs: attrs.clone(),
+        }
+    }
+
+    /// Fold a `like` expression
+    fn fold_like(&mut self, expr: &Expr, pattern: &[PatternElem]) -> Expr {
+        Expr::Like {
+            expr: Arc::new(self.fold_expr(expr)),
+            pattern: pattern.to_vec(),
+        }
+    }
+
+    /// Fold an `is` expression, with the expression of its `in` if it has one
+    fn fold_is(&mut self, expr: &Expr, entity_type: &EntityType, in_expr: Option<&Expr>) -> Expr {
+        Expr::Is {
+            expr: Arc::new(self.fold_expr(expr)),
+            entity_type: entity_type.clone(),
+            in_expr: in_expr.map(|in_expr| Arc::new(self.fold_expr(in_expr))),
+        }
+    }
+
+    /// Fold an `if-then-else` expression
+    fn fold_if_then_else(&mut self, cond: &Expr, then_expr: &Expr, else_expr: &Expr) -> Expr {
+        Expr::IfThenElse {
+            cond: Arc::new(self.fold_expr(cond)),
+            then_expr: Arc::new(self.fold_expr(then_expr)),
+            else_expr: Arc::new(self.fold_expr(else_expr)),
+        }
+    }
+
+    /// Fold a set literal
+    fn fold_set(&mut self, elements: &[Arc<Expr>]) -> Expr {
+        Expr::Set(
+            elements
+                .iter()
+                .map(|element| Arc::new(self.fold_expr(element)))
+                .collect(),
+        )
+    }
+
+    /// Fold a record literal
+    fn fold_record(&mut self, fields: &BTreeMap<String, Arc<Expr>>) -> Expr {
+        Expr::Record(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), Arc::new(self.fold_expr(value))))
+                .collect(),
+        )
+    }
+
+    /// Fold an expression of a kind without a dedicated method, which is
+    /// returned unchanged by default. Kinds of expression added in the future
+    /// are folded with this method until they get their own.
+    fn fold_other(&mut self, expr: &Expr) -> Expr {
+        expr.clone()
+    }
+}
+
+/// Call the method of `folder` for the kind of `expr`. This is the default
+/// implementation of [`ExprFolder::fold_expr`], for use by implementations
+/// overriding it to do something for every expression.
+pub fn walk_fold_expr<F: ExprFolder + ?Sized>(folder: &mut F, expr: &Expr) -> Expr {
+    match expr {
+        Expr::Literal(lit) => folder.fold_literal(lit),
+        Expr::Var(var) => folder.fold_var(var),
+        Expr::Slot(slot) => folder.fold_slot(*slot),
+        Expr::Unknown { name } => folder.fold_unknown(name),
+        Expr::UnaryOp { op, expr } => folder.fold_unary_op(*op, expr),
+        Expr::BinaryOp { op, left, right } => folder.fold_binary_op(*op, left, right),
+        Expr::GetAttr { expr, attr } => folder.fold_get_attr(expr, attr),
+        Expr::HasAttr { expr, attrs } => folder.fold_has_attr(expr, attrs),
+        Expr::Like { expr, pattern } => folder.fold_like(expr, pattern),
+        Expr::Is {
+            expr,
+            entity_type,
+            in_expr,
+        } => folder.fold_is(expr, entity_type, in_expr.as_deref()),
+        Expr::IfThenElse {
+            cond,
+            then_expr,
+            else_expr,
+        } => folder.fold_if_then_else(cond, then_expr, else_expr),
+        Expr::Set(elements) => folder.fold_set(elements),
+        Expr::Record(fields) => folder.fold_record(fields),
+        #[cfg(feature = "tpe")]
+        Expr::ResidualError => folder.fold_other(expr),
+    }
+}
+
+/// Iterator over an expression and its subexpressions, in pre-order, returned
+/// by [`Expr::subexpressions`]
+#[derive(Debug, Clone)]
+pub struct Subexpressions<'a> {
+    /// Expressions still to be returned, the next one last
+    stack: Vec<&'a Expr>,
+}
+
+impl<'a> Iterator for Subexpressions<'a> {
+    type Item = &'a Expr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let expr = self.stack.pop()?;
+        // Push the subexpressions in reverse, so they are returned in order
+        match expr {
+            Expr::Literal(_) | Expr::Var(_) | Expr::Slot(_) | Expr::Unknown { .. } => (),
+            #[cfg(feature = "tpe")]
+            Expr::ResidualError => (),
+            Expr::UnaryOp { expr, .. }
+            | Expr::GetAttr { expr, .. }
+            | Expr::HasAttr { expr, .. }
+            | Expr::Like { expr, .. } => self.stack.push(expr),
+            Expr::BinaryOp { left, right, .. } => {
+                self.stack.push(right);
+                self.stack.push(left);
+            }
+            Expr::Is { expr, in_expr, .. } => {
+                self.stack.extend(in_expr.as_deref());
+                self.stack.push(expr);
+            }
+            Expr::IfThenElse {
+                cond,
+                then_expr,
+                else_expr,
+            } => {
+                self.stack.push(else_expr);
+                self.stack.push(then_expr);
+                self.stack.push(cond);
+            }
+            Expr::Set(elements) => self.stack.extend(elements.iter().rev().map(Arc::as_ref)),
+            Expr::Record(fields) => self.stack.extend(fields.values().rev().map(Arc::as_ref)),
+        }
+        Some(expr)
+    }
+}
+
+impl Expr {
+    /// Get an iterator over this expression and all of its subexpressions, in
+    /// pre-order
+    pub fn subexpressions(&self) -> Subexpressions<'_> {
+        Subexpressions { stack: vec![self] }
+    }
+
+    /// Get an iterator over the literals in this expression, in pre-order
+    pub fn literals(&self) -> impl Iterator<Item = &Literal> {
+        self.subexpressions().filter_map(|expr| match expr {
+            Expr::Literal(lit) => Some(lit),
+            _ => None,
+        })
+    }
+}
+
+/// An [`ExprVisitor`] collecting the entity UIDs referenced in expressions
+/// and, with [`EntityUidCollector::visit_template`], in policy scopes.
+///
+/// Each entity UID is collected once, in the order it is first referenced.
+///
+/// ```
+/// # use cedar_policy_core::pst::*;
+/// # use std::sync::Arc;
+/// let alice = EntityUID {
+///     ty: EntityType::from_name(Name::unqualified("User").unwrap()),
+///     eid: "alice".into(),
+/// };
+/// // principal == User::"alice"
+/// let expr = Expr::BinaryOp {
+///     op: BinaryOp::Eq,
+///     left: Arc::new(Expr::Var(Var::Principal)),
+///     right: Arc::new(Expr::Literal(Literal::EntityUID(alice.clone()))),
+/// };
+/// let mut collector = EntityUidCollector::new();
+/// collector.visit_expr(&expr);
+/// assert_eq!(collector.into_uids(), vec![alice]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EntityUidCollector {
+    uids: Vec<EntityUID>,
+    seen: HashSet<EntityUID>,
+}
+
+impl EntityUidCollector {
+    /// Create a collector which has not collected any entity UIDs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the entity UIDs in the scope and conditions of a template
+    pub fn visit_template(&mut self, template: &Template) {
+        match &template.principal {
+            PrincipalConstraint::Eq(EntityOrSlot::Entity(uid))
+            | PrincipalConstraint::In(EntityOrSlot::Entity(uid))
+            | PrincipalConstraint::IsIn(_, EntityOrSlot::Entity(uid)) => self.add(uid),
+            PrincipalConstraint::Any
+            | PrincipalConstraint::Is(_)
+            | PrincipalConstraint::Eq(EntityOrSlot::Slot(_))
+            | PrincipalConstraint::In(EntityOrSlot::Slot(_))
+            | PrincipalConstraint::IsIn(_, EntityOrSlot::Slot(_)) => (),
+        }
+        match &template.action {
+            ActionConstraint::Any => (),
+            ActionConstraint::Eq(uid) => self.add(uid),
+            ActionConstraint::In(uids) => uids.iter().for_each(|uid| self.add(uid)),
+        }
+        match &template.resource {
+            ResourceConstraint::Eq(EntityOrSlot::Entity(uid))
+            | ResourceConstraint::In(EntityOrSlot::Entity(uid))
+            | ResourceConstraint::IsIn(_, EntityOrSlot::Entity(uid)) => self.add(uid),
+            ResourceConstraint::Any
+            | ResourceConstraint::Is(_)
+            | ResourceConstraint::Eq(EntityOrSlot::Slot(_))
+            | ResourceConstraint::In(EntityOrSlot::Slot(_))
+            | ResourceConstraint::IsIn(_, EntityOrSlot::Slot(_)) => (),
+        }
+        for clause in template.clauses() {
+            match clause {
+                Clause::When(expr) | Clause::Unless(expr) => self.visit_expr(expr),
+            }
+        }
+    }
+
+    /// Get the entity UIDs collected so far
+    pub fn uids(&self) -> &[EntityUID] {
+        &self.uids
+    }
+
+    /// Get the collected entity UIDs
+    pub fn into_uids(self) -> Vec<EntityUID> {
+        self.uids
+    }
+
+    fn add(&mut self, uid: &EntityUID) {
+        if self.seen.insert(uid.clone()) {
+            self.uids.push(uid.clone());
+        }
+    }
+}
+
+impl ExprVisitor for EntityUidCollector {
+    fn visit_literal(&mut self, lit: &Literal) {
+        if let Literal::EntityUID(uid) = lit {
+            self.add(uid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn parse_expr(s: &str) -> Expr {
+        parser::parse_expr(s)
+            .expect("parse failed")
+            .try_into()
+            .expect("ast -> pst conversion failed")
+    }
+
+    #[test]
+    fn visitor_visits_every_expression() {
+        /// Record the kind of each expression visited
+        #[derive(Default)]
+        struct Kinds(Vec<String>);
+        impl ExprVisitor for Kinds {
+            fn visit_expr(&mut self, expr: &Expr) {
+                self.0.push(expr.to_string());
+                walk_expr(self, expr);
+            }
+        }
+
+        let expr = parse_expr(
+            r#"if principal is User in [Group::"a"] then {x: context.y like "*"} else !(resource has z)"#,
+        );
+        let mut kinds = Kinds::default();
+        kinds.visit_expr(&expr);
+        assert_eq!(
+            kinds.0,
+            expr.subexpressions()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+        // `is ... in` is represented as an `is` and an `in` operation
+        assert_eq!(kinds.0.len(), 15);
+    }
+
+    #[test]
+    fn overridden_method_controls_recursion() {
+        /// Count the `&&` operations, without looking inside `||` operations
+        struct CountAnds(usize);
+        impl ExprVisitor for CountAnds {
+            fn visit_binary_op(&mut self, op: BinaryOp, left: &Expr, right: &Expr) {
+                match op {
+                    BinaryOp::And => self.0 += 1,
+                    BinaryOp::Or => return,
+                    _ => (),
+                }
+                self.visit_expr(left);
+                self.visit_expr(right);
+            }
+        }
+
+        let mut counter = CountAnds(0);
+        counter.visit_expr(&parse_expr(
+            "(context.a && context.b) && (context.c || (context.d && context.e))",
+        ));
+        assert_eq!(counter.0, 2);
+    }
+
+    #[test]
+    fn folder_rewrites_expressions() {
+        /// Replace `context` with `principal`, and add one to every integer
+        struct Rewrite;
+        impl ExprFolder for Rewrite {
+            fn fold_var(&mut self, var: &Var) -> Expr {
+                match var {
+                    Var::Context => Expr::Var(Var::Principal),
+                    _ => Expr::Var(var.clone()),
+                }
+            }
+
+            fn fold_literal(&mut self, lit: &Literal) -> Expr {
+                match lit {
+                    Literal::Long(i) => Expr::Literal(Literal::Long(i + 1)),
+                    _ => Expr::Literal(lit.clone()),
+                }
+            }
+        }
+
+        let expr = parse_expr(r#"[context.a, {b: 1}, context.c like "*"] == 2"#);
+        assert_eq!(
+            Rewrite.fold_expr(&expr),
+            parse_expr(r#"[principal.a, {b: 2}, principal.c like "*"] == 3"#)
+        );
+
+        // The default folder rebuilds the same expression
+        struct Identity;
+        impl ExprFolder for Identity {}
+        assert_eq!(Identity.fold_expr(&expr), expr);
+    }
+
+    #[test]
+    fn literals() {
+        let expr = parse_expr(r#"[1, "a", true].contains(User::"b") && principal.c == 2"#);
+        assert_eq!(
+            expr.literals()
+                .map(|lit| Expr::Literal(lit.clone()).to_string())
+                .collect::<Vec<_>>(),
+            vec!["1", r#""a""#, "true", r#"User::"b""#, "2"]
+        );
+    }
+
+    #[test]
+    fn collect_entity_uids() {
+        let template = parser::parse_policy_or_template(
+            None,
+            r#"permit(principal in Group::"a", action in [Action::"b", Action::"c"], resource == ?resource)
+            when { context.owner == User::"d" || context.group == Group::"a" }
+            unless { resource in [Folder::"e"] };"#,
+        )
+        .unwrap();
+        let template: Template = template.try_into().unwrap();
+        let mut collector = EntityUidCollector::new();
+        collector.visit_template(&template);
+        assert_eq!(
+            collector
+                .uids()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                r#"Group::"a""#,
+                r#"Action::"b""#,
+                r#"Action::"c""#,
+                r#"User::"d""#,
+                r#"Folder::"e""#,
+            ]
+        );
+    }
+}
@@ -33,29 +33,54 @@
     )
 )]
 
-use crate::ast::{Policy, PolicySet, Template};
+use crate::ast::{
+    ActionConstraint, BinaryOp, Effect, Expr, ExprKind, Literal, PatternElem, Policy, PolicySet,
+    PrincipalOrResourceConstraint, Template, UnaryOp,
+};
+use crate::extensions::ExtStyles;
+use crate::parser::{self, Loc};
+use miette::Diagnostic;
 use std::collections::HashSet;
+use std::sync::Arc;
 mod level_validate;
 
+mod compatibility;
+mod completions;
+pub use compatibility::{CompatibilityReport, CompatibilityRule, Incompatibility};
+pub use completions::{Completion, CompletionKind};
 mod coreschema;
 #[cfg(feature = "entity-manifest")]
 pub mod entity_manifest;
 pub use coreschema::*;
 mod diagnostics;
 pub use diagnostics::*;
+mod entity_validation;
+pub use entity_validation::{EntityValidationError, EntityValidationResult};
+mod example_data;
+pub use example_data::ExampleDataError;
 mod expr_iterator;
 mod extension_schema;
+pub use extension_schema::ExtensionFunctionType;
 mod extensions;
+pub use extensions::ExtensionInitializationError;
+mod minimize;
 mod rbac;
+mod requirements;
+pub use requirements::{AttributeRequirement, SchemaRequirements};
 mod schema;
+mod stats;
 pub use schema::err::*;
 pub use schema::*;
+pub use stats::{UsageStats, ValidationStats};
+mod typed_policy;
+pub use typed_policy::TypedPolicy;
 mod deprecated_schema_compat;
 pub mod json_schema;
 mod str_checks;
 pub use str_checks::confusable_string_checks;
 pub mod cedar_schema;
 pub mod typecheck;
+use extensions::ExtensionSchemas;
 use typecheck::Typechecker;
 mod partition_nonempty;
 pub mod types;
@@ -101,12 +126,111 @@ impl ValidationMode {
 #[derive(Debug, Clone)]
 pub struct Validator {
     schema: ValidatorSchema,
+    /// Whether to warn when validating a policy set with no policies
+    empty_policy_set_warning: bool,
+    /// Whether to warn about `permit` policies with no scope constraints and
+    /// no conditions
+    overly_permissive_policy_warning: bool,
+    /// Whether to warn about expressions which mix `&&` and `||`, or apply `!`
+    /// to the operand of a comparison, without parentheses
+    ambiguous_precedence_warning: bool,
+    /// Whether to also report type errors in parts of a policy which are never
+    /// evaluated because of short circuiting
+    unreachable_type_errors: bool,
+    /// Signatures of custom extension functions, in addition to those of the
+    /// built-in extensions
+    extension_functions: Vec<Arc<ExtensionFunctionType>>,
 }
 
 impl Validator {
     /// Construct a new Validator from a schema file.
     pub fn new(schema: ValidatorSchema) -> Validator {
-        Self { schema }
+        Self {
+            schema,
+            empty_policy_set_warning: false,
+            overly_permissive_policy_warning: false,
+            ambiguous_precedence_warning: false,
+            unreachable_type_errors: false,
+            extension_functions: Vec::new(),
+        }
+    }
+
+    /// Construct a Validator without a schema, for use with
+    /// [`Validator::lint()`]. Its [`Validator::schema()`] is empty, so
+    /// [`Validator::validate()`] will report every entity type and action as
+    /// unrecognized.
+    pub fn without_schema() -> Validator {
+        Self::new(ValidatorSchema::empty())
+    }
+
+    /// Enable or disable the [`PolicySetWarning::EmptyPolicySet`] warning,
+    /// which is reported when validating a policy set containing no policies
+    /// or templates. It is disabled by default.
+    pub fn with_empty_policy_set_warning(mut self, enabled: bool) -> Validator {
+        self.empty_policy_set_warning = enabled;
+        self
+    }
+
+    /// Enable or disable the [`ValidationWarning::OverlyPermissivePolicy`]
+    /// warning, which is reported for each `permit` policy with no scope
+    /// constraints and no conditions. It is disabled by default.
+    pub fn with_overly_permissive_policy_warning(mut self, enabled: bool) -> Validator {
+        self.overly_permissive_policy_warning = enabled;
+        self
+    }
+
+    /// Enable or disable the [`ValidationWarning::AmbiguousPrecedence`]
+    /// warning, which is reported for each expression which mixes `&&` and
+    /// `||`, or applies `!` to the left operand of a comparison, without
+    /// parentheses. It is disabled by default.
+    pub fn with_ambiguous_precedence_warning(mut self, enabled: bool) -> Validator {
+        self.ambiguous_precedence_warning = enabled;
+        self
+    }
+
+    /// Enable or disable reporting type errors in parts of a policy which are
+    /// never evaluated, for any request, because the validator knows from the
+    /// schema that an earlier operand of `&&` or `||`, or the guard of an
+    /// `if`, is always `true` or always `false`. For example, in
+    /// `resource is Albm && context.x`, `context.x` is not typechecked if
+    /// `Albm` is not an entity type, so fixing the misspelling reveals a
+    /// second error if `context` has no attribute `x`. With this option, both
+    /// are reported by the same validation run. Errors which only occur in
+    /// some request environments, e.g., an access to an attribute of
+    /// `principal` guarded by `principal is User`, are still not reported. It
+    /// is disabled by default.
+    pub fn with_unreachable_type_errors(mut self, enabled: bool) -> Validator {
+        self.unreachable_type_errors = enabled;
+        self
+    }
+
+    /// Add the signature of a custom extension function, so that calls to it
+    /// are typechecked against `signature` rather than reported as calls to an
+    /// undefined extension function. Returns an error if an extension function
+    /// with the same name is already defined, either by a built-in extension
+    /// or by a previous call to this method.
+    pub fn with_extension(
+        mut self,
+        signature: ExtensionFunctionType,
+    ) -> std::result::Result<Validator, ExtensionInitializationError> {
+        if self
+            .extension_schemas()
+            .func_type(signature.name())
+            .is_some()
+        {
+            return Err(ExtensionInitializationError::func_multiply_defined(
+                signature.name().clone(),
+            ));
+        }
+        self.extension_functions.push(Arc::new(signature));
+        Ok(self)
+    }
+
+    /// Get the signatures of all extension functions available to policies
+    /// validated by this `Validator`
+    fn extension_schemas(&self) -> ExtensionSchemas<'_> {
+        ExtensionSchemas::all_available()
+            .with_function_types(self.extension_functions.iter().map(AsRef::as_ref))
     }
 
     /// Get the `ValidatorSchema` this `Validator` is using.
@@ -116,6 +240,9 @@ impl Validator {
 
     /// Validate all templates, links, and static policies in a policy set.
     /// Return a `ValidationResult`.
+    ///
+    /// Warnings found in a policy or template whose `@suppress` annotation
+    /// lists their code are not reported, here and in [`Validator::lint()`].
     pub fn validate(&self, policies: &PolicySet, mode: ValidationMode) -> ValidationResult {
         let validate_policy_results: (Vec<_>, Vec<_>) = policies
             .all_templates()
@@ -130,8 +257,36 @@ impl Validator {
         ValidationResult::new(
             template_and_static_policy_errs.chain(link_errs),
             template_and_static_policy_warnings
-                .chain(confusable_string_checks(policies.all_templates())),
+                .chain(confusable_string_checks(policies.all_templates()))
+                .chain(self.check_overly_permissive_policies(policies))
+                .chain(Self::check_ambiguous_attribute_names(policies))
+                .chain(Self::check_suspicious_like_patterns(policies))
+                .chain(self.check_ambiguous_precedence(policies)),
+        )
+        .with_policy_set_warnings(self.check_empty_policy_set(policies))
+        .with_policies(policies)
+        .without_suppressed_warnings(policies)
+    }
+
+    /// Run only the checks which do not require a schema, i.e., the string and
+    /// identifier safety checks, the [`ValidationWarning::AmbiguousAttributeName`],
+    /// [`ValidationWarning::SuspiciousLikePattern`] and
+    /// [`ValidationWarning::AmbiguousPrecedence`] checks, and the
+    /// [`PolicySetWarning::EmptyPolicySet`] check, skipping the typechecker. The schema of this validator, if any,
+    /// is ignored, so the result never contains errors about types or about
+    /// entity types and actions which are not in the schema.
+    pub fn lint(&self, policies: &PolicySet) -> ValidationResult {
+        ValidationResult::new(
+            std::iter::empty(),
+            confusable_string_checks(policies.all_templates())
+                .chain(self.check_overly_permissive_policies(policies))
+                .chain(Self::check_ambiguous_attribute_names(policies))
+                .chain(Self::check_suspicious_like_patterns(policies))
+                .chain(self.check_ambiguous_precedence(policies)),
         )
+        .with_policy_set_warnings(self.check_empty_policy_set(policies))
+        .with_policies(policies)
+        .without_suppressed_warnings(policies)
     }
 
     /// Validate all templates, links, and static policies in a policy set.
@@ -157,8 +312,210 @@ impl Validator {
         ValidationResult::new(
             template_and_static_policy_errs.chain(link_errs),
             template_and_static_policy_warnings
-                .chain(confusable_string_checks(policies.all_templates())),
+                .chain(confusable_string_checks(policies.all_templates()))
+                .chain(self.check_overly_permissive_policies(policies))
+                .chain(Self::check_ambiguous_attribute_names(policies))
+                .chain(Self::check_suspicious_like_patterns(policies))
+                .chain(self.check_ambiguous_precedence(policies)),
         )
+        .with_policy_set_warnings(self.check_empty_policy_set(policies))
+        .with_policies(policies)
+        .without_suppressed_warnings(policies)
+    }
+
+    /// Parse and validate the policies in `text`.
+    ///
+    /// Unlike parsing the whole policy set before validating it, a policy which
+    /// fails to parse does not prevent validating the rest. Each parse error is
+    /// reported as a [`ValidationError::ParseError`] for the failing policy,
+    /// alongside the errors and warnings for the policies which did parse.
+    /// Policies are given the same numbered ids as when parsing with
+    /// [`crate::parser::parse_policyset`].
+    pub fn validate_policies_str(&self, text: &str, mode: ValidationMode) -> ValidationResult {
+        let (policies, parse_errs) = parser::parse_policyset_recovering(text);
+        let src: Arc<str> = Arc::from(text);
+        let parse_errs = parse_errs
+            .into_iter()
+            .flat_map(|(policy_id, errs)| {
+                let src = Arc::clone(&src);
+                errs.into_iter().map(move |err| {
+                    let source_loc = err
+                        .labels()
+                        .and_then(|mut labels| labels.next())
+                        .map(|label| Loc::new(*label.inner(), Arc::clone(&src)));
+                    ValidationError::parse_error(
+                        source_loc,
+                        policy_id.clone(),
+                        err.to_string(),
+                        err.help().map(|help| help.to_string()),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        let (errors, warnings) = self.validate(&policies, mode).into_errors_and_warnings();
+        // The policy set is not really empty if some policies failed to parse
+        let policy_set_warnings = if parse_errs.is_empty() {
+            self.check_empty_policy_set(&policies)
+        } else {
+            None
+        };
+        ValidationResult::new(parse_errs.into_iter().chain(errors), warnings)
+            .with_policy_set_warnings(policy_set_warnings)
+            .with_policies(&policies)
+    }
+
+    /// Report a [`PolicySetWarning::EmptyPolicySet`] if `policies` contains no
+    /// policies or templates and the warning is enabled.
+    fn check_empty_policy_set(&self, policies: &PolicySet) -> Option<PolicySetWarning> {
+        (self.empty_policy_set_warning && policies.all_templates().next().is_none())
+            .then(PolicySetWarning::empty_policy_set)
+    }
+
+    /// Report a [`ValidationWarning::OverlyPermissivePolicy`] for each `permit`
+    /// policy in `policies` with no scope constraints and no conditions, if the
+    /// warning is enabled.
+    fn check_overly_permissive_policies<'a>(
+        &self,
+        policies: &'a PolicySet,
+    ) -> impl Iterator<Item = ValidationWarning> + 'a {
+        let enabled = self.overly_permissive_policy_warning;
+        policies
+            .all_templates()
+            .filter(move |t| {
+                enabled
+                    && t.effect() == Effect::Permit
+                    && matches!(
+                        t.principal_constraint().as_inner(),
+                        PrincipalOrResourceConstraint::Any
+                    )
+                    && matches!(t.action_constraint(), ActionConstraint::Any)
+                    && matches!(
+                        t.resource_constraint().as_inner(),
+                        PrincipalOrResourceConstraint::Any
+                    )
+                    && t.non_scope_constraints().is_none()
+            })
+            .map(|t| ValidationWarning::overly_permissive_policy(t.loc().cloned(), t.id().clone()))
+    }
+
+    /// Report a [`ValidationWarning::AmbiguousAttributeName`] for each
+    /// attribute access in `policies` which is written with dot syntax and
+    /// names a built-in method (e.g., `principal.isEmpty`) or an extension
+    /// method (e.g., `context.ip.isLoopback`), so that it reads like a method
+    /// call missing its argument list. Accesses written with bracket syntax,
+    /// e.g., `principal["isEmpty"]`, and `has` tests are unambiguous and are
+    /// not reported. The syntax is read from the source text, so policies
+    /// without source text (e.g., parsed from JSON) are never reported.
+    fn check_ambiguous_attribute_names<'a>(
+        policies: &'a PolicySet,
+    ) -> impl Iterator<Item = ValidationWarning> + 'a {
+        policies.all_templates().flat_map(|t| {
+            t.non_scope_constraints()
+                .into_iter()
+                .flat_map(Expr::subexpressions)
+                .filter_map(move |e| match e.expr_kind() {
+                    ExprKind::GetAttr { attr, .. }
+                        if is_method_name(attr) && is_dot_access(e.source_loc(), attr) =>
+                    {
+                        Some(ValidationWarning::ambiguous_attribute_name(
+                            e.source_loc().cloned(),
+                            t.id().clone(),
+                            attr.as_str(),
+                        ))
+                    }
+                    _ => None,
+                })
+        })
+    }
+
+    /// Report a [`ValidationWarning::AmbiguousPrecedence`] for each `||` in
+    /// `policies` with an operand which is an `&&` without parentheses, and for
+    /// each comparison whose left operand is a `!` without parentheses, if the
+    /// warning is enabled. Chained comparisons, e.g., `a == b == c`, are
+    /// rejected by the parser, so need no warning. The parentheses are read
+    /// from the source text, so policies without source text (e.g., parsed
+    /// from JSON) are never reported.
+    fn check_ambiguous_precedence<'a>(
+        &self,
+        policies: &'a PolicySet,
+    ) -> impl Iterator<Item = ValidationWarning> + 'a {
+        let enabled = self.ambiguous_precedence_warning;
+        policies
+            .all_templates()
+            .filter(move |_| enabled)
+            .flat_map(|t| {
+                t.non_scope_constraints()
+                    .into_iter()
+                    .flat_map(Expr::subexpressions)
+                    .filter_map(move |e| {
+                        let loc = e.source_loc()?;
+                        let (kind, operands) = match e.expr_kind() {
+                            ExprKind::Or { left, right } => (
+                                validation_warnings::AmbiguousPrecedenceKind::MixedAndOr,
+                                [left, right]
+                                    .into_iter()
+                                    .filter(|operand| is_unparenthesized_and(operand))
+                                    .collect::<Vec<_>>(),
+                            ),
+                            ExprKind::BinaryApp {
+                                op: BinaryOp::Eq | BinaryOp::Less | BinaryOp::LessEq | BinaryOp::In,
+                                arg1,
+                                arg2,
+                            } => (
+                                validation_warnings::AmbiguousPrecedenceKind::NegatedComparisonOperand,
+                                [arg1, arg2]
+                                    .into_iter()
+                                    .filter(|operand| is_unparenthesized_not(operand, loc.start()))
+                                    .collect(),
+                            ),
+                            _ => return None,
+                        };
+                        let operand_locs = operands
+                            .into_iter()
+                            .filter_map(|operand| operand.source_loc())
+                            .collect::<Vec<_>>();
+                        if operand_locs.is_empty() {
+                            return None;
+                        }
+                        Some(ValidationWarning::ambiguous_precedence(
+                            Some(loc.clone()),
+                            t.id().clone(),
+                            parenthesize(loc, &operand_locs)?,
+                            kind,
+                        ))
+                    })
+            })
+    }
+
+    /// Report a [`ValidationWarning::SuspiciousLikePattern`] for each `like`
+    /// in `policies` whose pattern is empty or contains no wildcards, so that
+    /// it is equivalent to an `==` comparison with a string.
+    fn check_suspicious_like_patterns<'a>(
+        policies: &'a PolicySet,
+    ) -> impl Iterator<Item = ValidationWarning> + 'a {
+        policies.all_templates().flat_map(|t| {
+            t.non_scope_constraints()
+                .into_iter()
+                .flat_map(Expr::subexpressions)
+                .filter_map(move |e| match e.expr_kind() {
+                    ExprKind::Like { pattern, .. } => {
+                        let kind = if pattern.is_empty() {
+                            validation_warnings::SuspiciousLikePatternKind::Empty
+                        } else if pattern.iter().all(|elem| *elem != PatternElem::Wildcard) {
+                            validation_warnings::SuspiciousLikePatternKind::NoWildcards
+                        } else {
+                            return None;
+                        };
+                        Some(ValidationWarning::suspicious_like_pattern(
+                            e.source_loc().map(pattern_loc),
+                            t.id().clone(),
+                            pattern.to_string(),
+                            kind,
+                        ))
+                    }
+                    _ => None,
+                })
+        })
     }
 
     /// Run all validations against a single static policy or template (note
@@ -192,8 +549,18 @@ impl Validator {
         }
         .into_iter()
         .flatten();
+        let action_group_warnings = (!mode.is_partial())
+            .then(|| Validator::validate_action_groups(&self.schema, p))
+            .into_iter()
+            .flatten();
         let (errors, warnings) = self.typecheck_policy(p, mode);
-        (validation_errors.chain(errors), warnings)
+        (
+            Validator::validate_slot_positions(p)
+                .chain(Validator::validate_constant_arithmetic(p))
+                .chain(validation_errors)
+                .chain(errors),
+            warnings.chain(action_group_warnings),
+        )
     }
 
     /// Check that all entity types are defined in the schema, and each entity
@@ -209,6 +576,35 @@ impl Validator {
             .chain(Validator::validate_action_ids(schema, p))
     }
 
+    /// Generate a `MisplacedTemplateSlot` error for every slot in the
+    /// conditions of the template. Slots are only allowed in the scope, where
+    /// the AST already guarantees that each slot is in its own constraint.
+    fn validate_slot_positions(p: &Template) -> impl Iterator<Item = ValidationError> + '_ {
+        p.non_scope_constraints()
+            .into_iter()
+            .flat_map(Expr::slots)
+            .map(|slot| ValidationError::misplaced_template_slot(slot.loc, p.id().clone(), slot.id))
+    }
+
+    /// Generate an `ArithmeticOverflow` error for every arithmetic operation
+    /// in the conditions of the template whose operands are integer constants
+    /// but whose result does not fit in a 64-bit integer. Operations on
+    /// anything else are never reported, since whether they overflow depends
+    /// on the request.
+    fn validate_constant_arithmetic(p: &Template) -> impl Iterator<Item = ValidationError> + '_ {
+        p.non_scope_constraints()
+            .into_iter()
+            .flat_map(Expr::subexpressions)
+            .filter(|e| constant_arithmetic(e) == Some(ConstantArithmetic::Overflow))
+            .map(|e| {
+                ValidationError::arithmetic_overflow(
+                    e.source_loc().cloned(),
+                    p.id().clone(),
+                    e.to_string(),
+                )
+            })
+    }
+
     /// Run relevant validations against a single template-linked policy,
     /// gathering all validation errors together in the returned iterator.
     fn validate_slots<'a>(
@@ -248,7 +644,10 @@ impl Validator {
         impl Iterator<Item = ValidationError> + 'a,
         impl Iterator<Item = ValidationWarning> + 'a,
     ) {
-        let typecheck = Typechecker::new(&self.schema, mode);
+        let extensions = self.extension_schemas();
+        let typecheck = Typechecker::new(&self.schema, mode)
+            .with_extensions(&extensions)
+            .with_unreachable_errors(self.unreachable_type_errors);
         let mut errors = HashSet::new();
         let mut warnings = HashSet::new();
         typecheck.typecheck_policy(t, &mut errors, &mut warnings);
@@ -256,8 +655,160 @@ impl Validator {
     }
 }
 
+/// Names of the methods built into the Cedar language, as opposed to
+/// extension methods
+const BUILTIN_METHODS: [&str; 6] = [
+    "contains",
+    "containsAll",
+    "containsAny",
+    "isEmpty",
+    "getTag",
+    "hasTag",
+];
+
+/// Is `name` the name of a built-in or extension method?
+fn is_method_name(name: &str) -> bool {
+    BUILTIN_METHODS.contains(&name) || ExtStyles::is_method_str(name)
+}
+
+/// Is the attribute access at `loc`, of the attribute `attr`, written with dot
+/// syntax, i.e., `e.attr` rather than `e["attr"]`?
+fn is_dot_access(loc: Option<&Loc>, attr: &str) -> bool {
+    loc.and_then(Loc::snippet)
+        .and_then(|snippet| snippet.trim_end().strip_suffix(attr))
+        .is_some_and(|before| before.trim_end().ends_with('.'))
+}
+
+/// Is `e` an `&&` expression written without parentheses around it? The
+/// parentheses and the operator are read from the source text, so that an
+/// `&&` which is not written as such, e.g., from `principal is User in
+/// Group::"g"`, is not reported.
+fn is_unparenthesized_and(e: &Expr) -> bool {
+    let ExprKind::And { right, .. } = e.expr_kind() else {
+        return false;
+    };
+    let (Some(loc), Some(right)) = (e.source_loc(), right.source_loc()) else {
+        return false;
+    };
+    loc.src
+        .get(..right.start())
+        .is_some_and(|before| before.trim_end().ends_with("&&"))
+        && !is_parenthesized(loc)
+}
+
+/// Is `e` a `!` expression which starts at `start` and is written without
+/// parentheses around it? A `!` which is not written as such, e.g., from
+/// `x != y`, is not reported.
+fn is_unparenthesized_not(e: &Expr, start: usize) -> bool {
+    matches!(
+        e.expr_kind(),
+        ExprKind::UnaryApp {
+            op: UnaryOp::Not,
+            ..
+        }
+    ) && e.source_loc().is_some_and(|loc| {
+        loc.start() == start
+            && loc.snippet().is_some_and(|s| s.starts_with('!'))
+            && !is_parenthesized(loc)
+    })
+}
+
+/// Is the source text at `loc` immediately enclosed in parentheses?
+fn is_parenthesized(loc: &Loc) -> bool {
+    let before = loc.src.get(..loc.start()).unwrap_or_default();
+    let after = loc.src.get(loc.end()..).unwrap_or_default();
+    before.trim_end().ends_with('(') && after.trim_start().starts_with(')')
+}
+
+/// The source text at `loc`, with parentheses added around each of the
+/// (non-overlapping) `operands` within it
+fn parenthesize(loc: &Loc, operands: &[&Loc]) -> Option<String> {
+    let mut text = loc.snippet()?.to_string();
+    let mut operands = operands.to_vec();
+    operands.sort_by_key(|operand| std::cmp::Reverse(operand.start()));
+    for operand in operands {
+        text.insert(operand.end().checked_sub(loc.start())?, ')');
+        text.insert(operand.start().checked_sub(loc.start())?, '(');
+    }
+    Some(text)
+}
+
+/// The location of the pattern in the `like` expression at `loc`, i.e., of the
+/// last string literal in its source text, or `loc` itself if it has no source
+/// text
+fn pattern_loc(loc: &Loc) -> Loc {
+    let mut pattern_start = None;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in loc.snippet().unwrap_or_default().char_indices() {
+        if !in_string {
+            if c == '"' {
+                in_string = true;
+                pattern_start = Some(i);
+            }
+        } else if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            in_string = false;
+        }
+    }
+    match pattern_start {
+        Some(start) => loc.span(loc.start() + start..loc.end()),
+        None => loc.clone(),
+    }
+}
+
+/// The value of `e` if it is an integer literal, or an arithmetic operation
+/// on integer literals which does not overflow
+fn constant_long(e: &Expr) -> Option<i64> {
+    match e.expr_kind() {
+        ExprKind::Lit(Literal::Long(i)) => Some(*i),
+        _ => match constant_arithmetic(e)? {
+            ConstantArithmetic::Value(i) => Some(i),
+            ConstantArithmetic::Overflow => None,
+        },
+    }
+}
+
+/// Result of an arithmetic operation whose operands are all constant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConstantArithmetic {
+    Value(i64),
+    Overflow,
+}
+
+impl From<Option<i64>> for ConstantArithmetic {
+    fn from(checked: Option<i64>) -> Self {
+        checked.map_or(Self::Overflow, Self::Value)
+    }
+}
+
+/// If `e` is an arithmetic operation whose operands are all constant, the
+/// result of the operation. `None` if `e` is anything else.
+fn constant_arithmetic(e: &Expr) -> Option<ConstantArithmetic> {
+    match e.expr_kind() {
+        ExprKind::UnaryApp {
+            op: UnaryOp::Neg,
+            arg,
+        } => constant_long(arg).map(|i| i.checked_neg().into()),
+        ExprKind::BinaryApp { op, arg1, arg2 } => {
+            let checked_op = match op {
+                BinaryOp::Add => i64::checked_add,
+                BinaryOp::Sub => i64::checked_sub,
+                BinaryOp::Mul => i64::checked_mul,
+                _ => return None,
+            };
+            Some(checked_op(constant_long(arg1)?, constant_long(arg2)?).into())
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use cool_asserts::assert_matches;
     use itertools::Itertools;
     use std::{collections::HashMap, sync::Arc};
 
@@ -270,6 +821,7 @@ mod test {
         ast::{self, PolicyID},
         est::Annotations,
         parser::{self, Loc},
+        test_utils::{expect_err, ExpectedErrorMessage, ExpectedErrorMessageBuilder},
     };
 
     use similar_asserts::assert_eq;
@@ -307,6 +859,7 @@ mod test {
                     applies_to: Some(json_schema::ApplySpec {
                         principal_types: vec!["foo_type".parse().unwrap()],
                         resource_types: vec!["bar_type".parse().unwrap()],
+                        no_resource: false,
                         context: json_schema::AttributesOrContext::default(),
                     }),
                     member_of: None,
@@ -353,6 +906,7 @@ mod test {
             Some(UnrecognizedActionIdHelp::SuggestAlternative(
                 "Action::\"action\"".to_string(),
             )),
+            None,
         );
 
         assert!(!result.validation_passed());
@@ -371,6 +925,177 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn all_warning_kinds_have_unique_codes() {
+        let kinds = all_warning_kinds();
+        assert_eq!(
+            kinds.iter().map(|kind| kind.code()).unique().count(),
+            kinds.len()
+        );
+        assert!(kinds.contains(PolicySetWarning::empty_policy_set().kind_info()));
+        assert!(kinds.contains(
+            ValidationWarning::impossible_policy(None, PolicyID::from_string("0")).kind_info()
+        ));
+    }
+
+    #[test]
+    fn case_only_mismatch_suggestions() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User, usr;
+            action View appliesTo { principal: User, resource: User };
+            "#,
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+        let policy = parser::parse_policy(
+            Some(PolicyID::from_string("0")),
+            r#"permit(principal == user::"alice", action == Action::"view", resource);"#,
+        )
+        .unwrap();
+        let mut set = PolicySet::new();
+        set.add_static(policy).unwrap();
+        let result = validator.validate(&set, ValidationMode::default());
+
+        // `user` is at edit distance 1 from both `User` and `usr`, but only
+        // the letter case is wrong for `User`
+        assert_matches!(
+            result.validation_errors().find(|e| matches!(e, ValidationError::UnrecognizedEntityType(_))),
+            Some(e) => {
+                assert_eq!(
+                    e.help().map(|help| help.to_string()).as_deref(),
+                    Some("entity types are case-sensitive; did you mean `User`?")
+                );
+            }
+        );
+        assert_matches!(
+            result.validation_errors().find(|e| matches!(e, ValidationError::UnrecognizedActionId(_))),
+            Some(e) => {
+                assert_eq!(
+                    e.help().map(|help| help.to_string()).as_deref(),
+                    Some(r#"action ids are case-sensitive; did you mean `Action::"View"`?"#)
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn misplaced_template_slots() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            "entity User; action view appliesTo { principal: User, resource: User };",
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+        let template = |id: &str, condition: Expr| {
+            Template::new(
+                PolicyID::from_string(id),
+                None,
+                ast::Annotations::new(),
+                Effect::Permit,
+                ast::PrincipalConstraint::is_eq_slot(),
+                ast::ActionConstraint::any(),
+                ast::ResourceConstraint::any(),
+                Some(condition),
+            )
+        };
+        let mut set = PolicySet::new();
+        // `?principal` is allowed in the principal scope, but not in a condition
+        set.add_template(template(
+            "principal_slot",
+            Expr::is_eq(
+                Expr::var(ast::Var::Resource),
+                Expr::slot(ast::SlotId::principal()),
+            ),
+        ))
+        .unwrap();
+        set.add_template(template(
+            "resource_slot",
+            Expr::and(
+                Expr::val(true),
+                Expr::is_eq(
+                    Expr::var(ast::Var::Principal),
+                    Expr::slot(ast::SlotId::resource()),
+                ),
+            ),
+        ))
+        .unwrap();
+        set.add_template(template("no_slot", Expr::val(true)))
+            .unwrap();
+
+        let result = validator.validate(&set, ValidationMode::default());
+        let mut misplaced = result
+            .validation_errors()
+            .filter_map(|e| match e {
+                ValidationError::MisplacedTemplateSlot(e) => {
+                    Some((e.policy_id.to_string(), e.slot.to_string()))
+                }
+                _ => None,
+            })
+            .collect_vec();
+        misplaced.sort();
+        assert_eq!(
+            misplaced,
+            vec![
+                ("principal_slot".to_string(), "?principal".to_string()),
+                ("resource_slot".to_string(), "?resource".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn constant_arithmetic_overflow() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            "entity User; action view appliesTo { principal: User, resource: User, context: { count: Long } };",
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+        let overflows = |src: &str| {
+            let mut set = PolicySet::new();
+            set.add_static(parser::parse_policy(None, src).unwrap())
+                .unwrap();
+            validator
+                .validate(&set, ValidationMode::default())
+                .validation_errors()
+                .filter_map(|e| match e {
+                    ValidationError::ArithmeticOverflow(e) => Some(e.expr.clone()),
+                    _ => None,
+                })
+                .collect_vec()
+        };
+
+        assert_eq!(
+            overflows("permit(principal, action, resource) when { 9223372036854775807 + 1 > 0 };"),
+            vec!["9223372036854775807 + 1".to_string()]
+        );
+        assert_eq!(
+            overflows("permit(principal, action, resource) when { -9223372036854775807 - 2 < 0 };"),
+            vec!["(-9223372036854775807) - 2".to_string()]
+        );
+        // Only the innermost overflowing operation is reported
+        assert_eq!(
+            overflows(
+                "permit(principal, action, resource) when { (4611686018427387904 * 2) * 2 > 0 };"
+            ),
+            vec!["4611686018427387904 * 2".to_string()]
+        );
+        // Operations which only overflow for some requests are not reported
+        assert_eq!(
+            overflows(
+                "permit(principal, action, resource) when { context.count * 1000000000000 > 0 };"
+            ),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            overflows(
+                "permit(principal, action, resource) when { 4611686018427387903 * 2 + 1 == 9223372036854775807 };"
+            ),
+            Vec::<String>::new()
+        );
+    }
+
     #[test]
     fn top_level_validate_with_links() -> Result<()> {
         let mut set = PolicySet::new();
@@ -484,6 +1209,7 @@ mod test {
             PolicyID::from_string("link2"),
             false,
             false,
+            false,
         );
 
         let actual_undef_error = result
@@ -523,6 +1249,7 @@ mod test {
             PolicyID::from_string("link3"),
             false,
             false,
+            false,
         );
         assert!(result.validation_errors().contains(&invalid_action_err));
 
@@ -580,6 +1307,574 @@ mod test {
             )]
         );
     }
+
+    /// A schema with a single action, so that policies are typechecked
+    fn photo_schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(
+            "entity Photo; action view appliesTo { principal: Photo, resource: Photo };",
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn unexpected_type_exposes_expected_and_actual() {
+        let validator = Validator::new(photo_schema());
+        let mut set = PolicySet::new();
+        let p = parser::parse_policy(
+            Some(PolicyID::from_string("policy0")),
+            r#"permit(principal, action, resource) when { 1 + "one" > 0 };"#,
+        )
+        .unwrap();
+        set.add_static(p).unwrap();
+        let result = validator.validate(&set, ValidationMode::default());
+        assert_eq!(result.validation_errors().count(), 1);
+        let err = result.validation_errors().next().unwrap();
+        assert_eq!(
+            err.expected_types(),
+            Some([Type::primitive_long()].as_slice())
+        );
+        assert_eq!(err.actual_type(), Some(&Type::primitive_string()));
+    }
+
+    #[test]
+    fn validate_warns_on_empty_policy_set() {
+        let validator = Validator::new(ValidatorSchema::empty());
+        let set = PolicySet::new();
+        let result = validator.validate(&set, ValidationMode::default());
+        assert_eq!(result.policy_set_warnings().count(), 0);
+
+        let validator = validator.with_empty_policy_set_warning(true);
+        for result in [
+            validator.validate(&set, ValidationMode::default()),
+            validator.validate_with_level(&set, ValidationMode::default(), 0),
+            validator.lint(&set),
+        ] {
+            assert!(result.validation_passed());
+            assert_eq!(result.validation_warnings().count(), 0);
+            assert_eq!(
+                result.policy_set_warnings().collect::<Vec<_>>(),
+                vec![&PolicySetWarning::empty_policy_set()]
+            );
+        }
+
+        let set = parser::parse_policyset("permit(principal, action, resource);").unwrap();
+        let result = validator.validate(&set, ValidationMode::default());
+        assert_eq!(result.policy_set_warnings().count(), 0);
+    }
+
+    #[test]
+    fn overly_permissive_policy_warning() {
+        let warned = |validator: &Validator, src: &str| {
+            let set = parser::parse_policyset(src).unwrap();
+            validator
+                .lint(&set)
+                .validation_warnings()
+                .filter_map(|w| match w {
+                    ValidationWarning::OverlyPermissivePolicy(w) => Some(w.policy_id.to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let validator = Validator::without_schema();
+        let open_permit = "permit(principal, action, resource);";
+        assert_eq!(warned(&validator, open_permit), Vec::<String>::new());
+
+        let validator = validator.with_overly_permissive_policy_warning(true);
+        assert_eq!(warned(&validator, open_permit), vec!["policy0"]);
+        for src in [
+            "forbid(principal, action, resource);",
+            r#"permit(principal == User::"alice", action, resource);"#,
+            r#"permit(principal, action == Action::"view", resource);"#,
+            r#"permit(principal, action in [Action::"view"], resource);"#,
+            "permit(principal, action, resource is Photo);",
+            "permit(principal, action, resource) when { true };",
+            "permit(principal, action, resource) unless { false };",
+            "permit(principal == ?principal, action, resource);",
+        ] {
+            assert_eq!(warned(&validator, src), Vec::<String>::new(), "{src}");
+        }
+
+        let set = parser::parse_policyset(open_permit).unwrap();
+        let result = validator.validate(&set, ValidationMode::default());
+        assert!(result
+            .validation_warnings()
+            .any(|w| matches!(w, ValidationWarning::OverlyPermissivePolicy(_))));
+    }
+
+    #[test]
+    fn ambiguous_precedence_warning() {
+        let warned = |validator: &Validator, src: &str| {
+            let set = parser::parse_policyset(src).unwrap();
+            validator
+                .lint(&set)
+                .validation_warnings()
+                .filter_map(|w| match w {
+                    ValidationWarning::AmbiguousPrecedence(w) => Some((
+                        w.source_loc
+                            .as_ref()
+                            .and_then(Loc::snippet)
+                            .unwrap()
+                            .to_string(),
+                        w.parenthesized.clone(),
+                    )),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let validator = Validator::without_schema();
+        let src =
+            "permit(principal, action, resource) when { context.a && context.b || context.c };";
+        assert_eq!(warned(&validator, src), vec![]);
+
+        let validator = validator.with_ambiguous_precedence_warning(true);
+        for (cond, region, parenthesized) in [
+            (
+                "context.a && context.b || context.c",
+                "context.a && context.b || context.c",
+                "(context.a && context.b) || context.c",
+            ),
+            (
+                "context.a || context.b && context.c && context.d",
+                "context.a || context.b && context.c && context.d",
+                "context.a || (context.b && context.c && context.d)",
+            ),
+            (
+                "context.e || (context.a && context.b || context.c)",
+                "context.a && context.b || context.c",
+                "(context.a && context.b) || context.c",
+            ),
+            (
+                "!context.a == context.b",
+                "!context.a == context.b",
+                "(!context.a) == context.b",
+            ),
+            (
+                "!context.a != context.b",
+                "!context.a != context.b",
+                "(!context.a) != context.b",
+            ),
+            (
+                "!context.a.b in principal",
+                "!context.a.b in principal",
+                "(!context.a.b) in principal",
+            ),
+        ] {
+            let src = format!("permit(principal, action, resource) when {{ {cond} }};");
+            assert_eq!(
+                warned(&validator, &src),
+                vec![(region.to_string(), parenthesized.to_string())],
+                "{cond}"
+            );
+        }
+        for cond in [
+            "(context.a && context.b) || context.c",
+            "context.a || (context.b && context.c)",
+            "context.a && context.b && context.c",
+            "context.a || context.b",
+            "!(context.a == context.b)",
+            "(!context.a) == context.b",
+            "context.a != context.b",
+            "context.a > context.b",
+            "context.a == !context.b",
+            r#"principal is User in Group::"g" || context.a"#,
+        ] {
+            let src = format!("permit(principal, action, resource) when {{ {cond} }};");
+            assert_eq!(warned(&validator, &src), vec![], "{cond}");
+        }
+
+        let src = r#"
+            @suppress("ambiguous-precedence")
+            permit(principal, action, resource) when { context.a && context.b || context.c };
+        "#;
+        assert_eq!(warned(&validator, src), vec![]);
+    }
+
+    #[test]
+    fn ambiguous_attribute_name_warning() {
+        let warned = |src: &str| {
+            let set = parser::parse_policyset(src).unwrap();
+            Validator::without_schema()
+                .lint(&set)
+                .validation_warnings()
+                .filter_map(|w| match w {
+                    ValidationWarning::AmbiguousAttributeName(w) => Some((
+                        w.attribute.clone(),
+                        w.source_loc
+                            .as_ref()
+                            .and_then(Loc::snippet)
+                            .map(str::to_owned),
+                    )),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        // built-in methods
+        assert_eq!(
+            warned("permit(principal, action, resource) when { principal.isEmpty };"),
+            vec![("isEmpty".to_string(), Some("principal.isEmpty".to_string()))]
+        );
+        assert_eq!(
+            warned("permit(principal, action, resource) when { resource.tags . contains };"),
+            vec![(
+                "contains".to_string(),
+                Some("resource.tags . contains".to_string())
+            )]
+        );
+        // extension methods
+        assert_eq!(
+            warned("permit(principal, action, resource) when { context.isLoopback == true };"),
+            vec![(
+                "isLoopback".to_string(),
+                Some("context.isLoopback".to_string())
+            )]
+        );
+        assert_eq!(
+            warned("permit(principal, action, resource) when { context.lessThan };")
+                .into_iter()
+                .map(|(attr, _)| attr)
+                .collect::<Vec<_>>(),
+            vec!["lessThan"]
+        );
+
+        for src in [
+            // bracket syntax is unambiguous
+            r#"permit(principal, action, resource) when { principal["isEmpty"] };"#,
+            r#"permit(principal, action, resource) when { context["isLoopback"] == true };"#,
+            // so are `has` tests and actual method calls
+            "permit(principal, action, resource) when { principal has isEmpty };",
+            "permit(principal, action, resource) when { principal.tags.isEmpty() };",
+            "permit(principal, action, resource) when { context.ip.isLoopback() };",
+            // attributes which are not named like a method
+            "permit(principal, action, resource) when { principal.empty };",
+            "permit(principal, action, resource) when { principal.Contains };",
+        ] {
+            assert_eq!(warned(src), vec![], "{src}");
+        }
+    }
+
+    #[test]
+    fn suspicious_like_pattern_warning() {
+        use validation_warnings::SuspiciousLikePatternKind;
+        let warned = |src: &str| {
+            let set = parser::parse_policyset(src).unwrap();
+            Validator::without_schema()
+                .lint(&set)
+                .validation_warnings()
+                .filter_map(|w| match w {
+                    ValidationWarning::SuspiciousLikePattern(w) => Some((
+                        w.kind,
+                        w.source_loc
+                            .as_ref()
+                            .and_then(Loc::snippet)
+                            .map(str::to_owned),
+                    )),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            warned(r#"permit(principal, action, resource) when { resource.name like "report" };"#),
+            vec![(
+                SuspiciousLikePatternKind::NoWildcards,
+                Some(r#""report""#.to_string())
+            )]
+        );
+        assert_eq!(
+            warned(r#"permit(principal, action, resource) when { resource.name like "" };"#),
+            vec![(SuspiciousLikePatternKind::Empty, Some(r#""""#.to_string()))]
+        );
+        // an escaped `*` is not a wildcard
+        assert_eq!(
+            warned(r#"permit(principal, action, resource) when { resource.name like "a\*b" };"#),
+            vec![(
+                SuspiciousLikePatternKind::NoWildcards,
+                Some(r#""a\*b""#.to_string())
+            )]
+        );
+        // the span is that of the pattern, even if the left-hand side contains
+        // strings
+        assert_eq!(
+            warned(
+                r#"permit(principal, action, resource) when { context["like \"x\""] like "y" };"#
+            ),
+            vec![(
+                SuspiciousLikePatternKind::NoWildcards,
+                Some(r#""y""#.to_string())
+            )]
+        );
+        // every `like` is checked
+        assert_eq!(
+            warned(
+                r#"permit(principal, action, resource) when { principal.a like "*" && !(principal.b like "b") } unless { [resource.c like ""].isEmpty() };"#
+            )
+            .into_iter()
+            .filter_map(|(_, snippet)| snippet)
+            .sorted()
+            .collect::<Vec<_>>(),
+            vec![r#""""#, r#""b""#]
+        );
+
+        for src in [
+            r#"permit(principal, action, resource) when { resource.name like "*" };"#,
+            r#"permit(principal, action, resource) when { resource.name like "report*" };"#,
+            r#"permit(principal, action, resource) when { resource.name like "a\*b*" };"#,
+            r#"permit(principal, action, resource) when { resource.name == "report" };"#,
+        ] {
+            assert_eq!(warned(src), vec![], "{src}");
+        }
+    }
+
+    #[test]
+    fn suspicious_like_pattern_warning_message() {
+        let expect_warning = |src: &str, expected: &ExpectedErrorMessage<'_>| {
+            let set = parser::parse_policyset(src).unwrap();
+            let result = Validator::without_schema().lint(&set);
+            assert_matches!(result.validation_warnings().collect::<Vec<_>>().as_slice(), [warning] => {
+                expect_err(src, &miette::Report::new((*warning).clone()), expected);
+            });
+        };
+        expect_warning(
+            r#"permit(principal, action, resource) when { resource.name like "a\*b" };"#,
+            &ExpectedErrorMessageBuilder::error(
+                r#"for policy `policy0`, the pattern `"a\*b"` contains no wildcards, so it only matches that exact string"#,
+            )
+            .help("to test for equality, use `==` instead; to match any sequence of characters, add a `*` wildcard")
+            .exactly_one_underline(r#""a\*b""#)
+            .build(),
+        );
+        expect_warning(
+            r#"permit(principal, action, resource) when { resource.name like "" };"#,
+            &ExpectedErrorMessageBuilder::error(
+                r#"for policy `policy0`, the pattern `""` is empty, so it only matches the empty string"#,
+            )
+            .help(r#"to test for the empty string, use `== ""` instead; to match any string, use the pattern `"*"`"#)
+            .exactly_one_underline(r#""""#)
+            .build(),
+        );
+    }
+
+    #[test]
+    fn suppress_annotation() {
+        let codes = |src: &str| {
+            let set = parser::parse_policyset(src).unwrap();
+            Validator::without_schema()
+                .with_overly_permissive_policy_warning(true)
+                .lint(&set)
+                .validation_warnings()
+                .map(|w| (w.policy_id().to_string(), w.kind_info().code()))
+                .collect::<Vec<_>>()
+        };
+        let src = r#"
+            @suppress("ambiguous-attribute-name")
+            permit(principal, action, resource) when { principal.isEmpty && "a\u{0430}" == "" };
+            permit(principal, action, resource) when { principal.isEmpty };
+        "#;
+        let mut warnings = codes(src);
+        warnings.sort();
+        assert_eq!(
+            warnings,
+            vec![
+                ("policy0".to_string(), "mixed-script-string"),
+                ("policy1".to_string(), "ambiguous-attribute-name"),
+            ]
+        );
+
+        // several codes separated by commas
+        let src = r#"
+            @suppress("overly-permissive-policy , mixed-script-string")
+            permit(principal, action, resource);
+            permit(principal, action, resource);
+        "#;
+        assert_eq!(
+            codes(src),
+            vec![("policy1".to_string(), "overly-permissive-policy")]
+        );
+
+        // unknown codes are ignored
+        let src = r#"
+            @suppress("no-such-warning")
+            permit(principal, action, resource);
+        "#;
+        assert_eq!(
+            codes(src),
+            vec![("policy0".to_string(), "overly-permissive-policy")]
+        );
+    }
+
+    #[test]
+    fn lint_skips_typechecking() {
+        let validator = Validator::without_schema();
+        let set = parser::parse_policyset(
+            r#"
+            permit(principal == User::"alice", action, resource) when { "һenry" == "" };
+            permit(principal, action, resource) when { 1 + "one" > 0 };
+        "#,
+        )
+        .unwrap();
+        let result = validator.lint(&set);
+        assert!(result.validation_passed());
+        assert_matches!(
+            result.validation_warnings().collect::<Vec<_>>().as_slice(),
+            [ValidationWarning::MixedScriptString(warning)] => {
+                assert_eq!(warning.policy_id, PolicyID::from_string("policy0"));
+            }
+        );
+        // the same policies fail validation against a schema
+        let result = Validator::new(photo_schema()).validate(&set, ValidationMode::default());
+        assert!(result
+            .validation_errors()
+            .any(|e| matches!(e, ValidationError::UnrecognizedEntityType(_))));
+        assert!(result
+            .validation_errors()
+            .any(|e| matches!(e, ValidationError::UnexpectedType(_))));
+
+        assert_eq!(
+            validator
+                .with_empty_policy_set_warning(true)
+                .lint(&PolicySet::new())
+                .policy_set_warnings()
+                .collect::<Vec<_>>(),
+            vec![&PolicySetWarning::empty_policy_set()]
+        );
+    }
+
+    #[test]
+    fn validate_policies_str_skips_unparseable_policies() {
+        let validator = Validator::new(photo_schema());
+        let src = r#"
+            permit(principal, action, resource) when { "һenry" like "*" };
+            permit(principal, action, resource) when { 1 + };
+            permit(principal, action, resource) when { 1 + "one" > 0 };
+        "#;
+        let result = validator.validate_policies_str(src, ValidationMode::default());
+        let (parse_errs, errs): (Vec<_>, Vec<_>) = result
+            .validation_errors()
+            .partition(|e| matches!(e, ValidationError::ParseError(_)));
+        assert!(!parse_errs.is_empty());
+        for err in parse_errs {
+            assert_matches!(err, ValidationError::ParseError(err) => {
+                assert_eq!(err.policy_id, PolicyID::from_string("policy1"));
+                assert!(err.source_loc.is_some());
+            });
+        }
+        assert_matches!(errs.as_slice(), [ValidationError::UnexpectedType(err)] => {
+            assert_eq!(err.policy_id, PolicyID::from_string("policy2"));
+        });
+        assert_matches!(
+            result.validation_warnings().collect::<Vec<_>>().as_slice(),
+            [ValidationWarning::MixedScriptString(warning)] => {
+                assert_eq!(warning.policy_id, PolicyID::from_string("policy0"));
+            }
+        );
+    }
+
+    #[test]
+    fn validate_policies_str_recovers_from_unterminated_policy() {
+        let validator = Validator::new(photo_schema()).with_empty_policy_set_warning(true);
+        let src = r#"
+            permit(principal, action, resource) when { 1 + "one" > 0 };
+            permit(principal, action, resource) when { true }
+        "#;
+        let result = validator.validate_policies_str(src, ValidationMode::default());
+        let errs = result.validation_errors().collect::<Vec<_>>();
+        assert_matches!(errs.as_slice(), [ValidationError::ParseError(parse_err), ValidationError::UnexpectedType(err)] => {
+            assert_eq!(err.policy_id, PolicyID::from_string("policy0"));
+            assert_eq!(parse_err.policy_id, PolicyID::from_string("policy1"));
+            assert_eq!(parse_err.msg, "unexpected end of input");
+        });
+
+        // A policy set where every policy fails to parse is not reported as empty
+        let result =
+            validator.validate_policies_str("permit(principal,", ValidationMode::default());
+        assert_matches!(
+            result.validation_errors().collect::<Vec<_>>().as_slice(),
+            [ValidationError::ParseError(err)] => {
+                assert_eq!(err.policy_id, PolicyID::from_string("policy0"));
+            }
+        );
+        assert_eq!(result.validation_warnings().count(), 0);
+        assert_eq!(result.policy_set_warnings().count(), 0);
+    }
+
+    #[test]
+    fn validate_does_not_warn_on_template_only_policy_set() {
+        let validator =
+            Validator::new(ValidatorSchema::empty()).with_empty_policy_set_warning(true);
+        let mut set = PolicySet::new();
+        let t = parser::parse_policy_or_template(
+            Some(PolicyID::from_string("t0")),
+            "permit(principal == ?principal, action, resource);",
+        )
+        .unwrap();
+        set.add_template(t).unwrap();
+        let result = validator.validate(&set, ValidationMode::default());
+        assert_eq!(result.policy_set_warnings().count(), 0);
+    }
+
+    #[test]
+    fn custom_extension_functions() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            "entity User; action view appliesTo { principal: User, resource: User };",
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap();
+        let distance: ast::Name = "geo::distance".parse().unwrap();
+        let signature = || {
+            ExtensionFunctionType::custom(
+                distance.clone(),
+                vec![Type::primitive_string(), Type::primitive_string()],
+                Type::primitive_long(),
+            )
+        };
+        let validate = |validator: &Validator, args: Vec<ast::Expr>| {
+            let when = ast::Expr::greater(
+                ast::Expr::call_extension_fn(distance.clone(), args),
+                ast::Expr::val(10),
+            );
+            let mut policies = PolicySet::new();
+            policies
+                .add(ast::Policy::from_when_clause(
+                    Effect::Permit,
+                    when,
+                    PolicyID::from_string("p"),
+                    None,
+                ))
+                .unwrap();
+            validator
+                .validate(&policies, ValidationMode::default())
+                .validation_errors()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let args = || vec![ast::Expr::val("a"), ast::Expr::val("b")];
+
+        // Without the signature, the function is undefined
+        let validator = Validator::new(schema);
+        assert_matches!(validate(&validator, args()).as_slice(), [ValidationError::UndefinedFunction(err)] => {
+            assert_eq!(err.name, "geo::distance");
+        });
+
+        let validator = validator.with_extension(signature()).unwrap();
+        assert_eq!(validate(&validator, args()), vec![]);
+        assert_matches!(
+            validate(&validator, vec![ast::Expr::val("a")]).as_slice(),
+            [ValidationError::WrongNumberArguments(err)] => {
+                assert_eq!((err.expected, err.actual), (2, 1));
+            }
+        );
+        assert_matches!(
+            validate(&validator, vec![ast::Expr::val("a"), ast::Expr::val(1)]).as_slice(),
+            [ValidationError::UnexpectedType(_)]
+        );
+
+        // A function can only be defined once
+        assert_matches!(
+            validator.with_extension(signature()),
+            Err(ExtensionInitializationError::FuncMultiplyDefined(_))
+        );
+    }
 }
 
 #[cfg(test)]
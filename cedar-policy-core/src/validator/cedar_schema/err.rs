@@ -47,6 +47,8 @@ pub enum UserError {
     StringEscape(Node<NonEmpty<UnescapeError>>),
     #[error("`{0}` is a reserved identifier")]
     ReservedIdentifierUsed(Node<SmolStr>),
+    #[error("`{0}` is a reserved word and cannot be used as an attribute name")]
+    ReservedAttributeName(Node<SmolStr>),
     #[error("duplicate annotations: `{}`", .0)]
     DuplicateAnnotations(AnyId, Node<()>, Node<()>),
 }
@@ -56,8 +58,15 @@ impl UserError {
     pub(crate) fn primary_source_span(&self) -> Option<SourceSpan> {
         match self {
             Self::EmptyList(n) => n.loc.as_ref().map(|loc| loc.span),
-            Self::StringEscape(n) => n.loc.as_ref().map(|loc| loc.span),
+            // use the first invalid escape as the primary source span
+            Self::StringEscape(n) => n.loc.as_ref().map(|loc| {
+                n.node
+                    .first()
+                    .escape_loc(loc)
+                    .map_or(loc.span, |loc| loc.span)
+            }),
             Self::ReservedIdentifierUsed(n) => n.loc.as_ref().map(|loc| loc.span),
+            Self::ReservedAttributeName(n) => n.loc.as_ref().map(|loc| loc.span),
             // use the first occurrence as the primary source span
             Self::DuplicateAnnotations(_, n, _) => n.loc.as_ref().map(|loc| loc.span),
         }
@@ -167,6 +176,27 @@ impl ParseError {
             _ => None,
         }
     }
+
+    fn string_escape_help(&self) -> Option<Box<dyn Display + '_>> {
+        match &self.err {
+            OwnedRawParseError::User {
+                error: UserError::StringEscape(errs),
+            } => errs.node.first().help(),
+            _ => None,
+        }
+    }
+
+    fn reserved_attribute_name_help(&self) -> Option<String> {
+        match &self.err {
+            OwnedRawParseError::User {
+                error: UserError::ReservedAttributeName(name),
+            } => Some(format!(
+                "quote it to use it as an attribute name, e.g., `\"{}\"`",
+                name.node
+            )),
+            _ => None,
+        }
+    }
 }
 
 impl ParseError {
@@ -219,6 +249,11 @@ impl Diagnostic for ParseError {
         self.missing_applies_to_help()
             .or_else(|| self.trailing_namespace_semicolon_help())
             .map(|help| Box::new(help) as Box<dyn Display + 'a>)
+            .or_else(|| {
+                self.reserved_attribute_name_help()
+                    .map(|help| Box::new(help) as Box<dyn Display + 'a>)
+            })
+            .or_else(|| self.string_escape_help())
     }
 
     fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
@@ -259,6 +294,13 @@ impl Diagnostic for ParseError {
                     Some(Box::new(spans.into_iter()))
                 }
             }
+            OwnedRawParseError::User {
+                error: UserError::StringEscape(errs),
+            } => errs.loc.as_ref().map(|loc| {
+                Box::new(errs.node.iter().map(|e| {
+                    LabeledSpan::underline(e.escape_loc(loc).map_or(loc.span, |loc| loc.span))
+                })) as _
+            }),
             OwnedRawParseError::User { .. } => primary_source_span
                 .map(|span| Box::new(std::iter::once(LabeledSpan::underline(span))) as _),
         }
@@ -791,6 +833,8 @@ impl Diagnostic for DuplicateNamespace {
 
 /// Error subtypes for [`SchemaWarning`]
 pub mod schema_warnings {
+    use std::fmt::Display;
+
     use crate::parser::Loc;
     use miette::Diagnostic;
     use smol_str::SmolStr;
@@ -857,6 +901,108 @@ pub mod schema_warnings {
             Some(miette::Severity::Warning)
         }
     }
+
+    /// Display `` in namespace `NS` `` for a non-empty namespace
+    fn in_namespace(namespace: &str) -> String {
+        if namespace.is_empty() {
+            String::new()
+        } else {
+            format!(" in namespace `{namespace}`")
+        }
+    }
+
+    /// Warning when an action which is not an action group applies to no
+    /// principal types or no resource types, so no request can use it
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Eq, PartialEq, Debug, Clone, Error)]
+    #[error("action `{name}`{} applies to no {}, so no request can use it", in_namespace(.namespace), missing_types(*.no_principal_types, *.no_resource_types))]
+    pub struct EmptyAppliesToWarning {
+        pub(crate) namespace: SmolStr,
+        pub(crate) name: SmolStr,
+        pub(crate) no_principal_types: bool,
+        pub(crate) no_resource_types: bool,
+        pub(crate) loc: Option<Loc>,
+    }
+
+    fn missing_types(no_principal_types: bool, no_resource_types: bool) -> &'static str {
+        match (no_principal_types, no_resource_types) {
+            (true, true) => "principal or resource types",
+            (true, false) => "principal types",
+            _ => "resource types",
+        }
+    }
+
+    impl EmptyAppliesToWarning {
+        /// The namespace of the action (`""` for the empty namespace)
+        pub fn namespace(&self) -> &str {
+            &self.namespace
+        }
+
+        /// The id of the action, without its namespace
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    impl Diagnostic for EmptyAppliesToWarning {
+        impl_diagnostic_from_source_loc_opt_field!(loc);
+
+        fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            let missing = match (self.no_principal_types, self.no_resource_types) {
+                (true, true) => "principal and resource types",
+                (true, false) => "principal types",
+                _ => "resource types",
+            };
+            Some(Box::new(format!(
+                "declare the {missing} of the action in its `appliesTo`, or remove the action if it is unused"
+            )))
+        }
+
+        fn severity(&self) -> Option<miette::Severity> {
+            Some(miette::Severity::Warning)
+        }
+    }
+
+    /// Warning when an entity type is neither a principal or resource type of
+    /// any action, nor an ancestor of one through `memberOfTypes`
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Eq, PartialEq, Debug, Clone, Error)]
+    #[error("entity type `{name}`{} is not reachable from the `appliesTo` of any action", in_namespace(.namespace))]
+    pub struct UnreachableEntityTypeWarning {
+        pub(crate) namespace: SmolStr,
+        pub(crate) name: SmolStr,
+        pub(crate) loc: Option<Loc>,
+    }
+
+    impl UnreachableEntityTypeWarning {
+        /// The namespace of the entity type (`""` for the empty namespace)
+        pub fn namespace(&self) -> &str {
+            &self.namespace
+        }
+
+        /// The basename of the entity type, without its namespace
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    impl Diagnostic for UnreachableEntityTypeWarning {
+        impl_diagnostic_from_source_loc_opt_field!(loc);
+
+        fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            Some(Box::new("an entity type can only appear in a request if it is a principal or resource type of an action, or a `memberOfTypes` ancestor of one"))
+        }
+
+        fn severity(&self) -> Option<miette::Severity> {
+            Some(miette::Severity::Warning)
+        }
+    }
 }
 
 /// Warning when constructing a schema
@@ -875,4 +1021,12 @@ pub enum SchemaWarning {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ShadowsEntity(#[from] schema_warnings::ShadowsEntityWarning),
+    /// Warning when an action applies to no principal or no resource types
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    EmptyAppliesTo(#[from] schema_warnings::EmptyAppliesToWarning),
+    /// Warning when an entity type can't appear in any request
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnreachableEntityType(#[from] schema_warnings::UnreachableEntityTypeWarning),
 }
@@ -360,6 +360,11 @@ pub enum ToCedarSchemaSyntaxError {
     #[diagnostic(transparent)]
     #[error(transparent)]
     UnconvertibleEntityTypeShape(#[from] UnconvertibleEntityTypeShapeError),
+    /// Actions declared with no resource, which Cedar schema syntax cannot
+    /// express, prevented the conversion
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    UnconvertibleNoResourceAction(#[from] UnconvertibleNoResourceActionError),
 }
 
 /// Duplicate names were found in the schema
@@ -408,6 +413,25 @@ impl UnconvertibleEntityTypeShapeError {
     }
 }
 
+/// Actions declared with no resource were found in the schema. Cedar schema
+/// syntax has no way to express them.
+//
+// This is NOT a publicly exported error type.
+#[derive(Debug, Error, Diagnostic)]
+#[error("The following actions are declared with no resource, which cannot be converted to Cedar schema syntax: [{}]", .names.iter().join(", "))]
+#[diagnostic(help("actions with no resource can only be declared in the JSON schema syntax"))]
+pub struct UnconvertibleNoResourceActionError {
+    /// Names of the actions declared with no resource
+    names: NonEmpty<SmolStr>,
+}
+
+impl UnconvertibleNoResourceActionError {
+    /// Get the names of the actions declared with no resource
+    pub fn names(&self) -> impl Iterator<Item = &SmolStr> {
+        self.names.iter()
+    }
+}
+
 /// Convert a [`json_schema::Fragment`] to a string containing the Cedar schema syntax
 ///
 /// As of this writing, this existing code throws an error if any
@@ -483,6 +507,23 @@ pub fn json_schema_to_cedar_schema_str<N: Display>(
         .into());
     }
 
+    let no_resource_actions = json_schema
+        .0
+        .values()
+        .flat_map(|ns| ns.actions.iter())
+        .filter(|(_, action)| {
+            action
+                .applies_to
+                .as_ref()
+                .is_some_and(|spec| spec.no_resource)
+        })
+        .map(|(name, _)| name.clone())
+        .sorted_unstable()
+        .collect();
+    if let Some(names) = NonEmpty::from_vec(no_resource_actions) {
+        return Err(UnconvertibleNoResourceActionError { names }.into());
+    }
+
     Ok(json_schema.to_string())
 }
 
@@ -164,6 +164,84 @@ mod demo_tests {
         });
     }
 
+    #[test]
+    fn invalid_escapes_located() {
+        for (src, esc, help) in [
+            (
+                r#"entity User { "a\u{D800}b": Long };"#,
+                r"\u{D800}",
+                r"unicode escapes for surrogate code points, `\u{D800}` through `\u{DFFF}`, are not allowed",
+            ),
+            (
+                r#"action "view\u{}";"#,
+                r"\u{}",
+                r"unicode escapes must have at least one hexadecimal digit, e.g., `\u{41}`",
+            ),
+            (
+                r#"entity User; action view appliesTo { principal: [User], resource: [User] }; action edit in [Action::"vi\u{0000076}ew"];"#,
+                r"\u{0000076}",
+                r"unicode escapes must have at most six hexadecimal digits, e.g., `\u{10FFFF}`",
+            ),
+        ] {
+            assert_matches!(collect_warnings(json_schema::Fragment::from_cedarschema_str(src, Extensions::all_available())), Err(e) => {
+                expect_err(
+                    src,
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error("error parsing schema: Invalid escape codes")
+                        .exactly_one_underline(esc)
+                        .help(help)
+                        .build(),
+                );
+            });
+        }
+        let (schema, _) = json_schema::Fragment::from_cedarschema_str(
+            r#"action "\u{1F408}\u{41}";"#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(
+            schema
+                .0
+                .get(&None)
+                .unwrap()
+                .actions
+                .keys()
+                .collect::<Vec<_>>(),
+            ["🐈A"]
+        );
+    }
+
+    #[test]
+    fn reserved_attribute_name() {
+        for (src, word) in [
+            ("entity User { in: Long };", "in"),
+            ("entity User { tags: { has?: Bool } };", "has"),
+            ("type Ctx = { if: String };", "if"),
+        ] {
+            assert_matches!(collect_warnings(json_schema::Fragment::from_cedarschema_str(src, Extensions::all_available())), Err(e) => {
+                expect_err(
+                    src,
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error(&format!("error parsing schema: `{word}` is a reserved word and cannot be used as an attribute name"))
+                        .exactly_one_underline(word)
+                        .help(&format!("quote it to use it as an attribute name, e.g., `\"{word}\"`"))
+                        .build(),
+                );
+            });
+        }
+        // Quoted reserved words are valid attribute names
+        let (schema, _) = json_schema::Fragment::from_cedarschema_str(
+            r#"entity User { "in": Long, "has"?: Bool };"#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_matches!(&schema.0.get(&None).unwrap().entity_types.get(&"User".parse().unwrap()).unwrap().kind, EntityTypeKind::Standard(user) => {
+            assert_matches!(&user.shape.0, json_schema::Type::Type { ty: json_schema::TypeVariant::Record(record), .. } => {
+                assert_eq!(record.attributes.keys().collect::<Vec<_>>(), ["has", "in"]);
+            });
+        });
+    }
+
     #[test]
     fn resource_only() {
         let src = r#"
@@ -514,6 +592,7 @@ namespace Baz {action "Foo" appliesTo {
                     applies_to: Some(json_schema::ApplySpec::<RawName> {
                         resource_types: vec![],
                         principal_types: vec!["a".parse().unwrap()],
+                        no_resource: false,
                         context: json_schema::AttributesOrContext::default(),
                     }),
                     member_of: None,
@@ -1576,7 +1655,7 @@ mod translator_tests {
                 &miette::Report::new(e),
                 &ExpectedErrorMessageBuilder::error("definition of `Demo::email_address` illegally shadows the existing definition of `email_address`")
                     .help("try renaming one of the definitions, or moving `email_address` to a different namespace")
-                    .exactly_one_underline("entity email_address {\n              where: String,\n            };")
+                    .exactly_two_underlines("entity email_address {\n              where: String,\n            };", "type email_address = {\n            id: String,\n            domain: String,\n          };")
                     .build(),
             );
         });
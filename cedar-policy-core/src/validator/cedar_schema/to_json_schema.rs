@@ -237,6 +237,7 @@ fn convert_action_decl(
             resource_types: vec![],
             principal_types: vec![],
             context: json_schema::AttributesOrContext::default(),
+            no_resource: false,
         });
     let member_of = parents.map(|parents| parents.into_iter().map(convert_qual_name).collect());
 
@@ -371,6 +372,7 @@ fn convert_app_decls(
             .map(|node| node.node)
             .ok_or_else(|| ToJsonSchemaError::no_principal(&name, name_loc.cloned()))?,
         context: context.map(|c| c.node).unwrap_or_default(),
+        no_resource: false,
     })
 }
 
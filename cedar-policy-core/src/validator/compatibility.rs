@@ -0,0 +1,322 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Checks whether a new version of a schema is backward compatible with an
+//! old version, i.e., whether policies which validate against the old schema
+//! can be expected to validate against the new one.
+
+use std::fmt::Display;
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use super::types::{Attributes, Type};
+use super::{ValidatorActionId, ValidatorSchema};
+
+/// A rule of backward compatibility between two versions of a schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CompatibilityRule {
+    /// An entity type, action, attribute, or tag declaration was removed
+    RemovedDeclaration,
+    /// A required attribute was made optional, so policies reading it without
+    /// first checking `has` no longer validate
+    AttributeMadeOptional,
+    /// An action applies to fewer principal or resource types
+    AppliesToNarrowed,
+    /// The type of an attribute or of the tags of an entity type changed
+    TypeChanged,
+}
+
+impl Display for CompatibilityRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RemovedDeclaration => write!(f, "removed declaration"),
+            Self::AttributeMadeOptional => write!(f, "required attribute made optional"),
+            Self::AppliesToNarrowed => write!(f, "narrowed `appliesTo`"),
+            Self::TypeChanged => write!(f, "changed type"),
+        }
+    }
+}
+
+/// A single backward incompatible change between two versions of a schema
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Error)]
+#[error("`{path}`: {message}")]
+pub struct Incompatibility {
+    path: String,
+    rule: CompatibilityRule,
+    message: String,
+}
+
+impl Incompatibility {
+    /// Path to the declaration in the old schema which the incompatible change
+    /// affects, e.g., `User`, `User.address.street`, `Action::"view"`, or
+    /// `Action::"view".appliesTo.principal`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The rule which this change violates
+    pub fn rule(&self) -> CompatibilityRule {
+        self.rule
+    }
+
+    /// Human-readable description of the change
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Result of checking whether a schema is backward compatible with a
+/// previous version of it, returned by
+/// [`ValidatorSchema::is_backward_compatible_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    incompatibilities: Vec<Incompatibility>,
+}
+
+impl CompatibilityReport {
+    /// `true` if no backward incompatible changes were found
+    pub fn is_compatible(&self) -> bool {
+        self.incompatibilities.is_empty()
+    }
+
+    /// The backward incompatible changes, ordered by path
+    pub fn incompatibilities(&self) -> impl Iterator<Item = &Incompatibility> {
+        self.incompatibilities.iter()
+    }
+
+    fn push(&mut self, path: String, rule: CompatibilityRule, message: String) {
+        self.incompatibilities.push(Incompatibility {
+            path,
+            rule,
+            message,
+        });
+    }
+
+    /// Compare the attributes of an entity type or record type in the old and
+    /// new schema, including nested record attributes
+    fn check_attributes(&mut self, path: &str, old: &Attributes, new: &Attributes) {
+        for (attr, old_ty) in old.iter() {
+            let attr_path = format!("{path}.{attr}");
+            match new.get_attr(attr) {
+                None => {
+                    let message = format!("attribute `{attr_path}` was removed");
+                    self.push(attr_path, CompatibilityRule::RemovedDeclaration, message);
+                }
+                Some(new_ty) => {
+                    if old_ty.is_required && !new_ty.is_required {
+                        let message = format!("attribute `{attr_path}` was made optional");
+                        self.push(
+                            attr_path.clone(),
+                            CompatibilityRule::AttributeMadeOptional,
+                            message,
+                        );
+                    }
+                    self.check_type(&attr_path, &old_ty.attr_type, &new_ty.attr_type);
+                }
+            }
+        }
+    }
+
+    /// Compare the type of an attribute or tags in the old and new schema
+    fn check_type(&mut self, path: &str, old: &Type, new: &Type) {
+        match (old, new) {
+            (Type::Record { attrs: old, .. }, Type::Record { attrs: new, .. }) => {
+                self.check_attributes(path, old, new);
+            }
+            _ if old != new => {
+                let message = format!("type of `{path}` changed from `{old}` to `{new}`");
+                self.push(path.to_string(), CompatibilityRule::TypeChanged, message);
+            }
+            _ => (),
+        }
+    }
+
+    fn check_action(&mut self, old: &ValidatorActionId, new: &ValidatorActionId) {
+        let path = old.name().to_string();
+        for (kind, old_types, new_types) in [
+            (
+                "principal",
+                old.applies_to_principals().collect::<Vec<_>>(),
+                new.applies_to_principals().collect::<Vec<_>>(),
+            ),
+            (
+                "resource",
+                old.applies_to_resources().collect::<Vec<_>>(),
+                new.applies_to_resources().collect::<Vec<_>>(),
+            ),
+        ] {
+            for removed in old_types
+                .iter()
+                .filter(|ety| !new_types.contains(*ety))
+                .sorted()
+            {
+                self.push(
+                    format!("{path}.appliesTo.{kind}"),
+                    CompatibilityRule::AppliesToNarrowed,
+                    format!("action `{path}` no longer applies to {kind} type `{removed}`"),
+                );
+            }
+        }
+        self.check_type(&format!("{path}.context"), old.context(), new.context());
+    }
+}
+
+impl ValidatorSchema {
+    /// Check whether this schema is backward compatible with the `old` schema,
+    /// i.e., whether policies which validate against `old` can be expected to
+    /// validate against this schema. The following changes are incompatible:
+    ///
+    /// - removing an entity type, action, attribute, or the tags of an entity
+    ///   type;
+    /// - making a required attribute optional;
+    /// - removing a principal or resource type from the `appliesTo` of an
+    ///   action;
+    /// - changing the type of an attribute or of tags, unless it is a record
+    ///   type whose attributes are themselves compatible.
+    ///
+    /// Adding entity types, actions, optional attributes, or `appliesTo` types
+    /// is compatible.
+    pub fn is_backward_compatible_with(&self, old: &ValidatorSchema) -> CompatibilityReport {
+        let mut report = CompatibilityReport::default();
+        for old_ety in old
+            .entity_types()
+            .sorted_unstable_by(|a, b| a.name().cmp(b.name()))
+        {
+            let path = old_ety.name().to_string();
+            let Some(new_ety) = self.get_entity_type(old_ety.name()) else {
+                let message = format!("entity type `{path}` was removed");
+                report.push(path, CompatibilityRule::RemovedDeclaration, message);
+                continue;
+            };
+            report.check_attributes(&path, old_ety.attributes(), new_ety.attributes());
+            match (old_ety.tag_type(), new_ety.tag_type()) {
+                (Some(_), None) => {
+                    let message = format!("tags of entity type `{path}` were removed");
+                    report.push(
+                        format!("{path}.tags"),
+                        CompatibilityRule::RemovedDeclaration,
+                        message,
+                    );
+                }
+                (Some(old_tags), Some(new_tags)) => {
+                    report.check_type(&format!("{path}.tags"), old_tags, new_tags);
+                }
+                (None, _) => (),
+            }
+        }
+        for old_action in old
+            .action_ids()
+            .sorted_unstable_by(|a, b| a.name().cmp(b.name()))
+        {
+            match self.get_action_id(old_action.name()) {
+                None => {
+                    let path = old_action.name().to_string();
+                    let message = format!("action `{path}` was removed");
+                    report.push(path, CompatibilityRule::RemovedDeclaration, message);
+                }
+                Some(new_action) => report.check_action(old_action, new_action),
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extensions::Extensions;
+    use crate::validator::ValidatorSchema;
+
+    use super::CompatibilityRule;
+
+    fn schema(src: &str) -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(src, Extensions::all_available())
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn compatible_changes() {
+        let old = schema(
+            r#"
+            entity User { name: String, address?: { street: String } };
+            entity Photo;
+            action view appliesTo { principal: User, resource: Photo };
+            "#,
+        );
+        let new = schema(
+            r#"
+            entity User { name: String, age?: Long, address?: { street: String, zip?: String } };
+            entity Photo, Album;
+            action view appliesTo { principal: User, resource: [Photo, Album] };
+            action edit appliesTo { principal: User, resource: Photo };
+            "#,
+        );
+        let report = new.is_backward_compatible_with(&old);
+        assert!(report.is_compatible(), "{report:?}");
+        assert!(old.is_backward_compatible_with(&old).is_compatible());
+    }
+
+    #[test]
+    fn incompatible_changes() {
+        let old = schema(
+            r#"
+            entity User { name: String, age: Long, address: { street: String } } tags String;
+            entity Photo;
+            entity Album;
+            action view appliesTo { principal: User, resource: [Photo, Album], context: { ip: String } };
+            action edit appliesTo { principal: User, resource: Photo };
+            "#,
+        );
+        let new = schema(
+            r#"
+            entity User { name?: String, age: String, address: { } };
+            entity Photo;
+            action view appliesTo { principal: User, resource: Photo, context: { ip: Long } };
+            "#,
+        );
+        let report = new.is_backward_compatible_with(&old);
+        assert!(!report.is_compatible());
+        let incompatibilities = report
+            .incompatibilities()
+            .map(|i| (i.path(), i.rule()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            incompatibilities,
+            vec![
+                ("Album", CompatibilityRule::RemovedDeclaration),
+                ("User.address.street", CompatibilityRule::RemovedDeclaration),
+                ("User.age", CompatibilityRule::TypeChanged),
+                ("User.name", CompatibilityRule::AttributeMadeOptional),
+                ("User.tags", CompatibilityRule::RemovedDeclaration),
+                (r#"Action::"edit""#, CompatibilityRule::RemovedDeclaration),
+                (
+                    r#"Action::"view".appliesTo.resource"#,
+                    CompatibilityRule::AppliesToNarrowed
+                ),
+                (
+                    r#"Action::"view".context.ip"#,
+                    CompatibilityRule::TypeChanged
+                ),
+            ]
+        );
+        assert_eq!(
+            report.incompatibilities().nth(6).unwrap().to_string(),
+            r#"`Action::"view".appliesTo.resource`: action `Action::"view"` no longer applies to resource type `Album`"#
+        );
+    }
+}
@@ -0,0 +1,471 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Completions of attribute, method, and entity type names for editors,
+//! based on the types inferred by the typechecker.
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+
+use smol_str::{SmolStr, ToSmolStr};
+
+use crate::ast::{BinaryOp, Expr, ExprKind, Template, UnaryOp, UnreservedId};
+use crate::extensions::ExtStyles;
+use crate::parser::Loc;
+
+use super::extensions::ExtensionSchemas;
+use super::typecheck::Typechecker;
+use super::types::{EntityKind, Type};
+use super::{ValidationMode, Validator, ValidatorEntityType};
+
+/// Names of the methods built into the Cedar language which apply to sets
+const SET_METHODS: [&str; 4] = ["contains", "containsAll", "containsAny", "isEmpty"];
+
+/// What a [`Completion`] completes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompletionKind {
+    /// An attribute of an entity or record
+    Attribute,
+    /// A built-in or extension method
+    Method,
+    /// An entity type declared in the schema
+    EntityType,
+}
+
+/// A name which can replace the name under the cursor, returned by
+/// [`Validator::completions_at`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    label: SmolStr,
+    kind: CompletionKind,
+    ty: Option<Type>,
+}
+
+impl Completion {
+    /// The name to complete with
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Whether this is an attribute, method, or entity type
+    pub fn kind(&self) -> CompletionKind {
+        self.kind
+    }
+
+    /// The type of the attribute, the return type of the method, or the
+    /// entity type. `None` if the type differs between request environments
+    /// and the types have no least upper bound.
+    pub fn ty(&self) -> Option<&Type> {
+        self.ty.as_ref()
+    }
+}
+
+/// The name under the cursor
+enum Target<'a> {
+    /// The name of an attribute accessed on, or method called on, this
+    /// expression
+    Member(&'a Expr),
+    /// The entity type of an `is` expression
+    EntityType,
+}
+
+impl Validator {
+    /// Get the names which can replace the name at `offset` in the source of
+    /// the template `p`, e.g., to suggest completions in an editor. `offset`
+    /// must be inside or at the end of the attribute name of an attribute
+    /// access written with dot syntax (`e.attr`) or a `has` expression, or of
+    /// the entity type in an `is` expression. Since the template was parsed,
+    /// this is typically a partially typed name, like `principal.na`. The
+    /// completions are the attributes and methods of the type of `e` in any
+    /// request environment, or the entity types declared in the schema. They
+    /// are sorted by kind and then by label, and are not filtered by the name
+    /// already written.
+    pub fn completions_at(&self, p: &Template, offset: usize) -> Vec<Completion> {
+        let extensions = self.extension_schemas();
+        let mode = ValidationMode::default();
+        let typechecker = Typechecker::new(&self.schema, mode).with_extensions(&extensions);
+        let condition = p.condition();
+        let candidates = match completion_target(&condition, offset) {
+            Some(Target::Member(receiver)) => typechecker
+                .expr_types_by_request_env(p, receiver)
+                .iter()
+                .flat_map(|ty| self.member_completions(ty, &extensions, mode))
+                .collect(),
+            Some(Target::EntityType) => self
+                .schema
+                .entity_type_names()
+                .map(|name| {
+                    (
+                        CompletionKind::EntityType,
+                        name.to_smolstr(),
+                        Some(Type::named_entity_reference(name.clone())),
+                    )
+                })
+                .collect(),
+            None => vec![],
+        };
+        // As for `TypedPolicy`, a name with different types in different
+        // request environments gets their least upper bound
+        let mut completions: BTreeMap<(CompletionKind, SmolStr), Option<Type>> = BTreeMap::new();
+        for (kind, label, ty) in candidates {
+            match completions.entry((kind, label)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(ty);
+                }
+                Entry::Occupied(mut entry) => {
+                    let lub = entry
+                        .get()
+                        .as_ref()
+                        .zip(ty.as_ref())
+                        .and_then(|(prev, ty)| Type::least_upper_bound(prev, ty, mode).ok());
+                    entry.insert(lub);
+                }
+            }
+        }
+        completions
+            .into_iter()
+            .map(|((kind, label), ty)| Completion { label, kind, ty })
+            .collect()
+    }
+
+    /// The attributes and methods of an expression of type `ty`
+    fn member_completions(
+        &self,
+        ty: &Type,
+        extensions: &ExtensionSchemas<'_>,
+        mode: ValidationMode,
+    ) -> Vec<(CompletionKind, SmolStr, Option<Type>)> {
+        let attributes = ty.all_attributes(&self.schema).into_iter().map(|attr| {
+            let attr_ty = Type::lookup_attribute_type(&self.schema, ty, &attr)
+                .map(|attr_ty| attr_ty.attr_type.as_ref().clone());
+            (CompletionKind::Attribute, attr, attr_ty)
+        });
+        let methods = match ty {
+            Type::Set { .. } => SET_METHODS
+                .iter()
+                .map(|method| {
+                    (
+                        CompletionKind::Method,
+                        SmolStr::new_static(method),
+                        Some(Type::primitive_boolean()),
+                    )
+                })
+                .collect(),
+            Type::Entity(EntityKind::Entity(lub)) => {
+                let tag_types = lub
+                    .iter()
+                    .filter_map(|ety| {
+                        self.schema
+                            .get_entity_type(ety)
+                            .and_then(ValidatorEntityType::tag_type)
+                    })
+                    .collect::<Vec<_>>();
+                match tag_types.split_first() {
+                    Some((first, rest)) => {
+                        let tag_type = rest.iter().try_fold((*first).clone(), |lub, ty| {
+                            Type::least_upper_bound(&lub, ty, mode).ok()
+                        });
+                        vec![
+                            (
+                                CompletionKind::Method,
+                                SmolStr::new_static("getTag"),
+                                tag_type,
+                            ),
+                            (
+                                CompletionKind::Method,
+                                SmolStr::new_static("hasTag"),
+                                Some(Type::primitive_boolean()),
+                            ),
+                        ]
+                    }
+                    None => vec![],
+                }
+            }
+            Type::ExtensionType { .. } => extensions
+                .function_types()
+                .filter(|f| {
+                    f.name().is_unqualified()
+                        && ExtStyles::is_method(&f.name().basename())
+                        && f.argument_types().first() == Some(ty)
+                })
+                .map(|f| {
+                    (
+                        CompletionKind::Method,
+                        f.name().basename().to_smolstr(),
+                        Some(f.return_type().clone()),
+                    )
+                })
+                .collect(),
+            _ => vec![],
+        };
+        attributes.chain(methods).collect()
+    }
+}
+
+/// Find the name in `expr` which contains `offset`
+fn completion_target(expr: &Expr, offset: usize) -> Option<Target<'_>> {
+    // The source span of each attribute access or method call in a chain like
+    // `a.b.c.isEmpty()` covers the whole chain, so the end of the text of
+    // `a.b` is only known from the access `.c` to it. `subexpressions()`
+    // visits `a.b.c` before `a.b`, so `ends` always has the end of `a.b` when
+    // it is visited.
+    let mut ends: HashMap<*const Expr, usize> = HashMap::new();
+    expr.subexpressions().find_map(|e| {
+        let loc = e.source_loc()?;
+        let end = ends
+            .get(&std::ptr::from_ref(e))
+            .copied()
+            .or_else(|| Some(loc.start() + loc.snippet()?.trim_end().len()))?;
+        let dot_receiver = dot_receiver(e, loc, end);
+        if let Some((receiver, receiver_end)) = dot_receiver {
+            if receiver.source_loc().map(|l| l.span) == Some(loc.span) {
+                ends.insert(std::ptr::from_ref(receiver), receiver_end);
+            }
+        }
+        let (name, target) = match e.expr_kind() {
+            // Only complete accesses written with dot syntax, `e.attr`
+            ExprKind::GetAttr { expr, attr } => {
+                dot_receiver?;
+                (attr.to_string(), Target::Member(expr))
+            }
+            ExprKind::HasAttr { expr, attr } => (attr.to_string(), Target::Member(expr)),
+            ExprKind::Is { entity_type, .. } => (entity_type.to_string(), Target::EntityType),
+            _ => return None,
+        };
+        let name_start = trailing_name_start(loc, end, &name)?;
+        (name_start <= offset && offset <= end).then_some(target)
+    })
+}
+
+/// If `e` is an attribute access or method call written with dot syntax,
+/// `recv.attr` or `recv.method(args)`, where the text of `e` ends at `end`,
+/// the receiver `recv` and the end of its text
+fn dot_receiver<'a>(e: &'a Expr, loc: &Loc, end: usize) -> Option<(&'a Expr, usize)> {
+    let src = loc.src.get(..end)?;
+    let (receiver, before_receiver_end) = match e.expr_kind() {
+        ExprKind::GetAttr { expr, attr } => (expr.as_ref(), src.strip_suffix(attr.as_str())?),
+        ExprKind::UnaryApp {
+            op: UnaryOp::IsEmpty,
+            arg,
+        } => (
+            arg.as_ref(),
+            method_name_end(src, None)?.strip_suffix("isEmpty")?,
+        ),
+        ExprKind::BinaryApp { op, arg1, arg2 } => {
+            let name = match op {
+                BinaryOp::Contains => "contains",
+                BinaryOp::ContainsAll => "containsAll",
+                BinaryOp::ContainsAny => "containsAny",
+                BinaryOp::GetTag => "getTag",
+                BinaryOp::HasTag => "hasTag",
+                _ => return None,
+            };
+            (
+                arg1.as_ref(),
+                method_name_end(src, Some(arg2))?.strip_suffix(name)?,
+            )
+        }
+        ExprKind::ExtensionFunctionApp { fn_name, args }
+            if fn_name.is_unqualified() && ExtStyles::is_method(&fn_name.basename()) =>
+        {
+            let name = fn_name.basename();
+            (
+                args.first()?,
+                method_name_end(src, args.get(1))?
+                    .strip_suffix(<UnreservedId as AsRef<str>>::as_ref(&name))?,
+            )
+        }
+        _ => return None,
+    };
+    let receiver_end = before_receiver_end
+        .trim_end()
+        .strip_suffix('.')?
+        .trim_end()
+        .len();
+    Some((receiver, receiver_end))
+}
+
+/// The text of a method call `src`, up to the end of the method name, given
+/// the first argument after the receiver, if any
+fn method_name_end<'s>(src: &'s str, first_arg: Option<&Expr>) -> Option<&'s str> {
+    let before_args = match first_arg {
+        Some(arg) => src.get(..arg.source_loc()?.start())?,
+        None => src.strip_suffix(')')?,
+    };
+    before_args.trim_end().strip_suffix('(').map(str::trim_end)
+}
+
+/// The start of `name`, if it is written just before `end` in the source of
+/// `loc`
+fn trailing_name_start(loc: &Loc, end: usize, name: &str) -> Option<usize> {
+    loc.src
+        .get(..end)?
+        .ends_with(name)
+        .then(|| end - name.len())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extensions::Extensions;
+    use crate::parser;
+    use crate::validator::{CompletionKind, Validator, ValidatorSchema};
+
+    fn completions(src: &str, cursor_after: &str) -> Vec<(String, CompletionKind, String)> {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity Group;
+            entity User in Group { name: String, age?: Long, groups: Set<Group>, addr: ipaddr } tags String;
+            entity Photo { owner: User, meta: { width: Long, height: Long } };
+            action view appliesTo { principal: User, resource: Photo };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let policy = parser::parse_policy_or_template(None, src).unwrap();
+        let offset = src.find(cursor_after).unwrap() + cursor_after.len();
+        Validator::new(schema)
+            .completions_at(&policy, offset)
+            .into_iter()
+            .map(|c| {
+                (
+                    c.label().to_string(),
+                    c.kind(),
+                    c.ty().map(ToString::to_string).unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    fn labels(completions: &[(String, CompletionKind, String)]) -> Vec<&str> {
+        completions
+            .iter()
+            .map(|(label, _, _)| label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn entity_attributes_and_tag_methods() {
+        let principal = completions(
+            "permit(principal, action, resource) when { principal.na == \"a\" };",
+            "principal.na",
+        );
+        assert_eq!(
+            labels(&principal),
+            vec!["addr", "age", "groups", "name", "getTag", "hasTag"]
+        );
+        assert!(principal.contains(&(
+            "name".to_string(),
+            CompletionKind::Attribute,
+            "String".to_string()
+        )));
+        assert!(principal.contains(&(
+            "getTag".to_string(),
+            CompletionKind::Method,
+            "String".to_string()
+        )));
+    }
+
+    #[test]
+    fn nested_record_attributes() {
+        let meta = completions(
+            "permit(principal, action, resource) when { resource.meta.w > 1 };",
+            "resource.meta.w",
+        );
+        assert_eq!(labels(&meta), vec!["height", "width"]);
+        // Completing the middle of a chain of accesses
+        let resource = completions(
+            "permit(principal, action, resource) when { resource.me.width > 1 };",
+            "resource.m",
+        );
+        assert_eq!(labels(&resource), vec!["meta", "owner"]);
+        // Completing the receiver of method calls
+        let owner = completions(
+            "permit(principal, action, resource) when { resource.owner.ad.isLoopback() && resource.owner.gr.contains(principal) };",
+            "resource.owner.ad",
+        );
+        assert_eq!(
+            labels(&owner),
+            vec!["addr", "age", "groups", "name", "getTag", "hasTag"]
+        );
+        assert_eq!(
+            completions(
+                "permit(principal, action, resource) when { resource.owner.ad.isLoopback() && resource.owner.gr.contains(principal) };",
+                "resource.owner.gr",
+            ),
+            owner
+        );
+    }
+
+    #[test]
+    fn methods() {
+        let set = completions(
+            "permit(principal, action, resource) when { principal.groups.cont };",
+            "groups.cont",
+        );
+        assert_eq!(
+            labels(&set),
+            vec!["contains", "containsAll", "containsAny", "isEmpty"]
+        );
+        let ip = completions(
+            "permit(principal, action, resource) when { principal.addr.isL };",
+            "addr.isL",
+        );
+        assert!(ip.contains(&(
+            "isLoopback".to_string(),
+            CompletionKind::Method,
+            "Bool".to_string()
+        )));
+        assert!(ip
+            .iter()
+            .all(|(_, kind, _)| *kind == CompletionKind::Method));
+    }
+
+    #[test]
+    fn has_and_is() {
+        let has = completions(
+            "permit(principal, action, resource) when { principal has a };",
+            "has a",
+        );
+        assert_eq!(
+            labels(&has),
+            vec!["addr", "age", "groups", "name", "getTag", "hasTag"]
+        );
+        let is = completions(
+            "permit(principal, action, resource) when { resource.owner is Us };",
+            "is Us",
+        );
+        assert_eq!(labels(&is), vec!["Group", "Photo", "User"]);
+        assert!(is
+            .iter()
+            .all(|(_, kind, _)| *kind == CompletionKind::EntityType));
+    }
+
+    #[test]
+    fn no_completions() {
+        // The cursor is not on an attribute name
+        assert!(completions(
+            "permit(principal, action, resource) when { principal.name == \"a\" };",
+            "== \"",
+        )
+        .is_empty());
+        // Strings have no attributes or methods
+        assert!(completions(
+            "permit(principal, action, resource) when { principal.name.x };",
+            "name.x",
+        )
+        .is_empty());
+    }
+}
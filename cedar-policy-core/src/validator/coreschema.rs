@@ -260,8 +260,24 @@ impl ast::RequestSchema for ValidatorSchema {
             }
         }
 
-        // Validate resource if provided
-        if let Some(resource) = resource {
+        // The resource of a request for an action declared with no resource
+        // must be the action itself
+        if let (Some(resource), Some(action)) = (resource, action) {
+            if resource != action
+                && self
+                    .get_action_id(action)
+                    .is_some_and(ValidatorActionId::has_no_resource)
+            {
+                return Err(request_validation_errors::UnspecifiedResourceError {
+                    resource: resource.clone(),
+                    action: Arc::new(action.clone()),
+                }
+                .into());
+            }
+        }
+
+        // Validate resource if provided, unless it is the action itself
+        if let Some(resource) = resource.filter(|resource| Some(*resource) != action) {
             let resource_type = resource.entity_type();
             if let Some(et) = self.get_entity_type(resource_type) {
                 if let ValidatorEntityType {
@@ -414,6 +430,11 @@ pub enum RequestValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     InvalidEnumEntity(#[from] InvalidEnumEntityError),
+    /// Request action is declared with no resource, but the request resource
+    /// is not the action itself
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnspecifiedResource(#[from] request_validation_errors::UnspecifiedResourceError),
 }
 
 /// Errors related to validation
@@ -603,6 +624,32 @@ pub mod request_validation_errors {
         }
     }
 
+    /// Request action is declared with no resource, but the request resource
+    /// is not the action itself
+    #[derive(Debug, Error, Diagnostic)]
+    #[error("request's action `{action}` is declared with no resource, but the request's resource is `{resource}`")]
+    #[diagnostic(help(
+        "the resource of a request for an action with no resource must be the action itself"
+    ))]
+    pub struct UnspecifiedResourceError {
+        /// Resource of the request
+        pub(crate) resource: ast::EntityUID,
+        /// Action of the request
+        pub(crate) action: Arc<ast::EntityUID>,
+    }
+
+    impl UnspecifiedResourceError {
+        /// The resource of the request
+        pub fn resource(&self) -> &ast::EntityUID {
+            &self.resource
+        }
+
+        /// The action of the request
+        pub fn action(&self) -> &ast::EntityUID {
+            &self.action
+        }
+    }
+
     /// Context does not comply with the shape specified for the request action
     #[derive(Debug, Error, Diagnostic)]
     #[error("context `{}` is not valid for `{action}`", ast::BoundedToString::to_string_bounded(.context, BOUNDEDDISPLAY_BOUND_FOR_INVALID_CONTEXT_ERROR))]
@@ -297,6 +297,7 @@ impl TryFrom<compat::ApplySpec> for ApplySpec<RawName> {
             resource_types,
             principal_types,
             context: value.context.try_into()?,
+            no_resource: false,
         })
     }
 }
@@ -23,23 +23,42 @@ use miette::Diagnostic;
 use thiserror::Error;
 use validation_errors::UnrecognizedActionIdHelp;
 
-use std::collections::BTreeSet;
+use smol_str::{SmolStr, ToSmolStr};
+use std::collections::{BTreeSet, HashMap};
 
-use crate::ast::{EntityType, Expr, PolicyID};
+use crate::ast::{
+    AnyId, Effect, EntityType, EntityUID, Expr, PolicyID, PolicySet, ResourceConstraint, SlotId,
+    Template,
+};
 
 use crate::validator::types::{EntityLUB, Type};
 
 pub mod validation_errors;
 pub mod validation_warnings;
+pub use validation_warnings::{WarningCategory, WarningKindInfo};
+
+/// Key of the annotation with which a policy suppresses validation warnings
+const SUPPRESS_ANNOTATION: &str = "suppress";
 
 /// Contains the result of policy validation. The result includes the list of
 /// issues found by validation and whether validation succeeds or fails.
 /// Validation succeeds if there are no fatal errors. There may still be
 /// non-fatal warnings present when validation passes.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ValidationResult {
     validation_errors: Vec<ValidationError>,
     validation_warnings: Vec<ValidationWarning>,
+    /// Warnings about the policy set as a whole, rather than any one policy
+    policy_set_warnings: Vec<PolicySetWarning>,
+    /// Source location of the whole text of each validated policy or
+    /// template, as opposed to the narrower location attached to each error
+    /// or warning
+    policy_locs: HashMap<PolicyID, Loc>,
+    /// Effect of each validated policy or template
+    policy_effects: HashMap<PolicyID, Effect>,
+    /// Id of each validated policy or template, in the order they were
+    /// validated
+    policy_order: Vec<PolicyID>,
 }
 
 impl ValidationResult {
@@ -52,6 +71,75 @@ impl ValidationResult {
         Self {
             validation_errors: errors.into_iter().collect(),
             validation_warnings: warnings.into_iter().collect(),
+            policy_set_warnings: Vec::new(),
+            policy_locs: HashMap::new(),
+            policy_effects: HashMap::new(),
+            policy_order: Vec::new(),
+        }
+    }
+
+    /// Record the source location and effect of every template, static
+    /// policy, and template-linked policy in `policies`. A linked policy is
+    /// given the location of its template.
+    pub(crate) fn with_policies(mut self, policies: &PolicySet) -> Self {
+        for t in policies.all_templates() {
+            self.record_policy(t.id(), t.loc(), t.effect());
+        }
+        for p in policies.policies() {
+            self.record_policy(p.id(), p.loc(), p.effect());
+        }
+        self
+    }
+
+    /// Add these warnings about the policy set as a whole
+    pub(crate) fn with_policy_set_warnings(
+        mut self,
+        warnings: impl IntoIterator<Item = PolicySetWarning>,
+    ) -> Self {
+        self.policy_set_warnings.extend(warnings);
+        self
+    }
+
+    /// Drop each warning found in a template or static policy which has a
+    /// `@suppress` annotation listing the code of the warning (see
+    /// [`WarningKindInfo::code`]). The annotation value may list several
+    /// codes separated by commas, e.g.,
+    /// `@suppress("mixed-script-string, confusable-identifier")`.
+    pub(crate) fn without_suppressed_warnings(mut self, policies: &PolicySet) -> Self {
+        let key = AnyId::new_unchecked(SUPPRESS_ANNOTATION);
+        let suppressed: HashMap<&PolicyID, Vec<&str>> = policies
+            .all_templates()
+            .filter_map(|t| {
+                let codes = t
+                    .annotation(&key)?
+                    .as_ref()
+                    .split(',')
+                    .map(str::trim)
+                    .collect::<Vec<_>>();
+                Some((t.id(), codes))
+            })
+            .collect();
+        self.validation_warnings.retain(|warning| {
+            !suppressed
+                .get(warning.policy_id())
+                .is_some_and(|codes| codes.contains(&warning.kind_info().code()))
+        });
+        self
+    }
+
+    /// Record the source location and effect of the single template or static
+    /// policy `t`
+    pub(crate) fn with_template(mut self, t: &Template) -> Self {
+        self.record_policy(t.id(), t.loc(), t.effect());
+        self
+    }
+
+    fn record_policy(&mut self, id: &PolicyID, loc: Option<&Loc>, effect: Effect) {
+        if let Some(loc) = loc {
+            self.policy_locs.insert(id.clone(), loc.clone());
+        }
+        if self.policy_effects.insert(id.clone(), effect).is_none() {
+            self.policy_order.push(id.clone());
         }
     }
 
@@ -71,6 +159,45 @@ impl ValidationResult {
         self.validation_warnings.iter()
     }
 
+    /// Get an iterator over the warnings found by the validator about the
+    /// policy set as a whole, rather than any one policy. These are not
+    /// included in [`ValidationResult::validation_warnings()`].
+    pub fn policy_set_warnings(&self) -> impl Iterator<Item = &PolicySetWarning> {
+        self.policy_set_warnings.iter()
+    }
+
+    /// Get the source location of the whole text of the policy or template
+    /// with this id, if it was validated and has a source location. This is
+    /// wider than the location attached to an error or warning, which covers
+    /// only the offending part of the policy.
+    pub fn policy_loc(&self, policy_id: &PolicyID) -> Option<&Loc> {
+        self.policy_locs.get(policy_id)
+    }
+
+    /// Get an iterator over the source locations of every validated policy or
+    /// template which has one.
+    pub fn policy_locs(&self) -> impl Iterator<Item = (&PolicyID, &Loc)> {
+        self.policy_locs.iter()
+    }
+
+    /// Get the ids of every validated policy or template, in the order they
+    /// appear in the validated policy set. Templates and static policies come
+    /// first, followed by template-linked policies.
+    pub fn policy_ids(&self) -> impl Iterator<Item = &PolicyID> {
+        self.policy_order.iter()
+    }
+
+    /// Get the effect of the policy or template with this id, if it was
+    /// validated
+    pub fn policy_effect(&self, policy_id: &PolicyID) -> Option<Effect> {
+        self.policy_effects.get(policy_id).copied()
+    }
+
+    /// Get an iterator over the effects of every validated policy or template
+    pub fn policy_effects(&self) -> impl Iterator<Item = (&PolicyID, Effect)> {
+        self.policy_effects.iter().map(|(id, effect)| (id, *effect))
+    }
+
     /// Get an iterator over the errors and warnings found by the validator.
     pub fn into_errors_and_warnings(
         self,
@@ -116,6 +243,25 @@ pub enum ValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     IncompatibleTypes(#[from] validation_errors::IncompatibleTypes),
+    /// The `then` and `else` branches of a conditional have incompatible
+    /// types.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    IncompatibleBranches(#[from] validation_errors::IncompatibleBranches),
+    /// The elements of a set literal have incompatible types.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    IncompatibleSetElements(#[from] validation_errors::IncompatibleSetElements),
+    /// The operands of an `==` or `in` expression confuse a set with a single
+    /// value.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    SetScalarConfusion(#[from] validation_errors::SetScalarConfusion),
+    /// An attribute of a record literal does not match the record type the
+    /// literal is compared with.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    RecordLiteralMismatch(#[from] validation_errors::RecordLiteralMismatch),
     /// The typechecker detected an access to a record or entity attribute
     /// that it could not statically guarantee would be present.
     #[error(transparent)]
@@ -156,6 +302,10 @@ pub enum ValidationError {
     #[diagnostic(transparent)]
     #[error(transparent)]
     NonLitExtConstructor(#[from] validation_errors::NonLitExtConstructor),
+    /// The policy could not be parsed, so it was not validated
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ParseError(#[from] validation_errors::ParseError),
     /// Returned when an internal invariant is violated (should not happen; if
     /// this is ever returned, please file an issue)
     #[error(transparent)]
@@ -171,20 +321,90 @@ pub enum ValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     EntityDerefLevelViolation(#[from] validation_errors::EntityDerefLevelViolation),
+    /// A template slot appears in a policy condition rather than in the scope
+    /// constraint it belongs to. The parser never produces such a template,
+    /// but one constructed directly may contain it.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MisplacedTemplateSlot(#[from] validation_errors::MisplacedTemplateSlot),
+    /// An arithmetic expression over integer literals always overflows, so
+    /// the policy will produce an evaluation error whenever it is evaluated.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ArithmeticOverflow(#[from] validation_errors::ArithmeticOverflow),
+    /// The resource scope constraint of a policy names an entity, but the
+    /// policy only applies to actions declared with no resource.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnspecifiedEntity(#[from] validation_errors::UnspecifiedEntity),
 }
 
 impl ValidationError {
+    /// The id of the policy where the validator found the issue
+    pub fn policy_id(&self) -> &PolicyID {
+        match self {
+            Self::UnrecognizedEntityType(e) => &e.policy_id,
+            Self::UnrecognizedActionId(e) => &e.policy_id,
+            Self::InvalidActionApplication(e) => &e.policy_id,
+            Self::UnexpectedType(e) => &e.policy_id,
+            Self::IncompatibleTypes(e) => &e.policy_id,
+            Self::IncompatibleBranches(e) => &e.policy_id,
+            Self::IncompatibleSetElements(e) => &e.policy_id,
+            Self::SetScalarConfusion(e) => &e.policy_id,
+            Self::RecordLiteralMismatch(e) => &e.policy_id,
+            Self::UnsafeAttributeAccess(e) => &e.policy_id,
+            Self::UnsafeOptionalAttributeAccess(e) => &e.policy_id,
+            Self::UnsafeTagAccess(e) => &e.policy_id,
+            Self::NoTagsAllowed(e) => &e.policy_id,
+            Self::UndefinedFunction(e) => &e.policy_id,
+            Self::WrongNumberArguments(e) => &e.policy_id,
+            Self::FunctionArgumentValidation(e) => &e.policy_id,
+            Self::EmptySetForbidden(e) => &e.policy_id,
+            Self::NonLitExtConstructor(e) => &e.policy_id,
+            Self::ParseError(e) => &e.policy_id,
+            Self::InternalInvariantViolation(e) => &e.policy_id,
+            Self::InvalidEnumEntity(e) => &e.policy_id,
+            Self::EntityDerefLevelViolation(e) => &e.policy_id,
+            Self::MisplacedTemplateSlot(e) => &e.policy_id,
+            Self::ArithmeticOverflow(e) => &e.policy_id,
+            Self::UnspecifiedEntity(e) => &e.policy_id,
+        }
+    }
+
+    /// For an [`ValidationError::UnexpectedType`] error, the type(s) which
+    /// were expected. `None` for all other errors.
+    pub fn expected_types(&self) -> Option<&[Type]> {
+        match self {
+            Self::UnexpectedType(e) => Some(e.expected_types()),
+            _ => None,
+        }
+    }
+
+    /// For an [`ValidationError::UnexpectedType`] error, the type which was
+    /// encountered. `None` for all other errors.
+    pub fn actual_type(&self) -> Option<&Type> {
+        match self {
+            Self::UnexpectedType(e) => Some(e.actual_type()),
+            _ => None,
+        }
+    }
+
     pub(crate) fn unrecognized_entity_type(
         source_loc: Option<Loc>,
         policy_id: PolicyID,
         actual_entity_type: String,
         suggested_entity_type: Option<String>,
     ) -> Self {
+        let suggestion_differs_only_in_case =
+            suggested_entity_type.as_deref().is_some_and(|suggested| {
+                validation_errors::differs_only_in_case(&actual_entity_type, suggested)
+            });
         validation_errors::UnrecognizedEntityType {
             source_loc,
             policy_id,
             actual_entity_type,
             suggested_entity_type,
+            suggestion_differs_only_in_case,
         }
         .into()
     }
@@ -195,12 +415,14 @@ impl ValidationError {
         policy_id: PolicyID,
         actual_action_id: String,
         hint: Option<UnrecognizedActionIdHelp>,
+        schema_location: Option<Loc>,
     ) -> Self {
         validation_errors::UnrecognizedActionId {
             source_loc,
             policy_id,
             actual_action_id,
             hint,
+            schema_location,
         }
         .into()
     }
@@ -209,12 +431,14 @@ impl ValidationError {
         source_loc: Option<Loc>,
         policy_id: PolicyID,
         would_in_fix_principal: bool,
+        would_in_fix_action: bool,
         would_in_fix_resource: bool,
     ) -> Self {
         validation_errors::InvalidActionApplication {
             source_loc,
             policy_id,
             would_in_fix_principal,
+            would_in_fix_action,
             would_in_fix_resource,
         }
         .into()
@@ -234,6 +458,7 @@ impl ValidationError {
             expected,
             actual,
             help,
+            operands: None,
         }
         .into()
     }
@@ -257,6 +482,76 @@ impl ValidationError {
         .into()
     }
 
+    pub(crate) fn incompatible_branches(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        then_branch: (Type, Option<Loc>),
+        else_branch: (Type, Option<Loc>),
+        hint: validation_errors::LubHelp,
+    ) -> Self {
+        validation_errors::IncompatibleBranches {
+            source_loc,
+            policy_id,
+            then_type: then_branch.0,
+            then_loc: then_branch.1,
+            else_type: else_branch.0,
+            else_loc: else_branch.1,
+            hint,
+        }
+        .into()
+    }
+
+    pub(crate) fn incompatible_set_elements(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        elements: Vec<(Type, Option<Loc>)>,
+        hint: validation_errors::LubHelp,
+    ) -> Self {
+        validation_errors::IncompatibleSetElements {
+            source_loc,
+            policy_id,
+            elements,
+            hint,
+        }
+        .into()
+    }
+
+    pub(crate) fn set_scalar_confusion(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        lhs: (Type, Option<Loc>),
+        rhs: (Type, Option<Loc>),
+        kind: validation_errors::SetScalarConfusionKind,
+    ) -> Self {
+        validation_errors::SetScalarConfusion {
+            source_loc,
+            policy_id,
+            lhs_type: lhs.0,
+            lhs_loc: lhs.1,
+            rhs_type: rhs.0,
+            rhs_loc: rhs.1,
+            kind,
+        }
+        .into()
+    }
+
+    pub(crate) fn record_literal_mismatch(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        attribute_path: Vec<SmolStr>,
+        expected_type: Type,
+        kind: validation_errors::RecordLiteralMismatchKind,
+    ) -> Self {
+        validation_errors::RecordLiteralMismatch {
+            source_loc,
+            policy_id,
+            attribute_path,
+            expected_type,
+            kind,
+        }
+        .into()
+    }
+
     pub(crate) fn unsafe_attribute_access(
         source_loc: Option<Loc>,
         policy_id: PolicyID,
@@ -366,6 +661,21 @@ impl ValidationError {
         .into()
     }
 
+    pub(crate) fn parse_error(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        msg: String,
+        help: Option<String>,
+    ) -> Self {
+        validation_errors::ParseError {
+            source_loc,
+            policy_id,
+            msg,
+            help,
+        }
+        .into()
+    }
+
     pub(crate) fn non_lit_ext_constructor(source_loc: Option<Loc>, policy_id: PolicyID) -> Self {
         validation_errors::NonLitExtConstructor {
             source_loc,
@@ -415,6 +725,47 @@ impl ValidationError {
         .into()
     }
 
+    pub(crate) fn misplaced_template_slot(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        slot: SlotId,
+    ) -> Self {
+        validation_errors::MisplacedTemplateSlot {
+            source_loc,
+            policy_id,
+            slot,
+        }
+        .into()
+    }
+
+    pub(crate) fn arithmetic_overflow(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        expr: String,
+    ) -> Self {
+        validation_errors::ArithmeticOverflow {
+            source_loc,
+            policy_id,
+            expr,
+        }
+        .into()
+    }
+
+    pub(crate) fn unspecified_entity(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        entity: EntityUID,
+        scope_constraint: &ResourceConstraint,
+    ) -> Self {
+        validation_errors::UnspecifiedEntity {
+            source_loc,
+            policy_id,
+            entity,
+            scope_constraint: scope_constraint.to_smolstr(),
+        }
+        .into()
+    }
+
     pub(crate) fn literal_dereference_target(source_loc: Option<Loc>, policy_id: PolicyID) -> Self {
         validation_errors::EntityDerefLevelViolation {
             source_loc,
@@ -453,6 +804,39 @@ pub enum ValidationWarning {
     #[diagnostic(transparent)]
     #[error(transparent)]
     ImpossiblePolicy(#[from] validation_warnings::ImpossiblePolicy),
+    /// An `action in` scope constraint references an action group with no
+    /// member actions which does not apply to any request itself.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    EmptyActionGroup(#[from] validation_warnings::EmptyActionGroup),
+    /// A `permit` policy has no scope constraints and no conditions, so it
+    /// allows every request. This warning is only reported if enabled with
+    /// [`crate::validator::Validator::with_overly_permissive_policy_warning`].
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    OverlyPermissivePolicy(#[from] validation_warnings::OverlyPermissivePolicy),
+    /// An attribute is accessed with dot syntax but is named like a built-in
+    /// or extension method, so the access may be confused with a method call.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    AmbiguousAttributeName(#[from] validation_warnings::AmbiguousAttributeName),
+    /// A `like` pattern is empty or contains no wildcards, so the `like` is
+    /// equivalent to an `==` comparison.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    SuspiciousLikePattern(#[from] validation_warnings::SuspiciousLikePattern),
+    /// An expression mixes `&&` and `||`, or applies `!` to the operand of a
+    /// comparison, without parentheses. This warning is only reported if
+    /// enabled with
+    /// [`crate::validator::Validator::with_ambiguous_precedence_warning`].
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    AmbiguousPrecedence(#[from] validation_warnings::AmbiguousPrecedence),
+    /// An `is` test in a policy condition is always true or always false for
+    /// the entity types in the schema.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    TrivialTypeTest(#[from] validation_warnings::TrivialTypeTest),
 }
 
 impl ValidationWarning {
@@ -530,4 +914,168 @@ impl ValidationWarning {
         }
         .into()
     }
+
+    pub(crate) fn empty_action_group(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        action_group: impl Into<String>,
+    ) -> Self {
+        validation_warnings::EmptyActionGroup {
+            source_loc,
+            policy_id,
+            action_group: action_group.into(),
+        }
+        .into()
+    }
+
+    pub(crate) fn overly_permissive_policy(source_loc: Option<Loc>, policy_id: PolicyID) -> Self {
+        validation_warnings::OverlyPermissivePolicy {
+            source_loc,
+            policy_id,
+        }
+        .into()
+    }
+
+    pub(crate) fn ambiguous_attribute_name(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        attribute: impl Into<String>,
+    ) -> Self {
+        validation_warnings::AmbiguousAttributeName {
+            source_loc,
+            policy_id,
+            attribute: attribute.into(),
+        }
+        .into()
+    }
+
+    pub(crate) fn suspicious_like_pattern(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        pattern: impl Into<String>,
+        kind: validation_warnings::SuspiciousLikePatternKind,
+    ) -> Self {
+        validation_warnings::SuspiciousLikePattern {
+            source_loc,
+            policy_id,
+            pattern: pattern.into(),
+            kind,
+        }
+        .into()
+    }
+
+    pub(crate) fn ambiguous_precedence(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        parenthesized: impl Into<String>,
+        kind: validation_warnings::AmbiguousPrecedenceKind,
+    ) -> Self {
+        validation_warnings::AmbiguousPrecedence {
+            source_loc,
+            policy_id,
+            parenthesized: parenthesized.into(),
+            kind,
+        }
+        .into()
+    }
+
+    pub(crate) fn trivial_type_test(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        test: impl Into<String>,
+        kind: validation_warnings::TrivialTypeTestKind,
+    ) -> Self {
+        validation_warnings::TrivialTypeTest {
+            source_loc,
+            policy_id,
+            test: test.into(),
+            kind,
+        }
+        .into()
+    }
+
+    /// The id of the policy where the validator found the issue
+    pub fn policy_id(&self) -> &PolicyID {
+        match self {
+            Self::MixedScriptString(w) => &w.policy_id,
+            Self::BidiCharsInString(w) => &w.policy_id,
+            Self::BidiCharsInIdentifier(w) => &w.policy_id,
+            Self::MixedScriptIdentifier(w) => &w.policy_id,
+            Self::ConfusableIdentifier(w) => &w.policy_id,
+            Self::ImpossiblePolicy(w) => &w.policy_id,
+            Self::EmptyActionGroup(w) => &w.policy_id,
+            Self::OverlyPermissivePolicy(w) => &w.policy_id,
+            Self::AmbiguousAttributeName(w) => &w.policy_id,
+            Self::SuspiciousLikePattern(w) => &w.policy_id,
+            Self::AmbiguousPrecedence(w) => &w.policy_id,
+            Self::TrivialTypeTest(w) => &w.policy_id,
+        }
+    }
+
+    /// Metadata describing the kind of this warning
+    pub fn kind_info(&self) -> &'static WarningKindInfo {
+        match self {
+            Self::MixedScriptString(_) => &validation_warnings::MixedScriptString::KIND,
+            Self::BidiCharsInString(_) => &validation_warnings::BidiCharsInString::KIND,
+            Self::BidiCharsInIdentifier(_) => &validation_warnings::BidiCharsInIdentifier::KIND,
+            Self::MixedScriptIdentifier(_) => &validation_warnings::MixedScriptIdentifier::KIND,
+            Self::ConfusableIdentifier(_) => &validation_warnings::ConfusableIdentifier::KIND,
+            Self::ImpossiblePolicy(_) => &validation_warnings::ImpossiblePolicy::KIND,
+            Self::EmptyActionGroup(_) => &validation_warnings::EmptyActionGroup::KIND,
+            Self::OverlyPermissivePolicy(_) => &validation_warnings::OverlyPermissivePolicy::KIND,
+            Self::AmbiguousAttributeName(_) => &validation_warnings::AmbiguousAttributeName::KIND,
+            Self::SuspiciousLikePattern(_) => &validation_warnings::SuspiciousLikePattern::KIND,
+            Self::AmbiguousPrecedence(_) => &validation_warnings::AmbiguousPrecedence::KIND,
+            Self::TrivialTypeTest(_) => &validation_warnings::TrivialTypeTest::KIND,
+        }
+    }
+}
+
+/// A warning generated by the validator about the policy set as a whole, rather
+/// than any one policy
+#[derive(Debug, Clone, PartialEq, Diagnostic, Error, Eq, Hash)]
+pub enum PolicySetWarning {
+    /// The policy set being validated contains no policies or templates. This
+    /// warning is only reported if enabled with
+    /// [`crate::validator::Validator::with_empty_policy_set_warning`].
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    EmptyPolicySet(#[from] validation_warnings::EmptyPolicySet),
+}
+
+impl PolicySetWarning {
+    pub(crate) fn empty_policy_set() -> Self {
+        validation_warnings::EmptyPolicySet.into()
+    }
+
+    /// Metadata describing the kind of this warning
+    pub fn kind_info(&self) -> &'static WarningKindInfo {
+        match self {
+            Self::EmptyPolicySet(_) => &validation_warnings::EmptyPolicySet::KIND,
+        }
+    }
+}
+
+/// Metadata for every kind of warning the validator can emit, in the order of
+/// the [`ValidationWarning`] variants followed by the [`PolicySetWarning`]
+/// variants
+static ALL_WARNING_KINDS: [WarningKindInfo; 13] = [
+    validation_warnings::MixedScriptString::KIND,
+    validation_warnings::BidiCharsInString::KIND,
+    validation_warnings::BidiCharsInIdentifier::KIND,
+    validation_warnings::MixedScriptIdentifier::KIND,
+    validation_warnings::ConfusableIdentifier::KIND,
+    validation_warnings::ImpossiblePolicy::KIND,
+    validation_warnings::EmptyActionGroup::KIND,
+    validation_warnings::OverlyPermissivePolicy::KIND,
+    validation_warnings::AmbiguousAttributeName::KIND,
+    validation_warnings::SuspiciousLikePattern::KIND,
+    validation_warnings::AmbiguousPrecedence::KIND,
+    validation_warnings::TrivialTypeTest::KIND,
+    validation_warnings::EmptyPolicySet::KIND,
+];
+
+/// Get metadata for every kind of warning the validator can emit
+pub fn all_warning_kinds() -> &'static [WarningKindInfo] {
+    &ALL_WARNING_KINDS
 }
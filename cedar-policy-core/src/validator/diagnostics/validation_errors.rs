@@ -18,6 +18,7 @@
 
 use crate::entities::conformance::err::InvalidEnumEntityError;
 use crate::parser::Loc;
+use educe::Educe;
 use miette::Diagnostic;
 use thiserror::Error;
 
@@ -27,7 +28,9 @@ use crate::fuzzy_match::fuzzy_search;
 
 use std::collections::BTreeSet;
 
-use crate::ast::{is_normalized_ident, Eid, EntityType, EntityUID, Expr, ExprKind, PolicyID, Var};
+use crate::ast::{
+    is_normalized_ident, Eid, EntityType, EntityUID, Expr, ExprKind, PolicyID, SlotId, Var,
+};
 use crate::parser::join_with_conjunction;
 
 use crate::validator::level_validate::EntityDerefLevel;
@@ -50,6 +53,9 @@ pub struct UnrecognizedEntityType {
     /// An entity type from the schema that the user might reasonably have
     /// intended to write.
     pub suggested_entity_type: Option<String>,
+    /// True if `suggested_entity_type` differs from `actual_entity_type` only
+    /// in letter case.
+    pub suggestion_differs_only_in_case: bool,
 }
 
 impl Diagnostic for UnrecognizedEntityType {
@@ -57,6 +63,9 @@ impl Diagnostic for UnrecognizedEntityType {
 
     fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
         match &self.suggested_entity_type {
+            Some(s) if self.suggestion_differs_only_in_case => Some(Box::new(format!(
+                "entity types are case-sensitive; did you mean `{s}`?"
+            ))),
             Some(s) => Some(Box::new(format!("did you mean `{s}`?"))),
             None => None,
         }
@@ -64,13 +73,25 @@ impl Diagnostic for UnrecognizedEntityType {
 }
 
 /// Given an unknown entity type and a schema, compute a suggestion for what
-/// entity type may have been intended.
+/// entity type may have been intended. An entity type which differs only in
+/// letter case is preferred over any other similar entity type.
 pub fn get_suggested_entity_type(unknown: &EntityType, schema: &ValidatorSchema) -> Option<String> {
+    let unknown = unknown.to_string();
     let known_entity_types = schema
         .entity_type_names()
         .map(ToString::to_string)
         .collect::<Vec<_>>();
-    fuzzy_search(&unknown.to_string(), known_entity_types.as_slice())
+    known_entity_types
+        .iter()
+        .find(|known| differs_only_in_case(&unknown, known))
+        .cloned()
+        .or_else(|| fuzzy_search(&unknown, known_entity_types.as_slice()))
+}
+
+/// Returns true if `actual` and `known` are different, but equal when letter
+/// case is ignored.
+pub(crate) fn differs_only_in_case(actual: &str, known: &str) -> bool {
+    actual != known && actual.to_lowercase() == known.to_lowercase()
 }
 
 /// Structure containing details about an unrecognized action id error.
@@ -85,6 +106,9 @@ pub struct UnrecognizedActionId {
     pub actual_action_id: String,
     /// Hint for resolving the error
     pub hint: Option<UnrecognizedActionIdHelp>,
+    /// Source location of the declaration in the schema of the action
+    /// suggested by `hint`, if available
+    pub schema_location: Option<Loc>,
 }
 
 impl Diagnostic for UnrecognizedActionId {
@@ -106,6 +130,34 @@ pub enum UnrecognizedActionIdHelp {
     /// Suggest an alternative action
     #[error("did you mean `{0}`?")]
     SuggestAlternative(String),
+    /// Suggest an action which differs only in letter case
+    #[error("action ids are case-sensitive; did you mean `{0}`?")]
+    SuggestCaseSensitiveAlternative(String),
+    /// Suggest an alternative action group, for an unrecognized action id
+    /// used as an action group in an `action in` scope constraint
+    #[error("did you mean the action group `{0}`?")]
+    SuggestActionGroup(String),
+}
+
+impl UnrecognizedActionIdHelp {
+    /// The action suggested by this hint
+    pub fn suggestion(&self) -> &str {
+        match self {
+            Self::AvoidActionTypeInActionId(action)
+            | Self::SuggestAlternative(action)
+            | Self::SuggestCaseSensitiveAlternative(action)
+            | Self::SuggestActionGroup(action) => action,
+        }
+    }
+
+    /// Source location of the declaration of the suggested action in the
+    /// schema, if available
+    pub(crate) fn schema_location(&self, schema: &ValidatorSchema) -> Option<Loc> {
+        schema
+            .action_ids()
+            .find(|action_id| action_id.name().to_string() == self.suggestion())
+            .and_then(|action_id| action_id.loc().cloned())
+    }
 }
 
 /// Determine the help to offer in the presence of an unrecognized action id error.
@@ -126,17 +178,57 @@ pub fn unrecognized_action_id_help(
         Some(UnrecognizedActionIdHelp::AvoidActionTypeInActionId(
             id.name().to_string(),
         ))
+    } else if let Some(id) = schema
+        .action_ids()
+        .find(|action_id| differs_only_in_case(&euid.to_string(), &action_id.name().to_string()))
+    {
+        // If the user got only the letter case wrong, say so
+        Some(UnrecognizedActionIdHelp::SuggestCaseSensitiveAlternative(
+            id.name().to_string(),
+        ))
     } else {
         // Otherwise, suggest using another id
         let euids_strs = schema
             .action_ids()
             .map(|id| id.name().to_string())
             .collect::<Vec<_>>();
-        fuzzy_search(euid.eid().as_ref(), &euids_strs)
+        fuzzy_search(&euid.to_string(), &euids_strs)
             .map(UnrecognizedActionIdHelp::SuggestAlternative)
     }
 }
 
+/// Determine the help to offer for an unrecognized action id used as an action
+/// group, i.e., in an `action in` scope constraint. Alternatives are drawn
+/// from the action groups declared in the schema, if there are any.
+pub fn unrecognized_action_group_help(
+    euid: &EntityUID,
+    schema: &ValidatorSchema,
+) -> Option<UnrecognizedActionIdHelp> {
+    let groups = schema
+        .action_ids()
+        .filter(|action_id| action_id.descendants().next().is_some())
+        .map(|action_id| action_id.name().to_string())
+        .collect::<Vec<_>>();
+    match unrecognized_action_id_help(euid, schema) {
+        // Suggestions about the form of the id take priority
+        Some(help @ UnrecognizedActionIdHelp::AvoidActionTypeInActionId(_)) => Some(help),
+        help if groups.is_empty() => help,
+        _ => {
+            if let Some(group) = groups
+                .iter()
+                .find(|group| differs_only_in_case(&euid.to_string(), group))
+            {
+                Some(UnrecognizedActionIdHelp::SuggestCaseSensitiveAlternative(
+                    group.clone(),
+                ))
+            } else {
+                fuzzy_search(&euid.to_string(), &groups)
+                    .map(UnrecognizedActionIdHelp::SuggestActionGroup)
+            }
+        }
+    }
+}
+
 /// Structure containing details about an invalid action application error.
 #[derive(Debug, Clone, Error, Hash, Eq, PartialEq)]
 #[error("for policy `{policy_id}`, unable to find an applicable action given the policy scope constraints")]
@@ -147,6 +239,9 @@ pub struct InvalidActionApplication {
     pub policy_id: PolicyID,
     /// `true` if changing `==` to `in` would fix the principal clause
     pub would_in_fix_principal: bool,
+    /// `true` if the action clause uses `==` with an action group which
+    /// declares no `appliesTo`, so changing `==` to `in` may fix it
+    pub would_in_fix_action: bool,
     /// `true` if changing `==` to `in` would fix the resource clause
     pub would_in_fix_resource: bool,
 }
@@ -155,23 +250,31 @@ impl Diagnostic for InvalidActionApplication {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
 
     fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
-        match (self.would_in_fix_principal, self.would_in_fix_resource) {
-            (true, false) => Some(Box::new(
-                "try replacing `==` with `in` in the principal clause",
-            )),
-            (false, true) => Some(Box::new(
-                "try replacing `==` with `in` in the resource clause",
-            )),
-            (true, true) => Some(Box::new(
-                "try replacing `==` with `in` in the principal clause and the resource clause",
-            )),
-            (false, false) => None,
+        let clauses = [
+            (self.would_in_fix_principal, "the principal clause"),
+            (self.would_in_fix_action, "the action clause"),
+            (self.would_in_fix_resource, "the resource clause"),
+        ]
+        .into_iter()
+        .filter_map(|(would_in_fix, clause)| would_in_fix.then_some(clause))
+        .join(" and ");
+        if clauses.is_empty() {
+            None
+        } else if self.would_in_fix_action {
+            Some(Box::new(format!(
+                "the action is an action group which does not apply to any request itself; try replacing `==` with `in` in {clauses}"
+            )))
+        } else {
+            Some(Box::new(format!(
+                "try replacing `==` with `in` in {clauses}"
+            )))
         }
     }
 }
 
 /// Structure containing details about an unexpected type error.
-#[derive(Error, Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Educe, Error, Debug, Clone)]
+#[educe(Hash, PartialEq, Eq)]
 #[error("for policy `{policy_id}`, unexpected type: expected {} but saw {}",
     match .expected.iter().next() {
         Some(single) if .expected.len() == 1 => format!("{single}"),
@@ -189,16 +292,100 @@ pub struct UnexpectedType {
     pub actual: Type,
     /// Optional help for resolving the error
     pub help: Option<UnexpectedTypeHelp>,
+    /// If the unexpected type is that of an operand of a binary operator, the
+    /// operator and both of its operands, which are only used to label the
+    /// error
+    #[educe(PartialEq(ignore))]
+    #[educe(Hash(ignore))]
+    pub operands: Option<BinaryOperands>,
+}
+
+impl UnexpectedType {
+    /// The type(s) which were expected. The actual type should have been a
+    /// subtype of one of these.
+    pub fn expected_types(&self) -> &[Type] {
+        &self.expected
+    }
+
+    /// The type which was encountered
+    pub fn actual_type(&self) -> &Type {
+        &self.actual
+    }
 }
 
 impl Diagnostic for UnexpectedType {
-    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.source_loc
+            .as_ref()
+            .map(|loc| loc as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let loc = self.source_loc.as_ref()?;
+        match &self.operands {
+            Some(operands) => Some(Box::new(operands.labels(&self.expected, loc))),
+            None => Some(Box::new(std::iter::once(miette::LabeledSpan::underline(
+                loc.span,
+            )))),
+        }
+    }
 
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.help.as_ref().map(|h| Box::new(h) as Box<dyn Display>)
     }
 }
 
+/// The operator and operands of a binary operator, such as `+`, used to label
+/// each of them in an [`UnexpectedType`] error on one of the operands
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct BinaryOperands {
+    /// Source location of the operator
+    pub operator_loc: Option<Loc>,
+    /// Source location of the left operand
+    pub left_loc: Option<Loc>,
+    /// Type of the left operand, if it could be inferred
+    pub left_type: Option<Type>,
+    /// Source location of the right operand
+    pub right_loc: Option<Loc>,
+    /// Type of the right operand, if it could be inferred
+    pub right_type: Option<Type>,
+}
+
+impl BinaryOperands {
+    /// Labels for both operands, with their types, and for the operator, with
+    /// the types it `expected`. The label of the operand at `error_loc`, which
+    /// has the unexpected type, is the primary label.
+    fn labels<'a>(
+        &'a self,
+        expected: &'a [Type],
+        error_loc: &'a Loc,
+    ) -> impl Iterator<Item = miette::LabeledSpan> + 'a {
+        let operand_label = |loc: &Option<Loc>, ty: &Option<Type>| {
+            loc.as_ref().map(|loc| {
+                let label = ty.as_ref().map(|ty| format!("has type `{ty}`"));
+                if loc.span == error_loc.span {
+                    miette::LabeledSpan::new_primary_with_span(label, loc.span)
+                } else {
+                    miette::LabeledSpan::new_with_span(label, loc.span)
+                }
+            })
+        };
+        let operator_label = self.operator_loc.as_ref().map(|loc| {
+            miette::LabeledSpan::new_with_span(
+                Some(format!(
+                    "expects operands of type {}",
+                    expected.iter().map(|ty| format!("`{ty}`")).join(", or ")
+                )),
+                loc.span,
+            )
+        });
+        operand_label(&self.left_loc, &self.left_type)
+            .into_iter()
+            .chain(operator_label)
+            .chain(operand_label(&self.right_loc, &self.right_type))
+    }
+}
+
 /// Help for resolving a type error
 #[derive(Error, Debug, Clone, Hash, Eq, PartialEq)]
 pub enum UnexpectedTypeHelp {
@@ -270,6 +457,167 @@ impl Display for IncompatibleTypes {
     }
 }
 
+/// Structure containing details about a conditional whose `then` and `else`
+/// branches have incompatible types, e.g., `if context.b then 1 else "x"`
+#[derive(Error, Debug, Clone, Hash, PartialEq, Eq)]
+#[error("for policy `{policy_id}`, the branches of a conditional have incompatible types: the `then` branch has type `{then_type}`, but the `else` branch has type `{else_type}`")]
+pub struct IncompatibleBranches {
+    /// Source location of the whole conditional
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// Type of the `then` branch
+    pub then_type: Type,
+    /// Source location of the `then` branch
+    pub then_loc: Option<Loc>,
+    /// Type of the `else` branch
+    pub else_type: Type,
+    /// Source location of the `else` branch
+    pub else_loc: Option<Loc>,
+    /// Hint for resolving the error
+    pub hint: LubHelp,
+}
+
+impl Diagnostic for IncompatibleBranches {
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.then_loc
+            .as_ref()
+            .or(self.else_loc.as_ref())
+            .or(self.source_loc.as_ref())
+            .map(|loc| loc as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let label = |loc: &Option<Loc>, ty: &Type| {
+            loc.as_ref().map(|loc| {
+                miette::LabeledSpan::new_with_span(Some(format!("has type `{ty}`")), loc.span)
+            })
+        };
+        let labels = [
+            label(&self.then_loc, &self.then_type),
+            label(&self.else_loc, &self.else_type),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        if labels.is_empty() {
+            self.source_loc
+                .as_ref()
+                .map(|loc| Box::new(std::iter::once(miette::LabeledSpan::underline(loc.span))) as _)
+        } else {
+            Some(Box::new(labels.into_iter()))
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!(
+            "{} must have compatible types. {}. Try making both branches have type `{}`, or both have type `{}`, or moving the operation applied to the conditional into each branch",
+            LubContext::Conditional,
+            self.hint,
+            self.then_type,
+            self.else_type,
+        )))
+    }
+}
+
+impl IncompatibleBranches {
+    /// Type of the `then` branch
+    pub fn then_type(&self) -> &Type {
+        &self.then_type
+    }
+
+    /// Type of the `else` branch
+    pub fn else_type(&self) -> &Type {
+        &self.else_type
+    }
+
+    /// Source location of the `then` branch
+    pub fn then_loc(&self) -> Option<&Loc> {
+        self.then_loc.as_ref()
+    }
+
+    /// Source location of the `else` branch
+    pub fn else_loc(&self) -> Option<&Loc> {
+        self.else_loc.as_ref()
+    }
+}
+
+/// Structure containing details about a set literal whose elements have
+/// incompatible types, e.g., `[1, "a"]`
+#[derive(Error, Debug, Clone, Hash, PartialEq, Eq)]
+pub struct IncompatibleSetElements {
+    /// Source location of the whole set literal
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// Type and source location of each element of the set literal, in the
+    /// order they appear in it
+    pub elements: Vec<(Type, Option<Loc>)>,
+    /// Hint for resolving the error
+    pub hint: LubHelp,
+}
+
+impl Diagnostic for IncompatibleSetElements {
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.elements
+            .iter()
+            .find_map(|(_, loc)| loc.as_ref())
+            .or(self.source_loc.as_ref())
+            .map(|loc| loc as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let labels = self
+            .elements
+            .iter()
+            .filter_map(|(ty, loc)| {
+                loc.as_ref().map(|loc| {
+                    miette::LabeledSpan::new_with_span(Some(format!("has type `{ty}`")), loc.span)
+                })
+            })
+            .collect::<Vec<_>>();
+        if labels.is_empty() {
+            self.source_loc
+                .as_ref()
+                .map(|loc| Box::new(std::iter::once(miette::LabeledSpan::underline(loc.span))) as _)
+        } else {
+            Some(Box::new(labels.into_iter()))
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!(
+            "{} must have compatible types. {}",
+            LubContext::Set,
+            self.hint,
+        )))
+    }
+}
+
+impl Display for IncompatibleSetElements {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "for policy `{}`, the elements of a set literal have incompatible types: the elements have types ",
+            self.policy_id
+        )?;
+        join_with_conjunction(
+            f,
+            "and",
+            self.elements.iter().map(|(ty, _)| ty).unique(),
+            |f, ty| write!(f, "`{ty}`"),
+        )
+    }
+}
+
+impl IncompatibleSetElements {
+    /// Type and source location of each element of the set literal, in the
+    /// order they appear in it
+    pub fn elements(&self) -> impl Iterator<Item = (&Type, Option<&Loc>)> {
+        self.elements.iter().map(|(ty, loc)| (ty, loc.as_ref()))
+    }
+}
+
 /// Hints for resolving an incompatible-types error
 #[derive(Error, Debug, Clone, Hash, Eq, PartialEq)]
 pub enum LubHelp {
@@ -313,6 +661,176 @@ pub enum LubContext {
     GetTag,
 }
 
+/// Structure containing details about an `==` or `in` expression which
+/// confuses a set with a single value.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Error)]
+#[error("for policy `{policy_id}`, {kind}: the operands have types `{lhs_type}` and `{rhs_type}`")]
+pub struct SetScalarConfusion {
+    /// Source location of the whole `==` or `in` expression
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// Type of the left operand
+    pub lhs_type: Type,
+    /// Source location of the left operand
+    pub lhs_loc: Option<Loc>,
+    /// Type of the right operand
+    pub rhs_type: Type,
+    /// Source location of the right operand
+    pub rhs_loc: Option<Loc>,
+    /// How the set and single value were confused
+    pub kind: SetScalarConfusionKind,
+}
+
+impl Diagnostic for SetScalarConfusion {
+    impl_diagnostic_from_two_source_loc_opt_fields!(lhs_loc, rhs_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(match self.kind {
+            SetScalarConfusionKind::SetEqualsElement | SetScalarConfusionKind::ElementInSet => {
+                "try using `.contains()` to test if the set contains the value"
+            }
+            SetScalarConfusionKind::InSingleValue => {
+                "try using `==` to compare single values; `in` tests entity hierarchy membership"
+            }
+        }))
+    }
+}
+
+impl SetScalarConfusion {
+    /// Type of the left operand
+    pub fn lhs_type(&self) -> &Type {
+        &self.lhs_type
+    }
+
+    /// Type of the right operand
+    pub fn rhs_type(&self) -> &Type {
+        &self.rhs_type
+    }
+
+    /// Source location of the left operand
+    pub fn lhs_loc(&self) -> Option<&Loc> {
+        self.lhs_loc.as_ref()
+    }
+
+    /// Source location of the right operand
+    pub fn rhs_loc(&self) -> Option<&Loc> {
+        self.rhs_loc.as_ref()
+    }
+
+    /// How the set and single value were confused
+    pub fn kind(&self) -> SetScalarConfusionKind {
+        self.kind
+    }
+}
+
+/// The ways a set can be confused with a single value
+#[derive(Error, Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum SetScalarConfusionKind {
+    /// `==` between a set and a value of its element type, e.g.,
+    /// `resource.tags == "admin"` where `tags` is a `Set<String>`
+    #[error("`==` compares a set with a single value")]
+    SetEqualsElement,
+    /// `in` with a non-entity value on the left and a set of such values on the
+    /// right, e.g., `"admin" in resource.tags` where `tags` is a `Set<String>`
+    #[error("`in` tests a non-entity value for membership in a set")]
+    ElementInSet,
+    /// `in` between two non-entity, non-set values, e.g.,
+    /// `"admin" in resource.tag` where `tag` is a `String`
+    #[error("`in` is applied to two single non-entity values")]
+    InSingleValue,
+}
+
+/// Structure containing details about an attribute of a record literal which
+/// does not match the record type the literal is compared with, e.g., in
+/// `context.address == { street: "Main St", zip: 12345 }`
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Error)]
+#[error("for policy `{policy_id}`, {}", RecordLiteralMismatchMessage(self))]
+pub struct RecordLiteralMismatch {
+    /// Source location of the attribute's value, or of the whole record
+    /// literal for a missing attribute
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// Path to the attribute, starting with an attribute of the outermost
+    /// record literal
+    pub attribute_path: Vec<SmolStr>,
+    /// The record type the outermost record literal was expected to have
+    pub expected_type: Type,
+    /// How the attribute does not match the record type
+    pub kind: RecordLiteralMismatchKind,
+}
+
+/// Error message for a [`RecordLiteralMismatch`]
+struct RecordLiteralMismatchMessage<'a>(&'a RecordLiteralMismatch);
+
+impl Display for RecordLiteralMismatchMessage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let attr = self.0.attribute_path.iter().join(".");
+        match &self.0.kind {
+            RecordLiteralMismatchKind::ExtraAttribute => {
+                write!(f, "record literal has attribute `{attr}`, which the expected record type does not have")
+            }
+            RecordLiteralMismatchKind::MissingAttribute => {
+                write!(
+                    f,
+                    "record literal is missing the required attribute `{attr}`"
+                )
+            }
+            RecordLiteralMismatchKind::AttributeType { expected, actual } => {
+                write!(f, "attribute `{attr}` of record literal has type `{actual}`, but the expected record type requires `{expected}`")
+            }
+        }
+    }
+}
+
+impl Diagnostic for RecordLiteralMismatch {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!(
+            "the record literal must match the record type `{}`",
+            self.expected_type
+        )))
+    }
+}
+
+impl RecordLiteralMismatch {
+    /// Path to the attribute, starting with an attribute of the outermost
+    /// record literal
+    pub fn attribute_path(&self) -> &[SmolStr] {
+        &self.attribute_path
+    }
+
+    /// The record type the outermost record literal was expected to have
+    pub fn expected_type(&self) -> &Type {
+        &self.expected_type
+    }
+
+    /// How the attribute does not match the record type
+    pub fn kind(&self) -> &RecordLiteralMismatchKind {
+        &self.kind
+    }
+}
+
+/// The ways an attribute of a record literal can fail to match a record type
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum RecordLiteralMismatchKind {
+    /// The record literal has an attribute which the record type does not have
+    ExtraAttribute,
+    /// The record literal does not have an attribute which is required by the
+    /// record type
+    MissingAttribute,
+    /// The record literal's attribute has a type which is not compatible with
+    /// the record type's attribute
+    AttributeType {
+        /// Type of the attribute in the record type
+        expected: Type,
+        /// Type of the attribute in the record literal
+        actual: Type,
+    },
+}
+
 /// Structure containing details about a missing attribute error.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Error)]
 #[error("for policy `{policy_id}`, attribute {attribute_access} not found")]
@@ -543,6 +1061,161 @@ impl Diagnostic for EmptySetForbidden {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
 }
 
+/// The policy could not be parsed, so it was not validated
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
+#[error("for policy `{policy_id}`, failed to parse policy: {msg}")]
+pub struct ParseError {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID the policy would have had if it had parsed
+    pub policy_id: PolicyID,
+    /// Error message from the parser
+    pub msg: String,
+    /// Optional help for resolving the error
+    pub help: Option<String>,
+}
+
+impl Diagnostic for ParseError {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.help.as_ref().map(|h| Box::new(h) as Box<dyn Display>)
+    }
+}
+
+/// A template slot appears outside the scope constraint it belongs to
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
+#[error("for policy `{policy_id}`, template slot `{slot}` is not allowed in a policy condition")]
+pub struct MisplacedTemplateSlot {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// The misplaced slot
+    pub slot: SlotId,
+}
+
+impl Diagnostic for MisplacedTemplateSlot {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(
+            "`?principal` may only appear in the principal scope constraint, and `?resource` only in the resource scope constraint",
+        ))
+    }
+}
+
+/// A constant arithmetic expression always overflows a 64-bit integer
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
+#[error("for policy `{policy_id}`, arithmetic expression `{expr}` always overflows")]
+pub struct ArithmeticOverflow {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// The overflowing expression, as it would be printed in policy text
+    pub expr: String,
+}
+
+impl Diagnostic for ArithmeticOverflow {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(
+            "Cedar integers are 64-bit signed integers, and evaluating this expression will produce an overflow error",
+        ))
+    }
+}
+
+/// The resource scope constraint of a policy names an entity, but the policy
+/// can only apply to actions declared with no resource, so it can never apply
+/// to any request
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
+#[error("for policy `{policy_id}`, the resource scope constraint `{scope_constraint}` uses the entity `{entity}`, but the policy only applies to actions declared with no resource")]
+pub struct UnspecifiedEntity {
+    /// Source location of the entity literal in the resource scope constraint
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// The entity in the resource scope constraint
+    pub entity: EntityUID,
+    /// The resource scope constraint, as it would be printed in policy text,
+    /// e.g., `resource == Doc::"d"`
+    pub scope_constraint: SmolStr,
+}
+
+impl Diagnostic for UnspecifiedEntity {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(
+            "the resource of a request for an action with no resource is the action itself; remove the resource scope constraint",
+        ))
+    }
+}
+
+/// An entity is missing an attribute which the schema declares as required
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
+#[error("for entity `{entity}`, required attribute `{attribute}` is missing")]
+pub struct MissingRequiredAttribute {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Entity where the error occurred
+    pub entity: EntityUID,
+    /// Name of the missing attribute
+    pub attribute: SmolStr,
+}
+
+impl Diagnostic for MissingRequiredAttribute {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+}
+
+/// An entity attribute has a value which does not match the type declared for
+/// it in the schema
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
+#[error(
+    "for entity `{entity}`, attribute `{attribute}` does not match its declared type: {detail}"
+)]
+pub struct EntityAttributeTypeMismatch {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Entity where the error occurred
+    pub entity: EntityUID,
+    /// Name of the attribute with the mismatched value
+    pub attribute: SmolStr,
+    /// Description of the mismatch
+    pub detail: String,
+}
+
+impl Diagnostic for EntityAttributeTypeMismatch {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+}
+
+/// An entity has a parent whose type the schema does not allow as a parent of
+/// the entity's type
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
+#[error("for entity `{entity}`, `{}` is not allowed as a parent because its type is not in the `memberOfTypes` of `{}`", parent, entity.entity_type())]
+pub struct InvalidEntityParent {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Entity where the error occurred
+    pub entity: EntityUID,
+    /// The disallowed parent
+    pub parent: EntityUID,
+}
+
+impl Diagnostic for InvalidEntityParent {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!(
+            "add `{}` to the `memberOfTypes` of `{}` in the schema, or remove the parent",
+            self.parent.entity_type(),
+            self.entity.entity_type()
+        )))
+    }
+}
+
 /// The policy passes a non-literal to an extension constructor, which is
 /// forbidden in strict validation
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
@@ -28,9 +28,57 @@ macro_rules! impl_diagnostic_warning {
 }
 
 use crate::{ast::PolicyID, parser::Loc};
-use miette::Diagnostic;
+use miette::{Diagnostic, Severity};
 use thiserror::Error;
 
+/// Broad category of a kind of validator warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WarningCategory {
+    /// Text in a policy, such as a string or identifier, may be confusing or
+    /// deceptive to a human reader
+    TextSafety,
+    /// A policy is valid, but probably does not do what was intended
+    PolicyLogic,
+    /// The policy set as a whole, rather than any particular policy, is
+    /// suspicious
+    PolicySet,
+}
+
+/// Metadata describing a kind of validator warning, e.g., for displaying a
+/// settings UI where users can enable or disable warnings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarningKindInfo {
+    code: &'static str,
+    category: WarningCategory,
+    default_severity: Severity,
+    description: &'static str,
+}
+
+impl WarningKindInfo {
+    /// Stable, unique, kebab-case identifier for this kind of warning, e.g.,
+    /// `mixed-script-string`
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// Category of this kind of warning
+    pub fn category(&self) -> WarningCategory {
+        self.category
+    }
+
+    /// Severity of this kind of warning, as reported by
+    /// [`Diagnostic::severity`]
+    pub fn default_severity(&self) -> Severity {
+        self.default_severity
+    }
+
+    /// Human-readable description of this kind of warning
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+}
+
 /// Warning for strings containing mixed scripts
 #[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
 #[error("for policy `{policy_id}`, string `\"{string}\"` contains mixed scripts")]
@@ -43,6 +91,16 @@ pub struct MixedScriptString {
     pub string: String,
 }
 
+impl MixedScriptString {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "mixed-script-string",
+        category: WarningCategory::TextSafety,
+        default_severity: Severity::Warning,
+        description: "A string contains characters from a mix of scripts, which may be visually confused for each other.",
+    };
+}
+
 impl Diagnostic for MixedScriptString {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
     impl_diagnostic_warning!();
@@ -60,6 +118,16 @@ pub struct BidiCharsInString {
     pub string: String,
 }
 
+impl BidiCharsInString {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "bidi-chars-in-string",
+        category: WarningCategory::TextSafety,
+        default_severity: Severity::Warning,
+        description: "A string contains bidirectional text control characters, which can be used to obfuscate the meaning of a policy.",
+    };
+}
+
 impl Diagnostic for BidiCharsInString {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
     impl_diagnostic_warning!();
@@ -77,6 +145,16 @@ pub struct BidiCharsInIdentifier {
     pub id: String,
 }
 
+impl BidiCharsInIdentifier {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "bidi-chars-in-identifier",
+        category: WarningCategory::TextSafety,
+        default_severity: Severity::Warning,
+        description: "An identifier contains bidirectional text control characters, which can be used to obfuscate the meaning of a policy.",
+    };
+}
+
 impl Diagnostic for BidiCharsInIdentifier {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
     impl_diagnostic_warning!();
@@ -93,6 +171,16 @@ pub struct MixedScriptIdentifier {
     /// Identifier containing mixed scripts
     pub id: String,
 }
+impl MixedScriptIdentifier {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "mixed-script-identifier",
+        category: WarningCategory::TextSafety,
+        default_severity: Severity::Warning,
+        description: "An identifier contains characters from a mix of scripts, which may be visually confused for each other.",
+    };
+}
+
 impl Diagnostic for MixedScriptIdentifier {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
     impl_diagnostic_warning!();
@@ -116,6 +204,16 @@ pub struct ConfusableIdentifier {
     pub confusable_character: char,
 }
 
+impl ConfusableIdentifier {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "confusable-identifier",
+        category: WarningCategory::TextSafety,
+        default_severity: Severity::Warning,
+        description: "An identifier contains characters outside of the General Security Profile for Identifiers, which may be confused for other characters.",
+    };
+}
+
 impl Diagnostic for ConfusableIdentifier {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
     impl_diagnostic_warning!();
@@ -131,7 +229,301 @@ pub struct ImpossiblePolicy {
     pub policy_id: PolicyID,
 }
 
+impl ImpossiblePolicy {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "impossible-policy",
+        category: WarningCategory::PolicyLogic,
+        default_severity: Severity::Warning,
+        description: "A policy condition evaluates to false for all valid requests, so the policy never applies.",
+    };
+}
+
 impl Diagnostic for ImpossiblePolicy {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
     impl_diagnostic_warning!();
 }
+
+/// Warning for a `permit` policy with no scope constraints and no conditions,
+/// which allows every request
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, policy is overly permissive: it permits every request because it has no scope constraints and no conditions")]
+pub struct OverlyPermissivePolicy {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+}
+
+impl OverlyPermissivePolicy {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "overly-permissive-policy",
+        category: WarningCategory::PolicyLogic,
+        default_severity: Severity::Warning,
+        description: "A `permit` policy has no scope constraints and no conditions, so it allows every request. This warning is disabled by default.",
+    };
+}
+
+impl Diagnostic for OverlyPermissivePolicy {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(
+            "constrain the principal, action, or resource, or add a `when` or `unless` condition",
+        ))
+    }
+}
+
+/// Warning for an attribute access written with dot syntax, e.g.,
+/// `principal.isEmpty`, where the attribute is named like a method, so that it
+/// may be mistaken for a method call, e.g., `principal.isEmpty()`
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, attribute access `.{attribute}` may be confused with a call to the `{attribute}` method")]
+pub struct AmbiguousAttributeName {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// Name of the attribute, which is also the name of a method
+    pub attribute: String,
+}
+
+impl AmbiguousAttributeName {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "ambiguous-attribute-name",
+        category: WarningCategory::TextSafety,
+        default_severity: Severity::Warning,
+        description: "An attribute is accessed with dot syntax but is named like a built-in or extension method, so the access may be mistaken for a method call.",
+    };
+}
+
+impl Diagnostic for AmbiguousAttributeName {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(format!(
+            "to call the method, add an argument list, e.g., `.{}(...)`; to access the attribute, write `[\"{}\"]` instead",
+            self.attribute, self.attribute
+        )))
+    }
+}
+
+/// Warning for a `like` pattern which is empty or contains no wildcards, so
+/// that the `like` is equivalent to an `==` comparison with a string
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, the pattern `\"{pattern}\"` {kind}")]
+pub struct SuspiciousLikePattern {
+    /// Source location of the pattern
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// The pattern, as it would be written in a policy
+    pub pattern: String,
+    /// Why the pattern is suspicious
+    pub kind: SuspiciousLikePatternKind,
+}
+
+/// Details for specific kinds of suspicious `like` patterns
+#[derive(Debug, Clone, Copy, PartialEq, Error, Eq, Hash)]
+pub enum SuspiciousLikePatternKind {
+    /// The pattern is empty, so it only matches the empty string
+    #[error("is empty, so it only matches the empty string")]
+    Empty,
+    /// The pattern is not empty, but contains no wildcards, so it only matches
+    /// exactly the string it spells out
+    #[error("contains no wildcards, so it only matches that exact string")]
+    NoWildcards,
+}
+
+impl SuspiciousLikePattern {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "suspicious-like-pattern",
+        category: WarningCategory::PolicyLogic,
+        default_severity: Severity::Warning,
+        description: "A `like` pattern is empty or contains no wildcards, so the `like` could be written as an `==` comparison.",
+    };
+}
+
+impl Diagnostic for SuspiciousLikePattern {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(match self.kind {
+            SuspiciousLikePatternKind::Empty => {
+                "to test for the empty string, use `== \"\"` instead; to match any string, use the pattern `\"*\"`"
+            }
+            SuspiciousLikePatternKind::NoWildcards => {
+                "to test for equality, use `==` instead; to match any sequence of characters, add a `*` wildcard"
+            }
+        }))
+    }
+}
+
+/// Warning for an expression whose meaning depends on operator precedence in
+/// a way that is often misread, e.g., `a && b || c` or `!x == y`
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, {kind}: it is parsed as `{parenthesized}`")]
+pub struct AmbiguousPrecedence {
+    /// Source location of the ambiguous expression
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// The expression, with parentheses added to show how it is parsed
+    pub parenthesized: String,
+    /// Which operators are involved
+    pub kind: AmbiguousPrecedenceKind,
+}
+
+/// Details for specific kinds of ambiguous operator precedence
+#[derive(Debug, Clone, Copy, PartialEq, Error, Eq, Hash)]
+pub enum AmbiguousPrecedenceKind {
+    /// An operand of `||` is an `&&` expression without parentheses, e.g.,
+    /// `a && b || c`
+    #[error("`&&` and `||` are mixed without parentheses")]
+    MixedAndOr,
+    /// The left operand of a comparison is a `!` expression without
+    /// parentheses, e.g., `!x == y`, so the `!` applies to the operand rather
+    /// than to the comparison
+    #[error("`!` applies to the left operand of the comparison, not to the comparison")]
+    NegatedComparisonOperand,
+}
+
+impl AmbiguousPrecedence {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "ambiguous-precedence",
+        category: WarningCategory::PolicyLogic,
+        default_severity: Severity::Warning,
+        description: "An expression mixes `&&` and `||`, or applies `!` to the operand of a comparison, without parentheses, so it may not mean what it appears to. This warning is disabled by default.",
+    };
+}
+
+impl Diagnostic for AmbiguousPrecedence {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(
+            "add parentheses to make the intended meaning explicit; this does not change how the policy is evaluated",
+        ))
+    }
+}
+
+/// Warning for an `is` test in a policy condition whose result is known from
+/// the schema, because its operand always or never has the tested entity type
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, the type test `{test}` {kind}")]
+pub struct TrivialTypeTest {
+    /// Source location of the `is` test
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// The `is` test, as it would be written in a policy
+    pub test: String,
+    /// Whether the test is always true or always false
+    pub kind: TrivialTypeTestKind,
+}
+
+/// Details for specific kinds of trivial `is` tests
+#[derive(Debug, Clone, Copy, PartialEq, Error, Eq, Hash)]
+pub enum TrivialTypeTestKind {
+    /// The operand always has the tested entity type, so the test is always
+    /// true
+    #[error("is always true: its operand always has this entity type")]
+    AlwaysTrue,
+    /// The operand can never have the tested entity type, so the test is
+    /// always false
+    #[error("is always false: its operand can never have this entity type")]
+    AlwaysFalse,
+}
+
+impl TrivialTypeTest {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "trivial-type-test",
+        category: WarningCategory::PolicyLogic,
+        default_severity: Severity::Warning,
+        description: "An `is` test in a policy condition is always true or always false for the entity types in the schema.",
+    };
+}
+
+impl Diagnostic for TrivialTypeTest {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(match self.kind {
+            TrivialTypeTestKind::AlwaysTrue => {
+                "the test can be removed; if other entity types were intended, check the schema"
+            }
+            TrivialTypeTestKind::AlwaysFalse => {
+                "check the spelling of the entity type, and that the schema allows the operand to have it"
+            }
+        }))
+    }
+}
+
+/// Warning for validating a policy set that contains no policies or templates
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("policy set is empty: there are no policies or templates to validate")]
+pub struct EmptyPolicySet;
+
+impl EmptyPolicySet {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "empty-policy-set",
+        category: WarningCategory::PolicySet,
+        default_severity: Severity::Warning,
+        description: "The policy set contains no policies or templates, which usually means the policies were loaded from the wrong location.",
+    };
+}
+
+impl Diagnostic for EmptyPolicySet {
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(
+            "check that the policies were loaded from the intended location",
+        ))
+    }
+}
+
+/// Warning for an `action in` scope constraint referencing an action group
+/// which has no member actions and does not apply to any request itself
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, action group `{action_group}` is empty: it has no member actions and does not apply to any principal and resource types")]
+pub struct EmptyActionGroup {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// The empty action group
+    pub action_group: String,
+}
+
+impl EmptyActionGroup {
+    /// Metadata describing this kind of warning
+    pub const KIND: WarningKindInfo = WarningKindInfo {
+        code: "empty-action-group",
+        category: WarningCategory::PolicyLogic,
+        default_severity: Severity::Warning,
+        description: "An `action in` scope constraint references an action group with no member actions, so it does not match any request.",
+    };
+}
+
+impl Diagnostic for EmptyActionGroup {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(
+            "add actions to the group using `memberOf` in the schema",
+        ))
+    }
+}
@@ -0,0 +1,287 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Validation of entity data against the schema used by a [`Validator`].
+
+use miette::Diagnostic;
+use smol_str::SmolStr;
+use thiserror::Error;
+
+use crate::ast::{Entity, EntityUID};
+use crate::entities::conformance::typecheck_value_against_schematype;
+use crate::entities::{Entities, EntityTypeDescription as _};
+use crate::extensions::Extensions;
+use crate::parser::Loc;
+use crate::validator::validation_errors;
+use crate::validator::{EntityTypeDescription, Validator};
+
+/// Contains the result of entity validation: the list of problems found in
+/// the entity data. Validation succeeds if there are no errors.
+#[derive(Debug, Clone)]
+pub struct EntityValidationResult {
+    errors: Vec<EntityValidationError>,
+}
+
+impl EntityValidationResult {
+    /// True when validation passes, i.e., there are no errors
+    pub fn validation_passed(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Get an iterator over the errors found by entity validation
+    pub fn validation_errors(&self) -> impl Iterator<Item = &EntityValidationError> {
+        self.errors.iter()
+    }
+
+    /// Get an iterator over the errors found by entity validation, consuming
+    /// this result
+    pub fn into_validation_errors(self) -> impl Iterator<Item = EntityValidationError> {
+        self.errors.into_iter()
+    }
+}
+
+/// An error generated by entity validation when an entity does not conform to
+/// the schema. Unlike a [`crate::validator::ValidationError`], it is located by
+/// the entity where the problem was found rather than by a policy.
+#[derive(Debug, Clone, PartialEq, Diagnostic, Error, Eq, Hash)]
+pub enum EntityValidationError {
+    /// An entity is missing an attribute which the schema declares as
+    /// required
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingRequiredAttribute(#[from] validation_errors::MissingRequiredAttribute),
+    /// An entity attribute value does not match the type declared in the
+    /// schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    EntityAttributeTypeMismatch(#[from] validation_errors::EntityAttributeTypeMismatch),
+    /// An entity has a parent of a type not permitted by the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidEntityParent(#[from] validation_errors::InvalidEntityParent),
+}
+
+impl EntityValidationError {
+    /// The entity where the validator found the issue
+    pub fn entity(&self) -> &EntityUID {
+        match self {
+            Self::MissingRequiredAttribute(e) => &e.entity,
+            Self::EntityAttributeTypeMismatch(e) => &e.entity,
+            Self::InvalidEntityParent(e) => &e.entity,
+        }
+    }
+
+    pub(crate) fn missing_required_attribute(
+        source_loc: Option<Loc>,
+        entity: EntityUID,
+        attribute: SmolStr,
+    ) -> Self {
+        validation_errors::MissingRequiredAttribute {
+            source_loc,
+            entity,
+            attribute,
+        }
+        .into()
+    }
+
+    pub(crate) fn entity_attribute_type_mismatch(
+        source_loc: Option<Loc>,
+        entity: EntityUID,
+        attribute: SmolStr,
+        detail: String,
+    ) -> Self {
+        validation_errors::EntityAttributeTypeMismatch {
+            source_loc,
+            entity,
+            attribute,
+            detail,
+        }
+        .into()
+    }
+
+    pub(crate) fn invalid_entity_parent(
+        source_loc: Option<Loc>,
+        entity: EntityUID,
+        parent: EntityUID,
+    ) -> Self {
+        validation_errors::InvalidEntityParent {
+            source_loc,
+            entity,
+            parent,
+        }
+        .into()
+    }
+}
+
+impl Validator {
+    /// Validate the entities in `entities` against the schema, reporting every
+    /// problem found rather than stopping at the first one.
+    ///
+    /// Each entity is checked for missing required attributes, attribute
+    /// values which do not match their declared types, and parents whose type
+    /// the schema does not allow. Action entities and entities whose type is
+    /// not declared in the schema are not checked; constructing the
+    /// [`Entities`] with a schema enforces full conformance instead.
+    ///
+    /// The returned errors are ordered by entity uid.
+    pub fn validate_entities(&self, entities: &Entities) -> EntityValidationResult {
+        let mut sorted: Vec<&Entity> = entities.iter().collect();
+        sorted.sort_by(|a, b| a.uid().cmp(b.uid()));
+        let errors = sorted
+            .into_iter()
+            .flat_map(|entity| self.validate_entity(entity))
+            .collect();
+        EntityValidationResult { errors }
+    }
+
+    /// Validate a single entity against the schema
+    fn validate_entity(&self, entity: &Entity) -> Vec<EntityValidationError> {
+        let uid = entity.uid();
+        if uid.is_action() {
+            return Vec::new();
+        }
+        let Some(desc) = EntityTypeDescription::new(&self.schema, uid.entity_type()) else {
+            return Vec::new();
+        };
+        let loc = uid.loc().cloned();
+        let mut errors = Vec::new();
+
+        let mut missing = desc
+            .required_attrs()
+            .filter(|attr| entity.get(attr).is_none())
+            .collect::<Vec<_>>();
+        missing.sort();
+        errors.extend(missing.into_iter().map(|attr| {
+            EntityValidationError::missing_required_attribute(loc.clone(), uid.clone(), attr)
+        }));
+
+        for (attr, val) in entity.attrs() {
+            let Some(ty) = desc.attr_type(attr) else {
+                continue;
+            };
+            if let Err(e) =
+                typecheck_value_against_schematype(val, &ty, Extensions::all_available())
+            {
+                errors.push(EntityValidationError::entity_attribute_type_mismatch(
+                    loc.clone(),
+                    uid.clone(),
+                    attr.clone(),
+                    e.to_string(),
+                ));
+            }
+        }
+
+        let allowed_parents = desc.allowed_parent_types();
+        let mut invalid_parents = entity
+            .parents()
+            .filter(|parent| !allowed_parents.contains(parent.entity_type()))
+            .collect::<Vec<_>>();
+        invalid_parents.sort();
+        errors.extend(invalid_parents.into_iter().map(|parent| {
+            EntityValidationError::invalid_entity_parent(loc.clone(), uid.clone(), parent.clone())
+        }));
+
+        errors
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::indexing_slicing, reason = "unit test code")]
+mod test {
+    use super::*;
+    use crate::entities::{EntityJsonParser, NoEntitiesSchema, TCComputation};
+    use crate::validator::json_schema;
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    fn validator() -> Validator {
+        let schema = json_schema::Fragment::from_json_value(json!({
+            "": {
+                "entityTypes": {
+                    "User": {
+                        "memberOfTypes": ["Group"],
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "name": { "type": "String" },
+                                "age": { "type": "Long" },
+                                "nickname": { "type": "String", "required": false },
+                            }
+                        }
+                    },
+                    "Group": {},
+                    "Photo": {},
+                },
+                "actions": {
+                    "view": {
+                        "appliesTo": {
+                            "principalTypes": ["User"],
+                            "resourceTypes": ["Photo"],
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        Validator::new(schema.try_into().unwrap())
+    }
+
+    fn entities(json: serde_json::Value) -> Entities {
+        EntityJsonParser::new(
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+            TCComputation::AssumeAlreadyComputed,
+        )
+        .from_json_value(json)
+        .unwrap()
+    }
+
+    #[test]
+    fn conforming_entities() {
+        let entities = entities(json!([
+            { "uid": { "type": "User", "id": "alice" }, "attrs": { "name": "Alice", "age": 30 }, "parents": [{ "type": "Group", "id": "admins" }] },
+            { "uid": { "type": "Group", "id": "admins" }, "attrs": {}, "parents": [] },
+            { "uid": { "type": "Unknown", "id": "x" }, "attrs": {}, "parents": [{ "type": "Photo", "id": "p" }] },
+        ]));
+        let result = validator().validate_entities(&entities);
+        assert!(result.validation_passed());
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let entities = entities(json!([
+            { "uid": { "type": "User", "id": "bob" }, "attrs": { "age": "old" }, "parents": [{ "type": "Photo", "id": "p" }] },
+            { "uid": { "type": "User", "id": "alice" }, "attrs": { "age": 30 }, "parents": [] },
+        ]));
+        let result = validator().validate_entities(&entities);
+        let errors = result.validation_errors().collect::<Vec<_>>();
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert_matches!(errors[0], EntityValidationError::MissingRequiredAttribute(e) => {
+            assert_eq!(e.entity.to_string(), r#"User::"alice""#);
+            assert_eq!(e.attribute, "name");
+        });
+        assert_matches!(errors[1], EntityValidationError::MissingRequiredAttribute(e) => {
+            assert_eq!(e.entity.to_string(), r#"User::"bob""#);
+        });
+        assert_matches!(errors[2], EntityValidationError::EntityAttributeTypeMismatch(e) => {
+            assert_eq!(e.attribute, "age");
+        });
+        assert_matches!(errors[3], EntityValidationError::InvalidEntityParent(e) => {
+            assert_eq!(e.parent.to_string(), r#"Photo::"p""#);
+        });
+        assert_eq!(errors[3].entity().to_string(), r#"User::"bob""#);
+    }
+}
@@ -0,0 +1,540 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generation of example entities and requests that conform to a schema.
+//!
+//! The generated data is deterministic for a given schema and seed, and is
+//! always checked against the schema before being returned, so it can be used
+//! directly as test fixtures for policies validated against the same schema.
+
+use std::collections::{HashMap, HashSet};
+
+use miette::Diagnostic;
+use smol_str::SmolStr;
+use thiserror::Error;
+
+use crate::ast::{
+    Context, ContextCreationError, Eid, Entity, EntityAttrEvaluationError, EntityType, EntityUID,
+    Name, Request, RestrictedExpr,
+};
+use crate::entities::{err::EntitiesError, Entities, TCComputation};
+use crate::extensions::Extensions;
+use crate::validator::{
+    types::{Attributes, BoolType, EntityKind, Type},
+    CoreSchema, RequestValidationError, ValidatorEntityType, ValidatorEntityTypeKind,
+    ValidatorSchema,
+};
+
+/// Number of example entities generated for each standard (non-enum) entity
+/// type. Enumerated entity types get one entity per declared value.
+const ENTITIES_PER_TYPE: usize = 2;
+
+/// Maximum number of elements generated for a set-typed value
+const MAX_SET_ELEMENTS: u64 = 3;
+
+/// Maximum number of tags generated for an entity whose type allows tags
+const MAX_TAGS: u64 = 2;
+
+/// Words used to build example string values
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel",
+];
+
+/// Errors that can occur while generating example data from a schema
+//
+// CAUTION: this type is publicly exported in `cedar-policy`.
+#[derive(Debug, Diagnostic, Error)]
+pub enum ExampleDataError {
+    /// The requested action is not declared in the schema
+    #[error("action `{0}` is not declared in the schema")]
+    UndeclaredAction(EntityUID),
+    /// The requested action does not apply to any principal or resource type,
+    /// so no request can be constructed for it
+    #[error("action `{action}` does not apply to any {} type", if *.is_principal { "principal" } else { "resource" })]
+    NoApplicableEntityType {
+        /// The action
+        action: EntityUID,
+        /// `true` if there is no principal type, `false` if there is no resource type
+        is_principal: bool,
+    },
+    /// The schema contains a type for which no example value can be generated
+    #[error("cannot generate an example value of type {0}")]
+    UnsupportedType(Box<Type>),
+    /// An example entity could not be constructed
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    EntityAttrEvaluation(#[from] EntityAttrEvaluationError),
+    /// The generated entities did not conform to the schema or could not be
+    /// assembled into an entity store
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Entities(#[from] EntitiesError),
+    /// The generated context could not be constructed
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Context(#[from] ContextCreationError),
+    /// The generated request did not conform to the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Request(#[from] RequestValidationError),
+}
+
+/// Small deterministic pseudo-random number generator (`SplitMix64`). We
+/// only need reproducible variety, not statistical quality, and don't want to
+/// depend on the output of a third-party generator staying stable.
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`. `bound` must be nonzero.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// Pick an index into a collection of length `len`. `len` must be nonzero.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "result is less than `len`, which is a `usize`"
+    )]
+    fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
+
+/// Generates example values by walking the schema
+#[derive(Debug)]
+struct ExampleGenerator<'a> {
+    schema: &'a ValidatorSchema,
+    rng: SplitMix64,
+}
+
+impl<'a> ExampleGenerator<'a> {
+    fn new(schema: &'a ValidatorSchema, seed: u64) -> Self {
+        Self {
+            schema,
+            rng: SplitMix64(seed),
+        }
+    }
+
+    /// Entity types in the order we generate entities for them. Types with
+    /// more descendants come first, so that (for an acyclic type hierarchy)
+    /// every parent type is generated before its member types. Ties are
+    /// broken by name to keep the order independent of `HashMap` iteration.
+    fn entity_type_order(&self) -> Vec<&'a ValidatorEntityType> {
+        let mut types = self.schema.entity_types().collect::<Vec<_>>();
+        types.sort_by(|a, b| {
+            b.descendants
+                .len()
+                .cmp(&a.descendants.len())
+                .then_with(|| a.name().cmp(b.name()))
+        });
+        types
+    }
+
+    /// The UIDs of the example entities of type `ety`. This depends only on
+    /// the schema, so requests can refer to entities generated with any seed.
+    fn example_uids(&self, ety: &EntityType) -> Vec<EntityUID> {
+        if ety.is_action() {
+            let mut actions = self
+                .schema
+                .actions()
+                .filter(|a| a.entity_type() == ety)
+                .cloned()
+                .collect::<Vec<_>>();
+            actions.sort();
+            return actions;
+        }
+        match self.schema.get_entity_type(ety).map(|v| &v.kind) {
+            Some(ValidatorEntityTypeKind::Enum(choices)) => choices
+                .iter()
+                .map(|eid| EntityUID::from_components(ety.clone(), eid.clone(), None))
+                .collect(),
+            _ => (0..ENTITIES_PER_TYPE)
+                .map(|i| {
+                    EntityUID::from_components(ety.clone(), Eid::new(format!("example-{i}")), None)
+                })
+                .collect(),
+        }
+    }
+
+    /// Pick one of the example entities of one of the types in `types`
+    fn pick_uid<'t>(
+        &mut self,
+        types: impl IntoIterator<Item = &'t EntityType>,
+    ) -> Option<EntityUID> {
+        let mut types = types.into_iter().collect::<Vec<_>>();
+        types.sort();
+        if types.is_empty() {
+            return None;
+        }
+        let idx = self.rng.index(types.len());
+        let uids = self.example_uids(types.get(idx)?);
+        if uids.is_empty() {
+            return None;
+        }
+        let idx = self.rng.index(uids.len());
+        uids.into_iter().nth(idx)
+    }
+
+    fn entities(&mut self) -> Result<Vec<Entity>, ExampleDataError> {
+        let order = self.entity_type_order();
+        // Position of every example entity in generation order. An entity may
+        // only have parents generated strictly before it, which guarantees
+        // the generated hierarchy is acyclic even when the schema allows
+        // cycles (e.g., `Group in [Group]`).
+        let uids = order
+            .iter()
+            .flat_map(|ety| self.example_uids(ety.name()))
+            .collect::<Vec<_>>();
+        let position = uids
+            .iter()
+            .enumerate()
+            .map(|(pos, uid)| (uid.clone(), pos))
+            .collect::<HashMap<_, _>>();
+
+        let mut entities = Vec::with_capacity(uids.len());
+        for (pos, uid) in uids.iter().enumerate() {
+            let ety = uid.entity_type();
+            let Some(vety) = self.schema.get_entity_type(ety) else {
+                continue;
+            };
+            let mut ancestor_types = self
+                .schema
+                .ancestors(ety)
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            ancestor_types.sort();
+            let mut parents = HashSet::new();
+            for ancestor_ty in ancestor_types {
+                for candidate in self.example_uids(ancestor_ty) {
+                    let earlier = position.get(&candidate).is_some_and(|p| *p < pos);
+                    if earlier && self.rng.coin() {
+                        parents.insert(candidate);
+                    }
+                }
+            }
+            let attrs = self.record_attrs(vety.attributes())?;
+            let tags = match vety.tag_type() {
+                Some(tag_ty) => (0..self.rng.below(MAX_TAGS + 1))
+                    .map(|i| Ok((SmolStr::from(format!("tag{i}")), self.value(tag_ty)?)))
+                    .collect::<Result<Vec<_>, ExampleDataError>>()?,
+                None => Vec::new(),
+            };
+            entities.push(Entity::new(
+                uid.clone(),
+                attrs,
+                HashSet::new(),
+                parents,
+                tags,
+                Extensions::all_available(),
+            )?);
+        }
+        Ok(entities)
+    }
+
+    /// Generate values for every required attribute, and for some of the
+    /// optional ones.
+    fn record_attrs(
+        &mut self,
+        attrs: &Attributes,
+    ) -> Result<Vec<(SmolStr, RestrictedExpr)>, ExampleDataError> {
+        let mut pairs = Vec::new();
+        for (attr, attr_ty) in attrs.iter() {
+            if attr_ty.is_required || self.rng.coin() {
+                pairs.push((attr.clone(), self.value(&attr_ty.attr_type)?));
+            }
+        }
+        Ok(pairs)
+    }
+
+    fn value(&mut self, ty: &Type) -> Result<RestrictedExpr, ExampleDataError> {
+        match ty {
+            Type::Bool(BoolType::True) => Ok(RestrictedExpr::val(true)),
+            Type::Bool(BoolType::False) => Ok(RestrictedExpr::val(false)),
+            Type::Bool(BoolType::AnyBool) => Ok(RestrictedExpr::val(self.rng.coin())),
+            Type::Long => Ok(RestrictedExpr::val(
+                i64::try_from(self.rng.below(1000)).unwrap_or_default(),
+            )),
+            Type::String => {
+                let idx = self.rng.index(WORDS.len());
+                let word = WORDS.get(idx).unwrap_or(&"example");
+                Ok(RestrictedExpr::val(format!(
+                    "{word}-{}",
+                    self.rng.below(100)
+                )))
+            }
+            Type::Entity(EntityKind::Entity(lub)) => self
+                .pick_uid(lub.iter())
+                .map(RestrictedExpr::val)
+                .ok_or_else(|| ExampleDataError::UnsupportedType(Box::new(ty.clone()))),
+            Type::Set {
+                element_type: Some(element_type),
+            } => {
+                let len = self.rng.below(MAX_SET_ELEMENTS + 1);
+                let elements = (0..len)
+                    .map(|_| self.value(element_type))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(RestrictedExpr::set(elements))
+            }
+            Type::Set { element_type: None } => {
+                Ok(RestrictedExpr::set(Vec::<RestrictedExpr>::new()))
+            }
+            Type::Record { attrs, .. } => {
+                let pairs = self.record_attrs(attrs)?;
+                #[expect(
+                    clippy::expect_used,
+                    reason = "attribute names from `Attributes` are unique"
+                )]
+                let record =
+                    RestrictedExpr::record(pairs).expect("record attributes should be unique");
+                Ok(record)
+            }
+            Type::ExtensionType { name } => self.extension_value(name, ty),
+            Type::Never | Type::Entity(EntityKind::AnyEntity) => {
+                Err(ExampleDataError::UnsupportedType(Box::new(ty.clone())))
+            }
+        }
+    }
+
+    /// Construct an extension value using the constructor for the built-in
+    /// extension type `name`.
+    fn extension_value(
+        &mut self,
+        name: &Name,
+        ty: &Type,
+    ) -> Result<RestrictedExpr, ExampleDataError> {
+        let (constructor, arg) = match name.to_string().as_str() {
+            "ipaddr" => (
+                "ip",
+                format!("10.0.{}.{}", self.rng.below(256), self.rng.below(256)),
+            ),
+            "decimal" => (
+                "decimal",
+                format!("{}.{:04}", self.rng.below(1000), self.rng.below(10000)),
+            ),
+            "datetime" => (
+                "datetime",
+                format!(
+                    "2024-{:02}-{:02}",
+                    self.rng.below(12) + 1,
+                    self.rng.below(28) + 1
+                ),
+            ),
+            "duration" => (
+                "duration",
+                format!("{}h{}m", self.rng.below(24), self.rng.below(60)),
+            ),
+            _ => return Err(ExampleDataError::UnsupportedType(Box::new(ty.clone()))),
+        };
+        #[expect(
+            clippy::expect_used,
+            reason = "extension constructor names are valid unqualified names"
+        )]
+        let constructor = Name::parse_unqualified_name(constructor)
+            .expect("extension constructor name should be a valid name");
+        Ok(RestrictedExpr::call_extension_fn(
+            constructor,
+            [RestrictedExpr::val(arg)],
+        ))
+    }
+}
+
+impl ValidatorSchema {
+    /// Generate example entities conforming to this schema.
+    ///
+    /// Every entity type gets example entities (one per declared value for
+    /// enumerated entity types), with all required attributes populated and
+    /// parents chosen from the types allowed by `memberOfTypes`. The result is
+    /// deterministic for a given `seed`, and is validated against this schema
+    /// before it is returned. The action entities from the schema are
+    /// included.
+    pub fn example_entities(&self, seed: u64) -> Result<Entities, ExampleDataError> {
+        let entities = ExampleGenerator::new(self, seed).entities()?;
+        Ok(Entities::from_entities(
+            entities,
+            Some(&CoreSchema::new(self)),
+            TCComputation::ComputeNow,
+            Extensions::all_available(),
+        )?)
+    }
+
+    /// Generate an example request for `action` conforming to this schema.
+    ///
+    /// The principal and resource are chosen from the types the action
+    /// applies to, and always refer to entities that are present in
+    /// [`ValidatorSchema::example_entities`] (for any seed). The context is
+    /// populated according to the action's declared context type. The result
+    /// is deterministic for a given `seed`.
+    pub fn example_request(
+        &self,
+        action: &EntityUID,
+        seed: u64,
+    ) -> Result<Request, ExampleDataError> {
+        let action_id = self
+            .get_action_id(action)
+            .ok_or_else(|| ExampleDataError::UndeclaredAction(action.clone()))?;
+        let mut generator = ExampleGenerator::new(self, seed);
+        let principal = generator
+            .pick_uid(action_id.applies_to_principals())
+            .ok_or_else(|| ExampleDataError::NoApplicableEntityType {
+                action: action.clone(),
+                is_principal: true,
+            })?;
+        // The resource of a request for an action with no resource is the
+        // action itself
+        let resource = if action_id.has_no_resource() {
+            action.clone()
+        } else {
+            generator
+                .pick_uid(action_id.applies_to_resources())
+                .ok_or_else(|| ExampleDataError::NoApplicableEntityType {
+                    action: action.clone(),
+                    is_principal: false,
+                })?
+        };
+        let context = match action_id.context_type() {
+            Type::Record { attrs, .. } => {
+                Context::from_pairs(generator.record_attrs(attrs)?, Extensions::all_available())?
+            }
+            _ => Context::empty(),
+        };
+        Ok(Request::new(
+            (principal, None),
+            (action.clone(), None),
+            (resource, None),
+            context,
+            Some(self),
+            Extensions::all_available(),
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cool_asserts::assert_matches;
+
+    use super::*;
+    use crate::entities::Dereference;
+
+    fn schema() -> ValidatorSchema {
+        let src = r#"
+            namespace PhotoApp {
+                type Meta = { "tags": Set<String>, "score"?: Long };
+                entity Org enum ["acme", "initech"];
+                entity Group in [Group, Org];
+                entity User in [Group] {
+                    name: String,
+                    age: Long,
+                    admin: Bool,
+                    ip: ipaddr,
+                    manager?: User,
+                } tags String;
+                entity Album in [Album];
+                entity Photo in [Album] {
+                    owner: User,
+                    meta: Meta,
+                    price: decimal,
+                    taken: datetime,
+                };
+                action view appliesTo {
+                    principal: [User],
+                    resource: [Photo, Album],
+                    context: { "mfa": Bool, "source": ipaddr, "reason"?: String }
+                };
+                action noop;
+            }
+        "#;
+        ValidatorSchema::from_cedarschema_str(src, Extensions::all_available())
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn entities_conform_to_schema() {
+        let schema = schema();
+        for seed in 0..16 {
+            let entities = schema.example_entities(seed).unwrap();
+            for ety in ["PhotoApp::User", "PhotoApp::Photo", "PhotoApp::Group"] {
+                let ety: EntityType = ety.parse().unwrap();
+                let uid = EntityUID::from_components(ety, Eid::new("example-0"), None);
+                assert_matches!(entities.entity(&uid), Dereference::Data(_));
+            }
+            let acme = EntityUID::from_components(
+                "PhotoApp::Org".parse().unwrap(),
+                Eid::new("acme"),
+                None,
+            );
+            assert_matches!(entities.entity(&acme), Dereference::Data(_));
+        }
+    }
+
+    #[test]
+    fn entities_are_deterministic() {
+        let schema = schema();
+        let a = schema.example_entities(42).unwrap();
+        let b = schema.example_entities(42).unwrap();
+        assert!(a.deep_eq(&b));
+    }
+
+    #[test]
+    fn request_conforms_to_schema() {
+        let schema = schema();
+        let view: EntityUID = r#"PhotoApp::Action::"view""#.parse().unwrap();
+        for seed in 0..16 {
+            let request = schema.example_request(&view, seed).unwrap();
+            let entities = schema.example_entities(seed).unwrap();
+            let principal = request.principal().uid().unwrap();
+            let resource = request.resource().uid().unwrap();
+            assert_matches!(entities.entity(principal), Dereference::Data(_));
+            assert_matches!(entities.entity(resource), Dereference::Data(_));
+            assert_eq!(
+                request.to_string(),
+                schema.example_request(&view, seed).unwrap().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn request_errors() {
+        let schema = schema();
+        let missing: EntityUID = r#"PhotoApp::Action::"edit""#.parse().unwrap();
+        assert_matches!(
+            schema.example_request(&missing, 0),
+            Err(ExampleDataError::UndeclaredAction(_))
+        );
+        let noop: EntityUID = r#"PhotoApp::Action::"noop""#.parse().unwrap();
+        assert_matches!(
+            schema.example_request(&noop, 0),
+            Err(ExampleDataError::NoApplicableEntityType {
+                is_principal: true,
+                ..
+            })
+        );
+    }
+}
@@ -99,6 +99,36 @@ pub(super) fn expr_text(e: &'_ Expr) -> impl Iterator<Item = TextKind<'_>> {
     e.subexpressions().flat_map(text_in_expr)
 }
 
+/// Returns an iterator over all text (strings and identifiers) in a policy.
+/// This yields the same text as calling [`expr_text`] on the policy's
+/// condition, though not necessarily in the same order, but reads the scope
+/// constraints in place instead of first building the full condition
+/// expression.
+pub(super) fn policy_text(template: &Template) -> impl Iterator<Item = TextKind<'_>> {
+    template
+        .principal_constraint()
+        .as_inner()
+        .get_euid()
+        .into_iter()
+        .map(|euid| euid.as_ref())
+        .chain(template.action_constraint().iter_euids())
+        .chain(
+            template
+                .resource_constraint()
+                .as_inner()
+                .get_euid()
+                .into_iter()
+                .map(|euid| euid.as_ref()),
+        )
+        .flat_map(|euid| text_in_euid(None, euid))
+        .chain(
+            template
+                .non_scope_constraints()
+                .into_iter()
+                .flat_map(expr_text),
+        )
+}
+
 // Returns a vector containing the text in the top level expression
 fn text_in_expr(e: &Expr) -> impl IntoIterator<Item = TextKind<'_>> {
     match e.expr_kind() {
@@ -162,9 +192,11 @@ fn text_in_name<'a>(loc: Option<&'a Loc>, name: &'a Name) -> impl Iterator<Item
 
 #[cfg(test)]
 mod tests {
-    use super::{expr_entity_uids, expr_text};
+    use super::{expr_entity_uids, expr_text, policy_text};
     use crate::ast::{EntityUID, Expr, Literal, Pattern, PatternElem, Var};
+    use crate::parser::parse_policy_or_template;
     use crate::validator::expr_iterator::TextKind;
+    use itertools::Itertools;
     use std::{collections::HashSet, str::FromStr};
 
     #[test]
@@ -430,4 +462,19 @@ mod tests {
             strs
         );
     }
+
+    #[test]
+    fn test_policy_text_matches_condition() {
+        for src in [
+            r#"permit(principal, action, resource);"#,
+            r#"permit(principal == a::"b", action in [Action::"c", Action::"d"], resource is T in e::"f") when { principal.g like "h*" };"#,
+            r#"permit(principal in ?principal, action == Action::"c", resource == ?resource) unless { context has i };"#,
+        ] {
+            let t = parse_policy_or_template(None, src).unwrap();
+            let condition = t.condition();
+            let expected = expr_text(&condition).counts();
+            let actual = policy_text(&t).counts();
+            assert_eq!(expected, actual, "for policy {src}");
+        }
+    }
 }
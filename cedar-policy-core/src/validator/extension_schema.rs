@@ -106,7 +106,7 @@ pub struct ExtensionFunctionType {
 
 impl ExtensionFunctionType {
     /// Create a new `ExtensionFunctionType`
-    pub fn new(
+    pub(crate) fn new(
         name: Name,
         argument_types: Vec<Type>,
         return_type: Type,
@@ -122,6 +122,13 @@ impl ExtensionFunctionType {
         }
     }
 
+    /// Create the type of a custom extension function taking exactly the
+    /// arguments `argument_types`, for use with
+    /// [`crate::validator::Validator::with_extension`]
+    pub fn custom(name: Name, argument_types: Vec<Type>, return_type: Type) -> Self {
+        Self::new(name, argument_types, return_type, None, false)
+    }
+
     /// Get the name of the extension function
     pub fn name(&self) -> &Name {
         &self.name
@@ -138,7 +145,7 @@ impl ExtensionFunctionType {
     }
 
     /// Call the `check_arguments` function with the given args
-    pub fn check_arguments(&self, args: &[Expr]) -> Result<(), ArgumentValidationError> {
+    pub(crate) fn check_arguments(&self, args: &[Expr]) -> Result<(), ArgumentValidationError> {
         if let Some(f) = &self.check_arguments {
             return (f)(args);
         }
@@ -151,6 +158,8 @@ impl ExtensionFunctionType {
         self.check_arguments.is_some()
     }
 
+    /// Return true when this extension function may be called with additional
+    /// arguments of its last argument type
     pub fn is_variadic(&self) -> bool {
         self.is_variadic
     }
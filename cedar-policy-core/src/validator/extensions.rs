@@ -117,6 +117,38 @@ impl<'a> ExtensionSchemas<'a> {
         })
     }
 
+    /// Get a new `ExtensionSchemas` with the functions in `self` along with
+    /// `function_types`, which take precedence over functions in `self` with
+    /// the same name.
+    pub(crate) fn with_function_types<'b>(
+        &self,
+        function_types: impl IntoIterator<Item = &'b ExtensionFunctionType>,
+    ) -> ExtensionSchemas<'b>
+    where
+        'a: 'b,
+    {
+        let mut all_function_types: HashMap<&'b Name, &'b ExtensionFunctionType> = self
+            .function_types
+            .iter()
+            .map(|(name, f)| (*name, *f))
+            .collect();
+        all_function_types.extend(function_types.into_iter().map(|f| (f.name(), f)));
+        ExtensionSchemas {
+            function_types: all_function_types,
+            types_with_operator_overloading: self
+                .types_with_operator_overloading
+                .iter()
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Get the [`ExtensionFunctionType`]s for all the functions, in no
+    /// particular order
+    pub(crate) fn function_types(&self) -> impl Iterator<Item = &ExtensionFunctionType> {
+        self.function_types.values().copied()
+    }
+
     /// Get the [`ExtensionFunctionType`] for a function with this [`Name`].
     /// Return `None` if no such function exists.
     pub fn func_type(&self, name: &Name) -> Option<&ExtensionFunctionType> {
@@ -147,9 +179,9 @@ fn eval_extension_constructor(
     evaluator.interpret(constructor_call_expr.as_borrowed())
 }
 
-/// Errors occurring while initializing extensions. These are internal errors, so
-/// this enum should not become part of the public API unless we publicly expose
-/// user-defined extension functions.
+/// Errors occurring while initializing extension schemas, e.g., when adding a
+/// custom extension function signature with
+/// [`super::Validator::with_extension`].
 #[derive(Diagnostic, Debug, Error)]
 pub enum ExtensionInitializationError {
     /// An extension function was defined by multiple extensions.
@@ -158,6 +190,13 @@ pub enum ExtensionInitializationError {
     FuncMultiplyDefined(#[from] extension_initialization_errors::FuncMultiplyDefinedError),
 }
 
+impl ExtensionInitializationError {
+    /// Construct a [`ExtensionInitializationError::FuncMultiplyDefined`]
+    pub(crate) fn func_multiply_defined(name: Name) -> Self {
+        FuncMultiplyDefinedError { name }.into()
+    }
+}
+
 /// Error subtypes for [`ExtensionInitializationError`]
 mod extension_initialization_errors {
     use crate::ast::Name;
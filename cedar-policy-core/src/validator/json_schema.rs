@@ -161,14 +161,40 @@ impl<N: Serialize> Serialize for Fragment<N> {
 impl Fragment<RawName> {
     /// Create a [`Fragment`] from a string containing JSON (which should
     /// be an object of the appropriate shape).
+    ///
+    /// Unknown fields anywhere in the schema are rejected, and the error
+    /// reports where the unknown field was found.
     pub fn from_json_str(json: &str) -> Result<Self> {
-        serde_json::from_str(json).map_err(|e| JsonDeserializationError::new(e, Some(json)).into())
+        serde_json::from_str(json).map_err(|e| {
+            let err = JsonDeserializationError::new(e, Some(json));
+            match serde_json::from_str(json) {
+                Ok(value) => err.with_unknown_field_location(&value).into(),
+                Err(_) => err.into(),
+            }
+        })
     }
 
     /// Create a [`Fragment`] from a JSON value (which should be an object
     /// of the appropriate shape).
+    ///
+    /// Unknown fields anywhere in the schema are rejected, and the error
+    /// reports where the unknown field was found.
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "taking the value by reference would be a breaking change to this public API"
+    )]
     pub fn from_json_value(json: serde_json::Value) -> Result<Self> {
-        serde_json::from_value(json).map_err(|e| JsonDeserializationError::new(e, None).into())
+        Self::from_json_value_ref(&json)
+    }
+
+    /// Like [`Self::from_json_value()`], but borrowing the JSON value, so
+    /// callers can use it to locate later errors
+    pub(crate) fn from_json_value_ref(json: &serde_json::Value) -> Result<Self> {
+        Self::deserialize(json).map_err(|e| {
+            JsonDeserializationError::new(e, None)
+                .with_unknown_field_location(json)
+                .into()
+        })
     }
 
     /// Create a [`Fragment`] directly from a file containing a JSON object.
@@ -206,6 +232,9 @@ impl Fragment<RawName> {
         let validator_fragment = ValidatorSchemaFragment::from_schema_fragment(self.clone())?;
 
         let mut all_defs = AllDefs::single_fragment(&validator_fragment);
+        // Without the RFC 70 checks, unqualified references could silently
+        // resolve to one of several definitions
+        all_defs.rfc_70_shadowing_checks()?;
 
         // Add built-in primitive types in the __cedar namespace
         let cedar_namespace = InternalName::__cedar();
@@ -440,7 +469,6 @@ pub struct NamespaceDefinition<N> {
     pub loc: Option<Loc>,
 }
 
-#[cfg(test)]
 impl<N> NamespaceDefinition<N> {
     /// Create a new [`NamespaceDefinition`] with specified entity types and
     /// actions, and no common types or annotations
@@ -968,6 +996,25 @@ pub struct ActionType<N> {
     pub(crate) defn_loc: Option<Loc>,
 }
 
+impl<N> ActionType<N> {
+    /// Create a new [`ActionType`] with no source location
+    pub fn new(
+        applies_to: Option<ApplySpec<N>>,
+        member_of: Option<Vec<ActionEntityUID<N>>>,
+        annotations: Annotations,
+    ) -> Self {
+        Self {
+            attributes: None,
+            applies_to,
+            member_of,
+            annotations,
+            loc: None,
+            #[cfg(feature = "extended-schema")]
+            defn_loc: None,
+        }
+    }
+}
+
 impl ActionType<RawName> {
     /// (Conditionally) prefix unqualified entity and common type references with the namespace they are in
     pub fn conditionally_qualify_type_references(
@@ -1050,6 +1097,13 @@ pub struct ApplySpec<N> {
     #[serde(default)]
     #[serde(skip_serializing_if = "AttributesOrContext::is_empty_record")]
     pub context: AttributesOrContext<N>,
+    /// Whether the action involves no resource at all, e.g., an action to
+    /// list the actions a principal may take. If this is `true`,
+    /// `resource_types` must be empty, and the resource of every request for
+    /// the action is the action itself.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub no_resource: bool,
 }
 
 impl ApplySpec<RawName> {
@@ -1070,6 +1124,7 @@ impl ApplySpec<RawName> {
                 .map(|rname| rname.conditionally_qualify_with(ns, ReferenceType::Entity)) // Only entity, not common, here for now; see #1064
                 .collect(),
             context: self.context.conditionally_qualify_type_references(ns),
+            no_resource: self.no_resource,
         }
     }
 }
@@ -1097,6 +1152,7 @@ impl ApplySpec<ConditionalName> {
                 .map(|cname| cname.resolve(all_defs))
                 .collect::<std::result::Result<_, TypeNotDefinedError>>()?,
             context: self.context.fully_qualify_type_references(all_defs)?,
+            no_resource: self.no_resource,
         })
     }
 }
@@ -1248,7 +1304,7 @@ impl ActionEntityUID<ConditionalName> {
                 }
             }
         }
-        Err(ActionNotDefinedError(nonempty!(self)))
+        Err(ActionNotDefinedError(nonempty!(self), None))
     }
 
     /// Get the possible fully-qualified [`ActionEntityUID<InternalName>`]s
@@ -1582,6 +1638,7 @@ impl TypeVariant<InternalName> {
                             ),
                             tail: vec![],
                         },
+                        json_location: None,
                     })
                 }
             }
@@ -1690,6 +1747,7 @@ impl ApplySpec<InternalName> {
             context: self
                 .context
                 .resolve_attributes_or_context_entity_or_common(all_defs)?,
+            no_resource: self.no_resource,
         })
     }
 }
@@ -2451,6 +2509,19 @@ pub struct TypeOfAttribute<N> {
     pub loc: Option<Loc>,
 }
 
+impl<N> TypeOfAttribute<N> {
+    /// Create a new [`TypeOfAttribute`] with no source location
+    pub fn new(ty: Type<N>, required: bool, annotations: Annotations) -> Self {
+        Self {
+            ty,
+            annotations,
+            required,
+            #[cfg(feature = "extended-schema")]
+            loc: None,
+        }
+    }
+}
+
 impl TypeOfAttribute<RawName> {
     fn into_n<N: From<RawName>>(self) -> TypeOfAttribute<N> {
         TypeOfAttribute {
@@ -2607,6 +2678,7 @@ mod test {
         let spec = ApplySpec {
             resource_types: vec!["Album".parse().unwrap()],
             principal_types: vec!["User".parse().unwrap()],
+            no_resource: false,
             context: AttributesOrContext::default(),
         };
         assert_eq!(at.applies_to, Some(spec));
@@ -2621,6 +2693,35 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_action_type_parser_no_resource() {
+        let src = serde_json::json!({
+            "appliesTo": {
+                "principalTypes": ["User"],
+                "resourceTypes": [],
+                "noResource": true
+            }
+        });
+        let at: ActionType<RawName> = serde_json::from_value(src.clone()).expect("Parse Error");
+        assert_matches!(&at.applies_to, Some(spec) => {
+            assert!(spec.no_resource);
+            assert!(spec.resource_types.is_empty());
+        });
+        assert_eq!(serde_json::to_value(&at).unwrap(), src);
+
+        // `noResource` is omitted when it is `false`
+        let at: ActionType<RawName> = serde_json::from_value(serde_json::json!({
+            "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Album"] }
+        }))
+        .expect("Parse Error");
+        assert_eq!(
+            serde_json::to_value(&at).unwrap(),
+            serde_json::json!({
+                "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Album"] }
+            })
+        );
+    }
+
     #[test]
     fn test_action_type_parser2() {
         let src = r#"
@@ -2876,7 +2977,9 @@ mod test {
             expect_err(
                 "",
                 &miette::Report::new(e),
-                &ExpectedErrorMessageBuilder::error(r#"unknown field `foo`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`"#).build()
+                &ExpectedErrorMessageBuilder::error(r#"unknown field `foo`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`"#)
+                    .help("unknown field is at `//entityTypes/User/shape/attributes/a/attributes/b/foo`")
+                    .build()
             );
         });
     }
@@ -2911,7 +3014,9 @@ mod test {
             expect_err(
                 "",
                 &miette::Report::new(e),
-                &ExpectedErrorMessageBuilder::error(r#"unknown field `element`, expected `attributes` or `additionalAttributes`"#).build()
+                &ExpectedErrorMessageBuilder::error(r#"unknown field `element`, expected `attributes` or `additionalAttributes`"#)
+                    .help("unknown field is at `//entityTypes/User/shape/attributes/foo/element`")
+                    .build()
             );
         });
     }
@@ -3501,6 +3606,76 @@ mod entity_tags {
                 &json,
                 &miette::Report::new(e),
                 &ExpectedErrorMessageBuilder::error("unknown field `tags`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
+                    .help("unknown field is at `//entityTypes/User/shape/tags`")
+                    .build(),
+            );
+        });
+    }
+
+    #[test]
+    fn unknown_field_location() {
+        let json = json!({"N": {
+            "entityTypes": {
+                "User": {
+                    "memberOfTypess": ["Group"],
+                },
+                "Group": {},
+            },
+            "actions": {}
+        }});
+        assert_matches!(Fragment::from_json_value(json.clone()), Err(e) => {
+            expect_err(
+                &json,
+                &miette::Report::new(e),
+                &ExpectedErrorMessageBuilder::error("unknown field `memberOfTypess`, expected one of `memberOfTypes`, `shape`, `tags`, `enum`, `annotations`")
+                    .help("unknown field is at `/N/entityTypes/User/memberOfTypess`; did you mean `memberOfTypes`?")
+                    .build(),
+            );
+        });
+
+        let src = r#"{"N": {
+            "entityTypes": { "User": {} },
+            "actions": {
+                "view": {
+                    "appliesTo": {
+                        "principalType": ["User"],
+                        "resourceTypes": ["User"]
+                    }
+                }
+            }
+        }}"#;
+        assert_matches!(Fragment::from_json_str(src), Err(e) => {
+            expect_err(
+                src,
+                &miette::Report::new(e),
+                &ExpectedErrorMessageBuilder::error_starts_with("unknown field `principalType`, expected one of `resourceTypes`, `principalTypes`, `context`")
+                    .help("unknown field is at `/N/actions/view/appliesTo/principalType`; did you mean `principalTypes`?")
+                    .build(),
+            );
+        });
+
+        // Attributes named like fields are not mistaken for the unknown
+        // field, and names are escaped in the pointer
+        let json = json!({"N": {
+            "entityTypes": {
+                "User": {
+                    "shape": {
+                        "type": "Record",
+                        "attributes": {
+                            "element": { "type": "Long" },
+                            "a/b": { "type": "Long", "element": { "type": "Long" } },
+                        },
+                    },
+                },
+            },
+            "actions": {}
+        }});
+        assert_matches!(Fragment::from_json_value(json.clone()), Err(e) => {
+            expect_err(
+                &json,
+                &miette::Report::new(e),
+                &ExpectedErrorMessageBuilder::error("unknown field `element`, there are no fields")
+                    .help("unknown field is at `/N/entityTypes/User/shape/attributes/a~1b/element`")
                     .build(),
             );
         });
@@ -3564,6 +3739,7 @@ mod test_json_roundtrip {
                         applies_to: Some(ApplySpec {
                             resource_types: vec!["a".parse().unwrap()],
                             principal_types: vec!["a".parse().unwrap()],
+                            no_resource: false,
                             context: AttributesOrContext(Type::Type {
                                 ty: TypeVariant::Record(RecordType {
                                     attributes: BTreeMap::new(),
@@ -3622,6 +3798,7 @@ mod test_json_roundtrip {
                             applies_to: Some(ApplySpec {
                                 resource_types: vec!["foo::a".parse().unwrap()],
                                 principal_types: vec!["foo::a".parse().unwrap()],
+                                no_resource: false,
                                 context: AttributesOrContext(Type::Type {
                                     ty: TypeVariant::Record(RecordType {
                                         attributes: BTreeMap::new(),
@@ -4000,7 +4177,8 @@ mod annotations {
         "`commonTypes`, `entityTypes`, `actions`, `annotations`";
     const ATTRIBUTE_TYPE_EXPECTED_ATTRIBUTES: &str =
         "`type`, `element`, `attributes`, `additionalAttributes`, `name`";
-    const APPLIES_TO_EXPECTED_ATTRIBUTES: &str = "`resourceTypes`, `principalTypes`, `context`";
+    const APPLIES_TO_EXPECTED_ATTRIBUTES: &str =
+        "`resourceTypes`, `principalTypes`, `context`, `noResource`";
 
     #[test]
     fn unknown_fields() {
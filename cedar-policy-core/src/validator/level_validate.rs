@@ -73,7 +73,8 @@ impl Validator {
         // some policies have an error. This allows us to report more errors.
         let (errors, warnings) = self.validate_policy(p, mode);
 
-        let typechecker = Typechecker::new(&self.schema, mode);
+        let extensions = self.extension_schemas();
+        let typechecker = Typechecker::new(&self.schema, mode).with_extensions(&extensions);
         let type_annotated_asts = typechecker.typecheck_by_request_env(p);
         let mut level_checker = LevelChecker {
             policy_id: p.id(),
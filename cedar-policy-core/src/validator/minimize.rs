@@ -0,0 +1,372 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Reduction of a policy which fails validation to a smaller policy which
+//! fails with the same kind of error, e.g., for bug reports.
+
+use std::mem::discriminant;
+use std::sync::Arc;
+
+use miette::Diagnostic;
+
+use crate::ast::{
+    ActionConstraint, Annotations, Effect, Expr, ExprKind, Policy, PolicyID, PrincipalConstraint,
+    ResourceConstraint, StaticPolicy, Template,
+};
+
+use super::{ValidationError, ValidationMode, Validator};
+
+impl Validator {
+    /// Reduce `policy`, which fails validation in `mode`, to a smaller static
+    /// policy which fails validation with the same kind of error. The kind of
+    /// error is that of the error which starts earliest in the policy's
+    /// source text, preferring errors with a source location.
+    ///
+    /// The policy is reduced greedily, by removing its annotations, scope
+    /// constraints and conditions, replacing subexpressions with one of their
+    /// operands, and removing elements of set and record literals, for as
+    /// long as any of these changes keeps the error. The result has the id of
+    /// `policy`, and no source locations. A template-linked policy is reduced
+    /// as the static policy obtained by filling in its slots.
+    ///
+    /// Return `None` if `policy` validates without errors.
+    pub fn minimize_failure(&self, policy: &Policy, mode: ValidationMode) -> Option<StaticPolicy> {
+        let mut current = Parts {
+            id: policy.id().clone(),
+            annotations: policy.annotations_arc().as_ref().clone(),
+            effect: policy.effect(),
+            principal: policy.principal_constraint(),
+            action: policy.action_constraint().clone(),
+            resource: policy.resource_constraint(),
+            condition: policy.non_scope_constraints().cloned(),
+        };
+        let template = current.template();
+        let (errors, _) = self.validate_policy(&template, mode);
+        let target = errors
+            .min_by_key(|e| (source_offset(e).unwrap_or(usize::MAX), e.to_string()))
+            .map(|e| discriminant(&e))?;
+        let fails = |parts: &Parts| {
+            let template = parts.template();
+            let (mut errors, _) = self.validate_policy(&template, mode);
+            errors.any(|e| discriminant(&e) == target)
+        };
+        loop {
+            let smaller = current.reductions().find(|parts| fails(parts));
+            match smaller {
+                Some(smaller) => current = smaller,
+                None => break,
+            }
+        }
+        current.condition = current.condition.as_ref().map(without_source_locs);
+        StaticPolicy::try_from(current.template()).ok()
+    }
+}
+
+/// `expr` with the source locations of it and all of its subexpressions
+/// removed
+fn without_source_locs(expr: &Expr) -> Expr {
+    map_children(expr, without_source_locs).with_maybe_source_loc(None)
+}
+
+/// The offset of the earliest label of `error`, if it has any
+fn source_offset(error: &ValidationError) -> Option<usize> {
+    error
+        .labels()
+        .and_then(|labels| labels.map(|label| label.offset()).min())
+}
+
+/// The components of a policy being reduced
+#[derive(Debug, Clone)]
+struct Parts {
+    id: PolicyID,
+    annotations: Annotations,
+    effect: Effect,
+    principal: PrincipalConstraint,
+    action: ActionConstraint,
+    resource: ResourceConstraint,
+    condition: Option<Expr>,
+}
+
+impl Parts {
+    fn template(&self) -> Template {
+        Template::new(
+            self.id.clone(),
+            None,
+            self.annotations.clone(),
+            self.effect,
+            self.principal.clone(),
+            self.action.clone(),
+            self.resource.clone(),
+            self.condition.clone(),
+        )
+    }
+
+    /// The policies obtained from this one by each single reduction step,
+    /// roughly from the largest reduction to the smallest. Every step makes
+    /// the policy strictly smaller, so repeated reduction terminates.
+    fn reductions(&self) -> impl Iterator<Item = Parts> + '_ {
+        let annotations = (!self.annotations.is_empty()).then(|| Parts {
+            annotations: Annotations::new(),
+            ..self.clone()
+        });
+        let principal = (self.principal != PrincipalConstraint::any()).then(|| Parts {
+            principal: PrincipalConstraint::any(),
+            ..self.clone()
+        });
+        let action = (self.action != ActionConstraint::any()).then(|| Parts {
+            action: ActionConstraint::any(),
+            ..self.clone()
+        });
+        let resource = (self.resource != ResourceConstraint::any()).then(|| Parts {
+            resource: ResourceConstraint::any(),
+            ..self.clone()
+        });
+        let condition = self.condition.is_some().then(|| Parts {
+            condition: None,
+            ..self.clone()
+        });
+        let expressions = self
+            .condition
+            .iter()
+            .flat_map(expr_reductions)
+            .map(|e| Parts {
+                condition: Some(e),
+                ..self.clone()
+            });
+        annotations
+            .into_iter()
+            .chain(principal)
+            .chain(action)
+            .chain(resource)
+            .chain(condition)
+            .chain(expressions)
+    }
+}
+
+/// The expressions obtained from `expr` by replacing it, or one of its
+/// subexpressions, with one of its operands, or by removing an element of a
+/// set or record literal in it
+fn expr_reductions(expr: &Expr) -> Box<dyn Iterator<Item = Expr> + '_> {
+    let operands = children(expr);
+    let hoisted = operands.clone().into_iter().cloned();
+    let removed: Box<dyn Iterator<Item = Expr>> = match expr.expr_kind() {
+        ExprKind::Set(elements) => Box::new((0..elements.len()).map(move |i| {
+            let elements = elements
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, e)| e.clone());
+            Expr::new(
+                ExprKind::Set(Arc::new(elements.collect())),
+                expr.source_loc().cloned(),
+                (),
+            )
+        })),
+        ExprKind::Record(fields) => Box::new(fields.keys().map(move |key| {
+            let mut fields = fields.as_ref().clone();
+            fields.remove(key);
+            Expr::new(
+                ExprKind::Record(Arc::new(fields)),
+                expr.source_loc().cloned(),
+                (),
+            )
+        })),
+        _ => Box::new(std::iter::empty()),
+    };
+    let nested = operands
+        .into_iter()
+        .enumerate()
+        .flat_map(move |(i, operand)| {
+            expr_reductions(operand).map(move |reduced| {
+                let mut reduced = Some(reduced);
+                let mut j = 0;
+                map_children(expr, |child| {
+                    let replacement = if j == i { reduced.take() } else { None };
+                    j += 1;
+                    replacement.unwrap_or_else(|| child.clone())
+                })
+            })
+        });
+    Box::new(hoisted.chain(removed).chain(nested))
+}
+
+/// The direct subexpressions of `expr`, in the order [`map_children`] visits
+/// them
+fn children(expr: &Expr) -> Vec<&Expr> {
+    match expr.expr_kind() {
+        ExprKind::Lit(_) | ExprKind::Var(_) | ExprKind::Slot(_) | ExprKind::Unknown(_) => vec![],
+        #[cfg(feature = "tolerant-ast")]
+        ExprKind::Error { .. } => vec![],
+        ExprKind::If {
+            test_expr,
+            then_expr,
+            else_expr,
+        } => vec![test_expr, then_expr, else_expr],
+        ExprKind::And { left, right } | ExprKind::Or { left, right } => vec![left, right],
+        ExprKind::BinaryApp { arg1, arg2, .. } => vec![arg1, arg2],
+        ExprKind::UnaryApp { arg: expr, .. }
+        | ExprKind::GetAttr { expr, .. }
+        | ExprKind::HasAttr { expr, .. }
+        | ExprKind::Like { expr, .. }
+        | ExprKind::Is { expr, .. } => vec![expr],
+        ExprKind::ExtensionFunctionApp { args, .. } | ExprKind::Set(args) => args.iter().collect(),
+        ExprKind::Record(fields) => fields.values().collect(),
+    }
+}
+
+/// Rebuild `expr` with each of its direct subexpressions replaced by the
+/// result of `f` on it
+fn map_children(expr: &Expr, mut f: impl FnMut(&Expr) -> Expr) -> Expr {
+    let kind = match expr.expr_kind() {
+        kind @ (ExprKind::Lit(_) | ExprKind::Var(_) | ExprKind::Slot(_) | ExprKind::Unknown(_)) => {
+            kind.clone()
+        }
+        #[cfg(feature = "tolerant-ast")]
+        kind @ ExprKind::Error { .. } => kind.clone(),
+        ExprKind::If {
+            test_expr,
+            then_expr,
+            else_expr,
+        } => ExprKind::If {
+            test_expr: Arc::new(f(test_expr)),
+            then_expr: Arc::new(f(then_expr)),
+            else_expr: Arc::new(f(else_expr)),
+        },
+        ExprKind::And { left, right } => ExprKind::And {
+            left: Arc::new(f(left)),
+            right: Arc::new(f(right)),
+        },
+        ExprKind::Or { left, right } => ExprKind::Or {
+            left: Arc::new(f(left)),
+            right: Arc::new(f(right)),
+        },
+        ExprKind::UnaryApp { op, arg } => ExprKind::UnaryApp {
+            op: *op,
+            arg: Arc::new(f(arg)),
+        },
+        ExprKind::BinaryApp { op, arg1, arg2 } => ExprKind::BinaryApp {
+            op: *op,
+            arg1: Arc::new(f(arg1)),
+            arg2: Arc::new(f(arg2)),
+        },
+        ExprKind::ExtensionFunctionApp { fn_name, args } => ExprKind::ExtensionFunctionApp {
+            fn_name: fn_name.clone(),
+            args: Arc::new(args.iter().map(f).collect()),
+        },
+        ExprKind::GetAttr { expr, attr } => ExprKind::GetAttr {
+            expr: Arc::new(f(expr)),
+            attr: attr.clone(),
+        },
+        ExprKind::HasAttr { expr, attr } => ExprKind::HasAttr {
+            expr: Arc::new(f(expr)),
+            attr: attr.clone(),
+        },
+        ExprKind::Like { expr, pattern } => ExprKind::Like {
+            expr: Arc::new(f(expr)),
+            pattern: pattern.clone(),
+        },
+        ExprKind::Is { expr, entity_type } => ExprKind::Is {
+            expr: Arc::new(f(expr)),
+            entity_type: entity_type.clone(),
+        },
+        ExprKind::Set(elements) => ExprKind::Set(Arc::new(elements.iter().map(f).collect())),
+        ExprKind::Record(fields) => ExprKind::Record(Arc::new(
+            fields.iter().map(|(k, v)| (k.clone(), f(v))).collect(),
+        )),
+    };
+    Expr::new(kind, expr.source_loc().cloned(), ())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{PolicyID, SlotId, Template},
+        extensions::Extensions,
+        parser::{parse_policy, parse_policy_or_template},
+        validator::{ValidationMode, Validator, ValidatorSchema},
+    };
+
+    fn validator() -> Validator {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r"
+            entity User { age: Long };
+            entity Doc { public: Bool, owner: User };
+            action read appliesTo { principal: User, resource: Doc };
+            ",
+            Extensions::all_available(),
+        )
+        .unwrap();
+        Validator::new(schema)
+    }
+
+    #[test]
+    fn minimize_failure() {
+        let validator = validator();
+        let minimize = |src: &str| {
+            let policy = parse_policy(Some(PolicyID::from_string("p")), src).unwrap();
+            let (_, policy) = Template::link_static_policy(policy);
+            validator
+                .minimize_failure(&policy, ValidationMode::Strict)
+                .map(|policy| policy.to_string())
+        };
+
+        assert_eq!(
+            minimize(
+                r#"
+                @reason("bug")
+                permit(principal == User::"alice", action == Action::"read", resource is Doc)
+                when { resource.public && [1, 2, 3].contains(principal.age) }
+                unless { resource.owner.name == "bob" || principal.age > 3 };
+                "#
+            )
+            .as_deref(),
+            Some("permit(\n  principal,\n  action,\n  resource\n) when {\n  resource.name\n};")
+        );
+        assert_eq!(
+            minimize(r#"permit(principal, action, resource) when { resource.public };"#),
+            None
+        );
+    }
+
+    #[test]
+    fn minimize_linked_failure() {
+        let validator = validator();
+        let template = parse_policy_or_template(
+            Some(PolicyID::from_string("t")),
+            r#"permit(principal == ?principal, action, resource) when { principal.age == "old" };"#,
+        )
+        .unwrap();
+        let policy = Template::link(
+            std::sync::Arc::new(template),
+            PolicyID::from_string("linked"),
+            [(SlotId::principal(), r#"User::"alice""#.parse().unwrap())].into(),
+        )
+        .unwrap();
+        let minimized = validator
+            .minimize_failure(&policy, ValidationMode::Strict)
+            .unwrap();
+        assert_eq!(minimized.id(), &PolicyID::from_string("linked"));
+        assert!(minimized.loc().is_none());
+        assert!(minimized
+            .condition()
+            .subexpressions()
+            .all(|e| e.source_loc().is_none()));
+        assert_eq!(
+            minimized.to_string(),
+            "permit(\n  principal,\n  action,\n  resource\n) when {\n  principal == \"old\"\n};"
+        );
+    }
+}
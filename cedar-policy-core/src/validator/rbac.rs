@@ -26,12 +26,17 @@ use crate::{
     validator::validation_errors::get_suggested_entity_type,
 };
 
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
 
 use crate::validator::{
     expr_iterator::{policy_entity_type_names, policy_entity_uids},
-    validation_errors::unrecognized_action_id_help,
-    ValidationError,
+    typecheck::{PolicyCheck, Typechecker},
+    types::RequestEnv,
+    validation_errors::{self, unrecognized_action_group_help, unrecognized_action_id_help},
+    ValidationError, ValidationMode, ValidationWarning,
 };
 
 use super::{schema::*, Validator};
@@ -85,19 +90,33 @@ impl Validator {
 
     /// Generate `UnrecognizedActionId` error for every entity id with an action
     /// entity type where the id could not be found in the actions list from the
-    /// schema.
+    /// schema. For an action group in an `action in` scope constraint with a
+    /// single action, the suggested alternatives are other action groups.
     pub fn validate_action_ids<'a>(
         schema: &'a ValidatorSchema,
         template: &'a Template,
     ) -> impl Iterator<Item = ValidationError> + 'a {
+        let action_groups: HashSet<&EntityUID> = match template.action_constraint() {
+            ActionConstraint::In(euids) if euids.len() == 1 => {
+                euids.iter().map(AsRef::as_ref).collect()
+            }
+            _ => HashSet::new(),
+        };
         policy_entity_uids(template).filter_map(move |euid| {
             let entity_type = euid.entity_type();
             if entity_type.is_action() && !schema.is_known_action_id(euid) {
+                let hint = if action_groups.contains(euid) {
+                    unrecognized_action_group_help(euid, schema)
+                } else {
+                    unrecognized_action_id_help(euid, schema)
+                };
+                let schema_location = hint.as_ref().and_then(|hint| hint.schema_location(schema));
                 Some(ValidationError::unrecognized_action_id(
                     euid.loc().cloned(),
                     template.id().clone(),
                     euid.to_string(),
-                    unrecognized_action_id_help(euid, schema),
+                    hint,
+                    schema_location,
                 ))
             } else {
                 None
@@ -105,6 +124,31 @@ impl Validator {
         })
     }
 
+    /// Generate an `EmptyActionGroup` warning for every action in an
+    /// `action in` scope constraint which has no member actions and does not
+    /// apply to any request itself, so that it cannot match any request.
+    pub(crate) fn validate_action_groups<'a>(
+        schema: &'a ValidatorSchema,
+        template: &'a Template,
+    ) -> impl Iterator<Item = ValidationWarning> + 'a {
+        let action_groups = match template.action_constraint() {
+            ActionConstraint::In(euids) => euids.as_slice(),
+            _ => &[],
+        };
+        action_groups.iter().filter_map(move |euid| {
+            let action = schema.get_action_id(euid)?;
+            (action.descendants().next().is_none() && !Self::applies_to_any_request(action)).then(
+                || {
+                    ValidationWarning::empty_action_group(
+                        euid.loc().cloned(),
+                        template.id().clone(),
+                        euid.to_string(),
+                    )
+                },
+            )
+        })
+    }
+
     /// Generate `UnrecognizedEntityType` error for
     /// every entity type in the slot environment that is not in the schema
     pub(crate) fn validate_entity_types_in_slots<'a>(
@@ -153,6 +197,25 @@ impl Validator {
         )
     }
 
+    /// An action group commonly declares no `appliesTo`, since it is only
+    /// meant to be used in `action in` constraints. Check if the action clause
+    /// instead uses such an action group with `==`.
+    fn check_if_in_fixes_action(&self, action_constraint: &ActionConstraint) -> bool {
+        match action_constraint {
+            ActionConstraint::Eq(euid) => self.schema.get_action_id(euid).is_some_and(|action| {
+                action.descendants().next().is_some() && !Self::applies_to_any_request(action)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Check if `action` applies to at least one principal type and one
+    /// resource type, so that some request can use it.
+    fn applies_to_any_request(action: &ValidatorActionId) -> bool {
+        action.applies_to_principals().next().is_some()
+            && action.applies_to_resources().next().is_some()
+    }
+
     fn check_if_in_fixes<'a>(
         &'a self,
         scope_constraint: &PrincipalOrResourceConstraint,
@@ -249,6 +312,151 @@ impl Validator {
         )
     }
 
+    /// Get the actions declared in the schema which the policy could apply
+    /// to: those satisfying its action scope constraint which apply to some
+    /// principal type and some resource type satisfying its principal and
+    /// resource scope constraints.
+    ///
+    /// If there are none, this returns the
+    /// [`validation_errors::InvalidActionApplication`] error the validator
+    /// reports for the policy, which says whether replacing `==` with `in` in
+    /// any of its scope constraints would help.
+    pub fn applicable_actions<'a>(
+        &'a self,
+        p: &'a Policy,
+    ) -> Result<HashSet<&'a EntityUID>, validation_errors::InvalidActionApplication> {
+        let principal_constraint = p.principal_constraint();
+        let resource_constraint = p.resource_constraint();
+        let actions = self.get_actions_applicable_to_scope(
+            &principal_constraint,
+            p.action_constraint(),
+            &resource_constraint,
+        );
+        if actions.is_empty() {
+            Err(validation_errors::InvalidActionApplication {
+                source_loc: p.loc().cloned(),
+                policy_id: p.id().clone(),
+                would_in_fix_principal: self
+                    .check_if_in_fixes_principal(&principal_constraint, p.action_constraint()),
+                would_in_fix_action: self.check_if_in_fixes_action(p.action_constraint()),
+                would_in_fix_resource: self
+                    .check_if_in_fixes_resource(&resource_constraint, p.action_constraint()),
+            })
+        } else {
+            Ok(actions)
+        }
+    }
+
+    /// Get the actions declared in the schema which no policy or template in
+    /// `policies` could apply to, in the sense of
+    /// [`Validator::applicable_actions`]. Actions which apply to no principal
+    /// type or no resource type, e.g., action groups without an `appliesTo`,
+    /// are never requested, so they are not included.
+    ///
+    /// Linked policies are not considered separately, since a link can only
+    /// apply to actions its template could apply to.
+    pub fn unused_actions<'a>(&'a self, policies: &ast::PolicySet) -> HashSet<&'a EntityUID> {
+        let used = policies
+            .all_templates()
+            .flat_map(|t| {
+                self.get_actions_applicable_to_scope(
+                    t.principal_constraint(),
+                    t.action_constraint(),
+                    t.resource_constraint(),
+                )
+            })
+            .collect::<HashSet<_>>();
+        self.schema
+            .action_ids()
+            .filter(|action| Self::applies_to_any_request(action))
+            .map(ValidatorActionId::name)
+            .filter(|action| !used.contains(action))
+            .collect()
+    }
+
+    /// Get the ids of the `forbid` policies in `policies` which can never
+    /// apply to the same request as any `permit` policy, so they can never
+    /// override a permit.
+    ///
+    /// A forbid policy and a permit policy are considered to apply to the same
+    /// request if there is a principal type, action and resource type in the
+    /// schema for which both their scopes can be satisfied and neither's
+    /// conditions are always false, and their scopes do not require the
+    /// principal or resource to be `==` different entities. Policies which
+    /// have type errors for a principal type, action and resource type are
+    /// assumed to apply to them. Template-linked policies are checked with the
+    /// entity types their template could be linked with.
+    ///
+    /// The ids are returned in order.
+    pub fn orphaned_forbids<'a>(&self, policies: &'a ast::PolicySet) -> Vec<&'a PolicyID> {
+        let extensions = self.extension_schemas();
+        let typechecker =
+            Typechecker::new(&self.schema, ValidationMode::default()).with_extensions(&extensions);
+        // The request types each template could apply to, or `None` if it
+        // could apply to a request for an undeclared action
+        let mut request_types = HashMap::new();
+        for template in policies.all_templates() {
+            let mut types = BTreeSet::new();
+            let mut undeclared = false;
+            for (env, check) in typechecker.typecheck_by_request_env(template) {
+                if matches!(check, PolicyCheck::Irrelevant(..)) {
+                    continue;
+                }
+                match env {
+                    RequestEnv::DeclaredAction {
+                        principal,
+                        action,
+                        resource,
+                        ..
+                    } => {
+                        types.insert((principal, action, resource));
+                    }
+                    RequestEnv::UndeclaredAction => undeclared = true,
+                }
+            }
+            request_types.insert(template.id(), (!undeclared).then_some(types));
+        }
+
+        let (forbids, permits): (Vec<_>, Vec<_>) = policies
+            .policies()
+            .partition(|p| p.effect() == ast::Effect::Forbid);
+        let overlap = |forbid: &Policy, permit: &Policy| match (
+            request_types.get(forbid.template().id()),
+            request_types.get(permit.template().id()),
+        ) {
+            (Some(Some(forbid_types)), Some(Some(permit_types))) => {
+                !forbid_types.is_disjoint(permit_types) && !Self::scopes_conflict(forbid, permit)
+            }
+            _ => true,
+        };
+        let mut orphaned = forbids
+            .into_iter()
+            .filter(|forbid| !permits.iter().any(|permit| overlap(forbid, permit)))
+            .map(Policy::id)
+            .collect::<Vec<_>>();
+        orphaned.sort();
+        orphaned
+    }
+
+    /// Do the scopes of `p1` and `p2` require the principal or resource to be
+    /// `==` different entities?
+    fn scopes_conflict(p1: &Policy, p2: &Policy) -> bool {
+        fn eq_uid(constraint: &PrincipalOrResourceConstraint) -> Option<&EntityUID> {
+            match constraint {
+                PrincipalOrResourceConstraint::Eq(EntityReference::EUID(euid)) => Some(euid),
+                _ => None,
+            }
+        }
+        let conflict = |c1: &PrincipalOrResourceConstraint, c2: &PrincipalOrResourceConstraint| matches!((eq_uid(c1), eq_uid(c2)), (Some(e1), Some(e2)) if e1 != e2);
+        conflict(
+            p1.principal_constraint().as_inner(),
+            p2.principal_constraint().as_inner(),
+        ) || conflict(
+            p1.resource_constraint().as_inner(),
+            p2.resource_constraint().as_inner(),
+        )
+    }
+
     // Check that there exists a (action id, principal type, resource type)
     // entity type pair where the action can be applied to both the principal
     // and resource. This function takes the three scope constraints as input
@@ -261,7 +469,68 @@ impl Validator {
         action_constraint: &ActionConstraint,
         resource_constraint: &ResourceConstraint,
     ) -> impl Iterator<Item = ValidationError> {
-        let mut apply_specs = self.get_apply_specs_for_action(action_constraint);
+        let no_applicable_actions = self
+            .get_actions_applicable_to_scope(
+                principal_constraint,
+                action_constraint,
+                resource_constraint,
+            )
+            .is_empty();
+
+        no_applicable_actions
+            .then(|| {
+                match self.entity_in_no_resource_scope(action_constraint, resource_constraint) {
+                    // Point at the entity literal itself rather than the
+                    // whole policy, when it has a location
+                    Some(euid) => ValidationError::unspecified_entity(
+                        euid.loc().or(source_loc).cloned(),
+                        policy_id.clone(),
+                        euid.clone(),
+                        resource_constraint,
+                    ),
+                    None => ValidationError::invalid_action_application(
+                        source_loc.cloned(),
+                        policy_id.clone(),
+                        self.check_if_in_fixes_principal(principal_constraint, action_constraint),
+                        self.check_if_in_fixes_action(action_constraint),
+                        self.check_if_in_fixes_resource(resource_constraint, action_constraint),
+                    ),
+                }
+            })
+            .into_iter()
+    }
+
+    /// If the resource scope constraint names an entity, but every action
+    /// satisfying the action scope constraint is declared with no resource,
+    /// get that entity.
+    fn entity_in_no_resource_scope<'a>(
+        &self,
+        action_constraint: &ActionConstraint,
+        resource_constraint: &'a ResourceConstraint,
+    ) -> Option<&'a EntityUID> {
+        let euid = match resource_constraint.as_inner() {
+            PrincipalOrResourceConstraint::Eq(EntityReference::EUID(euid))
+            | PrincipalOrResourceConstraint::In(EntityReference::EUID(euid))
+            | PrincipalOrResourceConstraint::IsIn(_, EntityReference::EUID(euid)) => euid,
+            _ => return None,
+        };
+        let mut actions = self
+            .get_actions_satisfying_constraint(action_constraint)
+            .filter_map(|action| self.schema.get_action_id(action))
+            .peekable();
+        (actions.peek().is_some() && actions.all(ValidatorActionId::has_no_resource))
+            .then_some(euid.as_ref())
+    }
+
+    /// Get the actions satisfying the action scope constraint which apply to
+    /// some principal type and some resource type satisfying the principal and
+    /// resource scope constraints.
+    fn get_actions_applicable_to_scope<'a>(
+        &'a self,
+        principal_constraint: &PrincipalConstraint,
+        action_constraint: &'a ActionConstraint,
+        resource_constraint: &ResourceConstraint,
+    ) -> HashSet<&'a EntityUID> {
         let resources_for_scope: HashSet<&ast::EntityType> = self
             .get_resources_satisfying_constraint(resource_constraint)
             .collect();
@@ -269,27 +538,28 @@ impl Validator {
             .get_principals_satisfying_constraint(principal_constraint)
             .collect();
 
-        let would_in_fix_principal =
-            self.check_if_in_fixes_principal(principal_constraint, action_constraint);
-        let would_in_fix_resource =
-            self.check_if_in_fixes_resource(resource_constraint, action_constraint);
-
-        Some(ValidationError::invalid_action_application(
-            source_loc.cloned(),
-            policy_id.clone(),
-            would_in_fix_principal,
-            would_in_fix_resource,
-        ))
-        .filter(|_| {
-            !apply_specs.any(|spec| {
-                let action_principals = spec.applicable_principal_types().collect::<HashSet<_>>();
-                let action_resources = spec.applicable_resource_types().collect::<HashSet<_>>();
-                let matching_principal = !principals_for_scope.is_disjoint(&action_principals);
-                let matching_resource = !resources_for_scope.is_disjoint(&action_resources);
-                matching_principal && matching_resource
+        self.get_actions_satisfying_constraint(action_constraint)
+            .filter(|action_id| {
+                self.schema.get_action_id(action_id).is_some_and(|action| {
+                    let spec = &action.applies_to;
+                    let action_principals =
+                        spec.applicable_principal_types().collect::<HashSet<_>>();
+                    let action_resources = spec.applicable_resource_types().collect::<HashSet<_>>();
+                    // The resource of a request for an action with no
+                    // resource is the action itself, which a resource scope
+                    // constraint can only allow if it is unconstrained
+                    let resource_applies = if action.has_no_resource() {
+                        matches!(
+                            resource_constraint.as_inner(),
+                            PrincipalOrResourceConstraint::Any
+                        )
+                    } else {
+                        !resources_for_scope.is_disjoint(&action_resources)
+                    };
+                    !principals_for_scope.is_disjoint(&action_principals) && resource_applies
+                })
             })
-        })
-        .into_iter()
+            .collect()
     }
 
     /// Gather all `ApplySpec` objects for all actions in the schema.
@@ -404,9 +674,13 @@ mod test {
     use crate::{
         ast::{Effect, Eid, EntityUID, PolicyID, PrincipalConstraint, ResourceConstraint},
         est::Annotations,
-        parser::{parse_policy, parse_policy_or_template},
+        parser::{
+            parse_policy, parse_policy_or_template, parse_policyset,
+            parse_policyset_with_id_annotations,
+        },
         test_utils::{expect_err, ExpectedErrorMessageBuilder},
     };
+    use cool_asserts::assert_matches;
     use miette::Report;
 
     use super::*;
@@ -482,6 +756,150 @@ mod test {
         assert_eq!(notes.len(), 1, "{notes:?}");
     }
 
+    #[test]
+    fn applicable_actions() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r"
+            entity User in [Group];
+            entity Group;
+            entity Doc in [Folder];
+            entity Folder;
+            action read, write appliesTo { principal: User, resource: Doc };
+            action list appliesTo { principal: User, resource: Folder };
+            action admin appliesTo { principal: Group, resource: Folder };
+            ",
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+        let applicable_actions = |src: &str| {
+            let p = parse_policy(None, src).unwrap();
+            let (_, p) = Template::link_static_policy(p);
+            validator.applicable_actions(&p).map(|actions| {
+                actions
+                    .into_iter()
+                    .map(|action| action.eid().escaped().to_string())
+                    .collect::<HashSet<_>>()
+            })
+        };
+
+        assert_matches!(
+            applicable_actions(r#"permit(principal, action, resource is Doc);"#),
+            Ok(actions) => assert_eq!(actions, HashSet::from(["read".into(), "write".into()]))
+        );
+        assert_matches!(
+            applicable_actions(r#"permit(principal == User::"alice", action, resource);"#),
+            Ok(actions) => assert_eq!(actions, HashSet::from(["read".into(), "write".into(), "list".into()]))
+        );
+        assert_matches!(
+            applicable_actions(
+                r#"permit(principal, action in [Action::"read", Action::"admin"], resource in Folder::"f");"#
+            ),
+            Ok(actions) => assert_eq!(actions, HashSet::from(["read".into(), "admin".into()]))
+        );
+        assert_matches!(
+            applicable_actions(
+                r#"permit(principal, action == Action::"read", resource == Folder::"f");"#
+            ),
+            Err(err) => {
+                assert!(!err.would_in_fix_principal);
+                assert!(err.would_in_fix_resource);
+            }
+        );
+    }
+
+    #[test]
+    fn unused_actions() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r"
+            entity User;
+            entity Doc;
+            action all;
+            action read, write in [all] appliesTo { principal: User, resource: Doc };
+            action delete appliesTo { principal: User, resource: Doc };
+            ",
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+        let unused_actions = |src: &str| {
+            let pset = parse_policyset(src).unwrap();
+            validator
+                .unused_actions(&pset)
+                .into_iter()
+                .map(|action| action.eid().escaped().to_string())
+                .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(
+            unused_actions(""),
+            HashSet::from(["read".into(), "write".into(), "delete".into()])
+        );
+        assert_eq!(
+            unused_actions(r#"permit(principal, action == Action::"read", resource);"#),
+            HashSet::from(["write".into(), "delete".into()])
+        );
+        assert_eq!(
+            unused_actions(
+                r#"permit(principal == ?principal, action in Action::"all", resource);"#
+            ),
+            HashSet::from(["delete".into()])
+        );
+        assert_eq!(
+            unused_actions(r#"forbid(principal, action, resource);"#),
+            HashSet::new()
+        );
+    }
+
+    #[test]
+    fn orphaned_forbids() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r"
+            entity User, Admin;
+            entity Doc { public: Bool };
+            action read appliesTo { principal: User, resource: Doc };
+            action manage appliesTo { principal: Admin, resource: Doc };
+            ",
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+        let orphaned_forbids = |src: &str| {
+            let pset = parse_policyset_with_id_annotations(src).unwrap();
+            validator
+                .orphaned_forbids(&pset)
+                .into_iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        let permits = r#"
+            @id("read") permit(principal == User::"alice", action == Action::"read", resource);
+        "#;
+        let forbids = r#"
+            @id("overlaps") forbid(principal, action, resource) when { !resource.public };
+            @id("other_action") forbid(principal, action == Action::"manage", resource);
+            @id("other_principal") forbid(principal == User::"bob", action, resource);
+            @id("never") forbid(principal, action == Action::"read", resource) when { false };
+            @id("template") forbid(principal == ?principal, action, resource);
+            @id("admins") forbid(principal is Admin, action, resource);
+        "#;
+        assert_eq!(
+            orphaned_forbids(&format!("{permits}{forbids}")),
+            ["admins", "never", "other_action", "other_principal"]
+        );
+        // Every forbid is orphaned without any permits. Unlinked templates
+        // are not policies, so they are never reported.
+        assert_eq!(orphaned_forbids(forbids).len(), 5);
+        assert_eq!(
+            orphaned_forbids(&format!(
+                "{permits}{forbids}{}",
+                r#"@id("manage") permit(principal, action == Action::"manage", resource);"#
+            )),
+            ["never", "other_principal"]
+        );
+    }
+
     #[test]
     fn validate_entity_type_in_singleton_schema() {
         let foo_type = "foo_type";
@@ -821,6 +1239,165 @@ mod test {
         assert_eq!(notes.len(), 1, "{notes:?}");
     }
 
+    #[test]
+    fn validate_action_ids_in_action_groups() {
+        let schema: ValidatorSchema = json_schema::Fragment::from_json_str(
+            r#"
+                {
+                    "": {
+                        "entityTypes": {},
+                        "actions": {
+                            "read": { "memberOf": [{ "id": "readOnly" }, { "id": "superuser" }] },
+                            "write": { "memberOf": [{ "id": "superuser" }] },
+                            "readOnly": {},
+                            "superuser": {}
+                        }
+                    }
+                }"#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        let validate = Validator::new(schema);
+
+        // A missing action in a list of actions gets suggestions from all actions
+        let src = r#"permit(principal, action in [Action::"raed", Action::"write"], resource);"#;
+        let policy = parse_policy_or_template(None, src).unwrap();
+        let notes: Vec<ValidationError> =
+            Validator::validate_action_ids(validate.schema(), &policy).collect();
+        expect_err(
+            src,
+            &Report::new(notes.first().unwrap().clone()),
+            &ExpectedErrorMessageBuilder::error(
+                r#"for policy `policy0`, unrecognized action `Action::"raed"`"#,
+            )
+            .exactly_one_underline(r#"Action::"raed""#)
+            .help(r#"did you mean `Action::"read"`?"#)
+            .build(),
+        );
+        assert_eq!(notes.len(), 1, "{notes:?}");
+
+        // A missing action group gets suggestions from the action groups only
+        let src = r#"permit(principal, action in Action::"readAll", resource);"#;
+        let policy = parse_policy_or_template(None, src).unwrap();
+        let notes: Vec<ValidationError> =
+            Validator::validate_action_ids(validate.schema(), &policy).collect();
+        expect_err(
+            src,
+            &Report::new(notes.first().unwrap().clone()),
+            &ExpectedErrorMessageBuilder::error(
+                r#"for policy `policy0`, unrecognized action `Action::"readAll"`"#,
+            )
+            .exactly_one_underline(r#"Action::"readAll""#)
+            .help(r#"did you mean the action group `Action::"readOnly"`?"#)
+            .build(),
+        );
+        assert_eq!(notes.len(), 1, "{notes:?}");
+    }
+
+    #[test]
+    fn validate_action_ids_schema_location() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User;
+            action view appliesTo { principal: User, resource: User };
+            action edit;
+            "#,
+            crate::extensions::Extensions::all_available(),
+        )
+        .unwrap();
+        let src = r#"permit(principal, action == Action::"veiw", resource);"#;
+        let policy = parse_policy_or_template(None, src).unwrap();
+        let notes: Vec<ValidationError> =
+            Validator::validate_action_ids(&schema, &policy).collect();
+        assert_matches!(notes.as_slice(), [ValidationError::UnrecognizedActionId(e)] => {
+            assert_matches!(e.hint.as_ref(), Some(hint) => {
+                assert_eq!(hint.suggestion(), r#"Action::"view""#);
+            });
+            assert_matches!(e.schema_location.as_ref().and_then(Loc::snippet), Some(snippet) => {
+                assert_eq!(snippet, "action view appliesTo { principal: User, resource: User };");
+            });
+        });
+
+        let src = r#"permit(principal, action in [Action::"view", Action::"edti"], resource);"#;
+        let policy = parse_policy_or_template(None, src).unwrap();
+        let notes: Vec<ValidationError> =
+            Validator::validate_action_ids(&schema, &policy).collect();
+        assert_matches!(notes.as_slice(), [ValidationError::UnrecognizedActionId(e)] => {
+            assert_matches!(e.schema_location.as_ref().and_then(Loc::snippet), Some(snippet) => {
+                assert_eq!(snippet, "action edit;");
+            });
+        });
+    }
+
+    #[test]
+    fn validate_action_groups_without_applies_to() {
+        let schema: ValidatorSchema = json_schema::Fragment::from_json_str(
+            r#"
+                {
+                    "": {
+                        "entityTypes": { "User": {}, "Photo": {} },
+                        "actions": {
+                            "view": {
+                                "memberOf": [{ "id": "readOnly" }],
+                                "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] }
+                            },
+                            "readOnly": {},
+                            "writeOnly": {}
+                        }
+                    }
+                }"#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        let validator = Validator::new(schema);
+
+        // The group applies to the actions in it
+        let src = r#"permit(principal, action in Action::"readOnly", resource);"#;
+        let policy = parse_policy_or_template(None, src).unwrap();
+        assert_validate_policy_succeeds(&validator, &policy);
+
+        // but using the group with `==` is flagged
+        let src = r#"permit(principal, action == Action::"readOnly", resource);"#;
+        let policy = parse_policy_or_template(None, src).unwrap();
+        let errors: Vec<_> = validator
+            .validate_template_action_application(&policy)
+            .collect();
+        expect_err(
+            src,
+            &Report::new(errors.first().unwrap().clone()),
+            &ExpectedErrorMessageBuilder::error(
+                "for policy `policy0`, unable to find an applicable action given the policy scope constraints",
+            )
+            .exactly_one_underline(src)
+            .help("the action is an action group which does not apply to any request itself; try replacing `==` with `in` in the action clause")
+            .build(),
+        );
+        assert_eq!(errors.len(), 1, "{errors:?}");
+
+        // An empty group matches no requests
+        let src =
+            r#"permit(principal, action in [Action::"view", Action::"writeOnly"], resource);"#;
+        let policy = parse_policy_or_template(None, src).unwrap();
+        let warnings: Vec<ValidationWarning> =
+            Validator::validate_action_groups(validator.schema(), &policy).collect();
+        expect_err(
+            src,
+            &Report::new(warnings.first().unwrap().clone()),
+            &ExpectedErrorMessageBuilder::error(
+                r#"for policy `policy0`, action group `Action::"writeOnly"` is empty: it has no member actions and does not apply to any principal and resource types"#,
+            )
+            .exactly_one_underline(r#"Action::"writeOnly""#)
+            .help("add actions to the group using `memberOf` in the schema")
+            .build(),
+        );
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        let (errors, warnings) = validator.validate_policy(&policy, ValidationMode::default());
+        assert_eq!(errors.count(), 0);
+        assert_eq!(warnings.count(), 1);
+    }
+
     #[test]
     fn validate_namespaced_action_id_in_schema() {
         let descriptors = json_schema::Fragment::from_json_str(
@@ -1115,6 +1692,7 @@ mod test {
                     applies_to: Some(json_schema::ApplySpec {
                         resource_types: vec![resource_type.parse().unwrap()],
                         principal_types: vec![principal_type.parse().unwrap()],
+                        no_resource: false,
                         context: json_schema::AttributesOrContext::default(),
                     }),
                     member_of: Some(vec![]),
@@ -1308,6 +1886,7 @@ mod test {
                 PolicyID::from_string("policy0"),
                 false,
                 false,
+                false,
             )],
         );
         assert_validate_policy_flags_impossible_policy(&validator, &policy);
@@ -1336,6 +1915,7 @@ mod test {
                     PolicyID::from_string("policy0"),
                     false,
                     false,
+                    false,
                 ),
             ],
         );
@@ -1352,6 +1932,7 @@ mod test {
                 PolicyID::from_string("policy0"),
                 false,
                 false,
+                false,
             )],
         );
         assert_validate_policy_flags_impossible_policy(&validator, &policy);
@@ -1392,6 +1973,7 @@ mod test {
                 PolicyID::from_string("policy0"),
                 false,
                 false,
+                false,
             )],
         );
         assert_validate_policy_flags_impossible_policy(&validator, &policy);
@@ -1407,6 +1989,7 @@ mod test {
                 PolicyID::from_string("policy0"),
                 false,
                 false,
+                false,
             )],
         );
         assert_validate_policy_flags_impossible_policy(&validator, &policy);
@@ -1435,6 +2018,7 @@ mod test {
                     PolicyID::from_string("policy0"),
                     false,
                     false,
+                    false,
                 ),
             ],
         );
@@ -1531,6 +2115,7 @@ mod test {
                         applies_to: Some(json_schema::ApplySpec {
                             resource_types: vec![resource_type.parse().unwrap()],
                             principal_types: vec![principal_type.parse().unwrap()],
+                            no_resource: false,
                             context: json_schema::AttributesOrContext::default(),
                         }),
                         member_of: Some(vec![json_schema::ActionEntityUID::new(
@@ -0,0 +1,677 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Inference of the entity types, attributes, and actions a schema must
+//! declare for a policy, from how the policy uses them.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+use smol_str::SmolStr;
+
+use crate::ast::{
+    ActionConstraint, BinaryOp, EntityReference, EntityType, EntityUID, Expr, ExprKind, Literal,
+    Name, PrincipalOrResourceConstraint, Template, UnaryOp, Var,
+};
+
+use super::extensions::ExtensionSchemas;
+use super::types::{EntityKind, Type};
+use super::Validator;
+
+/// Attributes required of an entity type or record, by name
+type Attributes = BTreeMap<SmolStr, AttributeRequirement>;
+
+/// The entity types, attributes, and actions a schema must declare for a
+/// policy to validate, returned by [`Validator::inferred_requirements`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaRequirements {
+    entity_types: BTreeMap<EntityType, Attributes>,
+    principal: Attributes,
+    resource: Attributes,
+    context: Attributes,
+    actions: BTreeSet<EntityUID>,
+}
+
+impl SchemaRequirements {
+    /// Entity types the policy uses, ordered by name: those in the policy
+    /// scope, in entity literals, and in `is` expressions. Action entity types
+    /// are not included.
+    pub fn entity_types(&self) -> impl Iterator<Item = &EntityType> {
+        self.entity_types.keys()
+    }
+
+    /// Attributes of the entity type `ety` the policy uses, ordered by name.
+    /// Attributes of `principal` and `resource` are included if the policy
+    /// scope determines their entity type, e.g., `principal == User::"alice"`
+    /// or `resource is Photo`.
+    pub fn entity_type_attributes(
+        &self,
+        ety: &EntityType,
+    ) -> impl Iterator<Item = (&SmolStr, &AttributeRequirement)> {
+        self.entity_types.get(ety).into_iter().flatten()
+    }
+
+    /// Attributes of `principal` the policy uses, ordered by name, if the
+    /// policy scope does not determine its entity type
+    pub fn principal_attributes(&self) -> impl Iterator<Item = (&SmolStr, &AttributeRequirement)> {
+        self.principal.iter()
+    }
+
+    /// Attributes of `resource` the policy uses, ordered by name, if the
+    /// policy scope does not determine its entity type
+    pub fn resource_attributes(&self) -> impl Iterator<Item = (&SmolStr, &AttributeRequirement)> {
+        self.resource.iter()
+    }
+
+    /// Attributes of `context` the policy uses, ordered by name
+    pub fn context_attributes(&self) -> impl Iterator<Item = (&SmolStr, &AttributeRequirement)> {
+        self.context.iter()
+    }
+
+    /// Actions the policy uses, ordered by name: those in the policy scope
+    /// and in entity literals
+    pub fn actions(&self) -> impl Iterator<Item = &EntityUID> {
+        self.actions.iter()
+    }
+}
+
+/// An attribute a policy uses, with the minimal type it requires of the
+/// attribute, as part of [`SchemaRequirements`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeRequirement {
+    ty: RequiredType,
+    optional: bool,
+}
+
+impl AttributeRequirement {
+    /// The minimal type, in Cedar schema syntax, the policy requires of the
+    /// attribute, inferred from the operations the policy applies to it, e.g.,
+    /// `Long` for an operand of `<`. Parts of the type which could not be
+    /// inferred are written `_`, e.g., `Set<_>` for an operand of `isEmpty()`.
+    /// An attribute whose own attributes are accessed is inferred to be a
+    /// record, although it may also be an entity. Returns `None` if nothing
+    /// could be inferred, or only that the attribute is an entity of unknown
+    /// type.
+    pub fn ty(&self) -> Option<String> {
+        match self.ty {
+            RequiredType::Unknown | RequiredType::Entity(None) => None,
+            _ => Some(self.ty.to_string()),
+        }
+    }
+
+    /// Whether the policy tests for the attribute with `has`, which suggests
+    /// that it should be declared optional
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    fn merge(&mut self, other: Self) {
+        let ty = std::mem::replace(&mut self.ty, RequiredType::Unknown);
+        self.ty = ty.merge(other.ty);
+        self.optional |= other.optional;
+    }
+}
+
+/// Minimal type required of an expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RequiredType {
+    /// Nothing is required
+    Unknown,
+    Bool,
+    Long,
+    String,
+    /// An entity, of the given type if it is known
+    Entity(Option<EntityType>),
+    Set(Box<RequiredType>),
+    /// A record with (at least) these attributes
+    Record(Attributes),
+    Extension(Name),
+}
+
+impl RequiredType {
+    fn record(attr: &SmolStr, ty: Self, optional: bool) -> Self {
+        Self::Record(BTreeMap::from([(
+            attr.clone(),
+            AttributeRequirement { ty, optional },
+        )]))
+    }
+
+    fn set_of_unknown() -> Self {
+        Self::Set(Box::new(Self::Unknown))
+    }
+
+    /// The type of the elements of a set of this type
+    fn element(&self) -> Self {
+        match self {
+            Self::Set(element) => element.as_ref().clone(),
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Combine the requirements of `self` and `other`. If they are
+    /// incompatible, `self` is kept.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Unknown, other) => other,
+            (this, Self::Unknown) => this,
+            (Self::Set(this), Self::Set(other)) => Self::Set(Box::new(this.merge(*other))),
+            (Self::Record(mut this), Self::Record(other)) => {
+                merge_attributes(&mut this, other);
+                Self::Record(this)
+            }
+            (Self::Entity(None), Self::Entity(ety)) => Self::Entity(ety),
+            (this, _) => this,
+        }
+    }
+
+    /// The requirement corresponding to a type from an extension function
+    /// signature
+    fn from_type(ty: &Type) -> Self {
+        match ty {
+            Type::Bool(_) => Self::Bool,
+            Type::Long => Self::Long,
+            Type::String => Self::String,
+            Type::Set {
+                element_type: Some(element),
+            } => Self::Set(Box::new(Self::from_type(element))),
+            Type::Entity(EntityKind::Entity(lub)) => Self::Entity(lub.get_single_entity().cloned()),
+            Type::ExtensionType { name } => Self::Extension(name.clone()),
+            Type::Never
+            | Type::Set { element_type: None }
+            | Type::Entity(_)
+            | Type::Record { .. } => Self::Unknown,
+        }
+    }
+}
+
+impl Display for RequiredType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown | Self::Entity(None) => write!(f, "_"),
+            Self::Bool => write!(f, "Bool"),
+            Self::Long => write!(f, "Long"),
+            Self::String => write!(f, "String"),
+            Self::Entity(Some(ety)) => write!(f, "{ety}"),
+            Self::Set(element) => write!(f, "Set<{element}>"),
+            Self::Record(attrs) => write!(
+                f,
+                "{{{}}}",
+                attrs
+                    .iter()
+                    .map(|(name, attr)| format!(
+                        "{name}{}: {}",
+                        if attr.optional { "?" } else { "" },
+                        attr.ty
+                    ))
+                    .join(", ")
+            ),
+            Self::Extension(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+fn merge_attributes(attrs: &mut Attributes, other: Attributes) {
+    for (name, attr) in other {
+        match attrs.get_mut(&name) {
+            Some(prev) => prev.merge(attr),
+            None => {
+                attrs.insert(name, attr);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inferrer<'a> {
+    requirements: SchemaRequirements,
+    principal_type: Option<EntityType>,
+    resource_type: Option<EntityType>,
+    extensions: &'a ExtensionSchemas<'a>,
+}
+
+impl Inferrer<'_> {
+    /// Record the use of an entity literal
+    fn use_entity(&mut self, euid: &EntityUID) {
+        if euid.entity_type().is_action() {
+            self.requirements.actions.insert(euid.clone());
+        } else {
+            self.use_entity_type(euid.entity_type());
+        }
+    }
+
+    fn use_entity_type(&mut self, ety: &EntityType) {
+        if !ety.is_action() {
+            self.requirements
+                .entity_types
+                .entry(ety.clone())
+                .or_default();
+        }
+    }
+
+    /// Record the entity types used in a principal or resource scope
+    /// constraint, and return the entity type of the principal or resource
+    /// if the constraint determines it
+    fn scope(&mut self, constraint: &PrincipalOrResourceConstraint) -> Option<EntityType> {
+        match constraint {
+            PrincipalOrResourceConstraint::Any
+            | PrincipalOrResourceConstraint::Eq(EntityReference::Slot(_))
+            | PrincipalOrResourceConstraint::In(EntityReference::Slot(_)) => None,
+            PrincipalOrResourceConstraint::Eq(EntityReference::EUID(euid)) => {
+                self.use_entity(euid);
+                Some(euid.entity_type().clone())
+            }
+            PrincipalOrResourceConstraint::In(EntityReference::EUID(euid)) => {
+                self.use_entity(euid);
+                None
+            }
+            PrincipalOrResourceConstraint::Is(ety) => {
+                self.use_entity_type(ety);
+                Some(ety.as_ref().clone())
+            }
+            PrincipalOrResourceConstraint::IsIn(ety, reference) => {
+                if let EntityReference::EUID(euid) = reference {
+                    self.use_entity(euid);
+                }
+                self.use_entity_type(ety);
+                Some(ety.as_ref().clone())
+            }
+        }
+    }
+
+    /// The type of `e`, as far as it can be determined without knowing the
+    /// types of attributes
+    fn type_of(&self, e: &Expr) -> RequiredType {
+        match e.expr_kind() {
+            ExprKind::Lit(Literal::Bool(_)) => RequiredType::Bool,
+            ExprKind::Lit(Literal::Long(_)) => RequiredType::Long,
+            ExprKind::Lit(Literal::String(_)) => RequiredType::String,
+            ExprKind::Lit(Literal::EntityUID(euid)) => {
+                RequiredType::Entity(Some(euid.entity_type().clone()))
+            }
+            ExprKind::Var(Var::Principal) => RequiredType::Entity(self.principal_type.clone()),
+            ExprKind::Var(Var::Resource) => RequiredType::Entity(self.resource_type.clone()),
+            ExprKind::Var(Var::Action) | ExprKind::Slot(_) => RequiredType::Entity(None),
+            ExprKind::If {
+                then_expr,
+                else_expr,
+                ..
+            } => self.type_of(then_expr).merge(self.type_of(else_expr)),
+            ExprKind::And { .. }
+            | ExprKind::Or { .. }
+            | ExprKind::UnaryApp {
+                op: UnaryOp::Not | UnaryOp::IsEmpty,
+                ..
+            }
+            | ExprKind::HasAttr { .. }
+            | ExprKind::Like { .. }
+            | ExprKind::Is { .. } => RequiredType::Bool,
+            ExprKind::UnaryApp {
+                op: UnaryOp::Neg, ..
+            } => RequiredType::Long,
+            ExprKind::BinaryApp { op, .. } => match op {
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => RequiredType::Long,
+                BinaryOp::GetTag => RequiredType::Unknown,
+                BinaryOp::Eq
+                | BinaryOp::Less
+                | BinaryOp::LessEq
+                | BinaryOp::In
+                | BinaryOp::Contains
+                | BinaryOp::ContainsAll
+                | BinaryOp::ContainsAny
+                | BinaryOp::HasTag => RequiredType::Bool,
+            },
+            ExprKind::ExtensionFunctionApp { fn_name, .. } => self
+                .extensions
+                .func_type(fn_name)
+                .map_or(RequiredType::Unknown, |f| {
+                    RequiredType::from_type(f.return_type())
+                }),
+            ExprKind::Set(elements) => RequiredType::Set(Box::new(
+                elements
+                    .iter()
+                    .fold(RequiredType::Unknown, |ty, e| ty.merge(self.type_of(e))),
+            )),
+            ExprKind::Record(attrs) => RequiredType::Record(
+                attrs
+                    .iter()
+                    .map(|(name, e)| {
+                        (
+                            name.clone(),
+                            AttributeRequirement {
+                                ty: self.type_of(e),
+                                optional: false,
+                            },
+                        )
+                    })
+                    .collect(),
+            ),
+            ExprKind::Var(Var::Context) | ExprKind::GetAttr { .. } | ExprKind::Unknown(_) => {
+                RequiredType::Unknown
+            }
+            #[cfg(feature = "tolerant-ast")]
+            ExprKind::Error { .. } => RequiredType::Unknown,
+        }
+    }
+
+    /// Record the requirements of `e` and its subexpressions, given that the
+    /// policy uses `e` as a value of type `expected`
+    fn visit(&mut self, e: &Expr, expected: RequiredType) {
+        match e.expr_kind() {
+            ExprKind::Lit(Literal::EntityUID(euid)) => {
+                self.use_entity(euid);
+                if let RequiredType::Record(attrs) = expected {
+                    if !euid.entity_type().is_action() {
+                        self.require_attributes(Some(euid.entity_type()), attrs);
+                    }
+                }
+            }
+            ExprKind::Var(var) => {
+                if let RequiredType::Record(attrs) = expected {
+                    match var {
+                        Var::Principal => match self.principal_type.clone() {
+                            Some(ety) => self.require_attributes(Some(&ety), attrs),
+                            None => merge_attributes(&mut self.requirements.principal, attrs),
+                        },
+                        Var::Resource => match self.resource_type.clone() {
+                            Some(ety) => self.require_attributes(Some(&ety), attrs),
+                            None => merge_attributes(&mut self.requirements.resource, attrs),
+                        },
+                        Var::Context => merge_attributes(&mut self.requirements.context, attrs),
+                        Var::Action => {}
+                    }
+                }
+            }
+            ExprKind::Lit(_) | ExprKind::Slot(_) | ExprKind::Unknown(_) => {}
+            ExprKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => {
+                self.visit(test_expr, RequiredType::Bool);
+                self.visit(then_expr, expected.clone());
+                self.visit(else_expr, expected);
+            }
+            ExprKind::And { left, right } | ExprKind::Or { left, right } => {
+                self.visit(left, RequiredType::Bool);
+                self.visit(right, RequiredType::Bool);
+            }
+            ExprKind::UnaryApp { op, arg } => {
+                let ty = match op {
+                    UnaryOp::Not => RequiredType::Bool,
+                    UnaryOp::Neg => RequiredType::Long,
+                    UnaryOp::IsEmpty => RequiredType::set_of_unknown(),
+                };
+                self.visit(arg, ty);
+            }
+            ExprKind::BinaryApp { op, arg1, arg2 } => self.visit_binary(*op, arg1, arg2),
+            ExprKind::ExtensionFunctionApp { fn_name, args } => {
+                let arg_types = self
+                    .extensions
+                    .func_type(fn_name)
+                    .map(|f| {
+                        f.argument_types()
+                            .iter()
+                            .map(RequiredType::from_type)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                for (i, arg) in args.iter().enumerate() {
+                    let ty = arg_types.get(i).cloned().unwrap_or(RequiredType::Unknown);
+                    self.visit(arg, ty);
+                }
+            }
+            ExprKind::GetAttr { expr, attr } => {
+                self.visit(expr, RequiredType::record(attr, expected, false));
+            }
+            ExprKind::HasAttr { expr, attr } => {
+                self.visit(
+                    expr,
+                    RequiredType::record(attr, RequiredType::Unknown, true),
+                );
+            }
+            ExprKind::Like { expr, .. } => self.visit(expr, RequiredType::String),
+            ExprKind::Is { expr, entity_type } => {
+                self.use_entity_type(entity_type);
+                self.visit(expr, RequiredType::Entity(Some(entity_type.clone())));
+            }
+            ExprKind::Set(elements) => {
+                let element = expected.element();
+                for e in elements.iter() {
+                    self.visit(e, element.clone());
+                }
+            }
+            ExprKind::Record(attrs) => {
+                let expected = match expected {
+                    RequiredType::Record(attrs) => attrs,
+                    _ => Attributes::new(),
+                };
+                for (name, e) in attrs.iter() {
+                    let ty = expected
+                        .get(name)
+                        .map_or(RequiredType::Unknown, |attr| attr.ty.clone());
+                    self.visit(e, ty);
+                }
+            }
+            #[cfg(feature = "tolerant-ast")]
+            ExprKind::Error { .. } => {}
+        }
+    }
+
+    fn visit_binary(&mut self, op: BinaryOp, arg1: &Expr, arg2: &Expr) {
+        let (ty1, ty2) = (self.type_of(arg1), self.type_of(arg2));
+        let (expected1, expected2) = match op {
+            BinaryOp::Eq => (ty2, ty1),
+            BinaryOp::Less | BinaryOp::LessEq => {
+                // Extension types like `decimal` and `datetime` can also be
+                // compared, but only with values of the same type
+                let ty = match ty1.merge(ty2) {
+                    ty @ RequiredType::Extension(_) => ty,
+                    _ => RequiredType::Long,
+                };
+                (ty.clone(), ty)
+            }
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                (RequiredType::Long, RequiredType::Long)
+            }
+            BinaryOp::In => (RequiredType::Entity(None), RequiredType::Unknown),
+            BinaryOp::Contains => (RequiredType::Set(Box::new(ty2)), ty1.element()),
+            BinaryOp::ContainsAll | BinaryOp::ContainsAny => (
+                RequiredType::set_of_unknown().merge(ty2),
+                RequiredType::set_of_unknown().merge(ty1),
+            ),
+            BinaryOp::GetTag | BinaryOp::HasTag => {
+                (RequiredType::Entity(None), RequiredType::String)
+            }
+        };
+        self.visit(arg1, expected1);
+        self.visit(arg2, expected2);
+    }
+
+    fn require_attributes(&mut self, ety: Option<&EntityType>, attrs: Attributes) {
+        if let Some(ety) = ety {
+            merge_attributes(
+                self.requirements
+                    .entity_types
+                    .entry(ety.clone())
+                    .or_default(),
+                attrs,
+            );
+        }
+    }
+}
+
+impl Validator {
+    /// Infer the entity types, attributes, and actions a schema must declare
+    /// for the template `p`, and the minimal types of the attributes, from
+    /// how `p` uses them, e.g., to bootstrap a schema from existing policies.
+    /// This is inference, not checking: the schema of this validator, if any,
+    /// is ignored, and no errors are reported.
+    pub fn inferred_requirements(&self, p: &Template) -> SchemaRequirements {
+        let mut inferrer = Inferrer {
+            requirements: SchemaRequirements::default(),
+            principal_type: None,
+            resource_type: None,
+            extensions: ExtensionSchemas::all_available(),
+        };
+        inferrer.principal_type = inferrer.scope(p.principal_constraint().as_inner());
+        inferrer.resource_type = inferrer.scope(p.resource_constraint().as_inner());
+        match p.action_constraint() {
+            ActionConstraint::Any => {}
+            ActionConstraint::Eq(action) => inferrer.use_entity(action),
+            ActionConstraint::In(actions) => {
+                for action in actions {
+                    inferrer.use_entity(action);
+                }
+            }
+            #[cfg(feature = "tolerant-ast")]
+            ActionConstraint::ErrorConstraint => {}
+        }
+        if let Some(conditions) = p.non_scope_constraints() {
+            inferrer.visit(conditions, RequiredType::Bool);
+        }
+        inferrer.requirements
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::EntityType;
+    use crate::parser;
+    use crate::validator::Validator;
+
+    use super::SchemaRequirements;
+
+    fn requirements(src: &str) -> SchemaRequirements {
+        let policies = parser::parse_policyset(src).unwrap();
+        let template = policies.all_templates().next().unwrap();
+        Validator::without_schema().inferred_requirements(template)
+    }
+
+    /// Attributes in `attrs` as `name: type` strings, with `?` after the name
+    /// of optional attributes
+    fn display<'a>(
+        attrs: impl Iterator<Item = (&'a smol_str::SmolStr, &'a super::AttributeRequirement)>,
+    ) -> Vec<String> {
+        attrs
+            .map(|(name, attr)| {
+                format!(
+                    "{name}{}: {}",
+                    if attr.is_optional() { "?" } else { "" },
+                    attr.ty().unwrap_or_else(|| "_".to_string())
+                )
+            })
+            .collect()
+    }
+
+    fn ety(name: &str) -> EntityType {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn entity_types_and_actions() {
+        let reqs = requirements(
+            r#"permit(principal in Group::"admins", action in [Action::"view", Action::"edit"], resource is Photo in Album::"trip")
+            when { context.owner == User::"alice" && resource is Video && action == Action::"share" };"#,
+        );
+        assert_eq!(
+            reqs.entity_types()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["Album", "Group", "Photo", "User", "Video"]
+        );
+        assert_eq!(
+            reqs.actions().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                r#"Action::"edit""#,
+                r#"Action::"share""#,
+                r#"Action::"view""#
+            ]
+        );
+    }
+
+    #[test]
+    fn attribute_types() {
+        let reqs = requirements(
+            r#"permit(principal == User::"alice", action, resource)
+            when {
+                principal.age >= 18 &&
+                principal.name like "a*" &&
+                principal.roles.contains("admin") &&
+                principal.address.city == "Seattle" &&
+                principal.manager in Group::"managers" &&
+                !principal.tags.isEmpty() &&
+                principal.ip.isInRange(ip("10.0.0.0/8")) &&
+                principal.score.lessThan(decimal("1.5")) &&
+                principal.flag &&
+                principal.other == principal.another
+            };"#,
+        );
+        assert_eq!(
+            display(reqs.entity_type_attributes(&ety("User"))),
+            vec![
+                "address: {city: String}",
+                "age: Long",
+                "another: _",
+                "flag: Bool",
+                "ip: ipaddr",
+                "manager: _",
+                "name: String",
+                "other: _",
+                "roles: Set<String>",
+                "score: decimal",
+                "tags: Set<_>",
+            ]
+        );
+        assert_eq!(display(reqs.principal_attributes()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn principal_resource_and_context_attributes() {
+        let reqs = requirements(
+            r#"permit(principal, action, resource in Folder::"root")
+            when { principal has department && principal.department == resource.department }
+            unless { context.authenticated == false || context has mfa && context.mfa.level < 2 };"#,
+        );
+        assert_eq!(display(reqs.principal_attributes()), vec!["department?: _"]);
+        assert_eq!(display(reqs.resource_attributes()), vec!["department: _"]);
+        assert_eq!(
+            display(reqs.context_attributes()),
+            vec!["authenticated: Bool", "mfa?: {level: Long}"]
+        );
+        assert_eq!(
+            display(reqs.entity_type_attributes(&ety("Folder"))),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn literal_and_template_attributes() {
+        let reqs = requirements(
+            r#"permit(principal == ?principal, action, resource is Doc)
+            when { resource.owner == User::"alice" && User::"alice".level > resource.level && [resource.tag] == ["x"] };"#,
+        );
+        assert_eq!(display(reqs.principal_attributes()), Vec::<String>::new());
+        assert_eq!(
+            display(reqs.entity_type_attributes(&ety("Doc"))),
+            vec!["level: Long", "owner: User", "tag: String"]
+        );
+        assert_eq!(
+            display(reqs.entity_type_attributes(&ety("User"))),
+            vec!["level: Long"]
+        );
+    }
+}
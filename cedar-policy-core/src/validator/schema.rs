@@ -28,6 +28,7 @@ use crate::{
     transitive_closure::compute_tc,
 };
 use educe::Educe;
+use itertools::Itertools;
 use namespace_def::EntityTypeFragment;
 use nonempty::NonEmpty;
 #[cfg(feature = "extended-schema")]
@@ -48,14 +49,20 @@ use crate::validator::{
 mod action;
 pub use action::ValidatorActionId;
 pub(crate) use action::ValidatorApplySpec;
+mod canonical_json;
+mod context_json_schema;
 mod entity_type;
 pub use entity_type::{ValidatorEntityType, ValidatorEntityTypeKind};
+mod json_location;
+mod limits;
+pub use limits::{SchemaLimit, SchemaLimits};
 mod namespace_def;
 pub(crate) use namespace_def::try_jsonschema_type_into_validator_type;
 pub use namespace_def::ValidatorNamespaceDef;
 mod raw_name;
 pub use raw_name::{ConditionalName, RawName, ReferenceType};
 pub(crate) mod err;
+mod unusable_declarations;
 use err::{schema_errors::*, *};
 
 /// A `ValidatorSchemaFragment` consists of any number (even 0) of
@@ -458,6 +465,30 @@ impl ValidatorSchema {
         }
     }
 
+    /// Returns an iterator over all the entity types that can be a descendant of `ty`
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`None`] if the `ty` is not found in the schema
+    pub fn descendants<'a>(
+        &'a self,
+        ty: &'a EntityType,
+    ) -> Option<impl Iterator<Item = &'a EntityType> + 'a> {
+        self.entity_types.get(ty).map(|ety| ety.descendants.iter())
+    }
+
+    /// Returns `true` if an entity of type `from` can be a member of an entity
+    /// of type `to`, directly or transitively, according to the
+    /// `memberOfTypes` declared in the schema. Returns `false` if either type
+    /// is not found in the schema.
+    pub fn hierarchy_path_exists(&self, from: &EntityType, to: &EntityType) -> bool {
+        self.entity_types.contains_key(from)
+            && self
+                .entity_types
+                .get(to)
+                .is_some_and(|ety| ety.has_descendant_entity_type(from))
+    }
+
     /// Returns an iterator over all the action groups defined in this schema
     pub fn action_groups(&self) -> impl Iterator<Item = &EntityUID> {
         self.action_ids.values().filter_map(|action| {
@@ -490,20 +521,48 @@ impl ValidatorSchema {
 
     /// Construct a [`ValidatorSchema`] from a JSON value in the appropriate
     /// shape.
+    ///
+    /// Errors report where in `json` they occurred; see
+    /// [`SchemaError::json_location()`].
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "taking the value by reference would be a breaking change to this public API"
+    )]
     pub fn from_json_value(json: serde_json::Value, extensions: &Extensions<'_>) -> Result<Self> {
         Self::from_schema_frag(
-            json_schema::Fragment::<RawName>::from_json_value(json)?,
+            json_schema::Fragment::<RawName>::from_json_value_ref(&json)?,
             extensions,
         )
+        .map_err(|err| err.with_json_location(&json, None))
+    }
+
+    /// Construct a [`ValidatorSchema`] from a JSON value in the appropriate
+    /// shape, failing with [`SchemaError::LimitExceeded`] if it exceeds any of
+    /// `limits`. The limits are checked before anything else is done with
+    /// `json`.
+    pub fn from_json_value_with_limits(
+        json: serde_json::Value,
+        extensions: &Extensions<'_>,
+        limits: &SchemaLimits,
+    ) -> Result<Self> {
+        limits::check_json(&json, limits)?;
+        Self::from_json_value(json, extensions)
     }
 
     /// Construct a [`ValidatorSchema`] from a string containing JSON in the
     /// appropriate shape.
+    ///
+    /// Errors report where in `json` they occurred, including the line and
+    /// column; see [`SchemaError::json_location()`].
     pub fn from_json_str(json: &str, extensions: &Extensions<'_>) -> Result<Self> {
         Self::from_schema_frag(
             json_schema::Fragment::<RawName>::from_json_str(json)?,
             extensions,
         )
+        .map_err(|err| match serde_json::from_str(json) {
+            Ok(value) => err.with_json_location(&value, Some(json)),
+            Err(_) => err,
+        })
     }
 
     /// Construct a [`ValidatorSchema`] directly from a file containing JSON
@@ -737,10 +796,14 @@ impl ValidatorSchema {
                                 attributes.0,
                                 extensions,
                                 &common_types,
-                            )?;
+                            )
+                            .map_err(|e| {
+                                e.in_declaration(TypeDeclaration::EntityTypeShape(name.clone()))
+                            })?;
                             Self::record_attributes_or_none(attrs_ty).ok_or_else(|| {
                                 ContextOrShapeNotRecordError {
                                     ctx_or_shape: ContextOrShape::EntityTypeShape(name.clone()),
+                                    json_location: None,
                                 }
                             })?
                         };
@@ -751,6 +814,9 @@ impl ValidatorSchema {
                                     extensions,
                                     &common_types,
                                 )
+                                .map_err(|e| {
+                                    e.in_declaration(TypeDeclaration::EntityTypeTags(name.clone()))
+                                })
                             })
                             .transpose()?;
 
@@ -788,18 +854,20 @@ impl ValidatorSchema {
                         action.context,
                         extensions,
                         &common_types,
-                    )?;
+                    )
+                    .map_err(|e| e.in_declaration(TypeDeclaration::ActionContext(name.clone())))?;
                     Self::record_attributes_or_none(context_ty).ok_or_else(|| {
                         ContextOrShapeNotRecordError {
                             ctx_or_shape: ContextOrShape::ActionContext(name.clone()),
+                            json_location: None,
                         }
                     })?
                 };
                 Ok((
                     name.clone(),
                     ValidatorActionId {
+                        applies_to: action.applies_to.with_action_as_resource(&name),
                         name,
-                        applies_to: action.applies_to,
                         descendants,
                         context: Type::record_with_attributes(context, open_context_attributes),
                         loc: action.loc,
@@ -901,14 +969,23 @@ impl ValidatorSchema {
                 }
             }
 
-            for r_entity in action.applies_to_resources() {
+            // The only resource type of an action with no resource is its
+            // own action entity type, which is always declared
+            for r_entity in action
+                .applies_to_resources()
+                .filter(|_| !action.has_no_resource())
+            {
                 if !entity_types.contains_key(r_entity) {
                     undeclared_e.insert(r_entity.clone());
                 }
             }
         }
         if let Some(types) = NonEmpty::collect(undeclared_e) {
-            return Err(UndeclaredEntityTypesError { types }.into());
+            return Err(UndeclaredEntityTypesError {
+                types,
+                json_location: None,
+            }
+            .into());
         }
         if let Some(euids) = NonEmpty::collect(undeclared_a) {
             // This should not happen, because undeclared actions should be caught
@@ -1276,37 +1353,43 @@ impl AllDefs {
     ///
     /// [RFC 70]: https://github.com/cedar-policy/rfcs/blob/main/text/0070-disallow-empty-namespace-shadowing.md
     pub fn rfc_70_shadowing_checks(&self) -> Result<()> {
-        for unqualified_name in self
-            .entity_and_common_names()
-            .filter(|name| name.is_unqualified())
-        {
+        // Definitions are checked in sorted order so that the reported error
+        // does not depend on the order in which they were declared
+        let names = self.entity_and_common_names().collect::<BTreeSet<_>>();
+        for unqualified_name in names.iter().filter(|name| name.is_unqualified()) {
             // `unqualified_name` is a definition in the empty namespace
-            if let Some(name) = self.entity_and_common_names().find(|name| {
+            let mut shadowing_defs = names.iter().filter(|name| {
                 !name.is_unqualified() // RFC 70 specifies that shadowing an entity typename with a common typename is OK, including in the empty namespace
                 && !name.is_reserved() // do not throw an error if the shadowing name is something like `__cedar::String` "shadowing" an empty-namespace declaration of `String`
                 && name.basename() == unqualified_name.basename()
-            }) {
+            });
+            if let Some(name) = shadowing_defs.next() {
                 return Err(TypeShadowingError {
-                    shadowed_def: unqualified_name.clone(),
-                    shadowing_def: name.clone(),
+                    shadowed_def: (*unqualified_name).clone(),
+                    shadowing_def: (*name).clone(),
+                    other_shadowing_defs: shadowing_defs.map(|name| (*name).clone()).collect(),
+                    json_location: None,
                 }
                 .into());
             }
         }
-        for unqualified_action in self
-            .action_defs
+        let actions = self.action_defs.iter().collect::<BTreeSet<_>>();
+        for unqualified_action in actions
             .iter()
             .filter(|euid| euid.entity_type().as_ref().is_unqualified())
         {
             // `unqualified_action` is a definition in the empty namespace
-            if let Some(action) = self.action_defs.iter().find(|euid| {
+            let mut shadowing_defs = actions.iter().filter(|euid| {
                 !euid.entity_type().as_ref().is_unqualified() // do not throw an error for an action "shadowing" itself
                 // we do not need to check that the basenames are the same, because we assume they are both `Action`
                 && euid.eid() == unqualified_action.eid()
-            }) {
+            });
+            if let Some(action) = shadowing_defs.next() {
                 return Err(ActionShadowingError {
-                    shadowed_def: unqualified_action.clone(),
-                    shadowing_def: action.clone(),
+                    shadowed_def: (*unqualified_action).clone(),
+                    shadowing_def: (*action).clone(),
+                    other_shadowing_defs: shadowing_defs.map(|action| (*action).clone()).collect(),
+                    json_location: None,
                 }
                 .into());
             }
@@ -1475,6 +1558,117 @@ impl<'a> CommonTypeResolver<'a> {
         }
     }
 
+    /// Find a cycle in the dependency graph, if there is one, and describe
+    /// it as the sequence of common types and the record attributes or set
+    /// elements through which each refers to the next. The cycle starts and
+    /// ends with the same common type.
+    ///
+    /// Common types are visited in sorted order so that the reported cycle
+    /// doesn't depend on declaration order. The search is iterative so that
+    /// long chains of references can't overflow the stack.
+    fn find_cycle(&self) -> Option<Vec<CommonTypeCycleStep>> {
+        // Use the names of the declarations rather than of the references, so
+        // that the reported cycle is located at a declaration
+        let sorted_deps = |name: &InternalName| {
+            let mut deps: Vec<&'a InternalName> = self
+                .graph
+                .get(name)
+                .into_iter()
+                .flatten()
+                .filter_map(|dep| self.graph.get_key_value(*dep).map(|(decl, _)| *decl))
+                .collect();
+            deps.sort();
+            deps.into_iter()
+        };
+        let mut roots: Vec<&'a InternalName> = self.graph.keys().copied().collect();
+        roots.sort();
+
+        let mut finished: HashSet<&'a InternalName> = HashSet::new();
+        for root in roots {
+            if finished.contains(root) {
+                continue;
+            }
+            // The current path from `root`, and for each type on it, the
+            // references we have yet to follow
+            let mut path = vec![root];
+            let mut on_path = HashSet::from([root]);
+            let mut pending = vec![sorted_deps(root)];
+            while let Some(deps) = pending.last_mut() {
+                match deps.next() {
+                    Some(dep) if on_path.contains(dep) => {
+                        let start = path.iter().position(|name| *name == dep)?;
+                        let mut cycle = path.split_off(start);
+                        cycle.push(dep);
+                        return Some(self.describe_cycle(&cycle));
+                    }
+                    Some(dep) if !finished.contains(dep) => {
+                        path.push(dep);
+                        on_path.insert(dep);
+                        pending.push(sorted_deps(dep));
+                    }
+                    Some(_) => (),
+                    None => {
+                        pending.pop();
+                        if let Some(name) = path.pop() {
+                            on_path.remove(name);
+                            finished.insert(name);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Describe the cycle through the common types `cycle`, in which each type
+    /// refers to the next
+    fn describe_cycle(&self, cycle: &[&InternalName]) -> Vec<CommonTypeCycleStep> {
+        let mut steps = Vec::new();
+        for (name, next) in cycle.iter().tuple_windows() {
+            steps.push(CommonTypeCycleStep::Type((*name).clone()));
+            if let Some(def) = self.defs.get(*name) {
+                steps.extend(Self::reference_path(def, next).unwrap_or_default());
+            }
+        }
+        if let Some(last) = cycle.last() {
+            steps.push(CommonTypeCycleStep::Type((*last).clone()));
+        }
+        steps
+    }
+
+    /// The record attributes and set elements leading from `ty` to its first
+    /// reference to the common type `target`, or `None` if `ty` doesn't
+    /// refer to `target`
+    fn reference_path(
+        ty: &json_schema::Type<InternalName>,
+        target: &InternalName,
+    ) -> Option<Vec<CommonTypeCycleStep>> {
+        match ty {
+            json_schema::Type::CommonTypeRef { type_name, .. }
+            | json_schema::Type::Type {
+                ty: json_schema::TypeVariant::EntityOrCommon { type_name },
+                ..
+            } => (type_name == target).then(Vec::new),
+            json_schema::Type::Type {
+                ty: json_schema::TypeVariant::Set { element },
+                ..
+            } => Self::reference_path(element, target).map(|mut path| {
+                path.insert(0, CommonTypeCycleStep::SetElement);
+                path
+            }),
+            json_schema::Type::Type {
+                ty: json_schema::TypeVariant::Record(json_schema::RecordType { attributes, .. }),
+                ..
+            } => attributes.iter().find_map(|(attr, attr_ty)| {
+                Self::reference_path(&attr_ty.ty, target).map(|mut path| {
+                    path.insert(0, CommonTypeCycleStep::Attribute(attr.clone()));
+                    path
+                })
+            }),
+            _ => None,
+        }
+    }
+
     // Substitute common type references in `ty` according to `resolve_table`.
     // Resolved types will still have the source loc of `ty`, unless `ty` is
     // exactly a common type reference, in which case they will have the source
@@ -1549,7 +1743,16 @@ impl<'a> CommonTypeResolver<'a> {
         extensions: &Extensions<'_>,
     ) -> Result<HashMap<&'a InternalName, LocatedType>> {
         let sorted_names = self.topo_sort().map_err(|n| {
-            SchemaError::CycleInCommonTypeReferences(CycleInCommonTypeReferencesError { ty: n })
+            let cycle = self.find_cycle().unwrap_or_default();
+            let ty = match cycle.first() {
+                Some(CommonTypeCycleStep::Type(ty)) => ty.clone(),
+                _ => n,
+            };
+            SchemaError::CycleInCommonTypeReferences(CycleInCommonTypeReferencesError {
+                ty,
+                cycle,
+                json_location: None,
+            })
         })?;
 
         let mut resolve_table: HashMap<&InternalName, json_schema::Type<InternalName>> =
@@ -1568,7 +1771,8 @@ impl<'a> CommonTypeResolver<'a> {
                 substituted_ty,
                 extensions,
                 &HashMap::new(),
-            )?;
+            )
+            .map_err(|e| e.in_declaration(TypeDeclaration::CommonType(name.clone())))?;
 
             tys.insert(name, validator_type);
         }
@@ -1989,7 +2193,7 @@ pub(crate) mod test {
         let schema: Result<ValidatorSchema> = schema_file.try_into();
         assert_matches!(
             schema,
-            Err(SchemaError::CycleInActionHierarchy(CycleInActionHierarchyError { uid: euid })) => {
+            Err(SchemaError::CycleInActionHierarchy(CycleInActionHierarchyError { uid: euid, .. })) => {
                 assert_eq!(euid, r#"Action::"view_photo""#.parse().unwrap());
             }
         )
@@ -2204,6 +2408,171 @@ pub(crate) mod test {
         TryInto::<ValidatorSchema>::try_into(schema_json).expect("Did not expect any errors.");
     }
 
+    /// Declaring an `Action` entity type in a namespace would make it
+    /// ambiguous whether `NS::Action::"view"` is an action or an entity of the
+    /// declared type
+    #[test]
+    fn cannot_declare_action_type_in_namespace() {
+        let src = "
+            namespace NS {
+                entity Action;
+                entity User;
+                action view appliesTo { principal: User, resource: User };
+            }
+        ";
+        assert_matches!(collect_warnings(ValidatorSchema::from_cedarschema_str(src, Extensions::all_available())), Err(CedarSchemaError::Schema(e)) => {
+            assert_matches!(&e, SchemaError::ActionEntityTypeDeclared(_));
+            expect_err(
+                src,
+                &miette::Report::new(e),
+                &ExpectedErrorMessageBuilder::error("entity type `NS::Action` declared in `entityTypes` list")
+                    .help("`NS::Action` is the implicitly declared type of the actions in this namespace; try renaming this entity type")
+                    .exactly_one_underline("entity Action;")
+                    .build(),
+            );
+        });
+    }
+
+    /// Names starting with `__` are reserved, so that future versions of Cedar
+    /// can introduce such names without changing the meaning of existing
+    /// schemas
+    #[test]
+    fn cannot_declare_reserved_prefix() {
+        for (src, kind, name, component, underline) in [
+            (
+                "namespace __NS { entity User; }",
+                "namespace",
+                "__NS",
+                "__NS",
+                None,
+            ),
+            (
+                "namespace NS::__internal::A { entity User; }",
+                "namespace",
+                "NS::__internal::A",
+                "__internal",
+                None,
+            ),
+            (
+                "namespace NS { entity __User; }",
+                "entity type",
+                "NS::__User",
+                "__User",
+                Some("entity __User;"),
+            ),
+            (
+                "type __T = String;",
+                "common type",
+                "__T",
+                "__T",
+                Some("type __T = String;"),
+            ),
+        ] {
+            assert_matches!(collect_warnings(ValidatorSchema::from_cedarschema_str(src, Extensions::all_available())), Err(CedarSchemaError::Schema(e)) => {
+                assert_matches!(&e, SchemaError::ReservedPrefix(_));
+                assert_eq!(
+                    e.to_string(),
+                    format!("the {kind} `{name}` uses the reserved name `{component}`")
+                );
+                if let Some(underline) = underline {
+                    expect_err(
+                        src,
+                        &miette::Report::new(e),
+                        &ExpectedErrorMessageBuilder::error(&format!("the {kind} `{name}` uses the reserved name `{component}`"))
+                            .help("names starting with `__` are reserved for use by Cedar")
+                            .exactly_one_underline(underline)
+                            .build(),
+                    );
+                }
+            });
+        }
+
+        // `__cedar` is still rejected as before, rather than by this check
+        let src = "namespace __cedar { entity User; }";
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                src,
+                Extensions::all_available()
+            )),
+            Err(e) => assert!(!matches!(e, CedarSchemaError::Schema(SchemaError::ReservedPrefix(_))))
+        );
+
+        // a single leading underscore is fine
+        assert_valid_cedar_schema("namespace _NS { entity _User; type _T = String; }");
+    }
+
+    #[test]
+    fn json_schema_error_locations() {
+        let json = json!({
+            "PhotoApp": {
+                "entityTypes": { "User": {}, "Photo": {} },
+                "actions": {
+                    "viewPhoto": {
+                        "appliesTo": {
+                            "principalTypes": ["User"],
+                            "resourceTypes": ["Photo", "User", "Album"]
+                        }
+                    }
+                }
+            }
+        });
+        let src = serde_json::to_string_pretty(&json).unwrap();
+        assert_matches!(ValidatorSchema::from_json_str(&src, Extensions::all_available()), Err(e @ SchemaError::TypeNotDefined(_)) => {
+            let location = e.json_location().expect("should have a location");
+            assert_eq!(
+                location.pointer(),
+                "/PhotoApp/actions/viewPhoto/appliesTo/resourceTypes/2"
+            );
+            assert_eq!((location.line(), location.column()), (Some(16), Some(13)));
+            assert_eq!(
+                location.to_string(),
+                "`/PhotoApp/actions/viewPhoto/appliesTo/resourceTypes/2` (line 16, column 13)"
+            );
+        });
+        assert_matches!(ValidatorSchema::from_json_value(json, Extensions::all_available()), Err(e @ SchemaError::TypeNotDefined(_)) => {
+            let location = e.json_location().expect("should have a location");
+            assert_eq!(
+                location.pointer(),
+                "/PhotoApp/actions/viewPhoto/appliesTo/resourceTypes/2"
+            );
+            assert_eq!((location.line(), location.column()), (None, None));
+        });
+
+        for (json, pointers) in [
+            (
+                json!({ "": { "entityTypes": {}, "actions": { "view": { "memberOf": [{ "id": "edit" }] } } } }),
+                vec!["//actions/view/memberOf/0"],
+            ),
+            (
+                json!({ "": { "commonTypes": { "A": { "type": "B" }, "B": { "type": "A" } }, "entityTypes": {}, "actions": {} } }),
+                vec!["//commonTypes/A", "//commonTypes/B"],
+            ),
+            (
+                json!({ "": { "commonTypes": { "MyLong": { "type": "Long" } }, "entityTypes": { "User": { "shape": { "type": "MyLong" } } }, "actions": {} } }),
+                vec!["//entityTypes/User/shape"],
+            ),
+        ] {
+            assert_matches!(ValidatorSchema::from_json_value(json, Extensions::all_available()), Err(e) => {
+                let location = e.json_location().expect("should have a location");
+                assert!(pointers.contains(&location.pointer()), "{location}");
+            });
+        }
+
+        // deserialization errors are located too
+        let src = r#"{"PhotoApp": {"actions": {"viewPhoto": {"#;
+        assert_matches!(ValidatorSchema::from_json_str(src, Extensions::all_available()), Err(e @ SchemaError::JsonDeserialization(_)) => {
+            let location = e.json_location().expect("should have a location");
+            assert_eq!(location.pointer(), "/PhotoApp/actions/viewPhoto");
+            assert_eq!(location.line(), Some(1));
+        });
+        let src = r#"{"PhotoApp": {"actions": {"viewPhoto": {"appliesto": {}}}}}"#;
+        assert_matches!(ValidatorSchema::from_json_str(src, Extensions::all_available()), Err(e @ SchemaError::JsonDeserialization(_)) => {
+            let location = e.json_location().expect("should have a location");
+            assert_eq!(location.pointer(), "/PhotoApp/actions/viewPhoto/appliesto");
+            assert!(location.line().is_some());
+        });
+    }
+
     #[test]
     fn cannot_declare_action_in_group_when_prohibited() {
         let schema_json = json_schema::Fragment::from_json_str(
@@ -3188,7 +3557,7 @@ pub(crate) mod test {
             expect_err(
                 &src,
                 &miette::Report::new(e),
-                &ExpectedErrorMessageBuilder::error("unknown extension type `ip`")
+                &ExpectedErrorMessageBuilder::error("unknown extension type `ip` in attribute `a` of entity type `User`")
                     .help("did you mean `ipaddr`?")
                     .build());
         });
@@ -3224,7 +3593,7 @@ pub(crate) mod test {
             expect_err(
                 &src,
                 &miette::Report::new(e),
-                &ExpectedErrorMessageBuilder::error("unknown extension type `deciml`")
+                &ExpectedErrorMessageBuilder::error(r#"unknown extension type `deciml` in attribute `a` of the context for action `Action::"A"`"#)
                     .help("did you mean `decimal`?")
                     .build());
         });
@@ -3251,7 +3620,7 @@ pub(crate) mod test {
             expect_err(
                 &src,
                 &miette::Report::new(e),
-                &ExpectedErrorMessageBuilder::error("unknown extension type `i`")
+                &ExpectedErrorMessageBuilder::error("unknown extension type `i` in attribute `a` of common type `ty`")
                     .help("did you mean `ipaddr`?")
                     .build());
         });
@@ -3279,11 +3648,67 @@ pub(crate) mod test {
                 expect_err(
                     &src,
                     &miette::Report::new(e),
-                    &ExpectedErrorMessageBuilder::error("unknown extension type `partial_evaluation`")
+                    &ExpectedErrorMessageBuilder::error("unknown extension type `partial_evaluation` in attribute `a` of common type `ty`")
                         .help("did you mean `duration`?")
                         .build());
             });
         }
+
+        let src: serde_json::Value = json!({
+            "": {
+                "entityTypes": {
+                    "User": {
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "addr": {
+                                    "type": "Record",
+                                    "attributes": {
+                                        "home": {
+                                            "type": "Extension",
+                                            "name": "ipadr",
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "actions": {}
+            }
+        });
+        let schema = ValidatorSchema::from_json_value(src.clone(), Extensions::all_available());
+        assert_matches!(schema, Err(e) => {
+            expect_err(
+                &src,
+                &miette::Report::new(e),
+                &ExpectedErrorMessageBuilder::error("unknown extension type `ipadr` in attribute `addr.home` of entity type `User`")
+                    .help("did you mean `ipaddr`?")
+                    .build());
+        });
+
+        let src: serde_json::Value = json!({
+            "": {
+                "entityTypes": {
+                    "User": {
+                        "tags": {
+                            "type": "Extension",
+                            "name": "decimall",
+                        }
+                    }
+                },
+                "actions": {}
+            }
+        });
+        let schema = ValidatorSchema::from_json_value(src.clone(), Extensions::all_available());
+        assert_matches!(schema, Err(e) => {
+            expect_err(
+                &src,
+                &miette::Report::new(e),
+                &ExpectedErrorMessageBuilder::error("unknown extension type `decimall` in the tags of entity type `User`")
+                    .help("did you mean `decimal`?")
+                    .build());
+        });
     }
 
     #[track_caller]
@@ -3896,7 +4321,8 @@ mod test_579; // located in separate file test_579.rs
 #[expect(clippy::cognitive_complexity, reason = "unit tests")]
 mod test_rfc70 {
     use super::test::utils::*;
-    use super::ValidatorSchema;
+    use super::{SchemaError, ValidatorSchema, ValidatorSchemaFragment};
+    use crate::validator::json_schema;
     use crate::validator::types::Type;
     use crate::{
         extensions::Extensions,
@@ -3921,7 +4347,7 @@ mod test_rfc70 {
                 &miette::Report::new(e),
                 &ExpectedErrorMessageBuilder::error("definition of `NS::T` illegally shadows the existing definition of `T`")
                     .help("try renaming one of the definitions, or moving `T` to a different namespace")
-                    .exactly_one_underline("type T = String;")
+                    .exactly_two_underlines("type T = String;", "type T = String;")
                     .build(),
             );
         });
@@ -3978,7 +4404,7 @@ mod test_rfc70 {
                 &miette::Report::new(e),
                 &ExpectedErrorMessageBuilder::error("definition of `NS::T` illegally shadows the existing definition of `T`")
                     .help("try renaming one of the definitions, or moving `T` to a different namespace")
-                    .exactly_one_underline("entity T { bar: String };")
+                    .exactly_two_underlines("entity T { bar: String };", "entity T in T { foo: String };")
                     .build(),
             );
         });
@@ -3996,7 +4422,7 @@ mod test_rfc70 {
                 &miette::Report::new(e),
                 &ExpectedErrorMessageBuilder::error("definition of `NS::T` illegally shadows the existing definition of `T`")
                     .help("try renaming one of the definitions, or moving `T` to a different namespace")
-                    .exactly_one_underline("entity T { bar: String };")
+                    .exactly_two_underlines("entity T { bar: String };", "entity T { foo: String };")
                     .build(),
             );
         });
@@ -4066,7 +4492,7 @@ mod test_rfc70 {
                 &miette::Report::new(e),
                 &ExpectedErrorMessageBuilder::error("definition of `NS::T` illegally shadows the existing definition of `T`")
                     .help("try renaming one of the definitions, or moving `T` to a different namespace")
-                    .exactly_one_underline("type T = String;")
+                    .exactly_two_underlines("type T = String;", "entity T in T { foo: String };")
                     .build(),
             );
         });
@@ -4131,7 +4557,7 @@ mod test_rfc70 {
                 &miette::Report::new(e),
                 &ExpectedErrorMessageBuilder::error("definition of `NS::T` illegally shadows the existing definition of `T`")
                     .help("try renaming one of the definitions, or moving `T` to a different namespace")
-                    .exactly_one_underline("entity T in T { foo: String };")
+                    .exactly_two_underlines("entity T in T { foo: String };", "type T = String;")
                     .build(),
             );
         });
@@ -4191,7 +4617,7 @@ mod test_rfc70 {
             let assertion = ExpectedErrorMessageBuilder::error("definition of `NS::Action::\"A\"` illegally shadows the existing definition of `Action::\"A\"`")
                 .help("try renaming one of the actions, or moving `Action::\"A\"` to a different namespace");
             #[cfg(feature = "extended-schema")]
-            let assertion = assertion.exactly_one_underline("A");
+            let assertion = assertion.exactly_two_underlines("A", "A");
 
             expect_err(
                 src,
@@ -4888,6 +5314,98 @@ mod test_rfc70 {
             assert_eq!(atype.attr_type.as_ref(), &Type::extension("decimal".parse().unwrap()));
         });
     }
+
+    /// When a definition in the empty namespace is shadowed in several
+    /// namespaces, the reported error does not depend on the declaration
+    /// order, and lists all of the shadowing definitions
+    #[test]
+    fn multiple_namespaces_conflict() {
+        for (first, second) in [("A", "B"), ("B", "A")] {
+            let src = format!(
+                "
+                entity T;
+                namespace {first} {{
+                    entity T {{ {first}: String }};
+                    entity User {{ t: T }};
+                }}
+                namespace {second} {{
+                    entity T {{ {second}: String }};
+                    entity User {{ t: T }};
+                }}
+                "
+            );
+            assert_matches!(collect_warnings(ValidatorSchema::from_cedarschema_str(&src, Extensions::all_available())), Err(e) => {
+                expect_err(
+                    src.as_str(),
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error("definition of `A::T` illegally shadows the existing definition of `T`")
+                        .help("`T` is also shadowed by `B::T`; try renaming one of the definitions, or moving `T` to a different namespace")
+                        .exactly_two_underlines("entity T { A: String };", "entity T;")
+                        .build(),
+                );
+            });
+        }
+    }
+
+    /// References to other nonempty namespaces must be qualified, and may
+    /// refer to definitions in a different schema fragment, declared either
+    /// before or after the reference
+    #[test]
+    fn cross_namespace_references() {
+        let fragment = |src: &str| {
+            let (fragment, _) =
+                json_schema::Fragment::from_cedarschema_str(src, Extensions::all_available())
+                    .unwrap();
+            ValidatorSchemaFragment::from_schema_fragment(fragment).unwrap()
+        };
+        let shared_lib = "
+            namespace SharedLib {
+                entity Collection in [PhotoApp::Album, Folder] { owner: Owner };
+                entity Folder;
+            }
+            type Owner = String;
+        ";
+        let photo_app = "
+            namespace PhotoApp {
+                entity Photo in [Album, SharedLib::Collection];
+                entity Album;
+            }
+        ";
+        for fragments in [
+            [fragment(shared_lib), fragment(photo_app)],
+            [fragment(photo_app), fragment(shared_lib)],
+        ] {
+            let schema =
+                ValidatorSchema::from_schema_fragments(fragments, Extensions::all_available())
+                    .unwrap();
+            let collection = "SharedLib::Collection".parse().unwrap();
+            let photo = "PhotoApp::Photo".parse().unwrap();
+            assert!(assert_entity_type_exists(&schema, "PhotoApp::Album")
+                .has_descendant_entity_type(&collection));
+            assert!(assert_entity_type_exists(&schema, "SharedLib::Folder")
+                .has_descendant_entity_type(&collection));
+            assert!(assert_entity_type_exists(&schema, "SharedLib::Collection")
+                .has_descendant_entity_type(&photo));
+            assert_matches!(assert_entity_type_exists(&schema, "SharedLib::Collection").attr("owner"), Some(atype) => {
+                assert_eq!(atype.attr_type.as_ref(), &Type::primitive_string());
+            });
+        }
+
+        // an unqualified reference does not resolve to a definition in a
+        // different nonempty namespace
+        let other = "
+            namespace Other {
+                entity Gallery in [Album];
+            }
+        ";
+        assert_matches!(
+            ValidatorSchema::from_schema_fragments(
+                [fragment(photo_app), fragment(other)],
+                Extensions::all_available()
+            ),
+            Err(SchemaError::TypeNotDefined(_))
+        );
+    }
 }
 
 /// Tests involving entity tags (RFC 82)
@@ -5040,8 +5558,10 @@ mod entity_tags {
 mod test_resolver {
     use std::collections::HashMap;
 
+    use crate::test_utils::{expect_err, ExpectedErrorMessageBuilder};
     use crate::{ast::InternalName, extensions::Extensions};
     use cool_asserts::assert_matches;
+    use serde_json::json;
 
     use super::{AllDefs, CommonTypeResolver, LocatedType};
     use crate::validator::{
@@ -5409,6 +5929,103 @@ mod test_resolver {
         let res = resolve(schema);
         assert_matches!(res, Err(SchemaError::CycleInCommonTypeReferences(_)));
     }
+
+    #[test]
+    fn cycle_path() {
+        let schema = serde_json::json!(
+            {
+                "": {
+                    "entityTypes": {},
+                    "actions": {},
+                    "commonTypes": {
+                        "a" : {
+                            "type": "Record",
+                            "attributes": {
+                                "w": { "type": "Long" },
+                                "x": { "type": "b" }
+                            }
+                        },
+                        "b" : {
+                            "type": "Set",
+                            "element": { "type": "a" }
+                        }
+                    }
+                }
+            }
+        );
+        assert_matches!(resolve(schema), Err(e @ SchemaError::CycleInCommonTypeReferences(_)) => {
+            expect_err(
+                "",
+                &miette::Report::new(e),
+                &ExpectedErrorMessageBuilder::error("cycle in common type references containing `a`")
+                    .help("the cycle is `a` -> attribute `x` -> `b` -> set element -> `a`")
+                    .build(),
+            );
+        });
+
+        // `a` refers to the cycle between `b` and `c` but is not part of it
+        let schema = serde_json::json!(
+            {
+                "": {
+                    "entityTypes": {},
+                    "actions": {},
+                    "commonTypes": {
+                        "a" : { "type": "b" },
+                        "b" : { "type": "c" },
+                        "c" : { "type": "b" }
+                    }
+                }
+            }
+        );
+        assert_matches!(resolve(schema), Err(e @ SchemaError::CycleInCommonTypeReferences(_)) => {
+            expect_err(
+                "",
+                &miette::Report::new(e),
+                &ExpectedErrorMessageBuilder::error("cycle in common type references containing `b`")
+                    .help("the cycle is `b` -> `c` -> `b`")
+                    .build(),
+            );
+        });
+    }
+
+    #[test]
+    fn deeply_nested_acyclic() {
+        // A long chain of common types, each a record referring to the next,
+        // plus many types reusing the same common type
+        const DEPTH: usize = 50;
+        let mut common_types = serde_json::Map::new();
+        for i in 0..DEPTH {
+            common_types.insert(
+                format!("t{i}"),
+                json!({
+                    "type": "Record",
+                    "attributes": { "next": { "type": format!("t{}", i + 1) } }
+                }),
+            );
+            common_types.insert(
+                format!("u{i}"),
+                json!({
+                    "type": "Record",
+                    "attributes": {
+                        "one": { "type": "shared" },
+                        "many": { "type": "Set", "element": { "type": "shared" } }
+                    }
+                }),
+            );
+        }
+        common_types.insert(format!("t{DEPTH}"), json!({ "type": "shared" }));
+        common_types.insert("shared".to_string(), json!({ "type": "Long" }));
+        let schema = json!({
+            "": {
+                "entityTypes": {},
+                "actions": {},
+                "commonTypes": common_types
+            }
+        });
+        assert_matches!(resolve(schema), Ok(tys) => {
+            assert_eq!(tys.len(), 2 * DEPTH + 2);
+        });
+    }
 }
 
 #[cfg(test)]
@@ -139,6 +139,13 @@ impl ValidatorActionId {
     pub fn is_applicable_resource_type(&self, ty: &ast::EntityType) -> bool {
         self.applies_to.is_applicable_resource_type(ty)
     }
+
+    /// Is this action declared with no resource? If so, the resource of a
+    /// request for the action is the action itself, and the only resource
+    /// type it applies to is the action's entity type.
+    pub fn has_no_resource(&self) -> bool {
+        self.applies_to.no_resource
+    }
 }
 
 impl TCNode<EntityUID> for ValidatorActionId {
@@ -179,12 +186,16 @@ pub(crate) struct ValidatorApplySpec<N> {
 
     /// The resource entity types the action can be applied to.
     resource_apply_spec: HashSet<N>,
+
+    /// Whether the action is declared with no resource
+    no_resource: bool,
 }
 
 impl<N: PartialEq + Eq + std::hash::Hash> PartialEq for ValidatorApplySpec<N> {
     fn eq(&self, other: &Self) -> bool {
         self.principal_apply_spec == other.principal_apply_spec
             && self.resource_apply_spec == other.resource_apply_spec
+            && self.no_resource == other.no_resource
     }
 }
 
@@ -197,6 +208,17 @@ impl<N> ValidatorApplySpec<N> {
         Self {
             principal_apply_spec,
             resource_apply_spec,
+            no_resource: false,
+        }
+    }
+
+    /// Create an apply spec for an action that involves no resource and can
+    /// be applied to some specific principals.
+    pub fn without_resource(principal_apply_spec: HashSet<N>) -> Self {
+        Self {
+            principal_apply_spec,
+            resource_apply_spec: HashSet::new(),
+            no_resource: true,
         }
     }
 }
@@ -221,6 +243,16 @@ impl ValidatorApplySpec<ast::EntityType> {
     pub fn applicable_resource_types(&self) -> impl Iterator<Item = &ast::EntityType> {
         self.resource_apply_spec.iter()
     }
+
+    /// If this spec is for an action with no resource, make the entity type
+    /// of `action` its only resource type, since the resource of a request
+    /// for the action is the action itself.
+    pub(crate) fn with_action_as_resource(mut self, action: &EntityUID) -> Self {
+        if self.no_resource {
+            self.resource_apply_spec = HashSet::from([action.entity_type().clone()]);
+        }
+        self
+    }
 }
 
 impl ValidatorApplySpec<ConditionalName> {
@@ -256,6 +288,7 @@ impl ValidatorApplySpec<ConditionalName> {
             (Ok(principal_apply_spec), Ok(resource_apply_spec)) => Ok(ValidatorApplySpec {
                 principal_apply_spec,
                 resource_apply_spec,
+                no_resource: self.no_resource,
             }),
             (Ok(_), Err(errs)) => Err(SchemaError::join_nonempty(errs)),
             (Err(resource_errs), Ok(_)) => Err(SchemaError::join_nonempty(resource_errs)),
@@ -285,6 +318,7 @@ mod test {
                     "App".parse().unwrap(),
                     "File".parse().unwrap(),
                 ]),
+                no_resource: false,
             },
             descendants: HashSet::new(),
             context: Type::any_record(),
@@ -0,0 +1,392 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Canonical JSON serialization of a [`ValidatorSchema`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde_json::{json, Map, Value};
+
+use crate::ast::{EntityType, EntityUID};
+use crate::validator::types::{Attributes, BoolType, EntityKind, OpenTag, Type};
+
+use super::{ValidatorEntityTypeKind, ValidatorSchema};
+
+impl ValidatorSchema {
+    /// Get a canonical serialization of this schema in the Cedar JSON schema
+    /// format. Two schemas produce the same output exactly when they are
+    /// equal, regardless of how their source was formatted or split into
+    /// fragments.
+    ///
+    /// The output is built from the resolved schema, so common types are
+    /// inlined, every entity type name is fully qualified, and every optional
+    /// field is written out. `memberOfTypes` and `memberOf` list all
+    /// ancestors, not only the direct parents. Object keys and lists other
+    /// than enumerated entity ids are sorted.
+    pub fn canonical_json(&self) -> Value {
+        let mut namespaces: BTreeMap<String, NamespaceJson> = BTreeMap::new();
+
+        let mut entity_types = self.entity_types().collect::<Vec<_>>();
+        entity_types.sort_by(|a, b| a.name().cmp(b.name()));
+        for ety in entity_types {
+            let def = match &ety.kind {
+                ValidatorEntityTypeKind::Standard(standard) => {
+                    let ancestors = self
+                        .ancestors(ety.name())
+                        .into_iter()
+                        .flatten()
+                        .map(ToString::to_string)
+                        .collect::<BTreeSet<_>>();
+                    let mut def = Map::new();
+                    def.insert("memberOfTypes".into(), json!(ancestors));
+                    def.insert(
+                        "shape".into(),
+                        record_json(ety.attributes(), standard.open_attributes),
+                    );
+                    if let Some(tags) = standard.tag_type() {
+                        def.insert("tags".into(), type_json(tags));
+                    }
+                    def
+                }
+                // Enumerated entity types cannot have parents
+                ValidatorEntityTypeKind::Enum(eids) => {
+                    let mut def = Map::new();
+                    def.insert(
+                        "enum".into(),
+                        json!(eids.iter().map(AsRef::as_ref).collect::<Vec<&str>>()),
+                    );
+                    def
+                }
+            };
+            let (namespace, basename) = split_name(ety.name());
+            namespaces
+                .entry(namespace)
+                .or_default()
+                .entity_types
+                .insert(basename, Value::Object(def));
+        }
+
+        let mut parents: HashMap<&EntityUID, BTreeSet<&EntityUID>> = HashMap::new();
+        for action in self.action_ids() {
+            for descendant in action.descendants() {
+                parents.entry(descendant).or_default().insert(action.name());
+            }
+        }
+        let mut actions = self.action_ids().collect::<Vec<_>>();
+        actions.sort_by(|a, b| a.name().cmp(b.name()));
+        for action in actions {
+            let member_of = parents
+                .get(action.name())
+                .into_iter()
+                .flatten()
+                .map(|parent| {
+                    json!({
+                        "id": parent.eid().as_ref(),
+                        "type": parent.entity_type().to_string(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let def = json!({
+                "appliesTo": {
+                    "context": type_json(action.context_type()),
+                    "principalTypes": sorted_names(action.principals()),
+                    "resourceTypes": sorted_names(action.resources()),
+                },
+                "memberOf": member_of,
+            });
+            let (namespace, _) = split_name(action.name().entity_type());
+            namespaces
+                .entry(namespace)
+                .or_default()
+                .actions
+                .insert(action.name().eid().as_ref().to_string(), def);
+        }
+
+        sort_keys(Value::Object(
+            namespaces
+                .into_iter()
+                .map(|(namespace, def)| {
+                    (
+                        namespace,
+                        json!({
+                            "actions": Value::Object(def.actions),
+                            "entityTypes": Value::Object(def.entity_types),
+                        }),
+                    )
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Entity type and action definitions of one namespace, keyed by basename and
+/// action id respectively
+#[derive(Debug, Default)]
+struct NamespaceJson {
+    entity_types: Map<String, Value>,
+    actions: Map<String, Value>,
+}
+
+/// Recursively sort the keys of every object in `value`. Lists are left in
+/// their original order.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, sort_keys(v)))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+/// Sort the names of `tys`
+fn sorted_names<'a>(tys: impl Iterator<Item = &'a EntityType>) -> BTreeSet<String> {
+    tys.map(ToString::to_string).collect()
+}
+
+/// Split an entity type name into its namespace (empty for the empty
+/// namespace) and its basename
+fn split_name(ety: &EntityType) -> (String, String) {
+    let name = ety.name();
+    (
+        name.as_ref().namespace(),
+        name.basename_as_ref().to_string(),
+    )
+}
+
+/// Convert a record type or entity shape into the JSON schema format, with
+/// every attribute's `required` flag written out
+fn record_json(attrs: &Attributes, open_attributes: OpenTag) -> Value {
+    let attributes = attrs
+        .iter()
+        .map(|(name, attr_ty)| {
+            let mut attr = match type_json(&attr_ty.attr_type) {
+                Value::Object(attr) => attr,
+                _ => Map::new(),
+            };
+            attr.insert("required".into(), attr_ty.is_required.into());
+            (name.to_string(), Value::Object(attr))
+        })
+        .collect::<Map<_, _>>();
+    json!({
+        "additionalAttributes": matches!(open_attributes, OpenTag::OpenAttributes),
+        "attributes": attributes,
+        "type": "Record",
+    })
+}
+
+/// Convert a validator type into the JSON schema format
+fn type_json(ty: &Type) -> Value {
+    match ty {
+        // Singleton boolean types never appear in a schema, but `Boolean` is
+        // the closest type which does
+        Type::Bool(BoolType::AnyBool | BoolType::True | BoolType::False) => {
+            json!({ "type": "Boolean" })
+        }
+        Type::Long => json!({ "type": "Long" }),
+        Type::String => json!({ "type": "String" }),
+        Type::Entity(EntityKind::Entity(lub)) => match lub.get_single_entity() {
+            Some(ety) => json!({
+                "name": ety.to_string(),
+                "type": "Entity",
+            }),
+            // A union of entity types cannot be written in a schema
+            None => Value::Null,
+        },
+        Type::Set {
+            element_type: Some(element_type),
+        } => json!({
+            "element": type_json(element_type),
+            "type": "Set",
+        }),
+        Type::Record {
+            attrs,
+            open_attributes,
+        } => record_json(attrs, *open_attributes),
+        Type::ExtensionType { name } => json!({
+            "name": name.to_string(),
+            "type": "Extension",
+        }),
+        // The remaining types cannot be written in a schema, so a schema never
+        // contains them
+        Type::Never | Type::Entity(EntityKind::AnyEntity) | Type::Set { element_type: None } => {
+            Value::Null
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::extensions::Extensions;
+    use crate::validator::ValidatorSchema;
+
+    fn from_json(json: serde_json::Value) -> ValidatorSchema {
+        ValidatorSchema::from_json_value(json, Extensions::all_available()).unwrap()
+    }
+
+    #[test]
+    fn differently_formatted_schemas() {
+        let first = from_json(json!({
+            "NS": {
+                "commonTypes": {
+                    "Name": { "type": "String" }
+                },
+                "entityTypes": {
+                    "Group": {},
+                    "User": {
+                        "memberOfTypes": ["Group"],
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "name": { "type": "Name" },
+                                "age": { "type": "Long", "required": false }
+                            }
+                        }
+                    }
+                },
+                "actions": {
+                    "view": {
+                        "appliesTo": {
+                            "principalTypes": ["User"],
+                            "resourceTypes": ["User", "Group"]
+                        }
+                    }
+                }
+            }
+        }));
+        let second = from_json(json!({
+            "NS": {
+                "actions": {
+                    "view": {
+                        "appliesTo": {
+                            "context": { "type": "Record", "attributes": {} },
+                            "resourceTypes": ["Group", "User"],
+                            "principalTypes": ["NS::User"]
+                        },
+                        "memberOf": []
+                    }
+                },
+                "entityTypes": {
+                    "User": {
+                        "shape": {
+                            "attributes": {
+                                "name": { "type": "String", "required": true },
+                                "age": { "required": false, "type": "Long" }
+                            },
+                            "type": "Record"
+                        },
+                        "memberOfTypes": ["NS::Group"]
+                    },
+                    "Group": { "memberOfTypes": [] }
+                }
+            }
+        }));
+        assert_eq!(first, second);
+        assert_eq!(
+            first.canonical_json().to_string(),
+            second.canonical_json().to_string()
+        );
+        assert_eq!(
+            first.canonical_json(),
+            json!({
+                "NS": {
+                    "actions": {
+                        "view": {
+                            "appliesTo": {
+                                "context": {
+                                    "additionalAttributes": false,
+                                    "attributes": {},
+                                    "type": "Record",
+                                },
+                                "principalTypes": ["NS::User"],
+                                "resourceTypes": ["NS::Group", "NS::User"],
+                            },
+                            "memberOf": [],
+                        }
+                    },
+                    "entityTypes": {
+                        "Group": {
+                            "memberOfTypes": [],
+                            "shape": {
+                                "additionalAttributes": false,
+                                "attributes": {},
+                                "type": "Record",
+                            },
+                        },
+                        "User": {
+                            "memberOfTypes": ["NS::Group"],
+                            "shape": {
+                                "additionalAttributes": false,
+                                "attributes": {
+                                    "age": { "required": false, "type": "Long" },
+                                    "name": { "required": true, "type": "String" },
+                                },
+                                "type": "Record",
+                            },
+                        },
+                    },
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn attribute_type_difference() {
+        let schema = |ty: &str| {
+            ValidatorSchema::from_cedarschema_str(
+                &format!("entity User {{ name: {ty} }};"),
+                Extensions::all_available(),
+            )
+            .unwrap()
+            .0
+        };
+        assert_ne!(schema("String"), schema("Long"));
+        assert_ne!(
+            schema("String").canonical_json(),
+            schema("Long").canonical_json()
+        );
+    }
+
+    #[test]
+    fn canonical_json_round_trips() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity Team;
+            entity User in [Team] { tags?: Set<String>, ip: ipaddr } tags Long;
+            entity Color enum ["red", "green"];
+            namespace App {
+                type Meta = { owner: User };
+                entity Doc { meta: Meta };
+                action read appliesTo { principal: User, resource: Doc, context: Meta };
+                action write in [read] appliesTo { principal: User, resource: Doc };
+            }
+            action ping;
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let canonical = schema.canonical_json();
+        let reparsed = from_json(canonical.clone());
+        assert_eq!(schema, reparsed);
+        assert_eq!(canonical, reparsed.canonical_json());
+    }
+}
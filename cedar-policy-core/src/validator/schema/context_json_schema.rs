@@ -0,0 +1,212 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Export of action context types as [JSON Schema](https://json-schema.org/)
+//! (draft 2020-12) documents.
+
+use serde_json::{json, Map, Value};
+
+use crate::ast::EntityUID;
+use crate::validator::types::{BoolType, EntityKind, OpenTag, Type};
+
+use super::ValidatorSchema;
+
+/// URI identifying the JSON Schema dialect of the generated documents
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl ValidatorSchema {
+    /// Get a JSON Schema (draft 2020-12) document describing the context of
+    /// requests for `action`, in the same JSON format accepted for contexts by
+    /// `Context::from_json_value`.
+    ///
+    /// Records become objects listing their required attributes in
+    /// `required`, sets become arrays, and entity references become objects
+    /// with string `type` and `id` properties. Extension values are expected
+    /// to be strings (the argument to the extension constructor) and are
+    /// annotated with a `format` naming the extension type, e.g., `"ipaddr"`
+    /// or `"decimal"`.
+    ///
+    /// Returns `None` if the action is not in the schema.
+    pub fn context_json_schema(&self, action: &EntityUID) -> Option<Value> {
+        let mut schema = match type_to_json_schema(self.context_type(action)?) {
+            Value::Object(schema) => schema,
+            _ => Map::new(),
+        };
+        let mut document = Map::new();
+        document.insert("$schema".into(), JSON_SCHEMA_DIALECT.into());
+        document.insert("title".into(), format!("Context for {action}").into());
+        document.append(&mut schema);
+        Some(Value::Object(document))
+    }
+}
+
+/// Convert a validator type into the equivalent JSON Schema
+fn type_to_json_schema(ty: &Type) -> Value {
+    match ty {
+        // No value has type `Never`, which is exactly the `false` schema
+        Type::Never => Value::Bool(false),
+        Type::Bool(BoolType::AnyBool) => json!({ "type": "boolean" }),
+        Type::Bool(BoolType::True) => json!({ "type": "boolean", "const": true }),
+        Type::Bool(BoolType::False) => json!({ "type": "boolean", "const": false }),
+        Type::Long => json!({
+            "type": "integer",
+            "minimum": i64::MIN,
+            "maximum": i64::MAX,
+        }),
+        Type::String => json!({ "type": "string" }),
+        Type::Entity(kind) => {
+            let entity_type = match kind {
+                EntityKind::AnyEntity => json!({ "type": "string" }),
+                EntityKind::Entity(lub) => json!({
+                    "type": "string",
+                    "enum": lub.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                }),
+            };
+            json!({
+                "type": "object",
+                "properties": {
+                    "type": entity_type,
+                    "id": { "type": "string" },
+                },
+                "required": ["type", "id"],
+                "additionalProperties": false,
+            })
+        }
+        Type::Set { element_type } => match element_type {
+            Some(element_type) => json!({
+                "type": "array",
+                "items": type_to_json_schema(element_type),
+            }),
+            // The only set with no element type is the empty set
+            None => json!({ "type": "array", "maxItems": 0 }),
+        },
+        Type::Record {
+            attrs,
+            open_attributes,
+        } => {
+            let properties = attrs
+                .iter()
+                .map(|(name, attr_ty)| (name.to_string(), type_to_json_schema(&attr_ty.attr_type)))
+                .collect::<Map<_, _>>();
+            let required = attrs
+                .iter()
+                .filter(|(_, attr_ty)| attr_ty.is_required)
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": matches!(open_attributes, OpenTag::OpenAttributes),
+            })
+        }
+        Type::ExtensionType { name } => json!({
+            "type": "string",
+            "format": name.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::extensions::Extensions;
+    use crate::validator::ValidatorSchema;
+
+    #[test]
+    fn context_json_schema() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User;
+            type Address = { street: String, zip?: String };
+            action view appliesTo {
+                principal: User,
+                resource: User,
+                context: {
+                    authenticated: Bool,
+                    level?: Long,
+                    source: ipaddr,
+                    owner: User,
+                    addresses: Set<Address>,
+                    home: Address,
+                }
+            };
+            action ping;
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let address = json!({
+            "type": "object",
+            "properties": {
+                "street": { "type": "string" },
+                "zip": { "type": "string" },
+            },
+            "required": ["street"],
+            "additionalProperties": false,
+        });
+        assert_eq!(
+            schema
+                .context_json_schema(&r#"Action::"view""#.parse().unwrap())
+                .unwrap(),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": r#"Context for Action::"view""#,
+                "type": "object",
+                "properties": {
+                    "addresses": { "type": "array", "items": address },
+                    "authenticated": { "type": "boolean" },
+                    "home": address,
+                    "level": {
+                        "type": "integer",
+                        "minimum": i64::MIN,
+                        "maximum": i64::MAX,
+                    },
+                    "owner": {
+                        "type": "object",
+                        "properties": {
+                            "type": { "type": "string", "enum": ["User"] },
+                            "id": { "type": "string" },
+                        },
+                        "required": ["type", "id"],
+                        "additionalProperties": false,
+                    },
+                    "source": { "type": "string", "format": "ipaddr" },
+                },
+                "required": ["addresses", "authenticated", "home", "owner", "source"],
+                "additionalProperties": false,
+            })
+        );
+        assert_eq!(
+            schema
+                .context_json_schema(&r#"Action::"ping""#.parse().unwrap())
+                .unwrap(),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": r#"Context for Action::"ping""#,
+                "type": "object",
+                "properties": {},
+                "required": [],
+                "additionalProperties": false,
+            })
+        );
+        assert_eq!(
+            schema.context_json_schema(&r#"Action::"edit""#.parse().unwrap()),
+            None
+        );
+    }
+}
@@ -21,10 +21,13 @@ use crate::{
 use itertools::{Either, Itertools};
 use miette::Diagnostic;
 use nonempty::NonEmpty;
+use smol_str::SmolStr;
 use thiserror::Error;
 
 use crate::validator::cedar_schema;
 
+use super::json_location;
+
 /// Error creating a schema from the Cedar syntax
 #[derive(Debug, Error, Diagnostic)]
 pub enum CedarSchemaError {
@@ -192,6 +195,10 @@ pub enum SchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     DuplicateAction(#[from] schema_errors::DuplicateActionError),
+    /// An action declared with `noResource` also lists resource types
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NoResourceWithResourceTypes(#[from] schema_errors::NoResourceWithResourceTypesError),
     /// Duplicate specification for a common type declaration
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -211,6 +218,11 @@ pub enum SchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ActionEntityTypeDeclared(#[from] schema_errors::ActionEntityTypeDeclaredError),
+    /// The schema declared a namespace, entity type, or common type with a
+    /// name component starting with `__`, which is reserved for Cedar.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ReservedPrefix(#[from] schema_errors::ReservedPrefixError),
     /// `context` or `shape` fields are not records
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -257,6 +269,16 @@ pub enum SchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ActionInvariantViolation(#[from] schema_errors::ActionInvariantViolationError),
+    /// The schema contains declarations which no policy can meaningfully
+    /// use, and these warnings were escalated to an error.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnusableDeclarations(#[from] schema_errors::UnusableDeclarationsError),
+    /// The schema exceeds one of the limits it was constructed with; see
+    /// [`crate::validator::SchemaLimits`].
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    LimitExceeded(#[from] schema_errors::SchemaLimitExceededError),
 }
 
 impl From<transitive_closure::TcError<EntityUID>> for SchemaError {
@@ -271,6 +293,7 @@ impl From<transitive_closure::TcError<EntityUID>> for SchemaError {
             transitive_closure::TcError::HasCycle(err) => {
                 schema_errors::CycleInActionHierarchyError {
                     uid: err.vertex_with_loop().clone(),
+                    json_location: None,
                 }
                 .into()
             }
@@ -314,6 +337,79 @@ impl SchemaError {
             }
         }
     }
+    /// Record that the unknown extension type reported by this error appears
+    /// in the type of the record attribute `attr`. Other errors are returned
+    /// unchanged.
+    pub(crate) fn in_attribute(self, attr: &SmolStr) -> Self {
+        match self {
+            Self::UnknownExtensionType(e) => {
+                Self::UnknownExtensionType(e.in_attribute(attr.clone()))
+            }
+            e => e,
+        }
+    }
+
+    /// Record the declaration containing the unknown extension type reported
+    /// by this error. Other errors are returned unchanged.
+    pub(crate) fn in_declaration(self, declaration: schema_errors::TypeDeclaration) -> Self {
+        match self {
+            Self::UnknownExtensionType(e) => {
+                Self::UnknownExtensionType(e.in_declaration(declaration))
+            }
+            e => e,
+        }
+    }
+
+    /// Location in the JSON schema of the value which this error is about,
+    /// e.g., the reference to an undeclared entity type. This is
+    /// available only when the schema was constructed from JSON, and includes
+    /// a line and column only when the JSON was parsed from text.
+    pub fn json_location(&self) -> Option<&schema_errors::JsonLocation> {
+        match self {
+            Self::JsonDeserialization(e) => e.location(),
+            Self::UndeclaredEntityTypes(e) => e.json_location.as_ref(),
+            Self::TypeNotDefined(e) => e.json_location.as_ref(),
+            Self::ActionNotDefined(e) => e.1.as_ref(),
+            Self::TypeShadowing(e) => e.json_location.as_ref(),
+            Self::ActionShadowing(e) => e.json_location.as_ref(),
+            Self::CycleInActionHierarchy(e) => e.json_location.as_ref(),
+            Self::CycleInCommonTypeReferences(e) => e.json_location.as_ref(),
+            Self::ActionEntityTypeDeclared(e) => e.json_location.as_ref(),
+            Self::ReservedPrefix(e) => e.json_location.as_ref(),
+            Self::ContextOrShapeNotRecord(e) => e.json_location.as_ref(),
+            Self::UnknownExtensionType(e) => e.json_location.as_ref(),
+            Self::LimitExceeded(e) => e.json_location.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Record where in the JSON schema `json` this error occurred, if we can
+    /// find it. `src` is the text `json` was parsed from, if any.
+    pub(crate) fn with_json_location(
+        mut self,
+        json: &serde_json::Value,
+        src: Option<&str>,
+    ) -> Self {
+        let Some(pointer) = json_location::locate(json, &self) else {
+            return self;
+        };
+        let location = Some(schema_errors::JsonLocation::new(pointer, src));
+        match &mut self {
+            Self::UndeclaredEntityTypes(e) => e.json_location = location,
+            Self::TypeNotDefined(e) => e.json_location = location,
+            Self::ActionNotDefined(e) => e.1 = location,
+            Self::TypeShadowing(e) => e.json_location = location,
+            Self::ActionShadowing(e) => e.json_location = location,
+            Self::CycleInActionHierarchy(e) => e.json_location = location,
+            Self::CycleInCommonTypeReferences(e) => e.json_location = location,
+            Self::ActionEntityTypeDeclared(e) => e.json_location = location,
+            Self::ReservedPrefix(e) => e.json_location = location,
+            Self::ContextOrShapeNotRecord(e) => e.json_location = location,
+            Self::UnknownExtensionType(e) => e.json_location = location,
+            _ => (),
+        }
+        self
+    }
 }
 
 impl From<NonEmpty<SchemaError>> for SchemaError {
@@ -347,9 +443,13 @@ pub mod schema_errors {
 
     use std::fmt::Display;
 
-    use crate::ast::{EntityType, EntityUID, InternalName, Name};
+    use crate::ast::{EntityType, EntityUID, Id, InternalName, Name};
+    use crate::fuzzy_match::fuzzy_search_limited;
     use crate::parser::{join_with_conjunction, Loc};
     use crate::transitive_closure;
+    use crate::validator::cedar_schema::SchemaWarning;
+    use crate::validator::schema::json_location;
+    use crate::validator::SchemaLimit;
     use itertools::Itertools;
     use miette::Diagnostic;
     use nonempty::NonEmpty;
@@ -398,6 +498,8 @@ pub mod schema_errors {
     pub struct UndeclaredEntityTypesError {
         /// Entity type(s) which were not declared
         pub(crate) types: NonEmpty<EntityType>,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
     }
 
     impl Display for UndeclaredEntityTypesError {
@@ -431,6 +533,8 @@ pub mod schema_errors {
     pub struct TypeNotDefinedError {
         /// Names of type(s) which were not defined
         pub(crate) undefined_types: NonEmpty<crate::validator::ConditionalName>,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
     }
 
     impl Diagnostic for TypeNotDefinedError {
@@ -452,6 +556,7 @@ pub mod schema_errors {
         pub(crate) fn join_nonempty(errs: NonEmpty<TypeNotDefinedError>) -> Self {
             Self {
                 undefined_types: errs.flat_map(|err| err.undefined_types),
+                json_location: None,
             }
         }
     }
@@ -473,6 +578,8 @@ pub mod schema_errors {
         pub(crate)  NonEmpty<
             crate::validator::json_schema::ActionEntityUID<crate::validator::ConditionalName>,
         >,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) Option<JsonLocation>,
     );
 
     impl ActionNotDefinedError {
@@ -481,7 +588,7 @@ pub mod schema_errors {
         /// This cannot fail, because `NonEmpty` guarantees there is at least
         /// one error to join.
         pub(crate) fn join_nonempty(errs: NonEmpty<ActionNotDefinedError>) -> Self {
-            Self(errs.flat_map(|err| err.0))
+            Self(errs.flat_map(|err| err.0), None)
         }
     }
 
@@ -523,19 +630,32 @@ pub mod schema_errors {
         pub(crate) shadowed_def: InternalName,
         /// Definition that is responsible for shadowing it illegally
         pub(crate) shadowing_def: InternalName,
+        /// Any other definitions, in other namespaces, which also shadow it
+        /// illegally. Unqualified references to the shadowed name would be
+        /// ambiguous in each of these namespaces.
+        pub(crate) other_shadowing_defs: Vec<InternalName>,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
     }
 
     impl Diagnostic for TypeShadowingError {
         fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
-            Some(Box::new(format!(
-                "try renaming one of the definitions, or moving `{}` to a different namespace",
-                self.shadowed_def
+            Some(Box::new(shadowing_help(
+                "definitions",
+                &self.shadowed_def,
+                &self.other_shadowing_defs,
             )))
         }
 
-        // we use the location of the `shadowing_def` as the location of the error
-        // possible future improvement: provide two underlines
-        impl_diagnostic_from_method_on_field!(shadowing_def, loc);
+        fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+            self.shadowing_def
+                .loc()
+                .map(|loc| &loc.src as &dyn miette::SourceCode)
+        }
+
+        fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+            shadowing_labels(self.shadowing_def.loc(), self.shadowed_def.loc())
+        }
     }
 
     /// Action shadowing error. Some shadowing relationships are not allowed for
@@ -554,19 +674,72 @@ pub mod schema_errors {
         pub(crate) shadowed_def: EntityUID,
         /// Definition that is responsible for shadowing it illegally
         pub(crate) shadowing_def: EntityUID,
+        /// Any other definitions, in other namespaces, which also shadow it
+        /// illegally
+        pub(crate) other_shadowing_defs: Vec<EntityUID>,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
     }
 
     impl Diagnostic for ActionShadowingError {
         fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
-            Some(Box::new(format!(
-                "try renaming one of the actions, or moving `{}` to a different namespace",
-                self.shadowed_def
+            Some(Box::new(shadowing_help(
+                "actions",
+                &self.shadowed_def,
+                &self.other_shadowing_defs,
             )))
         }
 
-        // we use the location of the `shadowing_def` as the location of the error
-        // possible future improvement: provide two underlines
-        impl_diagnostic_from_method_on_field!(shadowing_def, loc);
+        fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+            self.shadowing_def
+                .loc()
+                .map(|loc| &loc.src as &dyn miette::SourceCode)
+        }
+
+        fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+            shadowing_labels(self.shadowing_def.loc(), self.shadowed_def.loc())
+        }
+    }
+
+    /// Labels for shadowing errors: the shadowing definition, followed by the
+    /// shadowed definition if it is in the same source, e.g., the same schema
+    /// file
+    fn shadowing_labels(
+        shadowing_loc: Option<&Loc>,
+        shadowed_loc: Option<&Loc>,
+    ) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan>>> {
+        let shadowing_loc = shadowing_loc?;
+        let shadowed_span = shadowed_loc
+            .filter(|loc| loc.src == shadowing_loc.src)
+            .map(|loc| loc.span);
+        Some(Box::new(
+            std::iter::once(shadowing_loc.span)
+                .chain(shadowed_span)
+                .map(miette::LabeledSpan::underline),
+        ))
+    }
+
+    /// Help message for shadowing errors, listing the other shadowing
+    /// definitions, if any
+    fn shadowing_help(
+        kind: &str,
+        shadowed_def: &impl Display,
+        other_shadowing_defs: &[impl Display],
+    ) -> String {
+        let help = format!(
+            "try renaming one of the {kind}, or moving `{shadowed_def}` to a different namespace"
+        );
+        if other_shadowing_defs.is_empty() {
+            help
+        } else {
+            format!(
+                "`{shadowed_def}` is also shadowed by {}; {help}",
+                other_shadowing_defs
+                    .iter()
+                    .map(|def| format!("`{def}`"))
+                    .join(", ")
+            )
+        }
     }
 
     /// Duplicate entity type error
@@ -593,6 +766,21 @@ pub mod schema_errors {
     #[error("duplicate action `{0}`")]
     pub struct DuplicateActionError(pub(crate) SmolStr);
 
+    /// Error for an action declared with `noResource` which also lists
+    /// resource types
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("action `{action}` is declared with no resource, but lists resource types")]
+    #[diagnostic(help(
+        "remove the resource types, or remove `noResource` to apply the action to them"
+    ))]
+    pub struct NoResourceWithResourceTypesError {
+        pub(crate) action: SmolStr,
+    }
+
     /// Duplicate common type error
     //
     // CAUTION: this type is publicly exported in `cedar-policy`.
@@ -617,6 +805,8 @@ pub mod schema_errors {
     #[error("cycle in action hierarchy containing `{uid}`")]
     pub struct CycleInActionHierarchyError {
         pub(crate) uid: EntityUID,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
     }
 
     impl Diagnostic for CycleInActionHierarchyError {
@@ -632,20 +822,103 @@ pub mod schema_errors {
     #[error("cycle in common type references containing `{ty}`")]
     pub struct CycleInCommonTypeReferencesError {
         pub(crate) ty: InternalName,
+        /// The references making up the cycle, starting and ending with `ty`.
+        /// Empty if the cycle was not recorded.
+        pub(crate) cycle: Vec<CommonTypeCycleStep>,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
     }
 
     impl Diagnostic for CycleInCommonTypeReferencesError {
+        fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+            if self.cycle.is_empty() {
+                None
+            } else {
+                Some(Box::new(format!(
+                    "the cycle is {}",
+                    self.cycle.iter().join(" -> ")
+                )))
+            }
+        }
+
         impl_diagnostic_from_method_on_field!(ty, loc);
     }
 
+    /// One step along a cycle of common type references
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) enum CommonTypeCycleStep {
+        /// A common type
+        Type(InternalName),
+        /// The record attribute of this name
+        Attribute(SmolStr),
+        /// The element type of a set
+        SetElement,
+    }
+
+    impl Display for CommonTypeCycleStep {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Type(ty) => write!(f, "`{ty}`"),
+                Self::Attribute(attr) => write!(f, "attribute `{attr}`"),
+                Self::SetElement => write!(f, "set element"),
+            }
+        }
+    }
+
     /// Action declared in `entityType` list error
     //
     // CAUTION: this type is publicly exported in `cedar-policy`.
     // Don't make fields `pub`, don't make breaking changes, and use caution
     // when adding public methods.
-    #[derive(Debug, Clone, Diagnostic, Error)]
-    #[error("entity type `Action` declared in `entityTypes` list")]
-    pub struct ActionEntityTypeDeclaredError {}
+    #[derive(Debug, Clone, Error)]
+    #[error("entity type `{name}` declared in `entityTypes` list")]
+    pub struct ActionEntityTypeDeclaredError {
+        /// Fully qualified name of the declared entity type
+        pub(crate) name: InternalName,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
+    }
+
+    impl Diagnostic for ActionEntityTypeDeclaredError {
+        fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            Some(Box::new(format!(
+                "`{}` is the implicitly declared type of the actions in this namespace; try renaming this entity type",
+                self.name
+            )))
+        }
+
+        impl_diagnostic_from_method_on_field!(name, loc);
+    }
+
+    /// Error when a namespace, entity type, or common type has a name
+    /// component starting with `__`. Such names are reserved for Cedar; see
+    /// also [`crate::ast::ReservedNameError`] for `__cedar` specifically.
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Clone, Error)]
+    #[error("the {kind} `{name}` uses the reserved name `{component}`")]
+    pub struct ReservedPrefixError {
+        /// Fully qualified name of the declaration
+        pub(crate) name: InternalName,
+        /// The name component starting with `__`
+        pub(crate) component: Id,
+        /// What was declared, e.g., "entity type"
+        pub(crate) kind: &'static str,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
+    }
+
+    impl Diagnostic for ReservedPrefixError {
+        fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            Some(Box::new(
+                "names starting with `__` are reserved for use by Cedar",
+            ))
+        }
+
+        impl_diagnostic_from_method_on_field!(name, loc);
+    }
 
     /// Context or entity type shape not declared as record error
     //
@@ -656,6 +929,8 @@ pub mod schema_errors {
     #[error("{ctx_or_shape} is declared with a type other than `Record`")]
     pub struct ContextOrShapeNotRecordError {
         pub(crate) ctx_or_shape: ContextOrShape,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
     }
 
     impl Diagnostic for ContextOrShapeNotRecordError {
@@ -770,6 +1045,8 @@ pub mod schema_errors {
         err: serde_json::Error,
         /// Possible fix for the error
         advice: Option<JsonDeserializationAdvice>,
+        /// Location of the error in the JSON, if known
+        location: Option<JsonLocation>,
     }
 
     impl Diagnostic for JsonDeserializationError {
@@ -786,6 +1063,13 @@ pub mod schema_errors {
         CedarFormat,
         #[error("JSON formatted schema must specify a namespace. If you want to use the empty namespace, explicitly specify it with `{{ \"\": {{..}} }}`")]
         MissingNamespace,
+        #[error("unknown field is at `{pointer}`{}", .suggestion.as_ref().map(|s| format!("; did you mean `{s}`?")).unwrap_or_default())]
+        UnknownField {
+            /// JSON pointer (RFC 6901) to the unknown field
+            pointer: String,
+            /// Known field with a similar name, if any
+            suggestion: Option<String>,
+        },
     }
 
     impl JsonDeserializationError {
@@ -794,7 +1078,11 @@ pub mod schema_errors {
         /// `src`: the JSON that we were trying to deserialize (if available in string form)
         pub(crate) fn new(err: serde_json::Error, src: Option<&str>) -> Self {
             match src {
-                None => Self { err, advice: None },
+                None => Self {
+                    err,
+                    advice: None,
+                    location: None,
+                },
                 Some(src) => {
                     // let's see what the first non-whitespace character is
                     let advice = match src.trim_start().chars().next() {
@@ -823,10 +1111,272 @@ pub mod schema_errors {
                         }
                         Some(_) => Some(JsonDeserializationAdvice::CedarFormat), // any character other than '{', we suspect it might be a Cedar-format schema
                     };
-                    Self { err, advice }
+                    // `serde_json` reports line 0 if the position is unknown
+                    let location = (err.line() > 0).then(|| JsonLocation {
+                        pointer: json_location::pointer_at(src, err.line(), err.column())
+                            .unwrap_or_default(),
+                        line_column: Some((err.line(), err.column())),
+                    });
+                    Self {
+                        err,
+                        advice,
+                        location,
+                    }
                 }
             }
         }
+
+        /// If `err` reports an unknown field, add advice pointing at the
+        /// location of that field in `json`, the value we were trying to
+        /// deserialize, and use it as the location of the error. Advice
+        /// already attached to the error takes priority.
+        pub(crate) fn with_unknown_field_location(mut self, json: &serde_json::Value) -> Self {
+            let Some(advice) = unknown_field_advice(&self.err, json) else {
+                return self;
+            };
+            if let JsonDeserializationAdvice::UnknownField { pointer, .. } = &advice {
+                let line_column = self.location.as_ref().and_then(|loc| loc.line_column);
+                self.location = Some(JsonLocation {
+                    pointer: pointer.clone(),
+                    line_column,
+                });
+            }
+            if self.advice.is_none() {
+                self.advice = Some(advice);
+            }
+            self
+        }
+
+        /// Location of the error in the JSON, if known. The location includes
+        /// a line and column only if the JSON was deserialized from text.
+        pub fn location(&self) -> Option<&JsonLocation> {
+            self.location.as_ref()
+        }
+    }
+
+    /// Location of an error in a JSON schema
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct JsonLocation {
+        /// JSON pointer (RFC 6901) to the offending value
+        pub(crate) pointer: String,
+        /// Line and column of the offending value, if the JSON was parsed
+        /// from text
+        pub(crate) line_column: Option<(usize, usize)>,
+    }
+
+    impl JsonLocation {
+        /// Location of the value at `pointer`, with its line and column in the
+        /// JSON text `src` if available
+        pub(crate) fn new(pointer: String, src: Option<&str>) -> Self {
+            let line_column = src.and_then(|src| json_location::line_column(src, &pointer));
+            Self {
+                pointer,
+                line_column,
+            }
+        }
+
+        /// JSON pointer (RFC 6901) to the offending value, e.g.,
+        /// `/PhotoApp/actions/viewPhoto/appliesTo/resourceTypes/2`. The empty
+        /// pointer refers to the whole schema.
+        pub fn pointer(&self) -> &str {
+            &self.pointer
+        }
+
+        /// Line number, starting at 1, of the offending value, if the JSON was
+        /// parsed from text
+        pub fn line(&self) -> Option<usize> {
+            self.line_column.map(|(line, _)| line)
+        }
+
+        /// Column number, starting at 1, of the offending value, if the JSON
+        /// was parsed from text
+        pub fn column(&self) -> Option<usize> {
+            self.line_column.map(|(_, column)| column)
+        }
+    }
+
+    impl Display for JsonLocation {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match (self.pointer.as_str(), self.line_column) {
+                ("", None) => write!(f, "the top level of the schema"),
+                ("", Some((line, column))) => write!(f, "line {line}, column {column}"),
+                (pointer, None) => write!(f, "`{pointer}`"),
+                (pointer, Some((line, column))) => {
+                    write!(f, "`{pointer}` (line {line}, column {column})")
+                }
+            }
+        }
+    }
+
+    /// Maximum edit distance between an unknown field and a known field for
+    /// the latter to be suggested as a replacement
+    const UNKNOWN_FIELD_SUGGESTION_DISTANCE: usize = 2;
+
+    fn unknown_field_advice(
+        err: &serde_json::Error,
+        json: &serde_json::Value,
+    ) -> Option<JsonDeserializationAdvice> {
+        // The walk finds every unknown field in `json`; `err` is about the
+        // one `serde` reached first
+        let msg = err.to_string();
+        let unknown = unknown_fields::locate(json)
+            .into_iter()
+            .find(|unknown| msg.starts_with(&format!("unknown field `{}`", unknown.field)))?;
+        let suggestion = fuzzy_search_limited(
+            unknown.field,
+            unknown.expected,
+            Some(UNKNOWN_FIELD_SUGGESTION_DISTANCE),
+        );
+        Some(JsonDeserializationAdvice::UnknownField {
+            pointer: unknown.pointer,
+            suggestion,
+        })
+    }
+
+    /// Locating unknown fields in JSON schemas.
+    ///
+    /// `serde` reports the name of an unknown field but not where it occurs,
+    /// so we walk the JSON schema the same way it is deserialized, looking for
+    /// fields which are not allowed where they occur.
+    mod unknown_fields {
+        use crate::validator::schema::json_location;
+        use serde_json::{Map, Value};
+
+        /// The kind of object found at some position in a JSON schema
+        #[derive(Debug, Clone, Copy)]
+        enum Kind {
+            /// Map from namespace names to namespace definitions
+            Namespaces,
+            Namespace,
+            /// Map from common type names to types
+            CommonTypes,
+            /// Map from entity type names to entity type declarations
+            EntityTypes,
+            EntityType,
+            /// Map from action names to action declarations
+            Actions,
+            Action,
+            AppliesTo,
+            Type,
+            /// Map from attribute names to types
+            Attributes,
+        }
+
+        impl Kind {
+            /// The kind of object expected as the value of `key` in an object
+            /// of this kind, if it may contain fields we should check
+            fn child(self, key: &str) -> Option<Kind> {
+                match (self, key) {
+                    (Kind::Namespaces, _) => Some(Kind::Namespace),
+                    (Kind::Namespace, "commonTypes") => Some(Kind::CommonTypes),
+                    (Kind::Namespace, "entityTypes") => Some(Kind::EntityTypes),
+                    (Kind::Namespace, "actions") => Some(Kind::Actions),
+                    (Kind::EntityTypes, _) => Some(Kind::EntityType),
+                    (Kind::Actions, _) => Some(Kind::Action),
+                    (Kind::CommonTypes | Kind::Attributes, _)
+                    | (Kind::EntityType, "shape" | "tags")
+                    | (Kind::AppliesTo, "context")
+                    | (Kind::Type, "element") => Some(Kind::Type),
+                    (Kind::Action, "appliesTo") => Some(Kind::AppliesTo),
+                    (Kind::Type, "attributes") => Some(Kind::Attributes),
+                    _ => None,
+                }
+            }
+        }
+
+        /// A field in a JSON schema which is not allowed where it occurs
+        pub(super) struct UnknownField<'a> {
+            /// JSON pointer (RFC 6901) to the field
+            pub(super) pointer: String,
+            /// Name of the field
+            pub(super) field: &'a str,
+            /// The fields which are allowed where it occurs
+            pub(super) expected: &'static [&'static str],
+        }
+
+        /// Get every unknown field in `json`, in the order `serde` encounters
+        /// them
+        pub(super) fn locate(json: &Value) -> Vec<UnknownField<'_>> {
+            let mut found = Vec::new();
+            find(json, Kind::Namespaces, &mut Vec::new(), &mut found);
+            found
+        }
+
+        fn find<'a>(
+            json: &'a Value,
+            kind: Kind,
+            path: &mut Vec<&'a str>,
+            found: &mut Vec<UnknownField<'a>>,
+        ) {
+            let Value::Object(obj) = json else {
+                return;
+            };
+            let expected = known_fields(kind, obj);
+            for (key, value) in obj {
+                path.push(key);
+                if let Some(expected) =
+                    expected.filter(|expected| !expected.contains(&key.as_str()))
+                {
+                    found.push(UnknownField {
+                        pointer: json_location::pointer(path.iter().copied()),
+                        field: key,
+                        expected,
+                    });
+                }
+                // `serde` may not reject a field until it has deserialized
+                // its value, so keep looking inside unknown fields too
+                if let Some(child) = kind.child(key) {
+                    find(value, child, path, found);
+                }
+                path.pop();
+            }
+        }
+
+        /// The fields allowed in an object of kind `kind`, or `None` if its
+        /// keys are names rather than fields
+        fn known_fields(kind: Kind, obj: &Map<String, Value>) -> Option<&'static [&'static str]> {
+            match kind {
+                Kind::Namespaces
+                | Kind::CommonTypes
+                | Kind::EntityTypes
+                | Kind::Actions
+                | Kind::Attributes => None,
+                Kind::Namespace => Some(&["commonTypes", "entityTypes", "actions", "annotations"]),
+                Kind::EntityType => {
+                    Some(&["memberOfTypes", "shape", "tags", "enum", "annotations"])
+                }
+                Kind::Action => Some(&["attributes", "appliesTo", "memberOf", "annotations"]),
+                Kind::AppliesTo => Some(&["principalTypes", "resourceTypes", "context"]),
+                // Which fields are allowed in a type depends on its `type`
+                Kind::Type => Some(match obj.get("type").and_then(Value::as_str) {
+                    Some("Set") => &["type", "required", "annotations", "element"],
+                    Some("Record") => &[
+                        "type",
+                        "required",
+                        "annotations",
+                        "attributes",
+                        "additionalAttributes",
+                    ],
+                    Some("Entity" | "Extension" | "EntityOrCommon") => {
+                        &["type", "required", "annotations", "name"]
+                    }
+                    Some(_) => &["type", "required", "annotations"],
+                    None => &[
+                        "type",
+                        "required",
+                        "annotations",
+                        "element",
+                        "attributes",
+                        "additionalAttributes",
+                        "name",
+                    ],
+                }),
+            }
+        }
     }
 
     /// Unknown extension type error
@@ -835,10 +1385,34 @@ pub mod schema_errors {
     // Don't make fields `pub`, don't make breaking changes, and use caution
     // when adding public methods.
     #[derive(Error, Debug)]
-    #[error("unknown extension type `{actual}`")]
+    #[error("unknown extension type `{actual}`{}", declaration_path(.attribute_path, .declaration.as_ref()))]
     pub struct UnknownExtensionTypeError {
         pub(crate) actual: Name,
         pub(crate) suggested_replacement: Option<String>,
+        /// Source location of the type, if available
+        pub(crate) loc: Option<Loc>,
+        /// Path of record attributes, outermost first, leading to the type
+        /// within its declaration
+        pub(crate) attribute_path: Vec<SmolStr>,
+        /// The declaration containing the type, if known
+        pub(crate) declaration: Option<TypeDeclaration>,
+        /// Location in the JSON schema, if it was constructed from JSON
+        pub(crate) json_location: Option<JsonLocation>,
+    }
+
+    impl UnknownExtensionTypeError {
+        /// Record that the unknown extension type appears in the type of the
+        /// record attribute `attr`
+        pub(crate) fn in_attribute(mut self, attr: SmolStr) -> Self {
+            self.attribute_path.insert(0, attr);
+            self
+        }
+
+        /// Record the declaration containing the unknown extension type
+        pub(crate) fn in_declaration(mut self, declaration: TypeDeclaration) -> Self {
+            self.declaration = Some(declaration);
+            self
+        }
     }
 
     impl Diagnostic for UnknownExtensionTypeError {
@@ -848,7 +1422,63 @@ pub mod schema_errors {
             })
         }
 
-        impl_diagnostic_from_method_on_field!(actual, loc);
+        impl_diagnostic_from_source_loc_opt_field!(loc);
+    }
+
+    /// A schema declaration which contains a type
+    #[derive(Debug, Clone)]
+    pub(crate) enum TypeDeclaration {
+        /// The shape of an entity type
+        EntityTypeShape(EntityType),
+        /// The tags of an entity type
+        EntityTypeTags(EntityType),
+        /// The context of an action
+        ActionContext(EntityUID),
+        /// A common type
+        CommonType(InternalName),
+    }
+
+    impl std::fmt::Display for TypeDeclaration {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::EntityTypeShape(ety) => write!(f, "entity type `{ety}`"),
+                Self::EntityTypeTags(ety) => write!(f, "the tags of entity type `{ety}`"),
+                Self::ActionContext(action) => write!(f, "the context for action `{action}`"),
+                Self::CommonType(name) => write!(f, "common type `{name}`"),
+            }
+        }
+    }
+
+    /// Displays where a type appears in the schema, as a suffix for error
+    /// messages, e.g., `` in attribute `a.b` of entity type `User` ``
+    struct DeclarationPath<'a> {
+        attribute_path: &'a [SmolStr],
+        declaration: Option<&'a TypeDeclaration>,
+    }
+
+    fn declaration_path<'a>(
+        attribute_path: &'a [SmolStr],
+        declaration: Option<&'a TypeDeclaration>,
+    ) -> DeclarationPath<'a> {
+        DeclarationPath {
+            attribute_path,
+            declaration,
+        }
+    }
+
+    impl std::fmt::Display for DeclarationPath<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match (self.attribute_path, self.declaration) {
+                ([], None) => Ok(()),
+                ([], Some(declaration)) => write!(f, " in {declaration}"),
+                (path, None) => write!(f, " in attribute `{}`", path.iter().join(".")),
+                (path, Some(declaration)) => write!(
+                    f,
+                    " in attribute `{}` of {declaration}",
+                    path.iter().join(".")
+                ),
+            }
+        }
     }
 
     /// Could not find a definition for a common type, at a point in the code
@@ -892,4 +1522,71 @@ pub mod schema_errors {
 
         impl_diagnostic_from_method_on_nonempty_field!(euids, loc);
     }
+
+    /// Warnings about unusable declarations, reported as an error by
+    /// [`crate::validator::ValidatorSchema::deny_unusable_declarations`]
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Error, Debug)]
+    #[error("schema contains {} unusable declaration{}", .warnings.len(), if .warnings.len() > 1 { "s" } else { "" })]
+    pub struct UnusableDeclarationsError {
+        /// The warnings which were escalated to an error
+        pub(crate) warnings: NonEmpty<SchemaWarning>,
+    }
+
+    impl UnusableDeclarationsError {
+        /// The warnings which were escalated to an error
+        pub fn warnings(&self) -> impl Iterator<Item = &SchemaWarning> {
+            self.warnings.iter()
+        }
+    }
+
+    impl Diagnostic for UnusableDeclarationsError {
+        fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+            Some(Box::new(
+                self.warnings
+                    .iter()
+                    .map(|warning| warning as &dyn Diagnostic),
+            ))
+        }
+    }
+
+    /// Error when a schema exceeds one of the limits it was constructed with
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Clone, Diagnostic, Error)]
+    #[error("schema exceeds the limit of {max} on the {limit} at {declaration}")]
+    pub struct SchemaLimitExceededError {
+        /// The limit which was exceeded
+        pub(crate) limit: SchemaLimit,
+        /// The value of the limit
+        pub(crate) max: usize,
+        /// Description of the first declaration found over the limit, e.g.,
+        /// "entity type `NS::User`"
+        pub(crate) declaration: String,
+        /// Location in the JSON schema
+        pub(crate) json_location: Option<JsonLocation>,
+    }
+
+    impl SchemaLimitExceededError {
+        /// The limit which was exceeded
+        pub fn limit(&self) -> SchemaLimit {
+            self.limit
+        }
+
+        /// The value of the limit which was exceeded
+        pub fn max(&self) -> usize {
+            self.max
+        }
+
+        /// Description of the first declaration found over the limit, e.g.,
+        /// "entity type `NS::User`"
+        pub fn declaration(&self) -> &str {
+            &self.declaration
+        }
+    }
 }
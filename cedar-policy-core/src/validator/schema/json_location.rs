@@ -0,0 +1,531 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Locating schema errors in JSON schemas.
+//!
+//! JSON schemas carry no source locations, so errors found while constructing
+//! a schema from JSON are located after the fact: we search the JSON for the
+//! declaration or reference which the error is about and report its JSON
+//! pointer (RFC 6901). When we also have the text the JSON was parsed from, we
+//! scan it to translate between JSON pointers and lines and columns.
+
+use serde_json::{Map, Value};
+
+use crate::ast::EntityUID;
+
+use super::err::schema_errors::ContextOrShape;
+use super::err::SchemaError;
+
+/// Build a JSON pointer (RFC 6901) from the keys and indices leading to a value
+pub(super) fn pointer<S: AsRef<str>>(path: impl IntoIterator<Item = S>) -> String {
+    let mut pointer = String::new();
+    for key in path {
+        pointer.push('/');
+        pointer.push_str(&key.as_ref().replace('~', "~0").replace('/', "~1"));
+    }
+    pointer
+}
+
+/// Get the JSON pointer to the declaration or reference in the JSON schema
+/// `json` which `err` is about, if we can find it
+pub(super) fn locate(json: &Value, err: &SchemaError) -> Option<String> {
+    let namespaces = json.as_object()?;
+    match err {
+        SchemaError::UndeclaredEntityTypes(e) => {
+            let types = e.types.iter().map(ToString::to_string).collect::<Vec<_>>();
+            find_reference(namespaces, |ns, reference| match reference {
+                Reference::EntityType(name) | Reference::Type(name) => {
+                    types.iter().any(|ty| qualifies(ns, name, ty))
+                }
+                _ => false,
+            })
+        }
+        SchemaError::TypeNotDefined(e) => {
+            let names = e
+                .undefined_types
+                .iter()
+                .map(|name| name.raw().to_string())
+                .collect::<Vec<_>>();
+            find_reference(namespaces, |_, reference| match reference {
+                Reference::EntityType(name) | Reference::Type(name) => {
+                    names.iter().any(|n| n == name)
+                }
+                _ => false,
+            })
+        }
+        SchemaError::ActionNotDefined(e) => find_reference(
+            namespaces,
+            |_, reference| matches!(reference, Reference::Action(id) if e.0.iter().any(|uid| uid.id == id)),
+        ),
+        SchemaError::UnknownExtensionType(e) => {
+            let actual = e.actual.to_string();
+            find_reference(namespaces, |_, reference| match reference {
+                Reference::Extension(name) | Reference::Type(name) => name == actual,
+                _ => false,
+            })
+        }
+        SchemaError::TypeShadowing(e) => {
+            let name = e.shadowing_def.to_string();
+            find_declaration(namespaces, "entityTypes", &name)
+                .or_else(|| find_declaration(namespaces, "commonTypes", &name))
+        }
+        SchemaError::ActionShadowing(e) => find_action(namespaces, &e.shadowing_def),
+        SchemaError::CycleInActionHierarchy(e) => find_action(namespaces, &e.uid),
+        SchemaError::CycleInCommonTypeReferences(e) => {
+            find_declaration(namespaces, "commonTypes", &e.ty.to_string())
+        }
+        SchemaError::ActionEntityTypeDeclared(e) => {
+            find_declaration(namespaces, "entityTypes", &e.name.to_string())
+        }
+        SchemaError::ReservedPrefix(e) => {
+            let name = e.name.to_string();
+            match e.kind {
+                "namespace" => namespaces
+                    .keys()
+                    .find(|ns| **ns == name)
+                    .map(|ns| pointer([ns])),
+                "entity type" => find_declaration(namespaces, "entityTypes", &name),
+                _ => find_declaration(namespaces, "commonTypes", &name),
+            }
+        }
+        SchemaError::ContextOrShapeNotRecord(e) => match &e.ctx_or_shape {
+            ContextOrShape::ActionContext(uid) => {
+                find_action(namespaces, uid).map(|action| format!("{action}/appliesTo/context"))
+            }
+            ContextOrShape::EntityTypeShape(ty) => {
+                find_declaration(namespaces, "entityTypes", &ty.to_string())
+                    .map(|ety| format!("{ety}/shape"))
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Does `name`, written in the namespace `ns`, refer to the fully qualified
+/// name `qualified`?
+fn qualifies(ns: &str, name: &str, qualified: &str) -> bool {
+    name == qualified
+        || (!ns.is_empty()
+            && qualified
+                .strip_prefix(ns)
+                .and_then(|rest| rest.strip_prefix("::"))
+                == Some(name))
+}
+
+/// Find the declaration of the fully qualified `name` in the `section`
+/// (`entityTypes` or `commonTypes`) of any namespace
+fn find_declaration(namespaces: &Map<String, Value>, section: &str, name: &str) -> Option<String> {
+    namespaces.iter().find_map(|(ns, nsdef)| {
+        nsdef
+            .get(section)?
+            .as_object()?
+            .keys()
+            .find(|decl| qualifies(ns, decl, name))
+            .map(|decl| pointer([ns.as_str(), section, decl.as_str()]))
+    })
+}
+
+/// Find the declaration of the action `uid`
+fn find_action(namespaces: &Map<String, Value>, uid: &EntityUID) -> Option<String> {
+    let action_type = uid.entity_type().to_string();
+    namespaces.iter().find_map(|(ns, nsdef)| {
+        if !qualifies(ns, "Action", &action_type) {
+            return None;
+        }
+        nsdef
+            .get("actions")?
+            .as_object()?
+            .keys()
+            .find(|id| id.as_str() == uid.eid().as_ref())
+            .map(|id| pointer([ns.as_str(), "actions", id.as_str()]))
+    })
+}
+
+/// A reference to another declaration in a JSON schema
+#[derive(Debug, Clone, Copy)]
+enum Reference<'a> {
+    /// Entity type in `memberOfTypes`, `principalTypes`, or `resourceTypes`,
+    /// or in an `Entity` type
+    EntityType(&'a str),
+    /// Common or entity type, either as the `type` of a type or in an
+    /// `EntityOrCommon` type
+    Type(&'a str),
+    /// Extension type in an `Extension` type
+    Extension(&'a str),
+    /// Id of an action in `memberOf`
+    Action(&'a str),
+}
+
+/// Find the first reference in the JSON schema for which `pred` holds, given
+/// the namespace it appears in
+fn find_reference(
+    namespaces: &Map<String, Value>,
+    pred: impl Fn(&str, Reference<'_>) -> bool,
+) -> Option<String> {
+    let mut search = ReferenceSearch {
+        pred: &pred,
+        ns: "",
+        path: Vec::new(),
+    };
+    for (ns, nsdef) in namespaces {
+        search.ns = ns.as_str();
+        search.path = vec![ns.clone()];
+        if search.namespace(nsdef) {
+            return Some(pointer(&search.path));
+        }
+    }
+    None
+}
+
+/// State of [`find_reference`]. Each method returns `true` if it found a
+/// matching reference, leaving `path` pointing at it.
+struct ReferenceSearch<'a, 'p> {
+    pred: &'p dyn Fn(&str, Reference<'_>) -> bool,
+    ns: &'a str,
+    path: Vec<String>,
+}
+
+impl ReferenceSearch<'_, '_> {
+    /// Search below the value at `keys`, relative to the current path
+    fn child(&mut self, keys: &[&str], f: impl FnOnce(&mut Self) -> bool) -> bool {
+        let len = self.path.len();
+        self.path.extend(keys.iter().map(|key| key.to_string()));
+        if f(self) {
+            return true;
+        }
+        self.path.truncate(len);
+        false
+    }
+
+    fn check(&self, reference: Reference<'_>) -> bool {
+        (self.pred)(self.ns, reference)
+    }
+
+    fn namespace(&mut self, nsdef: &Value) -> bool {
+        let section = |key| {
+            nsdef
+                .get(key)
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+        };
+        for (name, ety) in section("entityTypes") {
+            if self.child(&["entityTypes", name.as_str()], |s| s.entity_type(ety)) {
+                return true;
+            }
+        }
+        for (name, action) in section("actions") {
+            if self.child(&["actions", name.as_str()], |s| s.action(action)) {
+                return true;
+            }
+        }
+        for (name, ty) in section("commonTypes") {
+            if self.child(&["commonTypes", name.as_str()], |s| s.ty(ty)) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn entity_type(&mut self, ety: &Value) -> bool {
+        self.names("memberOfTypes", ety, Reference::EntityType)
+            || ["shape", "tags"].into_iter().any(|key| {
+                ety.get(key)
+                    .is_some_and(|ty| self.child(&[key], |s| s.ty(ty)))
+            })
+    }
+
+    fn action(&mut self, action: &Value) -> bool {
+        let member_of = action.get("memberOf").and_then(Value::as_array);
+        for (i, parent) in member_of.into_iter().flatten().enumerate() {
+            let found = parent
+                .get("id")
+                .and_then(Value::as_str)
+                .is_some_and(|id| self.check(Reference::Action(id)));
+            if found {
+                self.path.extend(["memberOf".into(), i.to_string()]);
+                return true;
+            }
+        }
+        let Some(applies_to) = action.get("appliesTo") else {
+            return false;
+        };
+        self.child(&["appliesTo"], |s| {
+            s.names("principalTypes", applies_to, Reference::EntityType)
+                || s.names("resourceTypes", applies_to, Reference::EntityType)
+                || applies_to
+                    .get("context")
+                    .is_some_and(|ty| s.child(&["context"], |s| s.ty(ty)))
+        })
+    }
+
+    /// Check the names in the array `obj[key]`
+    fn names<'v>(
+        &mut self,
+        key: &str,
+        obj: &'v Value,
+        reference: fn(&'v str) -> Reference<'v>,
+    ) -> bool {
+        let names = obj.get(key).and_then(Value::as_array);
+        for (i, name) in names.into_iter().flatten().enumerate() {
+            if name
+                .as_str()
+                .is_some_and(|name| self.check(reference(name)))
+            {
+                self.path.extend([key.to_string(), i.to_string()]);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn ty(&mut self, ty: &Value) -> bool {
+        let name = || ty.get("name").and_then(Value::as_str);
+        let found = match ty.get("type").and_then(Value::as_str) {
+            Some("Set") => {
+                return ty
+                    .get("element")
+                    .is_some_and(|elem| self.child(&["element"], |s| s.ty(elem)))
+            }
+            Some("Record") => {
+                let attrs = ty.get("attributes").and_then(Value::as_object);
+                return attrs.into_iter().flatten().any(|(attr, attr_ty)| {
+                    self.child(&["attributes", attr.as_str()], |s| s.ty(attr_ty))
+                });
+            }
+            Some("Entity") => name().is_some_and(|n| self.check(Reference::EntityType(n))),
+            Some("Extension") => name().is_some_and(|n| self.check(Reference::Extension(n))),
+            Some("EntityOrCommon") => name().is_some_and(|n| self.check(Reference::Type(n))),
+            Some(n) => {
+                if self.check(Reference::Type(n)) {
+                    self.path.push("type".into());
+                    return true;
+                }
+                false
+            }
+            None => false,
+        };
+        if found {
+            self.path.push("name".into());
+        }
+        found
+    }
+}
+
+/// Line and column (both 1-based, with columns counted in bytes like
+/// `serde_json` does) of the start of the value at `pointer` in the JSON text
+/// `src`
+pub(super) fn line_column(src: &str, pointer: &str) -> Option<(usize, usize)> {
+    let start = scan(src)
+        .into_iter()
+        .find(|span| span.pointer == pointer)?
+        .start;
+    let before = src.get(..start)?;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Some((before.matches('\n').count() + 1, start - line_start + 1))
+}
+
+/// JSON pointer to the innermost value containing, or ending just before, the
+/// character at `line` and `column` (both 1-based, as reported by
+/// `serde_json`, which often reports the position just after the offending
+/// value) in the JSON text `src`. The text need not be valid JSON: values
+/// which were not closed before a syntax error are considered to extend to
+/// the end of the text.
+pub(super) fn pointer_at(src: &str, line: usize, column: usize) -> Option<String> {
+    let line_start = if line <= 1 {
+        0
+    } else {
+        src.match_indices('\n').nth(line - 2)?.0 + 1
+    };
+    let offset = line_start + column.saturating_sub(1);
+    scan(src)
+        .into_iter()
+        .filter(|span| span.start <= offset && span.end.is_none_or(|end| offset <= end))
+        .max_by_key(|span| span.start)
+        .map(|span| span.pointer)
+}
+
+/// Byte range of a JSON value in the text it was parsed from
+#[derive(Debug)]
+struct ValueSpan {
+    pointer: String,
+    start: usize,
+    /// `None` if the value was not closed because of a syntax error
+    end: Option<usize>,
+}
+
+/// Get the spans of all values in the JSON text `src`, stopping at the first
+/// syntax error
+fn scan(src: &str) -> Vec<ValueSpan> {
+    let mut scanner = Scanner {
+        src: src.as_bytes(),
+        text: src,
+        pos: 0,
+        spans: Vec::new(),
+    };
+    scanner.value("");
+    scanner.spans
+}
+
+/// Minimal JSON scanner which records where each value starts and ends. Each
+/// method returns `None` on a syntax error.
+struct Scanner<'a> {
+    src: &'a [u8],
+    text: &'a str,
+    pos: usize,
+    spans: Vec<ValueSpan>,
+}
+
+impl Scanner<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.src.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+        self.src.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.peek()? == byte).then(|| self.pos += 1)
+    }
+
+    fn value(&mut self, path: &str) -> Option<()> {
+        let start_byte = self.peek()?;
+        let index = self.spans.len();
+        self.spans.push(ValueSpan {
+            pointer: path.to_string(),
+            start: self.pos,
+            end: None,
+        });
+        match start_byte {
+            b'{' => {
+                self.pos += 1;
+                if self.peek()? == b'}' {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        let key = self.string()?;
+                        self.expect(b':')?;
+                        self.value(&format!("{path}{}", pointer([key])))?;
+                        match self.peek()? {
+                            b',' => self.pos += 1,
+                            b'}' => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return None,
+                        }
+                    }
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                if self.peek()? == b']' {
+                    self.pos += 1;
+                } else {
+                    for i in 0.. {
+                        self.value(&format!("{path}/{i}"))?;
+                        match self.peek()? {
+                            b',' => self.pos += 1,
+                            b']' => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return None,
+                        }
+                    }
+                }
+            }
+            b'"' => {
+                self.string()?;
+            }
+            _ => {
+                let len = self
+                    .src
+                    .get(self.pos..)?
+                    .iter()
+                    .take_while(|&&b| !b.is_ascii_whitespace() && !b",:]}".contains(&b))
+                    .count();
+                if len == 0 {
+                    return None;
+                }
+                self.pos += len;
+            }
+        }
+        if let Some(span) = self.spans.get_mut(index) {
+            span.end = Some(self.pos);
+        }
+        Some(())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let start = self.pos - 1;
+        let mut escaped = false;
+        loop {
+            let byte = *self.src.get(self.pos)?;
+            self.pos += 1;
+            match byte {
+                b'\\' if !escaped => escaped = true,
+                b'"' if !escaped => break,
+                _ => escaped = false,
+            }
+        }
+        serde_json::from_str(self.text.get(start..self.pos)?).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{line_column, pointer_at};
+
+    const SRC: &str = r#"{
+    "": {
+        "entityTypes": { "a/b": {} },
+        "actions": {
+            "view": { "appliesTo": { "resourceTypes": ["A", "B"] } }
+        }
+    }
+}"#;
+
+    #[test]
+    fn pointer_to_line_column() {
+        assert_eq!(line_column(SRC, ""), Some((1, 1)));
+        assert_eq!(line_column(SRC, "/~1entityTypes"), None);
+        assert_eq!(line_column(SRC, "//entityTypes/a~1b"), Some((3, 33)));
+        assert_eq!(
+            line_column(SRC, "//actions/view/appliesTo/resourceTypes/1"),
+            Some((5, 61))
+        );
+    }
+
+    #[test]
+    fn line_column_to_pointer() {
+        assert_eq!(
+            pointer_at(SRC, 5, 62).as_deref(),
+            Some("//actions/view/appliesTo/resourceTypes/1")
+        );
+        assert_eq!(
+            pointer_at(SRC, 5, 55).as_deref(),
+            Some("//actions/view/appliesTo/resourceTypes")
+        );
+        assert_eq!(pointer_at(SRC, 3, 9).as_deref(), Some("/"));
+        // values which were not closed extend to the end of the text
+        assert_eq!(
+            pointer_at(r#"{ "": { "actions": { "#, 1, 22).as_deref(),
+            Some("//actions")
+        );
+    }
+}
@@ -0,0 +1,475 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Resource limits enforced while constructing a schema from JSON.
+//!
+//! The limits are checked against the JSON value itself, before it is
+//! deserialized, so that a pathological schema is rejected before we build
+//! any of the structures describing it.
+
+use std::fmt::Display;
+
+use serde_json::{Map, Value};
+
+use super::err::schema_errors::{JsonLocation, SchemaLimitExceededError};
+use super::json_location::pointer;
+
+/// Limits on the size of a schema, checked by
+/// [`super::ValidatorSchema::from_json_value_with_limits`].
+///
+/// The default limits are generous enough for any realistic schema.
+/// [`SchemaLimits::unlimited`] disables all of the limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaLimits {
+    max_entity_types: usize,
+    max_actions: usize,
+    max_attributes_per_record: usize,
+    max_type_depth: usize,
+    max_declarations: usize,
+}
+
+impl Default for SchemaLimits {
+    fn default() -> Self {
+        Self {
+            max_entity_types: 10_000,
+            max_actions: 10_000,
+            max_attributes_per_record: 10_000,
+            max_type_depth: 64,
+            max_declarations: 50_000,
+        }
+    }
+}
+
+impl SchemaLimits {
+    /// No limits at all
+    pub fn unlimited() -> Self {
+        Self {
+            max_entity_types: usize::MAX,
+            max_actions: usize::MAX,
+            max_attributes_per_record: usize::MAX,
+            max_type_depth: usize::MAX,
+            max_declarations: usize::MAX,
+        }
+    }
+
+    /// Set the maximum number of entity types, across all namespaces
+    pub fn with_max_entity_types(mut self, max: usize) -> Self {
+        self.max_entity_types = max;
+        self
+    }
+
+    /// Set the maximum number of actions, across all namespaces
+    pub fn with_max_actions(mut self, max: usize) -> Self {
+        self.max_actions = max;
+        self
+    }
+
+    /// Set the maximum number of attributes in a single record type or entity
+    /// shape
+    pub fn with_max_attributes_per_record(mut self, max: usize) -> Self {
+        self.max_attributes_per_record = max;
+        self
+    }
+
+    /// Set the maximum nesting depth of a type, where a type which is not a
+    /// record or set has depth 1. Each type is measured as written, so a
+    /// reference to a common type counts as depth 1.
+    pub fn with_max_type_depth(mut self, max: usize) -> Self {
+        self.max_type_depth = max;
+        self
+    }
+
+    /// Set the maximum number of entity type, action, and common type
+    /// declarations combined, across all namespaces
+    pub fn with_max_declarations(mut self, max: usize) -> Self {
+        self.max_declarations = max;
+        self
+    }
+}
+
+/// A limit in [`SchemaLimits`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchemaLimit {
+    /// [`SchemaLimits::with_max_entity_types`]
+    EntityTypes,
+    /// [`SchemaLimits::with_max_actions`]
+    Actions,
+    /// [`SchemaLimits::with_max_attributes_per_record`]
+    AttributesPerRecord,
+    /// [`SchemaLimits::with_max_type_depth`]
+    TypeDepth,
+    /// [`SchemaLimits::with_max_declarations`]
+    Declarations,
+}
+
+impl Display for SchemaLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EntityTypes => write!(f, "number of entity types"),
+            Self::Actions => write!(f, "number of actions"),
+            Self::AttributesPerRecord => write!(f, "number of attributes in a record"),
+            Self::TypeDepth => write!(f, "type nesting depth"),
+            Self::Declarations => write!(f, "number of declarations"),
+        }
+    }
+}
+
+/// Check that the JSON schema `json` is within `limits`. Anything other than
+/// the expected shape of a JSON schema is ignored here, and left to be
+/// reported when `json` is deserialized.
+pub(super) fn check_json(
+    json: &Value,
+    limits: &SchemaLimits,
+) -> Result<(), SchemaLimitExceededError> {
+    let Some(namespaces) = json.as_object() else {
+        return Ok(());
+    };
+    let section = |nsdef: &'_ Value, key: &str| -> usize {
+        nsdef
+            .get(key)
+            .and_then(Value::as_object)
+            .map_or(0, Map::len)
+    };
+
+    // Count the declarations first, which is cheap
+    let (mut entity_types, mut actions, mut declarations) = (0_usize, 0_usize, 0_usize);
+    for (ns, nsdef) in namespaces {
+        for (key, count, limit, max) in [
+            (
+                "entityTypes",
+                &mut entity_types,
+                SchemaLimit::EntityTypes,
+                limits.max_entity_types,
+            ),
+            (
+                "actions",
+                &mut actions,
+                SchemaLimit::Actions,
+                limits.max_actions,
+            ),
+        ] {
+            let len = section(nsdef, key);
+            let room = max.saturating_sub(*count);
+            if len > room {
+                return Err(exceeded(limit, max, ns, key, nsdef, room));
+            }
+            *count += len;
+        }
+        for key in ["entityTypes", "actions", "commonTypes"] {
+            let len = section(nsdef, key);
+            let room = limits.max_declarations.saturating_sub(declarations);
+            if len > room {
+                return Err(exceeded(
+                    SchemaLimit::Declarations,
+                    limits.max_declarations,
+                    ns,
+                    key,
+                    nsdef,
+                    room,
+                ));
+            }
+            declarations += len;
+        }
+    }
+
+    // Then check the types in every declaration
+    for (ns, nsdef) in namespaces {
+        let declarations_in = |key: &'static str| {
+            nsdef
+                .get(key)
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+                .map(move |(name, decl)| (key, name, decl))
+        };
+        for (key, name, decl) in declarations_in("commonTypes")
+            .chain(declarations_in("entityTypes"))
+            .chain(declarations_in("actions"))
+        {
+            let types: Vec<(&Value, Vec<&str>)> = match key {
+                "commonTypes" => vec![(decl, vec![])],
+                "entityTypes" => ["shape", "tags"]
+                    .into_iter()
+                    .filter_map(|field| Some((decl.get(field)?, vec![field])))
+                    .collect(),
+                _ => decl
+                    .get("appliesTo")
+                    .and_then(|applies_to| applies_to.get("context"))
+                    .map(|context| (context, vec!["appliesTo", "context"]))
+                    .into_iter()
+                    .collect(),
+            };
+            for (ty, path) in types {
+                let mut checker = TypeChecker {
+                    limits,
+                    path: [ns.as_str(), key, name.as_str()]
+                        .into_iter()
+                        .chain(path)
+                        .map(ToString::to_string)
+                        .collect(),
+                };
+                if let Some((limit, max)) = checker.check(ty, 1) {
+                    return Err(SchemaLimitExceededError {
+                        limit,
+                        max,
+                        declaration: describe(ns, key, name),
+                        json_location: Some(JsonLocation::new(pointer(&checker.path), None)),
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Build the error for the declaration at index `index` of section `key` of
+/// namespace `ns`, which is the first declaration over `limit`
+fn exceeded(
+    limit: SchemaLimit,
+    max: usize,
+    ns: &str,
+    key: &str,
+    nsdef: &Value,
+    index: usize,
+) -> SchemaLimitExceededError {
+    let name = nsdef
+        .get(key)
+        .and_then(Value::as_object)
+        .and_then(|decls| decls.keys().nth(index))
+        .map_or("", String::as_str);
+    SchemaLimitExceededError {
+        limit,
+        max,
+        declaration: describe(ns, key, name),
+        json_location: Some(JsonLocation::new(pointer([ns, key, name]), None)),
+    }
+}
+
+/// Describe the declaration `name` in section `key` of namespace `ns`
+fn describe(ns: &str, key: &str, name: &str) -> String {
+    let qualified = |name: &str| {
+        if ns.is_empty() {
+            name.to_string()
+        } else {
+            format!("{ns}::{name}")
+        }
+    };
+    match key {
+        "entityTypes" => format!("entity type `{}`", qualified(name)),
+        "commonTypes" => format!("common type `{}`", qualified(name)),
+        _ => format!(
+            "action `{}::\"{}\"`",
+            qualified("Action"),
+            name.escape_debug()
+        ),
+    }
+}
+
+/// Walks a JSON type, tracking the path to the current type
+struct TypeChecker<'a> {
+    limits: &'a SchemaLimits,
+    path: Vec<String>,
+}
+
+impl TypeChecker<'_> {
+    /// Check the type `ty` at depth `depth`, returning the exceeded limit if
+    /// any. On error, `self.path` is left pointing to the offending type.
+    fn check(&mut self, ty: &Value, depth: usize) -> Option<(SchemaLimit, usize)> {
+        if !ty.is_object() {
+            return None;
+        }
+        if depth > self.limits.max_type_depth {
+            return Some((SchemaLimit::TypeDepth, self.limits.max_type_depth));
+        }
+        match ty.get("type").and_then(Value::as_str) {
+            Some("Record") => {
+                let attrs = ty.get("attributes").and_then(Value::as_object)?;
+                self.path.push("attributes".into());
+                if attrs.len() > self.limits.max_attributes_per_record {
+                    return Some((
+                        SchemaLimit::AttributesPerRecord,
+                        self.limits.max_attributes_per_record,
+                    ));
+                }
+                for (attr, attr_ty) in attrs {
+                    self.path.push(attr.clone());
+                    if let Some(exceeded) = self.check(attr_ty, depth + 1) {
+                        return Some(exceeded);
+                    }
+                    self.path.pop();
+                }
+                self.path.pop();
+                None
+            }
+            Some("Set") => {
+                let element = ty.get("element")?;
+                self.path.push("element".into());
+                let exceeded = self.check(element, depth + 1);
+                if exceeded.is_none() {
+                    self.path.pop();
+                }
+                exceeded
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cool_asserts::assert_matches;
+    use serde_json::{json, Value};
+
+    use super::{SchemaLimit, SchemaLimits};
+    use crate::extensions::Extensions;
+    use crate::validator::{SchemaError, ValidatorSchema};
+
+    fn check(json: Value, limits: SchemaLimits) -> Result<ValidatorSchema, SchemaError> {
+        ValidatorSchema::from_json_value_with_limits(json, Extensions::all_available(), &limits)
+    }
+
+    fn nested_record(depth: usize) -> Value {
+        (1..depth).fold(
+            json!({ "type": "Long" }),
+            |ty, _| json!({ "type": "Record", "attributes": { "a": ty } }),
+        )
+    }
+
+    #[test]
+    fn within_limits() {
+        let json = json!({
+            "NS": {
+                "commonTypes": { "T": nested_record(3) },
+                "entityTypes": { "User": { "shape": nested_record(3) } },
+                "actions": { "view": {} }
+            }
+        });
+        assert_matches!(check(json.clone(), SchemaLimits::default()), Ok(_));
+        assert_matches!(
+            check(
+                json,
+                SchemaLimits::default()
+                    .with_max_entity_types(1)
+                    .with_max_actions(1)
+                    .with_max_attributes_per_record(1)
+                    .with_max_type_depth(3)
+                    .with_max_declarations(3)
+            ),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn too_many_entity_types() {
+        let json = json!({
+            "A": { "entityTypes": { "X": {} }, "actions": {} },
+            "B": { "entityTypes": { "Y": {}, "Z": {} }, "actions": {} }
+        });
+        assert_matches!(
+            check(json, SchemaLimits::default().with_max_entity_types(2)),
+            Err(SchemaError::LimitExceeded(e)) => {
+                assert_eq!(e.limit(), SchemaLimit::EntityTypes);
+                assert_eq!(e.max(), 2);
+                assert_eq!(e.declaration(), "entity type `B::Z`");
+                assert_eq!(e.json_location.as_ref().unwrap().pointer(), "/B/entityTypes/Z");
+                assert_eq!(
+                    e.to_string(),
+                    "schema exceeds the limit of 2 on the number of entity types at entity type `B::Z`"
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn too_many_actions_and_declarations() {
+        let json = json!({
+            "": {
+                "commonTypes": { "T": { "type": "Long" } },
+                "entityTypes": {},
+                "actions": { "a": {}, "b": {} }
+            }
+        });
+        assert_matches!(
+            check(json.clone(), SchemaLimits::default().with_max_actions(1)),
+            Err(SchemaError::LimitExceeded(e)) => {
+                assert_eq!(e.limit(), SchemaLimit::Actions);
+                assert_eq!(e.declaration(), r#"action `Action::"b"`"#);
+            }
+        );
+        assert_matches!(
+            check(json, SchemaLimits::default().with_max_declarations(2)),
+            Err(SchemaError::LimitExceeded(e)) => {
+                assert_eq!(e.limit(), SchemaLimit::Declarations);
+                assert_eq!(e.declaration(), "common type `T`");
+            }
+        );
+    }
+
+    #[test]
+    fn too_many_attributes() {
+        let attributes = (0..5)
+            .map(|i| (format!("a{i}"), json!({ "type": "Long" })))
+            .collect::<serde_json::Map<_, _>>();
+        let json = json!({
+            "": {
+                "entityTypes": {},
+                "actions": {
+                    "view": {
+                        "appliesTo": {
+                            "context": { "type": "Record", "attributes": attributes }
+                        }
+                    }
+                }
+            }
+        });
+        assert_matches!(
+            check(json, SchemaLimits::default().with_max_attributes_per_record(4)),
+            Err(SchemaError::LimitExceeded(e)) => {
+                assert_eq!(e.limit(), SchemaLimit::AttributesPerRecord);
+                assert_eq!(e.declaration(), r#"action `Action::"view"`"#);
+                assert_eq!(
+                    e.json_location.as_ref().unwrap().pointer(),
+                    "//actions/view/appliesTo/context/attributes"
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn too_deep() {
+        let json = json!({
+            "NS": {
+                "entityTypes": {},
+                "actions": {},
+                "commonTypes": {
+                    "T": { "type": "Set", "element": nested_record(100) }
+                }
+            }
+        });
+        assert_matches!(
+            check(json.clone(), SchemaLimits::default()),
+            Err(SchemaError::LimitExceeded(e)) => {
+                assert_eq!(e.limit(), SchemaLimit::TypeDepth);
+                assert_eq!(e.max(), 64);
+                assert_eq!(e.declaration(), "common type `NS::T`");
+                let pointer = e.json_location.as_ref().unwrap().pointer();
+                assert!(pointer.starts_with("/NS/commonTypes/T/element/attributes/a/"));
+                assert_eq!(pointer.matches("/attributes/a").count(), 63);
+            }
+        );
+        assert_matches!(check(json, SchemaLimits::unlimited()), Ok(_));
+    }
+}
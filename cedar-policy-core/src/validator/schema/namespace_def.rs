@@ -22,7 +22,7 @@ use std::collections::{hash_map::Entry, HashMap, HashSet};
 use crate::ast::Eid;
 use crate::parser::Loc;
 use crate::{
-    ast::{EntityType, EntityUID, InternalName, Name, UnreservedId},
+    ast::{EntityType, EntityUID, Id, InternalName, Name, UnreservedId},
     extensions::Extensions,
     fuzzy_match::fuzzy_search,
 };
@@ -127,7 +127,8 @@ impl ValidatorNamespaceDef<ConditionalName, ConditionalName> {
     {
         // Return early with an error if actions cannot be in groups or have
         // attributes, but the schema contains action groups or attributes.
-        Self::check_action_restrictions(&namespace_def)?;
+        Self::check_action_restrictions(namespace.as_ref(), &namespace_def)?;
+        Self::check_reserved_prefixes(namespace.as_ref(), &namespace_def)?;
 
         // Convert the common types, actions and entity types from the schema
         // file into the representation used by the validator.
@@ -231,17 +232,23 @@ impl ValidatorNamespaceDef<ConditionalName, ConditionalName> {
     /// Check that `schema_nsdef` does not declare an `Action` entity type or
     /// declare `attributes` for any actions.
     fn check_action_restrictions<N>(
+        schema_namespace: Option<&InternalName>,
         schema_nsdef: &json_schema::NamespaceDefinition<N>,
     ) -> crate::validator::err::Result<()> {
-        if schema_nsdef
+        if let Some((id, entity_type)) = schema_nsdef
             .entity_types
             .iter()
             // The `name` in an entity type declaration cannot be qualified
             // with a namespace (it always implicitly takes the schema
             // namespace), so we do this comparison directly.
-            .any(|(name, _)| name.to_smolstr() == crate::ast::ACTION_ENTITY_TYPE)
+            .find(|(name, _)| name.to_smolstr() == crate::ast::ACTION_ENTITY_TYPE)
         {
-            return Err(ActionEntityTypeDeclaredError {}.into());
+            return Err(ActionEntityTypeDeclaredError {
+                name: RawName::new_from_unreserved(id.clone(), entity_type.loc.clone())
+                    .qualify_with(schema_namespace),
+                json_location: None,
+            }
+            .into());
         }
         let mut actions_with_attributes: Vec<String> = Vec::new();
         for (name, a) in &schema_nsdef.actions {
@@ -261,6 +268,54 @@ impl ValidatorNamespaceDef<ConditionalName, ConditionalName> {
 
         Ok(())
     }
+
+    /// Check that neither the namespace nor any entity type or common type
+    /// declared in `schema_nsdef` has a name component starting with `__`.
+    /// Names containing `__cedar` are reported as
+    /// [`crate::ast::ReservedNameError`]s elsewhere.
+    fn check_reserved_prefixes<N>(
+        schema_namespace: Option<&InternalName>,
+        schema_nsdef: &json_schema::NamespaceDefinition<N>,
+    ) -> crate::validator::err::Result<()> {
+        let is_reserved_prefix = |id: &Id| id.as_ref().starts_with("__") && !id.is_reserved();
+        if let Some(namespace) = schema_namespace {
+            if let Some(component) = namespace
+                .namespace_components()
+                .chain(std::iter::once(namespace.basename()))
+                .find(|id| is_reserved_prefix(id))
+            {
+                return Err(ReservedPrefixError {
+                    name: namespace.clone(),
+                    component: component.clone(),
+                    kind: "namespace",
+                    json_location: None,
+                }
+                .into());
+            }
+        }
+        let entity_types = schema_nsdef
+            .entity_types
+            .iter()
+            .map(|(id, ety)| (id, &ety.loc, "entity type"));
+        let common_types = schema_nsdef
+            .common_types
+            .iter()
+            .map(|(id, ty)| (id.as_ref(), &ty.loc, "common type"));
+        for (id, loc, kind) in entity_types.chain(common_types) {
+            let component: &Id = id.as_ref();
+            if is_reserved_prefix(component) {
+                return Err(ReservedPrefixError {
+                    name: RawName::new_from_unreserved(id.clone(), loc.clone())
+                        .qualify_with(schema_namespace),
+                    component: component.clone(),
+                    kind,
+                    json_location: None,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Holds a map from (fully qualified) [`InternalName`]s of common type
@@ -568,6 +623,7 @@ impl EntityTypeFragment<ConditionalName> {
                     }),
                     (Ok(_), Ok(_), Some(undeclared_parents)) => Err(TypeNotDefinedError {
                         undefined_types: undeclared_parents,
+                        json_location: None,
                     }),
                     (Err(e), Ok(_), None) | (Ok(_), Err(e), None) => Err(e),
                     (Err(e1), Err(e2), None) => {
@@ -578,6 +634,7 @@ impl EntityTypeFragment<ConditionalName> {
                         undeclared.extend(e.undefined_types);
                         Err(TypeNotDefinedError {
                             undefined_types: undeclared,
+                            json_location: None,
                         })
                     }
                     (Err(e1), Err(e2), Some(mut undeclared)) => {
@@ -585,6 +642,7 @@ impl EntityTypeFragment<ConditionalName> {
                         undeclared.extend(e2.undefined_types);
                         Err(TypeNotDefinedError {
                             undefined_types: undeclared,
+                            json_location: None,
                         })
                     }
                 }
@@ -656,7 +714,11 @@ impl ActionsDef<ConditionalName, ConditionalName> {
                 create_action_entity_uid_default_type(&action_name, &action_type, schema_namespace);
             match actions.entry(action_uid.clone().try_into()?) {
                 Entry::Vacant(ventry) => {
-                    let frag = ActionFragment::from_raw_action(action_type, schema_namespace)?;
+                    let frag = ActionFragment::from_raw_action(
+                        &action_name,
+                        action_type,
+                        schema_namespace,
+                    )?;
                     ventry.insert(frag);
                 }
                 Entry::Occupied(_) => {
@@ -717,37 +779,49 @@ pub struct ActionFragment<N, A> {
 
 impl ActionFragment<ConditionalName, ConditionalName> {
     pub(crate) fn from_raw_action(
+        action_name: &SmolStr,
         action_type: json_schema::ActionType<RawName>,
         schema_namespace: Option<&InternalName>,
     ) -> crate::validator::err::Result<Self> {
-        let (principal_types, resource_types, context) = action_type
+        let (principal_types, resource_types, context, no_resource) = action_type
             .applies_to
             .map(|applies_to| {
                 (
                     applies_to.principal_types,
                     applies_to.resource_types,
                     applies_to.context,
+                    applies_to.no_resource,
                 )
             })
             .unwrap_or_default();
-        Ok(Self {
-            context: context
-                .into_inner()
-                .conditionally_qualify_type_references(schema_namespace),
-            applies_to: ValidatorApplySpec::<ConditionalName>::new(
-                principal_types
-                    .into_iter()
-                    .map(|pty| {
-                        pty.conditionally_qualify_with(schema_namespace, ReferenceType::Entity)
-                    })
-                    .collect(),
+        let principal_types = principal_types
+            .into_iter()
+            .map(|pty| pty.conditionally_qualify_with(schema_namespace, ReferenceType::Entity))
+            .collect();
+        let applies_to = if no_resource {
+            if !resource_types.is_empty() {
+                return Err(NoResourceWithResourceTypesError {
+                    action: action_name.clone(),
+                }
+                .into());
+            }
+            ValidatorApplySpec::without_resource(principal_types)
+        } else {
+            ValidatorApplySpec::new(
+                principal_types,
                 resource_types
                     .into_iter()
                     .map(|rty| {
                         rty.conditionally_qualify_with(schema_namespace, ReferenceType::Entity)
                     })
                     .collect(),
-            ),
+            )
+        };
+        Ok(Self {
+            context: context
+                .into_inner()
+                .conditionally_qualify_type_references(schema_namespace),
+            applies_to,
             parents: action_type
                 .member_of
                 .unwrap_or_default()
@@ -872,6 +946,10 @@ pub(crate) fn try_jsonschema_type_into_validator_type(
                     UnknownExtensionTypeError {
                         actual: extension_type_name,
                         suggested_replacement,
+                        loc,
+                        attribute_path: Vec::new(),
+                        declaration: None,
+                        json_location: None,
                     },
                 ))
             }
@@ -957,7 +1035,8 @@ fn parse_record_attributes(
                 ty.ty.clone(),
                 extensions,
                 common_type_defs,
-            )?;
+            )
+            .map_err(|e| e.in_attribute(&attr))?;
             #[cfg(feature = "extended-schema")]
             let attr_ty = AttributeType::new_with_loc(attr_ty.ty.into(), ty.required, ty.loc);
             #[cfg(not(feature = "extended-schema"))]
@@ -119,12 +119,23 @@ impl RawName {
     ///   2. Itself in the empty namespace, if that name is declared in the schema
     ///      (in any schema fragment)
     ///
+    /// This order does not depend on the order in which anything is declared.
+    /// Also, [RFC 70] forbids declaring a name in a nonempty namespace if the
+    /// same basename is declared in the empty namespace, so at most one of (1)
+    /// and (2) is ever declared, and a reference which could resolve to both is
+    /// reported as a shadowing error listing all of the conflicting
+    /// definitions. To refer to a name in another nonempty namespace (in any
+    /// schema fragment, declared before or after the reference), it must be
+    /// explicitly qualified.
+    ///
     /// Note that if the [`RawName`] is the name of a primitive or extension
     /// type (without explicit `__cedar`), it will resolve via (2) above,
     /// because the primitive/extension type names will be added as defined
     /// common types in the empty namespace (aliasing to the real `__cedar`
     /// definitions), assuming the user didn't themselves define those names
     /// in the empty namespace.
+    ///
+    /// [RFC 70]: https://github.com/cedar-policy/rfcs/blob/main/text/0070-disallow-empty-namespace-shadowing.md
     pub fn conditionally_qualify_with(
         self,
         ns: Option<&InternalName>,
@@ -282,6 +293,7 @@ impl ConditionalName {
         }
         Err(TypeNotDefinedError {
             undefined_types: nonempty![self],
+            json_location: None,
         })
     }
 
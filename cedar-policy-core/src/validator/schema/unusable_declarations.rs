@@ -0,0 +1,198 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Checks for declarations in a schema which no policy can meaningfully use.
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use nonempty::NonEmpty;
+use smol_str::ToSmolStr;
+
+use crate::ast::EntityType;
+use crate::validator::cedar_schema::{schema_warnings, SchemaWarning};
+
+use super::err::schema_errors::UnusableDeclarationsError;
+use super::{Result, ValidatorSchema};
+
+impl ValidatorSchema {
+    /// Get warnings for declarations in this schema which no policy can
+    /// meaningfully use:
+    ///
+    /// - actions, other than action groups, which apply to no principal types
+    ///   or no resource types, including actions with no `appliesTo`;
+    /// - entity types which are neither a principal or resource type of any
+    ///   action, nor a `memberOfTypes` ancestor of one.
+    ///
+    /// Actions are reported before entity types, each ordered by name.
+    pub fn unusable_declaration_warnings(&self) -> impl Iterator<Item = SchemaWarning> + '_ {
+        let action_warnings = self
+            .action_ids()
+            .sorted_unstable_by(|a, b| a.name().cmp(b.name()))
+            .filter_map(|action| {
+                let no_principal_types = action.applies_to_principals().next().is_none();
+                let no_resource_types = action.applies_to_resources().next().is_none();
+                // Action groups commonly have no `appliesTo`, since they are only
+                // used in `action in` constraints
+                let is_group = action.descendants().next().is_some();
+                ((no_principal_types || no_resource_types) && !is_group).then(|| {
+                    SchemaWarning::from(schema_warnings::EmptyAppliesToWarning {
+                        namespace: action
+                            .name()
+                            .entity_type()
+                            .name()
+                            .as_ref()
+                            .namespace()
+                            .to_smolstr(),
+                        name: action.name().eid().escaped(),
+                        no_principal_types,
+                        no_resource_types,
+                        loc: action.loc().cloned(),
+                    })
+                })
+            });
+
+        let applies_to_types: HashSet<&EntityType> = self
+            .action_ids()
+            .flat_map(|action| {
+                action
+                    .applies_to_principals()
+                    .chain(action.applies_to_resources())
+            })
+            .collect();
+        let entity_type_warnings = self
+            .entity_types()
+            .filter(|ety| {
+                !applies_to_types.contains(ety.name())
+                    && !applies_to_types
+                        .iter()
+                        .any(|applies_to| ety.has_descendant_entity_type(applies_to))
+            })
+            .sorted_unstable_by(|a, b| a.name().cmp(b.name()))
+            .map(|ety| {
+                SchemaWarning::from(schema_warnings::UnreachableEntityTypeWarning {
+                    namespace: ety.name().name().as_ref().namespace().to_smolstr(),
+                    name: ety.name().name().basename().to_smolstr(),
+                    loc: ety.loc.clone(),
+                })
+            });
+
+        action_warnings.chain(entity_type_warnings)
+    }
+
+    /// Like [`ValidatorSchema::unusable_declaration_warnings`], but escalate
+    /// the warnings to an error, e.g., to reject such schemas in CI.
+    pub fn deny_unusable_declarations(&self) -> Result<()> {
+        match NonEmpty::collect(self.unusable_declaration_warnings()) {
+            Some(warnings) => Err(UnusableDeclarationsError { warnings }.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::extensions::Extensions;
+    use crate::validator::cedar_schema::SchemaWarning;
+    use crate::validator::{SchemaError, ValidatorSchema};
+
+    #[test]
+    fn unusable_declarations() {
+        let schema = ValidatorSchema::from_json_value(
+            json!({
+                "App": {
+                    "entityTypes": {
+                        "User": { "memberOfTypes": ["Group"] },
+                        "Group": {},
+                        "Photo": {},
+                        "Orphan": {},
+                    },
+                    "actions": {
+                        "view": {
+                            "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] },
+                        },
+                        "all": {},
+                        "edit": {
+                            "memberOf": [{ "id": "all" }],
+                            "appliesTo": { "principalTypes": [], "resourceTypes": ["Photo"] },
+                        },
+                        "ping": {},
+                        // Policies can use an optional context attribute after
+                        // checking for it with `has`, so this is usable
+                        "search": {
+                            "appliesTo": {
+                                "principalTypes": ["User"],
+                                "resourceTypes": ["Photo"],
+                                "context": {
+                                    "type": "Record",
+                                    "attributes": {
+                                        "query": { "type": "String", "required": false },
+                                    },
+                                },
+                            },
+                        },
+                    },
+                },
+            }),
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let warnings = schema.unusable_declaration_warnings().collect::<Vec<_>>();
+        assert_matches!(warnings.as_slice(), [
+            SchemaWarning::EmptyAppliesTo(edit),
+            SchemaWarning::EmptyAppliesTo(ping),
+            SchemaWarning::UnreachableEntityType(orphan),
+        ] => {
+            assert_eq!(edit.namespace(), "App");
+            assert_eq!(edit.name(), "edit");
+            assert_eq!(
+                edit.to_string(),
+                "action `edit` in namespace `App` applies to no principal types, so no request can use it"
+            );
+            assert_eq!(
+                ping.to_string(),
+                "action `ping` in namespace `App` applies to no principal or resource types, so no request can use it"
+            );
+            assert_eq!(orphan.namespace(), "App");
+            assert_eq!(orphan.name(), "Orphan");
+        });
+        assert_matches!(
+            schema.deny_unusable_declarations(),
+            Err(SchemaError::UnusableDeclarations(e)) => {
+                assert_eq!(e.warnings().count(), 3);
+            }
+        );
+    }
+
+    #[test]
+    fn no_unusable_declarations() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User in [Group];
+            entity Group;
+            entity Photo;
+            action view appliesTo { principal: User, resource: Photo, context: { n: Long } };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(schema.unusable_declaration_warnings().count(), 0);
+        assert_matches!(schema.deny_unusable_declarations(), Ok(()));
+    }
+}
@@ -0,0 +1,255 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Statistics about how a policy set uses the entity types and actions
+//! declared in a schema.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::ast::{EntityType, EntityUID, PolicyID, PolicySet};
+
+use super::expr_iterator::{policy_entity_type_names, policy_entity_uids};
+use super::{ValidationError, ValidationMode, ValidationResult, Validator, ValidatorSchema};
+
+/// Number of policies referencing an entity type or action, and number of
+/// validation errors in those policies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageStats {
+    policies: usize,
+    errors: usize,
+}
+
+impl UsageStats {
+    /// Number of policies and templates which reference the entity type or
+    /// action
+    pub fn policies(&self) -> usize {
+        self.policies
+    }
+
+    /// Number of validation errors reported for the policies and templates
+    /// which reference the entity type or action, including errors for
+    /// template-linked policies
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+}
+
+/// Per-entity-type and per-action statistics collected while validating a
+/// policy set, returned by [`Validator::validate_with_stats`].
+///
+/// A policy references an entity type or action if it appears in the policy
+/// scope or in an entity literal or `is` expression in the policy conditions.
+/// Every entity type and action declared in the schema is included, even if
+/// no policy references it, as well as any undeclared entity types and actions
+/// which are referenced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationStats {
+    entity_types: BTreeMap<EntityType, UsageStats>,
+    actions: BTreeMap<EntityUID, UsageStats>,
+}
+
+impl ValidationStats {
+    fn new<'a>(
+        schema: &ValidatorSchema,
+        policies: &PolicySet,
+        errors: impl IntoIterator<Item = &'a ValidationError>,
+    ) -> Self {
+        let mut stats = Self {
+            entity_types: schema
+                .entity_types()
+                .map(|ety| (ety.name().clone(), UsageStats::default()))
+                .collect(),
+            actions: schema
+                .action_ids()
+                .map(|action| (action.name().clone(), UsageStats::default()))
+                .collect(),
+        };
+
+        // Errors for a template-linked policy count towards its template
+        let mut errors_by_template: HashMap<&PolicyID, usize> = HashMap::new();
+        for error in errors {
+            let policy_id = error.policy_id();
+            let template_id = policies
+                .get(policy_id)
+                .map_or(policy_id, |policy| policy.template().id());
+            *errors_by_template.entry(template_id).or_default() += 1;
+        }
+
+        for template in policies.all_templates() {
+            let errors = errors_by_template
+                .get(template.id())
+                .copied()
+                .unwrap_or_default();
+            let entity_types = policy_entity_type_names(template)
+                .filter(|ety| !ety.is_action())
+                .collect::<BTreeSet<_>>();
+            for ety in entity_types {
+                let usage = stats.entity_types.entry(ety.clone()).or_default();
+                usage.policies += 1;
+                usage.errors += errors;
+            }
+            let actions = policy_entity_uids(template)
+                .filter(|euid| euid.is_action())
+                .collect::<BTreeSet<_>>();
+            for action in actions {
+                let usage = stats.actions.entry(action.clone()).or_default();
+                usage.policies += 1;
+                usage.errors += errors;
+            }
+        }
+        stats
+    }
+
+    /// Statistics for each entity type, ordered by name
+    pub fn entity_types(&self) -> impl Iterator<Item = (&EntityType, &UsageStats)> {
+        self.entity_types.iter()
+    }
+
+    /// Statistics for each action, ordered by name
+    pub fn actions(&self) -> impl Iterator<Item = (&EntityUID, &UsageStats)> {
+        self.actions.iter()
+    }
+
+    /// Statistics for the entity type `ety`, or `None` if it is neither
+    /// declared in the schema nor referenced by any policy
+    pub fn entity_type(&self, ety: &EntityType) -> Option<&UsageStats> {
+        self.entity_types.get(ety)
+    }
+
+    /// Statistics for the action `action`, or `None` if it is neither declared
+    /// in the schema nor referenced by any policy
+    pub fn action(&self, action: &EntityUID) -> Option<&UsageStats> {
+        self.actions.get(action)
+    }
+}
+
+impl Validator {
+    /// Validate all templates, links, and static policies in a policy set,
+    /// like [`Validator::validate`], and also collect statistics about how
+    /// often each entity type and action is referenced by the policies, and
+    /// how many validation errors those policies have.
+    pub fn validate_with_stats(
+        &self,
+        policies: &PolicySet,
+        mode: ValidationMode,
+    ) -> (ValidationResult, ValidationStats) {
+        let result = self.validate(policies, mode);
+        let stats = ValidationStats::new(self.schema(), policies, result.validation_errors());
+        (result, stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::{EntityUID, PolicyID, PolicySet};
+    use crate::extensions::Extensions;
+    use crate::parser;
+    use crate::validator::{ValidationMode, Validator, ValidatorSchema};
+
+    use super::UsageStats;
+
+    #[test]
+    fn validate_with_stats() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User, Group;
+            entity Photo { owner: User };
+            action view appliesTo { principal: User, resource: Photo };
+            action delete appliesTo { principal: User, resource: Photo };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+        let mut policies = PolicySet::new();
+        for (id, src) in [
+            (
+                "p0",
+                r#"permit(principal == User::"alice", action == Action::"view", resource is Photo);"#,
+            ),
+            (
+                "p1",
+                r#"permit(principal, action == Action::"view", resource) when { resource.owner == User::"bob" && resource.size > 1 };"#,
+            ),
+        ] {
+            let policy = parser::parse_policy(Some(PolicyID::from_string(id)), src).unwrap();
+            policies.add_static(policy).unwrap();
+        }
+        let template = parser::parse_policy_or_template(
+            Some(PolicyID::from_string("t0")),
+            r#"permit(principal == ?principal, action in [Action::"view", Action::"edit"], resource);"#,
+        )
+        .unwrap();
+        policies.add_template(template).unwrap();
+
+        let (result, stats) = validator.validate_with_stats(&policies, ValidationMode::default());
+        let errors_for = |id: &str| {
+            result
+                .validation_errors()
+                .filter(|e| e.policy_id() == &PolicyID::from_string(id))
+                .count()
+        };
+        // `p1` accesses the undeclared attribute `size`, and `t0` uses the
+        // undeclared action `edit`
+        assert_eq!(errors_for("p0"), 0);
+        assert!(errors_for("p1") > 0);
+        assert!(errors_for("t0") > 0);
+
+        let user = "User".parse().unwrap();
+        let photo = "Photo".parse().unwrap();
+        let group = "Group".parse().unwrap();
+        let view: EntityUID = r#"Action::"view""#.parse().unwrap();
+        let edit: EntityUID = r#"Action::"edit""#.parse().unwrap();
+        let delete: EntityUID = r#"Action::"delete""#.parse().unwrap();
+        assert_eq!(
+            stats.entity_type(&user),
+            Some(&UsageStats {
+                policies: 2,
+                errors: errors_for("p1"),
+            })
+        );
+        assert_eq!(
+            stats.entity_type(&photo),
+            Some(&UsageStats {
+                policies: 1,
+                errors: 0,
+            })
+        );
+        assert_eq!(stats.entity_type(&group), Some(&UsageStats::default()));
+        assert_eq!(
+            stats.action(&view),
+            Some(&UsageStats {
+                policies: 3,
+                errors: errors_for("p1") + errors_for("t0"),
+            })
+        );
+        assert_eq!(
+            stats.action(&edit),
+            Some(&UsageStats {
+                policies: 1,
+                errors: errors_for("t0"),
+            })
+        );
+        assert_eq!(stats.action(&delete), Some(&UsageStats::default()));
+        assert_eq!(
+            stats
+                .entity_types()
+                .map(|(ety, _)| ety.to_string())
+                .collect::<Vec<_>>(),
+            vec!["Group", "Photo", "User"]
+        );
+    }
+}
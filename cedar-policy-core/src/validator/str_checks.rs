@@ -17,7 +17,7 @@
 use crate::ast::{PolicyID, Template};
 use crate::parser::Loc;
 
-use crate::validator::expr_iterator::expr_text;
+use crate::validator::expr_iterator::policy_text;
 use crate::validator::expr_iterator::TextKind;
 use crate::validator::ValidationWarning;
 use unicode_security::GeneralSecurityProfile;
@@ -30,8 +30,7 @@ pub fn confusable_string_checks<'a>(
     let mut warnings = vec![];
 
     for policy in p {
-        let e = policy.condition();
-        for str in expr_text(&e) {
+        for str in policy_text(policy) {
             let warning = match str {
                 TextKind::String(span, s) => permissable_str(span, policy.id(), s),
                 TextKind::Identifier(span, i) => permissable_ident(span, policy.id(), i),
@@ -25,6 +25,7 @@ use itertools::EitherOrBoth::{Both, Left, Right};
 use itertools::Itertools;
 pub(crate) use typecheck_answer::TypecheckAnswer;
 
+use std::cell::RefCell;
 use std::sync::Arc;
 use std::{borrow::Cow, collections::HashSet};
 
@@ -35,11 +36,16 @@ use crate::validator::{
     extensions::ExtensionSchemas,
     schema::ValidatorSchema,
     types::{AttributeType, Capability, CapabilitySet, EntityKind, OpenTag, RequestEnv, Type},
-    validation_errors::{AttributeAccess, LubContext, UnexpectedTypeHelp},
+    validation_errors::{
+        AttributeAccess, BinaryOperands, LubContext, RecordLiteralMismatchKind,
+        SetScalarConfusionKind, UnexpectedTypeHelp,
+    },
+    validation_warnings::TrivialTypeTestKind,
     ValidationError, ValidationMode, ValidationWarning,
 };
 
 use crate::fuzzy_match::fuzzy_search;
+use crate::parser::Loc;
 use crate::{
     ast::{
         BinaryOp, EntityType, EntityUID, Expr, ExprBuilder, ExprKind, Literal, Name, PolicyID,
@@ -47,6 +53,7 @@ use crate::{
     },
     expr_builder::ExprBuilder as _,
 };
+use smol_str::SmolStr;
 
 const REQUIRED_STACK_SPACE: usize = 1024 * 100;
 
@@ -66,12 +73,16 @@ pub enum PolicyCheck {
 #[derive(Debug)]
 pub struct Typechecker<'a> {
     schema: &'a ValidatorSchema,
-    extensions: &'static ExtensionSchemas<'static>,
+    extensions: &'a ExtensionSchemas<'a>,
     mode: ValidationMode,
     /// List of valid (unlinked) `RequestEnv`s for this schema.
     /// Cached here so it can be computed once (during `Typechecker`
     /// construction) and potentially used for many typechecking operations.
     unlinked_envs: Vec<RequestEnv<'a>>,
+    /// Whether to also report type errors in subexpressions which are never
+    /// evaluated because of short circuiting on an operand with type `true`
+    /// or `false`
+    unreachable_errors: bool,
 }
 
 impl<'a> Typechecker<'a> {
@@ -82,9 +93,31 @@ impl<'a> Typechecker<'a> {
             extensions: ExtensionSchemas::all_available(),
             mode,
             unlinked_envs: schema.unlinked_request_envs(mode).collect(),
+            unreachable_errors: false,
         }
     }
 
+    /// Enable or disable reporting type errors in subexpressions which are
+    /// never evaluated in any request environment because the typechecker
+    /// short circuits a `&&`, `||` or `if` on an operand with type `true` or
+    /// `false`, e.g., `context.fake` in `resource is Usr && context.fake` when
+    /// `Usr` is not an entity type in the schema. These errors are normally
+    /// not reported, since they cannot cause an error at run time, so fixing
+    /// the first error in a policy can reveal new ones. It is disabled by
+    /// default.
+    pub fn with_unreachable_errors(self, enabled: bool) -> Typechecker<'a> {
+        Self {
+            unreachable_errors: enabled,
+            ..self
+        }
+    }
+
+    /// Typecheck extension function calls using the signatures in
+    /// `extensions` instead of those of the built-in extensions.
+    pub(crate) fn with_extensions(self, extensions: &'a ExtensionSchemas<'a>) -> Typechecker<'a> {
+        Self { extensions, ..self }
+    }
+
     /// The main entry point for typechecking policies. Checks that the policy
     /// expression has type boolean. If typechecking succeeds, then the method
     /// will return true, and no items will be added to the output list.
@@ -103,7 +136,7 @@ impl<'a> Typechecker<'a> {
         let typecheck_answers = self.typecheck_by_request_env(t);
 
         // consolidate the results from each query environment
-        let (all_false, all_succ) = typecheck_answers.into_iter().fold(
+        let (all_false, mut all_succ) = typecheck_answers.into_iter().fold(
             (true, true),
             |(all_false, all_succ), (_, check)| match check {
                 PolicyCheck::Success(_) => (false, all_succ),
@@ -127,10 +160,155 @@ impl<'a> Typechecker<'a> {
                 t.id().clone(),
             ));
         }
+        warnings.extend(self.trivial_type_test_warnings(t));
+        if self.unreachable_errors {
+            let unreachable = self.unreachable_type_errors(t);
+            all_succ &= unreachable.is_empty();
+            type_errors.extend(unreachable);
+        }
 
         all_succ
     }
 
+    /// Get the type errors in subexpressions of `t` which are skipped by short
+    /// circuiting in every request environment allowed by the scope of `t`.
+    /// An error is only reported if one of the same kind at the same location
+    /// is found in every such environment, so errors which depend on the
+    /// environment, e.g., an attribute access guarded by an `is` test which
+    /// is `false` in some environments, are not reported. Errors in
+    /// subexpressions which are evaluated in some environment are already
+    /// reported by the normal typechecking in that environment.
+    fn unreachable_type_errors(&self, t: &Template) -> Vec<ValidationError> {
+        let scope = [
+            t.principal_constraint().as_expr(),
+            t.action_constraint().as_expr(),
+            t.resource_constraint().as_expr(),
+        ];
+        let mut errors_by_env = self
+            .apply_typecheck_fn_by_request_env(t, |request_env, policy_id, expr| {
+                let unreachable_errors = RefCell::new(Vec::new());
+                let single_env_typechecker = SingleEnvTypechecker {
+                    schema: self.schema,
+                    extensions: self.extensions,
+                    mode: self.mode,
+                    policy_id,
+                    request_env,
+                    unreachable_errors: Some(&unreachable_errors),
+                };
+                if !single_env_typechecker.in_scope(&scope) {
+                    return None;
+                }
+                single_env_typechecker.expect_type(
+                    &CapabilitySet::new(),
+                    expr,
+                    Type::primitive_boolean(),
+                    &mut Vec::new(),
+                    |_| None,
+                );
+                Some(unreachable_errors.into_inner())
+            })
+            .into_iter()
+            .filter_map(|(_, errors)| errors);
+        let Some(first_env_errors) = errors_by_env.next() else {
+            return Vec::new();
+        };
+        // Errors are compared by kind and by the span of their first label,
+        // since the details of an error may differ between environments
+        let key = |err: &ValidationError| {
+            (
+                std::mem::discriminant(err),
+                miette::Diagnostic::labels(err)
+                    .into_iter()
+                    .flatten()
+                    .next()
+                    .map(|label| *label.inner()),
+            )
+        };
+        let mut errors = first_env_errors;
+        for env_errors in errors_by_env {
+            let keys = env_errors.iter().map(key).collect::<HashSet<_>>();
+            errors.retain(|err| keys.contains(&key(err)));
+        }
+        let mut seen = HashSet::new();
+        errors.retain(|err| seen.insert(key(err)));
+        errors
+    }
+
+    /// Get a [`ValidationWarning::TrivialTypeTest`] for each `is` test in the
+    /// `when` and `unless` conditions of `t` which has the same result in
+    /// every request environment allowed by the scope of `t` where it has a
+    /// type. Tests for entity types which are not in the schema are not
+    /// reported, since they are already validation errors.
+    fn trivial_type_test_warnings(&self, t: &Template) -> Vec<ValidationWarning> {
+        let tests = t
+            .non_scope_constraints()
+            .into_iter()
+            .flat_map(Expr::subexpressions)
+            .filter(|e| match e.expr_kind() {
+                ExprKind::Is { entity_type, .. } => self.schema.is_known_entity_type(entity_type),
+                _ => false,
+            })
+            .collect::<Vec<_>>();
+        if tests.is_empty() {
+            return Vec::new();
+        }
+        let scope = [
+            t.principal_constraint().as_expr(),
+            t.action_constraint().as_expr(),
+            t.resource_constraint().as_expr(),
+        ];
+        let types_by_env =
+            self.apply_typecheck_fn_by_request_env(t, |request_env, policy_id, _| {
+                let single_env_typechecker = SingleEnvTypechecker {
+                    schema: self.schema,
+                    extensions: self.extensions,
+                    mode: self.mode,
+                    policy_id,
+                    request_env,
+                    unreachable_errors: None,
+                };
+                let type_of = |e: &Expr| {
+                    single_env_typechecker
+                        .typecheck(&CapabilitySet::new(), e, &mut Vec::new())
+                        .into_typed_expr()
+                        .and_then(|typed| typed.into_data())
+                };
+                single_env_typechecker
+                    .in_scope(&scope)
+                    .then(|| tests.iter().map(|e| type_of(e)).collect::<Vec<_>>())
+            });
+        tests
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| {
+                let types = types_by_env
+                    .iter()
+                    .filter_map(|(_, types)| types.as_ref()?.get(i)?.as_ref())
+                    .collect::<Vec<_>>();
+                let always = |b| {
+                    !types.is_empty() && types.iter().all(|ty| **ty == Type::singleton_boolean(b))
+                };
+                let kind = if always(true) {
+                    TrivialTypeTestKind::AlwaysTrue
+                } else if always(false) {
+                    TrivialTypeTestKind::AlwaysFalse
+                } else {
+                    return None;
+                };
+                let test = match e.source_loc().and_then(Loc::snippet) {
+                    Some(snippet) => snippet.to_string(),
+                    None => e.to_string(),
+                };
+                Some(ValidationWarning::trivial_type_test(
+                    e.source_loc().cloned(),
+                    t.id().clone(),
+                    test,
+                    kind,
+                ))
+            })
+            .collect()
+    }
+
     /// Secondary entry point for typechecking requests. This method takes a policy and
     /// typechecks it under every schema-defined request environment. The result contains
     /// these environments and the individual typechecking response for each, in no
@@ -161,6 +339,7 @@ impl<'a> Typechecker<'a> {
             mode: self.mode,
             policy_id,
             request_env,
+            unreachable_errors: None,
         };
         let empty_prior_capability = CapabilitySet::new();
         let ans = single_env_typechecker.expect_type(
@@ -199,6 +378,32 @@ impl<'a> Typechecker<'a> {
         self.single_env_typechecking(request_env, t.id(), &t.condition())
     }
 
+    /// Type check `e`, an expression from the condition of a `Template`, on
+    /// its own in every schema-defined request environment for the template,
+    /// returning its type in each environment where it has one. Unlike
+    /// [`Typechecker::typecheck_by_request_env`], this returns the type even
+    /// where type checking fails but the type is still known, e.g., for an
+    /// access to an optional attribute which is not guarded by a `has`.
+    pub(crate) fn expr_types_by_request_env(&self, t: &Template, e: &Expr) -> Vec<Type> {
+        self.apply_typecheck_fn_by_request_env(t, |request_env, policy_id, _| {
+            let single_env_typechecker = SingleEnvTypechecker {
+                schema: self.schema,
+                extensions: self.extensions,
+                mode: self.mode,
+                policy_id,
+                request_env,
+                unreachable_errors: None,
+            };
+            single_env_typechecker
+                .typecheck(&CapabilitySet::new(), e, &mut Vec::new())
+                .into_typed_expr()
+                .and_then(|typed| typed.into_data())
+        })
+        .into_iter()
+        .filter_map(|(_, ty)| ty)
+        .collect()
+    }
+
     /// Apply `typecheck_fn` to the given policy in every schema-defined request
     /// environment, and collect all the results.
     ///
@@ -318,9 +523,34 @@ struct SingleEnvTypechecker<'a> {
     policy_id: &'a PolicyID,
     /// The single env which we're performing typechecking for
     request_env: &'a RequestEnv<'a>,
+    /// If present, type errors in subexpressions which are skipped by short
+    /// circuiting are typechecked anyway and their errors are added here
+    unreachable_errors: Option<&'a RefCell<Vec<ValidationError>>>,
 }
 
 impl<'a> SingleEnvTypechecker<'a> {
+    /// Can the policy with the scope constraints `scope`, given as
+    /// expressions, apply in the request env of this typechecker, i.e., does
+    /// none of them have type `false`?
+    fn in_scope(&self, scope: &[Expr]) -> bool {
+        scope.iter().all(|e| {
+            self.typecheck(&CapabilitySet::new(), e, &mut Vec::new())
+                .into_typed_expr()
+                .and_then(|typed| typed.into_data())
+                != Some(Type::singleton_boolean(false))
+        })
+    }
+
+    /// Run `check` on a subexpression which is skipped by short circuiting,
+    /// adding the errors it finds to `unreachable_errors` if those are
+    /// collected.
+    fn check_unreachable(&self, check: impl FnOnce(&mut Vec<ValidationError>)) {
+        if let Some(unreachable_errors) = self.unreachable_errors {
+            let mut errors = Vec::new();
+            check(&mut errors);
+            unreachable_errors.borrow_mut().extend(errors);
+        }
+    }
     /// This method handles the majority of the work. Given an expression, and
     /// the prior capability, return the result of typechecking the expression
     /// in the single env this typechecker was constructed for, and add any
@@ -421,8 +651,8 @@ impl<'a> SingleEnvTypechecker<'a> {
             // Literal entity reference have a type based on the entity type
             // that can be looked up in the schema.
             ExprKind::Lit(Literal::EntityUID(euid)) => {
-                // Unknown entity types/actions ids and unspecified entities will be
-                // detected by a different part of the validator, so a ValidationError is
+                // Unknown entity types and action ids will be detected by a
+                // different part of the validator, so a ValidationError is
                 // not generated here. We still return `TypecheckFail` so that
                 // typechecking is not considered successful.
                 match Type::euid_literal(euid.as_ref(), self.schema) {
@@ -465,6 +695,9 @@ impl<'a> SingleEnvTypechecker<'a> {
                     // If the guard has type `true` or `false`, we short circuit,
                     // looking at only the relevant branch.
                     if typ_test.data() == &Some(Type::singleton_boolean(true)) {
+                        self.check_unreachable(|errors| {
+                            self.typecheck(prior_capability, else_expr, errors);
+                        });
                         // The `then` branch needs to be typechecked using the
                         // prior capability of the `if` and any new capability generated
                         // by `test`. This enables an attribute access
@@ -493,6 +726,13 @@ impl<'a> SingleEnvTypechecker<'a> {
                             )
                         })
                     } else if typ_test.data() == &Some(Type::singleton_boolean(false)) {
+                        self.check_unreachable(|errors| {
+                            self.typecheck(
+                                &prior_capability.union(&test_capability),
+                                then_expr,
+                                errors,
+                            );
+                        });
                         // The `else` branch cannot use the `test` capability since
                         // we know in the `else` branch that the condition
                         // evaluated to `false`. It still can use the original
@@ -535,12 +775,33 @@ impl<'a> SingleEnvTypechecker<'a> {
                         // expression, will propagate to final TypecheckAnswer.
                         ans_then.then_typecheck(|typ_then, then_capability| {
                             ans_else.then_typecheck(|typ_else, else_capability| {
-                                let lub_ty = self.least_upper_bound_or_error(
-                                    e,
-                                    vec![typ_then.data().clone(), typ_else.data().clone()],
-                                    type_errors,
-                                    LubContext::Conditional,
-                                );
+                                let lub_ty = match (typ_then.data(), typ_else.data()) {
+                                    (Some(then_ty), Some(else_ty)) => {
+                                        match Type::least_upper_bound(then_ty, else_ty, self.mode) {
+                                            Ok(lub) => Some(lub),
+                                            Err(lub_hint) => {
+                                                type_errors.push(
+                                                    ValidationError::incompatible_branches(
+                                                        e.source_loc().cloned(),
+                                                        self.policy_id.clone(),
+                                                        (
+                                                            then_ty.clone(),
+                                                            then_expr.source_loc().cloned(),
+                                                        ),
+                                                        (
+                                                            else_ty.clone(),
+                                                            else_expr.source_loc().cloned(),
+                                                        ),
+                                                        lub_hint,
+                                                    ),
+                                                );
+                                                None
+                                            }
+                                        }
+                                    }
+                                    // One of the branches already failed to typecheck
+                                    _ => None,
+                                };
                                 let has_lub = lub_ty.is_some();
                                 let annot_expr = ExprBuilder::with_data(lub_ty)
                                     .with_same_source_loc(e)
@@ -578,9 +839,20 @@ impl<'a> SingleEnvTypechecker<'a> {
                     match typ_left.data() {
                         // LHS argument is false, so short circuit the `&&` to
                         // `False` _without_ typechecking the RHS.
-                        Some(Type::Bool(BoolType::False)) => TypecheckAnswer::success(
-                            typ_left.with_maybe_source_loc(e.source_loc().cloned()),
-                        ),
+                        Some(Type::Bool(BoolType::False)) => {
+                            self.check_unreachable(|errors| {
+                                self.expect_type(
+                                    &prior_capability.union(&capability_left),
+                                    right,
+                                    Type::primitive_boolean(),
+                                    errors,
+                                    |_| None,
+                                );
+                            });
+                            TypecheckAnswer::success(
+                                typ_left.with_maybe_source_loc(e.source_loc().cloned()),
+                            )
+                        }
                         _ => {
                             // Similar to the `then` branch of an `if`
                             // expression, the rhs of an `&&` is typechecked
@@ -674,10 +946,21 @@ impl<'a> SingleEnvTypechecker<'a> {
                     // LHS argument is true, so short circuit the `|| to `True`
                     // _without_ typechecking the RHS. Contrary to `&&`, we
                     // keep a capability  when short circuiting `||`.
-                    Some(Type::Bool(BoolType::True)) => TypecheckAnswer::success_with_capability(
-                        ty_expr_left.with_maybe_source_loc(e.source_loc().cloned()),
-                        capability_left,
-                    ),
+                    Some(Type::Bool(BoolType::True)) => {
+                        self.check_unreachable(|errors| {
+                            self.expect_type(
+                                prior_capability,
+                                right,
+                                Type::primitive_boolean(),
+                                errors,
+                                |_| None,
+                            );
+                        });
+                        TypecheckAnswer::success_with_capability(
+                            ty_expr_left.with_maybe_source_loc(e.source_loc().cloned()),
+                            capability_left,
+                        )
+                    }
                     _ => {
                         // The right operand of an `||` cannot be typechecked
                         // using the capability learned from the left because the
@@ -1036,19 +1319,35 @@ impl<'a> SingleEnvTypechecker<'a> {
                     .collect::<Vec<_>>();
 
                 // If we cannot compute a least upper bound for the element
-                // types, then a type error will be generated by
-                // `least_upper_bound_or_error` and TypecheckFail will be
-                // returned. It will also return TypecheckFail if any of the
-                // individual element failed to typecheck (were TypecheckFail).
+                // types, then an error naming the type of each element is
+                // reported and TypecheckFail will be returned. It will also
+                // return TypecheckFail if any of the individual element failed
+                // to typecheck (were TypecheckFail).
                 TypecheckAnswer::sequence_all_then_typecheck(elem_types, |types_and_capabilities| {
                     let (elem_expr_types, _): (Vec<Expr<Option<Type>>>, Vec<_>) =
                         types_and_capabilities.into_iter().unzip();
-                    let elem_lub = self.least_upper_bound_or_error(
-                        e,
-                        elem_expr_types.iter().map(|ety| ety.data().clone()),
-                        type_errors,
-                        LubContext::Set,
-                    );
+                    let elem_lub = elem_expr_types
+                        .iter()
+                        .map(|ety| ety.data().as_ref())
+                        .collect::<Option<Vec<_>>>()
+                        .and_then(|elem_tys| {
+                            Type::reduce_to_least_upper_bound(elem_tys.iter().copied(), self.mode)
+                                .map_err(|lub_hint| {
+                                    type_errors.push(ValidationError::incompatible_set_elements(
+                                        e.source_loc().cloned(),
+                                        self.policy_id.clone(),
+                                        elem_tys
+                                            .into_iter()
+                                            .zip(exprs.iter())
+                                            .map(|(ty, elem)| {
+                                                (ty.clone(), elem.source_loc().cloned())
+                                            })
+                                            .collect(),
+                                        lub_hint,
+                                    ));
+                                })
+                                .ok()
+                        });
                     match elem_lub {
                         _ if self.mode.is_strict() && exprs.is_empty() => {
                             type_errors.push(ValidationError::empty_set_forbidden(
@@ -1185,15 +1484,27 @@ impl<'a> SingleEnvTypechecker<'a> {
                             rhs_ty.data().as_ref(),
                         );
 
-                        if self.mode.is_strict() {
+                        if self.push_set_scalar_confusion(
+                            bin_expr,
+                            BinaryOp::Eq,
+                            (arg1.as_ref(), lhs_ty.data().as_ref()),
+                            (arg2.as_ref(), rhs_ty.data().as_ref()),
+                            type_errors,
+                        ) {
+                            TypecheckAnswer::fail(
+                                ExprBuilder::with_data(Some(type_of_eq))
+                                    .with_same_source_loc(bin_expr)
+                                    .binary_app(*op, lhs_ty, rhs_ty),
+                            )
+                        } else if self.mode.is_strict() {
                             let annotated_eq = ExprBuilder::with_data(Some(type_of_eq))
                                 .with_same_source_loc(bin_expr)
                                 .binary_app(*op, lhs_ty.clone(), rhs_ty.clone());
                             self.enforce_strict_equality(
                                 bin_expr,
                                 annotated_eq,
-                                lhs_ty.data().as_ref(),
-                                rhs_ty.data().as_ref(),
+                                (Some(arg1), lhs_ty.data().as_ref()),
+                                (Some(arg2), rhs_ty.data().as_ref()),
                                 type_errors,
                                 LubContext::Equality,
                             )
@@ -1218,7 +1529,8 @@ impl<'a> SingleEnvTypechecker<'a> {
                             .binary_app(*op, expr_ty_arg1.clone(), expr_ty_arg2.clone());
                         let t1 = expr_ty_arg1.data().as_ref();
                         let t2 = expr_ty_arg2.data().as_ref();
-                        match (t1, t2) {
+                        let first_error = type_errors.len();
+                        let answer = match (t1, t2) {
                             (Some(Type::Never), Some(Type::Never)) => TypecheckAnswer::fail(expr),
                             (Some(Type::Never), Some(other)) => {
                                 if self.is_valid_comparison_op_type(other) {
@@ -1312,7 +1624,13 @@ impl<'a> SingleEnvTypechecker<'a> {
                                 TypecheckAnswer::fail(expr)
                             }
                             _ => TypecheckAnswer::fail(expr),
-                        }
+                        };
+                        Self::label_operands(
+                            (arg1, t1.cloned()),
+                            (arg2, t2.cloned()),
+                            type_errors.get_mut(first_error..).unwrap_or_default(),
+                        );
+                        answer
                     })
                 })
             }
@@ -1325,21 +1643,31 @@ impl<'a> SingleEnvTypechecker<'a> {
                     (_, Type::Set { .. }) => Some(UnexpectedTypeHelp::SetOperationsNotSupported),
                     _ => None,
                 };
-                let ans_arg1 = self.expect_type(
-                    prior_capability,
+                let first_error = type_errors.len();
+                let ans_arg1 = self.typecheck(prior_capability, arg1, type_errors);
+                let arg1_ty = ans_arg1.type_of().cloned();
+                let ans_arg1 = self.check_type(
                     arg1,
+                    ans_arg1,
                     Type::primitive_long(),
                     type_errors,
                     help_builder,
                 );
                 ans_arg1.then_typecheck(|expr_ty_arg1, _| {
-                    let ans_arg2 = self.expect_type(
-                        prior_capability,
+                    let ans_arg2 = self.typecheck(prior_capability, arg2, type_errors);
+                    let arg2_ty = ans_arg2.type_of().cloned();
+                    let ans_arg2 = self.check_type(
                         arg2,
+                        ans_arg2,
                         Type::primitive_long(),
                         type_errors,
                         help_builder,
                     );
+                    Self::label_operands(
+                        (arg1, arg1_ty),
+                        (arg2, arg2_ty),
+                        type_errors.get_mut(first_error..).unwrap_or_default(),
+                    );
                     ans_arg2.then_typecheck(|expr_ty_arg2, _| {
                         TypecheckAnswer::success(
                             ExprBuilder::with_data(Some(Type::primitive_long()))
@@ -1384,13 +1712,16 @@ impl<'a> SingleEnvTypechecker<'a> {
                                 self.enforce_strict_equality(
                                     bin_expr,
                                     annotated_expr,
-                                    match expr_ty_arg1.data() {
-                                        Some(Type::Set {
-                                            element_type: Some(ty),
-                                        }) => Some(ty.as_ref()),
-                                        _ => None,
-                                    },
-                                    expr_ty_arg2.data().as_ref(),
+                                    (
+                                        None,
+                                        match expr_ty_arg1.data() {
+                                            Some(Type::Set {
+                                                element_type: Some(ty),
+                                            }) => Some(ty.as_ref()),
+                                            _ => None,
+                                        },
+                                    ),
+                                    (Some(arg2), expr_ty_arg2.data().as_ref()),
                                     type_errors,
                                     LubContext::Contains,
                                 )
@@ -1434,8 +1765,8 @@ impl<'a> SingleEnvTypechecker<'a> {
                             self.enforce_strict_equality(
                                 bin_expr,
                                 annotated_expr,
-                                expr_ty_arg1.data().as_ref(),
-                                expr_ty_arg2.data().as_ref(),
+                                (None, expr_ty_arg1.data().as_ref()),
+                                (None, expr_ty_arg2.data().as_ref()),
                                 type_errors,
                                 LubContext::ContainsAnyAll,
                             )
@@ -1633,8 +1964,8 @@ impl<'a> SingleEnvTypechecker<'a> {
         &self,
         unannotated_expr: &'b Expr,
         annotated_expr: Expr<Option<Type>>,
-        lhs_ty: Option<&Type>,
-        rhs_ty: Option<&Type>,
+        (lhs, lhs_ty): (Option<&Expr>, Option<&Type>),
+        (rhs, rhs_ty): (Option<&Expr>, Option<&Type>),
         type_errors: &mut Vec<ValidationError>,
         context: LubContext,
     ) -> TypecheckAnswer<'b> {
@@ -1645,6 +1976,19 @@ impl<'a> SingleEnvTypechecker<'a> {
             _ => match (lhs_ty, rhs_ty) {
                 (Some(lhs_ty), Some(rhs_ty)) => {
                     if let Err(lub_hint) = Type::least_upper_bound(lhs_ty, rhs_ty, self.mode) {
+                        let mismatches = match (lhs, rhs) {
+                            (_, Some(rhs)) if matches!(rhs.expr_kind(), ExprKind::Record(_)) => {
+                                self.record_literal_mismatches(rhs, rhs_ty, lhs_ty)
+                            }
+                            (Some(lhs), _) if matches!(lhs.expr_kind(), ExprKind::Record(_)) => {
+                                self.record_literal_mismatches(lhs, lhs_ty, rhs_ty)
+                            }
+                            _ => Vec::new(),
+                        };
+                        if !mismatches.is_empty() {
+                            type_errors.extend(mismatches);
+                            return TypecheckAnswer::fail(annotated_expr);
+                        }
                         type_errors.push(ValidationError::incompatible_types(
                             unannotated_expr.source_loc().cloned(),
                             self.policy_id.clone(),
@@ -1666,6 +2010,195 @@ impl<'a> SingleEnvTypechecker<'a> {
         }
     }
 
+    /// Report each attribute of the record literal `literal`, with type
+    /// `literal_ty`, which does not match the record type `expected`: extra
+    /// attributes, missing required attributes, and attributes with
+    /// incompatible types. Returns no errors if `expected` is not a record
+    /// type or if every attribute matches, e.g., when the record types only
+    /// differ in the optionality of an attribute.
+    fn record_literal_mismatches(
+        &self,
+        literal: &Expr,
+        literal_ty: &Type,
+        expected: &Type,
+    ) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        self.push_record_literal_mismatches(
+            literal,
+            literal_ty,
+            expected,
+            &mut Vec::new(),
+            expected,
+            &mut errors,
+        );
+        errors
+    }
+
+    /// Recursive helper for [`Self::record_literal_mismatches`], where `path`
+    /// is the path to `literal` in the outermost record literal, which was
+    /// expected to have type `outer_expected`
+    fn push_record_literal_mismatches(
+        &self,
+        literal: &Expr,
+        literal_ty: &Type,
+        expected: &Type,
+        path: &mut Vec<SmolStr>,
+        outer_expected: &Type,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let (
+            ExprKind::Record(fields),
+            Type::Record {
+                attrs: literal_attrs,
+                ..
+            },
+            Type::Record {
+                attrs: expected_attrs,
+                ..
+            },
+        ) = (literal.expr_kind(), literal_ty, expected)
+        else {
+            return;
+        };
+        let mismatch = |loc: Option<&Loc>, path: &[SmolStr], kind| {
+            ValidationError::record_literal_mismatch(
+                loc.cloned(),
+                self.policy_id.clone(),
+                path.to_vec(),
+                outer_expected.clone(),
+                kind,
+            )
+        };
+        for (attr, value) in fields.iter() {
+            path.push(attr.clone());
+            match (expected_attrs.get_attr(attr), literal_attrs.get_attr(attr)) {
+                (None, _) => errors.push(mismatch(
+                    value.source_loc(),
+                    path,
+                    RecordLiteralMismatchKind::ExtraAttribute,
+                )),
+                (Some(expected_attr), Some(literal_attr)) => {
+                    let (expected_ty, actual_ty) =
+                        (&*expected_attr.attr_type, &*literal_attr.attr_type);
+                    if Type::least_upper_bound(expected_ty, actual_ty, self.mode).is_err() {
+                        let errors_before = errors.len();
+                        self.push_record_literal_mismatches(
+                            value,
+                            actual_ty,
+                            expected_ty,
+                            path,
+                            outer_expected,
+                            errors,
+                        );
+                        if errors.len() == errors_before {
+                            errors.push(mismatch(
+                                value.source_loc(),
+                                path,
+                                RecordLiteralMismatchKind::AttributeType {
+                                    expected: expected_ty.clone(),
+                                    actual: actual_ty.clone(),
+                                },
+                            ));
+                        }
+                    }
+                }
+                // The literal's type has every attribute of the literal
+                (Some(_), None) => (),
+            }
+            path.pop();
+        }
+        for (attr, expected_attr) in expected_attrs.iter() {
+            if expected_attr.is_required && !fields.contains_key(attr) {
+                path.push(attr.clone());
+                errors.push(mismatch(
+                    literal.source_loc(),
+                    path,
+                    RecordLiteralMismatchKind::MissingAttribute,
+                ));
+                path.pop();
+            }
+        }
+    }
+
+    /// Determine whether the operands of an `==` or `in` expression, with
+    /// types `lhs_ty` and `rhs_ty`, confuse a set with a single value, e.g.,
+    /// `resource.tags == "admin"` where `tags` is a `Set<String>`, or
+    /// `"admin" in resource.tag` where `tag` is a `String`.
+    fn set_scalar_confusion_kind(
+        &self,
+        op: BinaryOp,
+        lhs_ty: &Type,
+        rhs_ty: &Type,
+    ) -> Option<SetScalarConfusionKind> {
+        let is_single_value = |ty: &Type| !matches!(ty, Type::Never | Type::Set { .. });
+        let compatible =
+            |ty0: &Type, ty1: &Type| Type::least_upper_bound(ty0, ty1, self.mode).is_ok();
+        match (op, lhs_ty, rhs_ty) {
+            (
+                BinaryOp::Eq,
+                Type::Set {
+                    element_type: Some(element_type),
+                },
+                other,
+            )
+            | (
+                BinaryOp::Eq,
+                other,
+                Type::Set {
+                    element_type: Some(element_type),
+                },
+            ) if is_single_value(other) && compatible(element_type, other) => {
+                Some(SetScalarConfusionKind::SetEqualsElement)
+            }
+            // `in` is meant for entities, so it is never confused when the
+            // left operand is an entity.
+            (BinaryOp::In, Type::Entity(_), _) => None,
+            (
+                BinaryOp::In,
+                lhs_ty,
+                Type::Set {
+                    element_type: Some(element_type),
+                },
+            ) if is_single_value(lhs_ty) && compatible(lhs_ty, element_type) => {
+                Some(SetScalarConfusionKind::ElementInSet)
+            }
+            (BinaryOp::In, lhs_ty, rhs_ty)
+                if is_single_value(lhs_ty)
+                    && is_single_value(rhs_ty)
+                    && compatible(lhs_ty, rhs_ty) =>
+            {
+                Some(SetScalarConfusionKind::InSingleValue)
+            }
+            _ => None,
+        }
+    }
+
+    /// If the operands of an `==` or `in` expression confuse a set with a
+    /// single value, push an error explaining so and return `true`.
+    fn push_set_scalar_confusion(
+        &self,
+        expr: &Expr,
+        op: BinaryOp,
+        (lhs, lhs_ty): (&Expr, Option<&Type>),
+        (rhs, rhs_ty): (&Expr, Option<&Type>),
+        type_errors: &mut Vec<ValidationError>,
+    ) -> bool {
+        let (Some(lhs_ty), Some(rhs_ty)) = (lhs_ty, rhs_ty) else {
+            return false;
+        };
+        let Some(kind) = self.set_scalar_confusion_kind(op, lhs_ty, rhs_ty) else {
+            return false;
+        };
+        type_errors.push(ValidationError::set_scalar_confusion(
+            expr.source_loc().cloned(),
+            self.policy_id.clone(),
+            (lhs_ty.clone(), lhs.source_loc().cloned()),
+            (rhs_ty.clone(), rhs.source_loc().cloned()),
+            kind,
+        ));
+        true
+    }
+
     /// Get the type for an `==` expression given the input types.
     fn type_of_equality<'b>(
         &self,
@@ -1768,18 +2301,41 @@ impl<'a> SingleEnvTypechecker<'a> {
         rhs: &'b Expr,
         type_errors: &mut Vec<ValidationError>,
     ) -> TypecheckAnswer<'b> {
-        // First, the basic typechecking rules for `in` that apply regardless of
-        // the syntactic special cases that follow.
-        let ty_lhs = self.expect_type(
-            prior_capability,
+        let ans_lhs = self.typecheck(prior_capability, lhs, type_errors);
+        let ans_rhs = self.typecheck(prior_capability, rhs, type_errors);
+        // An `in` between non-entity values is most likely a mistake for
+        // `contains` or `==`, so we report that instead of the operand types
+        // not being entities.
+        if self.push_set_scalar_confusion(
+            in_expr,
+            BinaryOp::In,
+            (lhs, ans_lhs.type_of()),
+            (rhs, ans_rhs.type_of()),
+            type_errors,
+        ) {
+            return ans_lhs.then_typecheck(|lhs_expr, _| {
+                ans_rhs.then_typecheck(|rhs_expr, _| {
+                    TypecheckAnswer::fail(
+                        ExprBuilder::with_data(Some(Type::primitive_boolean()))
+                            .with_same_source_loc(in_expr)
+                            .is_in(lhs_expr, rhs_expr),
+                    )
+                })
+            });
+        }
+
+        // Otherwise, the basic typechecking rules for `in` that apply
+        // regardless of the syntactic special cases that follow.
+        let ty_lhs = self.check_type(
             lhs,
+            ans_lhs,
             Type::any_entity_reference(),
             type_errors,
             |_| Some(UnexpectedTypeHelp::TryUsingContains),
         );
-        let ty_rhs = self.expect_one_of_types(
-            prior_capability,
+        let ty_rhs = self.check_one_of_types(
             rhs,
+            ans_rhs,
             &[
                 Type::set(Type::any_entity_reference().into()),
                 Type::any_entity_reference(),
@@ -2047,6 +2603,34 @@ impl<'a> SingleEnvTypechecker<'a> {
         }
     }
 
+    /// Label the operator and both operands of a binary operator, with the
+    /// operands' types, on the [`ValidationError::UnexpectedType`] errors in
+    /// `type_errors` which were reported for either operand.
+    fn label_operands(
+        (arg1, arg1_ty): (&Expr, Option<Type>),
+        (arg2, arg2_ty): (&Expr, Option<Type>),
+        type_errors: &mut [ValidationError],
+    ) {
+        let (left_loc, right_loc) = (arg1.source_loc(), arg2.source_loc());
+        let operands = BinaryOperands {
+            operator_loc: left_loc.zip(right_loc).and_then(operator_loc),
+            left_loc: left_loc.cloned(),
+            left_type: arg1_ty,
+            right_loc: right_loc.cloned(),
+            right_type: arg2_ty,
+        };
+        for error in type_errors {
+            if let ValidationError::UnexpectedType(error) = error {
+                if error.source_loc.is_some()
+                    && (error.source_loc.as_ref() == left_loc
+                        || error.source_loc.as_ref() == right_loc)
+                {
+                    error.operands = Some(operands.clone());
+                }
+            }
+        }
+    }
+
     /// Check that an expression has a type that is a subtype of one of the
     /// given types. If not, generate a type error and return `TypecheckFail`.
     /// Return `TypecheckSuccess` with the type otherwise.
@@ -2062,6 +2646,22 @@ impl<'a> SingleEnvTypechecker<'a> {
         F: FnOnce(&Type) -> Option<UnexpectedTypeHelp>,
     {
         let actual = self.typecheck(prior_capability, expr, type_errors);
+        self.check_one_of_types(expr, actual, expected, type_errors, type_error_help)
+    }
+
+    /// Like [`Self::expect_one_of_types`], but for an expression which
+    /// has already been typechecked, producing `actual`.
+    fn check_one_of_types<'b, F>(
+        &self,
+        expr: &'b Expr,
+        actual: TypecheckAnswer<'b>,
+        expected: &[Type],
+        type_errors: &mut Vec<ValidationError>,
+        type_error_help: F,
+    ) -> TypecheckAnswer<'b>
+    where
+        F: FnOnce(&Type) -> Option<UnexpectedTypeHelp>,
+    {
         actual.then_typecheck(|mut typ_actual, capability| match typ_actual.data() {
             Some(actual_ty) => {
                 if !expected.iter().any(|expected_ty| {
@@ -2124,43 +2724,20 @@ impl<'a> SingleEnvTypechecker<'a> {
         )
     }
 
-    /// Return the least upper bound of all types is the `types` vector. If
-    /// there isn't a least upper bound, then a type error is reported and
-    /// `TypecheckFail` is returned. Note that this function does not preserve the
-    /// capabilities of the input [`TypecheckAnswers`].
-    fn least_upper_bound_or_error(
+    /// Like [`Self::expect_type`], but for an expression which has
+    /// already been typechecked, producing `actual`.
+    fn check_type<'b, F>(
         &self,
-        expr: &Expr,
-        answers: impl IntoIterator<Item = Option<Type>>,
+        expr: &'b Expr,
+        actual: TypecheckAnswer<'b>,
+        expected: Type,
         type_errors: &mut Vec<ValidationError>,
-        context: LubContext,
-    ) -> Option<Type> {
-        answers
-            .into_iter()
-            // Inverting this to `Option<Vec<_>>` will cause this to fail to
-            // find a least upper bound if any of the input types were not
-            // defined.
-            .collect::<Option<Vec<_>>>()
-            .and_then(|typechecked_types| {
-                let lub = Type::reduce_to_least_upper_bound(&typechecked_types, self.mode);
-                match lub {
-                    Err(lub_hint) => {
-                        // A type error is generated if we could not find a least
-                        // upper bound for the types. The computed least upper bound
-                        // will be None, so this function will correctly report this
-                        // as a failure.
-                        type_errors.push(ValidationError::incompatible_types(
-                            expr.source_loc().cloned(),
-                            self.policy_id.clone(),
-                            typechecked_types,
-                            lub_hint,
-                            context,
-                        ));
-                        None
-                    }
-                    Ok(lub) => Some(lub),
-                }
-            })
+        type_error_help: F,
+    ) -> TypecheckAnswer<'b>
+    where
+        F: FnOnce(&Type) -> Option<UnexpectedTypeHelp>,
+    {
+        self.check_one_of_types(expr, actual, &[expected], type_errors, type_error_help)
     }
 
     /// If the `maybe_action_var` expression is `Expr::Var(Var::Action)`, return
@@ -2327,3 +2904,22 @@ impl<'a> SingleEnvTypechecker<'a> {
         }
     }
 }
+
+/// Source location of the operator between two operands, in either order, if
+/// the text between them is a single token, ignoring whitespace and
+/// parentheses
+fn operator_loc((left, right): (&Loc, &Loc)) -> Option<Loc> {
+    let (first, second) = if left.end() <= right.start() {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    let gap = left.src.get(first.end()..second.start())?;
+    let is_padding = |c: char| c.is_whitespace() || c == '(' || c == ')';
+    let operator = gap.trim_matches(is_padding);
+    if operator.is_empty() || operator.contains(is_padding) {
+        return None;
+    }
+    let start = first.end() + gap.find(operator)?;
+    Some(first.span(start..start + operator.len()))
+}
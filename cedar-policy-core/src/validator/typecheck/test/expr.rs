@@ -22,6 +22,7 @@ use std::{str::FromStr, vec};
 use crate::{
     ast::{BinaryOp, EntityUID, Expr, Pattern, PatternElem, SlotId, Var},
     extensions::Extensions,
+    test_utils::{expect_err, ExpectedErrorMessageBuilder},
     validator::types::BoolType,
 };
 use itertools::Itertools;
@@ -32,7 +33,9 @@ use crate::validator::{
     diagnostics::ValidationError,
     json_schema,
     types::Type,
-    validation_errors::{AttributeAccess, LubContext, LubHelp, UnexpectedTypeHelp},
+    validation_errors::{
+        AttributeAccess, LubContext, LubHelp, SetScalarConfusionKind, UnexpectedTypeHelp,
+    },
     RawName, ValidationMode,
 };
 
@@ -41,7 +44,7 @@ use super::test_utils::{
     assert_typecheck_fails_empty_schema, assert_typecheck_fails_empty_schema_without_type,
     assert_typecheck_fails_for_mode, assert_typechecks, assert_typechecks_empty_schema,
     assert_typechecks_empty_schema_permissive, assert_typechecks_for_mode, empty_schema_file,
-    expr_id_placeholder, get_loc,
+    expr_id_placeholder, get_loc, get_loc_after,
 };
 
 #[test]
@@ -157,14 +160,50 @@ fn heterogeneous_set() {
     let error = assert_exactly_one_diagnostic(errors);
     assert_eq!(
         error,
-        ValidationError::incompatible_types(
+        ValidationError::incompatible_set_elements(
+            get_loc(src, src),
+            expr_id_placeholder(),
+            vec![
+                (Type::singleton_boolean(true), get_loc(src, "true")),
+                (Type::primitive_long(), get_loc(src, "1")),
+            ],
+            LubHelp::None,
+        )
+    );
+}
+
+#[test]
+fn heterogeneous_set_long_and_string() {
+    let src = r#"[1, "a", 2]"#;
+    let errors = assert_typecheck_fails_empty_schema_without_type(&src.parse().unwrap());
+    let error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        error,
+        ValidationError::incompatible_set_elements(
             get_loc(src, src),
             expr_id_placeholder(),
-            [Type::singleton_boolean(true), Type::primitive_long()],
+            vec![
+                (Type::primitive_long(), get_loc(src, "1")),
+                (Type::primitive_string(), get_loc(src, r#""a""#)),
+                (Type::primitive_long(), get_loc(src, "2")),
+            ],
             LubHelp::None,
-            LubContext::Set,
         )
     );
+    expect_err(
+        src,
+        &miette::Report::new(error),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `expr`, the elements of a set literal have incompatible types: the elements have types `Long` and `String`",
+        )
+        .help("elements of a set must have compatible types. Types must be exactly equal to be compatible")
+        .with_underlines_or_labels([
+            ("1", Some("has type `Long`")),
+            (r#""a""#, Some("has type `String`")),
+            ("2", Some("has type `Long`")),
+        ])
+        .build(),
+    );
 }
 
 #[test]
@@ -802,18 +841,18 @@ fn record_get_attr_lub_typecheck_fails() {
     let error = assert_exactly_one_diagnostic(errors);
     assert_eq!(
         error,
-        ValidationError::incompatible_types(
+        ValidationError::incompatible_branches(
             get_loc(src, "if (0 < 1) then {foo: true} else 1"),
             expr_id_placeholder(),
-            [
+            (
                 Type::closed_record_with_required_attributes([(
                     "foo".into(),
                     Type::singleton_boolean(true).into(),
                 )]),
-                Type::primitive_long(),
-            ],
+                get_loc(src, "{foo: true}"),
+            ),
+            (Type::primitive_long(), get_loc_after(src, "else ", "1")),
             LubHelp::None,
-            LubContext::Conditional,
         )
     );
 }
@@ -1321,6 +1360,113 @@ fn in_typecheck_fails() {
     );
 }
 
+#[test]
+fn set_scalar_confusion() {
+    for (src, lhs, rhs, lhs_ty, rhs_ty, kind, ty) in [
+        (
+            "[1, 2] == 3",
+            "[1, 2]",
+            "3",
+            Type::set(Type::primitive_long().into()),
+            Type::primitive_long(),
+            SetScalarConfusionKind::SetEqualsElement,
+            Type::primitive_boolean(),
+        ),
+        (
+            "3 == [1, 2]",
+            "3",
+            "[1, 2]",
+            Type::primitive_long(),
+            Type::set(Type::primitive_long().into()),
+            SetScalarConfusionKind::SetEqualsElement,
+            Type::primitive_boolean(),
+        ),
+        (
+            "3 in [1, 2]",
+            "3",
+            "[1, 2]",
+            Type::primitive_long(),
+            Type::set(Type::primitive_long().into()),
+            SetScalarConfusionKind::ElementInSet,
+            Type::primitive_boolean(),
+        ),
+        (
+            r#""a" in "b""#,
+            r#""a""#,
+            r#""b""#,
+            Type::primitive_string(),
+            Type::primitive_string(),
+            SetScalarConfusionKind::InSingleValue,
+            Type::primitive_boolean(),
+        ),
+    ] {
+        let errors = assert_typecheck_fails_empty_schema(&src.parse().unwrap(), &ty);
+        let error = assert_exactly_one_diagnostic(errors);
+        assert_eq!(
+            error,
+            ValidationError::set_scalar_confusion(
+                get_loc(src, src),
+                expr_id_placeholder(),
+                (lhs_ty, get_loc(src, lhs)),
+                (rhs_ty, get_loc(src, rhs)),
+                kind,
+            ),
+            "{src}"
+        );
+    }
+
+    // Operands which are not compatible even as a set and its element are
+    // still reported as incompatible types
+    let src = r#"[1, 2] == "a""#;
+    let errors =
+        assert_typecheck_fails_empty_schema(&src.parse().unwrap(), &Type::primitive_boolean());
+    let error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        error,
+        ValidationError::incompatible_types(
+            get_loc(src, src),
+            expr_id_placeholder(),
+            [
+                Type::set(Type::primitive_long().into()),
+                Type::primitive_string()
+            ],
+            LubHelp::None,
+            LubContext::Equality,
+        )
+    );
+}
+
+#[test]
+fn set_scalar_confusion_message() {
+    let src = "[1, 2] == 3";
+    let errors =
+        assert_typecheck_fails_empty_schema(&src.parse().unwrap(), &Type::primitive_boolean());
+    expect_err(
+        src,
+        &miette::Report::new(assert_exactly_one_diagnostic(errors)),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `expr`, `==` compares a set with a single value: the operands have types `Set<Long>` and `Long`",
+        )
+        .help("try using `.contains()` to test if the set contains the value")
+        .exactly_two_underlines("[1, 2]", "3")
+        .build(),
+    );
+
+    let src = r#""a" in "b""#;
+    let errors =
+        assert_typecheck_fails_empty_schema(&src.parse().unwrap(), &Type::primitive_boolean());
+    expect_err(
+        src,
+        &miette::Report::new(assert_exactly_one_diagnostic(errors)),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `expr`, `in` is applied to two single non-entity values: the operands have types `String` and `String`",
+        )
+        .help("try using `==` to compare single values; `in` tests entity hierarchy membership")
+        .exactly_two_underlines(r#""a""#, r#""b""#)
+        .build(),
+    );
+}
+
 #[test]
 fn contains_typechecks() {
     assert_typechecks_empty_schema(
@@ -1665,12 +1811,12 @@ fn if_no_lub_error() {
     let error = assert_exactly_one_diagnostic(errors);
     assert_eq!(
         error,
-        ValidationError::incompatible_types(
+        ValidationError::incompatible_branches(
             get_loc(src, src),
             expr_id_placeholder(),
-            [Type::primitive_long(), Type::primitive_string()],
+            (Type::primitive_long(), get_loc_after(src, "then ", "1")),
+            (Type::primitive_string(), get_loc(src, r#""test""#)),
             LubHelp::None,
-            LubContext::Conditional,
         )
     );
 }
@@ -1682,12 +1828,12 @@ fn if_typecheck_fails() {
     assert_sets_equal(
         errors,
         [
-            ValidationError::incompatible_types(
+            ValidationError::incompatible_branches(
                 get_loc(src, src),
                 expr_id_placeholder(),
-                [Type::primitive_long(), Type::primitive_string()],
+                (Type::primitive_long(), get_loc(src, "1")),
+                (Type::primitive_string(), get_loc(src, r#""test""#)),
                 LubHelp::None,
-                LubContext::Conditional,
             ),
             ValidationError::expected_type(
                 get_loc(src, r#""fail""#),
@@ -101,6 +101,60 @@ fn ip_extension_typecheck_fails() {
     );
 }
 
+#[test]
+#[cfg(feature = "ipaddr")]
+fn ip_extension_declared_attribute() {
+    use crate::ast::{Name, PolicyID};
+    use crate::parser::parse_policy;
+    use crate::validator::typecheck::test::test_utils::{
+        assert_policy_typecheck_fails, assert_policy_typechecks,
+    };
+
+    let ipaddr_name = Name::parse_unqualified_name("ipaddr").expect("should be a valid identifier");
+    let schema = r#"
+        entity User { src: ipaddr };
+        action view appliesTo { principal: User, resource: User };
+    "#;
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when { principal.src.isInRange(ip("10.0.0.0/8")) };"#,
+    )
+    .expect("parsing should succeed");
+    assert_policy_typechecks(schema, policy);
+
+    let src = r#"permit(principal, action, resource) when { principal.src.isIpv4(1) };"#;
+    let policy =
+        parse_policy(Some(PolicyID::from_string("0")), src).expect("parsing should succeed");
+    let errors = assert_policy_typecheck_fails(schema, policy);
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::wrong_number_args(
+            get_loc(src, "principal.src.isIpv4(1)"),
+            PolicyID::from_string("0"),
+            1,
+            2,
+        )
+    );
+
+    let src =
+        r#"permit(principal, action, resource) when { principal.src.isInRange("10.0.0.0/8") };"#;
+    let policy =
+        parse_policy(Some(PolicyID::from_string("0")), src).expect("parsing should succeed");
+    let errors = assert_policy_typecheck_fails(schema, policy);
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::expected_type(
+            get_loc(src, r#""10.0.0.0/8""#),
+            PolicyID::from_string("0"),
+            Type::extension(ipaddr_name),
+            Type::primitive_string(),
+            None,
+        )
+    );
+}
+
 #[test]
 #[cfg(not(feature = "variadic-is-in-range"))]
 fn ip_extension_not_variadic() {
@@ -683,12 +683,12 @@ mod fail_partial_schema {
                 src,
             )
             .unwrap(),
-            [ValidationError::incompatible_types(
+            [ValidationError::incompatible_branches(
                 get_loc(src, "if resource.foo then principal.age else (if resource.bar then principal.name else principal.unknown)"),
                 PolicyID::from_string("policy0"),
-                vec![Type::primitive_long(), Type::primitive_string()],
+                (Type::primitive_long(), get_loc(src, "principal.age")),
+                (Type::primitive_string(), get_loc(src, "if resource.bar then principal.name else principal.unknown")),
                 LubHelp::None,
-                LubContext::Conditional,
             )],
         );
     }
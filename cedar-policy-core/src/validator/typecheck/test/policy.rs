@@ -17,12 +17,13 @@
 //! Contains test for typechecking complete Cedar policies with schema
 //! files.
 
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use crate::{
     ast::{EntityUID, Expr, PolicyID, Template},
     extensions::Extensions,
     parser::{parse_policy, parse_policy_or_template},
+    test_utils::{expect_err, ExpectedErrorMessageBuilder},
     validator::types::BoolType,
 };
 
@@ -30,14 +31,15 @@ use super::test_utils::{
     assert_exactly_one_diagnostic, assert_policy_typecheck_fails,
     assert_policy_typecheck_fails_for_mode, assert_policy_typecheck_warns,
     assert_policy_typecheck_warns_for_mode, assert_policy_typechecks,
-    assert_policy_typechecks_for_mode, assert_typechecks, get_loc,
+    assert_policy_typechecks_for_mode, assert_sets_equal, assert_typechecks, get_loc,
 };
 use crate::validator::{
     diagnostics::ValidationError,
     json_schema,
     typecheck::{PolicyCheck, Typechecker},
     types::{EntityLUB, Type},
-    validation_errors::{AttributeAccess, LubContext, LubHelp},
+    validation_errors::{AttributeAccess, LubHelp},
+    validation_warnings::TrivialTypeTestKind,
     RawName, ValidationMode, ValidationWarning, ValidatorSchema,
 };
 
@@ -309,6 +311,141 @@ fn policy_context_invalid_attribute() {
     );
 }
 
+#[test]
+fn policy_multiple_independent_errors() {
+    // Typechecking recovers from the error in the left operand of `&&`, so the
+    // unrelated error in the right operand is also reported. The failed
+    // comparison with `21` is not reported as a further error.
+    let src = r#"permit(principal, action == Action::"view_photo", resource) when { principal.age > 21 && context.fake };"#;
+    let errors = assert_policy_typecheck_fails(
+        simple_schema_file(),
+        parse_policy(Some(PolicyID::from_string("0")), src).expect("Policy should parse."),
+    );
+    assert_sets_equal(
+        [
+            ValidationError::unsafe_attribute_access(
+                get_loc(src, "principal.age"),
+                PolicyID::from_string("0"),
+                AttributeAccess::EntityLUB(
+                    EntityLUB::single_entity("Group".parse().unwrap()),
+                    vec!["age".into()],
+                ),
+                Some("name".into()),
+                false,
+            ),
+            ValidationError::unsafe_attribute_access(
+                get_loc(src, "context.fake"),
+                PolicyID::from_string("0"),
+                AttributeAccess::Context(
+                    r#"Action::"view_photo""#.parse().unwrap(),
+                    vec!["fake".into()],
+                ),
+                None,
+                false,
+            ),
+        ],
+        errors,
+    );
+}
+
+/// Typecheck `src` with the simple schema, also reporting type errors in
+/// subexpressions which are never evaluated.
+fn typecheck_with_unreachable_errors(src: &str) -> (bool, HashSet<ValidationError>) {
+    let schema = simple_schema_file()
+        .try_into()
+        .expect("Failed to construct schema.");
+    let typechecker =
+        Typechecker::new(&schema, ValidationMode::Strict).with_unreachable_errors(true);
+    let policy = parse_policy_or_template(Some(PolicyID::from_string("0")), src)
+        .expect("Policy should parse.");
+    let mut errors = HashSet::new();
+    let typechecked = typechecker.typecheck_policy(&policy, &mut errors, &mut HashSet::new());
+    (typechecked, errors)
+}
+
+#[test]
+fn policy_unreachable_errors() {
+    // `resource is Album` is always `false` for `view_photo`, so `context.fake`
+    // is never evaluated, and its error is only reported with the option.
+    let src = r#"permit(principal, action == Action::"view_photo", resource) when { principal.age > 21 && resource is Album && context.fake };"#;
+    let age_error = ValidationError::unsafe_attribute_access(
+        get_loc(src, "principal.age"),
+        PolicyID::from_string("0"),
+        AttributeAccess::EntityLUB(
+            EntityLUB::single_entity("Group".parse().unwrap()),
+            vec!["age".into()],
+        ),
+        Some("name".into()),
+        false,
+    );
+    let errors = assert_policy_typecheck_fails(
+        simple_schema_file(),
+        parse_policy(Some(PolicyID::from_string("0")), src).expect("Policy should parse."),
+    );
+    assert_sets_equal([age_error.clone()], errors);
+
+    let (typechecked, errors) = typecheck_with_unreachable_errors(src);
+    assert!(!typechecked);
+    assert_sets_equal(
+        [
+            age_error,
+            ValidationError::unsafe_attribute_access(
+                get_loc(src, "context.fake"),
+                PolicyID::from_string("0"),
+                AttributeAccess::Context(
+                    r#"Action::"view_photo""#.parse().unwrap(),
+                    vec!["fake".into()],
+                ),
+                None,
+                false,
+            ),
+        ],
+        errors,
+    );
+}
+
+#[test]
+fn policy_unreachable_errors_in_some_envs() {
+    // `principal.age` is never evaluated for a `Group` principal, but it is
+    // evaluated, and is safe, for a `User` principal.
+    let (typechecked, errors) = typecheck_with_unreachable_errors(
+        r#"permit(principal, action, resource) when { principal is User && principal.age > 21 };"#,
+    );
+    assert!(typechecked);
+    assert_sets_equal([], errors);
+
+    // The same holds for the untaken branch of an `if` and for the right of `||`.
+    let (typechecked, errors) = typecheck_with_unreachable_errors(
+        r#"permit(principal, action, resource) when { if principal is Group then true else principal.age > 21 };"#,
+    );
+    assert!(typechecked);
+    assert_sets_equal([], errors);
+    let (typechecked, errors) = typecheck_with_unreachable_errors(
+        r#"permit(principal, action, resource) when { principal is Group || principal.age > 21 };"#,
+    );
+    assert!(typechecked);
+    assert_sets_equal([], errors);
+}
+
+#[test]
+fn policy_unreachable_errors_in_if() {
+    let src = r#"permit(principal, action == Action::"view_photo", resource) when { if resource is Album then 1 + "a" == 2 else true };"#;
+    assert_policy_typechecks(
+        simple_schema_file(),
+        parse_policy(Some(PolicyID::from_string("0")), src).expect("Policy should parse."),
+    );
+    let (typechecked, errors) = typecheck_with_unreachable_errors(src);
+    assert!(!typechecked);
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(
+        matches!(
+            errors.iter().next(),
+            Some(ValidationError::UnexpectedType(_))
+        ),
+        "{errors:?}"
+    );
+}
+
 #[test]
 fn policy_entity_type_attr() {
     assert_policy_typechecks(simple_schema_file(), parse_policy(
@@ -681,24 +818,34 @@ fn is_entity_lub() {
         )
         .unwrap(),
     );
-    let p = parse_policy(
-        None,
-        r#"
+    let src = r#"
             permit(principal, action, resource) when {
                 (if principal.name == "foo" then principal else resource) is Album
             };
-        "#,
-    )
-    .unwrap();
+        "#;
+    let p = parse_policy(None, src).unwrap();
     let warnings = assert_policy_typecheck_warns_for_mode(
         simple_schema_file(),
         p.clone(),
         ValidationMode::Permissive,
     );
-    let warning = assert_exactly_one_diagnostic(warnings);
-    assert_eq!(
-        warning,
-        ValidationWarning::impossible_policy(p.loc().cloned(), PolicyID::from_string("policy0"),)
+    assert_sets_equal(
+        [
+            ValidationWarning::impossible_policy(
+                p.loc().cloned(),
+                PolicyID::from_string("policy0"),
+            ),
+            ValidationWarning::trivial_type_test(
+                get_loc(
+                    src,
+                    r#"(if principal.name == "foo" then principal else resource) is Album"#,
+                ),
+                PolicyID::from_string("policy0"),
+                r#"(if principal.name == "foo" then principal else resource) is Album"#,
+                TrivialTypeTestKind::AlwaysFalse,
+            ),
+        ],
+        warnings,
     );
 }
 
@@ -714,18 +861,111 @@ fn is_action() {
         )
         .unwrap(),
     );
-    let p = parse_policy(
-        None,
-        r#"
-            permit(principal, action, resource) when { action is User};
-        "#,
-    )
-    .unwrap();
+    let src = r#"permit(principal, action, resource) when { action is User };"#;
+    let p = parse_policy(None, src).unwrap();
     let warnings = assert_policy_typecheck_warns(simple_schema_file(), p.clone());
-    let warning = assert_exactly_one_diagnostic(warnings);
+    assert_sets_equal(
+        [
+            ValidationWarning::impossible_policy(
+                p.loc().cloned(),
+                PolicyID::from_string("policy0"),
+            ),
+            ValidationWarning::trivial_type_test(
+                get_loc(src, "action is User"),
+                PolicyID::from_string("policy0"),
+                "action is User",
+                TrivialTypeTestKind::AlwaysFalse,
+            ),
+        ],
+        warnings,
+    );
+}
+
+#[test]
+fn is_trivial() {
+    let trivial_tests = |src: &str| {
+        let p = parse_policy_or_template(None, src).unwrap();
+        let schema = simple_schema_file().try_into().unwrap();
+        let typechecker = Typechecker::new(&schema, ValidationMode::Strict);
+        let mut warnings = HashSet::new();
+        typechecker.typecheck_policy(&p, &mut HashSet::new(), &mut warnings);
+        warnings
+            .into_iter()
+            .filter_map(|w| match w {
+                ValidationWarning::TrivialTypeTest(w) => Some((w.test, w.kind)),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    // `delete_group` only applies to `User` principals and `Group` resources,
+    // and no action applies to `Album` resources
     assert_eq!(
-        warning,
-        ValidationWarning::impossible_policy(p.loc().cloned(), PolicyID::from_string("policy0"),)
+        trivial_tests(
+            r#"permit(principal, action == Action::"delete_group", resource) when { principal is User };"#
+        ),
+        vec![(
+            "principal is User".to_string(),
+            TrivialTypeTestKind::AlwaysTrue
+        )]
+    );
+    assert_eq!(
+        trivial_tests(r#"permit(principal, action, resource) when { resource is Album };"#),
+        vec![(
+            "resource is Album".to_string(),
+            TrivialTypeTestKind::AlwaysFalse
+        )]
+    );
+    assert_eq!(
+        trivial_tests(r#"permit(principal, action, resource) when { resource.owner is User };"#),
+        vec![]
+    );
+    assert_eq!(
+        trivial_tests(
+            r#"permit(principal, action == Action::"view_photo", resource) unless { resource.owner.favorite is Photo };"#
+        ),
+        vec![(
+            "resource.owner.favorite is Photo".to_string(),
+            TrivialTypeTestKind::AlwaysTrue
+        )]
+    );
+    // Both tests depend on the request
+    assert_eq!(
+        trivial_tests(
+            r#"permit(principal, action, resource) when { resource is Photo || principal is Group };"#
+        ),
+        vec![]
+    );
+    // Only the requests allowed by the scope are considered, and tests in the
+    // scope itself are not reported
+    assert_eq!(
+        trivial_tests(
+            r#"permit(principal, action, resource is Photo) when { principal is User || principal is Group };"#
+        ),
+        vec![]
+    );
+    assert_eq!(
+        trivial_tests(r#"permit(principal is User, action, resource) when { resource is Album };"#),
+        vec![(
+            "resource is Album".to_string(),
+            TrivialTypeTestKind::AlwaysFalse
+        )]
+    );
+    assert_eq!(
+        trivial_tests(
+            r#"permit(principal, action, resource is Group) when { principal is User };"#
+        ),
+        vec![(
+            "principal is User".to_string(),
+            TrivialTypeTestKind::AlwaysTrue
+        )]
+    );
+    assert_eq!(
+        trivial_tests(r#"permit(principal is User, action == Action::"delete_group", resource);"#),
+        vec![]
+    );
+    assert_eq!(
+        trivial_tests(r#"permit(principal, action, resource) when { resource is Unknown };"#),
+        vec![]
     );
 }
 
@@ -739,18 +979,21 @@ fn entity_record_lub_is_none() {
     let error = assert_exactly_one_diagnostic(errors);
     assert_eq!(
         error,
-        ValidationError::incompatible_types(
+        ValidationError::incompatible_branches(
             get_loc(src, r#"if 1 > 0 then User::"alice" else {name: "bob"}"#),
             PolicyID::from_string("0"),
-            [
+            (
+                Type::named_entity_reference_from_str("User"),
+                get_loc(src, r#"User::"alice""#),
+            ),
+            (
                 Type::closed_record_with_required_attributes([(
                     "name".into(),
                     Type::primitive_string().into(),
                 )]),
-                Type::named_entity_reference_from_str("User"),
-            ],
+                get_loc(src, r#"{name: "bob"}"#),
+            ),
             LubHelp::EntityRecord,
-            LubContext::Conditional,
         )
     );
 }
@@ -977,18 +1220,21 @@ fn record_entity_lub_non_term() {
     let error = assert_exactly_one_diagnostic(errors);
     assert_eq!(
         error,
-        ValidationError::incompatible_types(
+        ValidationError::incompatible_branches(
             get_loc(src, r#"if principal.bar then principal.foo else U::"b""#),
             PolicyID::from_string("policy0"),
-            [
+            (
                 Type::closed_record_with_required_attributes([(
                     "foo".into(),
                     Type::named_entity_reference_from_str("U").into(),
                 )]),
+                get_loc(src, "principal.foo"),
+            ),
+            (
                 Type::named_entity_reference_from_str("U"),
-            ],
+                get_loc(src, r#"U::"b""#),
+            ),
             LubHelp::EntityRecord,
-            LubContext::Conditional,
         )
     );
 }
@@ -1147,6 +1393,155 @@ fn extended_has() {
     );
 }
 
+#[test]
+fn binary_operand_labels() {
+    let schema_src = r#"
+        entity User;
+        action "action" appliesTo {
+          principal: User,
+          resource: User,
+          context: { name: String, age: Long },
+        };
+    "#;
+    let (schema, _) =
+        ValidatorSchema::from_cedarschema_str(schema_src, Extensions::none()).unwrap();
+
+    let src = r#"permit(principal, action, resource) when { context.name + 1 > 0 };"#;
+    let policy = parse_policy(None, src).unwrap();
+    let errors = assert_policy_typecheck_fails(schema.clone(), policy);
+    expect_err(
+        src,
+        &miette::Report::new(assert_exactly_one_diagnostic(errors)),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `policy0`, unexpected type: expected Long but saw String",
+        )
+        .help("Cedar does not support string concatenation")
+        .with_underlines_or_labels([
+            ("context.name", Some("has type `String`")),
+            ("+", Some("expects operands of type `Long`")),
+            ("1", Some("has type `Long`")),
+        ])
+        .build(),
+    );
+
+    let src = r#"permit(principal, action, resource) when { (context.age) < ("a") };"#;
+    let policy = parse_policy(None, src).unwrap();
+    let errors = assert_policy_typecheck_fails(schema, policy);
+    expect_err(
+        src,
+        &miette::Report::new(assert_exactly_one_diagnostic(errors)),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `policy0`, unexpected type: expected Long but saw String",
+        )
+        .with_underlines_or_labels([
+            ("context.age", Some("has type `Long`")),
+            ("<", Some("expects operands of type `Long`")),
+            (r#""a""#, Some("has type `String`")),
+        ])
+        .build(),
+    );
+}
+
+#[test]
+fn record_literal_mismatch() {
+    let schema_src = r#"
+        entity User;
+        action "action" appliesTo {
+          principal: User,
+          resource: User,
+          context: { address: { street: String, zip: Long, geo?: { lat: Long } } },
+        };
+    "#;
+    let (schema, _) =
+        ValidatorSchema::from_cedarschema_str(schema_src, Extensions::none()).unwrap();
+    let help = "the record literal must match the record type `{geo?: {lat: Long,},street: String,zip: Long,}`";
+
+    let src = r#"permit(principal, action, resource) when { context.address == { street: "Main", zip: 1, unit: 4 } };"#;
+    let policy = parse_policy(None, src).unwrap();
+    let errors = assert_policy_typecheck_fails(schema.clone(), policy);
+    expect_err(
+        src,
+        &miette::Report::new(assert_exactly_one_diagnostic(errors)),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `policy0`, record literal has attribute `unit`, which the expected record type does not have",
+        )
+        .help(help)
+        .exactly_one_underline("4")
+        .build(),
+    );
+
+    let src =
+        r#"permit(principal, action, resource) when { { street: "Main" } == context.address };"#;
+    let policy = parse_policy(None, src).unwrap();
+    let errors = assert_policy_typecheck_fails(schema.clone(), policy);
+    expect_err(
+        src,
+        &miette::Report::new(assert_exactly_one_diagnostic(errors)),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `policy0`, record literal is missing the required attribute `zip`",
+        )
+        .help(help)
+        .exactly_one_underline(r#"{ street: "Main" }"#)
+        .build(),
+    );
+
+    let src = r#"permit(principal, action, resource) when { context.address == { street: "Main", zip: 1, geo: { lat: "north" } } };"#;
+    let policy = parse_policy(None, src).unwrap();
+    let errors = assert_policy_typecheck_fails(schema, policy);
+    expect_err(
+        src,
+        &miette::Report::new(assert_exactly_one_diagnostic(errors)),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `policy0`, attribute `geo.lat` of record literal has type `String`, but the expected record type requires `Long`",
+        )
+        .help(help)
+        .exactly_one_underline(r#""north""#)
+        .build(),
+    );
+}
+
+#[test]
+fn incompatible_branches() {
+    let schema_src = r#"
+        entity User;
+        action "action" appliesTo {
+          principal: User,
+          resource: User,
+          context: { b: Bool },
+        };
+    "#;
+    let (schema, _) =
+        ValidatorSchema::from_cedarschema_str(schema_src, Extensions::none()).unwrap();
+    let src =
+        r#"permit(principal, action, resource) when { (if context.b then 1 else "x") == 1 };"#;
+    let policy = parse_policy(None, src).unwrap();
+    let errors = assert_policy_typecheck_fails(schema, policy);
+    let error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        error,
+        ValidationError::incompatible_branches(
+            get_loc(src, r#"if context.b then 1 else "x""#),
+            PolicyID::from_string("policy0"),
+            (Type::primitive_long(), get_loc(src, "1")),
+            (Type::primitive_string(), get_loc(src, r#""x""#)),
+            LubHelp::None,
+        )
+    );
+    expect_err(
+        src,
+        &miette::Report::new(error),
+        &ExpectedErrorMessageBuilder::error(
+            "for policy `policy0`, the branches of a conditional have incompatible types: the `then` branch has type `Long`, but the `else` branch has type `String`",
+        )
+        .help("both branches of a conditional must have compatible types. Types must be exactly equal to be compatible. Try making both branches have type `Long`, or both have type `String`, or moving the operation applied to the conditional into each branch")
+        .with_underlines_or_labels([
+            ("1", Some("has type `Long`")),
+            (r#""x""#, Some("has type `String`")),
+        ])
+        .build(),
+    );
+}
+
 mod templates {
     use super::*;
 
@@ -38,6 +38,7 @@ use crate::validator::{
 
 use super::test_utils::{
     assert_exactly_one_diagnostic, assert_policy_typecheck_fails, expr_id_placeholder, get_loc,
+    get_loc_after,
 };
 
 #[track_caller] // report the caller's location as the location of the panic, not the location in this function
@@ -54,6 +55,7 @@ fn assert_typechecks_strict(
         mode: ValidationMode::Strict,
         policy_id: &expr_id_placeholder(),
         request_env,
+        unreachable_errors: None,
     };
     let mut errs = Vec::new();
     let answer =
@@ -81,6 +83,7 @@ fn assert_strict_type_error(
         mode: ValidationMode::Strict,
         policy_id: &expr_id_placeholder(),
         request_env,
+        unreachable_errors: None,
     };
     let mut errs = Vec::new();
     let answer =
@@ -121,6 +124,39 @@ fn assert_types_must_match(
     )
 }
 
+/// Like [`assert_types_must_match`], for the branches of the conditional
+/// `snippet`, given the type of each branch and the source text after which
+/// the branch is first found
+#[expect(clippy::too_many_arguments, reason = "it's a test helper")]
+fn assert_branches_must_match(
+    schema: json_schema::Fragment<RawName>,
+    env: &RequestEnv<'_>,
+    e: &Expr,
+    snippet: impl AsRef<str>,
+    expected_type: Type,
+    (then_type, then_snippet): (Type, &str),
+    (else_type, else_snippet): (Type, &str),
+    hint: LubHelp,
+) {
+    let src = e.source_loc().unwrap().src.clone();
+    assert_strict_type_error(
+        schema,
+        env,
+        e,
+        expected_type,
+        ValidationError::incompatible_branches(
+            get_loc(src.clone(), snippet),
+            expr_id_placeholder(),
+            (
+                then_type,
+                get_loc_after(src.clone(), " then ", then_snippet),
+            ),
+            (else_type, get_loc_after(src, " else ", else_snippet)),
+            hint,
+        ),
+    )
+}
+
 fn simple_schema_file() -> json_schema::Fragment<RawName> {
     json_schema::Fragment::from_json_value(json!(
     { "": {
@@ -174,6 +210,7 @@ fn strict_typecheck_catches_regular_type_error() {
             mode: ValidationMode::Strict,
             policy_id: &expr_id_placeholder(),
             request_env: &q,
+            unreachable_errors: None,
         };
         let mut errs = Vec::new();
         typechecker.expect_type(
@@ -340,7 +377,7 @@ fn if_bool_keeps_both() {
 #[test]
 fn if_bool_strict_type_mismatch() {
     with_simple_schema_and_request(|s, q| {
-        assert_types_must_match(
+        assert_branches_must_match(
             s,
             &q,
             &Expr::from_str(
@@ -349,12 +386,15 @@ fn if_bool_strict_type_mismatch() {
             .unwrap(),
             r#"if principal == User::"alice" then User::"alice" else Photo::"pie.jpg""#,
             Type::any_entity_reference(),
-            [
+            (
                 Type::named_entity_reference_from_str("User"),
+                r#"User::"alice""#,
+            ),
+            (
                 Type::named_entity_reference_from_str("Photo"),
-            ],
+                r#"Photo::"pie.jpg""#,
+            ),
             LubHelp::EntityType,
-            LubContext::Conditional,
         )
     })
 }
@@ -362,18 +402,53 @@ fn if_bool_strict_type_mismatch() {
 #[test]
 fn set_strict_types_mismatch() {
     with_simple_schema_and_request(|s, q| {
-        assert_types_must_match(
+        let src = r#"[User::"alice", Photo::"foo.jpg"]"#;
+        assert_strict_type_error(
             s,
             &q,
-            &Expr::from_str(r#"[User::"alice", Photo::"foo.jpg"]"#).unwrap(),
-            r#"[User::"alice", Photo::"foo.jpg"]"#,
+            &Expr::from_str(src).unwrap(),
             Type::set(Type::entity_lub(["User", "Photo"]).into()),
-            [
-                Type::named_entity_reference_from_str("User"),
-                Type::named_entity_reference_from_str("Photo"),
-            ],
-            LubHelp::EntityType,
-            LubContext::Set,
+            ValidationError::incompatible_set_elements(
+                get_loc(src, src),
+                expr_id_placeholder(),
+                vec![
+                    (
+                        Type::named_entity_reference_from_str("User"),
+                        get_loc(src, r#"User::"alice""#),
+                    ),
+                    (
+                        Type::named_entity_reference_from_str("Photo"),
+                        get_loc(src, r#"Photo::"foo.jpg""#),
+                    ),
+                ],
+                LubHelp::EntityType,
+            ),
+        )
+    })
+}
+
+#[test]
+fn set_strict_mixed_types_mismatch() {
+    with_simple_schema_and_request(|s, q| {
+        let src = r#"[1, "a", User::"b"]"#;
+        assert_strict_type_error(
+            s,
+            &q,
+            &Expr::from_str(src).unwrap(),
+            Type::any_set(),
+            ValidationError::incompatible_set_elements(
+                get_loc(src, src),
+                expr_id_placeholder(),
+                vec![
+                    (Type::primitive_long(), get_loc(src, "1")),
+                    (Type::primitive_string(), get_loc(src, r#""a""#)),
+                    (
+                        Type::named_entity_reference_from_str("User"),
+                        get_loc(src, r#"User::"b""#),
+                    ),
+                ],
+                LubHelp::None,
+            ),
         )
     })
 }
@@ -434,7 +509,7 @@ fn entity_in_lub() {
     // transformation may be an EntityLub if the expression failed to validate.
     // A previous revision panicked when this happened.
     with_simple_schema_and_request(|s, q| {
-        assert_types_must_match(
+        assert_branches_must_match(
             s,
             &q,
             &Expr::from_str(
@@ -443,12 +518,15 @@ fn entity_in_lub() {
             .unwrap(),
             r#"if 1 > 0 then User::"alice" else Photo::"pie.jpg""#,
             Type::primitive_boolean(),
-            [
+            (
                 Type::named_entity_reference_from_str("User"),
+                r#"User::"alice""#,
+            ),
+            (
                 Type::named_entity_reference_from_str("Photo"),
-            ],
+                r#"Photo::"pie.jpg""#,
+            ),
             LubHelp::EntityType,
-            LubContext::Conditional,
         )
     });
 }
@@ -636,25 +714,28 @@ fn test_has_attr() {
             &Expr::from_str(r#"{name: "foo"} has name"#).unwrap(),
             Type::primitive_boolean(),
         );
-        assert_types_must_match(
+        assert_branches_must_match(
             s,
             &q,
             &Expr::from_str(r#"(if principal == principal then {name: 1} else {bar: 2}) has bar"#)
                 .unwrap(),
             "if principal == principal then {name: 1} else {bar: 2}",
             Type::primitive_boolean(),
-            [
+            (
                 Type::closed_record_with_required_attributes([(
                     "name".into(),
                     Type::primitive_long().into(),
                 )]),
+                "{name: 1}",
+            ),
+            (
                 Type::closed_record_with_required_attributes([(
                     "bar".into(),
                     Type::primitive_long().into(),
                 )]),
-            ],
+                "{bar: 2}",
+            ),
             LubHelp::RecordWidth,
-            LubContext::Conditional,
         );
     })
 }
@@ -205,9 +205,9 @@ fn tag_access_type_error() {
     expect_err(
         src,
         &miette::Report::new(error),
-        &ExpectedErrorMessageBuilder::error(r#"the types String and Set<String> are not compatible"#)
-            .help("for policy `0`, both operands to a `==` expression must have compatible types. Types must be exactly equal to be compatible")
-            .exactly_one_underline(r#"principal.getTag("foo") == "bar""#)
+        &ExpectedErrorMessageBuilder::error("for policy `0`, `==` compares a set with a single value: the operands have types `Set<String>` and `String`")
+            .help("try using `.contains()` to test if the set contains the value")
+            .exactly_two_underlines(r#"principal.getTag("foo")"#, r#""bar""#)
             .build(),
     );
 
@@ -223,9 +223,9 @@ fn tag_access_type_error() {
     expect_err(
         src,
         &miette::Report::new(error),
-        &ExpectedErrorMessageBuilder::error(r#"the types String and Set<String> are not compatible"#)
-            .help("for policy `0`, both operands to a `==` expression must have compatible types. Types must be exactly equal to be compatible")
-            .exactly_one_underline(r#"principal.getTag("foo") == "bar""#)
+        &ExpectedErrorMessageBuilder::error("for policy `0`, `==` compares a set with a single value: the operands have types `Set<String>` and `String`")
+            .help("try using `.contains()` to test if the set contains the value")
+            .exactly_two_underlines(r#"principal.getTag("foo")"#, r#""bar""#)
             .build(),
     );
 }
@@ -400,9 +400,12 @@ fn permissive_tags() {
     expect_err(
         src,
         &miette::Report::new(errors.into_iter().next().expect("already checked that len is 2")),
-        &ExpectedErrorMessageBuilder::error("the types Blank and E are not compatible")
-            .help("for policy `0`, both branches of a conditional must have compatible types. Different entity types are never compatible even when their attributes would be compatible")
-            .exactly_one_underline(r#"if context.bool then principal else Blank::"""#)
+        &ExpectedErrorMessageBuilder::error("for policy `0`, the branches of a conditional have incompatible types: the `then` branch has type `E`, but the `else` branch has type `Blank`")
+            .help("both branches of a conditional must have compatible types. Different entity types are never compatible even when their attributes would be compatible. Try making both branches have type `E`, or both have type `Blank`, or moving the operation applied to the conditional into each branch")
+            .with_underlines_or_labels([
+                ("principal", Some("has type `E`")),
+                (r#"Blank::"""#, Some("has type `Blank`")),
+            ])
             .build(),
     );
 
@@ -55,6 +55,27 @@ pub fn get_loc(src: impl AsRef<str>, snippet: impl AsRef<str>) -> Option<Loc> {
     Some(Loc::new(start..end, src.as_ref().into()))
 }
 
+/// Like [`get_loc`], but for the first occurrence of `snippet` after the
+/// first occurrence of `after`
+pub fn get_loc_after(
+    src: impl AsRef<str>,
+    after: impl AsRef<str>,
+    snippet: impl AsRef<str>,
+) -> Option<Loc> {
+    let offset = src
+        .as_ref()
+        .find(after.as_ref())
+        .expect("Snippet does not exist in source!")
+        + after.as_ref().len();
+    let (start, _) = src
+        .as_ref()
+        .match_indices(snippet.as_ref())
+        .find(|(start, _)| *start >= offset)
+        .expect("Snippet does not exist in source!");
+    let end = start + snippet.as_ref().len();
+    Some(Loc::new(start..end, src.as_ref().into()))
+}
+
 impl ValidationError {
     /// Testing utility for an unexpected type error when exactly one type was
     /// expected.
@@ -114,6 +135,7 @@ impl Typechecker<'_> {
             mode: self.mode,
             policy_id,
             request_env: &request_env,
+            unreachable_errors: None,
         };
         let mut type_errors = Vec::new();
         let ans = typechecker.typecheck(&CapabilitySet::new(), e, &mut type_errors);
@@ -77,6 +77,12 @@ impl<'a> TypecheckAnswer<'a> {
     /// contains a type if the type annotated AST contains `Some`
     /// of the argument type at its root.
     pub fn contains_type(&self, ty: &Type) -> bool {
+        self.type_of() == Some(ty)
+    }
+
+    /// Get the type at the root of the type annotated AST, if there is one,
+    /// whether or not typechecking succeeded.
+    pub fn type_of(&self) -> Option<&Type> {
         match self {
             TypecheckAnswer::TypecheckSuccess { expr_type, .. } => Some(expr_type),
             TypecheckAnswer::TypecheckFail { expr_recovery_type } => Some(expr_recovery_type),
@@ -85,7 +91,6 @@ impl<'a> TypecheckAnswer<'a> {
             TypecheckAnswer::ErrorAstNode => None,
         }
         .and_then(|e| e.data().as_ref())
-            == Some(ty)
     }
 
     pub fn into_typed_expr(self) -> Option<Expr<Option<Type>>> {
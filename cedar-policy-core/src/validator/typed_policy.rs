@@ -0,0 +1,218 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Policies annotated with the types inferred by the typechecker.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use miette::SourceSpan;
+
+use crate::ast::{PolicyID, Template};
+
+use super::typecheck::{PolicyCheck, Typechecker};
+use super::types::Type;
+use super::{ValidationMode, ValidationResult, ValidationWarning, Validator};
+
+/// A policy which passed validation, annotated with the type the typechecker
+/// inferred for each of its expressions, returned by
+/// [`Validator::validate_and_annotate`].
+#[derive(Debug, Clone)]
+pub struct TypedPolicy {
+    policy_id: PolicyID,
+    /// Type of the expression at each source span, as `(offset, length)`.
+    /// When a policy applies to several request environments, this is the
+    /// least upper bound of the types in each environment, or `None` if they
+    /// have no least upper bound.
+    types: BTreeMap<(usize, usize), Option<Type>>,
+    warnings: Vec<ValidationWarning>,
+}
+
+impl TypedPolicy {
+    /// The id of the annotated policy
+    pub fn policy_id(&self) -> &PolicyID {
+        &self.policy_id
+    }
+
+    /// Get the type of the innermost expression whose source span contains
+    /// `span`, e.g., the expression under the cursor in an editor. Returns
+    /// `None` if no expression contains `span`, or if the expression has
+    /// types without a least upper bound in different request environments.
+    pub fn type_at(&self, span: SourceSpan) -> Option<&Type> {
+        let (start, end) = (span.offset(), span.offset() + span.len());
+        self.types
+            .iter()
+            .filter(|((offset, len), _)| *offset <= start && end <= offset + len)
+            .min_by_key(|((_, len), _)| *len)
+            .and_then(|(_, ty)| ty.as_ref())
+    }
+
+    /// Get an iterator over the warnings found by the validator
+    pub fn validation_warnings(&self) -> impl Iterator<Item = &ValidationWarning> {
+        self.warnings.iter()
+    }
+}
+
+impl Validator {
+    /// Validate a single static policy or template (note that Core `Template`
+    /// includes static policies as well), and return it annotated with the
+    /// types of its expressions if validation passes. Otherwise, return the
+    /// validation errors and warnings.
+    pub fn validate_and_annotate(
+        &self,
+        p: &Template,
+        mode: ValidationMode,
+    ) -> Result<TypedPolicy, ValidationResult> {
+        let (errors, warnings) = self.validate_policy(p, mode);
+        let errors = errors.collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(ValidationResult::new(errors, warnings).with_template(p));
+        }
+
+        let mut types: BTreeMap<(usize, usize), Option<Type>> = BTreeMap::new();
+        let extensions = self.extension_schemas();
+        let typechecker = Typechecker::new(&self.schema, mode).with_extensions(&extensions);
+        for (_, check) in typechecker.typecheck_by_request_env(p) {
+            let (PolicyCheck::Success(expr) | PolicyCheck::Irrelevant(_, expr)) = check else {
+                continue;
+            };
+            // Several expressions may share a span, e.g., those built for a
+            // scope constraint. Keep the outermost, which comes first.
+            let mut env_types = BTreeMap::new();
+            for e in expr.subexpressions() {
+                if let (Some(loc), Some(ty)) = (e.source_loc(), e.data()) {
+                    env_types.entry((loc.start(), loc.span.len())).or_insert(ty);
+                }
+            }
+            for (span, ty) in env_types {
+                match types.entry(span) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(Some(ty.clone()));
+                    }
+                    Entry::Occupied(mut entry) => {
+                        let lub = entry
+                            .get()
+                            .as_ref()
+                            .and_then(|prev| Type::least_upper_bound(prev, ty, mode).ok());
+                        entry.insert(lub);
+                    }
+                }
+            }
+        }
+        Ok(TypedPolicy {
+            policy_id: p.id().clone(),
+            types,
+            warnings: warnings.collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use miette::SourceSpan;
+
+    use crate::ast::PolicyID;
+    use crate::extensions::Extensions;
+    use crate::parser;
+    use crate::validator::types::Type;
+    use crate::validator::{ValidationError, ValidationMode, Validator, ValidatorSchema};
+
+    fn validator() -> Validator {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User { age: Long, name: String };
+            entity Admin { age: Long };
+            entity Photo { owner: User };
+            action view appliesTo { principal: [User, Admin], resource: Photo };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        Validator::new(schema)
+    }
+
+    fn span_of(src: &str, snippet: &str) -> SourceSpan {
+        (src.find(snippet).unwrap(), snippet.len()).into()
+    }
+
+    #[test]
+    fn types_of_expressions() {
+        let src = r#"permit(principal, action == Action::"view", resource) when { resource.owner.name like "a*" && principal.age > 21 };"#;
+        let policy =
+            parser::parse_policy_or_template(Some(PolicyID::from_string("p")), src).unwrap();
+        let typed = validator()
+            .validate_and_annotate(&policy, ValidationMode::default())
+            .unwrap();
+        assert_eq!(typed.policy_id(), &PolicyID::from_string("p"));
+        let type_at = |snippet| {
+            typed
+                .type_at(span_of(src, snippet))
+                .map(ToString::to_string)
+        };
+        assert_eq!(type_at("resource.owner.name").as_deref(), Some("String"));
+        assert_eq!(
+            typed
+                .type_at((src.rfind("resource").unwrap(), "resource".len()).into())
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("Photo")
+        );
+        assert_eq!(type_at("21").as_deref(), Some("Long"));
+        // A span inside an expression gets the type of the innermost
+        // expression containing it. The span of an attribute access covers
+        // the whole chain of accesses, so this is `resource.owner.name`.
+        assert_eq!(type_at("wner").as_deref(), Some("String"));
+        // `principal` is a `User` or an `Admin`, but `principal.age` is a
+        // `Long` in both request environments
+        assert_eq!(type_at("principal.age").as_deref(), Some("Long"));
+        assert_eq!(typed.type_at((src.len() + 1, 1).into()), None);
+    }
+
+    #[test]
+    fn types_differing_by_request_env() {
+        let src =
+            r#"permit(principal, action == Action::"view", resource) when { principal has name };"#;
+        let policy = parser::parse_policy_or_template(None, src).unwrap();
+        let typed = validator()
+            .validate_and_annotate(&policy, ValidationMode::default())
+            .unwrap();
+        // The `has` is always true for a `User` and always false for an
+        // `Admin`, so it is a `Bool`
+        let has = span_of(src, "principal has name");
+        assert_eq!(
+            typed.type_at(has).map(ToString::to_string).as_deref(),
+            Some("Bool")
+        );
+        // There is no least upper bound of `User` and `Admin` in strict mode
+        let principal = SourceSpan::from((has.offset(), "principal".len()));
+        assert_eq!(typed.type_at(principal), None::<&Type>);
+    }
+
+    #[test]
+    fn validation_errors() {
+        let src = r#"permit(principal, action == Action::"view", resource) when { principal.age > "a" };"#;
+        let policy = parser::parse_policy_or_template(None, src).unwrap();
+        let result = validator()
+            .validate_and_annotate(&policy, ValidationMode::default())
+            .unwrap_err();
+        assert!(result
+            .validation_errors()
+            .all(|e| matches!(e, ValidationError::UnexpectedType(_))));
+        assert!(result
+            .policy_loc(&PolicyID::from_string("policy0"))
+            .is_some());
+    }
+}
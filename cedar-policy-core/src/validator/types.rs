@@ -579,7 +579,7 @@ impl Type {
     ///
     /// TODO(#437): Handling of restricted exprs containing `Unknown`s is not
     /// yet complete or correct, as of this writing.
-    pub(crate) fn typecheck_restricted_expr(
+    pub fn typecheck_restricted_expr(
         &self,
         restricted_expr: BorrowedRestrictedExpr<'_>,
         extensions: &Extensions<'_>,
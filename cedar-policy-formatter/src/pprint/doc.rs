@@ -581,7 +581,7 @@ impl Doc for Node<Option<Str>> {
         // _cannot_ be used here because this function will change indentation
         // on newlines, which may alter the string content.
         Some(add_comment(
-            RcDoc::as_string(e),
+            RcDoc::as_string(escape_control_chars(&e.to_string())),
             get_comment_at_start(self.loc.as_ref().map(|loc| loc.span), &mut context.tokens)?,
             RcDoc::nil(),
         ))
@@ -611,7 +611,7 @@ impl Doc for Node<Option<Ref>> {
 impl Doc for Node<Option<Literal>> {
     fn to_doc<'src>(&self, context: &mut Context<'_, 'src>) -> Option<RcDoc<'src>> {
         Some(add_comment(
-            RcDoc::as_string(self.as_inner()?),
+            RcDoc::as_string(escape_control_chars(&self.as_inner()?.to_string())),
             get_comment_at_start(self.loc.as_ref().map(|loc| loc.span), &mut context.tokens)?,
             RcDoc::nil(),
         ))
@@ -189,6 +189,35 @@ mod tests {
         soundness_check(p2, &parse_policyset(p1).unwrap()).unwrap();
     }
 
+    #[test]
+    fn test_id_annotations() {
+        // `@id` annotations are kept by the formatter, so the formatted policy
+        // set has the same ids when parsed using them
+        let config = Config {
+            line_width: 80,
+            indent_width: 2,
+        };
+        let p = r#"@id("billing-admin-access") permit(principal,action,resource);
+        forbid(principal, action, resource);
+        @id("b")
+        permit(principal == ?principal, action, resource);"#;
+        let formatted = policies_str_to_pretty(p, &config).unwrap();
+        let ids = |ps: &str| {
+            let pset = cedar_policy_core::parser::parse_policyset_with_id_annotations(ps).unwrap();
+            pset.all_templates()
+                .map(|t| t.id().to_smolstr())
+                .collect::<std::collections::BTreeSet<_>>()
+        };
+        assert_eq!(ids(&formatted), ids(p));
+        assert_eq!(
+            ids(&formatted),
+            ["b", "billing-admin-access", "policy1"]
+                .into_iter()
+                .map(Into::into)
+                .collect()
+        );
+    }
+
     #[test]
     fn test_add_trailing_newline() {
         // The formatter should add a trailing newline.
@@ -242,6 +271,26 @@ mod tests {
         assert_eq!(policies_str_to_pretty(p5, &config).unwrap(), formatted_p);
     }
 
+    #[test]
+    fn test_escape_control_chars() {
+        // Raw control characters in string literals are re-escaped, except
+        // for newlines and tabs, and existing escapes are kept as written
+        let config = Config {
+            line_width: 80,
+            indent_width: 2,
+        };
+        let p = "@note(\"bell\u{7}\")\npermit (principal == User::\"a\u{0}b\", action, resource)\nwhen { context.s == \"x\u{7f}y\u{1b}z\\u{7}\tw\n\" };";
+        let formatted = policies_str_to_pretty(p, &config).unwrap();
+        assert!(!formatted.contains(|c: char| c.is_control() && c != '\n' && c != '\t'));
+        assert!(formatted.contains(r#"@note("bell\u{7}")"#));
+        assert!(formatted.contains(r#"User::"a\0b""#));
+        assert!(formatted.contains("\"x\\u{7f}y\\u{1b}z\\u{7}\tw\n\""));
+        assert_eq!(
+            policies_str_to_pretty(&formatted, &config).unwrap(),
+            formatted
+        );
+    }
+
     #[test]
     fn test_format_files() {
         let config = Config {
@@ -44,6 +44,22 @@ pub fn get_leading_comment_doc_from_str<'src>(leading_comment: &[&'src str]) ->
     }
 }
 
+/// Escape the control characters in a string literal, other than newlines
+/// and tabs, so that the formatted policy does not contain them as raw bytes.
+/// The escapes produced are valid Cedar escapes, so the literal still has the
+/// same value.
+pub fn escape_control_chars(lit: &str) -> String {
+    let mut escaped = String::with_capacity(lit.len());
+    for c in lit.chars() {
+        if c.is_control() && c != '\n' && c != '\t' {
+            escaped.extend(c.escape_debug());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
 /// Convert multiline text into an `RcDoc`. Both `RcDoc::as_string` and
 /// `RcDoc::text` allow newlines in the text (although the official
 /// documentation says they don't), but the resulting text will maintain its
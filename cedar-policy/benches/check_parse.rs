@@ -0,0 +1,70 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::str::FromStr;
+
+use cedar_policy::{check_parse, PolicySet};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// A policy set of `n` policies, with a syntax error in every tenth policy if
+/// `with_errors` is true
+#[expect(clippy::unwrap_used, reason = "writing to a `String` cannot fail")]
+fn policies(n: usize, with_errors: bool) -> String {
+    (0..n).fold(String::new(), |mut text, i| {
+        let condition = if with_errors && i % 10 == 9 {
+            "context.count + ".to_string()
+        } else {
+            format!("context.count > {i} && principal.department == \"dept-{i}\"")
+        };
+        write!(
+            text,
+            "@id(\"policy-{i}\")\npermit(principal in Group::\"group-{i}\", action in [Action::\"view\", Action::\"edit\"], resource is Document)\nwhen {{ {condition} }}\nunless {{ resource.tags.contains(\"secret\") }};\n"
+        )
+        .unwrap();
+        text
+    })
+}
+
+pub fn check_parse_vs_full_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("check_parse");
+    for n in [10, 100, 1000] {
+        let valid = policies(n, false);
+        group.bench_with_input(BenchmarkId::new("check_parse", n), &valid, |b, text| {
+            b.iter(|| check_parse(black_box(text)))
+        });
+        group.bench_with_input(BenchmarkId::new("from_str", n), &valid, |b, text| {
+            b.iter(|| PolicySet::from_str(black_box(text)))
+        });
+        let invalid = policies(n, true);
+        group.bench_with_input(
+            BenchmarkId::new("check_parse_with_errors", n),
+            &invalid,
+            |b, text| b.iter(|| check_parse(black_box(text))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("parse_recovering_with_errors", n),
+            &invalid,
+            |b, text| b.iter(|| PolicySet::parse_recovering(black_box(text))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, check_parse_vs_full_parse);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::str::FromStr;
+
+use cedar_policy::{
+    eval_expression, Context, Entities, EntityUid, Expression, Request, RestrictedExpression,
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// A path-style resource name with `segments` directories
+#[expect(clippy::unwrap_used, reason = "writing to a `String` cannot fail")]
+fn path(segments: usize) -> String {
+    let mut path = (0..segments).fold(String::new(), |mut path, i| {
+        write!(path, "/directory-{i}").unwrap();
+        path
+    });
+    path.push_str("/report.pdf");
+    path
+}
+
+#[expect(clippy::unwrap_used, reason = "benchmarking")]
+pub fn like_pattern(c: &mut Criterion) {
+    let uid = EntityUid::from_str(r#"User::"alice""#).unwrap();
+    let entities = Entities::empty();
+    let mut group = c.benchmark_group("like_pattern");
+    for segments in [10, 100, 1000] {
+        let context = Context::from_pairs([(
+            "path".to_string(),
+            RestrictedExpression::new_string(path(segments)),
+        )])
+        .unwrap();
+        let request = Request::new(uid.clone(), uid.clone(), uid.clone(), context, None).unwrap();
+        for (name, pattern) in [
+            ("prefix", "/directory-0/*"),
+            ("suffix", "*/report.pdf"),
+            ("infix", "*/directory-5*/report*"),
+            ("no_match", "*/directory-*/summary.pdf"),
+        ] {
+            let expr = Expression::from_str(&format!("context.path like \"{pattern}\"")).unwrap();
+            group.bench_with_input(BenchmarkId::new(name, segments), &expr, |b, expr| {
+                b.iter(|| eval_expression(&request, &entities, black_box(expr)).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, like_pattern);
+criterion_main!(benches);
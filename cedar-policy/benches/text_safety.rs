@@ -0,0 +1,58 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::hint::black_box;
+
+use cedar_policy::{PolicySet, Validator};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const NUM_POLICIES: usize = 10_000;
+
+/// Policies exercising every kind of text the checks look at: entity uids in
+/// the scope, attribute names, string literals, and `like` patterns.
+fn synthetic_policies() -> String {
+    let policies: Vec<String> = (0..NUM_POLICIES)
+        .map(|i| {
+            format!(
+                r#"permit(
+                    principal == App::User::"user{i}",
+                    action in [App::Action::"view", App::Action::"edit"],
+                    resource in App::Folder::"folder{i}"
+                ) when {{
+                    principal.department == "dept{i}" &&
+                    resource.name like "report-{i}-*" &&
+                    context has "request{i}"
+                }};
+"#
+            )
+        })
+        .collect();
+    policies.concat()
+}
+
+#[expect(clippy::unwrap_used, reason = "benchmarking")]
+pub fn text_safety(c: &mut Criterion) {
+    let policy_set: PolicySet = synthetic_policies().parse().unwrap();
+    let validator = Validator::without_schema();
+
+    c.bench_function("text_safety_10k", |b| {
+        b.iter(|| validator.lint(black_box(&policy_set)))
+    });
+}
+
+criterion_group!(benches, text_safety);
+criterion_main!(benches);
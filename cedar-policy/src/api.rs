@@ -28,12 +28,17 @@ use cedar_policy_core::pst::error_body;
 #[cfg(feature = "entity-manifest")]
 use cedar_policy_core::validator::entity_manifest;
 // TODO (#1157) implement wrappers for these structs before they become public
+pub use cedar_policy_core::parser::{ParseLimit, ParseLimits, ParserOptions};
 #[cfg(feature = "entity-manifest")]
 pub use cedar_policy_core::validator::entity_manifest::{
     AccessTrie, EntityManifest, EntityRoot, Fields, RootAccessTrie,
 };
 use cedar_policy_core::validator::json_schema;
 use cedar_policy_core::validator::typecheck::{PolicyCheck, Typechecker};
+pub use cedar_policy_core::validator::AttributeRequirement;
+pub use cedar_policy_core::validator::UsageStats;
+pub use cedar_policy_core::validator::{CompatibilityReport, CompatibilityRule, Incompatibility};
+pub use cedar_policy_core::validator::{SchemaLimit, SchemaLimits};
 pub use id::*;
 
 #[cfg(feature = "deprecated-schema-compat")]
@@ -42,6 +47,27 @@ mod deprecated_schema_compat;
 mod err;
 pub use err::*;
 
+mod policy_annotations;
+pub use policy_annotations::{AnnotationEditError, MalformedAnnotationValue};
+mod expr_with_span;
+pub use expr_with_span::{ExprNodeKind, ExprWithSpan};
+#[cfg(feature = "fingerprint")]
+mod fingerprint;
+mod pattern;
+pub use pattern::Pattern;
+mod policy_builder;
+pub use policy_builder::PolicyBuilder;
+mod request_builder;
+pub use request_builder::{ConcreteRequestBuilder, ContextValue};
+mod schema_builder;
+pub use schema_builder::*;
+#[cfg(feature = "sarif")]
+mod sarif;
+#[cfg(feature = "sarif")]
+pub use sarif::SourceMap;
+mod schema_inference;
+pub use schema_inference::SchemaInferenceConflict;
+
 #[cfg(feature = "tpe")]
 mod tpe;
 #[cfg(feature = "tpe")]
@@ -65,6 +91,7 @@ use cedar_policy_core::FromNormalizedStr;
 use itertools::{Either, Itertools};
 use linked_hash_map::LinkedHashMap;
 use miette::Diagnostic;
+pub use parser::comments::{Comment, CommentPosition};
 use ref_cast::RefCast;
 use serde::{Deserialize, Serialize};
 use smol_str::SmolStr;
@@ -1517,6 +1544,101 @@ impl Validator {
         Self(cedar_policy_core::validator::Validator::new(schema.0))
     }
 
+    /// Construct a new `Validator` to validate policies using the schema in
+    /// `json`, which should be in the JSON schema format. This is equivalent
+    /// to [`Schema::from_json_value`] followed by [`Validator::new`], and
+    /// avoids serializing a programmatically generated schema to a string.
+    ///
+    /// Errors in the schema are returned as a [`SchemaError`] rather than in a
+    /// [`ValidationResult`], since they are not found in any policy and so
+    /// have no policy id. Like a `ValidationResult`, a `SchemaError`
+    /// implements [`miette::Diagnostic`], so both can be reported the same
+    /// way, and [`SchemaError::json_location()`] gives the JSON pointer to the
+    /// offending value in `json`.
+    ///
+    /// # Examples
+    /// ```
+    /// use cedar_policy::{PolicySet, Validator, ValidationMode};
+    /// let validator = Validator::from_json_schema(serde_json::json!({
+    ///     "": {
+    ///         "entityTypes": { "User": {} },
+    ///         "actions": {
+    ///             "view": {
+    ///                 "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["User"] }
+    ///             }
+    ///         }
+    ///     }
+    /// }))
+    /// .unwrap();
+    /// let pset: PolicySet = r#"permit(principal, action == Action::"view", resource);"#
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(validator.validate(&pset, ValidationMode::Strict).validation_passed());
+    /// ```
+    pub fn from_json_schema(json: serde_json::Value) -> Result<Self, SchemaError> {
+        Schema::from_json_value(json).map(Self::new)
+    }
+
+    /// Construct a `Validator` without a schema, for use with
+    /// [`Validator::lint()`]. Its [`Validator::schema()`] is empty, so
+    /// [`Validator::validate()`] will report every entity type and action as
+    /// unrecognized.
+    pub fn without_schema() -> Self {
+        Self(cedar_policy_core::validator::Validator::without_schema())
+    }
+
+    /// Enable or disable the [`PolicySetWarning::EmptyPolicySet`] warning,
+    /// which is reported when validating a [`PolicySet`] containing no
+    /// policies or templates. Since some pipelines legitimately validate empty
+    /// policy sets, it is disabled by default.
+    #[must_use]
+    pub fn with_empty_policy_set_warning(self, enabled: bool) -> Self {
+        Self(self.0.with_empty_policy_set_warning(enabled))
+    }
+
+    /// Enable or disable the [`ValidationWarning::OverlyPermissivePolicy`]
+    /// warning, which is reported for each `permit` policy with no scope
+    /// constraints and no conditions, i.e., which allows every request. Since
+    /// such policies are sometimes intended, it is disabled by default.
+    #[must_use]
+    pub fn with_overly_permissive_policy_warning(self, enabled: bool) -> Self {
+        Self(self.0.with_overly_permissive_policy_warning(enabled))
+    }
+
+    /// Enable or disable the [`ValidationWarning::AmbiguousPrecedence`]
+    /// warning, which is reported for each expression which mixes `&&` and
+    /// `||`, e.g., `a && b || c`, or applies `!` to the left operand of a
+    /// comparison, e.g., `!x == y`, without parentheses. The warning is purely
+    /// advisory: such policies are valid and are evaluated as written. It is
+    /// disabled by default.
+    #[must_use]
+    pub fn with_ambiguous_precedence_warning(self, enabled: bool) -> Self {
+        Self(self.0.with_ambiguous_precedence_warning(enabled))
+    }
+
+    /// Enable or disable reporting type errors in parts of a policy which are
+    /// never evaluated, for any request, because the validator knows from the
+    /// schema that an earlier operand of `&&` or `||`, or the guard of an
+    /// `if`, is always `true` or always `false`. For example, in
+    /// `resource is Albm && context.x`, `context.x` is normally not
+    /// typechecked if `Albm` is not an entity type, so fixing the misspelling
+    /// reveals a second error if `context` has no attribute `x`. With this
+    /// option, a single validation run reports both. Errors which only occur
+    /// for some requests, e.g., an access to an attribute of `principal`
+    /// guarded by `principal is User`, are still not reported. It is disabled
+    /// by default.
+    #[must_use]
+    pub fn with_unreachable_type_errors(self, enabled: bool) -> Self {
+        Self(self.0.with_unreachable_type_errors(enabled))
+    }
+
+    /// Get metadata about every kind of [`ValidationWarning`] the validator
+    /// can report, e.g., to generate documentation or configuration for
+    /// tools that filter warnings by code.
+    pub fn all_warning_kinds() -> &'static [WarningKindInfo] {
+        cedar_policy_core::validator::all_warning_kinds()
+    }
+
     /// Get the `Schema` this `Validator` is using.
     pub fn schema(&self) -> &Schema {
         RefCast::ref_cast(self.0.schema())
@@ -1529,8 +1651,321 @@ impl Validator {
     /// that policy passed the validator. If the function `validation_passed`
     /// returns true, then there were no validation errors found, so all
     /// policies in the policy set have passed the validator.
+    ///
+    /// A policy can suppress warnings found in it with a `@suppress`
+    /// annotation listing their codes (see [`ValidationWarning::kind_info`]),
+    /// separated by commas, e.g., `@suppress("confusable-identifier")`. This
+    /// also applies to [`Validator::lint`].
     pub fn validate(&self, pset: &PolicySet, mode: ValidationMode) -> ValidationResult {
-        ValidationResult::from(self.0.validate(&pset.ast, mode.into()))
+        ValidationResult::from(self.0.validate(&pset.ast, mode.into())).with_mode(mode)
+    }
+
+    /// Validate a template on its own, before it has any links, collecting
+    /// all validation errors and warnings found into the returned
+    /// `ValidationResult`.
+    ///
+    /// The body of the template is typechecked once for every entity type
+    /// each slot can take, according to the `appliesTo` of the actions the
+    /// template's scope allows, and an error is reported if it is ill-typed for
+    /// any of them. This is the same check [`Validator::validate`] applies to
+    /// the templates in a policy set.
+    ///
+    /// Checks on the entities a template is linked with are deferred until
+    /// the links are validated with [`Validator::validate`]: whether their
+    /// entity types are declared in the schema
+    /// ([`ValidationError::UnrecognizedEntityType`]), are valid enumerated
+    /// entities ([`ValidationError::InvalidEnumEntity`]), and are allowed by
+    /// the `appliesTo` of the template's actions
+    /// ([`ValidationError::InvalidActionApplication`]).
+    pub fn validate_template(&self, template: &Template, mode: ValidationMode) -> ValidationResult {
+        let mut pset = ast::PolicySet::new();
+        #[expect(
+            clippy::expect_used,
+            reason = "an empty policy set cannot already contain the template's id"
+        )]
+        pset.add_template(template.ast.clone())
+            .expect("failed to add template to an empty policy set");
+        ValidationResult::from(self.0.validate(&pset, mode.into())).with_mode(mode)
+    }
+
+    /// Validate entity data against the schema, collecting every problem found
+    /// into the returned [`EntityValidationResult`] rather than stopping at the
+    /// first. Each [`EntityValidationError`] is located by
+    /// [`EntityValidationError::entity_uid`].
+    ///
+    /// Action entities and entities whose type is not declared in the schema
+    /// are not checked. Use [`Entities::from_entities`] or another constructor
+    /// taking a schema to enforce full conformance.
+    pub fn validate_entities(&self, entities: &Entities) -> EntityValidationResult {
+        EntityValidationResult::from(self.0.validate_entities(&entities.0))
+    }
+
+    /// Run only the checks which do not require a schema, skipping the
+    /// typechecker: the checks for confusable, bidirectional control, and
+    /// mixed-script characters, the [`ValidationWarning::AmbiguousAttributeName`]
+    /// and [`ValidationWarning::SuspiciousLikePattern`] checks, and the
+    /// [`PolicySetWarning::EmptyPolicySet`] check. This is useful for policies which are not maintained alongside a
+    /// schema. The schema of this validator, if any, is ignored, so the result
+    /// never contains errors such as [`ValidationError::UnexpectedType`] or
+    /// [`ValidationError::UnrecognizedEntityType`].
+    pub fn lint(&self, pset: &PolicySet) -> ValidationResult {
+        ValidationResult::from(self.0.lint(&pset.ast))
+    }
+
+    /// Get the actions declared in the schema which `policy` could apply to,
+    /// given its scope constraints: those satisfying its action constraint
+    /// whose `appliesTo` includes some principal type and some resource type
+    /// satisfying its principal and resource constraints. For instance, an
+    /// authoring tool could use this to show which actions a policy governs.
+    ///
+    /// If there are none, this returns the
+    /// [`ValidationError::InvalidActionApplication`] error which
+    /// [`Validator::validate()`] reports for the policy, whose help suggests
+    /// replacing `==` with `in` in the scope constraints where that would fix
+    /// the policy.
+    pub fn applicable_actions(
+        &self,
+        policy: &Policy,
+    ) -> Result<HashSet<EntityUid>, validation_errors::InvalidActionApplication> {
+        self.0
+            .applicable_actions(&policy.ast)
+            .map(|actions| {
+                actions
+                    .into_iter()
+                    .map(|action| EntityUid::ref_cast(action).clone())
+                    .collect()
+            })
+            .map_err(Into::into)
+    }
+
+    /// Get the actions declared in the schema which no policy or template in
+    /// `pset` could apply to, in the sense of
+    /// [`Validator::applicable_actions()`]. For instance, this finds an action
+    /// which was added to the schema without writing any policy for it.
+    ///
+    /// Actions which apply to no principal type or no resource type, e.g.,
+    /// action groups without an `appliesTo`, are not included, since no
+    /// request can use them.
+    pub fn unused_actions(&self, pset: &PolicySet) -> HashSet<EntityUid> {
+        self.0
+            .unused_actions(&pset.ast)
+            .into_iter()
+            .map(|action| EntityUid::ref_cast(action).clone())
+            .collect()
+    }
+
+    /// Get the ids of the `forbid` policies in `pset` which can never apply
+    /// to the same request as any `permit` policy, so they can never override
+    /// a permit, e.g., because they apply to a different action, or their
+    /// conditions are always false.
+    ///
+    /// A forbid and a permit are considered to apply to the same request if
+    /// both could apply to some principal type, action and resource type in
+    /// the schema, and their scopes do not require the principal or resource
+    /// to be `==` different entities. The analysis is conservative: it does
+    /// not reason about entity hierarchies or about how the conditions of the
+    /// two policies interact, so a forbid which is not reported may still be
+    /// unable to override any permit.
+    ///
+    /// The ids are returned in order. Templates are not included, but
+    /// template-linked policies are.
+    ///
+    /// ```
+    /// # use cedar_policy::{PolicyId, PolicySet, Schema, Validator};
+    /// let (schema, _) = Schema::from_cedarschema_str(r"
+    ///     entity User;
+    ///     entity Doc;
+    ///     action read, delete appliesTo { principal: User, resource: Doc };
+    /// ").unwrap();
+    /// let pset = PolicySet::parse_with_id_annotations(r#"
+    ///     @id("read") permit(principal, action == Action::"read", resource);
+    ///     @id("secret") forbid(principal, action, resource) when { resource == Doc::"secret" };
+    ///     @id("delete") forbid(principal, action == Action::"delete", resource);
+    /// "#).unwrap();
+    /// assert_eq!(Validator::new(schema).orphaned_forbids(&pset), [PolicyId::new("delete")]);
+    /// ```
+    pub fn orphaned_forbids(&self, pset: &PolicySet) -> Vec<PolicyId> {
+        self.0
+            .orphaned_forbids(&pset.ast)
+            .into_iter()
+            .map(|id| PolicyId::ref_cast(id).clone())
+            .collect()
+    }
+
+    /// Reduce `policy`, which fails validation in `mode`, to a smaller policy
+    /// which still fails validation with the same kind of error, e.g., to
+    /// report a bug in a large policy. If the policy has several errors, the
+    /// one which starts earliest in its source text is kept, preferring errors
+    /// with a source location.
+    ///
+    /// The policy is reduced by removing its annotations, scope constraints
+    /// and conditions, replacing subexpressions with one of their operands,
+    /// and removing elements of set and record literals, for as long as any
+    /// of these changes keeps the error. The result has the same id as
+    /// `policy`. A template-linked policy is reduced to a static policy, with
+    /// its slots filled in.
+    ///
+    /// Returns `None` if `policy` has no validation errors.
+    ///
+    /// ```
+    /// # use cedar_policy::{Policy, Schema, ValidationMode, Validator};
+    /// let (schema, _) = Schema::from_cedarschema_str(r"
+    ///     entity User { age: Long };
+    ///     entity Doc { public: Bool };
+    ///     action read appliesTo { principal: User, resource: Doc };
+    /// ").unwrap();
+    /// let policy: Policy = r#"
+    ///     permit(principal, action == Action::"read", resource)
+    ///     when { resource.public && principal.age > 18 && resource.owner == principal };
+    /// "#.parse().unwrap();
+    /// let minimized = Validator::new(schema)
+    ///     .minimize_failure(&policy, ValidationMode::Strict)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     minimized.to_string(),
+    ///     "permit(principal, action, resource) when { resource.owner };"
+    /// );
+    /// ```
+    pub fn minimize_failure(&self, policy: &Policy, mode: ValidationMode) -> Option<Policy> {
+        self.0
+            .minimize_failure(&policy.ast, mode.into())
+            .map(Policy::from)
+    }
+
+    /// Parse and validate the policies in `text`, collecting all parse and
+    /// validation errors found into the returned `ValidationResult`.
+    ///
+    /// Unlike parsing with [`PolicySet::from_str()`] before validating, a policy
+    /// which fails to parse does not prevent validating the rest. Each parse
+    /// error is reported as a [`ValidationError::ParseError`] for the failing
+    /// policy, and the errors and warnings for the other policies are reported
+    /// as usual. This is useful for editors, which should report as many
+    /// problems as possible while a policy is being written.
+    ///
+    /// Policies are given the same numbered ids as with
+    /// [`PolicySet::from_str()`], i.e., `policy0`, `policy1`, etc.
+    pub fn validate_policies_str(&self, text: &str, mode: ValidationMode) -> ValidationResult {
+        ValidationResult::from(self.0.validate_policies_str(text, mode.into())).with_mode(mode)
+    }
+
+    /// Validate all policies in a policy set, like [`Validator::validate`],
+    /// and also collect statistics about how often each entity type and
+    /// action is referenced by the policies, and how many validation errors
+    /// those policies have.
+    ///
+    /// This is useful for finding the parts of a schema which are unused, or
+    /// which are the most common source of validation errors.
+    pub fn validate_with_stats(
+        &self,
+        pset: &PolicySet,
+        mode: ValidationMode,
+    ) -> (ValidationResult, ValidationStats) {
+        let (result, stats) = self.0.validate_with_stats(&pset.ast, mode.into());
+        (
+            ValidationResult::from(result).with_mode(mode),
+            ValidationStats(stats),
+        )
+    }
+
+    /// Validate a single policy and, if validation passes, return it annotated
+    /// with the type the validator inferred for each of its expressions, e.g.,
+    /// to show the type of the expression under the cursor in an editor.
+    /// Otherwise, return the validation errors and warnings for the policy.
+    ///
+    /// A template-linked policy is validated and annotated as its template.
+    /// Checks which apply to a whole policy set, like the checks for
+    /// confusable characters, are not run.
+    pub fn validate_and_annotate(
+        &self,
+        policy: &Policy,
+        mode: ValidationMode,
+    ) -> Result<TypedPolicy, ValidationResult> {
+        self.0
+            .validate_and_annotate(policy.ast.template(), mode.into())
+            .map(TypedPolicy)
+            .map_err(|result| ValidationResult::from(result).with_mode(mode))
+    }
+
+    /// Get the names which can replace the name at `offset` in the source of
+    /// `policy`, e.g., to suggest completions in an editor.
+    ///
+    /// `offset` is an offset into the source text `policy` was parsed from,
+    /// as for [`TypedPolicy::type_at`]. It must be inside or at the end of the
+    /// attribute name of an attribute access written with dot syntax
+    /// (`e.attr`) or of a `has` expression, or of the entity type of an `is`
+    /// expression. Since `policy` must parse, this is typically a partially
+    /// typed name, like `principal.na`. The completions are the attributes
+    /// and methods of the type of `e`, or the entity types declared in the
+    /// schema. They are sorted by kind and then by label, and are not
+    /// filtered by the name already written. Returns no completions if
+    /// `offset` is not in such a name.
+    ///
+    /// Only complete policies are supported, so there are no completions for
+    /// text which does not parse, such as `principal.` with no name after the
+    /// dot, or a policy missing its closing `}` or `;`. An editor can
+    /// complete such text by inserting a placeholder name, e.g., parsing
+    /// `principal.x` with `offset` just before the `x`.
+    ///
+    /// ```
+    /// # use cedar_policy::{CompletionKind, Policy, Schema, Validator};
+    /// let schema: Schema = r"
+    ///     entity User { name: String, age: Long };
+    ///     action view appliesTo { principal: User, resource: User };
+    /// "
+    /// .parse()
+    /// .unwrap();
+    /// let src = r#"permit(principal, action, resource) when { principal.na == "alice" };"#;
+    /// let policy = Policy::parse(None, src).unwrap();
+    /// let offset = src.find("principal.na").unwrap() + "principal.na".len();
+    /// let validator = Validator::new(schema);
+    /// let completions = validator.completions_at(&policy, offset);
+    /// let name = completions.iter().find(|c| c.label() == "name").unwrap();
+    /// assert_eq!(name.kind(), CompletionKind::Attribute);
+    /// assert_eq!(name.ty().as_deref(), Some("String"));
+    ///
+    /// // `principal.` does not parse, so complete a placeholder name instead
+    /// let src = r#"permit(principal, action, resource) when { principal.x };"#;
+    /// let policy = Policy::parse(None, src).unwrap();
+    /// let offset = src.find("principal.x").unwrap() + "principal.".len();
+    /// let completions = validator.completions_at(&policy, offset);
+    /// assert!(completions.iter().any(|c| c.label() == "age"));
+    /// ```
+    pub fn completions_at(&self, policy: &Policy, offset: usize) -> Vec<Completion> {
+        self.0
+            .completions_at(policy.ast.template(), offset)
+            .into_iter()
+            .map(Completion)
+            .collect()
+    }
+
+    /// Infer the entity types, attributes, and actions a schema must declare
+    /// for `policy`, and the minimal types of the attributes, from how
+    /// `policy` uses them. For instance, `principal.age >= 18` requires a
+    /// `Long` attribute `age`. This is useful for bootstrapping a schema from
+    /// existing policies.
+    ///
+    /// This is inference, not checking: the schema of this validator, if any,
+    /// is ignored, and no errors are reported. A template-linked policy is
+    /// inferred from its template.
+    ///
+    /// ```
+    /// # use cedar_policy::{EntityTypeName, Policy, Validator};
+    /// # use std::str::FromStr;
+    /// let policy = Policy::parse(
+    ///     None,
+    ///     r#"permit(principal is User, action == Action::"view", resource)
+    ///     when { principal.age >= 18 && context.tags.contains("public") };"#,
+    /// )
+    /// .unwrap();
+    /// let requirements = Validator::without_schema().inferred_requirements(&policy);
+    /// let user = EntityTypeName::from_str("User").unwrap();
+    /// let (name, attr) = requirements.entity_type_attributes(&user).next().unwrap();
+    /// assert_eq!((name, attr.ty().as_deref()), ("age", Some("Long")));
+    /// let (name, attr) = requirements.context_attributes().next().unwrap();
+    /// assert_eq!((name, attr.ty().as_deref()), ("tags", Some("Set<String>")));
+    /// ```
+    pub fn inferred_requirements(&self, policy: &Policy) -> SchemaRequirements {
+        SchemaRequirements(self.0.inferred_requirements(policy.ast.template()))
     }
 
     /// Validate all policies in a policy set, collecting all validation errors
@@ -1550,6 +1985,7 @@ impl Validator {
             self.0
                 .validate_with_level(&pset.ast, mode.into(), max_deref_level),
         )
+        .with_mode(mode)
     }
 }
 
@@ -1624,6 +2060,32 @@ fn annotations_to_pairs(annotations: &est::Annotations) -> impl Iterator<Item =
         .map(|(key, value)| (key.as_ref(), annotation_value_to_str_ref(value.as_ref())))
 }
 
+/// Order the annotations of a policy or template as they appear in the source
+/// text. Annotations without a source location, e.g., those added with
+/// [`Policy::set_annotation`], come last, in order of their keys.
+fn annotations_in_source_order<'a>(
+    annotations: impl Iterator<Item = (&'a ast::AnyId, &'a ast::Annotation)>,
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+    annotations
+        .sorted_by_key(|(_, value)| value.loc.as_ref().map_or(usize::MAX, parser::Loc::start))
+        .map(|(key, value)| (key.as_ref(), value.as_ref()))
+}
+
+/// Get the declaration of `attr` in an entity type shape or action context,
+/// if it is declared directly as a record rather than via a common type
+fn get_record_attribute<'a, N>(
+    shape: &'a json_schema::AttributesOrContext<N>,
+    attr: &str,
+) -> Option<&'a json_schema::TypeOfAttribute<N>> {
+    match &shape.0 {
+        json_schema::Type::Type {
+            ty: json_schema::TypeVariant::Record(record),
+            ..
+        } => record.attributes.get(attr),
+        _ => None,
+    }
+}
+
 impl SchemaFragment {
     /// Get annotations of a non-empty namespace.
     ///
@@ -1769,6 +2231,106 @@ impl SchemaFragment {
         )
     }
 
+    /// Get annotations of an attribute declaration of an entity type, e.g.,
+    /// to display the `@doc` annotation of the attribute in authoring tools
+    ///
+    /// Only attributes declared directly in the entity type declaration are
+    /// found, not those declared via a common type.
+    ///
+    /// Returns `None` if `namespace` is not found in the [`SchemaFragment`]
+    /// or `ty` is not a valid entity type name
+    /// or `ty` is not found in the corresponding namespace definition
+    /// or `attr` is not declared for `ty`
+    pub fn entity_attribute_annotations(
+        &self,
+        namespace: Option<EntityNamespace>,
+        ty: &str,
+        attr: &str,
+    ) -> Option<impl Iterator<Item = (&str, &str)>> {
+        let ns_def = self.lossless.0.get(&namespace.map(|n| n.0))?;
+        let ty = ast::UnreservedId::from_normalized_str(ty).ok()?;
+        let json_schema::EntityTypeKind::Standard(ty) = &ns_def.entity_types.get(&ty)?.kind else {
+            return None;
+        };
+        get_record_attribute(&ty.shape, attr).map(|attr| annotations_to_pairs(&attr.annotations))
+    }
+
+    /// Get annotation value of an attribute declaration of an entity type by
+    /// annotation key `annotation_key`
+    ///
+    /// Only attributes declared directly in the entity type declaration are
+    /// found, not those declared via a common type.
+    ///
+    /// Returns `None` if `namespace` is not found in the [`SchemaFragment`]
+    /// or `ty` is not a valid entity type name
+    /// or `ty` is not found in the corresponding namespace definition
+    /// or `attr` is not declared for `ty`
+    /// or `annotation_key` is not a valid annotation key
+    /// or it does not exist
+    pub fn entity_attribute_annotation(
+        &self,
+        namespace: Option<EntityNamespace>,
+        ty: &str,
+        attr: &str,
+        annotation_key: impl AsRef<str>,
+    ) -> Option<&str> {
+        let ns_def = self.lossless.0.get(&namespace.map(|n| n.0))?;
+        let ty = ast::UnreservedId::from_normalized_str(ty).ok()?;
+        let json_schema::EntityTypeKind::Standard(ty) = &ns_def.entity_types.get(&ty)?.kind else {
+            return None;
+        };
+        get_annotation_by_key(
+            &get_record_attribute(&ty.shape, attr)?.annotations,
+            annotation_key,
+        )
+    }
+
+    /// Get annotations of a context attribute declaration of an action
+    ///
+    /// Only attributes declared directly in the action declaration are found,
+    /// not those declared via a common type.
+    ///
+    /// Returns `None` if `namespace` is not found in the [`SchemaFragment`]
+    /// or `id` is not found in the corresponding namespace definition
+    /// or `attr` is not declared in the context of `id`
+    pub fn context_attribute_annotations(
+        &self,
+        namespace: Option<EntityNamespace>,
+        id: &EntityId,
+        attr: &str,
+    ) -> Option<impl Iterator<Item = (&str, &str)>> {
+        let ns_def = self.lossless.0.get(&namespace.map(|n| n.0))?;
+        let applies_to = ns_def.actions.get(id.unescaped())?.applies_to.as_ref()?;
+        get_record_attribute(&applies_to.context, attr)
+            .map(|attr| annotations_to_pairs(&attr.annotations))
+    }
+
+    /// Get annotation value of a context attribute declaration of an action by
+    /// annotation key `annotation_key`
+    ///
+    /// Only attributes declared directly in the action declaration are found,
+    /// not those declared via a common type.
+    ///
+    /// Returns `None` if `namespace` is not found in the [`SchemaFragment`]
+    /// or `id` is not found in the corresponding namespace definition
+    /// or `attr` is not declared in the context of `id`
+    /// or `annotation_key` is not a valid annotation key
+    /// or it does not exist
+    pub fn context_attribute_annotation(
+        &self,
+        namespace: Option<EntityNamespace>,
+        id: &EntityId,
+        attr: &str,
+        annotation_key: impl AsRef<str>,
+    ) -> Option<&str> {
+        let ns_def = self.lossless.0.get(&namespace.map(|n| n.0))?;
+        let applies_to = ns_def.actions.get(id.unescaped())?.applies_to.as_ref()?;
+        get_annotation_by_key(
+            &get_record_attribute(&applies_to.context, attr)?.annotations,
+            annotation_key,
+        )
+    }
+
     /// Extract namespaces defined in this [`SchemaFragment`].
     ///
     /// `None` indicates the empty namespace.
@@ -1903,8 +2465,13 @@ impl FromStr for SchemaFragment {
 }
 
 /// Object containing schema information used by the validator.
+///
+/// Two schemas compare equal if they declare the same entity types, actions,
+/// and types after common types are inlined and names are fully qualified,
+/// even if their source was formatted differently or split into fragments
+/// differently.
 #[repr(transparent)]
-#[derive(Debug, Clone, RefCast)]
+#[derive(Debug, Clone, PartialEq, Eq, RefCast)]
 pub struct Schema(pub(crate) cedar_policy_core::validator::ValidatorSchema);
 
 #[doc(hidden)] // because this converts to a private/internal type
@@ -1962,6 +2529,41 @@ impl Schema {
         ))
     }
 
+    /// Create a [`Schema`] from a JSON value, like [`Schema::from_json_value`],
+    /// but fail with [`SchemaError::LimitExceeded`] if the schema exceeds any
+    /// of `limits`. Use this for schemas from untrusted sources.
+    ///
+    /// The limits are checked before the schema is constructed, so an
+    /// oversized schema is rejected without building it.
+    ///
+    /// # Examples
+    /// ```
+    /// use cedar_policy::{Schema, SchemaError, SchemaLimit, SchemaLimits};
+    /// let json = serde_json::json!({
+    ///     "": { "entityTypes": { "A": {}, "B": {} }, "actions": {} }
+    /// });
+    /// let limits = SchemaLimits::default().with_max_entity_types(1);
+    /// match Schema::from_json_value_with_limits(json, &limits) {
+    ///     Err(SchemaError::LimitExceeded(e)) => {
+    ///         assert_eq!(e.limit(), SchemaLimit::EntityTypes);
+    ///         assert_eq!(e.declaration(), "entity type `B`");
+    ///     }
+    ///     _ => panic!("expected the limit to be exceeded"),
+    /// }
+    /// ```
+    pub fn from_json_value_with_limits(
+        json: serde_json::Value,
+        limits: &SchemaLimits,
+    ) -> Result<Self, SchemaError> {
+        Ok(Self(
+            cedar_policy_core::validator::ValidatorSchema::from_json_value_with_limits(
+                json,
+                Extensions::all_available(),
+                limits,
+            )?,
+        ))
+    }
+
     /// Create a [`Schema`] from a string containing JSON in the appropriate
     /// shape.
     pub fn from_json_str(json: &str) -> Result<Self, SchemaError> {
@@ -2119,6 +2721,34 @@ impl Schema {
             .map(|iter| iter.map(RefCast::ref_cast))
     }
 
+    /// Returns an iterator over all the entity types that can be a descendant of `ty`,
+    /// i.e., all the entity types whose entities can be members of an entity
+    /// of type `ty`, directly or transitively
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`None`] if the `ty` is not found in the schema
+    pub fn descendants<'a>(
+        &'a self,
+        ty: &'a EntityTypeName,
+    ) -> Option<impl Iterator<Item = &'a EntityTypeName> + 'a> {
+        self.0
+            .descendants(&ty.0)
+            .map(|iter| iter.map(RefCast::ref_cast))
+    }
+
+    /// Returns `true` if an entity of type `from` can be a member of an entity
+    /// of type `to`, directly or transitively, according to the
+    /// `memberOfTypes` declared in the schema. Returns `false` if either type
+    /// is not found in the schema.
+    ///
+    /// If this is `false`, then an `in` expression whose left operand has type
+    /// `from` and whose right operand has type `to` can only be `true` when
+    /// `from` and `to` are the same type and the two entities are equal.
+    pub fn hierarchy_path_exists(&self, from: &EntityTypeName, to: &EntityTypeName) -> bool {
+        self.0.hierarchy_path_exists(&from.0, &to.0)
+    }
+
     /// Returns an iterator over all the action groups defined in this schema
     pub fn action_groups(&self) -> impl Iterator<Item = &EntityUid> {
         self.0.action_groups().map(RefCast::ref_cast)
@@ -2136,6 +2766,56 @@ impl Schema {
         self.0.actions().map(RefCast::ref_cast)
     }
 
+    /// Returns warnings for declarations in this schema which no policy can
+    /// meaningfully use: actions (other than action groups) applying to no
+    /// principal or no resource types, actions whose context attributes are
+    /// all optional, and entity types which are neither a principal or
+    /// resource type of any action, nor a `memberOfTypes` ancestor of one.
+    ///
+    /// Each warning reports the namespace and name of the declaration.
+    pub fn unusable_declaration_warnings(&self) -> impl Iterator<Item = SchemaWarning> + '_ {
+        self.0.unusable_declaration_warnings()
+    }
+
+    /// Like [`Schema::unusable_declaration_warnings`], but return an error
+    /// if there are any such warnings, e.g., to reject these schemas in CI.
+    pub fn deny_unusable_declarations(&self) -> Result<(), SchemaError> {
+        self.0.deny_unusable_declarations()
+    }
+
+    /// Check whether this schema is backward compatible with the `old`
+    /// schema, e.g., before deploying it in place of `old`. The report lists
+    /// each change which may break policies validated against `old`, such as
+    /// removing a declaration, making a required attribute optional, narrowing
+    /// the `appliesTo` of an action, or changing the type of an attribute.
+    pub fn is_backward_compatible_with(&self, old: &Self) -> CompatibilityReport {
+        self.0.is_backward_compatible_with(&old.0)
+    }
+
+    /// Like [`Schema::is_backward_compatible_with`], but also validate
+    /// `policies` against this schema, since an incompatible change may not
+    /// actually affect any policy.
+    pub fn check_rollout(
+        &self,
+        old: &Self,
+        policies: &PolicySet,
+        mode: ValidationMode,
+    ) -> (CompatibilityReport, ValidationResult) {
+        let report = self.is_backward_compatible_with(old);
+        let result = Validator::new(self.clone()).validate(policies, mode);
+        (report, result)
+    }
+
+    /// Returns the names of all extension types which may be used in schemas,
+    /// e.g., `ipaddr` and `decimal`. This is useful for schema tooling, e.g.,
+    /// to offer completions. A schema declaring an attribute with any other
+    /// extension type is rejected when it is constructed.
+    pub fn extension_type_names() -> impl Iterator<Item = String> {
+        Extensions::all_available()
+            .ext_types()
+            .map(ToString::to_string)
+    }
+
     /// Returns an iterator over the actions that apply to this principal and
     /// resource type, as specified by the `appliesTo` block for the action in
     /// this schema.
@@ -2148,6 +2828,70 @@ impl Schema {
             .actions_for_principal_and_resource(&principal_type.0, &resource_type.0)
             .map(RefCast::ref_cast)
     }
+
+    /// Get a [JSON Schema](https://json-schema.org/) (draft 2020-12) document
+    /// describing the context of requests for `action`, in the JSON format
+    /// accepted by [`Context::from_json_value`].
+    ///
+    /// Records become objects listing their required attributes in
+    /// `required`, sets become arrays, and entity references become objects
+    /// with string `type` and `id` properties. Extension values are expected
+    /// to be strings and are annotated with a `format` naming the extension
+    /// type, e.g., `"ipaddr"` or `"decimal"`.
+    ///
+    /// Returns `None` if the action is not declared in this schema.
+    pub fn context_json_schema(&self, action: &EntityUid) -> Option<serde_json::Value> {
+        self.0.context_json_schema(&action.0)
+    }
+
+    /// Get a canonical serialization of this schema in the JSON schema format,
+    /// e.g., for hashing. Two schemas produce byte-identical output exactly
+    /// when they compare equal.
+    ///
+    /// Common types are inlined, entity type names are fully qualified, every
+    /// optional field is written out, and `memberOfTypes` and `memberOf` list
+    /// all ancestors rather than only direct parents. Object keys and lists are
+    /// sorted, except for the ids of enumerated entity types. Annotations are
+    /// not included. The output can be parsed back into an equal schema.
+    pub fn canonical_json(&self) -> String {
+        self.0.canonical_json().to_string()
+    }
+
+    /// Generate example entities conforming to this schema, including the
+    /// action entities declared in the schema.
+    ///
+    /// The result is deterministic for a given `seed`. This is intended for
+    /// producing test fixtures, e.g., to exercise policies validated against
+    /// this schema.
+    ///
+    /// # Examples
+    /// ```
+    /// use cedar_policy::{EntityUid, Schema};
+    /// let schema : Schema = r#"
+    ///     entity User { name: String };
+    ///     entity Photo { owner: User };
+    ///     action view appliesTo { principal: User, resource: Photo };
+    /// "#.parse().unwrap();
+    /// let entities = schema.example_entities(0).unwrap();
+    /// let alice: EntityUid = r#"User::"example-0""#.parse().unwrap();
+    /// assert!(entities.get(&alice).is_some());
+    /// ```
+    pub fn example_entities(&self, seed: u64) -> Result<Entities, ExampleDataError> {
+        Ok(Entities(self.0.example_entities(seed)?))
+    }
+
+    /// Generate an example [`Request`] for `action` conforming to this schema.
+    ///
+    /// The principal and resource are always entities returned by
+    /// [`Schema::example_entities`], so the two can be used together. The
+    /// result is deterministic for a given `seed`.
+    pub fn example_request(
+        &self,
+        action: &EntityUid,
+        seed: u64,
+    ) -> Result<Request, ExampleDataError> {
+        Ok(Request(self.0.example_request(&action.0, seed)?))
+    }
 }
 
 /// Convert a Cedar schema string to JSON format with resolved types.
@@ -2290,6 +3034,12 @@ mod test_schema_str_to_json_with_resolved_types {
 pub struct ValidationResult {
     validation_errors: Vec<ValidationError>,
     validation_warnings: Vec<ValidationWarning>,
+    policy_set_warnings: Vec<PolicySetWarning>,
+    policy_spans: HashMap<PolicyId, miette::SourceSpan>,
+    policy_effects: HashMap<PolicyId, Effect>,
+    /// Position of each validated policy or template in the policy set
+    policy_indices: HashMap<PolicyId, usize>,
+    mode: Option<ValidationMode>,
 }
 
 impl ValidationResult {
@@ -2301,9 +3051,12 @@ impl ValidationResult {
     }
 
     /// True when validation passes (i.e., there are no errors) and there are
-    /// additionally no non-fatal warnings.
+    /// additionally no non-fatal warnings, including
+    /// [`ValidationResult::policy_set_warnings`].
     pub fn validation_passed_without_warnings(&self) -> bool {
-        self.validation_errors.is_empty() && self.validation_warnings.is_empty()
+        self.validation_errors.is_empty()
+            && self.validation_warnings.is_empty()
+            && self.policy_set_warnings.is_empty()
     }
 
     /// Get an iterator over the errors found by the validator.
@@ -2316,17 +3069,198 @@ impl ValidationResult {
         self.validation_warnings.iter()
     }
 
-    fn first_error_or_warning(&self) -> Option<&dyn Diagnostic> {
+    /// Get an iterator over the warnings found by the validator about the
+    /// policy set as a whole, rather than any one policy, such as
+    /// [`PolicySetWarning::EmptyPolicySet`]. These are not included in
+    /// [`ValidationResult::validation_warnings`] or in the issues yielded by
+    /// iterating over this result.
+    pub fn policy_set_warnings(&self) -> impl Iterator<Item = &PolicySetWarning> {
+        self.policy_set_warnings.iter()
+    }
+
+    /// Get the error found by the validator that occurs earliest in the
+    /// source, if any.
+    ///
+    /// Errors are ordered by the position of their policy in the validated
+    /// policy set, which is source order for a policy set parsed from a single
+    /// text, then by the start of their own source location within the
+    /// policy. Errors without a source location come after the located errors
+    /// of the same policy.
+    pub fn first_error(&self) -> Option<&ValidationError> {
         self.validation_errors
-            .first()
+            .iter()
+            .min_by(|a, b| self.source_position_cmp(*a, a.policy_id(), *b, b.policy_id()))
+    }
+
+    /// Get the warning found by the validator that occurs earliest in the
+    /// source, if any. Warnings are ordered in the same way as for
+    /// [`ValidationResult::first_error`].
+    pub fn first_warning(&self) -> Option<&ValidationWarning> {
+        self.validation_warnings
+            .iter()
+            .min_by(|a, b| self.source_position_cmp(*a, a.policy_id(), *b, b.policy_id()))
+    }
+
+    /// Get the source span of the whole text of the policy or template with
+    /// id `policy_id`, if it was validated and was parsed from text.
+    ///
+    /// The labels of an error or warning only cover the part of the policy
+    /// which caused it. Together with the `policy_id()` of the error or
+    /// warning, this lets tools also show the enclosing policy, e.g., to
+    /// underline the offending token and shade the rest of the policy. A
+    /// template-linked policy has the span of its template.
+    pub fn policy_span(&self, policy_id: &PolicyId) -> Option<miette::SourceSpan> {
+        self.policy_spans.get(policy_id).copied()
+    }
+
+    /// Get the effect of the policy or template with id `policy_id`, if it
+    /// was validated
+    pub fn policy_effect(&self, policy_id: &PolicyId) -> Option<Effect> {
+        self.policy_effects.get(policy_id).copied()
+    }
+
+    /// Get the [`ValidationMode`] the policies were typechecked in, e.g., to
+    /// invalidate a cached result when the mode changes. Returns `None` if
+    /// this result was not produced by typechecking policies, as for the
+    /// result of [`Validator::lint`].
+    pub fn mode(&self) -> Option<ValidationMode> {
+        self.mode
+    }
+
+    /// Record that the policies were typechecked in `mode`
+    fn with_mode(mut self, mode: ValidationMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Get an iterator over the errors found by the validator in policies and
+    /// templates with this `effect`, e.g., to review errors in `forbid`
+    /// policies first.
+    pub fn errors_for_effect(&self, effect: Effect) -> impl Iterator<Item = &ValidationError> {
+        self.validation_errors
+            .iter()
+            .filter(move |e| self.policy_effect(e.policy_id()) == Some(effect))
+    }
+
+    /// Panic if validation failed, with a message listing all the errors
+    /// found by the validator. Does nothing if validation passed (warnings
+    /// are ignored).
+    ///
+    /// This is intended for use in tests, in place of
+    /// `assert!(result.validation_passed())`.
+    #[cfg(feature = "testing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+    #[track_caller]
+    #[expect(
+        clippy::panic,
+        reason = "this is an assertion helper for tests, so panicking is the intended behavior"
+    )]
+    pub fn assert_valid(&self) {
+        if self.validation_passed() {
+            return;
+        }
+        let errors = self
+            .validation_errors
+            .iter()
+            .map(|err| format!("  - {err}"))
+            .join("\n");
+        panic!(
+            "validation failed with {} error(s):\n{errors}",
+            self.validation_errors.len()
+        );
+    }
+
+    /// Append an error to this result, e.g., from a custom lint pass run
+    /// after the validator.
+    pub fn push_error(&mut self, error: ValidationError) {
+        self.validation_errors.push(error);
+    }
+
+    /// Append a warning to this result, e.g., from a custom lint pass run
+    /// after the validator.
+    pub fn push_warning(&mut self, warning: ValidationWarning) {
+        self.validation_warnings.push(warning);
+    }
+
+    /// Append all of `errors` to this result.
+    pub fn extend_errors(&mut self, errors: impl IntoIterator<Item = ValidationError>) {
+        self.validation_errors.extend(errors);
+    }
+
+    /// Append all of `warnings` to this result.
+    pub fn extend_warnings(&mut self, warnings: impl IntoIterator<Item = ValidationWarning>) {
+        self.validation_warnings.extend(warnings);
+    }
+
+    /// Append all the errors and warnings of `other` to this result, e.g., to
+    /// accumulate the results of several validation passes. The
+    /// [`ValidationResult::mode`] of this result is kept, unless it is `None`.
+    pub fn extend_from(&mut self, other: Self) {
+        self.validation_errors.extend(other.validation_errors);
+        self.validation_warnings.extend(other.validation_warnings);
+        self.policy_set_warnings.extend(other.policy_set_warnings);
+        self.policy_spans.extend(other.policy_spans);
+        self.policy_effects.extend(other.policy_effects);
+        self.mode = self.mode.or(other.mode);
+    }
+
+    /// Retain only the errors for which `f` returns `true`, e.g., to only
+    /// keep errors for policies from a particular file. Like [`Vec::retain`],
+    /// the errors which are kept stay in their original order.
+    /// [`ValidationResult::validation_passed`] reflects the errors which
+    /// remain.
+    pub fn retain_errors(&mut self, f: impl FnMut(&ValidationError) -> bool) {
+        self.validation_errors.retain(f);
+    }
+
+    /// Retain only the warnings for which `f` returns `true`. Like
+    /// [`Vec::retain`], the warnings which are kept stay in their original
+    /// order.
+    pub fn retain_warnings(&mut self, f: impl FnMut(&ValidationWarning) -> bool) {
+        self.validation_warnings.retain(f);
+    }
+
+    fn first_error_or_warning(&self) -> Option<&dyn Diagnostic> {
+        self.first_error()
             .map(|e| e as &dyn Diagnostic)
+            .or_else(|| self.first_warning().map(|w| w as &dyn Diagnostic))
             .or_else(|| {
-                self.validation_warnings
+                self.policy_set_warnings
                     .first()
                     .map(|w| w as &dyn Diagnostic)
             })
     }
 
+    /// Compare two validation diagnostics by the position of their policy in
+    /// the policy set, then by the start of their own source location. See
+    /// [`ValidationResult::first_error`].
+    fn source_position_cmp(
+        &self,
+        a: &dyn Diagnostic,
+        a_policy: &PolicyId,
+        b: &dyn Diagnostic,
+        b_policy: &PolicyId,
+    ) -> std::cmp::Ordering {
+        fn start(diagnostic: &dyn Diagnostic) -> Option<usize> {
+            diagnostic
+                .labels()
+                .and_then(|labels| labels.map(|label| label.offset()).min())
+        }
+        /// `None` compares greater than any `Some`
+        fn none_last(a: Option<usize>, b: Option<usize>) -> std::cmp::Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            }
+        }
+        let index = |id: &PolicyId| self.policy_indices.get(id).copied();
+        none_last(index(a_policy), index(b_policy))
+            .then_with(|| none_last(start(a), start(b)))
+            .then_with(|| a_policy.cmp(b_policy))
+    }
+
     pub(crate) fn into_errors_and_warnings(
         self,
     ) -> (
@@ -2340,13 +3274,210 @@ impl ValidationResult {
     }
 }
 
+/// Per-entity-type and per-action statistics collected while validating a
+/// policy set, returned by [`Validator::validate_with_stats`].
+///
+/// A policy references an entity type or action if it appears in the policy
+/// scope or in an entity literal or `is` expression in the policy conditions.
+/// Errors for a template-linked policy are counted towards its template.
+/// Every entity type and action declared in the schema is included, even if
+/// no policy references it, as well as any undeclared entity types and actions
+/// which are referenced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationStats(cedar_policy_core::validator::ValidationStats);
+
+impl ValidationStats {
+    /// Statistics for each entity type, ordered by name
+    pub fn entity_types(&self) -> impl Iterator<Item = (&EntityTypeName, UsageStats)> {
+        self.0
+            .entity_types()
+            .map(|(ety, stats)| (EntityTypeName::ref_cast(ety), *stats))
+    }
+
+    /// Statistics for each action, ordered by name
+    pub fn actions(&self) -> impl Iterator<Item = (&EntityUid, UsageStats)> {
+        self.0
+            .actions()
+            .map(|(action, stats)| (EntityUid::ref_cast(action), *stats))
+    }
+
+    /// Statistics for the entity type `ety`, or `None` if it is neither
+    /// declared in the schema nor referenced by any policy
+    pub fn entity_type(&self, ety: &EntityTypeName) -> Option<UsageStats> {
+        self.0.entity_type(&ety.0).copied()
+    }
+
+    /// Statistics for the action `action`, or `None` if it is neither declared
+    /// in the schema nor referenced by any policy
+    pub fn action(&self, action: &EntityUid) -> Option<UsageStats> {
+        self.0.action(&action.0).copied()
+    }
+}
+
+/// A policy which passed validation, annotated with the type the validator
+/// inferred for each of its expressions, returned by
+/// [`Validator::validate_and_annotate`].
+#[derive(Debug, Clone)]
+pub struct TypedPolicy(cedar_policy_core::validator::TypedPolicy);
+
+impl TypedPolicy {
+    /// The id of the annotated policy
+    pub fn policy_id(&self) -> &PolicyId {
+        PolicyId::ref_cast(self.0.policy_id())
+    }
+
+    /// Get the type, in Cedar schema syntax, of the innermost expression whose
+    /// source span contains `span`. Returns `None` if no expression contains
+    /// `span`, or if the expression has incompatible types for different
+    /// principal, action, and resource types, e.g., `principal` in a policy
+    /// which applies to two different principal types.
+    pub fn type_at(&self, span: miette::SourceSpan) -> Option<String> {
+        self.0.type_at(span).map(ToString::to_string)
+    }
+
+    /// Get an iterator over the warnings found by the validator
+    pub fn validation_warnings(&self) -> impl Iterator<Item = ValidationWarning> + '_ {
+        self.0
+            .validation_warnings()
+            .cloned()
+            .map(ValidationWarning::from)
+    }
+}
+
+/// What a [`Completion`] completes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum CompletionKind {
+    /// An attribute of an entity or record
+    Attribute,
+    /// A built-in or extension method
+    Method,
+    /// An entity type declared in the schema
+    EntityType,
+}
+
+#[doc(hidden)]
+impl From<cedar_policy_core::validator::CompletionKind> for CompletionKind {
+    fn from(kind: cedar_policy_core::validator::CompletionKind) -> Self {
+        match kind {
+            cedar_policy_core::validator::CompletionKind::Attribute => Self::Attribute,
+            cedar_policy_core::validator::CompletionKind::Method => Self::Method,
+            cedar_policy_core::validator::CompletionKind::EntityType => Self::EntityType,
+        }
+    }
+}
+
+/// A name which can replace the name under the cursor, returned by
+/// [`Validator::completions_at`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion(cedar_policy_core::validator::Completion);
+
+impl Completion {
+    /// The name to complete with
+    pub fn label(&self) -> &str {
+        self.0.label()
+    }
+
+    /// Whether this is an attribute, method, or entity type
+    pub fn kind(&self) -> CompletionKind {
+        self.0.kind().into()
+    }
+
+    /// The type, in Cedar schema syntax, of the attribute, the return type of
+    /// the method, or the entity type. Returns `None` if the type differs for
+    /// different principal, action, and resource types, as for
+    /// [`TypedPolicy::type_at`].
+    pub fn ty(&self) -> Option<String> {
+        self.0.ty().map(ToString::to_string)
+    }
+}
+
+/// The entity types, attributes, and actions a schema must declare for a
+/// policy, returned by [`Validator::inferred_requirements`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaRequirements(cedar_policy_core::validator::SchemaRequirements);
+
+impl SchemaRequirements {
+    /// Entity types the policy uses, ordered by name: those in the policy
+    /// scope, in entity literals, and in `is` expressions. Action entity types
+    /// are not included.
+    pub fn entity_types(&self) -> impl Iterator<Item = &EntityTypeName> {
+        self.0.entity_types().map(EntityTypeName::ref_cast)
+    }
+
+    /// Attributes of the entity type `ety` the policy uses, ordered by name.
+    /// Attributes of `principal` and `resource` are included if the policy
+    /// scope determines their entity type, e.g., `principal == User::"alice"`
+    /// or `resource is Photo`.
+    pub fn entity_type_attributes(
+        &self,
+        ety: &EntityTypeName,
+    ) -> impl Iterator<Item = (&str, &AttributeRequirement)> {
+        self.0
+            .entity_type_attributes(&ety.0)
+            .map(|(name, attr)| (name.as_str(), attr))
+    }
+
+    /// Attributes of `principal` the policy uses, ordered by name, if the
+    /// policy scope does not determine its entity type
+    pub fn principal_attributes(&self) -> impl Iterator<Item = (&str, &AttributeRequirement)> {
+        self.0
+            .principal_attributes()
+            .map(|(name, attr)| (name.as_str(), attr))
+    }
+
+    /// Attributes of `resource` the policy uses, ordered by name, if the
+    /// policy scope does not determine its entity type
+    pub fn resource_attributes(&self) -> impl Iterator<Item = (&str, &AttributeRequirement)> {
+        self.0
+            .resource_attributes()
+            .map(|(name, attr)| (name.as_str(), attr))
+    }
+
+    /// Attributes of `context` the policy uses, ordered by name
+    pub fn context_attributes(&self) -> impl Iterator<Item = (&str, &AttributeRequirement)> {
+        self.0
+            .context_attributes()
+            .map(|(name, attr)| (name.as_str(), attr))
+    }
+
+    /// Actions the policy uses, ordered by name: those in the policy scope
+    /// and in entity literals
+    pub fn actions(&self) -> impl Iterator<Item = &EntityUid> {
+        self.0.actions().map(EntityUid::ref_cast)
+    }
+}
+
 #[doc(hidden)]
 impl From<cedar_policy_core::validator::ValidationResult> for ValidationResult {
     fn from(r: cedar_policy_core::validator::ValidationResult) -> Self {
+        let policy_spans = r
+            .policy_locs()
+            .map(|(id, loc)| (PolicyId::ref_cast(id).clone(), loc.span))
+            .collect();
+        let policy_effects = r
+            .policy_effects()
+            .map(|(id, effect)| (PolicyId::ref_cast(id).clone(), effect))
+            .collect();
+        let policy_indices = r
+            .policy_ids()
+            .enumerate()
+            .map(|(index, id)| (PolicyId::ref_cast(id).clone(), index))
+            .collect();
+        let policy_set_warnings = r
+            .policy_set_warnings()
+            .cloned()
+            .map(PolicySetWarning::from)
+            .collect();
         let (errors, warnings) = r.into_errors_and_warnings();
         Self {
             validation_errors: errors.map(ValidationError::from).collect(),
             validation_warnings: warnings.map(ValidationWarning::from).collect(),
+            policy_set_warnings,
+            policy_spans,
+            policy_effects,
+            policy_indices,
+            mode: None,
         }
     }
 }
@@ -2398,6 +3529,11 @@ impl Diagnostic for ValidationResult {
                 self.validation_warnings
                     .iter()
                     .map(|warn| warn as &dyn Diagnostic),
+            )
+            .chain(
+                self.policy_set_warnings
+                    .iter()
+                    .map(|warn| warn as &dyn Diagnostic),
             );
         related.next().map(move |first| match first.related() {
             Some(first_related) => Box::new(first_related.chain(related)),
@@ -2436,6 +3572,79 @@ impl Diagnostic for ValidationResult {
             .and_then(Diagnostic::diagnostic_source)
     }
 }
+/// Utilities for defining `IntoIterator` over `ValidationResult`
+pub mod validation_result {
+    use super::{ValidationError, ValidationIssue, ValidationWarning};
+
+    /// `IntoIter` iterator for `ValidationResult`
+    #[derive(Debug)]
+    pub struct IntoIter {
+        pub(super) errors: std::vec::IntoIter<ValidationError>,
+        pub(super) warnings: std::vec::IntoIter<ValidationWarning>,
+    }
+
+    impl Iterator for IntoIter {
+        type Item = ValidationIssue;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.errors
+                .next()
+                .map(ValidationIssue::Error)
+                .or_else(|| self.warnings.next().map(ValidationIssue::Warning))
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            let len = self.errors.len().saturating_add(self.warnings.len());
+            (len, Some(len))
+        }
+    }
+
+    impl ExactSizeIterator for IntoIter {}
+}
+
+/// Iterate over all the errors in the result, followed by all the warnings
+impl IntoIterator for ValidationResult {
+    type Item = ValidationIssue;
+    type IntoIter = validation_result::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Self::IntoIter {
+            errors: self.validation_errors.into_iter(),
+            warnings: self.validation_warnings.into_iter(),
+        }
+    }
+}
+
+/// The result of validating entity data against a schema with
+/// [`Validator::validate_entities`]
+#[derive(Debug, Clone)]
+pub struct EntityValidationResult {
+    validation_errors: Vec<EntityValidationError>,
+}
+
+impl EntityValidationResult {
+    /// True when no entity violates the schema
+    pub fn validation_passed(&self) -> bool {
+        self.validation_errors.is_empty()
+    }
+
+    /// Get an iterator over the errors found in the entity data
+    pub fn validation_errors(&self) -> impl Iterator<Item = &EntityValidationError> {
+        self.validation_errors.iter()
+    }
+}
+
+#[doc(hidden)]
+impl From<cedar_policy_core::validator::EntityValidationResult> for EntityValidationResult {
+    fn from(r: cedar_policy_core::validator::EntityValidationResult) -> Self {
+        Self {
+            validation_errors: r
+                .into_validation_errors()
+                .map(EntityValidationError::from)
+                .collect(),
+        }
+    }
+}
 
 /// Scan a set of policies for potentially confusing/obfuscating text.
 ///
@@ -2449,6 +3658,20 @@ pub fn confusable_string_checker<'a>(
         .map(std::convert::Into::into)
 }
 
+/// Re-run only the text safety checks, i.e., the checks for confusable,
+/// bidirectional control, and mixed-script characters, on a single policy.
+///
+/// This is much cheaper than [`Validator::validate`], e.g., for an editor to
+/// give immediate feedback while a policy is being edited. The warnings are
+/// the same, including their source locations, as those reported for the
+/// policy by [`Validator::validate`] and [`confusable_string_checker`]. For a
+/// template-linked policy, the warnings are reported for its template.
+pub fn recheck_text_safety(policy: &Policy) -> Vec<ValidationWarning> {
+    cedar_policy_core::validator::confusable_string_checks(std::iter::once(policy.ast.template()))
+        .map(std::convert::Into::into)
+        .collect()
+}
+
 /// Represents a namespace.
 ///
 /// An `EntityNamespace` can can be constructed using
@@ -2545,6 +3768,68 @@ impl FromStr for PolicySet {
     /// See [`Policy`] for more.
     fn from_str(policies: &str) -> Result<Self, Self::Err> {
         let (texts, pset) = parser::parse_policyset_and_also_return_policy_text(policies)?;
+        Ok(Self::from_ast_and_texts(&texts, pset))
+    }
+}
+
+/// Parse a policy set like [`PolicySet::from_str`], naming the source text,
+/// e.g., with the name of the file it was read from.
+///
+/// On failure, the returned [`NamedParseErrors`] carries the source text under
+/// that name, so that rendering it with a `miette` report handler shows every
+/// parse error in the context of the named source.
+///
+/// ```
+/// # use cedar_policy::parse_policyset_named;
+/// let err = parse_policyset_named("policies.cedar", "permit(principal, action);").unwrap_err();
+/// assert_eq!(err.source_name(), "policies.cedar");
+/// ```
+pub fn parse_policyset_named(
+    source_name: impl AsRef<str>,
+    text: &str,
+) -> Result<PolicySet, NamedParseErrors> {
+    let source_name = source_name.as_ref();
+    let (texts, pset) =
+        parser::parse_named_policyset_and_also_return_policy_text(source_name, text)
+            .map_err(|errors| NamedParseErrors::new(errors.into(), source_name, text))?;
+    Ok(PolicySet::from_ast_and_texts(&texts, pset))
+}
+
+/// Check whether `text` parses as a policy set, returning its syntax errors,
+/// e.g., to underline them in an editor on every keystroke.
+///
+/// This recovers from syntax errors like [`PolicySet::parse_recovering`], but
+/// only runs the parser, without converting the policies to ASTs or building a
+/// policy set, so it is faster. The errors, ordered by location, are exactly
+/// the syntax errors [`PolicySet::parse_recovering`] reports for `text`.
+/// Errors it reports for policies which are syntactically valid, e.g., for a
+/// scope constraint comparing `principal` to something other than an entity,
+/// or a call to an unknown extension function, are not reported.
+///
+/// ```
+/// # use cedar_policy::check_parse;
+/// assert!(check_parse("permit(principal, action, resource);").is_empty());
+/// // The missing `resource` in the second policy is not a syntax error
+/// let errors = check_parse(
+///     "permit(principal, action, resource) when { 1 + }; forbid(principal, action);",
+/// );
+/// assert_eq!(errors.len(), 1);
+/// ```
+pub fn check_parse(text: &str) -> Vec<ParseError> {
+    parser::check_parse(text)
+        .into_iter()
+        .map(ParseError::from)
+        .collect()
+}
+
+impl PolicySet {
+    /// Create a policy set from its AST and the text of each of its policies
+    /// and templates, as returned by
+    /// `parser::parse_policyset_and_also_return_policy_text()`
+    fn from_ast_and_texts(
+        texts: &HashMap<ast::PolicyID, Option<&str>>,
+        pset: ast::PolicySet,
+    ) -> Self {
         #[expect(clippy::expect_used, reason = "By the invariant on `parse_policyset_and_also_return_policy_text(policies)`, every `PolicyId` in `pset.policies()` occurs as a key in `text`.")]
         let policies = pset.policies().map(|p|
             (
@@ -2562,65 +3847,218 @@ impl FromStr for PolicySet {
                 (
                     PolicyId::new(t.id().clone()),
                     Template {
-                        lossless: LosslessTemplate::from_text(*texts.get(t.id()).expect(
-                            "internal invariant violation: template id exists in asts but not ests",
-                        )),
+                        lossless: LosslessTemplate::from_text(*texts.get(t.id()).expect(
+                            "internal invariant violation: template id exists in asts but not ests",
+                        )),
+                        ast: t.clone(),
+                    },
+                )
+            })
+            .collect();
+        Self {
+            ast: pset,
+            policies,
+            templates,
+        }
+    }
+
+    /// Build the policy set AST from the EST
+    fn from_est(est: &est::PolicySet) -> Result<Self, PolicySetError> {
+        let ast: ast::PolicySet = est.clone().try_into()?;
+        #[expect(
+            clippy::expect_used,
+            reason = "Since conversion from EST to AST succeeded, every `PolicyId` in `ast.policies()` occurs in `est`"
+        )]
+        let policies = ast
+            .policies()
+            .map(|p| {
+                (
+                    PolicyId::new(p.id().clone()),
+                    Policy {
+                        lossless: LosslessPolicy::Est(est.get_policy(p.id()).expect(
+                            "internal invariant violation: policy id exists in asts but not ests",
+                        )),
+                        ast: p.clone(),
+                    },
+                )
+            })
+            .collect();
+        #[expect(
+            clippy::expect_used,
+            reason = "Since conversion from EST to AST succeeded, every `PolicyId` in `ast.templates()` occurs in `est`"
+        )]
+        let templates = ast
+            .templates()
+            .map(|t| {
+                (
+                    PolicyId::new(t.id().clone()),
+                    Template {
+                        lossless: LosslessTemplate::Est(est.get_template(t.id()).expect(
+                            "internal invariant violation: template id exists in asts but not ests",
+                        )),
+                        ast: t.clone(),
+                    },
+                )
+            })
+            .collect();
+        Ok(Self {
+            ast,
+            policies,
+            templates,
+        })
+    }
+
+    /// Create a policy set from multiple statements like
+    /// [`PolicySet::from_str`], but with the limits in `options` rather than
+    /// the default ones.
+    ///
+    /// The default limits protect against pathological input, e.g., a policy
+    /// nesting expressions so deep that converting it would overflow the
+    /// stack, so they should only be raised for trusted input.
+    ///
+    /// ```
+    /// # use cedar_policy::{ParserOptions, PolicySet};
+    /// let src = "permit(principal, action, resource) when { [[[1]]].isEmpty() };";
+    /// let options = ParserOptions::default().with_max_nesting_depth(4);
+    /// assert!(PolicySet::parse_with_options(src, &options).is_err());
+    /// let options = options.with_max_nesting_depth(5);
+    /// assert!(PolicySet::parse_with_options(src, &options).is_ok());
+    /// ```
+    pub fn parse_with_options(
+        policies: &str,
+        options: &ParserOptions,
+    ) -> Result<Self, ParseErrors> {
+        let (texts, pset) =
+            parser::parse_policyset_and_also_return_policy_text_with_options(policies, options)?;
+        Ok(Self::from_ast_and_texts(&texts, pset))
+    }
+
+    /// Create a policy set from multiple statements like
+    /// [`PolicySet::from_str`], but failing as soon as the input exceeds one
+    /// of the `limits`, e.g., to guard a service parsing untrusted policies.
+    ///
+    /// The parser stops at the first policy, expression node, or annotation
+    /// which exceeds a limit, and returns a [`ParseError`] naming the limit,
+    /// available from [`ParseError::exceeded_limit`], and located where it
+    /// was exceeded.
+    ///
+    /// ```
+    /// # use cedar_policy::{ParseLimit, ParseLimits, PolicySet};
+    /// let src = "permit(principal, action, resource); forbid(principal, action, resource);";
+    /// let limits = ParseLimits::default().with_max_policies(2);
+    /// assert!(PolicySet::parse_with_limits(src, &limits).is_ok());
+    /// let errs = PolicySet::parse_with_limits(src, &limits.with_max_policies(1)).unwrap_err();
+    /// assert_eq!(errs.iter().next().unwrap().exceeded_limit(), Some(ParseLimit::Policies));
+    /// ```
+    pub fn parse_with_limits(policies: &str, limits: &ParseLimits) -> Result<Self, ParseErrors> {
+        let (texts, pset) =
+            parser::parse_policyset_and_also_return_policy_text_with_limits(policies, limits)?;
+        Ok(Self::from_ast_and_texts(&texts, pset))
+    }
+
+    /// Create a policy set from multiple statements, recovering from syntax
+    /// errors so that policies which fail to parse do not prevent parsing the
+    /// rest of the policy set.
+    ///
+    /// Returns the policy set of the policies which parsed successfully, along
+    /// with the parse errors for each policy which did not. Each policy's
+    /// errors are reported under the id it would have had in
+    /// [`PolicySet::from_str`], and a single policy may have several errors,
+    /// e.g., one in each of its conditions. Policies with errors are never
+    /// added to the policy set.
+    pub fn parse_recovering(policies: &str) -> (Self, Vec<(PolicyId, ParseErrors)>) {
+        let (pset, errs) = parser::parse_policyset_recovering(policies);
+        let text_of =
+            |loc: Option<&parser::Loc>| loc.and_then(|loc| policies.get(loc.start()..loc.end()));
+        let static_policies = pset
+            .policies()
+            .map(|p| {
+                (
+                    PolicyId::new(p.id().clone()),
+                    Policy {
+                        lossless: LosslessPolicy::policy_or_template_text(text_of(p.loc())),
+                        ast: p.clone(),
+                    },
+                )
+            })
+            .collect();
+        let templates = pset
+            .templates()
+            .map(|t| {
+                (
+                    PolicyId::new(t.id().clone()),
+                    Template {
+                        lossless: LosslessTemplate::from_text(text_of(t.loc())),
                         ast: t.clone(),
                     },
                 )
             })
             .collect();
-        Ok(Self {
-            ast: pset,
-            policies,
-            templates,
-        })
+        let errs = errs
+            .into_iter()
+            .map(|(id, errs)| (PolicyId::new(id), errs.into()))
+            .collect();
+        (
+            Self {
+                ast: pset,
+                policies: static_policies,
+                templates,
+            },
+            errs,
+        )
     }
-}
 
-impl PolicySet {
-    /// Build the policy set AST from the EST
-    fn from_est(est: &est::PolicySet) -> Result<Self, PolicySetError> {
-        let ast: ast::PolicySet = est.clone().try_into()?;
-        #[expect(
-            clippy::expect_used,
-            reason = "Since conversion from EST to AST succeeded, every `PolicyId` in `ast.policies()` occurs in `est`"
-        )]
-        let policies = ast
+    /// Like [`PolicySet::from_str`], but a policy with an `@id("...")`
+    /// annotation gets the value of the annotation as its [`PolicyId`], so
+    /// that ids do not change when policies are reordered.
+    ///
+    /// Policies without an `@id` annotation get the same numbered ids as with
+    /// [`PolicySet::from_str`]. The `@id` annotation is kept in the
+    /// annotations of the policy, so it is preserved when the policy is
+    /// formatted or converted to JSON. It is an error for two policies to have
+    /// `@id` annotations with the same value, or for an `@id` annotation to
+    /// have the same value as the numbered id of a policy without one.
+    ///
+    /// ```
+    /// # use cedar_policy::{PolicyId, PolicySet};
+    /// let pset = PolicySet::parse_with_id_annotations(
+    ///     r#"@id("billing-admin-access")
+    ///     permit(principal in Group::"billing-admins", action, resource);"#,
+    /// )
+    /// .unwrap();
+    /// assert!(pset.policy(&PolicyId::new("billing-admin-access")).is_some());
+    /// ```
+    pub fn parse_with_id_annotations(policies: &str) -> Result<Self, ParseErrors> {
+        let pset = parser::parse_policyset_with_id_annotations(policies)?;
+        let text_of =
+            |loc: Option<&parser::Loc>| loc.and_then(|loc| policies.get(loc.start()..loc.end()));
+        let static_policies = pset
             .policies()
             .map(|p| {
                 (
                     PolicyId::new(p.id().clone()),
                     Policy {
-                        lossless: LosslessPolicy::Est(est.get_policy(p.id()).expect(
-                            "internal invariant violation: policy id exists in asts but not ests",
-                        )),
+                        lossless: LosslessPolicy::policy_or_template_text(text_of(p.loc())),
                         ast: p.clone(),
                     },
                 )
             })
             .collect();
-        #[expect(
-            clippy::expect_used,
-            reason = "Since conversion from EST to AST succeeded, every `PolicyId` in `ast.templates()` occurs in `est`"
-        )]
-        let templates = ast
+        let templates = pset
             .templates()
             .map(|t| {
                 (
                     PolicyId::new(t.id().clone()),
                     Template {
-                        lossless: LosslessTemplate::Est(est.get_template(t.id()).expect(
-                            "internal invariant violation: template id exists in asts but not ests",
-                        )),
+                        lossless: LosslessTemplate::from_text(text_of(t.loc())),
                         ast: t.clone(),
                     },
                 )
             })
             .collect();
         Ok(Self {
-            ast,
-            policies,
+            ast: pset,
+            policies: static_policies,
             templates,
         })
     }
@@ -2710,6 +4148,37 @@ impl PolicySet {
         Self::from_est(&est)
     }
 
+    /// Get a SHA-256 fingerprint of the content of this policy set.
+    ///
+    /// The fingerprint combines the [`Policy::fingerprint`] of every policy and
+    /// template in the set with its id, and for a template-linked policy, the id
+    /// of its template. It does not depend on the order in which policies were
+    /// added, but does change if any policy or template is added, removed,
+    /// renamed, or changed.
+    ///
+    /// Fingerprints are stable across releases of this crate, except for
+    /// releases whose changelog explicitly notes a new fingerprint format.
+    #[cfg(feature = "fingerprint")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fingerprint")))]
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let policies = self.policies.values().map(|p| {
+            let entry = p
+                .template_id()
+                .map_or(fingerprint::Entry::Static, |template_id| {
+                    fingerprint::Entry::Link(template_id.as_ref())
+                });
+            (p.id().as_ref(), entry, p.fingerprint())
+        });
+        let templates = self.templates.values().map(|t| {
+            (
+                t.id().as_ref(),
+                fingerprint::Entry::Template,
+                fingerprint::policy_fingerprint(t.ast.clone().into()),
+            )
+        });
+        fingerprint::policy_set_fingerprint(policies.chain(templates))
+    }
+
     /// Serialize the [`PolicySet`] as a JSON value
     pub fn to_json(self) -> Result<serde_json::Value, PolicySetError> {
         let est = self.est()?;
@@ -2903,6 +4372,83 @@ impl PolicySet {
         }
     }
 
+    /// Parse a policy set from several files, e.g., one file per team.
+    ///
+    /// Each file is given as its name, used in error messages and returned by
+    /// [`PolicySet::source_name`], and its text. Each file is parsed on its
+    /// own, as by [`parse_policyset_named`], so the policy ids do not depend
+    /// on the other files. A policy or template with an `@id` annotation gets
+    /// the annotation value as its id. Otherwise it gets the id
+    /// `{file_stem}.{index}`, where `index` counts the policies and templates
+    /// in the file from 0, e.g., `team_a.0` for the first policy in
+    /// `policies/team_a.cedar`.
+    ///
+    /// Policy text cannot contain template-linked policies, so templates from
+    /// any file can be linked with [`PolicySet::link`] once all the files are
+    /// loaded.
+    ///
+    /// ```
+    /// # use cedar_policy::{PolicyId, PolicySet};
+    /// let policies = PolicySet::from_files([
+    ///     ("team_a.cedar", "permit(principal, action, resource);"),
+    ///     ("team_b.cedar", r#"@id("deny") forbid(principal, action, resource);"#),
+    /// ])
+    /// .unwrap();
+    /// assert!(policies.policy(&PolicyId::new("team_a.0")).is_some());
+    /// assert_eq!(policies.source_name(&PolicyId::new("deny")), Some("team_b.cedar"));
+    /// ```
+    pub fn from_files<N: AsRef<str>, T: AsRef<str>>(
+        files: impl IntoIterator<Item = (N, T)>,
+    ) -> Result<Self, PolicySetFromFilesError> {
+        let mut set = Self::new();
+        let mut files_by_id: HashMap<PolicyId, String> = HashMap::new();
+        for (name, text) in files {
+            let name = name.as_ref();
+            let file_set = parse_policyset_named(name, text.as_ref())?;
+            let stem = std::path::Path::new(name)
+                .file_stem()
+                .map_or_else(|| name.into(), |stem| stem.to_string_lossy());
+            // Number the policies and templates in the order they appear in
+            // the file, which is also the order of the ids the parser assigns
+            let statements = file_set
+                .policies()
+                .map(Either::Left)
+                .chain(file_set.templates().map(Either::Right))
+                .sorted_by_key(|statement| match statement {
+                    Either::Left(policy) => policy.ast.loc().map(parser::Loc::start),
+                    Either::Right(template) => template.ast.loc().map(parser::Loc::start),
+                });
+            for (index, statement) in statements.enumerate() {
+                let annotated_id = match statement {
+                    Either::Left(policy) => policy.annotation("id"),
+                    Either::Right(template) => template.annotation("id"),
+                };
+                let id = PolicyId::new(
+                    annotated_id.map_or_else(|| format!("{stem}.{index}"), ToString::to_string),
+                );
+                if let Some(first_file) = files_by_id.get(&id) {
+                    return Err(policy_set_from_files_errors::DuplicateIdError {
+                        id,
+                        first_file: first_file.clone(),
+                        second_file: name.to_string(),
+                    }
+                    .into());
+                }
+                #[expect(
+                    clippy::expect_used,
+                    reason = "`id` was checked above not to be the id of a policy or template from an earlier statement"
+                )]
+                match statement {
+                    Either::Left(policy) => set.add(policy.new_id(id.clone())),
+                    Either::Right(template) => set.add_template(template.new_id(id.clone())),
+                }
+                .expect("policy id should not already be defined");
+                files_by_id.insert(id, name.to_string());
+            }
+        }
+        Ok(set)
+    }
+
     /// Create a `PolicySet` from the given policies
     pub fn from_policies(
         policies: impl IntoIterator<Item = Policy>,
@@ -3136,6 +4682,16 @@ impl PolicySet {
             .map(AsRef::as_ref)
     }
 
+    /// Get the name of the source text the policy or template with id `id`
+    /// was parsed from, e.g., the name of its file, if it was parsed with
+    /// [`parse_policyset_named`]. See [`Policy::source_name`].
+    pub fn source_name(&self, id: &PolicyId) -> Option<&str> {
+        match self.policy(id) {
+            Some(policy) => policy.source_name(),
+            None => self.template(id)?.source_name(),
+        }
+    }
+
     /// Returns true iff the `PolicySet` is empty
     pub fn is_empty(&self) -> bool {
         debug_assert_eq!(
@@ -3521,7 +5077,31 @@ impl Template {
     /// If the `id` is None, the parser will use the default "policy0".
     /// The behavior around None may change in the future.
     pub fn parse(id: Option<PolicyId>, src: impl AsRef<str>) -> Result<Self, ParseErrors> {
-        let ast = parser::parse_template(id.map(Into::into), src.as_ref())?;
+        Self::parse_with_options(id, src, &ParserOptions::default())
+    }
+
+    /// Like [`Template::parse`], but with the limits in `options` rather than
+    /// the default ones
+    pub fn parse_with_options(
+        id: Option<PolicyId>,
+        src: impl AsRef<str>,
+        options: &ParserOptions,
+    ) -> Result<Self, ParseErrors> {
+        let ast = parser::parse_template_with_options(id.map(Into::into), src.as_ref(), options)?;
+        Ok(Self {
+            ast,
+            lossless: LosslessTemplate::from_text(Some(src.as_ref())),
+        })
+    }
+
+    /// Like [`Template::parse`], but failing as soon as the input exceeds one
+    /// of the `limits`. See [`PolicySet::parse_with_limits`].
+    pub fn parse_with_limits(
+        id: Option<PolicyId>,
+        src: impl AsRef<str>,
+        limits: &ParseLimits,
+    ) -> Result<Self, ParseErrors> {
+        let ast = parser::parse_template_with_limits(id.map(Into::into), src.as_ref(), limits)?;
         Ok(Self {
             ast,
             lossless: LosslessTemplate::from_text(Some(src.as_ref())),
@@ -3562,13 +5142,29 @@ impl Template {
             .map(AsRef::as_ref)
     }
 
-    /// Iterate through annotation data of this `Template` as key-value pairs.
+    /// Iterate through annotation data of this `Template` as key-value pairs,
+    /// in the order they appear in the source text.
     /// Annotations which do not have an explicit value (e.g., `@annotation`),
     /// are included in the iterator with the value `""`.
     pub fn annotations(&self) -> impl Iterator<Item = (&str, &str)> {
+        annotations_in_source_order(self.ast.annotations())
+    }
+
+    /// Get the `//` comments before, inside, and after this `Template` in the
+    /// source it was parsed from, in source order. Returns an empty `Vec` if
+    /// the template was not parsed from text. See [`CommentPosition`] for
+    /// how comments are assigned to the template.
+    pub fn comments(&self) -> Vec<Comment> {
         self.ast
-            .annotations()
-            .map(|(k, v)| (k.as_ref(), v.as_ref()))
+            .loc()
+            .map(parser::comments::policy_comments)
+            .unwrap_or_default()
+    }
+
+    /// Get the name of the source text this `Template` was parsed from, e.g.,
+    /// the name of its file, if it was parsed with [`parse_policyset_named`]
+    pub fn source_name(&self) -> Option<&str> {
+        self.ast.loc()?.name()
     }
 
     /// Iterate over the open slots in this `Template`
@@ -3576,6 +5172,57 @@ impl Template {
         self.ast.slots().map(|slot| SlotId::ref_cast(&slot.id))
     }
 
+    /// Iterate over the open slots in this `Template`, together with where
+    /// they appear in its scope
+    pub fn slot_info(&self) -> impl Iterator<Item = TemplateSlot> + '_ {
+        self.ast.slots().map(|slot| TemplateSlot {
+            id: slot.id.into(),
+            position: if slot.id.is_principal() {
+                SlotPosition::Principal
+            } else {
+                SlotPosition::Resource
+            },
+            loc: slot.loc.clone(),
+        })
+    }
+
+    /// Get the entity types which `slot` may be linked with so that this
+    /// `Template` applies to some request allowed by the schema, i.e., the
+    /// slot types of the [`RequestEnv`]s returned by
+    /// [`Template::get_valid_request_envs`]. Returns an empty set if `slot`
+    /// is not a slot of this template or no request environment is valid.
+    pub fn slot_entity_types(&self, slot: &SlotId, s: &Schema) -> BTreeSet<EntityTypeName> {
+        get_valid_request_envs(&self.ast, s)
+            .filter_map(|env| {
+                if slot.as_ref().is_principal() {
+                    env.principal_slot
+                } else {
+                    env.resource_slot
+                }
+            })
+            .collect()
+    }
+
+    /// Check that `values` provides a value for every slot of this
+    /// `Template`, before attempting to link it with
+    /// [`PolicySet::link`]. Values for slots which are not in this template
+    /// are not checked.
+    pub fn link_values_complete(
+        &self,
+        values: &HashMap<SlotId, EntityUid>,
+    ) -> Result<(), MissingSlots> {
+        let slots = self
+            .slots()
+            .filter(|slot| !values.contains_key(slot))
+            .cloned()
+            .collect::<Vec<_>>();
+        if slots.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingSlots { slots })
+        }
+    }
+
     /// Get the scope constraint on this policy's principal
     pub fn principal_constraint(&self) -> TemplatePrincipalConstraint {
         match self.ast.principal_constraint().as_inner() {
@@ -3660,13 +5307,16 @@ impl Template {
     /// If `id` is Some, the policy will be given that Policy Id.
     /// If `id` is None, then "JSON policy" will be used.
     /// The behavior around None may change in the future.
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "taking the value by reference would be a breaking change to this public API"
+    )]
     pub fn from_json(
         id: Option<PolicyId>,
         json: serde_json::Value,
     ) -> Result<Self, PolicyFromJsonError> {
-        let est: est::Policy = serde_json::from_value(json)
-            .map_err(|e| entities_json_errors::JsonDeserializationError::Serde(e.into()))
-            .map_err(cedar_policy_core::est::FromJsonError::from)?;
+        let est = est::Policy::deserialize(&json)
+            .map_err(|e| PolicyFromJsonError::deserialization(e, &json))?;
         Self::from_est(id, est)
     }
 
@@ -3731,6 +5381,43 @@ impl FromStr for Template {
     }
 }
 
+/// An open slot of a [`Template`], as returned by [`Template::slot_info`]
+#[derive(Debug, Clone)]
+pub struct TemplateSlot {
+    id: SlotId,
+    position: SlotPosition,
+    loc: Option<parser::Loc>,
+}
+
+impl TemplateSlot {
+    /// Get the [`SlotId`] of this slot
+    pub fn id(&self) -> &SlotId {
+        &self.id
+    }
+
+    /// Get the scope constraint this slot appears in
+    pub fn position(&self) -> SlotPosition {
+        self.position
+    }
+
+    /// Get the source span of this slot in the template text, if the template
+    /// was parsed from text
+    pub fn source_span(&self) -> Option<miette::SourceSpan> {
+        self.loc.as_ref().map(|loc| loc.span)
+    }
+}
+
+/// The scope constraint a [`TemplateSlot`] appears in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlotPosition {
+    /// The slot appears in the principal scope constraint, e.g.,
+    /// `principal in ?principal`
+    Principal,
+    /// The slot appears in the resource scope constraint, e.g.,
+    /// `resource == ?resource`
+    Resource,
+}
+
 /// Scope constraint on policy principals.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PrincipalConstraint {
@@ -3845,6 +5532,11 @@ pub struct Policy {
     pub(crate) lossless: LosslessPolicy,
 }
 
+/// Two policies are equal if they have the same id, annotations, effect,
+/// scope and conditions, and, for template-linked policies, the same template
+/// id and slot values. Source locations, formatting and comments are ignored,
+/// but expressions are compared as written, e.g., `a && b` is not equal to
+/// `b && a`. See [`Policy::eq_structural`] to ignore ids or annotations.
 impl PartialEq for Policy {
     fn eq(&self, other: &Self) -> bool {
         // eq is based on just the `ast`
@@ -3853,6 +5545,63 @@ impl PartialEq for Policy {
 }
 impl Eq for Policy {}
 
+/// Consistent with the [`PartialEq`] implementation for [`Policy`]
+impl std::hash::Hash for Policy {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        // equal policies have equal templates and ids; the slot values are
+        // left out, since hashing a `HashMap` would require sorting it
+        self.ast.template().hash(state);
+        self.ast.id().hash(state);
+    }
+}
+
+/// Options for [`Policy::eq_structural`].
+///
+/// The default options consider both policy ids and annotations, like `==`
+/// on [`Policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyEqOptions {
+    ids: bool,
+    annotations: bool,
+}
+
+impl Default for PolicyEqOptions {
+    fn default() -> Self {
+        Self {
+            ids: true,
+            annotations: true,
+        }
+    }
+}
+
+impl PolicyEqOptions {
+    /// Set whether policy ids are compared. When they are not, a static
+    /// policy can be equal to a template-linked policy whose filled-in
+    /// template has the same content.
+    #[must_use]
+    pub fn with_ids(mut self, ids: bool) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Set whether annotations are compared
+    #[must_use]
+    pub fn with_annotations(mut self, annotations: bool) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    /// Whether policy ids are compared. See [`PolicyEqOptions::with_ids`].
+    pub fn ids(&self) -> bool {
+        self.ids
+    }
+
+    /// Whether annotations are compared
+    pub fn annotations(&self) -> bool {
+        self.annotations
+    }
+}
+
 #[doc(hidden)] // because this converts to a private/internal type
 impl AsRef<ast::Policy> for Policy {
     fn as_ref(&self) -> &ast::Policy {
@@ -3875,6 +5624,11 @@ impl From<ast::StaticPolicy> for Policy {
 }
 
 impl Policy {
+    /// Get a [`PolicyBuilder`] for constructing a policy programmatically
+    pub fn builder() -> PolicyBuilder {
+        PolicyBuilder::new()
+    }
+
     /// Construct a [`Policy`] from a PST policy.
     /// Accepts both static and linked PST policies.
     pub fn from_pst(pst_policy: pst::Policy) -> Result<Self, pst::PstConstructionError> {
@@ -3931,13 +5685,33 @@ impl Policy {
             .map(AsRef::as_ref)
     }
 
-    /// Iterate through annotation data of this template-linked or static policy.
+    /// Iterate through annotation data of this template-linked or static policy,
+    /// in the order they appear in the source text. Annotations added with
+    /// [`Policy::set_annotation`] come after those from the source text.
     /// Annotations which do not have an explicit value (e.g., `@annotation`),
     /// are included in the iterator with the value `""`.
     pub fn annotations(&self) -> impl Iterator<Item = (&str, &str)> {
+        annotations_in_source_order(self.ast.annotations())
+    }
+
+    /// Get the `//` comments before, inside, and after this template-linked
+    /// or static policy in the source it was parsed from, in source order. A
+    /// template-linked policy has the comments of its template. Returns an
+    /// empty `Vec` if the policy was not parsed from text. See
+    /// [`CommentPosition`] for how comments are assigned to the policy.
+    pub fn comments(&self) -> Vec<Comment> {
         self.ast
-            .annotations()
-            .map(|(k, v)| (k.as_ref(), v.as_ref()))
+            .loc()
+            .map(parser::comments::policy_comments)
+            .unwrap_or_default()
+    }
+
+    /// Get the name of the source text this template-linked or static policy
+    /// was parsed from, e.g., the name of its file, if it was parsed with
+    /// [`parse_policyset_named`]. A template-linked policy has the source name
+    /// of its template.
+    pub fn source_name(&self) -> Option<&str> {
+        self.ast.loc()?.name()
     }
 
     /// Get the `PolicyId` for this template-linked or static policy
@@ -4025,6 +5799,144 @@ impl Policy {
         }
     }
 
+    /// Iterate over the nodes of this policy's `when` and `unless` conditions
+    /// in pre-order, together with their source spans. The conditions are
+    /// combined into a single expression, where each `unless` condition is
+    /// negated and multiple conditions are joined with `&&`.
+    ///
+    /// ```
+    /// # use cedar_policy::{ExprNodeKind, Policy};
+    /// let policy: Policy = r#"permit(principal, action, resource)
+    ///     when { context.ip == ip("10.0.0.1") };"#.parse().unwrap();
+    /// let attrs = policy
+    ///     .condition_exprs()
+    ///     .filter(|e| e.kind() == ExprNodeKind::GetAttr)
+    ///     .map(|e| e.to_string())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(attrs, ["context.ip"]);
+    /// ```
+    pub fn condition_exprs(&self) -> impl Iterator<Item = ExprWithSpan<'_>> {
+        expr_with_span::PreOrder::new(self.ast.non_scope_constraints())
+    }
+
+    /// Get the `when` and `unless` conditions of this policy as a single
+    /// expression, as for [`Policy::condition_exprs`]: each `unless`
+    /// condition is negated, and multiple conditions are joined with `&&`, so
+    /// `when { a } when { b } unless { c }` is `a && (b && !c)`. A policy
+    /// without conditions has the condition `true`. Each condition keeps the
+    /// source span it was parsed from. The scope is not included; see
+    /// [`Policy::scope_constraint_exprs`].
+    ///
+    /// The expression is a copy of the one in the policy, which shares its
+    /// subexpressions, so getting it does not copy the whole condition.
+    ///
+    /// The condition can be evaluated with [`eval_expression`], e.g., to
+    /// check whether it holds for a request without authorizing it.
+    ///
+    /// ```
+    /// # use cedar_policy::{eval_expression, Context, Entities, EntityUid, EvalResult, Policy, Request};
+    /// # use std::str::FromStr;
+    /// let policy: Policy = r#"permit(principal, action, resource)
+    ///     when { context.mfa } unless { context.locked };"#.parse().unwrap();
+    /// assert_eq!(policy.condition().to_string(), "(context.mfa) && (!(context.locked))");
+    ///
+    /// let context = Context::from_json_value(serde_json::json!({ "mfa": true, "locked": false }), None).unwrap();
+    /// let request = Request::new(
+    ///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+    ///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+    ///     EntityUid::from_str(r#"Doc::"d""#).unwrap(),
+    ///     context,
+    ///     None,
+    /// ).unwrap();
+    /// let result = eval_expression(&request, &Entities::empty(), &policy.condition()).unwrap();
+    /// assert_eq!(result, EvalResult::Bool(true));
+    /// ```
+    pub fn condition(&self) -> Expression {
+        Expression(
+            self.ast
+                .non_scope_constraints()
+                .map_or_else(|| ast::Expr::val(true), Clone::clone),
+        )
+    }
+
+    /// Get a SHA-256 fingerprint of the content of this policy, e.g., to
+    /// detect whether a deployed policy differs from its source.
+    ///
+    /// The fingerprint is computed from the policy's AST, so it does not depend
+    /// on formatting, comments, or the order of the fields of record literals,
+    /// but changes with any change to the effect, scope, conditions, or
+    /// annotations. It does not depend on the policy's id. A template-linked
+    /// policy has the fingerprint of the static policy obtained by filling in
+    /// its slots.
+    ///
+    /// Fingerprints are stable across releases of this crate, except for
+    /// releases whose changelog explicitly notes a new fingerprint format.
+    ///
+    /// ```
+    /// # use cedar_policy::Policy;
+    /// let p1: Policy = r#"permit(principal, action, resource) when { context.a == 1 };"#.parse().unwrap();
+    /// let p2: Policy = r#"
+    ///     // Reformatted, with a comment
+    ///     permit (principal, action, resource)
+    ///     when { context.a == 1 };
+    /// "#.parse().unwrap();
+    /// assert_eq!(p1.fingerprint(), p2.fingerprint());
+    /// ```
+    #[cfg(feature = "fingerprint")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fingerprint")))]
+    pub fn fingerprint(&self) -> [u8; 32] {
+        fingerprint::policy_fingerprint(self.ast.clone().into())
+    }
+
+    /// Compare the structure of this policy to `other`: their effects, scopes
+    /// and conditions, and, depending on `options`, their ids and annotations.
+    ///
+    /// As for `==`, source locations, formatting and comments are ignored, but
+    /// expressions are compared as written. Template-linked policies are
+    /// compared with their slots filled in.
+    ///
+    /// ```
+    /// # use cedar_policy::{Policy, PolicyEqOptions, PolicyId};
+    /// let p1 = Policy::parse(Some(PolicyId::new("p1")), r#"@doc("a") permit(principal, action, resource);"#).unwrap();
+    /// let p2 = Policy::parse(Some(PolicyId::new("p2")), r#"permit(principal, action, resource);"#).unwrap();
+    /// assert!(p1 != p2);
+    /// let options = PolicyEqOptions::default().with_ids(false).with_annotations(false);
+    /// assert!(p1.eq_structural(&p2, &options));
+    /// ```
+    pub fn eq_structural(&self, other: &Self, options: &PolicyEqOptions) -> bool {
+        let (p1, p2) = (&self.ast, &other.ast);
+        // the id of a policy, and of its template if it is template-linked
+        let ids = |p: &ast::Policy| {
+            (
+                p.id().clone(),
+                (!p.is_static()).then(|| p.template().id().clone()),
+            )
+        };
+        if options.ids() && ids(p1) != ids(p2) {
+            return false;
+        }
+        if options.annotations() && p1.annotations_arc() != p2.annotations_arc() {
+            return false;
+        }
+        p1.effect() == p2.effect()
+            && p1.principal_constraint() == p2.principal_constraint()
+            && p1.action_constraint() == p2.action_constraint()
+            && p1.resource_constraint() == p2.resource_constraint()
+            && p1.non_scope_constraints() == p2.non_scope_constraints()
+    }
+
+    /// Get the scope constraints on this policy's principal, action and
+    /// resource, in that order, as expressions whose nodes can be iterated
+    /// with [`Expression::exprs`]. An unconstrained scope is `true`. The scope
+    /// constraints do not keep the source spans they were parsed from.
+    pub fn scope_constraint_exprs(&self) -> [Expression; 3] {
+        [
+            Expression(self.ast.principal_constraint().as_expr()),
+            Expression(self.ast.action_constraint().as_expr()),
+            Expression(self.ast.resource_constraint().as_expr()),
+        ]
+    }
+
     /// To avoid panicking, this function may only be called when `slot` is the
     /// `SlotId` corresponding to the scope constraint from which the entity
     /// reference `r` was extracted. I.e., If `r` is taken from the principal
@@ -4057,7 +5969,34 @@ impl Policy {
     /// It can also fail if a template was passed in, as this function only accepts static
     /// policies
     pub fn parse(id: Option<PolicyId>, policy_src: impl AsRef<str>) -> Result<Self, ParseErrors> {
-        let inline_ast = parser::parse_policy(id.map(Into::into), policy_src.as_ref())?;
+        Self::parse_with_options(id, policy_src, &ParserOptions::default())
+    }
+
+    /// Like [`Policy::parse`], but with the limits in `options` rather than the
+    /// default ones
+    pub fn parse_with_options(
+        id: Option<PolicyId>,
+        policy_src: impl AsRef<str>,
+        options: &ParserOptions,
+    ) -> Result<Self, ParseErrors> {
+        let inline_ast =
+            parser::parse_policy_with_options(id.map(Into::into), policy_src.as_ref(), options)?;
+        let (_, ast) = ast::Template::link_static_policy(inline_ast);
+        Ok(Self {
+            ast,
+            lossless: LosslessPolicy::policy_or_template_text(Some(policy_src.as_ref())),
+        })
+    }
+
+    /// Like [`Policy::parse`], but failing as soon as the input exceeds one of
+    /// the `limits`. See [`PolicySet::parse_with_limits`].
+    pub fn parse_with_limits(
+        id: Option<PolicyId>,
+        policy_src: impl AsRef<str>,
+        limits: &ParseLimits,
+    ) -> Result<Self, ParseErrors> {
+        let inline_ast =
+            parser::parse_policy_with_limits(id.map(Into::into), policy_src.as_ref(), limits)?;
         let (_, ast) = ast::Template::link_static_policy(inline_ast);
         Ok(Self {
             ast,
@@ -4130,13 +6069,16 @@ impl Policy {
     /// let text_policy = Policy::parse(None, src).unwrap();
     /// assert_eq!(json_policy.to_json().unwrap(), text_policy.to_json().unwrap());
     /// ```
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "taking the value by reference would be a breaking change to this public API"
+    )]
     pub fn from_json(
         id: Option<PolicyId>,
         json: serde_json::Value,
     ) -> Result<Self, PolicyFromJsonError> {
-        let est: est::Policy = serde_json::from_value(json)
-            .map_err(|e| entities_json_errors::JsonDeserializationError::Serde(e.into()))
-            .map_err(cedar_policy_core::est::FromJsonError::from)?;
+        let est = est::Policy::deserialize(&json)
+            .map_err(|e| PolicyFromJsonError::deserialization(e, &json))?;
         Self::from_est(id, est)
     }
 
@@ -4626,6 +6568,12 @@ impl From<ast::Expr> for Expression {
 }
 
 impl Expression {
+    /// Iterate over the nodes of this expression in pre-order, together with
+    /// their source spans
+    pub fn exprs(&self) -> impl Iterator<Item = ExprWithSpan<'_>> {
+        expr_with_span::PreOrder::new([&self.0])
+    }
+
     /// Create an expression representing a literal string.
     pub fn new_string(value: String) -> Self {
         Self(ast::Expr::val(value))
@@ -5091,6 +7039,12 @@ impl Request {
         RequestBuilder::default()
     }
 
+    /// Create a [`ConcreteRequestBuilder`], for building a request with a
+    /// known principal, action, resource and context one component at a time
+    pub fn concrete_builder<'a>() -> ConcreteRequestBuilder<'a> {
+        ConcreteRequestBuilder::default()
+    }
+
     /// Create a Request.
     ///
     /// Note that you can create the `EntityUid`s using `.parse()` on any
@@ -5120,6 +7074,21 @@ impl Request {
         )?))
     }
 
+    /// Create a Request for an action declared in the schema with no
+    /// resource (`"noResource": true`). The resource of such a request is the
+    /// action itself.
+    ///
+    /// If `schema` is present, this constructor will validate that the
+    /// `Request` complies with the given `schema`.
+    pub fn new_without_resource(
+        principal: EntityUid,
+        action: EntityUid,
+        context: Context,
+        schema: Option<&Schema>,
+    ) -> Result<Self, RequestValidationError> {
+        Self::new(principal, action.clone(), action, context, schema)
+    }
+
     /// Get the context component of the request. Returns `None` if the context is
     /// "unknown" (i.e., constructed using the partial evaluation APIs).
     pub fn context(&self) -> Option<&Context> {
@@ -5915,6 +7884,48 @@ action CreateList in Create appliesTo {
         assert_eq!(parents, expected);
     }
 
+    #[test]
+    fn descendants_and_hierarchy_paths() {
+        let schema = schema();
+        let children = schema
+            .descendants(&"Application".parse().unwrap())
+            .unwrap()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let expected = HashSet::from([
+            "User".parse().unwrap(),
+            "Team".parse().unwrap(),
+            "List".parse().unwrap(),
+        ]);
+        assert_eq!(children, expected);
+        let children = schema
+            .descendants(&"Team".parse().unwrap())
+            .unwrap()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let expected = HashSet::from(["User".parse().unwrap(), "Team".parse().unwrap()]);
+        assert_eq!(children, expected);
+        assert_eq!(
+            schema
+                .descendants(&"CoolList".parse().unwrap())
+                .unwrap()
+                .count(),
+            0
+        );
+        assert!(schema.descendants(&"Foo".parse().unwrap()).is_none());
+
+        let path_exists = |from: &str, to: &str| {
+            schema.hierarchy_path_exists(&from.parse().unwrap(), &to.parse().unwrap())
+        };
+        assert!(path_exists("User", "Team"));
+        assert!(path_exists("User", "Application"));
+        assert!(path_exists("Team", "Team"));
+        assert!(!path_exists("User", "User"));
+        assert!(!path_exists("Application", "User"));
+        assert!(!path_exists("CoolList", "Application"));
+        assert!(!path_exists("Foo", "Application"));
+    }
+
     #[test]
     fn action_groups() {
         let schema = schema();
@@ -184,6 +184,27 @@ mod test_utils {
         })
     }
 
+    /// Prefix of the help attached to unknown field errors for a type at
+    /// each of the positions used by the helpers below
+    const ENTITY_ATTRIBUTE_POINTER: &str =
+        "unknown field is at `/ns/entityTypes/User/shape/attributes/foo/";
+    const CONTEXT_ATTRIBUTE_POINTER: &str =
+        "unknown field is at `/ns/actions/Act/appliesTo/context/attributes/foo/";
+    const COMMON_TYPE_POINTER: &str = "unknown field is at `/ns/commonTypes/ty2/";
+
+    /// Expected error for a type that fails to parse with error `err` in the
+    /// standard format. Unknown fields are also reported with their location,
+    /// which must start with `pointer`.
+    fn standard_type_err<'a>(err: &'a str, pointer: &'a str) -> ExpectedErrorMessage<'a> {
+        if err.starts_with("unknown field") {
+            ExpectedErrorMessageBuilder::error_starts_with(err)
+                .help(pointer)
+                .build()
+        } else {
+            ExpectedErrorMessageBuilder::error(err).build()
+        }
+    }
+
     #[track_caller]
     pub(crate) fn assert_type_json_ok_deprecated_and_err_standard(
         ty: &serde_json::Value,
@@ -194,21 +215,21 @@ mod test_utils {
         expect_err(
             "",
             &Report::new(Schema::from_json_value(in_entity_attr).unwrap_err()),
-            &ExpectedErrorMessageBuilder::error(err).build(),
+            &standard_type_err(err, ENTITY_ATTRIBUTE_POINTER),
         );
         let in_context = schema_with_context_attribute(ty);
         Schema::from_deprecated_json_value(in_context.clone()).unwrap();
         expect_err(
             "",
             &Report::new(Schema::from_json_value(in_context).unwrap_err()),
-            &ExpectedErrorMessageBuilder::error(err).build(),
+            &standard_type_err(err, CONTEXT_ATTRIBUTE_POINTER),
         );
         let in_common = schema_with_common_type(ty);
         Schema::from_deprecated_json_value(in_common.clone()).unwrap();
         expect_err(
             "",
             &Report::new(Schema::from_json_value(in_common).unwrap_err()),
-            &ExpectedErrorMessageBuilder::error(err).build(),
+            &standard_type_err(err, COMMON_TYPE_POINTER),
         );
     }
 
@@ -228,15 +249,27 @@ mod test_utils {
     #[track_caller]
     pub(crate) fn assert_type_json_err_deprecated_and_standard(
         ty: &serde_json::Value,
-        current_err: &ExpectedErrorMessage<'_>,
+        current_err: &str,
         deprecated_err: &ExpectedErrorMessage<'_>,
     ) {
         let in_entity_attr = schema_with_entity_attribute(ty);
-        assert_schema_json_err_deprecated_and_standard(in_entity_attr, current_err, deprecated_err);
+        assert_schema_json_err_deprecated_and_standard(
+            in_entity_attr,
+            &standard_type_err(current_err, ENTITY_ATTRIBUTE_POINTER),
+            deprecated_err,
+        );
         let in_context = schema_with_context_attribute(ty);
-        assert_schema_json_err_deprecated_and_standard(in_context, current_err, deprecated_err);
+        assert_schema_json_err_deprecated_and_standard(
+            in_context,
+            &standard_type_err(current_err, CONTEXT_ATTRIBUTE_POINTER),
+            deprecated_err,
+        );
         let in_common = schema_with_common_type(ty);
-        assert_schema_json_err_deprecated_and_standard(in_common, current_err, deprecated_err);
+        assert_schema_json_err_deprecated_and_standard(
+            in_common,
+            &standard_type_err(current_err, COMMON_TYPE_POINTER),
+            deprecated_err,
+        );
     }
 
     #[track_caller]
@@ -475,8 +508,7 @@ mod extra_fields_forbidden {
                 "element": {"type": "Long"},
                 "bogus": "bogus",
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
-                .build(),
+            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Set")
                 .help("neither `ns::Set` nor `Set` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Set")
@@ -488,8 +520,7 @@ mod extra_fields_forbidden {
                 "element": {"type": "Long"},
                 "name": "my_long",
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `name`, expected `element`")
-                .build(),
+            "unknown field `name`, expected `element`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Set")
                 .help("neither `ns::Set` nor `Set` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Set")
@@ -502,8 +533,7 @@ mod extra_fields_forbidden {
                 "element": {"type": "Long"},
                 "attributes": {},
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `attributes`, expected `element`")
-                .build(),
+            "unknown field `attributes`, expected `element`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Set")
                 .help("neither `ns::Set` nor `Set` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Set")
@@ -515,8 +545,7 @@ mod extra_fields_forbidden {
                 "element": {"type": "Long"},
                 "additionalAttributes": false,
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `additionalAttributes`, expected `element`")
-                .build(),
+            "unknown field `additionalAttributes`, expected `element`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Set")
                 .help("neither `ns::Set` nor `Set` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Set")
@@ -532,8 +561,7 @@ mod extra_fields_forbidden {
                 "name": "User",
                 "bogus": "bogus",
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
-                .build(),
+            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Entity")
                 .help("neither `ns::Entity` nor `Entity` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Entity")
@@ -545,8 +573,7 @@ mod extra_fields_forbidden {
                 "name": "User",
                 "element": {"type": "Long"},
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `element`, expected `name`")
-                .build(),
+            "unknown field `element`, expected `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Entity")
                 .help("neither `ns::Entity` nor `Entity` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Entity")
@@ -558,8 +585,7 @@ mod extra_fields_forbidden {
                 "name": "User",
                 "attributes": {},
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `attributes`, expected `name`")
-                .build(),
+            "unknown field `attributes`, expected `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Entity")
                 .help("neither `ns::Entity` nor `Entity` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Entity")
@@ -571,8 +597,7 @@ mod extra_fields_forbidden {
                 "name": "User",
                 "additionalAttributes": false,
             }),
-            &ExpectedErrorMessageBuilder::error( "unknown field `additionalAttributes`, expected `name`",)
-                .build(),
+            "unknown field `additionalAttributes`, expected `name`",
             &ExpectedErrorMessageBuilder::error( "failed to resolve type: Entity",)
                 .help("neither `ns::Entity` nor `Entity` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Entity")
@@ -588,8 +613,7 @@ mod extra_fields_forbidden {
                 "name": "ip",
                 "bogus": "bogus"
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
-                .build(),
+            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Extension")
                 .help("neither `ns::Extension` nor `Extension` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Extension")
@@ -601,8 +625,7 @@ mod extra_fields_forbidden {
                 "name": "ip",
                 "element": {"type": "Long"},
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `element`, expected `name`")
-                .build(),
+            "unknown field `element`, expected `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Extension")
                 .help("neither `ns::Extension` nor `Extension` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Extension")
@@ -614,8 +637,7 @@ mod extra_fields_forbidden {
                 "name": "ip",
                 "attributes": {},
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `attributes`, expected `name`")
-                .build(),
+            "unknown field `attributes`, expected `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Extension")
                 .help("neither `ns::Extension` nor `Extension` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Extension")
@@ -627,8 +649,7 @@ mod extra_fields_forbidden {
                 "name": "ip",
                 "additionalAttributes": false,
             }),
-            &ExpectedErrorMessageBuilder::error( "unknown field `additionalAttributes`, expected `name`",)
-                .build(),
+            "unknown field `additionalAttributes`, expected `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Extension")
                 .help("neither `ns::Extension` nor `Extension` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Extension")
@@ -644,8 +665,7 @@ mod extra_fields_forbidden {
                 "attributes": {},
                 "bogus": "bogus"
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
-                .build(),
+            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`",
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Record")
                 .help("neither `ns::Record` nor `Record` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Record")
@@ -657,8 +677,7 @@ mod extra_fields_forbidden {
                 "attributes": {},
                 "element": {"type": "Long"},
             }),
-            &ExpectedErrorMessageBuilder::error( "unknown field `element`, expected `attributes` or `additionalAttributes`",)
-                .build(),
+            "unknown field `element`, expected `attributes` or `additionalAttributes`",
             &ExpectedErrorMessageBuilder::error( "failed to resolve type: Record",)
                 .help("neither `ns::Record` nor `Record` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Record")
@@ -670,8 +689,7 @@ mod extra_fields_forbidden {
                 "attributes": {},
                 "name": "ip",
             }),
-            &ExpectedErrorMessageBuilder::error( "unknown field `name`, expected `attributes` or `additionalAttributes`",)
-                 .build(),
+            "unknown field `name`, expected `attributes` or `additionalAttributes`",
             &ExpectedErrorMessageBuilder::error( "failed to resolve type: Record",)
                 .help("neither `ns::Record` nor `Record` refers to anything that has been declared as a common type")
                 .exactly_one_underline("Record")
@@ -690,7 +708,9 @@ mod extra_fields_forbidden {
                     "foo": {},
                 }
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `commonTypes`, `entityTypes`, `actions`, `annotations`").build(),
+            &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `commonTypes`, `entityTypes`, `actions`, `annotations`")
+                .help("unknown field is at `/ns/foo`")
+                .build(),
             &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `commonTypes`, `entityTypes`, `actions`").build(),
         );
     }
@@ -708,7 +728,9 @@ mod extra_fields_forbidden {
                     "actions": {},
                 }
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `memberOfTypes`, `shape`, `tags`, `enum`, `annotations`").build(),
+            &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `memberOfTypes`, `shape`, `tags`, `enum`, `annotations`")
+                .help("unknown field is at `/ns/entityTypes/User/foo`")
+                .build(),
             &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected `memberOfTypes` or `shape`").build(),
         );
     }
@@ -727,7 +749,9 @@ mod extra_fields_forbidden {
                     },
                 }
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `attributes`, `appliesTo`, `memberOf`, `annotations`").build(),
+            &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `attributes`, `appliesTo`, `memberOf`, `annotations`")
+                .help("unknown field is at `/ns/actions/act/foo`")
+                .build(),
             &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `attributes`, `appliesTo`, `memberOf`").build(),
         );
     }
@@ -752,6 +776,7 @@ mod extra_fields_forbidden {
             &ExpectedErrorMessageBuilder::error(
                 "unknown field `foo`, expected one of `resourceTypes`, `principalTypes`, `context`",
             )
+            .help("unknown field is at `/ns/actions/act/appliesTo/foo`")
             .build(),
             &ExpectedErrorMessageBuilder::error(
                 "unknown field `foo`, expected one of `resourceTypes`, `principalTypes`, `context`",
@@ -16,7 +16,7 @@
 
 //! This module defines the publicly exported error types.
 
-use crate::{EntityUid, PolicyId};
+use crate::{EntityUid, ParseLimit, PolicyId, SlotId};
 pub use cedar_policy_core::ast::{
     expression_construction_errors, restricted_expr_errors, ContainsUnknown,
     ExpressionConstructionError, PartialValueToValueError, RestrictedExpressionError,
@@ -36,8 +36,11 @@ pub use cedar_policy_core::validator::entity_manifest::slicing::EntitySliceError
 use cedar_policy_core::validator::entity_manifest::{
     self, PartialExpressionError, PartialRequestError, UnsupportedCedarFeatureError,
 };
-pub use cedar_policy_core::validator::{schema_errors, SchemaError};
+pub use cedar_policy_core::validator::{
+    schema_errors, ExampleDataError, SchemaError, WarningCategory, WarningKindInfo,
+};
 use cedar_policy_core::{ast, authorizer, est, pst};
+use itertools::Itertools;
 use miette::Diagnostic;
 use ref_cast::RefCast;
 use serde::ser::Error;
@@ -62,8 +65,9 @@ pub mod entities_json_errors {
     pub use cedar_policy_core::entities::json::err::{
         ActionParentIsNotAction, DuplicateKey, ExpectedExtnValue, ExpectedLiteralEntityRef,
         ExtnCall0Arguments, ExtnCall2OrMoreArguments, JsonDeserializationError, JsonError,
-        JsonSerializationError, MissingImpliedConstructor, MissingRequiredRecordAttr, ParseEscape,
-        ReservedKey, Residual, TypeMismatch, UnexpectedRecordAttr, UnexpectedRestrictedExprKind,
+        JsonSerializationError, MissingImpliedConstructor, MissingRequiredRecordAttr,
+        NestingTooDeep, ParseEscape, ReservedKey, Residual, TypeMismatch, UnexpectedRecordAttr,
+        UnexpectedRestrictedExprKind,
     };
 }
 
@@ -189,6 +193,13 @@ pub enum ToCedarSchemaError {
     #[diagnostic(transparent)]
     #[error(transparent)]
     UnconvertibleEntityTypeShape(#[from] to_cedar_syntax_errors::UnconvertibleEntityTypeShapeError),
+    /// Actions declared with no resource, which Cedar schema syntax cannot
+    /// express, were found in the schema
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    UnconvertibleNoResourceAction(
+        #[from] to_cedar_syntax_errors::UnconvertibleNoResourceActionError,
+    ),
 }
 
 /// Error subtypes for [`ToCedarSchemaError`]
@@ -234,6 +245,22 @@ pub mod to_cedar_syntax_errors {
                 .map(std::string::String::as_str)
         }
     }
+
+    /// Actions declared with no resource, which Cedar schema syntax cannot
+    /// express, were found in the schema
+    #[derive(Debug, Error, Diagnostic)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    pub struct UnconvertibleNoResourceActionError(
+        #[from] cedar_policy_core::validator::cedar_schema::fmt::UnconvertibleNoResourceActionError,
+    );
+
+    impl UnconvertibleNoResourceActionError {
+        /// Get the names of the actions declared with no resource
+        pub fn names(&self) -> impl Iterator<Item = &str> {
+            self.0.names().map(smol_str::SmolStr::as_str)
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -261,6 +288,9 @@ impl From<cedar_policy_core::validator::cedar_schema::fmt::ToCedarSchemaSyntaxEr
                     .collect(),
                 err,
             }.into(),
+            cedar_policy_core::validator::cedar_schema::fmt::ToCedarSchemaSyntaxError::UnconvertibleNoResourceAction(err) => {
+                to_cedar_syntax_errors::UnconvertibleNoResourceActionError::from(err).into()
+            }
         }
     }
 }
@@ -398,7 +428,29 @@ pub mod validation_errors;
 
 /// An error generated by the validator when it finds a potential problem in a
 /// policy.
-#[derive(Debug, Clone, Error, Diagnostic)]
+///
+/// Errors compare equal and hash alike when they report the same problem at
+/// the same source location in the same policy, so duplicate errors from
+/// several validation passes can be removed with a `HashSet`. This also
+/// allows tests to assert on the exact output of the validator:
+///
+/// ```
+/// # use cedar_policy::{PolicySet, Schema, ValidationMode, Validator};
+/// # use std::str::FromStr;
+/// let (schema, _) = Schema::from_cedarschema_str(
+///     "entity User; action view appliesTo { principal: User, resource: User };",
+/// )
+/// .unwrap();
+/// let policies =
+///     PolicySet::from_str("permit(principal, action, resource) when { principal.age > 3 };")
+///         .unwrap();
+/// let validator = Validator::new(schema);
+/// let first = validator.validate(&policies, ValidationMode::default());
+/// let second = validator.validate(&policies, ValidationMode::default());
+/// assert_eq!(first.validation_errors().count(), 1);
+/// assert!(first.validation_errors().eq(second.validation_errors()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
 #[non_exhaustive]
 pub enum ValidationError {
     /// A policy contains an entity type that is not declared in the schema.
@@ -424,6 +476,27 @@ pub enum ValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     IncompatibleTypes(#[from] validation_errors::IncompatibleTypes),
+    /// The `then` and `else` branches of a conditional have incompatible
+    /// types, e.g., `if context.b then 1 else "x"`.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    IncompatibleBranches(#[from] validation_errors::IncompatibleBranches),
+    /// The elements of a set literal have incompatible types, e.g.,
+    /// `[1, "a"]`.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    IncompatibleSetElements(#[from] validation_errors::IncompatibleSetElements),
+    /// The operands of an `==` or `in` expression confuse a set with a single
+    /// value, e.g., `resource.tags == "admin"` where `tags` is a set.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    SetScalarConfusion(#[from] validation_errors::SetScalarConfusion),
+    /// An attribute of a record literal does not match the record type it is
+    /// compared against: it is not in the record type, it is required by the
+    /// record type but missing, or it has an incompatible type.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    RecordLiteralMismatch(#[from] validation_errors::RecordLiteralMismatch),
     /// The typechecker detected an access to a record or entity attribute
     /// that it could not statically guarantee would be present.
     #[error(transparent)]
@@ -462,6 +535,11 @@ pub enum ValidationError {
     #[diagnostic(transparent)]
     #[error(transparent)]
     NonLitExtConstructor(#[from] validation_errors::NonLitExtConstructor),
+    /// Returned by [`crate::Validator::validate_policies_str()`] for a policy
+    /// which could not be parsed, and so was not validated.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    ParseError(#[from] validation_errors::ParseError),
     /// This error type is no longer ever returned, but remains here for
     /// backwards-compatibility (removing the variant entirely would be a
     /// breaking change).
@@ -481,17 +559,38 @@ pub enum ValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     InvalidEnumEntity(#[from] validation_errors::InvalidEnumEntity),
+    /// Returned when a template slot appears in a policy condition rather than
+    /// in the scope constraint it belongs to. The parser never produces such a
+    /// template, but one constructed programmatically may contain it.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MisplacedTemplateSlot(#[from] validation_errors::MisplacedTemplateSlot),
+    /// Returned when an arithmetic operation on integer constants always
+    /// overflows a 64-bit integer
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ArithmeticOverflow(#[from] validation_errors::ArithmeticOverflow),
+    /// Returned when the resource scope constraint of a policy names an
+    /// entity, but the policy only applies to actions declared with no
+    /// resource
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnspecifiedEntity(#[from] validation_errors::UnspecifiedEntity),
 }
 
 impl ValidationError {
     /// Extract the policy id of the policy where the validator found the issue.
-    pub fn policy_id(&self) -> &crate::PolicyId {
+    pub fn policy_id(&self) -> &PolicyId {
         match self {
             Self::UnrecognizedEntityType(e) => e.policy_id(),
             Self::UnrecognizedActionId(e) => e.policy_id(),
             Self::InvalidActionApplication(e) => e.policy_id(),
             Self::UnexpectedType(e) => e.policy_id(),
             Self::IncompatibleTypes(e) => e.policy_id(),
+            Self::IncompatibleBranches(e) => e.policy_id(),
+            Self::IncompatibleSetElements(e) => e.policy_id(),
+            Self::SetScalarConfusion(e) => e.policy_id(),
+            Self::RecordLiteralMismatch(e) => e.policy_id(),
             Self::UnsafeAttributeAccess(e) => e.policy_id(),
             Self::UnsafeOptionalAttributeAccess(e) => e.policy_id(),
             Self::UnsafeTagAccess(e) => e.policy_id(),
@@ -501,10 +600,33 @@ impl ValidationError {
             Self::FunctionArgumentValidation(e) => e.policy_id(),
             Self::EmptySetForbidden(e) => e.policy_id(),
             Self::NonLitExtConstructor(e) => e.policy_id(),
+            Self::ParseError(e) => e.policy_id(),
             Self::HierarchyNotRespected(e) => e.policy_id(),
             Self::InternalInvariantViolation(e) => e.policy_id(),
             Self::EntityDerefLevelViolation(e) => e.policy_id(),
             Self::InvalidEnumEntity(e) => e.policy_id(),
+            Self::MisplacedTemplateSlot(e) => e.policy_id(),
+            Self::ArithmeticOverflow(e) => e.policy_id(),
+            Self::UnspecifiedEntity(e) => e.policy_id(),
+        }
+    }
+
+    /// Display this error prefixed by the id of the policy and the line and
+    /// column where the validator found the issue, e.g.,
+    /// ``policy `p1`:3:12: unexpected type: ...``.
+    ///
+    /// `src` must be the text the policy was parsed from, i.e., the whole
+    /// policy set text for policies parsed as part of a policy set. Lines and
+    /// columns are 1-based, with columns counted in characters. If the location
+    /// of the issue is not within `src`, its byte offset and length are
+    /// displayed instead, and if it is not known, only the policy id is. If
+    /// the policy was parsed with [`crate::parse_policyset_named`], the prefix
+    /// starts with the source name, e.g., ``policies.cedar: policy `p1`:3:12``.
+    pub fn display_with_source<'a>(&'a self, src: &'a str) -> impl std::fmt::Display + 'a {
+        DisplayWithSource {
+            policy_id: self.policy_id(),
+            issue: self,
+            src,
         }
     }
 }
@@ -528,6 +650,18 @@ impl From<cedar_policy_core::validator::ValidationError> for ValidationError {
             cedar_policy_core::validator::ValidationError::IncompatibleTypes(e) => {
                 Self::IncompatibleTypes(e.into())
             }
+            cedar_policy_core::validator::ValidationError::IncompatibleBranches(e) => {
+                Self::IncompatibleBranches(e.into())
+            }
+            cedar_policy_core::validator::ValidationError::IncompatibleSetElements(e) => {
+                Self::IncompatibleSetElements(e.into())
+            }
+            cedar_policy_core::validator::ValidationError::SetScalarConfusion(e) => {
+                Self::SetScalarConfusion(e.into())
+            }
+            cedar_policy_core::validator::ValidationError::RecordLiteralMismatch(e) => {
+                Self::RecordLiteralMismatch(e.into())
+            }
             cedar_policy_core::validator::ValidationError::UnsafeAttributeAccess(e) => {
                 Self::UnsafeAttributeAccess(e.into())
             }
@@ -555,6 +689,9 @@ impl From<cedar_policy_core::validator::ValidationError> for ValidationError {
             cedar_policy_core::validator::ValidationError::NonLitExtConstructor(e) => {
                 Self::NonLitExtConstructor(e.into())
             }
+            cedar_policy_core::validator::ValidationError::ParseError(e) => {
+                Self::ParseError(e.into())
+            }
             cedar_policy_core::validator::ValidationError::InternalInvariantViolation(e) => {
                 Self::InternalInvariantViolation(e.into())
             }
@@ -564,6 +701,69 @@ impl From<cedar_policy_core::validator::ValidationError> for ValidationError {
             cedar_policy_core::validator::ValidationError::EntityDerefLevelViolation(e) => {
                 Self::EntityDerefLevelViolation(e.into())
             }
+            cedar_policy_core::validator::ValidationError::MisplacedTemplateSlot(e) => {
+                Self::MisplacedTemplateSlot(e.into())
+            }
+            cedar_policy_core::validator::ValidationError::ArithmeticOverflow(e) => {
+                Self::ArithmeticOverflow(e.into())
+            }
+            cedar_policy_core::validator::ValidationError::UnspecifiedEntity(e) => {
+                Self::UnspecifiedEntity(e.into())
+            }
+        }
+    }
+}
+
+/// An error found by [`crate::Validator::validate_entities`] in entity data.
+///
+/// Unlike a [`ValidationError`], which is found in a policy, it is located by
+/// the uid of the entity which does not conform to the schema.
+///
+/// Marked as `non_exhaustive` to allow adding additional errors in the future
+/// as a non-breaking change.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum EntityValidationError {
+    /// An entity is missing an attribute which the schema declares as required
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingRequiredAttribute(#[from] validation_errors::MissingRequiredAttribute),
+    /// An entity attribute value does not match the type declared in the
+    /// schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    EntityAttributeTypeMismatch(#[from] validation_errors::EntityAttributeTypeMismatch),
+    /// An entity has a parent whose type is not in the `memberOfTypes` of the
+    /// entity's type
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidEntityParent(#[from] validation_errors::InvalidEntityParent),
+}
+
+impl EntityValidationError {
+    /// Extract the uid of the entity where the validator found the issue.
+    pub fn entity_uid(&self) -> &crate::EntityUid {
+        match self {
+            Self::MissingRequiredAttribute(e) => e.entity_uid(),
+            Self::EntityAttributeTypeMismatch(e) => e.entity_uid(),
+            Self::InvalidEntityParent(e) => e.entity_uid(),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl From<cedar_policy_core::validator::EntityValidationError> for EntityValidationError {
+    fn from(error: cedar_policy_core::validator::EntityValidationError) -> Self {
+        match error {
+            cedar_policy_core::validator::EntityValidationError::MissingRequiredAttribute(e) => {
+                Self::MissingRequiredAttribute(e.into())
+            }
+            cedar_policy_core::validator::EntityValidationError::EntityAttributeTypeMismatch(e) => {
+                Self::EntityAttributeTypeMismatch(e.into())
+            }
+            cedar_policy_core::validator::EntityValidationError::InvalidEntityParent(e) => {
+                Self::InvalidEntityParent(e.into())
+            }
         }
     }
 }
@@ -578,7 +778,10 @@ pub mod validation_warnings;
 ///
 /// Marked as `non_exhaustive` to allow adding additional warnings in the future
 /// as a non-breaking change.
-#[derive(Debug, Clone, Error, Diagnostic)]
+///
+/// Like [`ValidationError`], warnings compare equal and hash alike when they
+/// report the same problem at the same source location in the same policy.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
 #[non_exhaustive]
 pub enum ValidationWarning {
     /// A string contains a mix of characters for different scripts (e.g., latin
@@ -612,6 +815,48 @@ pub enum ValidationWarning {
     #[diagnostic(transparent)]
     #[error(transparent)]
     ImpossiblePolicy(#[from] validation_warnings::ImpossiblePolicy),
+    /// An `action in` scope constraint references an action group which has
+    /// no member actions and does not apply to any request itself, so it
+    /// cannot match any request.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    EmptyActionGroup(#[from] validation_warnings::EmptyActionGroup),
+    /// A `permit` policy has no scope constraints and no conditions, so it
+    /// allows every request. This is often a starter policy which was left
+    /// unedited by mistake. This warning is disabled by default, and can be
+    /// enabled with [`crate::Validator::with_overly_permissive_policy_warning`].
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    OverlyPermissivePolicy(#[from] validation_warnings::OverlyPermissivePolicy),
+    /// An attribute is accessed with dot syntax but is named like a built-in
+    /// method (e.g., `principal.isEmpty`) or an extension method (e.g.,
+    /// `context.isLoopback`), so the access may be mistaken for a method call
+    /// missing its argument list. Accesses written with bracket syntax, e.g.,
+    /// `principal["isEmpty"]`, are not reported.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    AmbiguousAttributeName(#[from] validation_warnings::AmbiguousAttributeName),
+    /// A `like` pattern is empty or contains no wildcards, e.g.,
+    /// `resource.name like "report"`, so the `like` is equivalent to an `==`
+    /// comparison, which may have been intended instead.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    SuspiciousLikePattern(#[from] validation_warnings::SuspiciousLikePattern),
+    /// An expression mixes `&&` and `||`, e.g., `a && b || c`, or applies `!`
+    /// to the left operand of a comparison, e.g., `!x == y`, without
+    /// parentheses, so it may not mean what it appears to. This warning is
+    /// disabled by default, and can be enabled with
+    /// [`crate::Validator::with_ambiguous_precedence_warning`].
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    AmbiguousPrecedence(#[from] validation_warnings::AmbiguousPrecedence),
+    /// An `is` test in a policy condition is always true or always false for
+    /// the entity types in the schema, e.g., `resource is Album` where the
+    /// schema only allows `Photo` resources. Tests are checked against every
+    /// request the schema allows, regardless of the scope of the policy.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    TrivialTypeTest(#[from] validation_warnings::TrivialTypeTest),
 }
 
 impl ValidationWarning {
@@ -624,10 +869,120 @@ impl ValidationWarning {
             Self::MixedScriptIdentifier(w) => w.policy_id(),
             Self::ConfusableIdentifier(w) => w.policy_id(),
             Self::ImpossiblePolicy(w) => w.policy_id(),
+            Self::EmptyActionGroup(w) => w.policy_id(),
+            Self::OverlyPermissivePolicy(w) => w.policy_id(),
+            Self::AmbiguousAttributeName(w) => w.policy_id(),
+            Self::SuspiciousLikePattern(w) => w.policy_id(),
+            Self::AmbiguousPrecedence(w) => w.policy_id(),
+            Self::TrivialTypeTest(w) => w.policy_id(),
+        }
+    }
+
+    /// Get metadata about the kind of this warning, i.e., its code, category,
+    /// default severity, and description.
+    pub fn kind_info(&self) -> &'static WarningKindInfo {
+        use cedar_policy_core::validator::validation_warnings as core_warnings;
+        match self {
+            Self::MixedScriptString(_) => &core_warnings::MixedScriptString::KIND,
+            Self::BidiCharsInString(_) => &core_warnings::BidiCharsInString::KIND,
+            Self::BidiCharsInIdentifier(_) => &core_warnings::BidiCharsInIdentifier::KIND,
+            Self::MixedScriptIdentifier(_) => &core_warnings::MixedScriptIdentifier::KIND,
+            Self::ConfusableIdentifier(_) => &core_warnings::ConfusableIdentifier::KIND,
+            Self::ImpossiblePolicy(_) => &core_warnings::ImpossiblePolicy::KIND,
+            Self::EmptyActionGroup(_) => &core_warnings::EmptyActionGroup::KIND,
+            Self::OverlyPermissivePolicy(_) => &core_warnings::OverlyPermissivePolicy::KIND,
+            Self::AmbiguousAttributeName(_) => &core_warnings::AmbiguousAttributeName::KIND,
+            Self::SuspiciousLikePattern(_) => &core_warnings::SuspiciousLikePattern::KIND,
+            Self::AmbiguousPrecedence(_) => &core_warnings::AmbiguousPrecedence::KIND,
+            Self::TrivialTypeTest(_) => &core_warnings::TrivialTypeTest::KIND,
+        }
+    }
+
+    /// Display this warning prefixed by the id of the policy and the line and
+    /// column where the validator found the issue, e.g.,
+    /// ``policy `p1`:3:12: string "..." contains mixed scripts``.
+    ///
+    /// See [`ValidationError::display_with_source`] for the requirements on
+    /// `src`.
+    pub fn display_with_source<'a>(&'a self, src: &'a str) -> impl std::fmt::Display + 'a {
+        DisplayWithSource {
+            policy_id: self.policy_id(),
+            issue: self,
+            src,
+        }
+    }
+}
+
+/// An error or warning from a [`crate::ValidationResult`], as yielded by its
+/// [`IntoIterator`] implementation
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
+pub enum ValidationIssue {
+    /// An error found by the validator
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Error(ValidationError),
+    /// A warning found by the validator
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Warning(ValidationWarning),
+}
+
+impl ValidationIssue {
+    /// Get the [`PolicyId`] of the policy or template the error or warning is
+    /// in
+    pub fn policy_id(&self) -> &PolicyId {
+        match self {
+            Self::Error(err) => err.policy_id(),
+            Self::Warning(warning) => warning.policy_id(),
+        }
+    }
+}
+
+/// A validation error or warning displayed with its location in the policy
+/// text `src`
+struct DisplayWithSource<'a, D> {
+    policy_id: &'a PolicyId,
+    issue: &'a D,
+    src: &'a str,
+}
+
+impl<D: Diagnostic> std::fmt::Display for DisplayWithSource<'_, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let span = self
+            .issue
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .map(|label| *label.inner());
+        // The source code of the issue is named if the policy was parsed with
+        // `parse_policyset_named`
+        let source_name = span.and_then(|span| {
+            let contents = self.issue.source_code()?.read_span(&span, 0, 0).ok()?;
+            contents.name().map(ToString::to_string)
+        });
+        if let Some(source_name) = source_name {
+            write!(f, "{source_name}: ")?;
+        }
+        write!(f, "policy `{}`", self.policy_id)?;
+        if let Some(span) = span {
+            match line_and_column(self.src, span.offset()) {
+                Some((line, column)) => write!(f, ":{line}:{column}")?,
+                None => write!(f, "@{}+{}", span.offset(), span.len())?,
+            }
         }
+        write!(f, ": {}", self.issue)
     }
 }
 
+/// The 1-based line and column, with columns counted in characters, of the
+/// byte `offset` in `src`, or `None` if `offset` is not within `src`
+pub(crate) fn line_and_column(src: &str, offset: usize) -> Option<(usize, usize)> {
+    let before = src.get(..offset)?;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    let column = before.get(line_start..).map_or(0, |l| l.chars().count()) + 1;
+    Some((line, column))
+}
+
 #[doc(hidden)]
 impl From<cedar_policy_core::validator::ValidationWarning> for ValidationWarning {
     fn from(warning: cedar_policy_core::validator::ValidationWarning) -> Self {
@@ -650,10 +1005,424 @@ impl From<cedar_policy_core::validator::ValidationWarning> for ValidationWarning
             cedar_policy_core::validator::ValidationWarning::ImpossiblePolicy(w) => {
                 Self::ImpossiblePolicy(w.into())
             }
+            cedar_policy_core::validator::ValidationWarning::EmptyActionGroup(w) => {
+                Self::EmptyActionGroup(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::OverlyPermissivePolicy(w) => {
+                Self::OverlyPermissivePolicy(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::AmbiguousAttributeName(w) => {
+                Self::AmbiguousAttributeName(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::SuspiciousLikePattern(w) => {
+                Self::SuspiciousLikePattern(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::AmbiguousPrecedence(w) => {
+                Self::AmbiguousPrecedence(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::TrivialTypeTest(w) => {
+                Self::TrivialTypeTest(w.into())
+            }
         }
     }
 }
 
+/// A warning found by the validator about the policy set as a whole.
+///
+/// These warnings are returned by
+/// [`crate::ValidationResult::policy_set_warnings`], separately from the
+/// [`ValidationWarning`]s found in policies.
+///
+/// Marked as `non_exhaustive` to allow adding additional warnings in the future
+/// as a non-breaking change.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum PolicySetWarning {
+    /// The policy set being validated contains no policies or templates. This
+    /// usually means the policies were loaded from the wrong location. This
+    /// warning is disabled by default, and can be enabled with
+    /// [`crate::Validator::with_empty_policy_set_warning`].
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    EmptyPolicySet(#[from] validation_warnings::EmptyPolicySet),
+}
+
+impl PolicySetWarning {
+    /// Get metadata about the kind of this warning, i.e., its code, category,
+    /// default severity, and description.
+    pub fn kind_info(&self) -> &'static WarningKindInfo {
+        use cedar_policy_core::validator::validation_warnings as core_warnings;
+        match self {
+            Self::EmptyPolicySet(_) => &core_warnings::EmptyPolicySet::KIND,
+        }
+    }
+}
+
+#[doc(hidden)]
+impl From<cedar_policy_core::validator::PolicySetWarning> for PolicySetWarning {
+    fn from(warning: cedar_policy_core::validator::PolicySetWarning) -> Self {
+        match warning {
+            cedar_policy_core::validator::PolicySetWarning::EmptyPolicySet(w) => {
+                Self::EmptyPolicySet(w.into())
+            }
+        }
+    }
+}
+
+/// Error subtypes for [`SchemaBuilderError`]
+pub mod schema_builder_errors {
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// A name given to a schema builder is not a valid name of the expected
+    /// kind
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("in namespace `{namespace}`, declaration `{declaration}`: invalid {kind} `{name}`")]
+    pub struct InvalidNameError {
+        pub(crate) namespace: String,
+        pub(crate) declaration: String,
+        pub(crate) name: String,
+        pub(crate) kind: &'static str,
+    }
+
+    impl InvalidNameError {
+        /// The namespace containing the invalid name (`""` for the empty
+        /// namespace)
+        pub fn namespace(&self) -> &str {
+            &self.namespace
+        }
+
+        /// The declaration (common type, entity type, or action) containing
+        /// the invalid name
+        pub fn declaration(&self) -> &str {
+            &self.declaration
+        }
+
+        /// The invalid name
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    /// A declaration given to a schema builder is not valid, e.g., an
+    /// enumerated entity type which also has attributes
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("in namespace `{namespace}`, declaration `{declaration}`: {reason}")]
+    pub struct InvalidDeclarationError {
+        pub(crate) namespace: String,
+        pub(crate) declaration: String,
+        pub(crate) reason: &'static str,
+    }
+
+    impl InvalidDeclarationError {
+        /// The namespace containing the invalid declaration (`""` for the
+        /// empty namespace)
+        pub fn namespace(&self) -> &str {
+            &self.namespace
+        }
+
+        /// The invalid declaration (namespace, common type, entity type, or
+        /// action)
+        pub fn declaration(&self) -> &str {
+            &self.declaration
+        }
+    }
+
+    /// A declaration, attribute, or annotation was given more than once to a
+    /// schema builder
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("in namespace `{namespace}`, declaration `{declaration}`: `{name}` is declared more than once")]
+    pub struct DuplicateDeclarationError {
+        pub(crate) namespace: String,
+        pub(crate) declaration: String,
+        pub(crate) name: String,
+    }
+
+    impl DuplicateDeclarationError {
+        /// The namespace containing the duplicate (`""` for the empty
+        /// namespace)
+        pub fn namespace(&self) -> &str {
+            &self.namespace
+        }
+
+        /// The declaration containing the duplicate. This is the same as
+        /// [`DuplicateDeclarationError::name`] for duplicate declarations.
+        pub fn declaration(&self) -> &str {
+            &self.declaration
+        }
+
+        /// The name which was declared more than once
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+}
+
+/// Errors when building a schema with a [`crate::SchemaBuilder`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum SchemaBuilderError {
+    /// A name is not valid
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidName(#[from] schema_builder_errors::InvalidNameError),
+    /// Something was declared more than once
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    DuplicateDeclaration(#[from] schema_builder_errors::DuplicateDeclarationError),
+    /// A declaration is not valid
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidDeclaration(#[from] schema_builder_errors::InvalidDeclarationError),
+    /// The schema is not valid, e.g., because it refers to an undeclared type
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Schema(#[from] SchemaError),
+}
+
+/// Error subtypes for [`PolicyBuilderError`]
+pub mod policy_builder_errors {
+    use crate::SlotId;
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// A policy built as a static policy with a [`crate::PolicyBuilder`]
+    /// contains a template slot
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("expected a static policy, but found the template slot `{slot}`")]
+    #[diagnostic(help("use `PolicyBuilder::build_template` to build a template"))]
+    pub struct UnexpectedSlotError {
+        pub(crate) slot: SlotId,
+    }
+
+    impl UnexpectedSlotError {
+        /// The slot found in the policy
+        pub fn slot(&self) -> &SlotId {
+            &self.slot
+        }
+    }
+
+    /// An annotation added with [`crate::PolicyBuilder::annotation`] has a key
+    /// which is not a valid identifier
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("`{key}` is not a valid annotation key")]
+    pub struct InvalidAnnotationKeyError {
+        pub(crate) key: String,
+    }
+
+    impl InvalidAnnotationKeyError {
+        /// The invalid key
+        pub fn key(&self) -> &str {
+            &self.key
+        }
+    }
+}
+
+/// Errors when building a policy with a [`crate::PolicyBuilder`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum PolicyBuilderError {
+    /// A static policy contains a template slot
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnexpectedSlot(#[from] policy_builder_errors::UnexpectedSlotError),
+    /// An annotation key is not a valid identifier
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidAnnotationKey(#[from] policy_builder_errors::InvalidAnnotationKeyError),
+}
+
+/// Error subtypes for [`ConcreteRequestBuilderError`]
+pub mod concrete_request_builder_errors {
+    use crate::EntityUid;
+    use miette::Diagnostic;
+    use smol_str::SmolStr;
+    use thiserror::Error;
+
+    /// The principal, action or resource of a request was not set
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("the {component} of the request was not set")]
+    pub struct MissingComponentError {
+        pub(crate) component: &'static str,
+    }
+
+    impl MissingComponentError {
+        /// The component which was not set: `principal`, `action` or
+        /// `resource`
+        pub fn component(&self) -> &str {
+            self.component
+        }
+    }
+
+    /// The context of a request does not have an attribute required by the
+    /// schema for the request's action
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("the context is missing the attribute `{attr}`, which is required for `{action}`")]
+    pub struct MissingContextAttributeError {
+        pub(crate) attr: SmolStr,
+        pub(crate) action: EntityUid,
+    }
+
+    impl MissingContextAttributeError {
+        /// The missing attribute
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+
+        /// The action of the request
+        pub fn action(&self) -> &EntityUid {
+            &self.action
+        }
+    }
+
+    /// The context of a request has an attribute which is not declared in the
+    /// schema for the request's action
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("the context has the attribute `{attr}`, which is not declared for `{action}`")]
+    pub struct UnexpectedContextAttributeError {
+        pub(crate) attr: SmolStr,
+        pub(crate) action: EntityUid,
+    }
+
+    impl UnexpectedContextAttributeError {
+        /// The undeclared attribute
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+
+        /// The action of the request
+        pub fn action(&self) -> &EntityUid {
+            &self.action
+        }
+    }
+
+    /// An attribute of the context of a request does not have the type
+    /// declared in the schema for the request's action
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("the context attribute `{attr}` should have type `{expected}` for `{action}`")]
+    pub struct ContextAttributeTypeError {
+        pub(crate) attr: SmolStr,
+        pub(crate) expected: String,
+        pub(crate) action: EntityUid,
+    }
+
+    impl ContextAttributeTypeError {
+        /// The mistyped attribute
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+
+        /// The type declared for the attribute, in Cedar schema syntax
+        pub fn expected(&self) -> &str {
+            &self.expected
+        }
+
+        /// The action of the request
+        pub fn action(&self) -> &EntityUid {
+            &self.action
+        }
+    }
+}
+
+/// Errors when building a request with a [`crate::ConcreteRequestBuilder`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum ConcreteRequestBuilderError {
+    /// The principal, action or resource was not set
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingComponent(#[from] concrete_request_builder_errors::MissingComponentError),
+    /// The context is missing an attribute required by the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingContextAttribute(#[from] concrete_request_builder_errors::MissingContextAttributeError),
+    /// The context has an attribute not declared in the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnexpectedContextAttribute(
+        #[from] concrete_request_builder_errors::UnexpectedContextAttributeError,
+    ),
+    /// A context attribute does not have the type declared in the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ContextAttributeType(#[from] concrete_request_builder_errors::ContextAttributeTypeError),
+    /// The context could not be created
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ContextCreation(#[from] ContextCreationError),
+    /// The request does not conform to the schema, e.g., because the action
+    /// is not declared in the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    RequestValidation(#[from] RequestValidationError),
+}
+
+/// Error subtypes for [`PolicySetFromFilesError`]
+pub mod policy_set_from_files_errors {
+    use crate::PolicyId;
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// Two policies or templates loaded by [`crate::PolicySet::from_files`]
+    /// have the same id
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("policy id `{id}` in `{second_file}` is already used in `{first_file}`")]
+    #[diagnostic(help("give one of the policies a different `@id` annotation"))]
+    pub struct DuplicateIdError {
+        pub(crate) id: PolicyId,
+        pub(crate) first_file: String,
+        pub(crate) second_file: String,
+    }
+
+    impl DuplicateIdError {
+        /// The duplicated id
+        pub fn id(&self) -> &PolicyId {
+            &self.id
+        }
+
+        /// The name of the file where the id is first used
+        pub fn first_file(&self) -> &str {
+            &self.first_file
+        }
+
+        /// The name of the file where the id is used again, which is the same
+        /// as [`DuplicateIdError::first_file`] if both uses are in one file
+        pub fn second_file(&self) -> &str {
+            &self.second_file
+        }
+    }
+}
+
+/// Errors when loading a policy set with [`crate::PolicySet::from_files`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum PolicySetFromFilesError {
+    /// A file failed to parse
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Parse(#[from] NamedParseErrors),
+    /// Two policies or templates have the same id
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    DuplicateId(#[from] policy_set_from_files_errors::DuplicateIdError),
+}
+
+/// Error returned by [`crate::Template::link_values_complete`] when some slots
+/// of a template are not given a value
+#[derive(Debug, Clone, PartialEq, Eq, Diagnostic, Error)]
+#[error("no values provided for the template slots: {}", .slots.iter().join(", "))]
+pub struct MissingSlots {
+    pub(crate) slots: Vec<SlotId>,
+}
+
+impl MissingSlots {
+    /// The slots which are not given a value, in the order they appear in the
+    /// template
+    pub fn slots(&self) -> impl Iterator<Item = &SlotId> {
+        self.slots.iter()
+    }
+}
+
 /// Error subtypes for [`PolicySetError`]
 pub mod policy_set_errors {
     use super::Error;
@@ -929,6 +1698,113 @@ impl ParseErrors {
     pub fn iter(&self) -> impl Iterator<Item = &ParseError> {
         self.0.iter().map(ParseError::ref_cast)
     }
+
+    /// Get the id of the policy or template these errors were found in, when
+    /// parsing a single policy or template, e.g., with [`crate::Policy::parse`]
+    /// or [`crate::Template::parse`]. This is the id passed to the parser, or
+    /// the default id the policy would have been given if none was passed.
+    ///
+    /// Returns `None` for errors found while parsing a policy set.
+    pub fn policy_id(&self) -> Option<&PolicyId> {
+        self.0.policy_id().map(PolicyId::ref_cast)
+    }
+}
+
+/// [`ParseErrors`] together with the text they were parsed from and a name for
+/// that text, such as a file name. Returned by [`crate::parse_policyset_named`].
+///
+/// Unlike [`ParseErrors`], the `Diagnostic` implementation reports every error
+/// at once: the source is reported under its name, each error is a label on
+/// its span, and the tokens the parser expected at each error are included in
+/// the help text.
+///
+/// It is displayed as the first error prefixed by the source name and the line
+/// and column of the error, e.g., ``policies.cedar:1:25: unexpected token `)` ``.
+#[derive(Debug, Error)]
+pub struct NamedParseErrors {
+    errors: ParseErrors,
+    named_source: miette::NamedSource<String>,
+}
+
+impl NamedParseErrors {
+    pub(crate) fn new(errors: ParseErrors, source_name: impl AsRef<str>, text: &str) -> Self {
+        Self {
+            errors,
+            named_source: miette::NamedSource::new(source_name, text.to_string()),
+        }
+    }
+
+    /// The name of the source the errors were found in
+    pub fn source_name(&self) -> &str {
+        self.named_source.name()
+    }
+
+    /// Get the underlying [`ParseErrors`]
+    pub fn errors(&self) -> &ParseErrors {
+        &self.errors
+    }
+
+    /// Consume this error, returning the underlying [`ParseErrors`]
+    pub fn into_errors(self) -> ParseErrors {
+        self.errors
+    }
+}
+
+impl std::fmt::Display for NamedParseErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source_name())?;
+        let offset = self
+            .errors
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .map(|label| label.offset());
+        if let Some((line, column)) =
+            offset.and_then(|offset| line_and_column(self.named_source.inner(), offset))
+        {
+            write!(f, ":{line}:{column}")?;
+        }
+        write!(f, ": {}", self.errors)
+    }
+}
+
+impl Diagnostic for NamedParseErrors {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.errors.code()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.named_source)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        Some(Box::new(self.errors.iter().flat_map(|err| {
+            err.labels().into_iter().flatten().map(move |label| {
+                miette::LabeledSpan::new_with_span(Some(err.to_string()), *label.inner())
+            })
+        })))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        // The parser reports the tokens it expected as the label of an error,
+        // which is replaced by the error message in `labels()` above
+        let hints = self
+            .errors
+            .iter()
+            .flat_map(|err| {
+                err.labels()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|label| label.label().map(ToString::to_string))
+                    .chain(err.help().map(|help| help.to_string()))
+            })
+            .unique()
+            .collect::<Vec<_>>();
+        if hints.is_empty() {
+            None
+        } else {
+            Some(Box::new(hints.join("\n")))
+        }
+    }
 }
 
 /// Errors that can occur when parsing policies or expressions.
@@ -945,6 +1821,23 @@ pub struct ParseError {
     inner: cedar_policy_core::parser::err::ParseError,
 }
 
+impl ParseError {
+    /// Get the keyword that an identifier was likely meant to be, when the
+    /// identifier appears where a keyword such as `permit`, `when`,
+    /// `principal` or `in` is expected and is a near miss for it. The
+    /// suggestion is also included in the help message of the error.
+    pub fn suggestion(&self) -> Option<&str> {
+        self.inner.suggestion()
+    }
+
+    /// Get the limit in [`ParseLimits`](crate::ParseLimits) which the input
+    /// exceeded, if that is what this error is for. See, e.g.,
+    /// [`crate::PolicySet::parse_with_limits`].
+    pub fn exceeded_limit(&self) -> Option<ParseLimit> {
+        self.inner.exceeded_limit()
+    }
+}
+
 /// Errors that can happen when getting the JSON representation of a policy
 #[derive(Debug, Diagnostic, Error)]
 pub enum PolicyToJsonError {
@@ -1007,12 +1900,85 @@ pub mod policy_to_json_errors {
 }
 
 /// Error when converting a policy or template from JSON format
-#[derive(Debug, Diagnostic, Error)]
+#[derive(Debug, Error)]
 #[error("error deserializing a policy/template from JSON")]
-#[diagnostic(transparent)]
 pub struct PolicyFromJsonError {
-    #[from]
+    #[source]
     pub(crate) inner: cedar_policy_core::est::FromJsonError,
+    /// JSON pointer to the part of the policy which could not be
+    /// deserialized, if known
+    pub(crate) json_pointer: Option<String>,
+}
+
+impl PolicyFromJsonError {
+    /// Error for the policy `json` which failed to deserialize with `err`
+    pub(crate) fn deserialization(err: serde_json::Error, json: &serde_json::Value) -> Self {
+        if let Some((json_pointer, literal)) =
+            cedar_policy_core::est::locate_out_of_range_integer(json)
+        {
+            return Self {
+                inner: cedar_policy_core::est::FromJsonError::IntegerLiteralOutOfRange { literal },
+                json_pointer: Some(json_pointer),
+            };
+        }
+        Self {
+            inner: entities_json_errors::JsonDeserializationError::from(err).into(),
+            json_pointer: cedar_policy_core::est::locate_deserialization_error(json),
+        }
+    }
+
+    /// JSON pointer (RFC 6901) to the innermost part of the policy which could
+    /// not be deserialized, e.g., `/conditions/0/body/==/right`, if the error
+    /// occurred while deserializing the JSON and its location is known
+    pub fn json_pointer(&self) -> Option<&str> {
+        self.json_pointer.as_deref()
+    }
+}
+
+impl From<cedar_policy_core::est::FromJsonError> for PolicyFromJsonError {
+    fn from(inner: cedar_policy_core::est::FromJsonError) -> Self {
+        Self {
+            inner,
+            json_pointer: None,
+        }
+    }
+}
+
+impl Diagnostic for PolicyFromJsonError {
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.json_pointer.as_ref().map_or_else(
+            || self.inner.help(),
+            |pointer| Some(Box::new(format!("the error is at `{pointer}`"))),
+        )
+    }
+
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.inner.code()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        self.inner.severity()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.inner.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.inner.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.inner.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.inner.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.inner.diagnostic_source()
+    }
 }
 
 /// Error type for parsing `Context` from JSON
@@ -1141,6 +2107,11 @@ pub enum RequestValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     InvalidEnumEntity(#[from] request_validation_errors::InvalidEnumEntityError),
+    /// Request action is declared with no resource, but the request resource
+    /// is not the action itself
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnspecifiedResource(#[from] request_validation_errors::UnspecifiedResourceError),
 }
 
 #[doc(hidden)]
@@ -1171,6 +2142,9 @@ impl From<cedar_policy_core::validator::RequestValidationError> for RequestValid
             cedar_policy_core::validator::RequestValidationError::InvalidEnumEntity(e) => {
                 Self::InvalidEnumEntity(e.into())
             }
+            cedar_policy_core::validator::RequestValidationError::UnspecifiedResource(e) => {
+                Self::UnspecifiedResource(e.into())
+            }
         }
     }
 }
@@ -1307,6 +2281,27 @@ pub mod request_validation_errors {
     pub struct InvalidEnumEntityError(
         #[from] cedar_policy_core::entities::conformance::err::InvalidEnumEntityError,
     );
+
+    /// Request action is declared with no resource, but the request resource
+    /// is not the action itself
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    pub struct UnspecifiedResourceError(
+        #[from] cedar_policy_core::validator::request_validation_errors::UnspecifiedResourceError,
+    );
+
+    impl UnspecifiedResourceError {
+        /// The resource of the request
+        pub fn resource(&self) -> &EntityUid {
+            RefCast::ref_cast(self.0.resource())
+        }
+
+        /// The action of the request, which is declared with no resource
+        pub fn action(&self) -> &EntityUid {
+            RefCast::ref_cast(self.0.action())
+        }
+    }
 }
 
 /// An error generated by entity slicing.
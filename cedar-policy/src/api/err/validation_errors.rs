@@ -14,11 +14,12 @@
  * limitations under the License.
  */
 
+use cedar_policy_core::validator::validation_errors::UnrecognizedActionIdHelp;
 use miette::Diagnostic;
 use ref_cast::RefCast;
 use thiserror::Error;
 
-use crate::PolicyId;
+use crate::{EntityUid, PolicyId, SlotId};
 
 // Required for doc link to `ValidationError` without qualifying it with
 // `crate`, but not used otherwise, so non-doc builds warned about unused
@@ -34,7 +35,7 @@ use crate::ValidationError;
 // documentation should be written.
 macro_rules! wrap_core_error {
     ($s:ident) => {
-        #[derive(Debug, Clone, Error, Diagnostic)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
         #[error(transparent)]
         #[diagnostic(transparent)]
         #[doc=concat!("Structure containing details about a [`ValidationError::", stringify!($s), "`].")]
@@ -61,6 +62,10 @@ wrap_core_error!(UnrecognizedActionId);
 wrap_core_error!(InvalidActionApplication);
 wrap_core_error!(UnexpectedType);
 wrap_core_error!(IncompatibleTypes);
+wrap_core_error!(IncompatibleBranches);
+wrap_core_error!(IncompatibleSetElements);
+wrap_core_error!(SetScalarConfusion);
+wrap_core_error!(RecordLiteralMismatch);
 wrap_core_error!(UnsafeAttributeAccess);
 wrap_core_error!(UnsafeOptionalAttributeAccess);
 wrap_core_error!(UnsafeTagAccess);
@@ -72,5 +77,196 @@ wrap_core_error!(HierarchyNotRespected);
 wrap_core_error!(EntityDerefLevelViolation);
 wrap_core_error!(EmptySetForbidden);
 wrap_core_error!(NonLitExtConstructor);
+wrap_core_error!(ParseError);
 wrap_core_error!(InternalInvariantViolation);
 wrap_core_error!(InvalidEnumEntity);
+wrap_core_error!(MisplacedTemplateSlot);
+wrap_core_error!(ArithmeticOverflow);
+wrap_core_error!(UnspecifiedEntity);
+
+// Generates a public struct wrapping a core error struct found by entity
+// validation. These errors are not associated with any policy, so instead of
+// `policy_id()` the generated struct exposes the uid of the offending entity.
+// The name of the struct must be the same as the name of the corresponding
+// `EntityValidationError` variant in `err.rs`.
+macro_rules! wrap_core_entity_error {
+    ($s:ident) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
+        #[error(transparent)]
+        #[diagnostic(transparent)]
+        #[doc=concat!("Structure containing details about a [`crate::EntityValidationError::", stringify!($s), "`].")]
+        pub struct $s(cedar_policy_core::validator::validation_errors::$s);
+
+        impl $s {
+            /// Access the [`EntityUid`] of the entity where this error was found.
+            pub fn entity_uid(&self) -> &EntityUid {
+                EntityUid::ref_cast(&self.0.entity)
+            }
+        }
+
+        #[doc(hidden)]
+        impl From<cedar_policy_core::validator::validation_errors::$s> for $s {
+            fn from(e: cedar_policy_core::validator::validation_errors::$s) -> Self {
+                Self(e)
+            }
+        }
+    };
+}
+
+wrap_core_entity_error!(MissingRequiredAttribute);
+wrap_core_entity_error!(EntityAttributeTypeMismatch);
+wrap_core_entity_error!(InvalidEntityParent);
+
+impl UnrecognizedActionId {
+    /// The action suggested in place of the unrecognized action, if any
+    pub fn suggestion(&self) -> Option<&str> {
+        self.0
+            .hint
+            .as_ref()
+            .map(UnrecognizedActionIdHelp::suggestion)
+    }
+
+    /// Location of the declaration of the suggested action in the schema
+    /// text, e.g., so that an editor can jump to it. This is only available
+    /// when the schema was parsed from text which retains source locations,
+    /// such as the Cedar schema syntax.
+    pub fn schema_location(&self) -> Option<miette::SourceSpan> {
+        self.0.schema_location.as_ref().map(|loc| loc.span)
+    }
+}
+
+impl UnexpectedType {
+    /// The type(s) which were expected, in Cedar schema syntax. The actual
+    /// type should have been a subtype of one of these.
+    pub fn expected_types(&self) -> impl Iterator<Item = String> + '_ {
+        self.0.expected_types().iter().map(ToString::to_string)
+    }
+
+    /// The type which was encountered, in Cedar schema syntax
+    pub fn actual_type(&self) -> String {
+        self.0.actual_type().to_string()
+    }
+}
+
+impl IncompatibleBranches {
+    /// The type of the `then` branch, in Cedar schema syntax
+    pub fn then_type(&self) -> String {
+        self.0.then_type().to_string()
+    }
+
+    /// The type of the `else` branch, in Cedar schema syntax
+    pub fn else_type(&self) -> String {
+        self.0.else_type().to_string()
+    }
+
+    /// Location of the `then` branch in the policy text
+    pub fn then_span(&self) -> Option<miette::SourceSpan> {
+        self.0.then_loc().map(|loc| loc.span)
+    }
+
+    /// Location of the `else` branch in the policy text
+    pub fn else_span(&self) -> Option<miette::SourceSpan> {
+        self.0.else_loc().map(|loc| loc.span)
+    }
+}
+
+impl IncompatibleSetElements {
+    /// The type of each element of the set literal, in Cedar schema syntax,
+    /// in the order the elements appear in it
+    pub fn element_types(&self) -> impl Iterator<Item = String> + '_ {
+        self.0.elements().map(|(ty, _)| ty.to_string())
+    }
+
+    /// Location of each element of the set literal in the policy text, in the
+    /// order the elements appear in it
+    pub fn element_spans(&self) -> impl Iterator<Item = Option<miette::SourceSpan>> + '_ {
+        self.0.elements().map(|(_, loc)| loc.map(|loc| loc.span))
+    }
+}
+
+impl SetScalarConfusion {
+    /// The type of the left operand, in Cedar schema syntax
+    pub fn lhs_type(&self) -> String {
+        self.0.lhs_type().to_string()
+    }
+
+    /// The type of the right operand, in Cedar schema syntax
+    pub fn rhs_type(&self) -> String {
+        self.0.rhs_type().to_string()
+    }
+
+    /// Location of the left operand in the policy text
+    pub fn lhs_span(&self) -> Option<miette::SourceSpan> {
+        self.0.lhs_loc().map(|loc| loc.span)
+    }
+
+    /// Location of the right operand in the policy text
+    pub fn rhs_span(&self) -> Option<miette::SourceSpan> {
+        self.0.rhs_loc().map(|loc| loc.span)
+    }
+}
+
+impl RecordLiteralMismatch {
+    /// Path to the mismatched attribute, starting with an attribute of the
+    /// outermost record literal
+    pub fn attribute_path(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .attribute_path()
+            .iter()
+            .map(smol_str::SmolStr::as_str)
+    }
+
+    /// The record type the outermost record literal was compared against, in
+    /// Cedar schema syntax
+    pub fn expected_type(&self) -> String {
+        self.0.expected_type().to_string()
+    }
+}
+
+impl MisplacedTemplateSlot {
+    /// The slot which appears outside its scope constraint
+    pub fn slot(&self) -> SlotId {
+        self.0.slot.into()
+    }
+}
+
+impl ArithmeticOverflow {
+    /// The overflowing expression, as it would be printed in policy text
+    pub fn expr(&self) -> &str {
+        &self.0.expr
+    }
+}
+
+impl UnspecifiedEntity {
+    /// The entity in the resource scope constraint
+    pub fn entity(&self) -> &EntityUid {
+        RefCast::ref_cast(&self.0.entity)
+    }
+
+    /// The resource scope constraint the entity appears in, as it would be
+    /// printed in policy text, e.g., `resource == Doc::"d"`
+    pub fn scope_constraint(&self) -> &str {
+        &self.0.scope_constraint
+    }
+}
+
+impl MissingRequiredAttribute {
+    /// The name of the missing attribute
+    pub fn attribute(&self) -> &str {
+        &self.0.attribute
+    }
+}
+
+impl EntityAttributeTypeMismatch {
+    /// The name of the attribute whose value has the wrong type
+    pub fn attribute(&self) -> &str {
+        &self.0.attribute
+    }
+}
+
+impl InvalidEntityParent {
+    /// The parent whose type is not allowed by the schema
+    pub fn parent(&self) -> &EntityUid {
+        EntityUid::ref_cast(&self.0.parent)
+    }
+}
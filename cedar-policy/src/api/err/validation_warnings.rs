@@ -34,7 +34,7 @@ use crate::ValidationWarning;
 // documentation should be written.
 macro_rules! wrap_core_warning {
     ($s:ident) => {
-        #[derive(Debug, Clone, Error, Diagnostic)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
         #[error(transparent)]
         #[diagnostic(transparent)]
         #[doc=concat!("Structure containing details about a [`ValidationWarning::", stringify!($s), "`].")]
@@ -62,3 +62,43 @@ wrap_core_warning!(BidiCharsInIdentifier);
 wrap_core_warning!(MixedScriptIdentifier);
 wrap_core_warning!(ConfusableIdentifier);
 wrap_core_warning!(ImpossiblePolicy);
+wrap_core_warning!(EmptyActionGroup);
+wrap_core_warning!(OverlyPermissivePolicy);
+wrap_core_warning!(AmbiguousAttributeName);
+wrap_core_warning!(SuspiciousLikePattern);
+wrap_core_warning!(AmbiguousPrecedence);
+wrap_core_warning!(TrivialTypeTest);
+
+impl AmbiguousPrecedence {
+    /// The ambiguous expression, with parentheses added to show how it is
+    /// parsed, e.g., `(a && b) || c` for `a && b || c`
+    pub fn parenthesized(&self) -> &str {
+        &self.0.parenthesized
+    }
+}
+
+impl TrivialTypeTest {
+    /// The `is` test, e.g., `resource is Photo`
+    pub fn test(&self) -> &str {
+        &self.0.test
+    }
+
+    /// Whether the test is always true, rather than always false
+    pub fn is_always_true(&self) -> bool {
+        self.0.kind
+            == cedar_policy_core::validator::validation_warnings::TrivialTypeTestKind::AlwaysTrue
+    }
+}
+
+/// Structure containing details about a [`crate::PolicySetWarning::EmptyPolicySet`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Error, Diagnostic)]
+#[error(transparent)]
+#[diagnostic(transparent)]
+pub struct EmptyPolicySet(cedar_policy_core::validator::validation_warnings::EmptyPolicySet);
+
+#[doc(hidden)]
+impl From<cedar_policy_core::validator::validation_warnings::EmptyPolicySet> for EmptyPolicySet {
+    fn from(e: cedar_policy_core::validator::validation_warnings::EmptyPolicySet) -> Self {
+        Self(e)
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Read-only traversal of the sub-expressions of a policy.
+
+use std::fmt::Display;
+
+use cedar_policy_core::ast;
+
+use super::Expression;
+
+/// A node of an expression in a policy, together with the source span it was
+/// parsed from, as returned by [`super::Policy::condition_exprs`] and
+/// [`Expression::exprs`]
+#[derive(Debug, Clone, Copy)]
+pub struct ExprWithSpan<'a>(&'a ast::Expr);
+
+impl<'a> ExprWithSpan<'a> {
+    /// Get the kind of this node
+    pub fn kind(&self) -> ExprNodeKind {
+        match self.0.expr_kind() {
+            ast::ExprKind::Lit(_) => ExprNodeKind::Literal,
+            ast::ExprKind::Var(_) => ExprNodeKind::Variable,
+            ast::ExprKind::Slot(_) => ExprNodeKind::Slot,
+            ast::ExprKind::Unknown(_) => ExprNodeKind::Unknown,
+            ast::ExprKind::If { .. } => ExprNodeKind::If,
+            ast::ExprKind::And { .. } => ExprNodeKind::And,
+            ast::ExprKind::Or { .. } => ExprNodeKind::Or,
+            ast::ExprKind::UnaryApp { .. } => ExprNodeKind::UnaryOp,
+            ast::ExprKind::BinaryApp { .. } => ExprNodeKind::BinaryOp,
+            ast::ExprKind::ExtensionFunctionApp { .. } => ExprNodeKind::ExtensionCall,
+            ast::ExprKind::GetAttr { .. } => ExprNodeKind::GetAttr,
+            ast::ExprKind::HasAttr { .. } => ExprNodeKind::HasAttr,
+            ast::ExprKind::Like { .. } => ExprNodeKind::Like,
+            ast::ExprKind::Is { .. } => ExprNodeKind::Is,
+            ast::ExprKind::Set(_) => ExprNodeKind::Set,
+            ast::ExprKind::Record(_) => ExprNodeKind::Record,
+            #[cfg(feature = "tolerant-ast")]
+            ast::ExprKind::Error { .. } => ExprNodeKind::Error,
+        }
+    }
+
+    /// Get the direct children of this node, in source order, except that the
+    /// values of a record literal are ordered by their keys
+    pub fn children(&self) -> impl DoubleEndedIterator<Item = ExprWithSpan<'a>> {
+        let children: Vec<&'a ast::Expr> = match self.0.expr_kind() {
+            ast::ExprKind::Lit(_)
+            | ast::ExprKind::Var(_)
+            | ast::ExprKind::Slot(_)
+            | ast::ExprKind::Unknown(_) => vec![],
+            ast::ExprKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => vec![test_expr, then_expr, else_expr],
+            ast::ExprKind::And { left, right } | ast::ExprKind::Or { left, right } => {
+                vec![left, right]
+            }
+            ast::ExprKind::UnaryApp { arg, .. } => vec![arg],
+            ast::ExprKind::BinaryApp { arg1, arg2, .. } => vec![arg1, arg2],
+            ast::ExprKind::GetAttr { expr, .. }
+            | ast::ExprKind::HasAttr { expr, .. }
+            | ast::ExprKind::Like { expr, .. }
+            | ast::ExprKind::Is { expr, .. } => vec![expr],
+            ast::ExprKind::ExtensionFunctionApp { args: exprs, .. } | ast::ExprKind::Set(exprs) => {
+                exprs.iter().collect()
+            }
+            ast::ExprKind::Record(fields) => fields.values().collect(),
+            #[cfg(feature = "tolerant-ast")]
+            ast::ExprKind::Error { .. } => vec![],
+        };
+        children.into_iter().map(ExprWithSpan)
+    }
+
+    /// Get the source span of this node in the policy text, if the policy was
+    /// parsed from text
+    pub fn source_span(&self) -> Option<miette::SourceSpan> {
+        self.0.source_loc().map(|loc| loc.span)
+    }
+
+    /// Get the attribute accessed or tested by this node, if it is a
+    /// [`ExprNodeKind::GetAttr`] or [`ExprNodeKind::HasAttr`] node
+    pub fn attribute(&self) -> Option<&'a str> {
+        match self.0.expr_kind() {
+            ast::ExprKind::GetAttr { attr, .. } | ast::ExprKind::HasAttr { attr, .. } => {
+                Some(attr.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// Get this node, and its children, as an [`Expression`]
+    pub fn to_expression(&self) -> Expression {
+        Expression(self.0.clone())
+    }
+}
+
+impl Display for ExprWithSpan<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The kind of an [`ExprWithSpan`] node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExprNodeKind {
+    /// A literal boolean, integer, string or entity reference
+    Literal,
+    /// One of the variables `principal`, `action`, `resource` or `context`
+    Variable,
+    /// A template slot, e.g., `?principal`
+    Slot,
+    /// An unknown value, in a partially evaluated expression
+    Unknown,
+    /// `if .. then .. else ..`
+    If,
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+    /// A unary operator, e.g., `!` or `isEmpty()`
+    UnaryOp,
+    /// A binary operator, e.g., `==`, `in` or `contains()`
+    BinaryOp,
+    /// A call to an extension function or method, e.g., `ip("10.0.0.1")`
+    ExtensionCall,
+    /// An attribute access, e.g., `context.ip`
+    GetAttr,
+    /// A `has` test, e.g., `context has ip`
+    HasAttr,
+    /// A `like` test
+    Like,
+    /// An `is` test
+    Is,
+    /// A set literal
+    Set,
+    /// A record literal
+    Record,
+    /// An expression which could not be parsed
+    #[cfg(feature = "tolerant-ast")]
+    Error,
+}
+
+/// Iterator over the nodes of an expression in pre-order, as returned by
+/// [`super::Policy::condition_exprs`] and [`Expression::exprs`]
+#[derive(Debug, Clone)]
+pub(crate) struct PreOrder<'a> {
+    stack: Vec<ExprWithSpan<'a>>,
+}
+
+impl<'a> PreOrder<'a> {
+    pub(crate) fn new(roots: impl IntoIterator<Item = &'a ast::Expr>) -> Self {
+        let mut stack = roots.into_iter().map(ExprWithSpan).collect::<Vec<_>>();
+        stack.reverse();
+        Self { stack }
+    }
+}
+
+impl<'a> Iterator for PreOrder<'a> {
+    type Item = ExprWithSpan<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.stack.pop()?;
+        self.stack.extend(next.children().rev());
+        Some(next)
+    }
+}
@@ -0,0 +1,112 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Content fingerprints of policies and policy sets.
+//!
+//! A policy is fingerprinted by hashing its JSON (EST) form, with the keys of
+//! every object sorted, so that the fingerprint does not depend on formatting,
+//! comments, or the order of record literal fields. A policy set is
+//! fingerprinted by hashing the sorted fingerprints of its policies and
+//! templates, each combined with its id.
+
+use cedar_policy_core::est;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Version of the fingerprint format. Fingerprints computed with different
+/// versions are not comparable. This must change, and the change must be
+/// noted in the changelog, whenever the fingerprint of any policy or policy
+/// set changes.
+const FORMAT_VERSION: &[u8] = b"cedar-fingerprint-v1";
+
+/// What a policy set entry is, for [`policy_set_fingerprint`]
+pub(crate) enum Entry<'a> {
+    /// A static policy
+    Static,
+    /// A template
+    Template,
+    /// A policy linked from the template with this id
+    Link(&'a str),
+}
+
+/// Fingerprint of a policy or template, given in its JSON (EST) form
+pub(crate) fn policy_fingerprint(est: est::Policy) -> [u8; 32] {
+    #[expect(
+        clippy::expect_used,
+        reason = "the EST has no maps with non-string keys, so serializing it cannot fail"
+    )]
+    let json = serde_json::to_value(est).expect("failed to serialize policy to JSON");
+    let mut hasher = Sha256::new();
+    hasher.update(FORMAT_VERSION);
+    hasher.update(b"policy\0");
+    hasher.update(sort_keys(json).to_string());
+    hasher.finalize().into()
+}
+
+/// Fingerprint of a policy set, given the id, kind and fingerprint of each of
+/// its policies and templates, in any order
+pub(crate) fn policy_set_fingerprint<'a>(
+    entries: impl IntoIterator<Item = (&'a str, Entry<'a>, [u8; 32])>,
+) -> [u8; 32] {
+    let mut entries = entries
+        .into_iter()
+        .map(|(id, entry, fingerprint)| {
+            let mut hasher = Sha256::new();
+            hasher.update(FORMAT_VERSION);
+            match entry {
+                Entry::Static => hasher.update(b"static\0"),
+                Entry::Template => hasher.update(b"template\0"),
+                Entry::Link(template_id) => {
+                    hasher.update(b"link\0");
+                    update_with_str(&mut hasher, template_id);
+                }
+            }
+            update_with_str(&mut hasher, id);
+            hasher.update(fingerprint);
+            <[u8; 32]>::from(hasher.finalize())
+        })
+        .collect::<Vec<_>>();
+    entries.sort_unstable();
+    let mut hasher = Sha256::new();
+    hasher.update(FORMAT_VERSION);
+    hasher.update(b"policy set\0");
+    for entry in entries {
+        hasher.update(entry);
+    }
+    hasher.finalize().into()
+}
+
+/// Hash a length-prefixed string, so that adjacent strings can't run together
+fn update_with_str(hasher: &mut Sha256, s: &str) {
+    hasher.update((s.len() as u64).to_le_bytes());
+    hasher.update(s);
+}
+
+/// Recursively sort the keys of every object in `value`. Lists are left in
+/// their original order.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, sort_keys(v)))
+                .collect::<std::collections::BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
@@ -0,0 +1,102 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Patterns for the `like` operator.
+
+use std::convert::Infallible;
+use std::str::FromStr;
+
+use cedar_policy_core::ast;
+
+/// A pattern for the `like` operator, made of literal text and wildcards which
+/// match any string, including the empty string.
+///
+/// Patterns are built by composing literal text and wildcards, so that
+/// characters taken from user input, such as `*`, are always matched literally:
+///
+/// ```
+/// # use cedar_policy::Pattern;
+/// let pattern = Pattern::from_literal("/photos/*/")
+///     .concat(&Pattern::wildcard())
+///     .concat(&Pattern::from_literal(".jpg"));
+/// assert_eq!(pattern.to_string(), r"/photos/\*/*.jpg");
+/// assert!(pattern.matches("/photos/*/beach.jpg"));
+/// assert!(!pattern.matches("/photos/2024/beach.jpg"));
+/// ```
+///
+/// A pattern can also be parsed from the syntax of a `like` pattern in policy
+/// text, without the surrounding quotes, where `*` is a wildcard and `\*` is a
+/// literal `*`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pattern(pub(crate) ast::Pattern);
+
+impl Pattern {
+    /// A pattern matching exactly `text`. Every character of `text`, including
+    /// `*`, is matched literally.
+    pub fn from_literal(text: &str) -> Self {
+        Self(text.chars().map(ast::PatternElem::Char).collect())
+    }
+
+    /// A pattern consisting of a single wildcard, which matches any string
+    pub fn wildcard() -> Self {
+        Self(ast::Pattern::from(vec![ast::PatternElem::Wildcard]))
+    }
+
+    /// A pattern matching a string matched by `self` followed by a string
+    /// matched by `other`
+    #[must_use]
+    pub fn concat(self, other: &Self) -> Self {
+        Self(self.0.iter().chain(other.0.iter()).copied().collect())
+    }
+
+    /// Test whether `text` matches this pattern, as the `like` operator does.
+    /// Characters are compared by Unicode scalar value, with no normalization.
+    pub fn matches(&self, text: &str) -> bool {
+        self.0.wildcard_match(text)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = Infallible;
+
+    /// Parse a pattern where each `*` is a wildcard and `\*` is a literal `*`.
+    /// Every other character, including a `\` not followed by `*`, is matched
+    /// literally. Unlike in policy text, no other escape sequences are
+    /// interpreted.
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let mut elems = Vec::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => elems.push(ast::PatternElem::Wildcard),
+                '\\' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    elems.push(ast::PatternElem::Char('*'));
+                }
+                c => elems.push(ast::PatternElem::Char(c)),
+            }
+        }
+        Ok(Self(elems.into()))
+    }
+}
+
+/// Displays the pattern as it would appear between the quotes of a `like`
+/// expression in policy text
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -0,0 +1,711 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Typed access to, and editing of, the annotations of a [`Policy`].
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use cedar_policy_core::{ast, parser};
+use miette::Diagnostic;
+use smol_str::SmolStr;
+use thiserror::Error;
+
+use super::{LosslessPolicy, Policy, PolicyId};
+
+impl Policy {
+    /// Get the value of an annotation of this policy as a `bool`. The value
+    /// must be exactly `true` or `false`. Returns `Ok(None)` when the
+    /// annotation is not present or when `key` is not a valid annotation
+    /// identifier.
+    pub fn annotation_as_bool(
+        &self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<bool>, MalformedAnnotationValue> {
+        self.typed_annotation(
+            key.as_ref(),
+            AnnotationValueKind::Bool,
+            |value| match value {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            },
+        )
+    }
+
+    /// Get the value of an annotation of this policy as a point in time. The
+    /// value must be an RFC 3339 date-time with an explicit offset, e.g.,
+    /// `@expires("2024-10-15T11:35:00Z")` or `@expires("2024-10-15T13:35:00.5+02:00")`.
+    /// Leap seconds (`:60`) are not supported. Returns `Ok(None)` when the
+    /// annotation is not present or when `key` is not a valid annotation
+    /// identifier.
+    pub fn annotation_as_datetime(
+        &self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<SystemTime>, MalformedAnnotationValue> {
+        self.typed_annotation(key.as_ref(), AnnotationValueKind::Datetime, parse_rfc3339)
+    }
+
+    fn typed_annotation<T>(
+        &self,
+        key: &str,
+        kind: AnnotationValueKind,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> Result<Option<T>, MalformedAnnotationValue> {
+        self.annotation(key)
+            .map(|value| {
+                parse(value).ok_or_else(|| MalformedAnnotationValue {
+                    key: key.into(),
+                    value: value.into(),
+                    kind,
+                })
+            })
+            .transpose()
+    }
+
+    /// Set the annotation `key` of this static policy to `value`, returning
+    /// the previous value of the annotation, if any. The change is reflected
+    /// in the text and JSON representations of the policy. If the policy was
+    /// parsed from text, only the edited annotation changes in its text: a new
+    /// annotation is added after the existing ones, and the rest of the text,
+    /// including comments and formatting, is preserved.
+    ///
+    /// Error if `key` is not a valid annotation identifier, or if this is a
+    /// template-linked policy, whose annotations are those of its template.
+    pub fn set_annotation(
+        &mut self,
+        key: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) -> Result<Option<String>, AnnotationEditError> {
+        let value = SmolStr::from(value.as_ref());
+        self.edit_annotation(key.as_ref(), Some(value))
+    }
+
+    /// Remove the annotation `key` from this static policy, returning its
+    /// value, if it was present. As for [`Policy::set_annotation`], the change
+    /// is reflected in the text and JSON representations of the policy.
+    ///
+    /// Error if `key` is not a valid annotation identifier, or if this is a
+    /// template-linked policy, whose annotations are those of its template.
+    pub fn remove_annotation(
+        &mut self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<String>, AnnotationEditError> {
+        self.edit_annotation(key.as_ref(), None)
+    }
+
+    /// Set the annotation `key` to `value`, or remove it if `value` is `None`
+    fn edit_annotation(
+        &mut self,
+        key: &str,
+        value: Option<SmolStr>,
+    ) -> Result<Option<String>, AnnotationEditError> {
+        if !self.is_static() {
+            return Err(AnnotationEditError::LinkedPolicy {
+                id: self.id().clone(),
+            });
+        }
+        let key: ast::AnyId = key
+            .parse()
+            .map_err(|_| AnnotationEditError::InvalidKey { key: key.into() })?;
+        let previous = self.ast.annotation(&key).map(|a| a.val.to_string());
+        if previous.as_deref() == value.as_deref() {
+            return Ok(previous);
+        }
+
+        if let Some(text) = self.edited_source_text(&key, value.as_ref()) {
+            if let Ok(ast) = parser::parse_policy(Some(self.ast.id().clone()), &text) {
+                self.ast = ast::Template::link_static_policy(ast).1;
+                self.lossless = LosslessPolicy::Text {
+                    text,
+                    slots: HashMap::new(),
+                };
+                return Ok(previous);
+            }
+        }
+
+        // Existing annotations keep their source location, so that they are
+        // still ordered as in the source text by `Policy::annotations`
+        let annotations: ast::Annotations = self
+            .ast
+            .annotations()
+            .filter(|(k, _)| **k != key)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .chain(
+                value
+                    .clone()
+                    .map(|val| (key.clone(), ast::Annotation { val, loc: None })),
+            )
+            .collect();
+        let template = self.ast.template();
+        let ast = ast::StaticPolicy::new(
+            template.id().clone(),
+            template.loc().cloned(),
+            annotations,
+            template.effect(),
+            template.principal_constraint().clone(),
+            template.action_constraint().clone(),
+            template.resource_constraint().clone(),
+            template.non_scope_constraints().cloned(),
+        )
+        .map_err(|_| AnnotationEditError::LinkedPolicy {
+            id: self.id().clone(),
+        })?;
+
+        let mut est = self
+            .lossless
+            .est(|| self.ast.clone().into())
+            .unwrap_or_else(|_| self.ast.clone().into());
+        match value {
+            Some(val) => {
+                est.annotations_mut()
+                    .0
+                    .insert(key, Some(ast::Annotation { val, loc: None }));
+            }
+            None => {
+                est.annotations_mut().0.remove(&key);
+            }
+        }
+
+        self.ast = ast::Template::link_static_policy(ast).1;
+        self.lossless = LosslessPolicy::Est(est);
+        Ok(previous)
+    }
+
+    /// Apply an annotation edit to the source text of this policy, changing
+    /// only the text of the edited annotation. Returns `None` if the policy
+    /// has no source text.
+    fn edited_source_text(&self, key: &ast::AnyId, value: Option<&SmolStr>) -> Option<String> {
+        let LosslessPolicy::Text { text, .. } = &self.lossless else {
+            return None;
+        };
+        let policy_loc = self.ast.loc()?;
+        // The text is either the whole source the policy was parsed from, or
+        // just the part of it spanned by the policy
+        let base = if *policy_loc.src == **text {
+            0
+        } else {
+            policy_loc.start()
+        };
+        let span = |loc: &parser::Loc| {
+            let range = loc.start().checked_sub(base)?..loc.end().checked_sub(base)?;
+            (text.get(range.clone())? == loc.snippet()?).then_some(range)
+        };
+        let new_annotation = value.map(|val| {
+            let annotation = ast::Annotation {
+                val: val.clone(),
+                loc: None,
+            };
+            format!("@{key}({annotation})")
+        });
+
+        let (range, replacement) = match (self.ast.annotation(key), new_annotation) {
+            (Some(existing), Some(new)) => (span(existing.loc.as_ref()?)?, new),
+            (Some(existing), None) => {
+                let range = span(existing.loc.as_ref()?)?;
+                let rest = text.get(range.end..)?;
+                let line_rest = rest.split('\n').next().unwrap_or_default();
+                match line_indent(text, range.start) {
+                    // Remove the whole line if the annotation is alone on it
+                    Some(indent) if line_rest.trim().is_empty() => {
+                        let end = (range.end + line_rest.len() + 1).min(text.len());
+                        (range.start - indent.len()..end, String::new())
+                    }
+                    // Otherwise, remove the spaces separating it from the
+                    // previous token on its line, or else from the next one
+                    Some(_) => {
+                        let spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+                        (range.start..range.end + spaces, String::new())
+                    }
+                    None => {
+                        let before = text.get(..range.start)?;
+                        let spaces = before.len() - before.trim_end_matches([' ', '\t']).len();
+                        (range.start - spaces..range.end, String::new())
+                    }
+                }
+            }
+            (None, Some(new)) => {
+                let last = self
+                    .ast
+                    .annotations()
+                    .filter_map(|(_, annotation)| annotation.loc.as_ref())
+                    .max_by_key(|loc| loc.end());
+                // Add the annotation after the existing ones, or else before
+                // the policy, on its own line if they are on their own line
+                if let Some(last) = last {
+                    let end = span(last)?.end;
+                    let start = last.start() - base;
+                    (end..end, format!("{}{new}", separator(text, start)))
+                } else {
+                    let start = span(policy_loc)?.start;
+                    (start..start, format!("{new}{}", separator(text, start)))
+                }
+            }
+            (None, None) => return None,
+        };
+        let mut text = text.clone();
+        text.replace_range(range, &replacement);
+        Some(text)
+    }
+}
+
+/// The separator between an annotation added next to the token at `offset` in
+/// `text` and that token: a line break if the token is first on its line
+fn separator(text: &str, offset: usize) -> String {
+    line_indent(text, offset).map_or_else(|| " ".to_string(), |indent| format!("\n{indent}"))
+}
+
+/// The whitespace preceding `offset` on its line in `text`, if there is only
+/// whitespace there
+fn line_indent(text: &str, offset: usize) -> Option<&str> {
+    let before = text.get(..offset)?;
+    let indent = before.get(before.rfind('\n').map_or(0, |i| i + 1)..)?;
+    indent.chars().all(char::is_whitespace).then_some(indent)
+}
+
+/// The kind of value expected by a typed annotation accessor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnnotationValueKind {
+    Bool,
+    Datetime,
+}
+
+impl std::fmt::Display for AnnotationValueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool => write!(f, "boolean"),
+            Self::Datetime => write!(f, "RFC 3339 date-time"),
+        }
+    }
+}
+
+impl AnnotationValueKind {
+    fn help(self) -> &'static str {
+        match self {
+            Self::Bool => "expected `true` or `false`",
+            Self::Datetime => "expected a date-time such as `2024-10-15T11:35:00Z`",
+        }
+    }
+}
+
+/// Error returned by the typed annotation accessors, such as
+/// [`Policy::annotation_as_bool`], when the value of the annotation can't be
+/// read as the requested type
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("value `{value}` of annotation `{key}` is not a valid {kind}")]
+pub struct MalformedAnnotationValue {
+    key: String,
+    value: String,
+    kind: AnnotationValueKind,
+}
+
+impl MalformedAnnotationValue {
+    /// The key of the annotation
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The malformed value of the annotation
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl Diagnostic for MalformedAnnotationValue {
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(self.kind.help()))
+    }
+}
+
+/// Error returned by [`Policy::set_annotation`] and
+/// [`Policy::remove_annotation`]
+#[derive(Debug, Clone, PartialEq, Eq, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum AnnotationEditError {
+    /// The key is not a valid annotation identifier
+    #[error("`{key}` is not a valid annotation key")]
+    InvalidKey {
+        /// The invalid key
+        key: String,
+    },
+    /// The annotations of a template-linked policy can't be edited, because
+    /// they are the annotations of its template
+    #[error("cannot edit the annotations of template-linked policy `{id}`")]
+    #[diagnostic(help("edit the annotations of its template instead"))]
+    LinkedPolicy {
+        /// The id of the template-linked policy
+        id: PolicyId,
+    },
+}
+
+/// Parse an RFC 3339 date-time, e.g., `2024-10-15T11:35:00.5+02:00`
+fn parse_rfc3339(s: &str) -> Option<SystemTime> {
+    let byte = |i: usize| s.as_bytes().get(i).copied();
+    if byte(4) != Some(b'-')
+        || byte(7) != Some(b'-')
+        || !matches!(byte(10), Some(b'T' | b't' | b' '))
+        || byte(13) != Some(b':')
+        || byte(16) != Some(b':')
+    {
+        return None;
+    }
+    let year = digits(s.get(0..4)?)?;
+    let month = digits(s.get(5..7)?)?;
+    let day = digits(s.get(8..10)?)?;
+    let hour = digits(s.get(11..13)?)?;
+    let minute = digits(s.get(14..16)?)?;
+    let second = digits(s.get(17..19)?)?;
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let mut rest = s.get(19..)?;
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        // Digits beyond nanosecond precision are ignored
+        nanos = fraction
+            .bytes()
+            .take(len.min(9))
+            .chain(std::iter::repeat(b'0'))
+            .take(9)
+            .fold(0, |n, digit| n * 10 + u32::from(digit - b'0'));
+        rest = fraction.get(len..)?;
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.bytes().next()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            if rest.len() != 6 || rest.as_bytes().get(3) != Some(&b':') {
+                return None;
+            }
+            let (hours, minutes) = (digits(rest.get(1..3)?)?, digits(rest.get(4..6)?)?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+
+    let seconds =
+        days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset;
+    let whole_seconds = Duration::from_secs(seconds.unsigned_abs());
+    let time = if seconds >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(whole_seconds)?
+    } else {
+        SystemTime::UNIX_EPOCH.checked_sub(whole_seconds)?
+    };
+    time.checked_add(Duration::from_nanos(nanos.into()))
+}
+
+/// Parse a non-empty string of ASCII digits
+fn digits(s: &str) -> Option<i64> {
+    if !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Number of days from 1970-01-01 to the given date in the proleptic Gregorian
+/// calendar, following <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+#[expect(clippy::indexing_slicing, reason = "unit test code")]
+mod test {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    const EPOCH_2024_10_15: u64 = 1_728_950_400;
+
+    fn policy() -> Policy {
+        Policy::parse(
+            None,
+            r#"
+            @owner("alice")
+            @active("true")
+            @expires("2024-10-15T11:35:00Z")
+            @broken("yes")
+            @flag
+            permit(principal, action, resource);
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn annotations_in_source_order() {
+        assert_eq!(
+            policy().annotations().collect::<Vec<_>>(),
+            vec![
+                ("owner", "alice"),
+                ("active", "true"),
+                ("expires", "2024-10-15T11:35:00Z"),
+                ("broken", "yes"),
+                ("flag", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn annotation_as_bool() {
+        let policy = policy();
+        assert_eq!(policy.annotation_as_bool("active"), Ok(Some(true)));
+        assert_eq!(policy.annotation_as_bool("missing"), Ok(None));
+        assert_matches!(policy.annotation_as_bool("broken"), Err(e) => {
+            assert_eq!(e.key(), "broken");
+            assert_eq!(e.value(), "yes");
+            assert_eq!(
+                e.to_string(),
+                "value `yes` of annotation `broken` is not a valid boolean"
+            );
+            assert_eq!(
+                e.help().map(|h| h.to_string()).as_deref(),
+                Some("expected `true` or `false`")
+            );
+        });
+        assert_matches!(policy.annotation_as_bool("flag"), Err(_));
+    }
+
+    #[test]
+    fn annotation_as_datetime() {
+        let policy = policy();
+        assert_eq!(
+            policy.annotation_as_datetime("expires"),
+            Ok(Some(
+                SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(EPOCH_2024_10_15 + 11 * 3600 + 35 * 60)
+            ))
+        );
+        assert_eq!(policy.annotation_as_datetime("missing"), Ok(None));
+        assert_matches!(policy.annotation_as_datetime("owner"), Err(e) => {
+            assert_eq!(
+                e.to_string(),
+                "value `alice` of annotation `owner` is not a valid RFC 3339 date-time"
+            );
+        });
+    }
+
+    #[test]
+    fn rfc3339() {
+        let at = |secs: u64, nanos: u32| Some(SystemTime::UNIX_EPOCH + Duration::new(secs, nanos));
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), at(0, 0));
+        assert_eq!(
+            parse_rfc3339("2024-10-15T00:00:00Z"),
+            at(EPOCH_2024_10_15, 0)
+        );
+        assert_eq!(
+            parse_rfc3339("2024-10-15t02:00:00.25+02:00"),
+            at(EPOCH_2024_10_15, 250_000_000)
+        );
+        assert_eq!(
+            parse_rfc3339("2024-10-14 22:30:00.0000000001-01:30"),
+            at(EPOCH_2024_10_15, 0)
+        );
+        assert_eq!(parse_rfc3339("2024-02-29T00:00:00Z"), at(1_709_164_800, 0));
+        assert_eq!(
+            parse_rfc3339("1969-12-31T23:59:59.5Z"),
+            SystemTime::UNIX_EPOCH.checked_sub(Duration::from_millis(500))
+        );
+        for invalid in [
+            "",
+            "2024-10-15",
+            "2024-10-15T11:35:00",
+            "2024-10-15T11:35Z",
+            "2024-10-15T11:35:00+0200",
+            "2024-10-15T11:35:00.Z",
+            "2024-10-15T24:00:00Z",
+            "2024-10-15T11:35:60Z",
+            "2024-13-15T11:35:00Z",
+            "2023-02-29T11:35:00Z",
+            "2024-10-15T11:35:00+24:00",
+            "2024-10-15T11:35:00Z ",
+            "+024-10-15T11:35:00Z",
+        ] {
+            assert_eq!(parse_rfc3339(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn set_and_remove_annotation() {
+        let mut policy = policy();
+        assert_eq!(
+            policy.set_annotation("owner", "bob"),
+            Ok(Some("alice".to_string()))
+        );
+        assert_eq!(policy.set_annotation("ticket", "SEC-1"), Ok(None));
+        assert_eq!(
+            policy.remove_annotation("broken"),
+            Ok(Some("yes".to_string()))
+        );
+        assert_eq!(policy.remove_annotation("missing"), Ok(None));
+        assert_eq!(
+            policy.annotations().collect::<Vec<_>>(),
+            vec![
+                ("owner", "bob"),
+                ("active", "true"),
+                ("expires", "2024-10-15T11:35:00Z"),
+                ("flag", ""),
+                ("ticket", "SEC-1"),
+            ]
+        );
+
+        // The change is reflected when the policy is re-serialized
+        let reparsed = Policy::parse(None, policy.to_string()).unwrap();
+        assert_eq!(reparsed.annotation("owner"), Some("bob"));
+        assert_eq!(reparsed.annotation("ticket"), Some("SEC-1"));
+        assert_eq!(reparsed.annotation("broken"), None);
+        assert_eq!(reparsed, policy);
+        let json = policy.to_json().unwrap();
+        assert_eq!(json["annotations"]["owner"], "bob");
+        assert_eq!(json["annotations"].get("broken"), None);
+
+        assert_matches!(
+            policy.set_annotation("not a key", "x"),
+            Err(AnnotationEditError::InvalidKey { key }) => {
+                assert_eq!(key, "not a key");
+            }
+        );
+    }
+
+    #[test]
+    fn edits_preserve_source_text() {
+        let mut policy = policy();
+        policy
+            .set_annotation("owner", "bob \"the builder\"")
+            .unwrap();
+        policy.set_annotation("ticket", "SEC-1").unwrap();
+        policy.remove_annotation("broken").unwrap();
+        assert_eq!(
+            policy.to_string(),
+            r#"
+            @owner("bob \"the builder\"")
+            @active("true")
+            @expires("2024-10-15T11:35:00Z")
+            @flag
+            @ticket("SEC-1")
+            permit(principal, action, resource);
+            "#
+        );
+        assert_eq!(policy.annotation("owner"), Some("bob \"the builder\""));
+
+        let src = "// Allow admins\npermit(\n  principal in Group::\"admins\", // comment\n  action,\n  resource\n);";
+        let mut policy = Policy::parse(None, src).unwrap();
+        policy.set_annotation("owner", "alice").unwrap();
+        assert_eq!(
+            policy.to_string(),
+            format!(
+                "// Allow admins\n@owner(\"alice\")\n{}",
+                src.trim_start_matches("// Allow admins\n")
+            )
+        );
+        policy.remove_annotation("owner").unwrap();
+        assert_eq!(policy.to_string(), src);
+
+        let src = r#"@a("1") @b("2") permit(principal, action, resource) when { true };"#;
+        let mut policy = Policy::parse(None, src).unwrap();
+        policy.set_annotation("c", "3").unwrap();
+        policy.remove_annotation("a").unwrap();
+        assert_eq!(
+            policy.to_string(),
+            r#"@b("2") @c("3") permit(principal, action, resource) when { true };"#
+        );
+    }
+
+    #[test]
+    fn edits_preserve_policy_set_text() {
+        let src = r#"
+            // first
+            @id("a")
+            permit(principal, action, resource);
+
+            @id("b")
+            forbid(principal, action, resource) unless {
+                // why
+                context.ok
+            };
+        "#;
+        let set: crate::PolicySet = src.parse().unwrap();
+        let mut policy = set.policy(&PolicyId::new("policy1")).unwrap().clone();
+        policy.set_annotation("id", "c").unwrap();
+        assert_eq!(
+            policy.to_string(),
+            r#"@id("c")
+            forbid(principal, action, resource) unless {
+                // why
+                context.ok
+            };"#
+        );
+        assert_eq!(policy.id(), &PolicyId::new("policy1"));
+    }
+
+    #[test]
+    fn cannot_edit_linked_policy_annotations() {
+        let template = crate::Template::parse(
+            Some(PolicyId::new("t")),
+            "@owner(\"alice\") permit(principal == ?principal, action, resource);",
+        )
+        .unwrap();
+        let mut set = crate::PolicySet::new();
+        set.add_template(template).unwrap();
+        set.link(
+            PolicyId::new("t"),
+            PolicyId::new("linked"),
+            std::collections::HashMap::from([(
+                crate::SlotId::principal(),
+                r#"User::"alice""#.parse().unwrap(),
+            )]),
+        )
+        .unwrap();
+        let mut linked = set.policy(&PolicyId::new("linked")).unwrap().clone();
+        assert_matches!(
+            linked.set_annotation("owner", "bob"),
+            Err(AnnotationEditError::LinkedPolicy { id }) => {
+                assert_eq!(id, PolicyId::new("linked"));
+            }
+        );
+        assert_eq!(linked.annotation("owner"), Some("alice"));
+    }
+}
@@ -0,0 +1,549 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Programmatic construction of policies.
+//!
+//! Policies built this way never go through Cedar policy text, so attribute
+//! names and string literals taken from user input cannot change the
+//! structure of the policy. String literals and attribute names are escaped as
+//! needed when the policy is displayed as text.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use cedar_policy_core::ast;
+use smol_str::SmolStr;
+
+use super::{
+    ActionConstraint, Effect, EntityTypeName, EntityUid, Expression, Pattern, Policy, PolicyId,
+    PrincipalConstraint, ResourceConstraint, SlotId, Template,
+};
+use crate::{policy_builder_errors, ParseErrors, PolicyBuilderError};
+
+/// Constructors for the expressions of the Cedar grammar, for use in the
+/// conditions of a [`PolicyBuilder`]
+impl Expression {
+    /// Create an expression representing the `principal` variable
+    pub fn var_principal() -> Self {
+        Self(ast::Expr::var(ast::Var::Principal))
+    }
+
+    /// Create an expression representing the `action` variable
+    pub fn var_action() -> Self {
+        Self(ast::Expr::var(ast::Var::Action))
+    }
+
+    /// Create an expression representing the `resource` variable
+    pub fn var_resource() -> Self {
+        Self(ast::Expr::var(ast::Var::Resource))
+    }
+
+    /// Create an expression representing the `context` variable
+    pub fn var_context() -> Self {
+        Self(ast::Expr::var(ast::Var::Context))
+    }
+
+    /// Create an expression representing a literal string, bool, or long
+    pub fn val(value: impl Into<ast::Literal>) -> Self {
+        Self(ast::Expr::val(value))
+    }
+
+    /// Create an expression representing a literal entity UID
+    pub fn entity_uid(value: EntityUid) -> Self {
+        Self(ast::Expr::val(ast::EntityUID::from(value)))
+    }
+
+    /// Create an expression representing a template slot. A policy
+    /// containing a slot can only be built as a [`Template`].
+    pub fn slot(slot: SlotId) -> Self {
+        Self(ast::Expr::slot(slot.into()))
+    }
+
+    /// Create an expression representing the attribute access `expr.attr`
+    pub fn attr(expr: Self, attr: impl AsRef<str>) -> Self {
+        Self(ast::Expr::get_attr(expr.0, SmolStr::from(attr.as_ref())))
+    }
+
+    /// Create an expression representing `expr has attr`
+    pub fn has_attr(expr: Self, attr: impl AsRef<str>) -> Self {
+        Self(ast::Expr::has_attr(expr.0, SmolStr::from(attr.as_ref())))
+    }
+
+    /// Create an expression representing `expr.getTag(tag)`
+    pub fn get_tag(expr: Self, tag: Self) -> Self {
+        Self(ast::Expr::get_tag(expr.0, tag.0))
+    }
+
+    /// Create an expression representing `expr.hasTag(tag)`
+    pub fn has_tag(expr: Self, tag: Self) -> Self {
+        Self(ast::Expr::has_tag(expr.0, tag.0))
+    }
+
+    /// Create an expression representing `expr like pattern`. Each `*` in
+    /// `pattern` is a wildcard, and `\*` is a literal `*`. Every other
+    /// character, including a `\` not followed by `*`, is a literal character.
+    /// Use [`Expression::like_pattern`] to match text from user input, which
+    /// may contain `*`, literally.
+    pub fn like(expr: Self, pattern: impl AsRef<str>) -> Self {
+        let Ok(pattern) = Pattern::from_str(pattern.as_ref());
+        Self::like_pattern(expr, pattern)
+    }
+
+    /// Create an expression representing `expr like pattern`
+    pub fn like_pattern(expr: Self, pattern: Pattern) -> Self {
+        Self(ast::Expr::like(expr.0, pattern.0))
+    }
+
+    /// Create an expression representing `expr is entity_type`
+    pub fn is_entity_type(expr: Self, entity_type: EntityTypeName) -> Self {
+        Self(ast::Expr::is_entity_type(expr.0, entity_type.0))
+    }
+
+    /// Create an expression representing `if cond then then_expr else else_expr`
+    pub fn if_then_else(cond: Self, then_expr: Self, else_expr: Self) -> Self {
+        Self(ast::Expr::ite(cond.0, then_expr.0, else_expr.0))
+    }
+
+    /// Create an expression representing `!expr`
+    pub fn new_not(expr: Self) -> Self {
+        Self(ast::Expr::not(expr.0))
+    }
+
+    /// Create an expression representing `-expr`
+    pub fn new_neg(expr: Self) -> Self {
+        Self(ast::Expr::neg(expr.0))
+    }
+
+    /// Create an expression representing `left && right`
+    pub fn and(left: Self, right: Self) -> Self {
+        Self(ast::Expr::and(left.0, right.0))
+    }
+
+    /// Create an expression representing `left || right`
+    pub fn or(left: Self, right: Self) -> Self {
+        Self(ast::Expr::or(left.0, right.0))
+    }
+
+    /// Create an expression representing `left == right`
+    pub fn new_eq(left: Self, right: Self) -> Self {
+        Self(ast::Expr::is_eq(left.0, right.0))
+    }
+
+    /// Create an expression representing `left != right`
+    pub fn not_eq(left: Self, right: Self) -> Self {
+        Self(ast::Expr::noteq(left.0, right.0))
+    }
+
+    /// Create an expression representing `left < right`
+    pub fn less(left: Self, right: Self) -> Self {
+        Self(ast::Expr::less(left.0, right.0))
+    }
+
+    /// Create an expression representing `left <= right`
+    pub fn less_eq(left: Self, right: Self) -> Self {
+        Self(ast::Expr::lesseq(left.0, right.0))
+    }
+
+    /// Create an expression representing `left > right`
+    pub fn greater(left: Self, right: Self) -> Self {
+        Self(ast::Expr::greater(left.0, right.0))
+    }
+
+    /// Create an expression representing `left >= right`
+    pub fn greater_eq(left: Self, right: Self) -> Self {
+        Self(ast::Expr::greatereq(left.0, right.0))
+    }
+
+    /// Create an expression representing `left + right`
+    pub fn new_add(left: Self, right: Self) -> Self {
+        Self(ast::Expr::add(left.0, right.0))
+    }
+
+    /// Create an expression representing `left - right`
+    pub fn new_sub(left: Self, right: Self) -> Self {
+        Self(ast::Expr::sub(left.0, right.0))
+    }
+
+    /// Create an expression representing `left * right`
+    pub fn new_mul(left: Self, right: Self) -> Self {
+        Self(ast::Expr::mul(left.0, right.0))
+    }
+
+    /// Create an expression representing `left in right`
+    pub fn is_in(left: Self, right: Self) -> Self {
+        Self(ast::Expr::is_in(left.0, right.0))
+    }
+
+    /// Create an expression representing `set.contains(element)`
+    pub fn contains(set: Self, element: Self) -> Self {
+        Self(ast::Expr::contains(set.0, element.0))
+    }
+
+    /// Create an expression representing `left.containsAll(right)`
+    pub fn contains_all(left: Self, right: Self) -> Self {
+        Self(ast::Expr::contains_all(left.0, right.0))
+    }
+
+    /// Create an expression representing `left.containsAny(right)`
+    pub fn contains_any(left: Self, right: Self) -> Self {
+        Self(ast::Expr::contains_any(left.0, right.0))
+    }
+
+    /// Create an expression representing `set.isEmpty()`
+    pub fn is_empty(set: Self) -> Self {
+        Self(ast::Expr::is_empty(set.0))
+    }
+
+    /// Create an expression representing a call to the extension function
+    /// `name`. Extension methods are called with the receiver as the first
+    /// argument, e.g., `ip("10.0.0.1").isLoopback()` is a call to `isLoopback`
+    /// with the argument `ip("10.0.0.1")`.
+    ///
+    /// Error if `name` is not a valid name. This function does not check that
+    /// `name` is the name of an extension function.
+    pub fn extension_call(
+        name: impl AsRef<str>,
+        args: impl IntoIterator<Item = Self>,
+    ) -> Result<Self, ParseErrors> {
+        let name = name
+            .as_ref()
+            .parse::<ast::Name>()
+            .map_err(ParseErrors::from)?;
+        Ok(Self(ast::Expr::call_extension_fn(
+            name,
+            args.into_iter().map(|arg| arg.0).collect(),
+        )))
+    }
+}
+
+/// Builder for a [`Policy`] or [`Template`].
+///
+/// Each `when` and `unless` condition is added to the policy in order, as if
+/// it were written after the conditions already added. The policy is
+/// unconstrained in its scope and has no conditions unless they are added.
+///
+/// A policy built from the same components as a policy parsed from text is
+/// equal to it, and so evaluates and validates in the same way.
+///
+/// # Examples
+/// ```
+/// use cedar_policy::{Effect, EntityUid, Expression, Policy};
+/// # use std::str::FromStr;
+/// let action = EntityUid::from_str(r#"Action::"view""#).unwrap();
+/// let policy = Policy::builder()
+///     .effect(Effect::Permit)
+///     .action_eq(action)
+///     .when(Expression::new_eq(
+///         Expression::attr(Expression::var_principal(), "department"),
+///         Expression::val("eng"),
+///     ))
+///     .build()
+///     .unwrap();
+/// let text = policy.to_string();
+/// assert!(text.starts_with(r#"permit(principal, action == Action::"view", resource)"#));
+/// assert!(text.contains("principal.department") && text.contains(r#""eng""#));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PolicyBuilder {
+    id: Option<PolicyId>,
+    effect: Effect,
+    principal: ast::PrincipalConstraint,
+    action: ast::ActionConstraint,
+    resource: ast::ResourceConstraint,
+    conditions: Vec<ast::Expr>,
+    annotations: BTreeMap<String, SmolStr>,
+}
+
+impl Default for PolicyBuilder {
+    fn default() -> Self {
+        Self {
+            id: None,
+            effect: Effect::Permit,
+            principal: ast::PrincipalConstraint::any(),
+            action: ast::ActionConstraint::any(),
+            resource: ast::ResourceConstraint::any(),
+            conditions: Vec::new(),
+            annotations: BTreeMap::new(),
+        }
+    }
+}
+
+impl PolicyBuilder {
+    /// Create a builder for a `permit` policy with no constraints or
+    /// conditions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the id of the policy. If no id is set, the id is `policy0`, as for
+    /// [`Policy::parse`].
+    #[must_use]
+    pub fn id(mut self, id: PolicyId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Set the effect of the policy
+    #[must_use]
+    pub fn effect(mut self, effect: Effect) -> Self {
+        self.effect = effect;
+        self
+    }
+
+    /// Add the annotation `@key("value")`, replacing any previous value of
+    /// the annotation `key`
+    #[must_use]
+    pub fn annotation(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.annotations
+            .insert(key.as_ref().to_owned(), SmolStr::from(value.as_ref()));
+        self
+    }
+
+    /// Constrain the scope of the principal with `constraint`, e.g., as
+    /// returned by [`Policy::principal_constraint`]
+    #[must_use]
+    pub fn principal(mut self, constraint: PrincipalConstraint) -> Self {
+        match constraint {
+            PrincipalConstraint::Any => {
+                self.principal = ast::PrincipalConstraint::any();
+                self
+            }
+            PrincipalConstraint::In(euid) => self.principal_in(euid),
+            PrincipalConstraint::Eq(euid) => self.principal_eq(euid),
+            PrincipalConstraint::Is(entity_type) => self.principal_is(entity_type),
+            PrincipalConstraint::IsIn(entity_type, euid) => self.principal_is_in(entity_type, euid),
+        }
+    }
+
+    /// Constrain the scope of the action with `constraint`, e.g., as returned
+    /// by [`Policy::action_constraint`]
+    #[must_use]
+    pub fn action(mut self, constraint: ActionConstraint) -> Self {
+        match constraint {
+            ActionConstraint::Any => {
+                self.action = ast::ActionConstraint::any();
+                self
+            }
+            ActionConstraint::In(euids) => self.action_in(euids),
+            ActionConstraint::Eq(euid) => self.action_eq(euid),
+        }
+    }
+
+    /// Constrain the scope of the resource with `constraint`, e.g., as
+    /// returned by [`Policy::resource_constraint`]
+    #[must_use]
+    pub fn resource(mut self, constraint: ResourceConstraint) -> Self {
+        match constraint {
+            ResourceConstraint::Any => {
+                self.resource = ast::ResourceConstraint::any();
+                self
+            }
+            ResourceConstraint::In(euid) => self.resource_in(euid),
+            ResourceConstraint::Eq(euid) => self.resource_eq(euid),
+            ResourceConstraint::Is(entity_type) => self.resource_is(entity_type),
+            ResourceConstraint::IsIn(entity_type, euid) => self.resource_is_in(entity_type, euid),
+        }
+    }
+
+    /// Constrain the scope to `principal == euid`
+    #[must_use]
+    pub fn principal_eq(mut self, euid: EntityUid) -> Self {
+        self.principal = ast::PrincipalConstraint::is_eq(Arc::new(euid.into()));
+        self
+    }
+
+    /// Constrain the scope to `principal in euid`
+    #[must_use]
+    pub fn principal_in(mut self, euid: EntityUid) -> Self {
+        self.principal = ast::PrincipalConstraint::is_in(Arc::new(euid.into()));
+        self
+    }
+
+    /// Constrain the scope to `principal is entity_type`
+    #[must_use]
+    pub fn principal_is(mut self, entity_type: EntityTypeName) -> Self {
+        self.principal = ast::PrincipalConstraint::is_entity_type(Arc::new(entity_type.0));
+        self
+    }
+
+    /// Constrain the scope to `principal is entity_type in euid`
+    #[must_use]
+    pub fn principal_is_in(mut self, entity_type: EntityTypeName, euid: EntityUid) -> Self {
+        self.principal = ast::PrincipalConstraint::is_entity_type_in(
+            Arc::new(entity_type.0),
+            Arc::new(euid.into()),
+        );
+        self
+    }
+
+    /// Constrain the scope to `principal == ?principal`, for a template
+    #[must_use]
+    pub fn principal_eq_slot(mut self) -> Self {
+        self.principal = ast::PrincipalConstraint::is_eq_slot();
+        self
+    }
+
+    /// Constrain the scope to `principal in ?principal`, for a template
+    #[must_use]
+    pub fn principal_in_slot(mut self) -> Self {
+        self.principal = ast::PrincipalConstraint::is_in_slot();
+        self
+    }
+
+    /// Constrain the scope to `principal is entity_type in ?principal`, for a
+    /// template
+    #[must_use]
+    pub fn principal_is_in_slot(mut self, entity_type: EntityTypeName) -> Self {
+        self.principal = ast::PrincipalConstraint::is_entity_type_in_slot(Arc::new(entity_type.0));
+        self
+    }
+
+    /// Constrain the scope to `action == euid`
+    #[must_use]
+    pub fn action_eq(mut self, euid: EntityUid) -> Self {
+        self.action = ast::ActionConstraint::is_eq(euid.into());
+        self
+    }
+
+    /// Constrain the scope to `action in euids`, where `euids` is a set of
+    /// entity UIDs
+    #[must_use]
+    pub fn action_in(mut self, euids: impl IntoIterator<Item = EntityUid>) -> Self {
+        self.action = ast::ActionConstraint::is_in(euids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Constrain the scope to `resource == euid`
+    #[must_use]
+    pub fn resource_eq(mut self, euid: EntityUid) -> Self {
+        self.resource = ast::ResourceConstraint::is_eq(Arc::new(euid.into()));
+        self
+    }
+
+    /// Constrain the scope to `resource in euid`
+    #[must_use]
+    pub fn resource_in(mut self, euid: EntityUid) -> Self {
+        self.resource = ast::ResourceConstraint::is_in(Arc::new(euid.into()));
+        self
+    }
+
+    /// Constrain the scope to `resource is entity_type`
+    #[must_use]
+    pub fn resource_is(mut self, entity_type: EntityTypeName) -> Self {
+        self.resource = ast::ResourceConstraint::is_entity_type(Arc::new(entity_type.0));
+        self
+    }
+
+    /// Constrain the scope to `resource is entity_type in euid`
+    #[must_use]
+    pub fn resource_is_in(mut self, entity_type: EntityTypeName, euid: EntityUid) -> Self {
+        self.resource = ast::ResourceConstraint::is_entity_type_in(
+            Arc::new(entity_type.0),
+            Arc::new(euid.into()),
+        );
+        self
+    }
+
+    /// Constrain the scope to `resource == ?resource`, for a template
+    #[must_use]
+    pub fn resource_eq_slot(mut self) -> Self {
+        self.resource = ast::ResourceConstraint::is_eq_slot();
+        self
+    }
+
+    /// Constrain the scope to `resource in ?resource`, for a template
+    #[must_use]
+    pub fn resource_in_slot(mut self) -> Self {
+        self.resource = ast::ResourceConstraint::is_in_slot();
+        self
+    }
+
+    /// Constrain the scope to `resource is entity_type in ?resource`, for a
+    /// template
+    #[must_use]
+    pub fn resource_is_in_slot(mut self, entity_type: EntityTypeName) -> Self {
+        self.resource = ast::ResourceConstraint::is_entity_type_in_slot(Arc::new(entity_type.0));
+        self
+    }
+
+    /// Add a `when` condition
+    #[must_use]
+    pub fn when(mut self, condition: Expression) -> Self {
+        self.conditions.push(condition.0);
+        self
+    }
+
+    /// Add an `unless` condition
+    #[must_use]
+    pub fn unless(mut self, condition: Expression) -> Self {
+        self.conditions.push(ast::Expr::not(condition.0));
+        self
+    }
+
+    /// Build a static [`Policy`].
+    ///
+    /// Error if the policy contains a template slot, either in its scope or
+    /// in a condition, or if an annotation key is not a valid identifier. Use
+    /// [`PolicyBuilder::build_template`] to build a template.
+    pub fn build(self) -> Result<Policy, PolicyBuilderError> {
+        let policy = ast::StaticPolicy::try_from(self.into_template()?).map_err(
+            |ast::UnexpectedSlotError::FoundSlot(slot)| {
+                policy_builder_errors::UnexpectedSlotError {
+                    slot: slot.id.into(),
+                }
+            },
+        )?;
+        Ok(Policy::from(policy))
+    }
+
+    /// Build a [`Template`]. A template need not contain any slots.
+    ///
+    /// Error if an annotation key is not a valid identifier.
+    pub fn build_template(self) -> Result<Template, PolicyBuilderError> {
+        self.into_template().map(Template::from)
+    }
+
+    fn into_template(self) -> Result<ast::Template, PolicyBuilderError> {
+        let annotations = self
+            .annotations
+            .into_iter()
+            .map(|(key, val)| {
+                key.parse::<ast::AnyId>()
+                    .map(|id| (id, ast::Annotation { val, loc: None }))
+                    .map_err(|_| policy_builder_errors::InvalidAnnotationKeyError { key })
+            })
+            .collect::<Result<ast::Annotations, _>>()?;
+        // Conditions are folded to the right, e.g., `c1 && (c2 && c3)`, as
+        // the parser does, so that the policy is equal to its parsed text
+        let conditions = self
+            .conditions
+            .into_iter()
+            .rev()
+            .reduce(|rest, condition| ast::Expr::and(condition, rest));
+        Ok(ast::Template::new(
+            self.id
+                .map_or_else(|| ast::PolicyID::from_string("policy0"), Into::into),
+            None,
+            annotations,
+            self.effect,
+            self.principal,
+            self.action,
+            self.resource,
+            conditions,
+        ))
+    }
+}
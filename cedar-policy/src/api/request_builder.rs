@@ -0,0 +1,273 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Construction of concrete requests one component at a time.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use cedar_policy_core::ast;
+use cedar_policy_core::extensions::Extensions;
+use cedar_policy_core::validator::types::{OpenTag, Type};
+use smol_str::SmolStr;
+
+use super::{Context, EntityUid, Request, RestrictedExpression, Schema};
+use crate::{concrete_request_builder_errors, ConcreteRequestBuilderError, ContextCreationError};
+
+/// A value of a context attribute, for [`ConcreteRequestBuilder::context_attr`]
+#[derive(Debug, Clone)]
+pub enum ContextValue {
+    /// A boolean
+    Bool(bool),
+    /// A long
+    Long(i64),
+    /// A string
+    String(String),
+    /// A reference to an entity
+    EntityUid(EntityUid),
+    /// A set of values
+    Set(Vec<Self>),
+    /// A record
+    Record(BTreeMap<String, Self>),
+    /// An extension value, created with, e.g., [`RestrictedExpression::new_ip`]
+    /// or [`RestrictedExpression::new_decimal`]
+    Extension(RestrictedExpression),
+}
+
+impl ContextValue {
+    fn into_restricted_expr(self) -> ast::RestrictedExpr {
+        match self {
+            Self::Bool(b) => ast::RestrictedExpr::val(b),
+            Self::Long(i) => ast::RestrictedExpr::val(i),
+            Self::String(s) => ast::RestrictedExpr::val(s),
+            Self::EntityUid(euid) => ast::RestrictedExpr::val(ast::EntityUID::from(euid)),
+            Self::Set(values) => {
+                ast::RestrictedExpr::set(values.into_iter().map(Self::into_restricted_expr))
+            }
+            Self::Record(fields) => {
+                let fields = fields
+                    .into_iter()
+                    .map(|(k, v)| (SmolStr::from(k), v.into_restricted_expr().into()))
+                    .collect();
+                // the keys are unique, and every value is a restricted expression
+                ast::RestrictedExpr::new_unchecked(ast::Expr::record_arc(Arc::new(fields)))
+            }
+            Self::Extension(expr) => expr.0,
+        }
+    }
+}
+
+impl From<bool> for ContextValue {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<i64> for ContextValue {
+    fn from(i: i64) -> Self {
+        Self::Long(i)
+    }
+}
+
+impl From<&str> for ContextValue {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+}
+
+impl From<String> for ContextValue {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<EntityUid> for ContextValue {
+    fn from(euid: EntityUid) -> Self {
+        Self::EntityUid(euid)
+    }
+}
+
+impl<T: Into<Self>> From<Vec<T>> for ContextValue {
+    fn from(values: Vec<T>) -> Self {
+        Self::Set(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<RestrictedExpression> for ContextValue {
+    fn from(expr: RestrictedExpression) -> Self {
+        Self::Extension(expr)
+    }
+}
+
+/// Builder for a [`Request`] whose principal, action, resource and context
+/// are all known.
+///
+/// Unlike [`Request::new`], which only reports that the context does not
+/// match the schema, [`ConcreteRequestBuilder::build`] names the context
+/// attribute which is missing, undeclared, or of the wrong type.
+///
+/// # Examples
+/// ```
+/// # use cedar_policy::{EntityUid, Request, RestrictedExpression};
+/// # use std::str::FromStr;
+/// let request = Request::concrete_builder()
+///     .principal(EntityUid::from_str(r#"User::"alice""#).unwrap())
+///     .action(EntityUid::from_str(r#"Action::"view""#).unwrap())
+///     .resource(EntityUid::from_str(r#"Photo::"vacation.jpg""#).unwrap())
+///     .context_attr("mfa", true)
+///     .context_attr("ip", RestrictedExpression::new_ip("10.0.0.1"))
+///     .build()
+///     .unwrap();
+/// # assert!(request.context().is_some());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConcreteRequestBuilder<'a> {
+    principal: Option<EntityUid>,
+    action: Option<EntityUid>,
+    resource: Option<EntityUid>,
+    context: BTreeMap<SmolStr, ContextValue>,
+    schema: Option<&'a Schema>,
+}
+
+impl<'a> ConcreteRequestBuilder<'a> {
+    /// Set the principal
+    #[must_use]
+    pub fn principal(mut self, principal: EntityUid) -> Self {
+        self.principal = Some(principal);
+        self
+    }
+
+    /// Set the action
+    #[must_use]
+    pub fn action(mut self, action: EntityUid) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    /// Set the resource
+    #[must_use]
+    pub fn resource(mut self, resource: EntityUid) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    /// Set the attribute `attr` of the context, replacing any value it was
+    /// already set to. The context is empty unless attributes are set.
+    #[must_use]
+    pub fn context_attr(mut self, attr: impl AsRef<str>, value: impl Into<ContextValue>) -> Self {
+        self.context
+            .insert(SmolStr::from(attr.as_ref()), value.into());
+        self
+    }
+
+    /// Set the schema which the request is validated against
+    #[must_use]
+    pub fn schema(mut self, schema: &'a Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Create the [`Request`].
+    ///
+    /// Error if the principal, action or resource was not set, or if a schema
+    /// was set and the request does not conform to it.
+    pub fn build(self) -> Result<Request, ConcreteRequestBuilderError> {
+        let missing =
+            |component| concrete_request_builder_errors::MissingComponentError { component };
+        let principal = self.principal.ok_or_else(|| missing("principal"))?;
+        let action = self.action.ok_or_else(|| missing("action"))?;
+        let resource = self.resource.ok_or_else(|| missing("resource"))?;
+        let context = self
+            .context
+            .into_iter()
+            .map(|(k, v)| (k, v.into_restricted_expr()))
+            .collect::<BTreeMap<_, _>>();
+        if let Some(schema) = self.schema {
+            check_context_attrs(schema, &action, &context)?;
+        }
+        let context = Context(
+            ast::Context::from_pairs(context, Extensions::all_available())
+                .map_err(ContextCreationError::from)?,
+        );
+        Ok(Request::new(
+            principal,
+            action,
+            resource,
+            context,
+            self.schema,
+        )?)
+    }
+}
+
+/// Check each attribute of `context` against the context type declared for
+/// `action` in `schema`. Other ways the request can fail to conform to the
+/// schema, e.g., an undeclared action, are left to [`Request::new`].
+fn check_context_attrs(
+    schema: &Schema,
+    action: &EntityUid,
+    context: &BTreeMap<SmolStr, ast::RestrictedExpr>,
+) -> Result<(), ConcreteRequestBuilderError> {
+    let Some(Type::Record {
+        attrs,
+        open_attributes,
+    }) = schema
+        .0
+        .get_action_id(action.as_ref())
+        .map(cedar_policy_core::validator::ValidatorActionId::context_type)
+    else {
+        return Ok(());
+    };
+    for (attr, value) in context {
+        match attrs.get_attr(attr) {
+            None if *open_attributes == OpenTag::ClosedAttributes => {
+                return Err(
+                    concrete_request_builder_errors::UnexpectedContextAttributeError {
+                        attr: attr.clone(),
+                        action: action.clone(),
+                    }
+                    .into(),
+                );
+            }
+            Some(attr_ty)
+                if attr_ty.attr_type.typecheck_restricted_expr(
+                    value.as_borrowed(),
+                    Extensions::all_available(),
+                ) == Ok(false) =>
+            {
+                return Err(concrete_request_builder_errors::ContextAttributeTypeError {
+                    attr: attr.clone(),
+                    expected: attr_ty.attr_type.to_string(),
+                    action: action.clone(),
+                }
+                .into());
+            }
+            _ => (),
+        }
+    }
+    if let Some((attr, _)) = attrs
+        .iter()
+        .find(|(attr, attr_ty)| attr_ty.is_required && !context.contains_key(*attr))
+    {
+        return Err(
+            concrete_request_builder_errors::MissingContextAttributeError {
+                attr: attr.clone(),
+                action: action.clone(),
+            }
+            .into(),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,548 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Conversion of validation results into the SARIF 2.1.0 format, for
+//! consumption by code scanning tools, and into GitHub Actions workflow
+//! commands, for annotating pull requests.
+
+use std::collections::HashMap;
+
+use miette::Diagnostic;
+use serde_json::{json, Value};
+
+use super::err::line_and_column;
+use crate::{PolicyId, ValidationError, ValidationResult, WarningKindInfo};
+
+/// Maps policies to the files they were parsed from.
+///
+/// This lets [`ValidationResult::to_sarif`] and
+/// [`ValidationResult::to_github_annotations`] report the source locations of
+/// validation errors and warnings as file paths, lines, and columns.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+    policies: HashMap<PolicyId, usize>,
+}
+
+#[derive(Debug, Clone)]
+struct SourceFile {
+    path: String,
+    text: String,
+}
+
+impl SourceMap {
+    /// Create an empty `SourceMap`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the file at `path` with contents `text`, from which the policies
+    /// and templates with ids `policy_ids` were parsed. The `path` is reported
+    /// as is, so it should usually be relative to the repository root.
+    pub fn add_file(
+        &mut self,
+        path: impl Into<String>,
+        text: impl Into<String>,
+        policy_ids: impl IntoIterator<Item = PolicyId>,
+    ) {
+        let index = self.files.len();
+        self.files.push(SourceFile {
+            path: path.into(),
+            text: text.into(),
+        });
+        self.policies
+            .extend(policy_ids.into_iter().map(|id| (id, index)));
+    }
+
+    /// Get the file the policy or template with id `policy_id` was parsed from
+    fn file(&self, policy_id: &PolicyId) -> Option<&SourceFile> {
+        self.policies
+            .get(policy_id)
+            .and_then(|index| self.files.get(*index))
+    }
+}
+
+/// A SARIF reporting descriptor, describing a kind of error or warning
+struct Rule {
+    id: &'static str,
+    description: &'static str,
+}
+
+impl ValidationResult {
+    /// Convert this result into a SARIF 2.1.0 log, e.g., for uploading to
+    /// GitHub code scanning.
+    ///
+    /// The log contains a single run with one rule for each kind of error or
+    /// warning found. Rule ids for warnings are the codes from
+    /// [`crate::ValidationWarning::kind_info`]. Errors have level `error` and
+    /// warnings have the level corresponding to their default severity. Each
+    /// result is located by the file, line, and column of its source location,
+    /// as given by `source_map`; results for policies not in `source_map`, or
+    /// not tied to any policy, have no location.
+    #[cfg_attr(docsrs, doc(cfg(feature = "sarif")))]
+    pub fn to_sarif(&self, source_map: &SourceMap) -> Value {
+        let mut rules: Vec<Rule> = Vec::new();
+        let mut result_for = |rule: Rule, level: &str, diagnostic: &dyn Diagnostic, policy_id| {
+            let rule_index = rules
+                .iter()
+                .position(|r| r.id == rule.id)
+                .unwrap_or_else(|| {
+                    rules.push(rule);
+                    rules.len() - 1
+                });
+            sarif_result(
+                rule_index,
+                rules.get(rule_index).map_or("", |r| r.id),
+                level,
+                diagnostic,
+                policy_id,
+                source_map,
+            )
+        };
+        let mut results = self
+            .validation_errors()
+            .map(|err| result_for(error_rule(err), "error", err, Some(err.policy_id())))
+            .collect::<Vec<_>>();
+        results.extend(
+            self.warnings_with_kinds()
+                .map(|(warning, kind, policy_id)| {
+                    let level = match kind.default_severity() {
+                        miette::Severity::Error => "error",
+                        miette::Severity::Warning => "warning",
+                        miette::Severity::Advice => "note",
+                    };
+                    let rule = Rule {
+                        id: kind.code(),
+                        description: kind.description(),
+                    };
+                    result_for(rule, level, warning, policy_id)
+                }),
+        );
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "cedar-policy",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": "https://www.cedarpolicy.com",
+                        "rules": rules.iter().map(|rule| json!({
+                            "id": rule.id,
+                            "shortDescription": { "text": rule.description },
+                        })).collect::<Vec<_>>(),
+                    }
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": results,
+            }]
+        })
+    }
+}
+
+impl ValidationResult {
+    /// Convert this result into GitHub Actions workflow commands, one line per
+    /// error or warning, so that they are shown as annotations on pull
+    /// requests when printed by a workflow step.
+    ///
+    /// Errors are reported with `::error` and warnings with `::warning`, or
+    /// `::notice` for warnings whose default severity is advice. Each line is
+    /// titled with the code of the error or warning, as for
+    /// [`ValidationResult::to_sarif`], and located by the file, line, and
+    /// column of its source location, as given by `source_map`.
+    #[cfg_attr(docsrs, doc(cfg(feature = "sarif")))]
+    pub fn to_github_annotations(&self, source_map: &SourceMap) -> String {
+        let errors = self.validation_errors().map(|err| {
+            github_annotation(
+                "error",
+                error_rule(err).id,
+                err,
+                Some(err.policy_id()),
+                source_map,
+            )
+        });
+        let warnings = self
+            .warnings_with_kinds()
+            .map(|(warning, kind, policy_id)| {
+                let command = match kind.default_severity() {
+                    miette::Severity::Error => "error",
+                    miette::Severity::Warning => "warning",
+                    miette::Severity::Advice => "notice",
+                };
+                github_annotation(command, kind.code(), warning, policy_id, source_map)
+            });
+        errors.chain(warnings).collect()
+    }
+
+    /// Get the warnings found in policies, followed by the warnings about the
+    /// policy set as a whole, each with its kind and the id of its policy
+    fn warnings_with_kinds(
+        &self,
+    ) -> impl Iterator<Item = (&dyn Diagnostic, &'static WarningKindInfo, Option<&PolicyId>)> {
+        let policy_warnings = self.validation_warnings().map(|warning| {
+            (
+                warning as &dyn Diagnostic,
+                warning.kind_info(),
+                Some(warning.policy_id()),
+            )
+        });
+        let policy_set_warnings = self
+            .policy_set_warnings()
+            .map(|warning| (warning as &dyn Diagnostic, warning.kind_info(), None));
+        policy_warnings.chain(policy_set_warnings)
+    }
+}
+
+/// Build a GitHub Actions workflow command line for `diagnostic`, found in the
+/// policy `policy_id`, or in no particular policy if `policy_id` is `None`
+fn github_annotation(
+    command: &str,
+    code: &str,
+    diagnostic: &dyn Diagnostic,
+    policy_id: Option<&PolicyId>,
+    source_map: &SourceMap,
+) -> String {
+    let mut properties = Vec::new();
+    if let Some(file) = policy_id.and_then(|id| source_map.file(id)) {
+        properties.push(format!("file={}", escape_property(&file.path)));
+        let position = primary_label(diagnostic).and_then(|label| {
+            let span = label.inner();
+            Some((
+                line_and_column(&file.text, span.offset())?,
+                line_and_column(&file.text, span.offset() + span.len())?,
+            ))
+        });
+        if let Some(((line, col), (end_line, end_col))) = position {
+            properties.push(format!("line={line},col={col}"));
+            // GitHub only accepts an end column for annotations on one line
+            if end_line == line {
+                properties.push(format!("endColumn={end_col}"));
+            } else {
+                properties.push(format!("endLine={end_line}"));
+            }
+        }
+    }
+    properties.push(format!("title={}", escape_property(code)));
+    let mut message = diagnostic.to_string();
+    if let Some(help) = diagnostic.help() {
+        message = format!("{message}\nhelp: {help}");
+    }
+    format!(
+        "::{command} {}::{}\n",
+        properties.join(","),
+        escape_data(&message)
+    )
+}
+
+/// Escape the message of a workflow command
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape the value of a property of a workflow command
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Build a SARIF result for `diagnostic`, found in the policy `policy_id`, or
+/// in no particular policy if `policy_id` is `None`
+fn sarif_result(
+    rule_index: usize,
+    rule_id: &str,
+    level: &str,
+    diagnostic: &dyn Diagnostic,
+    policy_id: Option<&PolicyId>,
+    source_map: &SourceMap,
+) -> Value {
+    let mut message = diagnostic.to_string();
+    if let Some(help) = diagnostic.help() {
+        message = format!("{message}\nhelp: {help}");
+    }
+    let mut result = json!({
+        "ruleId": rule_id,
+        "ruleIndex": rule_index,
+        "level": level,
+        "message": { "text": message },
+    });
+    if let Some(file) = policy_id.and_then(|id| source_map.file(id)) {
+        let mut physical_location = json!({
+            "artifactLocation": { "uri": file.path },
+        });
+        let span = primary_label(diagnostic).map(|label| *label.inner());
+        let region = span.and_then(|span| {
+            let (start_line, start_column) = line_and_column(&file.text, span.offset())?;
+            let (end_line, end_column) = line_and_column(&file.text, span.offset() + span.len())?;
+            Some(json!({
+                "startLine": start_line,
+                "startColumn": start_column,
+                "endLine": end_line,
+                "endColumn": end_column,
+            }))
+        });
+        if let (Some(region), Some(location)) = (region, physical_location.as_object_mut()) {
+            location.insert("region".into(), region);
+        }
+        if let Some(result) = result.as_object_mut() {
+            result.insert(
+                "locations".into(),
+                json!([{ "physicalLocation": physical_location }]),
+            );
+        }
+    }
+    if let Some(policy_id) = policy_id {
+        if let Some(result) = result.as_object_mut() {
+            result.insert(
+                "properties".into(),
+                json!({ "policyId": policy_id.to_string() }),
+            );
+        }
+    }
+    result
+}
+
+/// The primary label of `diagnostic`, or its first label if none is marked
+/// as primary
+fn primary_label(diagnostic: &dyn Diagnostic) -> Option<miette::LabeledSpan> {
+    let labels = diagnostic.labels()?.collect::<Vec<_>>();
+    let primary = labels.iter().position(miette::LabeledSpan::primary);
+    labels.into_iter().nth(primary.unwrap_or(0))
+}
+
+/// The SARIF rule for the kind of `err`
+#[expect(clippy::too_many_lines, reason = "ValidationError has many variants")]
+fn error_rule(err: &ValidationError) -> Rule {
+    let (id, description) = match err {
+        ValidationError::UnrecognizedEntityType(_) => (
+            "unrecognized-entity-type",
+            "A policy contains an entity type that is not declared in the schema",
+        ),
+        ValidationError::UnrecognizedActionId(_) => (
+            "unrecognized-action-id",
+            "A policy contains an action that is not declared in the schema",
+        ),
+        ValidationError::InvalidActionApplication(_) => (
+            "invalid-action-application",
+            "No action in the action scope constraint applies to the principal and resource scope constraints",
+        ),
+        ValidationError::UnexpectedType(_) => (
+            "unexpected-type",
+            "An expression does not have the type required by its context",
+        ),
+        ValidationError::IncompatibleTypes(_) => (
+            "incompatible-types",
+            "Expressions that must have the same type have incompatible types",
+        ),
+        ValidationError::IncompatibleBranches(_) => (
+            "incompatible-branches",
+            "The branches of a conditional have incompatible types",
+        ),
+        ValidationError::IncompatibleSetElements(_) => (
+            "incompatible-set-elements",
+            "The elements of a set literal have incompatible types",
+        ),
+        ValidationError::SetScalarConfusion(_) => (
+            "set-scalar-confusion",
+            "The operands of an `==` or `in` expression confuse a set with a single value",
+        ),
+        ValidationError::RecordLiteralMismatch(_) => (
+            "record-literal-mismatch",
+            "An attribute of a record literal does not match the expected record type",
+        ),
+        ValidationError::UnsafeAttributeAccess(_) => (
+            "unsafe-attribute-access",
+            "An attribute is accessed which may not be present",
+        ),
+        ValidationError::UnsafeOptionalAttributeAccess(_) => (
+            "unsafe-optional-attribute-access",
+            "An optional attribute is accessed without first checking that it is present",
+        ),
+        ValidationError::UnsafeTagAccess(_) => (
+            "unsafe-tag-access",
+            "A tag is accessed without first checking that it is present",
+        ),
+        ValidationError::NoTagsAllowed(_) => (
+            "no-tags-allowed",
+            "A tag is accessed on an entity type which cannot have tags",
+        ),
+        ValidationError::UndefinedFunction(_) => (
+            "undefined-function",
+            "A policy calls an undefined extension function",
+        ),
+        ValidationError::WrongNumberArguments(_) => (
+            "wrong-number-arguments",
+            "An extension function is called with the wrong number of arguments",
+        ),
+        ValidationError::FunctionArgumentValidation(_) => (
+            "function-argument-validation",
+            "An extension function is called with an invalid argument",
+        ),
+        ValidationError::EmptySetForbidden(_) => (
+            "empty-set-forbidden",
+            "A policy contains an empty set literal whose type cannot be determined",
+        ),
+        ValidationError::NonLitExtConstructor(_) => (
+            "non-lit-ext-constructor",
+            "An extension constructor is called with a non-literal argument",
+        ),
+        ValidationError::ParseError(_) => ("parse-error", "A policy could not be parsed"),
+        ValidationError::HierarchyNotRespected(_) => (
+            "hierarchy-not-respected",
+            "An `in` expression can never be true according to the entity hierarchy in the schema",
+        ),
+        ValidationError::InternalInvariantViolation(_) => (
+            "internal-invariant-violation",
+            "The validator encountered an internal error",
+        ),
+        ValidationError::EntityDerefLevelViolation(_) => (
+            "entity-deref-level-violation",
+            "A policy dereferences entities more levels deep than allowed",
+        ),
+        ValidationError::InvalidEnumEntity(_) => (
+            "invalid-enum-entity",
+            "An entity of an enumerated entity type has an undeclared id",
+        ),
+        ValidationError::MisplacedTemplateSlot(_) => (
+            "misplaced-template-slot",
+            "A template slot appears in a policy condition",
+        ),
+        ValidationError::ArithmeticOverflow(_) => (
+            "arithmetic-overflow",
+            "An arithmetic operation on constants always overflows",
+        ),
+        ValidationError::UnspecifiedEntity(_) => (
+            "unspecified-entity",
+            "A policy constrains the resource of actions declared with no resource",
+        ),
+    };
+    Rule { id, description }
+}
+
+#[cfg(test)]
+#[expect(clippy::indexing_slicing, reason = "unit tests")]
+mod test {
+    use super::*;
+    use crate::{PolicySet, Schema, Validator};
+    use cool_asserts::assert_matches;
+
+    #[test]
+    fn errors_and_warnings_with_locations() {
+        let schema: Schema = r"
+            entity User { age: Long };
+            action view appliesTo { principal: User, resource: User };
+        "
+        .parse()
+        .unwrap();
+        let text = "permit(principal, action, resource)\nwhen { principal.age > \"old\" };\n\npermit(principal, action, resource) when { \"a\u{0430}\" == \"a\" };\n";
+        let pset: PolicySet = text.parse().unwrap();
+        let mut source_map = SourceMap::new();
+        source_map.add_file(
+            "policies/main.cedar",
+            text,
+            pset.policies().map(|p| p.id().clone()),
+        );
+        let result = Validator::new(schema).validate(&pset, crate::ValidationMode::default());
+        let sarif = result.to_sarif(&source_map);
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "cedar-policy");
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(
+            results.len(),
+            result.validation_errors().count() + result.validation_warnings().count()
+        );
+
+        let error = &results[0];
+        assert_eq!(error["ruleId"], "unexpected-type");
+        assert_eq!(error["level"], "error");
+        assert_eq!(rules[0]["id"], "unexpected-type");
+        assert_eq!(error["properties"]["policyId"], "policy0");
+        let location = &error["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "policies/main.cedar");
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["startColumn"], 24);
+
+        let warning = results
+            .iter()
+            .find(|r| r["ruleId"] == "mixed-script-string")
+            .unwrap();
+        assert_eq!(warning["level"], "warning");
+        assert_matches!(warning["ruleIndex"].as_u64(), Some(i) => {
+            assert_eq!(rules[usize::try_from(i).unwrap()]["id"], "mixed-script-string");
+        });
+        assert_eq!(
+            warning["locations"][0]["physicalLocation"]["region"]["startLine"],
+            4
+        );
+    }
+
+    #[test]
+    fn github_annotations() {
+        let schema: Schema = r"
+            entity User { age: Long };
+            action view appliesTo { principal: User, resource: User };
+        "
+        .parse()
+        .unwrap();
+        let text = "permit(principal, action, resource)\nwhen { principal.age > \"old\" };\n\npermit(principal, action, resource) when { \"a\u{0430}\" == \"a\" };\n";
+        let pset: PolicySet = text.parse().unwrap();
+        let mut source_map = SourceMap::new();
+        source_map.add_file(
+            "policies/main.cedar",
+            text,
+            pset.policies().map(|p| p.id().clone()),
+        );
+        let result = Validator::new(schema).validate(&pset, crate::ValidationMode::default());
+        let annotations = result.to_github_annotations(&source_map);
+        let lines = annotations.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines.len(),
+            result.validation_errors().count() + result.validation_warnings().count()
+        );
+        assert_eq!(
+            lines[0],
+            "::error file=policies/main.cedar,line=2,col=24,endColumn=29,title=unexpected-type::for policy `policy0`, unexpected type: expected Long but saw String"
+        );
+        assert!(lines.iter().any(|line| line.starts_with(
+            "::warning file=policies/main.cedar,line=4,col=44,endColumn=48,title=mixed-script-string::"
+        )));
+    }
+
+    #[test]
+    fn github_annotations_are_escaped() {
+        assert_eq!(escape_data("50%\nhelp: a"), "50%25%0Ahelp: a");
+        assert_eq!(escape_property("a,b: c"), "a%2Cb%3A c");
+    }
+
+    #[test]
+    fn unmapped_policy_has_no_location() {
+        let schema: Schema =
+            "entity User; action view appliesTo { principal: User, resource: User };"
+                .parse()
+                .unwrap();
+        let pset: PolicySet = "permit(principal, action, resource) when { principal.foo };"
+            .parse()
+            .unwrap();
+        let result = Validator::new(schema).validate(&pset, crate::ValidationMode::default());
+        let sarif = result.to_sarif(&SourceMap::new());
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| r.get("locations").is_none()));
+    }
+}
@@ -0,0 +1,1006 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Programmatic construction of schemas.
+//!
+//! The builders in this module construct the same schema as the equivalent
+//! JSON schema document, without going through JSON, and the result goes
+//! through exactly the same checks as [`Schema::from_schema_fragments`]
+//! (unknown references, cycles in the entity or action hierarchy, etc.).
+//! Names are additionally checked as they are converted, so that errors
+//! identify the namespace and declaration they occur in.
+
+use std::collections::BTreeMap;
+
+use cedar_policy_core::ast::{Annotation, AnyId, Eid, InternalName, Name, UnreservedId};
+use cedar_policy_core::est;
+use cedar_policy_core::validator::{json_schema, RawName};
+use cedar_policy_core::FromNormalizedStr;
+use nonempty::NonEmpty;
+use serde_json::Value;
+use smol_str::SmolStr;
+
+use super::{Schema, SchemaFragment};
+use crate::{schema_builder_errors, SchemaBuilderError, SchemaError};
+
+/// Builder for a [`Schema`] (or [`SchemaFragment`]).
+///
+/// # Examples
+/// ```
+/// use cedar_policy::{RecordTypeBuilder, SchemaBuilder, SchemaType};
+/// let schema = SchemaBuilder::new()
+///     .namespace("PhotoApp", |ns| {
+///         ns.entity_type("User");
+///         ns.entity_type("Album");
+///         ns.entity_type("Photo")
+///             .attribute("owner", SchemaType::entity("User"))
+///             .member_of("Album");
+///         ns.action("view")
+///             .applies_to_principal("User")
+///             .applies_to_resource("Photo")
+///             .context(RecordTypeBuilder::new().attribute("mfa", SchemaType::boolean()));
+///     })
+///     .build()
+///     .unwrap();
+/// assert_eq!(schema.entity_types().count(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBuilder {
+    namespaces: Vec<NamespaceBuilder>,
+}
+
+impl SchemaBuilder {
+    /// Create a builder for an empty schema
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add declarations to the namespace `name`, using `declare` to declare
+    /// them. Use `""` for the empty namespace. Calling this several times for
+    /// the same namespace adds to the existing declarations.
+    #[must_use]
+    pub fn namespace(mut self, name: &str, declare: impl FnOnce(&mut NamespaceBuilder)) -> Self {
+        if let Some(ns) = self.namespaces.iter_mut().find(|ns| ns.name == name) {
+            declare(ns);
+        } else {
+            let mut ns = NamespaceBuilder::new(name);
+            declare(&mut ns);
+            self.namespaces.push(ns);
+        }
+        self
+    }
+
+    /// Get the schema in the JSON schema format
+    pub fn to_json_value(&self) -> Result<Value, SchemaBuilderError> {
+        serde_json::to_value(self.to_json_schema()?)
+            .map_err(|e| SchemaError::JsonSerialization(e.into()).into())
+    }
+
+    /// Build a [`SchemaFragment`]. This does not check that references to
+    /// other declarations can be resolved, since they may be declared in
+    /// other fragments.
+    pub fn build_fragment(&self) -> Result<SchemaFragment, SchemaBuilderError> {
+        let lossless = self.to_json_schema()?;
+        Ok(SchemaFragment {
+            value: lossless.clone().try_into()?,
+            lossless,
+        })
+    }
+
+    /// Build a [`Schema`]
+    pub fn build(&self) -> Result<Schema, SchemaBuilderError> {
+        Ok(Schema::from_schema_fragments([self.build_fragment()?])?)
+    }
+
+    fn to_json_schema(&self) -> Result<json_schema::Fragment<RawName>, SchemaBuilderError> {
+        self.namespaces
+            .iter()
+            .map(NamespaceBuilder::to_json_schema)
+            .collect::<Result<_, _>>()
+            .map(json_schema::Fragment)
+    }
+}
+
+/// Builder for the declarations in one namespace of a schema. See
+/// [`SchemaBuilder::namespace`].
+#[derive(Debug, Clone)]
+pub struct NamespaceBuilder {
+    name: String,
+    common_types: Vec<CommonTypeBuilder>,
+    entity_types: Vec<EntityTypeBuilder>,
+    actions: Vec<ActionBuilder>,
+    annotations: Annotations,
+}
+
+impl NamespaceBuilder {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            common_types: Vec::new(),
+            entity_types: Vec::new(),
+            actions: Vec::new(),
+            annotations: Annotations::default(),
+        }
+    }
+
+    /// Declare a common type `name` in this namespace, as an alias for `ty`
+    pub fn common_type(&mut self, name: &str, ty: impl Into<SchemaType>) -> &mut CommonTypeBuilder {
+        push_and_get(
+            &mut self.common_types,
+            CommonTypeBuilder {
+                name: name.to_string(),
+                ty: ty.into(),
+                annotations: Annotations::default(),
+            },
+        )
+    }
+
+    /// Declare an entity type `name` in this namespace. The entity type has
+    /// no attributes, parent types, or tags until they are added with the
+    /// returned [`EntityTypeBuilder`].
+    pub fn entity_type(&mut self, name: &str) -> &mut EntityTypeBuilder {
+        push_and_get(&mut self.entity_types, EntityTypeBuilder::new(name))
+    }
+
+    /// Declare an action `name` in this namespace. The action does not apply
+    /// to any principals or resources until they are added with the returned
+    /// [`ActionBuilder`].
+    pub fn action(&mut self, name: &str) -> &mut ActionBuilder {
+        push_and_get(&mut self.actions, ActionBuilder::new(name))
+    }
+
+    /// Add the annotation `@key("value")` to this namespace
+    pub fn annotation(&mut self, key: &str, value: &str) -> &mut Self {
+        self.annotations.add(key, value);
+        self
+    }
+
+    fn to_json_schema(
+        &self,
+    ) -> Result<(Option<Name>, json_schema::NamespaceDefinition<RawName>), SchemaBuilderError> {
+        let cx = DeclarationContext {
+            namespace: &self.name,
+            declaration: &self.name,
+        };
+        let name = if self.name.is_empty() {
+            if !self.annotations.0.is_empty() {
+                return Err(
+                    cx.invalid_declaration("annotations are not allowed on the empty namespace")
+                );
+            }
+            None
+        } else {
+            Some(parse_name::<Name>(&self.name, &self.name, "", "namespace")?)
+        };
+        let mut common_types = BTreeMap::new();
+        for ct in &self.common_types {
+            let cx = cx.declaration(&ct.name);
+            let id = cx.parse_name::<UnreservedId>(&ct.name, "common type name")?;
+            let id = json_schema::CommonTypeId::new(id)
+                .map_err(|_| cx.invalid_name(&ct.name, "common type name"))?;
+            let ty = json_schema::CommonType {
+                ty: ct.ty.to_json_schema(&cx)?,
+                annotations: ct.annotations.to_json_schema(&cx)?,
+                loc: None,
+            };
+            cx.insert_unique(&mut common_types, id, &ct.name, ty)?;
+        }
+        let mut entity_types = BTreeMap::new();
+        for et in &self.entity_types {
+            let cx = cx.declaration(&et.name);
+            let id = cx.parse_name::<UnreservedId>(&et.name, "entity type name")?;
+            cx.insert_unique(&mut entity_types, id, &et.name, et.to_json_schema(&cx)?)?;
+        }
+        let mut actions = BTreeMap::new();
+        for action in &self.actions {
+            let cx = cx.declaration(&action.name);
+            let id = SmolStr::from(action.name.as_str());
+            cx.insert_unique(&mut actions, id, &action.name, action.to_json_schema(&cx)?)?;
+        }
+        let mut def = json_schema::NamespaceDefinition::new(entity_types, actions);
+        def.common_types = common_types;
+        def.annotations = self.annotations.to_json_schema(&cx)?;
+        Ok((name, def))
+    }
+}
+
+/// Builder for a common type declaration. See [`NamespaceBuilder::common_type`].
+#[derive(Debug, Clone)]
+pub struct CommonTypeBuilder {
+    name: String,
+    ty: SchemaType,
+    annotations: Annotations,
+}
+
+impl CommonTypeBuilder {
+    /// Add the annotation `@key("value")` to this common type
+    pub fn annotation(&mut self, key: &str, value: &str) -> &mut Self {
+        self.annotations.add(key, value);
+        self
+    }
+}
+
+/// Builder for an entity type declaration. See [`NamespaceBuilder::entity_type`].
+#[derive(Debug, Clone)]
+pub struct EntityTypeBuilder {
+    name: String,
+    member_of_types: Vec<String>,
+    shape: Option<SchemaType>,
+    attributes: RecordTypeBuilder,
+    tags: Option<SchemaType>,
+    choices: Option<Vec<String>>,
+    annotations: Annotations,
+}
+
+impl EntityTypeBuilder {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            member_of_types: Vec::new(),
+            shape: None,
+            attributes: RecordTypeBuilder::new(),
+            tags: None,
+            choices: None,
+            annotations: Annotations::default(),
+        }
+    }
+
+    /// Add a required attribute `name` to entities of this type. Use
+    /// [`AttributeType::optional`] or [`EntityTypeBuilder::optional_attribute`]
+    /// for an optional attribute.
+    pub fn attribute(&mut self, name: &str, ty: impl Into<AttributeType>) -> &mut Self {
+        self.attributes.add(name, ty.into());
+        self
+    }
+
+    /// Add an optional attribute `name` to entities of this type
+    pub fn optional_attribute(&mut self, name: &str, ty: impl Into<SchemaType>) -> &mut Self {
+        self.attributes.add(name, AttributeType::optional(ty));
+        self
+    }
+
+    /// Use `shape` as the type of the attributes of entities of this type,
+    /// instead of the attributes added with [`EntityTypeBuilder::attribute`].
+    /// This is usually a reference to a common type (see
+    /// [`SchemaType::common`]) which is a record type.
+    pub fn shape(&mut self, shape: impl Into<SchemaType>) -> &mut Self {
+        self.shape = Some(shape.into());
+        self
+    }
+
+    /// Allow entities of this type to be members of entities of type
+    /// `entity_type`
+    pub fn member_of(&mut self, entity_type: &str) -> &mut Self {
+        self.member_of_types.push(entity_type.to_string());
+        self
+    }
+
+    /// Allow entities of this type to have tags, with values of type `ty`
+    pub fn tags(&mut self, ty: impl Into<SchemaType>) -> &mut Self {
+        self.tags = Some(ty.into());
+        self
+    }
+
+    /// Make this an enumerated entity type, whose only entities are those
+    /// with the given ids. Enumerated entity types may not have attributes,
+    /// parent types, or tags.
+    pub fn enumeration<S: Into<String>>(&mut self, ids: impl IntoIterator<Item = S>) -> &mut Self {
+        self.choices = Some(ids.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Add the annotation `@key("value")` to this entity type
+    pub fn annotation(&mut self, key: &str, value: &str) -> &mut Self {
+        self.annotations.add(key, value);
+        self
+    }
+
+    fn to_json_schema(
+        &self,
+        cx: &DeclarationContext<'_>,
+    ) -> Result<json_schema::EntityType<RawName>, SchemaBuilderError> {
+        let kind = if let Some(choices) = &self.choices {
+            if !self.member_of_types.is_empty()
+                || self.shape.is_some()
+                || !self.attributes.attributes.is_empty()
+                || self.tags.is_some()
+            {
+                return Err(cx.invalid_declaration(
+                    "enumerated entity types may not have attributes, parent types, or tags",
+                ));
+            }
+            let choices =
+                NonEmpty::from_vec(choices.iter().map(Eid::new).collect()).ok_or_else(|| {
+                    cx.invalid_declaration("enumerated entity types must have at least one id")
+                })?;
+            json_schema::EntityTypeKind::Enum { choices }
+        } else {
+            let member_of_types = self
+                .member_of_types
+                .iter()
+                .map(|ty| cx.parse_raw_name(ty, "entity type"))
+                .collect::<Result<_, _>>()?;
+            let shape = match &self.shape {
+                Some(shape) => shape.to_json_schema(cx)?,
+                None => self.attributes.to_json_schema(cx)?,
+            };
+            json_schema::EntityTypeKind::Standard(json_schema::StandardEntityType {
+                member_of_types,
+                shape: json_schema::AttributesOrContext(shape),
+                tags: self
+                    .tags
+                    .as_ref()
+                    .map(|tags| tags.to_json_schema(cx))
+                    .transpose()?,
+            })
+        };
+        Ok(json_schema::EntityType {
+            kind,
+            annotations: self.annotations.to_json_schema(cx)?,
+            loc: None,
+        })
+    }
+}
+
+/// Builder for an action declaration. See [`NamespaceBuilder::action`].
+#[derive(Debug, Clone)]
+pub struct ActionBuilder {
+    name: String,
+    member_of: Vec<(Option<String>, String)>,
+    principal_types: Vec<String>,
+    resource_types: Vec<String>,
+    no_resource: bool,
+    context: Option<SchemaType>,
+    annotations: Annotations,
+}
+
+impl ActionBuilder {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            member_of: Vec::new(),
+            principal_types: Vec::new(),
+            resource_types: Vec::new(),
+            no_resource: false,
+            context: None,
+            annotations: Annotations::default(),
+        }
+    }
+
+    /// Allow this action to be used with principals of type `entity_type`
+    pub fn applies_to_principal(&mut self, entity_type: &str) -> &mut Self {
+        self.principal_types.push(entity_type.to_string());
+        self
+    }
+
+    /// Allow this action to be used with resources of type `entity_type`
+    pub fn applies_to_resource(&mut self, entity_type: &str) -> &mut Self {
+        self.resource_types.push(entity_type.to_string());
+        self
+    }
+
+    /// Declare that this action involves no resource at all. The resource of
+    /// every request for the action is then the action itself, and no
+    /// resource types may be added with [`Self::applies_to_resource`].
+    pub fn no_resource(&mut self) -> &mut Self {
+        self.no_resource = true;
+        self
+    }
+
+    /// Set the type of the context for requests for this action. This must
+    /// be a record type, or a reference to a common type which is a record
+    /// type.
+    pub fn context(&mut self, ty: impl Into<SchemaType>) -> &mut Self {
+        self.context = Some(ty.into());
+        self
+    }
+
+    /// Make this action a member of the action group `action` declared in
+    /// the same namespace
+    pub fn member_of(&mut self, action: &str) -> &mut Self {
+        self.member_of.push((None, action.to_string()));
+        self
+    }
+
+    /// Make this action a member of the action group `action` with the
+    /// action entity type `action_type` (e.g., `"OtherNamespace::Action"`)
+    pub fn member_of_action(&mut self, action_type: &str, action: &str) -> &mut Self {
+        self.member_of
+            .push((Some(action_type.to_string()), action.to_string()));
+        self
+    }
+
+    /// Add the annotation `@key("value")` to this action
+    pub fn annotation(&mut self, key: &str, value: &str) -> &mut Self {
+        self.annotations.add(key, value);
+        self
+    }
+
+    fn to_json_schema(
+        &self,
+        cx: &DeclarationContext<'_>,
+    ) -> Result<json_schema::ActionType<RawName>, SchemaBuilderError> {
+        let member_of = if self.member_of.is_empty() {
+            None
+        } else {
+            let member_of = self
+                .member_of
+                .iter()
+                .map(|(ty, id)| {
+                    let ty = ty
+                        .as_ref()
+                        .map(|ty| cx.parse_name::<Name>(ty, "action type"))
+                        .transpose()?
+                        .map(|ty| RawName::from_name(ty.into()));
+                    Ok(json_schema::ActionEntityUID::new(ty, id.into()))
+                })
+                .collect::<Result<Vec<_>, SchemaBuilderError>>()?;
+            Some(member_of)
+        };
+        let applies_to = if !self.principal_types.is_empty()
+            || !self.resource_types.is_empty()
+            || self.no_resource
+            || self.context.is_some()
+        {
+            let entity_types = |types: &[String]| {
+                types
+                    .iter()
+                    .map(|ty| cx.parse_raw_name(ty, "entity type"))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            Some(json_schema::ApplySpec {
+                principal_types: entity_types(&self.principal_types)?,
+                resource_types: entity_types(&self.resource_types)?,
+                no_resource: self.no_resource,
+                context: match &self.context {
+                    Some(context) => json_schema::AttributesOrContext(context.to_json_schema(cx)?),
+                    None => json_schema::AttributesOrContext::default(),
+                },
+            })
+        } else {
+            None
+        };
+        Ok(json_schema::ActionType::new(
+            applies_to,
+            member_of,
+            self.annotations.to_json_schema(cx)?,
+        ))
+    }
+}
+
+/// A type in a schema, e.g., the type of an attribute
+#[derive(Debug, Clone)]
+pub struct SchemaType(SchemaTypeKind);
+
+#[derive(Debug, Clone)]
+enum SchemaTypeKind {
+    Long,
+    String,
+    Boolean,
+    Set(Box<SchemaType>),
+    Entity(String),
+    Extension(String),
+    Common(String),
+    EntityOrCommon(String),
+    Record(RecordTypeBuilder),
+}
+
+impl SchemaType {
+    /// The `Long` type
+    pub fn long() -> Self {
+        Self(SchemaTypeKind::Long)
+    }
+
+    /// The `String` type
+    pub fn string() -> Self {
+        Self(SchemaTypeKind::String)
+    }
+
+    /// The `Bool` type
+    pub fn boolean() -> Self {
+        Self(SchemaTypeKind::Boolean)
+    }
+
+    /// The type of sets whose elements have type `element`
+    pub fn set(element: impl Into<Self>) -> Self {
+        Self(SchemaTypeKind::Set(Box::new(element.into())))
+    }
+
+    /// The type of entities of type `name`. Unqualified names are resolved
+    /// in the namespace where the type is used.
+    pub fn entity(name: &str) -> Self {
+        Self(SchemaTypeKind::Entity(name.to_string()))
+    }
+
+    /// The extension type `name`, e.g., `"ipaddr"` or `"decimal"`
+    pub fn extension(name: &str) -> Self {
+        Self(SchemaTypeKind::Extension(name.to_string()))
+    }
+
+    /// A reference to the common type `name`. Unqualified names are resolved
+    /// in the namespace where the type is used.
+    pub fn common(name: &str) -> Self {
+        Self(SchemaTypeKind::Common(name.to_string()))
+    }
+
+    /// A reference to the common type or entity type `name`, resolved in the
+    /// same way as type names in the Cedar schema syntax
+    pub fn named(name: &str) -> Self {
+        Self(SchemaTypeKind::EntityOrCommon(name.to_string()))
+    }
+
+    /// A record type
+    pub fn record(record: RecordTypeBuilder) -> Self {
+        Self(SchemaTypeKind::Record(record))
+    }
+
+    fn to_json_schema(
+        &self,
+        cx: &DeclarationContext<'_>,
+    ) -> Result<json_schema::Type<RawName>, SchemaBuilderError> {
+        use json_schema::TypeVariant;
+        let ty = match &self.0 {
+            SchemaTypeKind::Long => TypeVariant::Long,
+            SchemaTypeKind::String => TypeVariant::String,
+            SchemaTypeKind::Boolean => TypeVariant::Boolean,
+            SchemaTypeKind::Set(element) => TypeVariant::Set {
+                element: Box::new(element.to_json_schema(cx)?),
+            },
+            SchemaTypeKind::Entity(name) => TypeVariant::Entity {
+                name: cx.parse_raw_name(name, "entity type")?,
+            },
+            SchemaTypeKind::Extension(name) => TypeVariant::Extension {
+                name: cx.parse_name::<UnreservedId>(name, "extension type")?,
+            },
+            SchemaTypeKind::Common(name) => {
+                return Ok(json_schema::Type::CommonTypeRef {
+                    type_name: cx.parse_raw_name(name, "common type")?,
+                    loc: None,
+                });
+            }
+            SchemaTypeKind::EntityOrCommon(name) => TypeVariant::EntityOrCommon {
+                type_name: cx.parse_raw_name(name, "type")?,
+            },
+            SchemaTypeKind::Record(record) => return record.to_json_schema(cx),
+        };
+        Ok(json_schema::Type::Type { ty, loc: None })
+    }
+}
+
+impl From<RecordTypeBuilder> for SchemaType {
+    fn from(record: RecordTypeBuilder) -> Self {
+        Self::record(record)
+    }
+}
+
+/// The type of an attribute in a record type or entity type, along with
+/// whether the attribute is required and its annotations
+#[derive(Debug, Clone)]
+pub struct AttributeType {
+    ty: SchemaType,
+    required: bool,
+    annotations: Annotations,
+}
+
+impl AttributeType {
+    /// A required attribute of type `ty`
+    pub fn required(ty: impl Into<SchemaType>) -> Self {
+        Self {
+            ty: ty.into(),
+            required: true,
+            annotations: Annotations::default(),
+        }
+    }
+
+    /// An optional attribute of type `ty`
+    pub fn optional(ty: impl Into<SchemaType>) -> Self {
+        Self {
+            required: false,
+            ..Self::required(ty)
+        }
+    }
+
+    /// Add the annotation `@key("value")` to this attribute
+    #[must_use]
+    pub fn annotation(mut self, key: &str, value: &str) -> Self {
+        self.annotations.add(key, value);
+        self
+    }
+}
+
+impl From<SchemaType> for AttributeType {
+    fn from(ty: SchemaType) -> Self {
+        Self::required(ty)
+    }
+}
+
+impl From<RecordTypeBuilder> for AttributeType {
+    fn from(record: RecordTypeBuilder) -> Self {
+        Self::required(record)
+    }
+}
+
+/// Builder for a record type
+#[derive(Debug, Clone, Default)]
+pub struct RecordTypeBuilder {
+    attributes: Vec<(String, AttributeType)>,
+    additional_attributes: bool,
+}
+
+impl RecordTypeBuilder {
+    /// Create a builder for a record type with no attributes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a required attribute `name`. Use [`AttributeType::optional`] or
+    /// [`RecordTypeBuilder::optional_attribute`] for an optional attribute.
+    #[must_use]
+    pub fn attribute(mut self, name: &str, ty: impl Into<AttributeType>) -> Self {
+        self.add(name, ty.into());
+        self
+    }
+
+    /// Add an optional attribute `name`
+    #[must_use]
+    pub fn optional_attribute(mut self, name: &str, ty: impl Into<SchemaType>) -> Self {
+        self.add(name, AttributeType::optional(ty));
+        self
+    }
+
+    /// Allow records of this type to have attributes other than those
+    /// declared. This is only supported for partial schema validation.
+    #[must_use]
+    pub fn additional_attributes(mut self, allowed: bool) -> Self {
+        self.additional_attributes = allowed;
+        self
+    }
+
+    fn add(&mut self, name: &str, ty: AttributeType) {
+        self.attributes.push((name.to_string(), ty));
+    }
+
+    fn to_json_schema(
+        &self,
+        cx: &DeclarationContext<'_>,
+    ) -> Result<json_schema::Type<RawName>, SchemaBuilderError> {
+        let mut attributes = BTreeMap::new();
+        for (name, attr) in &self.attributes {
+            let ty = json_schema::TypeOfAttribute::new(
+                attr.ty.to_json_schema(cx)?,
+                attr.required,
+                attr.annotations.to_json_schema(cx)?,
+            );
+            cx.insert_unique(&mut attributes, SmolStr::from(name.as_str()), name, ty)?;
+        }
+        Ok(json_schema::Type::Type {
+            ty: json_schema::TypeVariant::Record(json_schema::RecordType {
+                attributes,
+                additional_attributes: self.additional_attributes,
+            }),
+            loc: None,
+        })
+    }
+}
+
+/// Annotations on a declaration, in the order they were added
+#[derive(Debug, Clone, Default)]
+struct Annotations(Vec<(String, String)>);
+
+impl Annotations {
+    fn add(&mut self, key: &str, value: &str) {
+        self.0.push((key.to_string(), value.to_string()));
+    }
+
+    fn to_json_schema(
+        &self,
+        cx: &DeclarationContext<'_>,
+    ) -> Result<est::Annotations, SchemaBuilderError> {
+        let mut annotations = BTreeMap::new();
+        for (key, value) in &self.0 {
+            let id = cx.parse_name::<AnyId>(key, "annotation key")?;
+            let annotation = Annotation {
+                val: value.into(),
+                loc: None,
+            };
+            cx.insert_unique(&mut annotations, id, key, Some(annotation))?;
+        }
+        Ok(est::Annotations(annotations))
+    }
+}
+
+/// The namespace and declaration currently being converted, used to
+/// identify where an error occurred
+#[derive(Debug, Clone, Copy)]
+struct DeclarationContext<'a> {
+    namespace: &'a str,
+    declaration: &'a str,
+}
+
+impl<'a> DeclarationContext<'a> {
+    fn declaration(self, declaration: &'a str) -> Self {
+        Self {
+            declaration,
+            ..self
+        }
+    }
+
+    fn parse_name<T: FromNormalizedStr>(
+        &self,
+        name: &str,
+        kind: &'static str,
+    ) -> Result<T, SchemaBuilderError> {
+        parse_name::<T>(name, self.namespace, self.declaration, kind)
+    }
+
+    /// Parse a reference to an entity type or common type
+    fn parse_raw_name(
+        &self,
+        name: &str,
+        kind: &'static str,
+    ) -> Result<RawName, SchemaBuilderError> {
+        self.parse_name::<InternalName>(name, kind)
+            .map(RawName::from_name)
+    }
+
+    fn invalid_name(&self, name: &str, kind: &'static str) -> SchemaBuilderError {
+        schema_builder_errors::InvalidNameError {
+            namespace: self.namespace.to_string(),
+            declaration: self.declaration.to_string(),
+            name: name.to_string(),
+            kind,
+        }
+        .into()
+    }
+
+    fn invalid_declaration(&self, reason: &'static str) -> SchemaBuilderError {
+        schema_builder_errors::InvalidDeclarationError {
+            namespace: self.namespace.to_string(),
+            declaration: self.declaration.to_string(),
+            reason,
+        }
+        .into()
+    }
+
+    /// Insert `key`, written `name` by the user, into `map`, failing if it is
+    /// already present
+    fn insert_unique<K: Ord, V>(
+        &self,
+        map: &mut BTreeMap<K, V>,
+        key: K,
+        name: &str,
+        value: V,
+    ) -> Result<(), SchemaBuilderError> {
+        if map.insert(key, value).is_some() {
+            return Err(schema_builder_errors::DuplicateDeclarationError {
+                namespace: self.namespace.to_string(),
+                declaration: self.declaration.to_string(),
+                name: name.to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+fn parse_name<T: FromNormalizedStr>(
+    name: &str,
+    namespace: &str,
+    declaration: &str,
+    kind: &'static str,
+) -> Result<T, SchemaBuilderError> {
+    T::from_normalized_str(name).map_err(|_| {
+        schema_builder_errors::InvalidNameError {
+            namespace: namespace.to_string(),
+            declaration: declaration.to_string(),
+            name: name.to_string(),
+            kind,
+        }
+        .into()
+    })
+}
+
+/// Push `item` onto `items` and return a reference to it
+fn push_and_get<T>(items: &mut Vec<T>, item: T) -> &mut T {
+    items.push(item);
+    #[expect(clippy::expect_used, reason = "we just pushed an item")]
+    items.last_mut().expect("vector should be nonempty")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    #[test]
+    fn builder_matches_json() {
+        let built = SchemaBuilder::new()
+            .namespace("PhotoApp", |ns| {
+                ns.annotation("doc", "photos");
+                ns.common_type(
+                    "Meta",
+                    RecordTypeBuilder::new()
+                        .attribute(
+                            "tags",
+                            AttributeType::required(SchemaType::set(SchemaType::string()))
+                                .annotation("doc", "tags"),
+                        )
+                        .optional_attribute("score", SchemaType::long()),
+                );
+                ns.entity_type("User").tags(SchemaType::string());
+                ns.entity_type("Album").member_of("Album");
+                ns.entity_type("Color").enumeration(["red", "green"]);
+                ns.entity_type("Photo")
+                    .attribute("owner", SchemaType::entity("User"))
+                    .attribute("meta", SchemaType::common("Meta"))
+                    .optional_attribute("src", SchemaType::extension("ipaddr"))
+                    .member_of("Album")
+                    .annotation("doc", "a photo");
+                ns.action("read");
+                ns.action("view")
+                    .member_of("read")
+                    .applies_to_principal("User")
+                    .applies_to_resource("Photo")
+                    .context(RecordTypeBuilder::new().attribute("mfa", SchemaType::boolean()));
+            })
+            .to_json_value()
+            .unwrap();
+        let expected = json!({
+            "PhotoApp": {
+                "annotations": { "doc": "photos" },
+                "commonTypes": {
+                    "Meta": {
+                        "type": "Record",
+                        "attributes": {
+                            "tags": {
+                                "type": "Set",
+                                "element": { "type": "String" },
+                                "annotations": { "doc": "tags" },
+                            },
+                            "score": { "type": "Long", "required": false },
+                        },
+                    },
+                },
+                "entityTypes": {
+                    "User": { "tags": { "type": "String" } },
+                    "Album": { "memberOfTypes": ["Album"] },
+                    "Color": { "enum": ["red", "green"] },
+                    "Photo": {
+                        "memberOfTypes": ["Album"],
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "owner": { "type": "Entity", "name": "User" },
+                                "meta": { "type": "Meta" },
+                                "src": { "type": "Extension", "name": "ipaddr", "required": false },
+                            },
+                        },
+                        "annotations": { "doc": "a photo" },
+                    },
+                },
+                "actions": {
+                    "read": {},
+                    "view": {
+                        "memberOf": [{ "id": "read" }],
+                        "appliesTo": {
+                            "principalTypes": ["User"],
+                            "resourceTypes": ["Photo"],
+                            "context": {
+                                "type": "Record",
+                                "attributes": { "mfa": { "type": "Boolean" } },
+                            },
+                        },
+                    },
+                },
+            },
+        });
+        assert_eq!(built, expected);
+        Schema::from_json_value(expected).unwrap();
+    }
+
+    #[test]
+    fn undeclared_reference() {
+        let result = SchemaBuilder::new()
+            .namespace("", |ns| {
+                ns.entity_type("Photo").member_of("Album");
+            })
+            .build();
+        assert_matches!(result, Err(SchemaBuilderError::Schema(e)) => {
+            // The schema is never converted to JSON, so the error does not
+            // point into a JSON document the user never wrote
+            assert!(e.json_location().is_none());
+        });
+    }
+
+    #[test]
+    fn invalid_declaration() {
+        let result = SchemaBuilder::new()
+            .namespace("PhotoApp", |ns| {
+                ns.entity_type("Color")
+                    .enumeration(["red"])
+                    .attribute("hex", SchemaType::string());
+            })
+            .build();
+        assert_matches!(result, Err(SchemaBuilderError::InvalidDeclaration(e)) => {
+            assert_eq!(e.namespace(), "PhotoApp");
+            assert_eq!(e.declaration(), "Color");
+        });
+
+        let result = SchemaBuilder::new()
+            .namespace("PhotoApp", |ns| {
+                ns.entity_type("Color").enumeration(Vec::<String>::new());
+            })
+            .build();
+        assert_matches!(result, Err(SchemaBuilderError::InvalidDeclaration(_)));
+
+        let result = SchemaBuilder::new()
+            .namespace("", |ns| {
+                ns.annotation("doc", "top level");
+            })
+            .build();
+        assert_matches!(result, Err(SchemaBuilderError::InvalidDeclaration(e)) => {
+            assert_eq!(
+                e.to_string(),
+                "in namespace ``, declaration ``: annotations are not allowed on the empty namespace"
+            );
+        });
+    }
+
+    #[test]
+    fn reserved_common_type_name() {
+        let result = SchemaBuilder::new()
+            .namespace("PhotoApp", |ns| {
+                ns.common_type("Set", SchemaType::long());
+            })
+            .build();
+        assert_matches!(result, Err(SchemaBuilderError::InvalidName(e)) => {
+            assert_eq!(e.declaration(), "Set");
+            assert_eq!(e.name(), "Set");
+        });
+    }
+
+    #[test]
+    fn invalid_name() {
+        let result = SchemaBuilder::new()
+            .namespace("PhotoApp", |ns| {
+                ns.entity_type("Photo")
+                    .attribute("owner", SchemaType::entity("not a name"));
+            })
+            .build();
+        assert_matches!(result, Err(SchemaBuilderError::InvalidName(e)) => {
+            assert_eq!(e.namespace(), "PhotoApp");
+            assert_eq!(e.declaration(), "Photo");
+            assert_eq!(e.name(), "not a name");
+        });
+    }
+
+    #[test]
+    fn duplicate_declaration() {
+        let result = SchemaBuilder::new()
+            .namespace("PhotoApp", |ns| {
+                ns.entity_type("Photo");
+            })
+            .namespace("PhotoApp", |ns| {
+                ns.entity_type("Photo");
+            })
+            .build();
+        assert_matches!(result, Err(SchemaBuilderError::DuplicateDeclaration(e)) => {
+            assert_eq!(e.namespace(), "PhotoApp");
+            assert_eq!(e.name(), "Photo");
+        });
+    }
+}
@@ -0,0 +1,666 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Inference of a draft schema from existing entity data and policies.
+//!
+//! The inferred schema is only a starting point: it describes the data which
+//! was observed, which may be incomplete, so it should be reviewed and edited
+//! by hand before it is used for validation.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+
+use cedar_policy_core::ast::{
+    self, ActionConstraint, EntityReference, Literal, PartialValue, PrincipalOrResourceConstraint,
+    Value, ValueKind,
+};
+use itertools::Itertools;
+use miette::Diagnostic;
+use smol_str::SmolStr;
+use thiserror::Error;
+
+use super::{
+    Entities, EntityTypeName, PolicySet, RecordTypeBuilder, Schema, SchemaBuilder, SchemaFragment,
+    SchemaType,
+};
+use crate::SchemaBuilderError;
+
+impl Schema {
+    /// Infer a draft [`SchemaFragment`] from existing entity data and
+    /// policies, as a starting point for writing a schema by hand. The
+    /// fragment can be converted to JSON with [`SchemaFragment::to_json_value`]
+    /// or to the Cedar schema syntax with [`SchemaFragment::to_cedarschema`]
+    /// for editing.
+    ///
+    /// - Every entity type of an entity in `entities`, or used in an attribute
+    ///   value, parent, or policy scope, is declared.
+    /// - The attributes of an entity type are the union of the attributes of
+    ///   its entities. Attributes which some entities don't have are optional.
+    ///   The same applies to the attributes of records.
+    /// - The parent types of an entity type are the types of the parents of
+    ///   its entities. Action entities in `entities` are declared as actions,
+    ///   members of the action groups which are their parents.
+    /// - The principal and resource types of an action are the types in the
+    ///   scope of policies for the action. A policy constraining `principal in`
+    ///   (or `resource in`) an entity contributes the type of the entity and
+    ///   all entity types which were observed to be descendants of it.
+    ///
+    /// Values of different types for the same attribute are not widened to a
+    /// common type. Instead, the attribute keeps the type observed first, in
+    /// order of entity UID, and each such attribute is reported as a
+    /// [`SchemaInferenceConflict`]. The element type of sets which were only
+    /// observed to be empty can't be inferred, and is `String`.
+    pub fn infer(
+        entities: &Entities,
+        policies: &PolicySet,
+    ) -> Result<(SchemaFragment, Vec<SchemaInferenceConflict>), SchemaBuilderError> {
+        let mut inferrer = Inferrer::default();
+        // Observe entities in a deterministic order, so that the same type is
+        // kept for conflicting attributes regardless of how `entities` is stored
+        for entity in entities
+            .0
+            .iter()
+            .sorted_unstable_by(|a, b| a.uid().cmp(b.uid()))
+        {
+            inferrer.observe_entity(entity);
+        }
+        for template in policies.ast.all_templates() {
+            inferrer.observe_policy_scope(template);
+        }
+        inferrer.finish()
+    }
+}
+
+/// Conflicting types were observed for the same attribute while inferring a
+/// schema with [`Schema::infer`]. The inferred schema uses the first type
+/// observed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("conflicting types observed for {}entity type `{entity_type}`: {}", location(.attribute_path, *.tags), .observed_types.iter().map(|ty| format!("`{ty}`")).join(", "))]
+pub struct SchemaInferenceConflict {
+    entity_type: EntityTypeName,
+    attribute_path: Vec<SmolStr>,
+    tags: bool,
+    observed_types: Vec<String>,
+}
+
+fn location(attribute_path: &[SmolStr], tags: bool) -> String {
+    let attribute = if attribute_path.is_empty() {
+        String::new()
+    } else {
+        format!("attribute `{}` of ", attribute_path.iter().join("."))
+    };
+    let tags = if tags { "the tags of " } else { "" };
+    format!("{attribute}{tags}")
+}
+
+impl SchemaInferenceConflict {
+    /// The entity type with the conflicting attribute
+    pub fn entity_type(&self) -> &EntityTypeName {
+        &self.entity_type
+    }
+
+    /// The path to the conflicting attribute, e.g., `["address", "zip"]`.
+    /// For tags, this is the path within the tag values, so it is empty if
+    /// the tag values themselves have conflicting types.
+    pub fn attribute_path(&self) -> impl Iterator<Item = &str> {
+        self.attribute_path.iter().map(SmolStr::as_str)
+    }
+
+    /// `true` if the conflict is in the values of tags, rather than attributes
+    pub fn is_in_tags(&self) -> bool {
+        self.tags
+    }
+
+    /// The types observed, in the order they were first observed. The first
+    /// one is used in the inferred schema.
+    pub fn observed_types(&self) -> impl Iterator<Item = &str> {
+        self.observed_types.iter().map(String::as_str)
+    }
+}
+
+impl Diagnostic for SchemaInferenceConflict {
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!(
+            "the inferred schema uses `{}`; update it to the intended type",
+            self.observed_types.first().map_or("", String::as_str)
+        )))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Warning)
+    }
+}
+
+/// Type of a value observed in entity data
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ObservedType {
+    Bool,
+    Long,
+    String,
+    Entity(ast::EntityType),
+    /// The element type is `None` if only empty sets were observed
+    Set(Option<Box<Self>>),
+    Record(BTreeMap<SmolStr, ObservedAttribute>),
+    Extension(ast::Name),
+}
+
+impl Display for ObservedType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool => write!(f, "Bool"),
+            Self::Long => write!(f, "Long"),
+            Self::String => write!(f, "String"),
+            Self::Entity(ety) => write!(f, "{ety}"),
+            Self::Set(Some(element)) => write!(f, "Set<{element}>"),
+            Self::Set(None) => write!(f, "Set"),
+            Self::Record(_) => write!(f, "Record"),
+            Self::Extension(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl ObservedType {
+    fn to_schema_type(&self) -> SchemaType {
+        match self {
+            Self::Bool => SchemaType::boolean(),
+            Self::Long => SchemaType::long(),
+            Self::String => SchemaType::string(),
+            Self::Entity(ety) => SchemaType::entity(&ety.to_string()),
+            Self::Set(element) => SchemaType::set(
+                element
+                    .as_ref()
+                    .map_or_else(SchemaType::string, |element| element.to_schema_type()),
+            ),
+            Self::Record(attrs) => SchemaType::record(attrs.iter().fold(
+                RecordTypeBuilder::new(),
+                |record, (name, attr)| {
+                    if attr.required {
+                        record.attribute(name, attr.ty.to_schema_type())
+                    } else {
+                        record.optional_attribute(name, attr.ty.to_schema_type())
+                    }
+                },
+            )),
+            Self::Extension(name) => SchemaType::extension(&name.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ObservedAttribute {
+    ty: ObservedType,
+    required: bool,
+}
+
+#[derive(Debug, Default)]
+struct ObservedEntityType {
+    /// Always an `ObservedType::Record`, or `None` if no entity of this type
+    /// was observed
+    shape: Option<ObservedType>,
+    tags: Option<ObservedType>,
+    parents: BTreeSet<ast::EntityType>,
+}
+
+#[derive(Debug, Default)]
+struct ObservedAction {
+    principals: BTreeSet<ast::EntityType>,
+    resources: BTreeSet<ast::EntityType>,
+    /// Action groups this action is a member of
+    groups: BTreeSet<ast::EntityUID>,
+}
+
+/// Where a value being observed is, for reporting conflicts
+#[derive(Debug)]
+struct Site<'a> {
+    entity_type: &'a ast::EntityType,
+    tags: bool,
+    path: Vec<SmolStr>,
+}
+
+#[derive(Debug, Default)]
+struct Inferrer {
+    entity_types: BTreeMap<ast::EntityType, ObservedEntityType>,
+    actions: BTreeMap<ast::EntityUID, ObservedAction>,
+    /// Principal and resource types of policies which apply to any action
+    any_action: ObservedAction,
+    /// Conflicts by entity type, tags or not, and attribute path
+    conflicts: BTreeMap<(ast::EntityType, bool, Vec<SmolStr>), Vec<String>>,
+}
+
+impl Inferrer {
+    fn declare(&mut self, ety: &ast::EntityType) {
+        // Action entity types are implicitly declared by declaring actions
+        if !ety.is_action() {
+            self.entity_types.entry(ety.clone()).or_default();
+        }
+    }
+
+    fn observe_entity(&mut self, entity: &ast::Entity) {
+        let ety = entity.uid().entity_type();
+        if ety.is_action() {
+            let groups = entity
+                .parents()
+                .filter(|parent| parent.entity_type().is_action())
+                .cloned()
+                .collect::<Vec<_>>();
+            for group in &groups {
+                self.actions.entry(group.clone()).or_default();
+            }
+            self.actions
+                .entry(entity.uid().clone())
+                .or_default()
+                .groups
+                .extend(groups);
+            return;
+        }
+        self.declare(ety);
+        let mut site = Site {
+            entity_type: ety,
+            tags: false,
+            path: Vec::new(),
+        };
+        let shape = ObservedType::Record(
+            entity
+                .attrs()
+                .filter_map(|(name, value)| {
+                    let ty = self.observe_partial_value(value, &mut site, name)?;
+                    Some((name.clone(), ObservedAttribute { ty, required: true }))
+                })
+                .collect(),
+        );
+        let shape = match self.entity_types.get_mut(ety).and_then(|e| e.shape.take()) {
+            Some(prev) => self.merge(prev, shape, &mut site),
+            None => shape,
+        };
+
+        site.tags = true;
+        let mut tags = None;
+        for (name, value) in entity.tags() {
+            if let Some(ty) = self.observe_partial_value(value, &mut site, name) {
+                tags = Some(match tags {
+                    Some(prev) => self.merge(prev, ty, &mut site),
+                    None => ty,
+                });
+            }
+        }
+        let tags = match (
+            self.entity_types.get_mut(ety).and_then(|e| e.tags.take()),
+            tags,
+        ) {
+            (Some(prev), Some(ty)) => Some(self.merge(prev, ty, &mut site)),
+            (prev, ty) => prev.or(ty),
+        };
+
+        let parents = entity
+            .parents()
+            .map(ast::EntityUID::entity_type)
+            .filter(|parent| !parent.is_action())
+            .cloned()
+            .collect::<Vec<_>>();
+        for parent in &parents {
+            self.declare(parent);
+        }
+        if let Some(observed) = self.entity_types.get_mut(ety) {
+            observed.shape = Some(shape);
+            observed.tags = tags;
+            observed.parents.extend(parents);
+        }
+    }
+
+    /// Observe the value of the attribute or tag `name`. Residuals contain
+    /// unknowns, so nothing can be inferred from them.
+    fn observe_partial_value(
+        &mut self,
+        value: &PartialValue,
+        site: &mut Site<'_>,
+        name: &SmolStr,
+    ) -> Option<ObservedType> {
+        match value {
+            PartialValue::Value(value) => {
+                // Tags are reported by the path within the tag values
+                if !site.tags {
+                    site.path.push(name.clone());
+                }
+                let ty = self.observe(value, site);
+                if !site.tags {
+                    site.path.pop();
+                }
+                Some(ty)
+            }
+            PartialValue::Residual(_) => None,
+        }
+    }
+
+    fn observe(&mut self, value: &Value, site: &mut Site<'_>) -> ObservedType {
+        match value.value_kind() {
+            ValueKind::Lit(Literal::Bool(_)) => ObservedType::Bool,
+            ValueKind::Lit(Literal::Long(_)) => ObservedType::Long,
+            ValueKind::Lit(Literal::String(_)) => ObservedType::String,
+            ValueKind::Lit(Literal::EntityUID(euid)) => {
+                self.declare(euid.entity_type());
+                ObservedType::Entity(euid.entity_type().clone())
+            }
+            ValueKind::Set(set) => {
+                let mut element = None;
+                for value in set.iter() {
+                    let ty = self.observe(value, site);
+                    element = Some(match element {
+                        Some(prev) => self.merge(prev, ty, site),
+                        None => ty,
+                    });
+                }
+                ObservedType::Set(element.map(Box::new))
+            }
+            ValueKind::Record(attrs) => ObservedType::Record(
+                attrs
+                    .iter()
+                    .map(|(name, value)| {
+                        site.path.push(name.clone());
+                        let ty = self.observe(value, site);
+                        site.path.pop();
+                        (name.clone(), ObservedAttribute { ty, required: true })
+                    })
+                    .collect(),
+            ),
+            ValueKind::ExtensionValue(ext) => ObservedType::Extension(ext.typename()),
+        }
+    }
+
+    /// Merge two types observed at the same site, reporting a conflict and
+    /// keeping `prev` if they are incompatible
+    fn merge(
+        &mut self,
+        prev: ObservedType,
+        next: ObservedType,
+        site: &mut Site<'_>,
+    ) -> ObservedType {
+        match (prev, next) {
+            (ObservedType::Set(Some(prev)), ObservedType::Set(Some(next))) => {
+                ObservedType::Set(Some(Box::new(self.merge(*prev, *next, site))))
+            }
+            (ObservedType::Set(prev), ObservedType::Set(next)) => ObservedType::Set(prev.or(next)),
+            (ObservedType::Record(mut prev), ObservedType::Record(next)) => {
+                // Attributes missing from either record are optional
+                for (name, attr) in &mut prev {
+                    if !next.contains_key(name) {
+                        attr.required = false;
+                    }
+                }
+                for (name, next_attr) in next {
+                    let attr = match prev.remove(&name) {
+                        Some(prev_attr) => {
+                            site.path.push(name.clone());
+                            let ty = self.merge(prev_attr.ty, next_attr.ty, site);
+                            site.path.pop();
+                            ObservedAttribute {
+                                ty,
+                                required: prev_attr.required && next_attr.required,
+                            }
+                        }
+                        None => ObservedAttribute {
+                            ty: next_attr.ty,
+                            required: false,
+                        },
+                    };
+                    prev.insert(name, attr);
+                }
+                ObservedType::Record(prev)
+            }
+            (prev, next) if prev == next => prev,
+            (prev, next) => {
+                let observed = self
+                    .conflicts
+                    .entry((site.entity_type.clone(), site.tags, site.path.clone()))
+                    .or_default();
+                for ty in [prev.to_string(), next.to_string()] {
+                    if !observed.contains(&ty) {
+                        observed.push(ty);
+                    }
+                }
+                prev
+            }
+        }
+    }
+
+    fn observe_policy_scope(&mut self, template: &ast::Template) {
+        let principals = self.scope_types(template.principal_constraint().as_inner());
+        let resources = self.scope_types(template.resource_constraint().as_inner());
+        let actions: Vec<&ast::EntityUID> = match template.action_constraint() {
+            ActionConstraint::Any => {
+                self.any_action.principals.extend(principals);
+                self.any_action.resources.extend(resources);
+                return;
+            }
+            ActionConstraint::Eq(action) => vec![action.as_ref()],
+            ActionConstraint::In(actions) => actions.iter().map(AsRef::as_ref).collect(),
+            #[cfg(feature = "tolerant-ast")]
+            ActionConstraint::ErrorConstraint => vec![],
+        };
+        for action in actions {
+            let observed = self.actions.entry(action.clone()).or_default();
+            observed.principals.extend(principals.iter().cloned());
+            observed.resources.extend(resources.iter().cloned());
+        }
+    }
+
+    /// Entity types of the principal or resource of requests matching a
+    /// policy scope constraint
+    fn scope_types(
+        &mut self,
+        constraint: &PrincipalOrResourceConstraint,
+    ) -> BTreeSet<ast::EntityType> {
+        let types: BTreeSet<ast::EntityType> = match constraint {
+            PrincipalOrResourceConstraint::Any
+            | PrincipalOrResourceConstraint::Eq(EntityReference::Slot(_))
+            | PrincipalOrResourceConstraint::In(EntityReference::Slot(_)) => BTreeSet::new(),
+            PrincipalOrResourceConstraint::Eq(EntityReference::EUID(euid)) => {
+                BTreeSet::from([euid.entity_type().clone()])
+            }
+            PrincipalOrResourceConstraint::In(EntityReference::EUID(euid)) => {
+                let mut types = self.descendant_types(euid.entity_type());
+                types.insert(euid.entity_type().clone());
+                types
+            }
+            PrincipalOrResourceConstraint::Is(ety)
+            | PrincipalOrResourceConstraint::IsIn(ety, _) => BTreeSet::from([ety.as_ref().clone()]),
+        };
+        let types = types
+            .into_iter()
+            .filter(|ety| !ety.is_action())
+            .collect::<BTreeSet<_>>();
+        for ety in &types {
+            self.declare(ety);
+        }
+        types
+    }
+
+    /// Entity types observed to be direct or indirect members of `ancestor`
+    fn descendant_types(&self, ancestor: &ast::EntityType) -> BTreeSet<ast::EntityType> {
+        let mut descendants = BTreeSet::new();
+        let mut frontier = vec![ancestor];
+        while let Some(ety) = frontier.pop() {
+            for (child, observed) in &self.entity_types {
+                if observed.parents.contains(ety) && descendants.insert(child.clone()) {
+                    frontier.push(child);
+                }
+            }
+        }
+        descendants
+    }
+
+    fn finish(
+        mut self,
+    ) -> Result<(SchemaFragment, Vec<SchemaInferenceConflict>), SchemaBuilderError> {
+        let any_action = std::mem::take(&mut self.any_action);
+        let mut builder = SchemaBuilder::new();
+        for (ety, observed) in &self.entity_types {
+            builder = builder.namespace(&ety.name().as_ref().namespace(), |ns| {
+                let decl = ns.entity_type(ety.name().basename().as_ref());
+                if let Some(ObservedType::Record(attrs)) = &observed.shape {
+                    for (name, attr) in attrs {
+                        if attr.required {
+                            decl.attribute(name, attr.ty.to_schema_type());
+                        } else {
+                            decl.optional_attribute(name, attr.ty.to_schema_type());
+                        }
+                    }
+                }
+                if let Some(tags) = &observed.tags {
+                    decl.tags(tags.to_schema_type());
+                }
+                for parent in &observed.parents {
+                    decl.member_of(&parent.to_string());
+                }
+            });
+        }
+        for (action, observed) in &self.actions {
+            let namespace = action.entity_type().name().as_ref().namespace();
+            builder = builder.namespace(&namespace, |ns| {
+                let decl = ns.action(action.eid().as_ref());
+                for ety in observed.principals.union(&any_action.principals) {
+                    decl.applies_to_principal(&ety.to_string());
+                }
+                for ety in observed.resources.union(&any_action.resources) {
+                    decl.applies_to_resource(&ety.to_string());
+                }
+                for group in &observed.groups {
+                    decl.member_of_action(&group.entity_type().to_string(), group.eid().as_ref());
+                }
+            });
+        }
+        let conflicts = self
+            .conflicts
+            .into_iter()
+            .map(
+                |((entity_type, tags, attribute_path), observed_types)| SchemaInferenceConflict {
+                    entity_type: EntityTypeName(entity_type),
+                    attribute_path,
+                    tags,
+                    observed_types,
+                },
+            )
+            .collect();
+        Ok((builder.build_fragment()?, conflicts))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::{Entities, PolicySet, Schema};
+
+    #[test]
+    #[cfg(feature = "ipaddr")]
+    fn infer_schema() {
+        let entities = Entities::from_json_value(
+            json!([
+                {
+                    "uid": { "type": "User", "id": "alice" },
+                    "attrs": {
+                        "age": 30,
+                        "address": { "city": "Seattle", "zip": "98101" },
+                        "roles": ["admin"],
+                        "ip": { "__extn": { "fn": "ip", "arg": "10.0.0.1" } },
+                    },
+                    "parents": [{ "type": "Group", "id": "admins" }],
+                },
+                {
+                    "uid": { "type": "User", "id": "bob" },
+                    "attrs": {
+                        "age": "forty",
+                        "address": { "city": "Paris" },
+                        "roles": [],
+                        "manager": { "__entity": { "type": "User", "id": "alice" } },
+                    },
+                    "parents": [],
+                },
+                {
+                    "uid": { "type": "App::Photo", "id": "beach" },
+                    "attrs": {},
+                    "parents": [],
+                    "tags": { "location": "beach" },
+                },
+            ]),
+            None,
+        )
+        .unwrap();
+        let policies: PolicySet = r#"
+            permit(principal in Group::"admins", action == App::Action::"view", resource is App::Photo);
+            permit(principal == User::"alice", action in [App::Action::"edit"], resource);
+        "#
+        .parse()
+        .unwrap();
+
+        let (fragment, conflicts) = Schema::infer(&entities, &policies).unwrap();
+        assert_eq!(
+            fragment.to_json_value().unwrap(),
+            json!({
+                "": {
+                    "entityTypes": {
+                        "Group": {},
+                        "User": {
+                            "memberOfTypes": ["Group"],
+                            "shape": {
+                                "type": "Record",
+                                "attributes": {
+                                    "address": {
+                                        "type": "Record",
+                                        "attributes": {
+                                            "city": { "type": "String" },
+                                            "zip": { "type": "String", "required": false },
+                                        },
+                                    },
+                                    "age": { "type": "Long" },
+                                    "ip": { "type": "Extension", "name": "ipaddr", "required": false },
+                                    "manager": { "type": "Entity", "name": "User", "required": false },
+                                    "roles": { "type": "Set", "element": { "type": "String" } },
+                                },
+                            },
+                        },
+                    },
+                    "actions": {},
+                },
+                "App": {
+                    "entityTypes": {
+                        "Photo": { "tags": { "type": "String" } },
+                    },
+                    "actions": {
+                        "edit": {
+                            "appliesTo": { "principalTypes": ["User"], "resourceTypes": [] },
+                        },
+                        "view": {
+                            "appliesTo": {
+                                "principalTypes": ["Group", "User"],
+                                "resourceTypes": ["App::Photo"],
+                            },
+                        },
+                    },
+                },
+            })
+        );
+        assert_matches!(conflicts.as_slice(), [conflict] => {
+            assert_eq!(conflict.entity_type().to_string(), "User");
+            assert_eq!(conflict.attribute_path().collect::<Vec<_>>(), vec!["age"]);
+            assert_eq!(conflict.observed_types().collect::<Vec<_>>(), vec!["Long", "String"]);
+            assert_eq!(
+                conflict.to_string(),
+                "conflicting types observed for attribute `age` of entity type `User`: `Long`, `String`"
+            );
+        });
+    }
+}
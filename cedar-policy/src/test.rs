@@ -18,6 +18,7 @@
 #![expect(
     clippy::panic,
     clippy::indexing_slicing,
+    clippy::string_slice,
     clippy::cognitive_complexity,
     clippy::too_many_lines,
     reason = "unit tests"